@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-pub(crate) use command_registry::wait::update_wait;
+pub(crate) use command_registry::wait::{set_command_source, update_wait};
 pub use command_registry::YarnCommands;
 pub use command_wrapping::{TaskFinishedIndicator, UntypedYarnCommand, YarnCommand};
 
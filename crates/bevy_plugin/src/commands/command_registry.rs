@@ -1,6 +1,7 @@
 use crate::commands::command_wrapping::YarnCommandWrapper;
 use crate::commands::UntypedYarnCommand;
 use crate::prelude::*;
+use anyhow::bail;
 use bevy::prelude::*;
 use std::borrow::Cow;
 use std::collections::HashMap;
@@ -63,6 +64,39 @@ impl YarnCommands {
         self
     }
 
+    /// Replaces the implementation of a command that is already registered, e.g. when gameplay code is hot patched at runtime.
+    /// The new implementation is used from the next time the command is run, even by a [`DialogueRunner`] that is currently running.
+    ///
+    /// The new command must accept the same parameters from Yarn, i.e. have the same [`YarnCommand::In`] type.
+    /// The Bevy system parameters it uses may differ.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error and leaves the registry untouched if there is no command with the given name or if the parameters differ.
+    /// Use [`YarnCommands::add_command`] to register a command regardless of its previous parameters.
+    pub fn replace_command<Marker, F>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        command: F,
+    ) -> Result<&mut Self>
+    where
+        Marker: 'static,
+        F: YarnCommand<Marker> + 'static + Clone,
+    {
+        let name = name.into();
+        let Some(old_command) = self.get(&name) else {
+            bail!("Cannot replace command \"{name}\" because it is not registered");
+        };
+        let new_command: Box<dyn UntypedYarnCommand> = Box::new(YarnCommandWrapper::from(command));
+        let old_input = old_command.input_type_name();
+        let new_input = new_command.input_type_name();
+        if old_input != new_input {
+            bail!("Cannot replace command \"{name}\" because its parameters changed from {old_input} to {new_input}");
+        }
+        self.0.insert(name, new_command);
+        Ok(self)
+    }
+
     /// Iterates over all registered commands.
     pub fn iter(&self) -> impl Iterator<Item = (&str, &(dyn UntypedYarnCommand))> {
         self.0
@@ -215,6 +249,24 @@ mod tests {
         method2.call(to_method_params([1.0]), &mut app.world);
     }
 
+    #[test]
+    fn replaces_command_with_same_parameters() {
+        let mut methods = YarnCommands::default();
+        methods.add_command("test", |_: In<f32>| panic!("Old command was called"));
+        methods
+            .replace_command("test", |In(a): In<f32>, _time: Res<Time>| {
+                assert_eq!(1.0, a)
+            })
+            .unwrap();
+        assert!(methods.replace_command("test", |_: In<String>| {}).is_err());
+        assert!(methods.replace_command("missing", |_: In<f32>| {}).is_err());
+
+        let mut app = App::new();
+        app.init_resource::<Time>();
+        let method = methods.get_mut("test").unwrap();
+        method.call(to_method_params([1.0]), &mut app.world);
+    }
+
     #[test]
     fn can_mutate_world() {
         let mut methods = YarnCommands::default();
@@ -1,9 +1,8 @@
 //! In an ideal world, this would just be a new thread doing `sleep`.
 //! Alas, Wasm forces us to do this
 
-use crate::prelude::YarnSpinnerSystemSet;
+use crate::prelude::*;
 use bevy::prelude::*;
-use bevy::utils::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -14,36 +13,56 @@ pub(crate) fn wait_command_plugin(app: &mut App) {
 }
 
 #[derive(Debug, Clone, Resource, Default)]
-pub(crate) struct Wait(HashMap<Duration, WaitPeriod>);
+pub(crate) struct Wait {
+    periods: Vec<WaitPeriod>,
+    /// The dialogue runner executing the current command and the time on its [`DialogueClock`].
+    command_source: Option<(Entity, Duration)>,
+}
 
 #[derive(Debug, Clone)]
 pub(crate) struct WaitPeriod {
-    duration: Duration,
+    source: Entity,
+    end: Duration,
     done: Arc<AtomicBool>,
 }
 
 impl Wait {
     pub(crate) fn add(&mut self, duration: Duration) -> Arc<AtomicBool> {
+        let (source, now) = self
+            .command_source
+            .expect("The wait command was executed outside of a dialogue runner. This is a bug. Please report it at https://github.com/YarnSpinnerTool/YarnSpinner-Rust/issues/new");
         let done = Arc::new(AtomicBool::new(false));
-        self.0.insert(
-            duration,
-            WaitPeriod {
-                duration,
-                done: done.clone(),
-            },
-        );
+        self.periods.push(WaitPeriod {
+            source,
+            end: now + duration,
+            done: done.clone(),
+        });
         done
     }
 }
 
-pub(crate) fn update_wait(time: Res<Time>, mut wait: ResMut<Wait>) {
-    for period in wait.0.values_mut() {
-        if period.duration <= time.delta() {
-            period.duration = Duration::from_secs(0);
-            period.done.store(true, Ordering::Relaxed);
-        } else {
-            period.duration -= time.delta();
-        }
-    }
-    wait.0.remove(&Duration::from_secs(0));
+/// Remembers which dialogue runner executes the next command, so that a `<<wait>>` is measured with that runner's [`DialogueClock`].
+pub(crate) fn set_command_source(world: &mut World, source: Entity) {
+    let now = world
+        .get::<DialogueRunner>(source)
+        .map(|dialogue_runner| dialogue_runner.clock().elapsed(world))
+        .unwrap_or_default();
+    world.resource_mut::<Wait>().command_source = Some((source, now));
+}
+
+pub(crate) fn update_wait(world: &mut World) {
+    world.resource_scope(|world, mut wait: Mut<Wait>| {
+        wait.periods.retain(|period| {
+            // Waits of despawned dialogue runners are done, as nobody is waiting for them anymore
+            let is_done = world
+                .get::<DialogueRunner>(period.source)
+                .map_or(true, |dialogue_runner| {
+                    dialogue_runner.clock().elapsed(world) >= period.end
+                });
+            if is_done {
+                period.done.store(true, Ordering::Relaxed);
+            }
+            !is_done
+        });
+    });
 }
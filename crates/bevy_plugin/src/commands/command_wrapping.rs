@@ -160,6 +160,9 @@ pub trait UntypedYarnCommand: Debug + Send + Sync + 'static {
     fn call(&mut self, input: Vec<YarnValue>, world: &mut World) -> Box<dyn TaskFinishedIndicator>;
     #[doc(hidden)]
     fn clone_box(&self) -> Box<dyn UntypedYarnCommand>;
+    /// The name of the [`YarnCommand::In`] type, i.e. the parameters the command accepts from Yarn.
+    #[doc(hidden)]
+    fn input_type_name(&self) -> &'static str;
 }
 
 impl Clone for Box<dyn UntypedYarnCommand> {
@@ -191,6 +194,10 @@ where
     fn clone_box(&self) -> Box<dyn UntypedYarnCommand> {
         Box::new(self.clone())
     }
+
+    fn input_type_name(&self) -> &'static str {
+        std::any::type_name::<T::In>()
+    }
 }
 
 pub(crate) struct YarnCommandWrapper<Marker, F>
@@ -1,4 +1,4 @@
-use crate::commands::UntypedYarnCommand;
+use crate::commands::{set_command_source, UntypedYarnCommand};
 use crate::dialogue_runner::DialogueExecutionSystemSet;
 use crate::events::ExecuteCommandEvent;
 use crate::prelude::*;
@@ -20,6 +20,7 @@ fn execute_commands(world: &mut World, mut reader: Local<ManualEventReader<Execu
         let Some(mut command) = clone_command(world, &event) else {
             continue;
         };
+        set_command_source(world, event.source);
         let params = event.command.parameters;
         let task_finished_indicator = command.call(params, world);
         if !task_finished_indicator.is_finished() {
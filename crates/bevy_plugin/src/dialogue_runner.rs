@@ -1,12 +1,15 @@
 pub use self::events::{
-    DialogueCompleteEvent, DialogueStartEvent, ExecuteCommandEvent, LineHintsEvent,
-    NodeCompleteEvent, NodeStartEvent, PresentLineEvent, PresentOptionsEvent,
+    DialogueCompleteEvent, DialogueStartEvent, ExecuteCommandEvent, LineAssetsPrefetchedEvent,
+    LineHintsEvent, NodeCompleteEvent, NodeStartEvent, PresentLineEvent, PresentOptionsEvent,
+    PresentSystemMessageEvent, TriggerFiredEvent, VariableChangedEvent,
 };
 pub use self::{
     builder::DialogueRunnerBuilder,
+    clock::{DialogueClock, ManualClock, RealTimeClock, VirtualTimeClock},
     dialogue_option::DialogueOption,
     inner::{InnerDialogue, InnerDialogueMut},
     localized_line::LocalizedLine,
+    system_channel::SystemChannel,
 };
 use crate::commands::TaskFinishedIndicator;
 use crate::line_provider::LineAssets;
@@ -22,11 +25,13 @@ use std::fmt::Debug;
 use yarnspinner::core::Library;
 
 mod builder;
+mod clock;
 mod dialogue_option;
 mod events;
 mod inner;
 mod localized_line;
 mod runtime_interaction;
+mod system_channel;
 
 pub(crate) fn dialogue_plugin(app: &mut App) {
     app.add_plugins(runtime_interaction::runtime_interaction_plugin)
@@ -34,7 +39,8 @@ pub(crate) fn dialogue_plugin(app: &mut App) {
         .add_plugins(events::dialogue_runner_events_plugin)
         .add_plugins(dialogue_option::dialogue_option_plugin)
         .add_plugins(builder::dialogue_runner_builder_plugin)
-        .add_plugins(inner::inner_dialogue_runner_plugin);
+        .add_plugins(inner::inner_dialogue_runner_plugin)
+        .add_plugins(system_channel::system_channel_plugin);
 }
 
 /// The main type to interact with the dialogue system.
@@ -51,9 +57,12 @@ pub struct DialogueRunner {
     localizations: Option<Localizations>,
     pub(crate) is_running: bool,
     run_selected_options_as_lines: bool,
+    draft_line_placeholder: Option<String>,
     pub(crate) just_started: bool,
     pub(crate) popped_line_hints: Option<Vec<LineId>>,
+    pub(crate) prefetched_line_ids: Option<Vec<LineId>>,
     pub(crate) unsent_events: Vec<DialogueEvent>,
+    clock: Box<dyn DialogueClock>,
 }
 
 impl DialogueRunner {
@@ -125,6 +134,35 @@ impl DialogueRunner {
         self.run_selected_options_as_lines
     }
 
+    /// If set, the text of every line and option tagged with `#draft` is replaced by the given placeholder before it is presented,
+    /// and its markup attributes are removed. Meant for production builds, so that unfinished text never reaches players
+    /// while the rest of the dialogue keeps working. Defaults to [`None`], which presents draft lines as they are.
+    ///
+    /// To instead fail the build when draft lines remain, see [`YarnCompiler::forbid_draft_lines`](yarnspinner::prelude::YarnCompiler::forbid_draft_lines).
+    pub fn set_draft_line_placeholder(
+        &mut self,
+        placeholder: impl Into<Option<String>>,
+    ) -> &mut Self {
+        self.draft_line_placeholder = placeholder.into();
+        self
+    }
+
+    /// The placeholder that replaces the text of lines tagged with `#draft`, see [`DialogueRunner::set_draft_line_placeholder`].
+    #[must_use]
+    pub fn draft_line_placeholder(&self) -> Option<&str> {
+        self.draft_line_placeholder.as_deref()
+    }
+
+    pub(crate) fn hide_draft_line(&self, line: &mut LocalizedLine) {
+        let Some(placeholder) = self.draft_line_placeholder.as_ref() else {
+            return;
+        };
+        if line.metadata.iter().any(|tag| tag == "draft") {
+            line.text = placeholder.clone();
+            line.attributes.clear();
+        }
+    }
+
     /// Stops the execution of the dialogue. Any pending dialogue events will still be sent in the next update, including a [`DialogueCompleteEvent`].
     /// After this, [`DialogueRunner::start_node`] must be called before the dialogue can be advanced again.
     pub fn stop(&mut self) -> &mut Self {
@@ -164,6 +202,48 @@ impl DialogueRunner {
         Ok(self)
     }
 
+    /// Starts loading the assets of all lines in the node `node_name` with the registered [`AssetProvider`]s,
+    /// so that the node does not have to wait for them when it is started later. Call this when you know that a node is about to run,
+    /// e.g. when the player approaches an NPC. Does not influence the dialogue itself.
+    ///
+    /// A [`LineAssetsPrefetchedEvent`] is sent once all assets have been loaded. Prefetching again replaces the previous prefetch.
+    /// See [`DialogueRunner::prefetch_reachable_nodes`] for also prefetching the nodes that `node_name` jumps to.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the node does not exist.
+    pub fn prefetch_node(&mut self, node_name: impl AsRef<str>) -> &mut Self {
+        let node_name = node_name.as_ref();
+        let line_ids = self
+            .dialogue
+            .get_line_ids_for_node(node_name)
+            .unwrap_or_else(|| panic!("Can't prefetch node {node_name}: no such node exists."));
+        self.prefetch_line_assets(line_ids)
+    }
+
+    /// Same as [`DialogueRunner::prefetch_node`], but also prefetches all nodes reachable from `node_name` through `<<jump>>`s,
+    /// e.g. all nodes of a scene. See [`Dialogue::get_line_ids_for_reachable_nodes`] for details.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the node does not exist.
+    pub fn prefetch_reachable_nodes(&mut self, node_name: impl AsRef<str>) -> &mut Self {
+        let node_name = node_name.as_ref();
+        let line_ids = self
+            .dialogue
+            .get_line_ids_for_reachable_nodes(node_name)
+            .unwrap_or_else(|| panic!("Can't prefetch node {node_name}: no such node exists."));
+        self.prefetch_line_assets(line_ids)
+    }
+
+    fn prefetch_line_assets(&mut self, line_ids: Vec<LineId>) -> &mut Self {
+        for asset_provider in self.asset_providers.values_mut() {
+            asset_provider.prefetch_line_assets(&line_ids);
+        }
+        self.prefetched_line_ids = Some(line_ids);
+        self
+    }
+
     /// Returns the tags for the node `node_name`.
     ///
     /// The tags for a node are defined by setting the `tags` header in
@@ -181,6 +261,24 @@ impl DialogueRunner {
         self.dialogue.node_exists(node_name)
     }
 
+    /// Adds the nodes of a program to the ones this runner can run, replacing existing nodes with the same name.
+    /// Usually, the program is a chunk of the [`ProgramChunks`] created by [`Compilation::split_program`], see [`DialogueRunnerBuilder::with_nodes_loaded_on_demand`].
+    /// The state of the runner is kept. See [`Dialogue::replace_nodes`] for details.
+    pub fn load_program_chunk(&mut self, chunk: YarnProgram) -> &mut Self {
+        self.dialogue.replace_nodes(chunk);
+        self
+    }
+
+    /// Removes the nodes with the given names from the ones this runner can run, e.g. after the player left the area they belong to.
+    /// The node that is currently running is kept. See [`Dialogue::remove_nodes`] for details.
+    pub fn unload_nodes(
+        &mut self,
+        node_names: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> &mut Self {
+        self.dialogue.remove_nodes(node_names);
+        self
+    }
+
     /// Gets the name of the node that this Dialogue is currently executing.
     /// This is [`None`] if [`DialogueRunner::is_running`] is `false`.
     #[must_use]
@@ -188,6 +286,18 @@ impl DialogueRunner {
         self.dialogue.current_node()
     }
 
+    /// Returns how many times the node with the given name was visited. See [`Dialogue::visit_count`].
+    #[must_use]
+    pub fn visit_count(&self, node_name: &str) -> usize {
+        self.dialogue.visit_count(node_name)
+    }
+
+    /// Returns `true` if the node with the given name was visited at least once. See [`Dialogue::has_visited`].
+    #[must_use]
+    pub fn has_visited(&self, node_name: &str) -> bool {
+        self.dialogue.has_visited(node_name)
+    }
+
     /// Returns a shallow clone of the registered [`VariableStorage`]. The storage used can be overridden by calling [`DialogueRunnerBuilder::with_variable_storage`].
     #[must_use]
     pub fn variable_storage(&self) -> &dyn VariableStorage {
@@ -287,6 +397,19 @@ impl DialogueRunner {
         &mut self.commands
     }
 
+    /// Returns the [`DialogueClock`] used for time-dependent behavior such as the `<<wait>>` command. By default, this is a [`VirtualTimeClock`].
+    #[must_use]
+    pub fn clock(&self) -> &dyn DialogueClock {
+        self.clock.as_ref()
+    }
+
+    /// Replaces the [`DialogueClock`]. Running `<<wait>>` commands are measured with the new clock from the next update on,
+    /// so switching between clocks that count from different points in time makes them finish early or late.
+    pub fn set_clock(&mut self, clock: impl DialogueClock) -> &mut Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
     /// Returns the language used by the [`TextProvider`]. If there are no [`Localizations`] available, this will return [`None`].
     #[must_use]
     pub fn text_language(&self) -> Option<Language> {
@@ -341,6 +464,17 @@ impl DialogueRunner {
         self.asset_providers.values().map(|p| p.as_ref())
     }
 
+    /// Returns whether all asset providers have loaded the assets of the lines passed to [`DialogueRunner::prefetch_line_assets`].
+    #[must_use]
+    pub(crate) fn update_prefetched_asset_availability(
+        &mut self,
+        loaded_untyped_assets: &Assets<LoadedUntypedAsset>,
+    ) -> bool {
+        self.asset_providers
+            .values_mut()
+            .all(|provider| provider.update_prefetched_asset_availability(loaded_untyped_assets))
+    }
+
     #[must_use]
     pub(crate) fn get_assets(&self, line: &UnderlyingYarnLine) -> LineAssets {
         self.asset_providers
@@ -3,10 +3,11 @@ use crate::line_provider::SharedTextProvider;
 use crate::prelude::*;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
-use rand::{rngs::SmallRng, Rng, SeedableRng};
 use std::any::{Any, TypeId};
 use std::fmt;
 use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use yarnspinner::compiler::DebugInfo;
 
 pub(crate) fn dialogue_runner_builder_plugin(_app: &mut App) {}
 
@@ -17,9 +18,16 @@ pub struct DialogueRunnerBuilder {
     asset_providers: HashMap<TypeId, Box<dyn AssetProvider>>,
     library: YarnLibrary,
     commands: YarnCommands,
-    compilation: Compilation,
+    program: Arc<YarnProgram>,
+    base_language: Option<String>,
+    debug_info: Vec<DebugInfo>,
+    line_metadata: HashMap<LineId, Vec<String>>,
+    variable_declarations: Vec<VariableDeclaration>,
     localizations: Option<Localizations>,
     asset_server: AssetServer,
+    clock: Box<dyn DialogueClock>,
+    load_nodes_on_demand: bool,
+    line_hint_lookahead: usize,
 }
 
 impl Debug for DialogueRunnerBuilder {
@@ -30,9 +38,16 @@ impl Debug for DialogueRunnerBuilder {
             .field("asset_providers", &self.asset_providers)
             .field("library", &self.library)
             .field("commands", &self.commands)
-            .field("compilation", &self.compilation)
+            .field("program", &self.program)
+            .field("base_language", &self.base_language)
+            .field("debug_info", &self.debug_info)
+            .field("line_metadata", &self.line_metadata)
+            .field("variable_declarations", &self.variable_declarations)
             .field("localizations", &self.localizations)
             .field("asset_server", &())
+            .field("clock", &self.clock)
+            .field("load_nodes_on_demand", &self.load_nodes_on_demand)
+            .field("line_hint_lookahead", &self.line_hint_lookahead)
             .finish()
     }
 }
@@ -46,11 +61,28 @@ impl DialogueRunnerBuilder {
                 yarn_project,
             )),
             asset_providers: HashMap::new(),
-            library: create_extended_standard_library(),
+            library: YarnLibrary::standard_library(),
             commands: YarnCommands::builtin_commands(),
-            compilation: yarn_project.compilation().clone(),
+            program: yarn_project.program().clone(),
+            base_language: yarn_project.compilation().base_language.clone(),
+            debug_info: yarn_project
+                .compilation()
+                .debug_info
+                .values()
+                .cloned()
+                .collect(),
+            line_metadata: yarn_project.metadata.clone(),
+            variable_declarations: yarn_project
+                .compilation()
+                .declarations
+                .iter()
+                .map(Into::into)
+                .collect(),
             localizations: yarn_project.localizations().cloned(),
             asset_server: yarn_project.asset_server.clone(),
+            clock: Box::new(VirtualTimeClock),
+            load_nodes_on_demand: false,
+            line_hint_lookahead: 0,
         }
     }
 
@@ -76,6 +108,30 @@ impl DialogueRunnerBuilder {
         self
     }
 
+    /// Replaces the [`DialogueClock`] used for time-dependent behavior such as the `<<wait>>` command. By default, this is a [`VirtualTimeClock`].
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl DialogueClock) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Makes the [`AssetProvider`]s start loading the assets of the nodes that can be reached from a starting node with at most `depth` `<<jump>>`s,
+    /// instead of only those of the starting node itself. See [`Dialogue::set_line_hint_lookahead`]. By default, this is `0`.
+    #[must_use]
+    pub fn with_line_hint_lookahead(mut self, depth: usize) -> Self {
+        self.line_hint_lookahead = depth;
+        self
+    }
+
+    /// Starts the [`DialogueRunner`] without any nodes, only knowing the initial values of the project's variables.
+    /// Load the nodes needed for the content at hand with [`DialogueRunner::load_program_chunk`] and unload them with [`DialogueRunner::unload_nodes`],
+    /// e.g. the [`ProgramChunks`] of [`Compilation::split_program`] for the area around the player.
+    #[must_use]
+    pub fn with_nodes_loaded_on_demand(mut self) -> Self {
+        self.load_nodes_on_demand = true;
+        self
+    }
+
     /// Builds the [`DialogueRunner`]. See [`DialogueRunnerBuilder::try_build`] for the fallible version.
     pub fn build(self) -> DialogueRunner {
         self.try_build().unwrap_or_else(|error| {
@@ -90,9 +146,35 @@ impl DialogueRunnerBuilder {
         let mut dialogue = Dialogue::new(self.variable_storage, text_provider.clone());
         dialogue
             .set_line_hints_enabled(true)
+            .set_line_hint_lookahead(self.line_hint_lookahead)
+            .set_variable_change_events_enabled(true)
             .library_mut()
             .extend(self.library);
-        dialogue.add_program(self.compilation.program.unwrap());
+        // Lines are written in the base language, so its plural rules apply to them, even when no localizations are used
+        let compiled_base_language = self
+            .base_language
+            .as_deref()
+            .and_then(|language| language.parse::<Language>().ok());
+        dialogue.set_base_language(compiled_base_language);
+        // Lets errors caused by invalid instructions point to the Yarn source
+        for debug_info in self.debug_info {
+            dialogue.add_source_positions(
+                debug_info.node_name,
+                debug_info.file_name,
+                debug_info.line_positions,
+            );
+        }
+        dialogue
+            .add_line_metadata(self.line_metadata)
+            .add_variable_declarations(self.variable_declarations);
+        if self.load_nodes_on_demand {
+            dialogue.add_program(YarnProgram {
+                initial_values: self.program.initial_values.clone(),
+                ..default()
+            });
+        } else {
+            dialogue.add_program(self.program);
+        }
 
         for asset_provider in self.asset_providers.values_mut() {
             if let Some(ref localizations) = self.localizations {
@@ -114,7 +196,9 @@ impl DialogueRunnerBuilder {
             dialogue,
             text_provider,
             popped_line_hints,
+            prefetched_line_ids: default(),
             run_selected_options_as_lines: false,
+            draft_line_placeholder: None,
             asset_providers: self.asset_providers,
             commands: self.commands,
             is_running: default(),
@@ -124,6 +208,7 @@ impl DialogueRunnerBuilder {
             just_started: default(),
             unsent_events: default(),
             localizations: self.localizations,
+            clock: self.clock,
         };
 
         if let Some(base_language) = base_language {
@@ -133,83 +218,3 @@ impl DialogueRunnerBuilder {
         Ok(dialogue_runner)
     }
 }
-
-fn create_extended_standard_library() -> YarnLibrary {
-    let mut library = YarnLibrary::standard_library();
-    library
-        .add_function("random", || SmallRng::from_entropy().gen_range(0.0..1.0))
-        .add_function("random_range", |min: f32, max: f32| {
-            if let Some(min) = min.as_int() {
-                if let Some(max_inclusive) = max.as_int() {
-                    return SmallRng::from_entropy().gen_range(min..=max_inclusive) as f32;
-                }
-            }
-            SmallRng::from_entropy().gen_range(min..max)
-        })
-        .add_function("dice", |sides: u32| {
-            if sides == 0 {
-                return 1;
-            }
-            SmallRng::from_entropy().gen_range(1..=sides)
-        })
-        .add_function("round", |num: f32| num.round() as i32)
-        .add_function("round_places", |num: f32, places: u32| {
-            num.round_places(places)
-        })
-        .add_function("floor", |num: f32| num.floor() as i32)
-        .add_function("ceil", |num: f32| num.ceil() as i32)
-        .add_function("inc", |num: f32| {
-            if let Some(num) = num.as_int() {
-                num + 1
-            } else {
-                num.ceil() as i32
-            }
-        })
-        .add_function("dec", |num: f32| {
-            if let Some(num) = num.as_int() {
-                num - 1
-            } else {
-                num.floor() as i32
-            }
-        })
-        .add_function("decimal", |num: f32| num.fract())
-        .add_function("int", |num: f32| num.trunc() as i32);
-    library
-}
-
-trait FloatExt: Copy {
-    fn as_int(self) -> Option<i32>;
-    fn round_places(self, places: u32) -> Self;
-}
-
-impl FloatExt for f32 {
-    fn as_int(self) -> Option<i32> {
-        (self.fract() <= f32::EPSILON).then_some(self as i32)
-    }
-
-    fn round_places(self, places: u32) -> Self {
-        let factor = 10_u32.pow(places) as f32;
-        (self * factor).round() / factor
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn rounds_places() {
-        for (num, places, expected) in [
-            (1.0, 0, 1.0),
-            (1.2, 1, 1.2),
-            (0.4, 0, 0.0),
-            (43.132, 0, 43.0),
-            (1.1, 2, 1.1),
-            (123.123, 3, 123.123),
-            (-10.3, 1, -10.3),
-            (-11.99, 1, -12.0),
-        ] {
-            assert_eq!(expected, num.round_places(places));
-        }
-    }
-}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// The source of time for the time-dependent behavior of a [`DialogueRunner`](crate::prelude::DialogueRunner), such as the builtin `<<wait>>` command.
+/// Set it with [`DialogueRunnerBuilder::with_clock`](crate::prelude::DialogueRunnerBuilder::with_clock) or [`DialogueRunner::set_clock`](crate::prelude::DialogueRunner::set_clock).
+///
+/// The following clocks are provided:
+/// - [`VirtualTimeClock`]: The default. Follows Bevy's [`Time<Virtual>`], so it stops while the game is paused and speeds up or slows down with it.
+/// - [`RealTimeClock`]: Follows the wall time as reported by Bevy's [`Time<Real>`], so it keeps running while the game is paused.
+/// - [`ManualClock`]: Only advances when told to, e.g. in automated tests.
+///
+/// Implement this trait to use any other notion of time, e.g. the in-game time of day.
+pub trait DialogueClock: Debug + Send + Sync + 'static {
+    /// Returns the time that passed on this clock since an arbitrary but fixed point, e.g. the start of the app.
+    /// Must never decrease. Only differences between two calls are used.
+    fn elapsed(&self, world: &World) -> Duration;
+}
+
+/// A [`DialogueClock`] following Bevy's [`Time<Virtual>`]. It stops while the game is paused via [`Time::<Virtual>::pause`].
+/// This is the default clock of a [`DialogueRunner`](crate::prelude::DialogueRunner).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VirtualTimeClock;
+
+impl DialogueClock for VirtualTimeClock {
+    fn elapsed(&self, world: &World) -> Duration {
+        world
+            .get_resource::<Time<Virtual>>()
+            .map(Time::elapsed)
+            .unwrap_or_default()
+    }
+}
+
+/// A [`DialogueClock`] following the wall time as reported by Bevy's [`Time<Real>`]. It keeps running while the game is paused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RealTimeClock;
+
+impl DialogueClock for RealTimeClock {
+    fn elapsed(&self, world: &World) -> Duration {
+        world
+            .get_resource::<Time<Real>>()
+            .map(Time::elapsed)
+            .unwrap_or_default()
+    }
+}
+
+/// A [`DialogueClock`] that only advances when [`ManualClock::advance`] is called, e.g. to control time in automated tests.
+/// Clones share the same time, so keep a clone around to advance the clock after passing it to the [`DialogueRunner`](crate::prelude::DialogueRunner).
+///
+/// ## Example
+///
+/// ```rust
+/// # use bevy_yarnspinner::prelude::*;
+/// # use bevy_yarnspinner::clock::ManualClock;
+/// # use std::time::Duration;
+/// # fn build(yarn_project: &YarnProject) {
+/// let clock = ManualClock::default();
+/// let dialogue_runner = yarn_project
+///     .build_dialogue_runner()
+///     .with_clock(clock.clone())
+///     .build();
+/// // Finishes a `<<wait 1>>` command on the next update
+/// clock.advance(Duration::from_secs(1));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock(Arc<Mutex<Duration>>);
+
+impl ManualClock {
+    /// Advances the clock by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) += duration;
+    }
+}
+
+impl DialogueClock for ManualClock {
+    fn elapsed(&self, _world: &World) -> Duration {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
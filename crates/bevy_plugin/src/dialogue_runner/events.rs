@@ -5,10 +5,14 @@ use bevy::prelude::*;
 pub(crate) fn dialogue_runner_events_plugin(app: &mut App) {
     app.add_event::<PresentLineEvent>()
         .add_event::<PresentOptionsEvent>()
+        .add_event::<PresentSystemMessageEvent>()
         .add_event::<ExecuteCommandEvent>()
         .add_event::<NodeCompleteEvent>()
         .add_event::<NodeStartEvent>()
         .add_event::<LineHintsEvent>()
+        .add_event::<LineAssetsPrefetchedEvent>()
+        .add_event::<TriggerFiredEvent>()
+        .add_event::<VariableChangedEvent>()
         .add_event::<DialogueCompleteEvent>()
         .add_event::<DialogueStartEvent>();
 }
@@ -32,10 +36,26 @@ pub struct PresentLineEvent {
 pub struct PresentOptionsEvent {
     /// The options to present to the user.
     pub options: Vec<DialogueOption>,
+    /// How long the player has to choose, if the script limited the time with `<<options_timeout>>`.
+    /// Running the timer and selecting the default option when it runs out is up to the dialogue view.
+    pub timeout: Option<OptionsTimeout>,
     /// The [`DialogueRunner`] that is presenting these options.
     pub source: Entity,
 }
 
+/// An event that is fired instead of a [`PresentLineEvent`] when a [`DialogueRunner`] with a [`SystemChannel`] wishes to present a line,
+/// e.g. a tutorial or a hint. The line's character name is already removed.
+/// Handling this event is **mandatory** when using a [`SystemChannel`].
+#[derive(Debug, Clone, PartialEq, Event)]
+pub struct PresentSystemMessageEvent {
+    /// The line to present to the user.
+    pub line: LocalizedLine,
+    /// The priority the node containing this line was queued with, see [`SystemChannel::enqueue`].
+    pub priority: i32,
+    /// The [`DialogueRunner`] that is presenting this line.
+    pub source: Entity,
+}
+
 /// An event that is fired after a dialogue advances and wishes to execute a command.
 /// Events are generally handled by looking them up in the [`YarnCommands`] of a [`DialogueRunner`],
 /// accessed via [`DialogueRunner::commands`] and [`DialogueRunner::commands_mut`].
@@ -70,6 +90,33 @@ pub struct NodeStartEvent {
     pub source: Entity,
 }
 
+/// An event that is fired when a trigger registered with [`Dialogue::add_trigger`](yarnspinner::runtime::Dialogue::add_trigger) fires,
+/// i.e. the line or node it waits for was delivered for the first time. Use it to e.g. unlock achievements.
+/// Register triggers through [`DialogueRunner::inner_mut`].
+/// Handling this event is **optional** for dialogue views.
+#[derive(Debug, Clone, PartialEq, Event)]
+pub struct TriggerFiredEvent {
+    /// The name the trigger was registered under.
+    pub trigger_name: String,
+    /// The [`DialogueRunner`] whose dialogue fired the trigger.
+    pub source: Entity,
+}
+
+/// An event that is fired when a `<<set>>` statement stores a value in a variable, so that e.g. a stats display can update itself.
+/// Changes made directly to the [`VariableStorage`] are not reported.
+/// Handling this event is **optional** for dialogue views.
+#[derive(Debug, Clone, PartialEq, Event)]
+pub struct VariableChangedEvent {
+    /// The name of the variable, including the leading `$`.
+    pub name: String,
+    /// The value before the `<<set>>`, if the variable had one.
+    pub old: Option<YarnValue>,
+    /// The value that was stored.
+    pub new: YarnValue,
+    /// The [`DialogueRunner`] whose dialogue changed the variable.
+    pub source: Entity,
+}
+
 /// An event that is fired when a new node has been started. Contains the IDs of all lines in the node as a general hint
 /// for asset providing systems to pre-load the lines. The lines are not guaranteed to be presented in the order of the IDs or at all.
 /// Handling this event is **optional** for dialogue views.
@@ -81,6 +128,17 @@ pub struct LineHintsEvent {
     pub source: Entity,
 }
 
+/// An event that is fired when the assets of the lines requested by [`DialogueRunner::prefetch_node`] or [`DialogueRunner::prefetch_reachable_nodes`]
+/// have been loaded by all [`AssetProvider`]s, so that starting the prefetched nodes will not have to wait for them.
+/// Handling this event is **optional** for dialogue views.
+#[derive(Debug, Clone, PartialEq, Event)]
+pub struct LineAssetsPrefetchedEvent {
+    /// The IDs of all prefetched lines.
+    pub line_ids: Vec<LineId>,
+    /// The [`DialogueRunner`] that prefetched the lines.
+    pub source: Entity,
+}
+
 /// An event that is fired when a dialogue has been started via [`DialogueRunner::start_node`]/
 /// Handling this event is **optional** for dialogue views.
 #[derive(Debug, Clone, PartialEq, Event)]
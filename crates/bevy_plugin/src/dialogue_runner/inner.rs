@@ -45,6 +45,12 @@ impl<'a> InnerDialogueMut<'a> {
         self.0.get_line_id_for_node(node_name)
     }
 
+    /// Proxy for [`Dialogue::set_button_glyphs`]. Call it whenever the player switches input devices.
+    pub fn set_button_glyphs(&mut self, glyphs: impl Into<Option<ButtonGlyphs>>) -> &mut Self {
+        self.0.set_button_glyphs(glyphs);
+        self
+    }
+
     /// Proxy for [`Dialogue::set_node`]
     pub fn set_node(&mut self, name: impl Into<String>) -> Result<&mut Self> {
         self.0.set_node(name)?;
@@ -1,7 +1,9 @@
 use crate::line_provider::LineAssets;
 use crate::prelude::*;
 use bevy::prelude::*;
-use yarnspinner::runtime::{CHARACTER_ATTRIBUTE, CHARACTER_ATTRIBUTE_NAME_PROPERTY};
+use yarnspinner::runtime::{
+    MarkupParseError, CHARACTER_ATTRIBUTE, CHARACTER_ATTRIBUTE_NAME_PROPERTY,
+};
 
 pub(crate) fn localized_line_plugin(_app: &mut App) {}
 
@@ -13,6 +15,11 @@ pub struct LocalizedLine {
     pub id: LineId,
     /// The original text, with all parsed markers removed.
     pub text: String,
+    /// The text before markup was parsed, with substitutions applied and markers and escaped brackets kept exactly as written.
+    pub raw_text: String,
+    /// Why the markup of this line could not be parsed, if the dialogue is set to deliver lines with broken markup as plain text.
+    /// See [`Dialogue::set_lenient_markup`](yarnspinner::runtime::Dialogue::set_lenient_markup).
+    pub markup_error: Option<MarkupParseError>,
     /// The [`MarkupAttribute`]s in this line. An example of markup is `Hello, [b]world[/b]!`.
     pub attributes: Vec<MarkupAttribute>,
     /// The list of metadata associated with this line, excluding the line ID.
@@ -21,6 +28,11 @@ pub struct LocalizedLine {
     pub metadata: Vec<String>,
     /// The assets associated with this line, provided by [`AssetProvider`]s that were added with [`DialogueRunnerBuilder::add_asset_provider`].
     pub assets: LineAssets,
+    /// The name of the node the line was delivered from.
+    pub node_name: String,
+    /// The place in the Yarn source code the line was compiled from.
+    /// Only available for nodes whose positions were added with [`Dialogue::add_source_positions`](yarnspinner::runtime::Dialogue::add_source_positions).
+    pub source_location: Option<SourceLocation>,
 }
 impl LocalizedLine {
     // Documentation taken from `YarnLine`
@@ -39,6 +51,8 @@ impl LocalizedLine {
     /// # let line = LocalizedLine {
     /// #    id: "line".into(),
     /// #    text: "Alice: Hello! How are you today?".to_owned(),
+    /// #    raw_text: "Alice: Hello! How are you today?".to_owned(),
+    /// #    markup_error: None,
     /// #    attributes: vec![MarkupAttribute {
     /// #        name: "character".to_owned(),
     /// #        position: 0,
@@ -48,6 +62,8 @@ impl LocalizedLine {
     /// #    }],
     /// #    metadata: vec![],
     /// #    assets: Default::default(),
+    /// #    node_name: "Start".to_owned(),
+    /// #    source_location: None,
     /// # };
     /// assert_eq!("Alice: Hello! How are you today?", line.text);
     /// assert_eq!(Some("Alice"), line.character_name());
@@ -60,9 +76,13 @@ impl LocalizedLine {
     /// # let line = LocalizedLine {
     /// #    id: "line".into(),
     /// #    text: "Great, thanks".to_owned(),
+    /// #    raw_text: "Great, thanks".to_owned(),
+    /// #    markup_error: None,
     /// #    attributes: vec![],
     /// #    metadata: vec![],
     /// #    assets: Default::default(),
+    /// #    node_name: "Start".to_owned(),
+    /// #    source_location: None,
     /// # };
     /// assert_eq!("Great, thanks", line.text);
     /// assert!(line.character_name().is_none());
@@ -92,6 +112,8 @@ impl LocalizedLine {
     /// # let line = LocalizedLine {
     /// #    id: "line".into(),
     /// #    text: "Alice: Hello! How are you today?".to_owned(),
+    /// #    raw_text: "Alice: Hello! How are you today?".to_owned(),
+    /// #    markup_error: None,
     /// #    attributes: vec![MarkupAttribute {
     /// #        name: "character".to_owned(),
     /// #        position: 0,
@@ -101,6 +123,8 @@ impl LocalizedLine {
     /// #    }],
     /// #    metadata: vec![],
     /// #    assets: Default::default(),
+    /// #    node_name: "Start".to_owned(),
+    /// #    source_location: None,
     /// # };
     /// assert_eq!("Alice: Hello! How are you today?", line.text);
     /// assert_eq!("Hello! How are you today?", &line.text_without_character_name());
@@ -113,9 +137,13 @@ impl LocalizedLine {
     /// # let line = LocalizedLine {
     /// #    id: "line".into(),
     /// #    text: "Great, thanks".to_owned(),
+    /// #    raw_text: "Great, thanks".to_owned(),
+    /// #    markup_error: None,
     /// #    attributes: vec![],
     /// #    metadata: vec![],
     /// #    assets: Default::default(),
+    /// #    node_name: "Start".to_owned(),
+    /// #    source_location: None,
     /// # };
     /// assert_eq!("Great, thanks", line.text);
     /// assert_eq!("Great, thanks", &line.text_without_character_name());
@@ -127,16 +155,27 @@ impl LocalizedLine {
         }
     }
 
+    /// Returns a copy of this line with the `character` attribute and the text it covers removed.
+    /// Useful for views that display [`LocalizedLine::character_name`] separately from the rest of the line.
+    pub fn without_character_name(&self) -> Self {
+        if let Some(attribute) = self.attribute(CHARACTER_ATTRIBUTE) {
+            self.delete_range(attribute)
+        } else {
+            self.clone()
+        }
+    }
+
     // Documentation taken from `YarnLine`
     /// Returns the substring of [`YarnLine::text`] covered by the passed `attribute`s [`MarkupAttribute::position`] and [`MarkupAttribute::length`] fields.
     pub fn text_for_attribute(&self, attribute: &MarkupAttribute) -> &str {
-        assert!(
-            self.text.len() <= attribute.position + attribute.length,
-            "Attribute \"{attribute}\" represents a range not representable by this text: \"{}\". \
-        Does this MarkupAttribute belong to this MarkupParseResult?",
-            self.text
-        );
-        &self.text[attribute.position..attribute.position + attribute.length]
+        let Some(range) = attribute.byte_range_in(&self.text) else {
+            panic!(
+                "Attribute \"{attribute}\" represents a range not representable by this text: \"{}\". \
+                Does this MarkupAttribute belong to this MarkupParseResult?",
+                self.text
+            );
+        };
+        &self.text[range]
     }
 
     // Documentation taken from `YarnLine`
@@ -167,6 +206,40 @@ impl LocalizedLine {
         Self::from_yarn_line(deleted_range, self.assets.clone(), self.metadata.clone())
     }
 
+    // Documentation taken from `YarnLine`
+    /// Replaces the text covered by an attribute with `replacement`, e.g. to substitute the position of an `[item/]` marker with the name of an item.
+    /// The replaced attribute is kept and covers `replacement` afterwards, while the other attributes are moved, grown or shrunk to still cover the same text.
+    ///
+    /// This method does not modify the current object. A new [`LocalizedLine`] is returned.
+    ///
+    /// See [`YarnLine::replace_range`] for details.
+    ///
+    /// ## Panics
+    /// Panics if `attribute_to_replace` is not an attribute of this [`YarnLine::attribute`].
+    pub fn replace_range(&self, attribute_to_replace: &MarkupAttribute, replacement: &str) -> Self {
+        let yarn_line: YarnLine = self.clone().into();
+        let replaced_range = yarn_line.replace_range(attribute_to_replace, replacement);
+        Self::from_yarn_line(replaced_range, self.assets.clone(), self.metadata.clone())
+    }
+
+    // Documentation taken from `YarnLine`
+    /// Splits this line into the longest beginning that still fits into a text box and the rest of the line, if any.
+    ///
+    /// The host measures the text: `fits` is called with candidate texts and returns whether they can be displayed in one go,
+    /// e.g. by laying them out with the font and box size used by the view.
+    /// The line is preferably split between words, falling back to splitting between graphemes.
+    /// Attributes are split alongside the text, so both returned lines are valid parse results on their own.
+    /// Both lines keep the [`LocalizedLine::metadata`] and [`LocalizedLine::assets`] of this line.
+    ///
+    /// See [`YarnLine::split_to_fit`] for details.
+    pub fn split_to_fit(&self, fits: impl FnMut(&str) -> bool) -> (Self, Option<Self>) {
+        let yarn_line: YarnLine = self.clone().into();
+        let (page, rest) = yarn_line.split_to_fit(fits);
+        let to_localized_line =
+            |line| Self::from_yarn_line(line, self.assets.clone(), self.metadata.clone());
+        (to_localized_line(page), rest.map(to_localized_line))
+    }
+
     /// Returns `true` if this line comes right before an options block.
     ///
     /// "right before" means that no commands are called in between them, no variables are set, etc., in which case this returns `false`.
@@ -180,7 +253,12 @@ impl From<LocalizedLine> for YarnLine {
         Self {
             id: line.id,
             text: line.text,
+            raw_text: line.raw_text,
+            markup_error: line.markup_error,
             attributes: line.attributes,
+            metadata: line.metadata,
+            node_name: line.node_name,
+            source_location: line.source_location,
         }
     }
 }
@@ -194,9 +272,13 @@ impl LocalizedLine {
         Self {
             id: line.id,
             text: line.text,
+            raw_text: line.raw_text,
+            markup_error: line.markup_error,
             attributes: line.attributes,
             metadata,
             assets,
+            node_name: line.node_name,
+            source_location: line.source_location,
         }
     }
 }
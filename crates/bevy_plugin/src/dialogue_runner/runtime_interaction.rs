@@ -16,6 +16,7 @@ pub(crate) fn runtime_interaction_plugin(app: &mut App) {
                 .pipe(panic_on_err)
                 .run_if(resource_exists::<YarnProject>),
             accept_line_hints,
+            report_prefetched_line_assets,
         )
             .chain()
             .after(LineProviderSystemSet)
@@ -29,20 +30,35 @@ pub(crate) fn runtime_interaction_plugin(app: &mut App) {
 pub(crate) struct DialogueExecutionSystemSet;
 
 fn continue_runtime(
-    mut dialogue_runners: Query<(Entity, &mut DialogueRunner)>,
+    mut dialogue_runners: Query<(Entity, &mut DialogueRunner, Option<&SystemChannel>)>,
     mut present_line_events: EventWriter<PresentLineEvent>,
+    mut present_system_message_events: EventWriter<PresentSystemMessageEvent>,
     mut present_options_events: EventWriter<PresentOptionsEvent>,
     mut execute_command_events: EventWriter<ExecuteCommandEvent>,
     mut node_complete_events: EventWriter<NodeCompleteEvent>,
     mut node_start_events: EventWriter<NodeStartEvent>,
     mut line_hints_events: EventWriter<LineHintsEvent>,
+    mut trigger_fired_events: EventWriter<TriggerFiredEvent>,
+    mut variable_changed_events: EventWriter<VariableChangedEvent>,
     mut dialogue_complete_events: EventWriter<DialogueCompleteEvent>,
     mut dialogue_start_events: EventWriter<DialogueStartEvent>,
     mut last_options: Local<HashMap<Entity, Vec<DialogueOption>>>,
     loaded_untyped_assets: Res<Assets<LoadedUntypedAsset>>,
     project: Res<YarnProject>,
 ) -> SystemResult {
-    for (source, mut dialogue_runner) in dialogue_runners.iter_mut() {
+    for (source, mut dialogue_runner, system_channel) in dialogue_runners.iter_mut() {
+        let mut present_line = |line: LocalizedLine| match system_channel {
+            Some(system_channel) => {
+                present_system_message_events.send(PresentSystemMessageEvent {
+                    line: line.without_character_name(),
+                    priority: system_channel.current_priority().unwrap_or_default(),
+                    source,
+                });
+            }
+            None => {
+                present_line_events.send(PresentLineEvent { line, source });
+            }
+        };
         let is_sending_missed_events = !dialogue_runner.unsent_events.is_empty();
         if !is_sending_missed_events {
             if dialogue_runner.just_started {
@@ -80,10 +96,7 @@ fn continue_runtime(
                             .join(", ");
                         bail!("Dialogue options does not contain selected option. Expected one of [{expected_options}], but found {option}");
                     };
-                    present_line_events.send(PresentLineEvent {
-                        line: option.line,
-                        source,
-                    });
+                    present_line(option.line);
                     continue;
                 }
             }
@@ -94,15 +107,19 @@ fn continue_runtime(
             dialogue_runner.dialogue.continue_()?
         };
 
+        let mut options_timeout = None;
         for event in events {
             match event {
                 DialogueEvent::Line(line) => {
                     let assets = dialogue_runner.get_assets(&line);
-                    let metadata = project.line_metadata(&line.id).unwrap_or_default().to_vec();
-                    present_line_events.send(PresentLineEvent {
-                        line: LocalizedLine::from_yarn_line(line, assets, metadata),
-                        source,
-                    });
+                    let mut metadata = project.line_metadata(&line.id).unwrap_or_default().to_vec();
+                    if dialogue_runner.dialogue.has_pending_pages() {
+                        // Only the last page of a paginated line comes right before the options
+                        metadata.retain(|tag| tag != "lastline");
+                    }
+                    let mut line = LocalizedLine::from_yarn_line(line, assets, metadata);
+                    dialogue_runner.hide_draft_line(&mut line);
+                    present_line(line);
                 }
                 DialogueEvent::Options(options) => {
                     let options: Vec<DialogueOption> = options
@@ -113,11 +130,21 @@ fn continue_runtime(
                                 .line_metadata(&option.line.id)
                                 .unwrap_or_default()
                                 .to_vec();
-                            DialogueOption::from_yarn_dialogue_option(option, assets, metadata)
+                            let mut option =
+                                DialogueOption::from_yarn_dialogue_option(option, assets, metadata);
+                            dialogue_runner.hide_draft_line(&mut option.line);
+                            option
                         })
                         .collect();
                     last_options.insert(source, options.clone());
-                    present_options_events.send(PresentOptionsEvent { options, source });
+                    present_options_events.send(PresentOptionsEvent {
+                        options,
+                        timeout: options_timeout.take(),
+                        source,
+                    });
+                }
+                DialogueEvent::OptionsTimeout(timeout) => {
+                    options_timeout = Some(timeout);
                 }
                 DialogueEvent::Command(command) => {
                     execute_command_events.send(ExecuteCommandEvent { command, source });
@@ -132,6 +159,31 @@ fn continue_runtime(
                 DialogueEvent::LineHints(line_ids) => {
                     line_hints_events.send(LineHintsEvent { line_ids, source });
                 }
+                DialogueEvent::BreakpointHit(breakpoint) => {
+                    // The runner stays paused until someone calls `DialogueRunner::continue_in_next_update`
+                    info!(
+                        "Dialogue hit breakpoint at instruction {} of node \"{}\"",
+                        breakpoint.instruction_index, breakpoint.node_name
+                    );
+                }
+                DialogueEvent::TriggerFired(trigger_name) => {
+                    trigger_fired_events.send(TriggerFiredEvent {
+                        trigger_name,
+                        source,
+                    });
+                }
+                DialogueEvent::VariableChanged { name, old, new } => {
+                    variable_changed_events.send(VariableChangedEvent {
+                        name,
+                        old,
+                        new,
+                        source,
+                    });
+                }
+                DialogueEvent::FunctionPending(_) | DialogueEvent::TextPending(_) => {
+                    // Poll the function or text again next frame
+                    dialogue_runner.continue_in_next_update();
+                }
                 DialogueEvent::DialogueComplete => {
                     if !is_sending_missed_events {
                         dialogue_runner.is_running = false;
@@ -144,6 +196,23 @@ fn continue_runtime(
     Ok(())
 }
 
+fn report_prefetched_line_assets(
+    mut dialogue_runners: Query<(Entity, &mut DialogueRunner)>,
+    mut prefetched_events: EventWriter<LineAssetsPrefetchedEvent>,
+    loaded_untyped_assets: Res<Assets<LoadedUntypedAsset>>,
+) {
+    for (source, mut dialogue_runner) in dialogue_runners.iter_mut() {
+        if dialogue_runner.prefetched_line_ids.is_none()
+            || !dialogue_runner.update_prefetched_asset_availability(&loaded_untyped_assets)
+        {
+            continue;
+        }
+        if let Some(line_ids) = dialogue_runner.prefetched_line_ids.take() {
+            prefetched_events.send(LineAssetsPrefetchedEvent { line_ids, source });
+        }
+    }
+}
+
 fn accept_line_hints(
     mut events: EventReader<LineHintsEvent>,
     mut dialogue_runners: Query<&mut DialogueRunner>,
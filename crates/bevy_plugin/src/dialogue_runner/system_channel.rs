@@ -0,0 +1,116 @@
+use crate::dialogue_runner::DialogueExecutionSystemSet;
+use crate::prelude::*;
+use bevy::prelude::*;
+
+pub(crate) fn system_channel_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        start_queued_system_messages
+            .before(DialogueExecutionSystemSet)
+            .in_set(YarnSpinnerSystemSet),
+    );
+}
+
+/// Turns the [`DialogueRunner`] on the same entity into a channel for non-conversational content such as tutorials, hints or narration.
+///
+/// Spawn it on its own entity next to a second [`DialogueRunner`], so that it can run concurrently with a normal conversation:
+/// - Its lines are sent as [`PresentSystemMessageEvent`](crate::events::PresentSystemMessageEvent)s instead of [`PresentLineEvent`](crate::events::PresentLineEvent)s,
+///   without the character name, so they never end up in the regular dialogue view. Continue the runner with
+///   [`DialogueRunner::continue_in_next_update`] once a message was shown, as usual.
+/// - Nodes are not started directly, but queued with [`SystemChannel::enqueue`]. Whenever the runner is idle, the queued node with the highest priority is started.
+///   Nodes with the same priority are started in the order they were queued.
+///
+/// Options and commands of system messages are sent through the usual events. Check whether their `source` has a [`SystemChannel`] to tell them apart.
+///
+/// ## Example
+///
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_yarnspinner::prelude::*;
+/// fn spawn_tutorial_channel(mut commands: Commands, project: Res<YarnProject>) {
+///     let mut system_channel = SystemChannel::new();
+///     system_channel.enqueue("MovementHint", 0);
+///     commands.spawn((project.create_dialogue_runner(), system_channel));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Component)]
+pub struct SystemChannel {
+    queue: Vec<QueuedSystemMessage>,
+    next_sequence_number: u64,
+    current_priority: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QueuedSystemMessage {
+    node_name: String,
+    priority: i32,
+    sequence_number: u64,
+}
+
+impl SystemChannel {
+    /// Creates a new [`SystemChannel`] with an empty queue.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues the node with the given name. Higher priorities are started first.
+    pub fn enqueue(&mut self, node_name: impl Into<String>, priority: i32) -> &mut Self {
+        self.queue.push(QueuedSystemMessage {
+            node_name: node_name.into(),
+            priority,
+            sequence_number: self.next_sequence_number,
+        });
+        self.next_sequence_number += 1;
+        self
+    }
+
+    /// Removes all queued nodes. The node that is currently running is not stopped.
+    pub fn clear(&mut self) -> &mut Self {
+        self.queue.clear();
+        self
+    }
+
+    /// Returns the number of nodes waiting to be started.
+    #[must_use]
+    pub fn queued_count(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns the priority of the node that is currently running, or [`None`] if the channel is idle.
+    #[must_use]
+    pub fn current_priority(&self) -> Option<i32> {
+        self.current_priority
+    }
+
+    fn pop_next(&mut self) -> Option<QueuedSystemMessage> {
+        let index = self
+            .queue
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, message)| {
+                (message.priority, std::cmp::Reverse(message.sequence_number))
+            })
+            .map(|(index, _)| index)?;
+        Some(self.queue.remove(index))
+    }
+}
+
+fn start_queued_system_messages(mut channels: Query<(&mut SystemChannel, &mut DialogueRunner)>) {
+    for (mut system_channel, mut dialogue_runner) in channels.iter_mut() {
+        if dialogue_runner.is_running() {
+            continue;
+        }
+        system_channel.current_priority = None;
+        while let Some(message) = system_channel.pop_next() {
+            if let Err(error) = dialogue_runner.try_start_node(&message.node_name) {
+                error!(
+                    "Failed to start system message \"{}\": {error}",
+                    message.node_name
+                );
+                continue;
+            }
+            system_channel.current_priority = Some(message.priority);
+            break;
+        }
+    }
+}
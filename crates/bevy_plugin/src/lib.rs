@@ -121,8 +121,9 @@ pub mod default_impl {
 pub mod events {
     //! Events that are sent by the [`DialogueRunner`](crate::prelude::DialogueRunner). A dialogue view is expected to at least handle [`PresentLineEvent`] and [`PresentOptionsEvent`].
     pub use crate::dialogue_runner::{
-        DialogueCompleteEvent, DialogueStartEvent, ExecuteCommandEvent, LineHintsEvent,
-        NodeCompleteEvent, NodeStartEvent, PresentLineEvent, PresentOptionsEvent,
+        DialogueCompleteEvent, DialogueStartEvent, ExecuteCommandEvent, LineAssetsPrefetchedEvent,
+        LineHintsEvent, NodeCompleteEvent, NodeStartEvent, PresentLineEvent, PresentOptionsEvent,
+        PresentSystemMessageEvent, TriggerFiredEvent, VariableChangedEvent,
     };
 }
 
@@ -135,7 +136,10 @@ pub mod prelude {
         commands::{YarnCommand, YarnCommands},
         default_impl::FileExtensionAssetProvider,
         development_file_generation::DevelopmentFileGeneration,
-        dialogue_runner::{DialogueOption, DialogueRunner, DialogueRunnerBuilder, LocalizedLine},
+        dialogue_runner::{
+            DialogueClock, DialogueOption, DialogueRunner, DialogueRunnerBuilder, LocalizedLine,
+            SystemChannel,
+        },
         line_provider::{AssetProvider, LineAssets, TextProvider},
         localization::{Localization, Localizations},
         plugin::{YarnFileSource, YarnSpinnerPlugin, YarnSpinnerSystemSet},
@@ -147,8 +151,9 @@ pub mod prelude {
     pub(crate) use serde::{Deserialize, Serialize};
     pub(crate) use yarnspinner::prelude::*;
     pub use yarnspinner::prelude::{
-        IntoYarnValueFromNonYarnValue, Language, LineId, MarkupAttribute, MarkupValue, OptionId,
-        VariableStorage, YarnFn, YarnLibrary, YarnValue,
+        ButtonGlyph, ButtonGlyphs, InputPlatform, IntoYarnValueFromNonYarnValue, Language, LineId,
+        MarkupAttribute, MarkupValue, OptionId, OptionsTimeout, TypedVariableStorage,
+        VariableSnapshot, VariableStorage, YarnFn, YarnLibrary, YarnValue,
     };
     pub(crate) type SystemResult = Result<()>;
 }
@@ -157,10 +162,25 @@ pub use crate::commands::{TaskFinishedIndicator, UntypedYarnCommand};
 pub use crate::dialogue_runner::{InnerDialogue, InnerDialogueMut};
 pub use yarnspinner::core::{yarn_fn_type, UntypedYarnFn};
 pub use yarnspinner::prelude::{
-    Compilation, StringInfo, TextProvider as UnderlyingTextProvider, YarnAnalysisContext,
-    YarnCommand as UnderlyingYarnCommand, YarnLine as UnderlyingYarnLine,
+    Compilation, ProgramChunkManifest, ProgramChunks, ProgramSplit, SourceLocation, StringInfo,
+    TextProvider as UnderlyingTextProvider, YarnAnalyser, YarnAnalysisContext,
+    YarnCommand as UnderlyingYarnCommand, YarnLine as UnderlyingYarnLine, YarnProgram,
 };
 
+pub mod clock {
+    //! Contains the [`DialogueClock`] implementations that can be passed to a [`DialogueRunner`](crate::prelude::DialogueRunner),
+    //! e.g. to make `<<wait>>` commands controllable in automated tests.
+    pub use crate::dialogue_runner::{DialogueClock, ManualClock, RealTimeClock, VirtualTimeClock};
+}
+
+pub mod audit {
+    //! Contains types needed to cross-check the line IDs of a [`YarnProject`](crate::prelude::YarnProject) across its program, string table, strings files and metadata.
+    //! Start with [`AuditYarnProjectEvent`].
+    pub use crate::project::{
+        AuditYarnProjectEvent, TranslationAudit, YarnProjectAuditReportEvent,
+    };
+}
+
 pub mod deferred_loading {
     //! Contains types needed for the deferred loading functionality, which is used when the list of Yarn files is not immediately available at startup.
     pub use crate::plugin::DeferredYarnSpinnerPlugin;
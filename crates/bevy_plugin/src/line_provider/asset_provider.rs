@@ -149,6 +149,24 @@ pub trait AssetProvider: Debug + Send + Sync {
     /// Returns the [`LineAssets`] for the given [`UnderlyingYarnLine`]. Will only be called if [`AssetProvider::update_asset_availability`] returns `true`,
     /// so an implementor is expected to panic if the assets are not available.
     fn get_assets(&self, line: &UnderlyingYarnLine) -> LineAssets;
+
+    /// Passes the [`LineId`]s of lines that are likely to be delivered after the current node, e.g. those of a node that is about to be started.
+    /// An implementor should start loading their assets so that they are available without delay once they are announced by [`AssetProvider::accept_line_hints`].
+    /// Unlike the line hints, these lines must not be taken into account by [`AssetProvider::update_asset_availability`]. Each call replaces the previously prefetched lines.
+    ///
+    /// Called by [`DialogueRunner::prefetch_node`] and [`DialogueRunner::prefetch_reachable_nodes`]. The default implementation does nothing.
+    fn prefetch_line_assets(&mut self, _line_ids: &[LineId]) {}
+
+    /// Returns whether the assets for all lines passed to [`AssetProvider::prefetch_line_assets`] have been loaded.
+    /// Like [`AssetProvider::update_asset_availability`], this method is allowed to process already loaded assets.
+    ///
+    /// The default implementation returns `true`, which is correct for providers that do not prefetch anything.
+    fn update_prefetched_asset_availability(
+        &mut self,
+        _loaded_untyped_assets: &Assets<LoadedUntypedAsset>,
+    ) -> bool {
+        true
+    }
 }
 
 /// Assets that were provided by one or more [`AssetProvider`]s. Stores them in the form of [`Handle`]s.
@@ -72,4 +72,16 @@ impl AssetProvider for AudioAssetProvider {
     fn get_assets(&self, line: &YarnLine) -> LineAssets {
         self.0.get_assets(line)
     }
+
+    fn prefetch_line_assets(&mut self, line_ids: &[LineId]) {
+        self.0.prefetch_line_assets(line_ids)
+    }
+
+    fn update_prefetched_asset_availability(
+        &mut self,
+        loaded_untyped_assets: &Assets<LoadedUntypedAsset>,
+    ) -> bool {
+        self.0
+            .update_prefetched_asset_availability(loaded_untyped_assets)
+    }
 }
@@ -5,7 +5,7 @@ use bevy::prelude::*;
 use bevy::utils::{HashMap, HashSet};
 use std::any::Any;
 use std::fmt::Debug;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub(crate) fn file_extension_asset_provider_plugin(_app: &mut App) {}
 
@@ -31,6 +31,7 @@ pub struct FileExtensionAssetProvider {
     loading_handles: HashMap<PathBuf, Handle<LoadedUntypedAsset>>,
     loaded_handles: HashMap<PathBuf, UntypedHandle>,
     line_ids: HashSet<LineId>,
+    prefetched_line_ids: HashSet<LineId>,
     file_extensions: HashMap<&'static str, Vec<String>>,
 }
 
@@ -134,37 +135,8 @@ impl AssetProvider for FileExtensionAssetProvider {
         if self.language.is_none() || self.localizations.is_none() || self.line_ids.is_empty() {
             return false;
         };
-
-        if self.loading_handles.is_empty() && self.loaded_handles.is_empty() {
-            return true;
-        }
-
-        let Some(asset_server) = self.asset_server.as_ref() else {
-            return false;
-        };
-
-        self.loading_handles.retain(|_path, handle| {
-            asset_server.get_load_state(handle.id()) != Some(LoadState::Failed)
-        });
-        let newly_loaded: HashMap<_, _> = self
-            .loading_handles
-            .iter()
-            .filter_map(|(path, handle)| {
-                loaded_untyped_assets
-                    .get(handle)
-                    .map(|loaded| (path.clone(), loaded.handle.clone()))
-            })
-            .collect();
-        self.loading_handles
-            .retain(|path, _| !newly_loaded.contains_key(path));
-        self.loaded_handles.extend(newly_loaded);
-        if !self.loading_handles.is_empty() {
-            false
-        } else {
-            self.loaded_handles
-                .iter()
-                .all(|(_path, handle)| asset_server.is_loaded_with_dependencies(handle.id()))
-        }
+        self.poll_loading_handles(loaded_untyped_assets);
+        self.are_assets_loaded(&self.line_ids)
     }
 
     fn accept_line_hints(&mut self, line_ids: &[LineId]) {
@@ -172,7 +144,7 @@ impl AssetProvider for FileExtensionAssetProvider {
             self.line_ids.clear();
             self.line_ids.extend(line_ids.iter().cloned());
         }
-        self.reload_assets();
+        self.load_missing_assets();
     }
 
     fn get_assets(&self, line: &UnderlyingYarnLine) -> LineAssets {
@@ -202,35 +174,121 @@ impl AssetProvider for FileExtensionAssetProvider {
         }
         default()
     }
+
+    fn prefetch_line_assets(&mut self, line_ids: &[LineId]) {
+        {
+            self.prefetched_line_ids.clear();
+            self.prefetched_line_ids.extend(line_ids.iter().cloned());
+        }
+        self.load_missing_assets();
+    }
+
+    fn update_prefetched_asset_availability(
+        &mut self,
+        loaded_untyped_assets: &Assets<LoadedUntypedAsset>,
+    ) -> bool {
+        if self.language.is_none() || self.localizations.is_none() {
+            return true;
+        };
+        self.poll_loading_handles(loaded_untyped_assets);
+        self.are_assets_loaded(&self.prefetched_line_ids)
+    }
 }
 
 impl FileExtensionAssetProvider {
     fn reload_assets(&mut self) {
-        if let Some(language) = self.language.as_ref() {
-            if let Some(localizations) = self.localizations.as_ref() {
-                if let Some(localization) = localizations.supported_localization(language) {
-                    let dir = localization.assets_sub_folder.as_path();
-                    self.loading_handles.clear();
-                    self.loaded_handles.clear();
-                    let Some(asset_server) = self.asset_server.as_ref() else {
-                        return;
-                    };
-                    for line_id in self.line_ids.iter() {
-                        for extension in self.file_extensions.values().flatten() {
-                            let file_name =
-                                format!("{}.{extension}", line_id.0.trim_start_matches("line:"));
-                            let path = dir.join(file_name);
-                            let asset_path = path.to_string_lossy().replace('\\', "/");
-                            let handle = asset_server.load_untyped(asset_path);
-                            self.loading_handles.insert(path, handle);
-                        }
-                    }
-                } else {
-                    panic!("Tried to find an asset for \"{language}\", which is a language that is not supported by localizations");
-                }
+        self.loading_handles.clear();
+        self.loaded_handles.clear();
+        self.load_missing_assets();
+    }
+
+    /// Starts loading the assets of both the hinted and the prefetched lines that are not loaded yet, and drops the assets of all other lines.
+    fn load_missing_assets(&mut self) {
+        let Some(dir) = self.assets_sub_folder() else {
+            return;
+        };
+        let Some(asset_server) = self.asset_server.clone() else {
+            return;
+        };
+        let paths: HashSet<_> = self
+            .line_ids
+            .iter()
+            .chain(self.prefetched_line_ids.iter())
+            .flat_map(|line_id| self.asset_paths(&dir, line_id))
+            .collect();
+        self.loading_handles.retain(|path, _| paths.contains(path));
+        self.loaded_handles.retain(|path, _| paths.contains(path));
+        for path in paths {
+            if self.loading_handles.contains_key(&path) || self.loaded_handles.contains_key(&path) {
+                continue;
             }
+            let asset_path = path.to_string_lossy().replace('\\', "/");
+            let handle = asset_server.load_untyped(asset_path);
+            self.loading_handles.insert(path, handle);
         }
     }
+
+    /// Drops the handles of assets that failed to load and moves the handles of newly loaded assets to [`FileExtensionAssetProvider::loaded_handles`].
+    fn poll_loading_handles(&mut self, loaded_untyped_assets: &Assets<LoadedUntypedAsset>) {
+        let Some(asset_server) = self.asset_server.as_ref() else {
+            return;
+        };
+        self.loading_handles.retain(|_path, handle| {
+            asset_server.get_load_state(handle.id()) != Some(LoadState::Failed)
+        });
+        let newly_loaded: HashMap<_, _> = self
+            .loading_handles
+            .iter()
+            .filter_map(|(path, handle)| {
+                loaded_untyped_assets
+                    .get(handle)
+                    .map(|loaded| (path.clone(), loaded.handle.clone()))
+            })
+            .collect();
+        self.loading_handles
+            .retain(|path, _| !newly_loaded.contains_key(path));
+        self.loaded_handles.extend(newly_loaded);
+    }
+
+    /// Returns whether all assets of the given lines that exist have been loaded along with their dependencies.
+    fn are_assets_loaded(&self, line_ids: &HashSet<LineId>) -> bool {
+        let Some(asset_server) = self.asset_server.as_ref() else {
+            return false;
+        };
+        let Some(dir) = self.assets_sub_folder() else {
+            return false;
+        };
+        line_ids
+            .iter()
+            .flat_map(|line_id| self.asset_paths(&dir, line_id))
+            .all(|path| {
+                !self.loading_handles.contains_key(&path)
+                    && self.loaded_handles.get(&path).map_or(true, |handle| {
+                        asset_server.is_loaded_with_dependencies(handle.id())
+                    })
+            })
+    }
+
+    fn assets_sub_folder(&self) -> Option<PathBuf> {
+        let language = self.language.as_ref()?;
+        let localizations = self.localizations.as_ref()?;
+        let Some(localization) = localizations.supported_localization(language) else {
+            panic!("Tried to find an asset for \"{language}\", which is a language that is not supported by localizations");
+        };
+        Some(localization.assets_sub_folder.clone())
+    }
+
+    fn asset_paths<'a>(
+        &'a self,
+        dir: &'a Path,
+        line_id: &'a LineId,
+    ) -> impl Iterator<Item = PathBuf> + 'a {
+        let file_name_without_extension = line_id.0.trim_start_matches("line:");
+        self.file_extensions
+            .values()
+            .flatten()
+            .map(move |extension| dir.join(format!("{file_name_without_extension}.{extension}")))
+    }
 }
 
 impl Debug for FileExtensionAssetProvider {
@@ -241,6 +299,7 @@ impl Debug for FileExtensionAssetProvider {
             .field("asset_server", &())
             .field("handles", &self.loading_handles)
             .field("line_ids", &self.line_ids)
+            .field("prefetched_line_ids", &self.prefetched_line_ids)
             .finish()
     }
 }
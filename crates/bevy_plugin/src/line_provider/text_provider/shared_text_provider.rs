@@ -5,6 +5,7 @@ use std::any::Any;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
 
 pub(crate) fn shared_text_provider_plugin(_app: &mut App) {}
 
@@ -54,6 +55,10 @@ impl UnderlyingTextProvider for SharedTextProvider {
         self.0.read().unwrap().get_text(id)
     }
 
+    fn poll_text(&mut self, id: &LineId, context: &mut Context<'_>) -> Poll<Option<String>> {
+        self.0.write().unwrap().poll_text(id, context)
+    }
+
     fn set_language(&mut self, language: Option<Language>) {
         self.0.write().unwrap().set_language(language)
     }
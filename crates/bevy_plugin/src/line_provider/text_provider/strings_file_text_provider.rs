@@ -49,6 +49,14 @@ impl UnderlyingTextProvider for StringsFileTextProvider {
         if self.is_base_language() {
             return self.base_string_table.get(id).map(|info| info.text.clone());
         }
+        // Lines of nodes with a `language` header are not translated, see `StringInfo::language`
+        if let Some(info) = self
+            .base_string_table
+            .get(id)
+            .filter(|info| info.language.is_some())
+        {
+            return Some(info.text.clone());
+        }
 
         self.translation_string_table
             .as_ref()
@@ -144,6 +144,10 @@ impl StringsFile {
         let language = language.into();
         let mut records = HashMap::new();
         for (id, string_info) in string_table {
+            // Lines of nodes with a `language` header are deliberately written in that language, so they are not translated
+            if string_info.language.is_some() {
+                continue;
+            }
             if string_info.is_implicit_tag {
                 bail!(
                     "Cannot build strings file from not fully tagged Yarn files (line {} in \"{}\" is not tagged).",
@@ -182,13 +186,7 @@ impl StringsFile {
         let file = File::create(path)
             .map_err(|e| anyhow!("Failed to create strings file \"{}\": {e}", path.display(),))?;
         let mut writer = csv::Writer::from_writer(file);
-        let mut records = self.0.iter().map(|(_, record)| record).collect::<Vec<_>>();
-        records.sort_by(|lhs, rhs| {
-            lhs.file
-                .cmp(&rhs.file)
-                .then(lhs.line_number.cmp(&rhs.line_number))
-        });
-        for record in records {
+        for record in self.records() {
             writer.serialize(record)?;
         }
         writer.flush()?;
@@ -204,12 +202,20 @@ impl StringsFile {
             .find(|record| &record.language != expected_language)
     }
 
+    /// Iterates over the records ordered by file, then by line number, and then by ID, so that written files produce stable diffs.
     pub(crate) fn iter(&self) -> impl Iterator<Item = (&LineId, &StringsFileRecord)> {
-        self.0.iter()
+        let mut records = self.0.iter().collect::<Vec<_>>();
+        records.sort_by(|(_, lhs), (_, rhs)| {
+            lhs.file
+                .cmp(&rhs.file)
+                .then(lhs.line_number.cmp(&rhs.line_number))
+                .then_with(|| lhs.id.0.cmp(&rhs.id.0))
+        });
+        records.into_iter()
     }
 
     pub(crate) fn records(&self) -> impl Iterator<Item = &StringsFileRecord> {
-        self.0.values()
+        self.iter().map(|(_, record)| record)
     }
 }
 
@@ -325,6 +331,26 @@ fn read_comments(metadata: impl IntoIterator<Item = String>) -> String {
 mod test {
     use super::*;
 
+    #[test]
+    fn leaves_out_lines_of_nodes_with_their_own_language() {
+        let string_info = |language: Option<&str>| StringInfo {
+            text: "Bonjour".to_owned(),
+            file_name: "test.yarn".to_owned(),
+            language: language.map(ToOwned::to_owned),
+            ..default()
+        };
+        let strings_file = StringsFile::from_string_table(
+            Language::new("de-CH"),
+            [
+                (LineId("line:a".to_owned()), string_info(None)),
+                (LineId("line:b".to_owned()), string_info(Some("fr"))),
+            ],
+        )
+        .unwrap();
+        let ids: Vec<_> = strings_file.iter().map(|(id, _)| id.0.as_str()).collect();
+        assert_eq!(ids, vec!["line:a"]);
+    }
+
     #[test]
     fn combines_comments_without_change() {
         let old = "Foo, Line metadata: Bar";
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+pub use audit::{AuditYarnProjectEvent, TranslationAudit, YarnProjectAuditReportEvent};
 use bevy::prelude::*;
 use bevy::utils::{HashMap, HashSet};
 pub(crate) use compilation::{
@@ -6,11 +7,14 @@ pub(crate) use compilation::{
 };
 use std::fmt::Debug;
 use std::iter;
+use std::sync::Arc;
 
+mod audit;
 mod compilation;
 
 pub(crate) fn project_plugin(app: &mut App) {
     app.add_plugins(compilation::project_compilation_plugin)
+        .add_plugins(audit::project_audit_plugin)
         .add_event::<LoadYarnProjectEvent>();
 }
 
@@ -33,6 +37,8 @@ pub(crate) struct CompilationSystemSet;
 pub struct YarnProject {
     pub(crate) yarn_files: HashSet<Handle<YarnFile>>,
     pub(crate) compilation: Compilation,
+    /// The program of [`YarnProject::compilation`], shared by all [`DialogueRunner`]s built from this project.
+    pub(crate) program: Arc<YarnProgram>,
     pub(crate) localizations: Option<Localizations>,
     pub(crate) asset_server: AssetServer,
     pub(crate) metadata: HashMap<LineId, Vec<String>>,
@@ -64,6 +70,12 @@ impl YarnProject {
         &self.compilation
     }
 
+    /// Returns the compiled program of this project. All [`DialogueRunner`]s built from this project share it instead of holding their own copy,
+    /// so spawning many of them, e.g. one per NPC, is cheap.
+    pub fn program(&self) -> &Arc<YarnProgram> {
+        &self.program
+    }
+
     /// Returns the [`Localizations`] of this project, if any. These come from [`YarnSpinnerPlugin::with_localizations`] or [`LoadYarnProjectEvent::with_localizations`].
     pub fn localizations(&self) -> Option<&Localizations> {
         self.localizations.as_ref()
@@ -92,16 +104,8 @@ impl YarnProject {
             .as_ref()
             .unwrap()
             .nodes
-            .get(node_name)?
-            .headers
-            .iter()
-            .fold(HashMap::new(), |mut map: HashMap<_, Vec<_>>, header| {
-                map.entry(header.key.as_str())
-                    .or_default()
-                    .push(header.value.as_str());
-                map
-            })
-            .into()
+            .get(node_name)
+            .map(|node| node.headers_by_key().into_iter().collect())
     }
 }
 
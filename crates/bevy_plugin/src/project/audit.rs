@@ -0,0 +1,307 @@
+use crate::prelude::*;
+use crate::project::CompilationSystemSet;
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+
+pub(crate) fn project_audit_plugin(app: &mut App) {
+    app.add_event::<AuditYarnProjectEvent>()
+        .add_event::<YarnProjectAuditReportEvent>()
+        .add_systems(
+            Update,
+            audit_yarn_project
+                .after(CompilationSystemSet)
+                .in_set(YarnSpinnerSystemSet),
+        );
+}
+
+/// Send this event to cross-check the line IDs of the [`YarnProject`] across all places they appear in:
+/// the compiled program, the base language string table, the strings file of every translation in the [`Localizations`]
+/// and the line metadata returned by [`YarnProject::line_metadata`].
+///
+/// Once the project is compiled and all strings files are loaded, a single [`YarnProjectAuditReportEvent`] is sent with the results.
+/// The audit is opt-in: nothing is checked unless this event is sent. It can be sent before the [`YarnProject`] is available.
+///
+/// ## Example
+///
+/// Failing a CI run when a translation is incomplete:
+///
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_yarnspinner::prelude::*;
+/// # use bevy_yarnspinner::audit::*;
+/// # let mut app = App::new();
+/// app.add_systems(Startup, |mut events: EventWriter<AuditYarnProjectEvent>| {
+///     events.send(AuditYarnProjectEvent);
+/// })
+/// .add_systems(Update, exit_on_failed_audit);
+///
+/// fn exit_on_failed_audit(mut reports: EventReader<YarnProjectAuditReportEvent>) {
+///     for report in reports.read() {
+///         if !report.is_consistent() {
+///             panic!("{report}");
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect, Event)]
+#[reflect(Debug, Default, PartialEq)]
+pub struct AuditYarnProjectEvent;
+
+/// The result of an audit requested by [`AuditYarnProjectEvent`].
+/// All lists of line IDs are sorted. The [`Display`] implementation lists every problem found, one per line.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Event)]
+pub struct YarnProjectAuditReportEvent {
+    /// Lines and options used by the compiled program that have no entry in the base language string table.
+    pub lines_missing_from_string_table: Vec<LineId>,
+    /// Entries of the base language string table that are never used by the compiled program.
+    pub orphaned_string_table_entries: Vec<LineId>,
+    /// Entries of the base language string table that have no entry in the line metadata.
+    pub lines_missing_metadata: Vec<LineId>,
+    /// Entries of the line metadata whose line is not in the base language string table.
+    pub orphaned_metadata: Vec<LineId>,
+    /// The results for the strings file of each translation, in the order of [`Localizations::translations`].
+    pub translations: Vec<TranslationAudit>,
+}
+
+/// The audit of a single translation's strings file, part of a [`YarnProjectAuditReportEvent`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranslationAudit {
+    /// The language of the translation.
+    pub language: Language,
+    /// The path of the strings file inside the `assets` folder, see [`Localization::strings_file`].
+    pub strings_file: PathBuf,
+    /// Whether the strings file could be loaded. If not, all lines of the base language string table are reported as missing.
+    pub loaded: bool,
+    /// Lines of the base language string table that the strings file has no entry for.
+    pub missing_lines: Vec<LineId>,
+    /// Entries of the strings file whose line is not in the base language string table.
+    pub orphaned_lines: Vec<LineId>,
+    /// Entries of the strings file that are written in another language than [`TranslationAudit::language`].
+    pub lines_in_wrong_language: Vec<LineId>,
+}
+
+impl YarnProjectAuditReportEvent {
+    /// Returns `true` if no problems were found, i.e. every line ID appears everywhere it is expected to and nowhere else.
+    pub fn is_consistent(&self) -> bool {
+        self.lines_missing_from_string_table.is_empty()
+            && self.orphaned_string_table_entries.is_empty()
+            && self.lines_missing_metadata.is_empty()
+            && self.orphaned_metadata.is_empty()
+            && self
+                .translations
+                .iter()
+                .all(|translation| translation.is_consistent())
+    }
+
+    pub(crate) fn new<'a>(
+        yarn_project: &YarnProject,
+        strings_files: impl IntoIterator<Item = (&'a Localization, Option<&'a StringsFile>)>,
+    ) -> Self {
+        let compilation = &yarn_project.compilation;
+        let string_table_ids: HashSet<_> = compilation.string_table.keys().collect();
+        // Lines of nodes with a `language` header are not translated, see `StringInfo::language`
+        let translated_ids: HashSet<_> = compilation
+            .string_table
+            .iter()
+            .filter(|(_, string_info)| string_info.language.is_none())
+            .map(|(id, _)| id)
+            .collect();
+        let metadata_ids: HashSet<_> = yarn_project.metadata.keys().collect();
+        let used_ids: HashSet<_> = compilation.program.iter().flat_map(used_line_ids).collect();
+        let program_ids: HashSet<_> = used_ids.iter().collect();
+
+        let translations = strings_files
+            .into_iter()
+            .map(|(localization, strings_file)| {
+                TranslationAudit::new(localization, strings_file, &translated_ids)
+            })
+            .collect();
+
+        Self {
+            lines_missing_from_string_table: sorted_difference(&program_ids, &string_table_ids),
+            orphaned_string_table_entries: sorted_difference(&string_table_ids, &program_ids),
+            lines_missing_metadata: sorted_difference(&string_table_ids, &metadata_ids),
+            orphaned_metadata: sorted_difference(&metadata_ids, &string_table_ids),
+            translations,
+        }
+    }
+}
+
+impl TranslationAudit {
+    /// Returns `true` if the strings file was loaded and contains exactly the lines of the base language string table, all in the right language.
+    pub fn is_consistent(&self) -> bool {
+        self.loaded
+            && self.missing_lines.is_empty()
+            && self.orphaned_lines.is_empty()
+            && self.lines_in_wrong_language.is_empty()
+    }
+
+    fn new(
+        localization: &Localization,
+        strings_file: Option<&StringsFile>,
+        translated_ids: &HashSet<&LineId>,
+    ) -> Self {
+        let strings_file_ids: HashSet<_> = strings_file
+            .into_iter()
+            .flat_map(|strings_file| strings_file.iter().map(|(id, _)| id))
+            .collect();
+        let mut lines_in_wrong_language: Vec<_> = strings_file
+            .into_iter()
+            .flat_map(StringsFile::records)
+            .filter(|record| record.language != localization.language)
+            .map(|record| record.id.clone())
+            .collect();
+        lines_in_wrong_language.sort_unstable();
+        Self {
+            language: localization.language.clone(),
+            strings_file: localization.strings_file.clone(),
+            loaded: strings_file.is_some(),
+            missing_lines: sorted_difference(translated_ids, &strings_file_ids),
+            orphaned_lines: sorted_difference(&strings_file_ids, translated_ids),
+            lines_in_wrong_language,
+        }
+    }
+}
+
+/// The IDs of all strings the program refers to: its lines and options, as well as the source text of nodes tagged with `rawText`.
+fn used_line_ids(program: &Program) -> Vec<LineId> {
+    let source_texts = program
+        .nodes
+        .values()
+        .map(|node| &node.source_text_string_id)
+        .filter(|id| !id.is_empty())
+        .map(|id| LineId(id.clone()));
+    program.line_ids().into_iter().chain(source_texts).collect()
+}
+
+fn sorted_difference(lhs: &HashSet<&LineId>, rhs: &HashSet<&LineId>) -> Vec<LineId> {
+    let mut difference: Vec<_> = lhs.difference(rhs).map(|&id| id.clone()).collect();
+    difference.sort_unstable();
+    difference
+}
+
+impl Display for YarnProjectAuditReportEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_consistent() {
+            return writeln!(f, "The Yarn project is consistent.");
+        }
+        let mut problems = |description: &str, ids: &[LineId]| -> fmt::Result {
+            for id in ids {
+                writeln!(f, "{id}: {description}")?;
+            }
+            Ok(())
+        };
+        problems(
+            "used by the program but missing from the string table",
+            &self.lines_missing_from_string_table,
+        )?;
+        problems(
+            "in the string table but not used by the program",
+            &self.orphaned_string_table_entries,
+        )?;
+        problems(
+            "in the string table but missing from the line metadata",
+            &self.lines_missing_metadata,
+        )?;
+        problems(
+            "in the line metadata but not in the string table",
+            &self.orphaned_metadata,
+        )?;
+        for translation in &self.translations {
+            write!(f, "{translation}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for TranslationAudit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let language = &self.language;
+        let path = self.strings_file.display();
+        if !self.loaded {
+            writeln!(f, "{path}: strings file for {language} could not be loaded")?;
+        }
+        for id in &self.missing_lines {
+            writeln!(
+                f,
+                "{id}: missing from the strings file for {language} at {path}"
+            )?;
+        }
+        for id in &self.orphaned_lines {
+            writeln!(
+                f,
+                "{id}: in the strings file for {language} at {path} but not in the string table"
+            )?;
+        }
+        for id in &self.lines_in_wrong_language {
+            writeln!(
+                f,
+                "{id}: in the strings file for {language} at {path} but written in another language"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct PendingAudit {
+    requested: bool,
+    strings_files: Option<Vec<Handle<StringsFile>>>,
+}
+
+fn audit_yarn_project(
+    mut audit_requests: EventReader<AuditYarnProjectEvent>,
+    mut reports: EventWriter<YarnProjectAuditReportEvent>,
+    yarn_project: Option<Res<YarnProject>>,
+    asset_server: Res<AssetServer>,
+    strings_file_assets: Res<Assets<StringsFile>>,
+    mut pending: Local<PendingAudit>,
+) {
+    if audit_requests.read().count() > 0 {
+        pending.requested = true;
+    }
+    let Some(yarn_project) = yarn_project else {
+        return;
+    };
+    if !pending.requested {
+        return;
+    }
+    let translations = yarn_project
+        .localizations
+        .as_ref()
+        .map(|localizations| localizations.translations.as_slice())
+        .unwrap_or_default();
+    let handles = pending.strings_files.get_or_insert_with(|| {
+        translations
+            .iter()
+            .map(|localization| {
+                let path = localization
+                    .strings_file
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                asset_server.load(path)
+            })
+            .collect()
+    });
+    let is_done_loading = |handle: &Handle<StringsFile>| {
+        matches!(
+            asset_server.get_load_state(handle.id()),
+            Some(LoadState::Loaded | LoadState::Failed)
+        )
+    };
+    if !handles.iter().all(is_done_loading) {
+        return;
+    }
+    let strings_files = translations
+        .iter()
+        .zip(handles.iter())
+        .map(|(localization, handle)| (localization, strings_file_assets.get(handle)));
+    reports.send(YarnProjectAuditReportEvent::new(
+        &yarn_project,
+        strings_files,
+    ));
+    *pending = default();
+}
@@ -6,6 +6,7 @@ use anyhow::bail;
 use bevy::prelude::*;
 use bevy::utils::{error, HashSet};
 use std::fmt::Debug;
+use std::sync::Arc;
 
 pub(crate) fn project_compilation_plugin(app: &mut App) {
     app.register_type::<YarnFilesToLoad>()
@@ -130,15 +131,20 @@ fn recompile_loaded_yarn_files(
         .iter()
         .map(|(line_id, string_info)| (line_id.clone(), string_info.metadata.clone()))
         .collect();
+    yarn_project.program = Arc::new(compilation.program.clone().unwrap());
     yarn_project.compilation = compilation;
     yarn_project.metadata = metadata;
-    let program = yarn_project.compilation.program.clone().unwrap();
     for mut dialogue_runner in dialogue_runners.iter_mut() {
-        let current_node = dialogue_runner.current_node();
-        dialogue_runner.dialogue.replace_program(program.clone());
+        // Only the nodes that changed are affected, so runners that are not in one of them keep running undisturbed
+        let changed_nodes = dialogue_runner
+            .dialogue
+            .patch_program(yarn_project.program.clone());
         dialogue_runner
             .text_provider
             .set_base_string_table(yarn_project.compilation.string_table.clone());
+        let current_node = dialogue_runner
+            .current_node()
+            .filter(|current_node| changed_nodes.contains(current_node));
         if let Some(current_node) = current_node {
             dialogue_runner
                 .stop()
@@ -235,6 +241,7 @@ fn compile_loaded_yarn_files(
         .collect();
     commands.insert_resource(YarnProject {
         yarn_files: std::mem::take(&mut yarn_files_being_loaded.0),
+        program: Arc::new(compilation.program.clone().unwrap()),
         compilation,
         localizations: yarn_project_config_to_load.localizations.clone().unwrap(),
         asset_server: asset_server.clone(),
@@ -283,6 +290,11 @@ fn compile_yarn_files(
         }
     }
     let inner_yarn_files = yarn_files.map(|file| file.file.clone());
-    let compilation = YarnCompiler::new().add_files(inner_yarn_files).compile()?;
+    let mut compiler = YarnCompiler::new();
+    compiler.add_files(inner_yarn_files);
+    if let Some(localizations) = localizations {
+        compiler.with_base_language(localizations.base_localization.language.to_string());
+    }
+    let compilation = compiler.compile()?;
     Ok(Some(compilation))
 }
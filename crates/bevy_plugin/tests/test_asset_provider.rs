@@ -2,6 +2,7 @@
 use anyhow::{bail, Result};
 use bevy::prelude::*;
 use bevy::utils::Instant;
+use bevy_yarnspinner::events::LineAssetsPrefetchedEvent;
 use bevy_yarnspinner::prelude::*;
 use utils::prelude::*;
 
@@ -197,3 +198,50 @@ fn does_not_load_asset_with_invalid_type() -> Result<()> {
     assert!(asset.is_none());
     Ok(())
 }
+
+#[test]
+fn prefetches_assets_before_node_is_started() -> Result<()> {
+    let mut app = App::new();
+
+    app.setup_default_plugins().add_plugins(
+        YarnSpinnerPlugin::with_yarn_source(YarnFileSource::file("lines_with_ids.yarn"))
+            .with_localizations(Localizations {
+                base_localization: "en-US".into(),
+                translations: vec![],
+            })
+            .with_development_file_generation(DevelopmentFileGeneration::None),
+    );
+
+    let project = app.load_project();
+    let mut dialogue_runner = project
+        .build_dialogue_runner()
+        .add_asset_provider(AudioAssetProvider::new())
+        .build();
+    dialogue_runner
+        .set_asset_language("en-US")
+        .prefetch_node("Start");
+    app.world.spawn(dialogue_runner);
+
+    let start = Instant::now();
+    let event = loop {
+        app.update();
+        let mut events = app
+            .world
+            .resource_mut::<Events<LineAssetsPrefetchedEvent>>();
+        if let Some(event) = events.drain().next() {
+            break event;
+        }
+        if start.elapsed().as_secs() > 2 {
+            bail!("Expected the prefetched assets to be loaded");
+        }
+    };
+    assert_eq!(12, event.line_ids.len());
+    assert!(!app.dialogue_runner().is_running());
+
+    let asset_server = app.world.resource::<AssetServer>();
+    let handle = asset_server
+        .get_handle_untyped("dialogue/en-US/9.ogg")
+        .unwrap();
+    assert!(asset_server.is_loaded_with_dependencies(handle.id()));
+    Ok(())
+}
@@ -260,6 +260,38 @@ fn default_language_is_base_language() {
     }
 }
 
+#[test]
+fn system_channel_presents_lines_as_system_messages() -> Result<()> {
+    let mut app = App::new();
+    let mut asserter = EventAsserter::new();
+    let dialogue_runner = app
+        .setup_default_plugins()
+        .add_plugins(YarnSpinnerPlugin::with_yarn_source(YarnFileSource::file(
+            "lines.yarn",
+        )))
+        .load_project()
+        .create_dialogue_runner();
+    let mut system_channel = SystemChannel::new();
+    system_channel.enqueue("Start", 1);
+    app.world.spawn((dialogue_runner, system_channel));
+
+    app.update();
+    assert_events!(asserter, app contains [
+        DialogueStartEvent,
+        PresentLineEvent (n = 0),
+        PresentSystemMessageEvent with |event| event.line.text == english_lines()[0] && event.priority == 1,
+    ]);
+    assert_eq!(
+        app.world
+            .query::<&SystemChannel>()
+            .single(&app.world)
+            .queued_count(),
+        0
+    );
+
+    Ok(())
+}
+
 fn setup_dialogue_runner_without_localizations(app: &mut App) -> Mut<DialogueRunner> {
     app.setup_default_plugins()
         .add_plugins(YarnSpinnerPlugin::with_yarn_source(YarnFileSource::file(
@@ -1,8 +1,9 @@
 use anyhow::Result;
 use bevy::prelude::*;
 use bevy::utils::Instant;
-use bevy_yarnspinner::{events::*, prelude::*};
+use bevy_yarnspinner::{clock::ManualClock, events::*, prelude::*};
 use std::thread::sleep;
+use std::time::Duration;
 use utils::prelude::*;
 
 mod utils;
@@ -33,7 +34,7 @@ fn waits_on_command() -> Result<()> {
             ExecuteCommandEvent (n = 0),
         ]);
     }
-    sleep(std::time::Duration::from_millis(150));
+    sleep(Duration::from_millis(150));
     app.continue_dialogue_and_update();
     assert_events!(asserter, app contains [
         PresentLineEvent with |event| event.line.text == "Ended wait",
@@ -43,6 +44,45 @@ fn waits_on_command() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn waits_on_dialogue_clock() -> Result<()> {
+    let mut app = App::new();
+    let mut asserter = EventAsserter::new();
+    let clock = ManualClock::default();
+    app.setup_dialogue_runner_for_wait()
+        .set_clock(clock.clone())
+        .start_node("Start");
+    app.update();
+    assert_events!(asserter, app contains [
+        PresentLineEvent with |event| event.line.text == "Starting wait",
+        ExecuteCommandEvent (n = 0),
+    ]);
+    app.continue_dialogue_and_update();
+    assert_events!(asserter, app contains [
+        PresentLineEvent (n = 0),
+        ExecuteCommandEvent with |event| event.command.name == "wait",
+    ]);
+    for _ in 0..3 {
+        app.continue_dialogue_and_update();
+        assert_events!(asserter, app contains [
+            PresentLineEvent (n = 0),
+            ExecuteCommandEvent (n = 0),
+        ]);
+    }
+    clock.advance(Duration::from_millis(999));
+    app.continue_dialogue_and_update();
+    assert_events!(asserter, app contains [
+        PresentLineEvent (n = 0),
+    ]);
+    clock.advance(Duration::from_millis(1));
+    app.continue_dialogue_and_update();
+    assert_events!(asserter, app contains [
+        PresentLineEvent with |event| event.line.text == "Ended wait",
+    ]);
+
+    Ok(())
+}
+
 #[test]
 fn executes_commands_and_fns() -> Result<()> {
     let mut app = App::new();
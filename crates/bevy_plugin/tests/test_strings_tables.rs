@@ -1,5 +1,6 @@
 use bevy::asset::LoadState;
 use bevy::prelude::*;
+use bevy_yarnspinner::audit::*;
 use bevy_yarnspinner::prelude::*;
 use std::fs;
 use tempfile::tempdir;
@@ -293,3 +294,41 @@ fn does_not_panic_on_missing_language_when_not_selected() {
 
     app.load_project();
 }
+
+#[test]
+fn audit_reports_untranslated_lines() {
+    let mut app = App::new();
+
+    app.setup_default_plugins().add_plugins(
+        YarnSpinnerPlugin::with_yarn_source(YarnFileSource::file("lines_with_ids.yarn"))
+            .with_localizations(Localizations {
+                base_localization: "en-US".into(),
+                translations: vec!["de-CH".into()],
+            })
+            .with_development_file_generation(DevelopmentFileGeneration::None),
+    );
+    app.world.send_event(AuditYarnProjectEvent);
+    app.load_project();
+
+    let report = loop {
+        app.update();
+        let mut reports = app
+            .world
+            .resource_mut::<Events<YarnProjectAuditReportEvent>>();
+        if let Some(report) = reports.drain().next() {
+            break report;
+        }
+    };
+
+    assert!(report.lines_missing_from_string_table.is_empty());
+    assert!(report.orphaned_string_table_entries.is_empty());
+    assert!(report.lines_missing_metadata.is_empty());
+    assert!(report.orphaned_metadata.is_empty());
+    assert_eq!(1, report.translations.len());
+    let translation = &report.translations[0];
+    assert!(translation.loaded);
+    assert_eq!(vec![LineId::from("line:10")], translation.missing_lines);
+    assert!(translation.orphaned_lines.is_empty());
+    assert!(translation.lines_in_wrong_language.is_empty());
+    assert!(!report.is_consistent());
+}
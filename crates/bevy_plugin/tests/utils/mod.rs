@@ -166,7 +166,12 @@ impl DialogueRunnerExt for DialogueRunner {
         let line_id = UnderlyingYarnLine {
             id: LineId(line_id.to_string()),
             text: String::new(),
+            raw_text: String::new(),
+            markup_error: None,
             attributes: vec![],
+            metadata: vec![],
+            node_name: String::new(),
+            source_location: None,
         };
         self.asset_providers()
             .map(|p| p.get_assets(&line_id))
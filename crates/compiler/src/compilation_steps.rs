@@ -11,6 +11,9 @@ mod parse_files;
 mod register_initial_variables;
 mod register_strings;
 mod resolve_deferred_type_diagnostic;
+mod validate_explicit_declarations;
+mod validate_hashtags;
+mod validate_review_status;
 mod validate_unique_node_names;
 
 pub(crate) use self::{
@@ -18,5 +21,6 @@ pub(crate) use self::{
     clean_up_diagnostics::*, create_declarations_for_tracking_nodes::*, early_breaks::*,
     find_tracking_nodes::*, generate_code::*, get_declarations::*, parse_files::*,
     register_initial_variables::*, register_strings::*, resolve_deferred_type_diagnostic::*,
+    validate_explicit_declarations::*, validate_hashtags::*, validate_review_status::*,
     validate_unique_node_names::*,
 };
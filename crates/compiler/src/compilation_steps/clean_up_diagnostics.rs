@@ -1,4 +1,4 @@
-use crate::listeners::DiagnosticVec;
+use crate::listeners::{sort_diagnostics_by_position, DiagnosticVec};
 use crate::prelude::*;
 use std::collections::HashSet;
 
@@ -23,6 +23,8 @@ pub(crate) fn clean_up_diagnostics(mut state: CompilationIntermediate) -> Compil
             ordered_unique_diagnostics.push(diagnostic);
         }
     }
+    // Diagnostics are collected from hash maps in some places, so sort them to report them in a deterministic order
+    sort_diagnostics_by_position(&mut ordered_unique_diagnostics);
     state.diagnostics = ordered_unique_diagnostics;
     if state.diagnostics.has_errors() {
         state.result = Some(Err(CompilerError(state.diagnostics.clone())));
@@ -7,6 +7,7 @@ pub(crate) fn break_on_job_with_only_strings(
         state.result = Some(Ok(Compilation {
             string_table: state.string_table.clone().into(),
             contains_implicit_string_tags: state.string_table.contains_implicit_string_tags(),
+            typed_metadata: state.typed_metadata.clone(),
             warnings: state.diagnostics.clone(),
             ..Default::default()
         }));
@@ -42,11 +42,15 @@ pub(crate) fn generate_code(mut state: CompilationIntermediate) -> CompilationIn
             .collect();
         Err(CompilerError(total_diagnostics))
     } else {
-        let compilations = results.into_iter().map(|r| r.unwrap());
-        Ok(Compilation::combine(
-            compilations,
-            state.string_table.clone(),
-        ))
+        let compilations: Vec<_> = results.into_iter().map(|r| r.unwrap()).collect();
+        state.file_programs = compilations
+            .iter()
+            .flat_map(|compilation| compilation.file_programs.clone())
+            .collect();
+        let mut compilation =
+            Compilation::combine(compilations.into_iter(), state.string_table.clone());
+        compilation.typed_metadata = state.typed_metadata.clone();
+        Ok(compilation)
     };
 
     state.result = Some(result);
@@ -83,8 +87,10 @@ fn generate_code_for_file<'a, 'b: 'a, 'input: 'a + 'b>(
             .map(|debug_info| (debug_info.node_name.clone(), debug_info.clone()))
             .collect();
 
+        let program = compiler_program.borrow().clone();
         Ok(Compilation {
-            program: Some(compiler_program.borrow().clone()),
+            file_programs: HashMap::from([(file.name.clone(), program.clone())]),
+            program: Some(program),
             warnings: compiler_diagnostics.borrow().clone(),
             debug_info: debug_infos,
             ..result_template
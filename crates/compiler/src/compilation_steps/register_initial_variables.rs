@@ -9,6 +9,7 @@ pub(crate) fn register_initial_variables(
     variables.extend(job_variable_declarations);
     let standard_library_declarations = get_declarations_from_library(&Library::standard_library());
     variables.extend(standard_library_declarations);
+    variables.extend(get_visit_tracking_declarations());
     let job_library_declarations = get_declarations_from_library(&state.job.library);
     variables.extend(job_library_declarations);
 
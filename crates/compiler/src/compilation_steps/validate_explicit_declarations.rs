@@ -0,0 +1,31 @@
+use crate::prelude::*;
+use crate::visitors::ImplicitVariableVisitor;
+use antlr_rust::tree::ParseTreeVisitorCompat;
+use std::collections::HashSet;
+use yarnspinner_core::types::Type;
+
+/// Turns every use of an implicitly declared variable into an error if [`Compiler::strict_variable_declarations`] is set.
+pub(crate) fn validate_explicit_declarations(
+    mut state: CompilationIntermediate,
+) -> CompilationIntermediate {
+    if !state.job.strict_variable_declarations {
+        return state;
+    }
+    let implicit_variables: HashSet<_> = state
+        .derived_variable_declarations
+        .iter()
+        .filter(|declaration| {
+            declaration.is_implicit && !matches!(declaration.r#type, Type::Function(_))
+        })
+        .map(|declaration| declaration.name.clone())
+        .collect();
+    if implicit_variables.is_empty() {
+        return state;
+    }
+    for file in &state.parsed_files {
+        let mut visitor = ImplicitVariableVisitor::new(&implicit_variables, file);
+        visitor.visit(file.tree.as_ref());
+        state.diagnostics.extend(visitor.diagnostics);
+    }
+    state
+}
@@ -0,0 +1,38 @@
+use crate::prelude::*;
+
+pub(crate) fn validate_hashtags(mut state: CompilationIntermediate) -> CompilationIntermediate {
+    let schemas = &state.job.hashtag_schemas;
+    if schemas.is_empty() {
+        return state;
+    }
+    for (line_id, string_info) in state.string_table.iter() {
+        for hashtag in &string_info.metadata {
+            let (name, value) = match hashtag.split_once(':') {
+                Some((name, value)) => (name, Some(value)),
+                None => (hashtag.as_str(), None),
+            };
+            let Some(schema) = schemas.iter().find(|schema| schema.name == name) else {
+                continue;
+            };
+            match schema.parse_value(value) {
+                Ok(value) => {
+                    state
+                        .typed_metadata
+                        .entry(line_id.clone())
+                        .or_default()
+                        .insert(name.to_owned(), value);
+                }
+                Err(problem) => {
+                    let line = string_info.line_number.saturating_sub(1);
+                    let position = Position { line, character: 0 };
+                    state.diagnostics.push(
+                        Diagnostic::from_message(format!("Invalid hashtag #{hashtag}: {problem}"))
+                            .with_file_name(&string_info.file_name)
+                            .with_range(position..position),
+                    );
+                }
+            }
+        }
+    }
+    state
+}
@@ -0,0 +1,29 @@
+use crate::prelude::*;
+
+/// Turns every line tagged with `#draft` into an error if [`Compiler::forbid_draft_lines`] is set.
+pub(crate) fn validate_review_status(
+    mut state: CompilationIntermediate,
+) -> CompilationIntermediate {
+    if !state.job.forbid_draft_lines {
+        return state;
+    }
+    let draft = ReviewStatus::Draft.hashtag();
+    let mut draft_lines: Vec<_> = state
+        .string_table
+        .iter()
+        .filter(|(_, string_info)| string_info.metadata.iter().any(|tag| tag == draft))
+        .collect();
+    draft_lines.sort_by_key(|(_, string_info)| (&string_info.file_name, string_info.line_number));
+    for (line_id, string_info) in draft_lines {
+        let line = string_info.line_number.saturating_sub(1);
+        let position = Position { line, character: 0 };
+        state.diagnostics.push(
+            Diagnostic::from_message(format!(
+                "Line {line_id} is tagged #{draft}, but draft lines are forbidden in this build"
+            ))
+            .with_file_name(&string_info.file_name)
+            .with_range(position..position),
+        );
+    }
+    state
+}
@@ -2,11 +2,15 @@
 //! and <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner.Compiler/CompilationJob.cs>
 
 use crate::prelude::*;
+use std::collections::HashMap;
 use std::path::Path;
 use yarnspinner_core::prelude::*;
 
 mod add_tags_to_lines;
 pub(crate) mod antlr_rust_ext;
+mod compile_node;
+mod evaluate_expression;
+mod import_line_text;
 pub(crate) mod run_compilation;
 pub(crate) mod utils;
 
@@ -43,6 +47,26 @@ pub struct Compiler {
 
     /// The declarations for variables.
     pub variable_declarations: Vec<Declaration>,
+
+    /// The schemas that line hashtags are validated against.
+    pub hashtag_schemas: Vec<HashtagSchema>,
+
+    /// The IETF BCP 47 code of the language the Yarn files are written in, e.g. `"de-DE"`. Passed on to [`Compilation::base_language`].
+    /// If [`None`], runtimes fall back to their own default, which is usually English.
+    pub base_language: Option<String>,
+
+    /// If `true`, variables must be declared with `<<declare>>` or [`Compiler::declare_variable`] before they are used.
+    /// Every use of a variable whose type would otherwise be inferred, e.g. from its first `<<set>>`, is reported as an error.
+    /// Defaults to `false`.
+    pub strict_variable_declarations: bool,
+
+    /// If set, the compiled project is additionally split into standalone programs as described by [`ProgramSplit`],
+    /// which are stored in [`Compilation::program_chunks`]. Defaults to [`None`].
+    pub program_split: Option<ProgramSplit>,
+
+    /// If `true`, every line tagged with `#draft` is reported as an error, so that release builds cannot ship unfinished text.
+    /// See [`ReviewStatus`] and [`Compilation::review_report`]. Defaults to `false`.
+    pub forbid_draft_lines: bool,
 }
 
 impl Compiler {
@@ -97,10 +121,105 @@ impl Compiler {
         self
     }
 
+    /// Registers a [`HashtagSchema`]. Lines using a hashtag with the schema's name must use a value accepted by it,
+    /// and the parsed values will be available in [`Compilation::typed_metadata`].
+    pub fn register_hashtag_schema(&mut self, schema: HashtagSchema) -> &mut Self {
+        self.hashtag_schemas.push(schema);
+        self
+    }
+
+    /// Sets the language the Yarn files are written in as an IETF BCP 47 code, e.g. `"de-DE"`. See [`Compiler::base_language`].
+    pub fn with_base_language(&mut self, base_language: impl Into<String>) -> &mut Self {
+        self.base_language = Some(base_language.into());
+        self
+    }
+
+    /// Sets whether implicitly declared variables are treated as errors. See [`Compiler::strict_variable_declarations`].
+    pub fn with_strict_variable_declarations(&mut self, strict: bool) -> &mut Self {
+        self.strict_variable_declarations = strict;
+        self
+    }
+
+    /// Sets whether lines tagged with `#draft` are treated as errors. See [`Compiler::forbid_draft_lines`].
+    pub fn with_draft_lines_forbidden(&mut self, forbidden: bool) -> &mut Self {
+        self.forbid_draft_lines = forbidden;
+        self
+    }
+
+    /// Requests the compiled project to be split into standalone programs. See [`Compiler::program_split`].
+    pub fn with_program_split(&mut self, split: ProgramSplit) -> &mut Self {
+        self.program_split = Some(split);
+        self
+    }
+
     /// Compiles the Yarn files previously added into a [`Compilation`].
     pub fn compile(&self) -> Result<Compilation> {
         run_compilation::compile(self)
     }
+
+    /// Evaluates a single Yarn expression, such as `$gold >= 10 and visited("Shop")`, without compiling any files.
+    /// The expression is written without the surrounding curly braces and must fit on a single line.
+    /// It cannot contain `}` or `>>`, not even inside a string literal.
+    ///
+    /// The expression is type-checked against the [`Compiler::variable_declarations`] and the functions found in the [`Compiler::library`]
+    /// and the standard library. Variables evaluate to the default value of their [`Declaration`], and functions are called through the library.
+    /// This is useful for editors that want to preview conditions and default values without running a dialogue.
+    ///
+    /// The files added to this compiler are not considered. If you want to use the variables declared in them,
+    /// run a compilation with [`CompilationType::DeclarationsOnly`] first and pass the resulting [`Compilation::declarations`] to [`Compiler::declare_variable`].
+    ///
+    /// Returns a [`CompilerError`] containing the diagnostics if the expression fails to parse or type-check.
+    pub fn evaluate_expression(&self, expression: &str) -> Result<YarnValue> {
+        evaluate_expression::evaluate_expression(self, expression, &HashMap::new())
+    }
+
+    /// Evaluates a single Yarn expression like [`Compiler::evaluate_expression`], but with the given values for its variables
+    /// instead of their default values. Their types are inferred from the values, so they don't need to be declared.
+    ///
+    /// This lets games reuse Yarn's expression semantics outside of a running dialogue, e.g. for quest conditions or a debug console,
+    /// by passing in the current state of a dialogue:
+    ///
+    /// ```rust
+    /// # use yarnspinner_compiler::prelude::*;
+    /// # use yarnspinner_core::prelude::*;
+    /// # use std::collections::HashMap;
+    /// # let library = Library::new();
+    /// # let variables = HashMap::from([("$gold".to_owned(), YarnValue::from(12.0))]);
+    /// // `library` would be `dialogue.library().clone()`, and
+    /// // `variables` would be `dialogue.variable_storage().variables()`
+    /// let can_afford = Compiler::new()
+    ///     .extend_library(library)
+    ///     .evaluate_expression_with_variables("$gold >= 10", &variables)
+    ///     .unwrap();
+    /// assert_eq!(can_afford, YarnValue::from(true));
+    /// ```
+    ///
+    /// Variables that are not in `variables` fall back to the [`Compiler::variable_declarations`].
+    /// Functions in the library are called as usual, so functions like `visited` that read a dialogue's variable storage see its current state.
+    pub fn evaluate_expression_with_variables(
+        &self,
+        expression: &str,
+        variables: &HashMap<String, YarnValue>,
+    ) -> Result<YarnValue> {
+        evaluate_expression::evaluate_expression(self, expression, variables)
+    }
+
+    /// Compiles the source of one or more edited nodes against an existing `project` without recompiling the rest of it, e.g. for a live editor.
+    /// The `node_source` is the text of the nodes including their headers and the `---` and `===` delimiters, as it would appear in a Yarn file.
+    /// The `file_name` should be the name of the file the nodes are in, so that implicit line IDs and diagnostics match those of a full compilation.
+    ///
+    /// The nodes are type-checked against the [`Compilation::declarations`] of the `project`, so they can use any variable declared elsewhere.
+    /// The files added to this compiler are ignored, but its library and other settings are used.
+    /// The returned [`Compilation`] only contains the edited nodes. Their [`Compilation::program`] can be spliced into a running dialogue
+    /// with the runtime's `Dialogue::replace_nodes`, and the new lines need to be added to the text provider from the [`Compilation::string_table`].
+    pub fn compile_node(
+        &self,
+        project: &Compilation,
+        file_name: impl Into<String>,
+        node_source: impl Into<String>,
+    ) -> Result<Compilation> {
+        compile_node::compile_node(self, project, file_name.into(), node_source.into())
+    }
 }
 
 /// Represents the contents of a file to compile.
@@ -170,4 +289,424 @@ a {1 + 3} cool expression
         };
         Compiler::new().add_file(file).compile().unwrap();
     }
+
+    #[test]
+    fn evaluates_constant_expression() {
+        let value = Compiler::new()
+            .evaluate_expression("(1 + 2) * 3 == 9 and \"foo\" != \"bar\"")
+            .unwrap();
+        assert_eq!(value, YarnValue::from(true));
+    }
+
+    #[test]
+    fn evaluates_variables_with_their_default_values() {
+        let value = Compiler::new()
+            .declare_variable(Declaration::new("$gold", Type::Number).with_default_value(5.0))
+            .evaluate_expression("$gold - 2")
+            .unwrap();
+        assert_eq!(value, YarnValue::from(3.0));
+    }
+
+    #[test]
+    fn evaluates_library_functions() {
+        let mut library = Library::new();
+        library.add_function("double", |x: f32| x * 2.0);
+        let value = Compiler::new()
+            .extend_library(library)
+            .evaluate_expression("double(21)")
+            .unwrap();
+        assert_eq!(value, YarnValue::from(42.0));
+    }
+
+    #[test]
+    fn reports_type_errors_when_evaluating_expression() {
+        let result = Compiler::new()
+            .declare_variable(Declaration::new("$gold", Type::Number).with_default_value(5.0))
+            .evaluate_expression("$gold + true");
+        let CompilerError(diagnostics) = result.unwrap_err();
+        assert!(diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.message
+                == "All terms of + must be the same, not Number, Bool"));
+    }
+
+    #[test]
+    fn reports_undeclared_variables_when_evaluating_expression() {
+        let result = Compiler::new().evaluate_expression("$unknown");
+        let CompilerError(diagnostics) = result.unwrap_err();
+        assert!(diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.message.contains("$unknown")));
+    }
+
+    #[test]
+    fn rejects_expressions_that_would_end_the_inline_expression() {
+        for expression in [
+            "1}\n<<set $gold to 5>>\n{1",
+            "1 >> 2",
+            "1\n+ 1",
+            r#""a" + "b}"#,
+            r#""a\"}"#,
+        ] {
+            let CompilerError(diagnostics) =
+                Compiler::new().evaluate_expression(expression).unwrap_err();
+            assert_eq!(diagnostics.len(), 1);
+            assert!(
+                diagnostics[0]
+                    .message
+                    .starts_with("Expressions cannot contain"),
+                "{}",
+                diagnostics[0].message
+            );
+        }
+    }
+
+    #[test]
+    fn evaluates_string_literals_containing_forbidden_text() {
+        for text in ["a}b", "x >> y"] {
+            let value = Compiler::new()
+                .evaluate_expression(&format!("{text:?}"))
+                .unwrap();
+            assert_eq!(value, YarnValue::from(text));
+        }
+    }
+
+    #[test]
+    fn unescapes_string_literals_when_evaluating_expression() {
+        let value = Compiler::new()
+            .evaluate_expression(r#""say \"hi\" \\o/""#)
+            .unwrap();
+        assert_eq!(value, YarnValue::from(r#"say "hi" \o/"#));
+    }
+
+    #[test]
+    fn retains_program_per_file() {
+        let compilation = Compiler::new()
+            .add_file(File {
+                file_name: "first.yarn".to_string(),
+                source: "title: A\n---\nfoo\n===\ntitle: B\n---\nbar\n===".to_string(),
+            })
+            .add_file(File {
+                file_name: "second.yarn".to_string(),
+                source: "title: C\n---\nbaz\n===".to_string(),
+            })
+            .compile()
+            .unwrap();
+
+        assert_eq!(compilation.file_programs.len(), 2);
+        assert_eq!(compilation.file_name_of_node("A"), Some("first.yarn"));
+        assert_eq!(compilation.file_name_of_node("C"), Some("second.yarn"));
+        assert_eq!(compilation.file_name_of_node("D"), None);
+
+        let mut first_nodes: Vec<_> = compilation.node_names_in_file("first.yarn").collect();
+        first_nodes.sort();
+        assert_eq!(first_nodes, vec!["A", "B"]);
+    }
+
+    #[test]
+    fn splits_program_into_chunks() {
+        let compilation = Compiler::new()
+            .add_file(File {
+                file_name: "first.yarn".to_string(),
+                source: "title: A\n---\n<<set $gold to 5>>\n===\ntitle: B\n---\nbar\n==="
+                    .to_string(),
+            })
+            .add_file(File {
+                file_name: "second.yarn".to_string(),
+                source: "title: C\n---\nbaz\n===".to_string(),
+            })
+            .with_program_split(ProgramSplit::PerNode)
+            .compile()
+            .unwrap();
+
+        let program_chunks = compilation.program_chunks.unwrap();
+        assert_eq!(program_chunks.chunks.len(), 3);
+        assert_eq!(program_chunks.chunks["A"].nodes.len(), 1);
+        assert_eq!(program_chunks.manifest.chunk_of_node("B"), Some("B"));
+        assert!(program_chunks.manifest.initial_values.contains_key("$gold"));
+        assert!(program_chunks.manifest.base_program().nodes.is_empty());
+
+        let per_file = compilation.split_program(ProgramSplit::PerFile).unwrap();
+        assert_eq!(per_file.chunks.len(), 2);
+        assert_eq!(per_file.manifest.chunk_of_node("B"), Some("first.yarn"));
+        let mut first_nodes: Vec<_> = per_file
+            .manifest
+            .node_names_in_chunk("first.yarn")
+            .collect();
+        first_nodes.sort();
+        assert_eq!(first_nodes, vec!["A", "B"]);
+    }
+
+    #[test]
+    fn validates_hashtags_against_schemas() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test\n---\nfoo #mood:angry #volume:0.5 #line:foo\nbar #mood:sad\n==="
+                .to_string(),
+        };
+        let mut compiler = Compiler::new();
+        compiler
+            .add_file(file)
+            .register_hashtag_schema(HashtagSchema::one_of("mood", ["angry", "happy"]))
+            .register_hashtag_schema(HashtagSchema::new("volume", HashtagValueType::Number));
+
+        let error = compiler.compile().unwrap_err();
+        assert_eq!(error.0.len(), 1);
+        assert!(error.0[0].message.contains("#mood:sad"));
+    }
+
+    #[test]
+    fn exposes_typed_metadata() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test\n---\nfoo #mood:angry #volume:0.5 #line:foo\n===".to_string(),
+        };
+        let compilation = Compiler::new()
+            .add_file(file)
+            .register_hashtag_schema(HashtagSchema::one_of("mood", ["angry", "happy"]))
+            .register_hashtag_schema(HashtagSchema::new("volume", HashtagValueType::Number))
+            .compile()
+            .unwrap();
+
+        let metadata = &compilation.typed_metadata[&LineId::from("line:foo")];
+        assert_eq!(metadata["mood"], YarnValue::from("angry"));
+        assert_eq!(metadata["volume"], YarnValue::from(0.5));
+    }
+
+    #[test]
+    fn reports_review_status() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test\n---\nfoo #draft #line:foo\nbar #approved #needs_vo #line:bar\nbaz #approved #line:baz\nqux #line:qux\n==="
+                .to_string(),
+        };
+        let mut compiler = Compiler::new();
+        compiler.add_file(file);
+        let report = compiler.compile().unwrap().review_report();
+
+        assert_eq!(report.count(ReviewStatus::Draft), 1);
+        assert_eq!(report.count(ReviewStatus::Approved), 2);
+        assert_eq!(report.count(ReviewStatus::NeedsVo), 1);
+        assert_eq!(
+            report.lines_by_status[&ReviewStatus::Approved],
+            vec![LineId::from("line:bar"), LineId::from("line:baz")]
+        );
+        assert_eq!(report.lines_without_status, vec![LineId::from("line:qux")]);
+
+        let error = compiler
+            .with_draft_lines_forbidden(true)
+            .compile()
+            .unwrap_err();
+        assert_eq!(error.0.len(), 1);
+        assert!(error.0[0].message.contains("line:foo"));
+    }
+
+    #[test]
+    fn calculates_stats() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: test
+---
+<<set $gold to 1>>
+Hello there, traveler!
+You have {$gold} gold.
+-> Buy something
+-> Leave
+==="
+            .to_string(),
+        };
+        let compilation = Compiler::new().add_file(file).compile().unwrap();
+        let stats = compilation.stats();
+
+        assert_eq!(stats.node_count(), 1);
+        let node = stats.nodes["test"];
+        assert_eq!(node.line_count, 2);
+        assert_eq!(node.option_count, 2);
+        assert_eq!(node.word_count, 3 + 4 + 2 + 1);
+        let gold = stats.variables["$gold"];
+        assert_eq!(gold.read_count, 1);
+        assert_eq!(gold.write_count, 1);
+    }
+
+    #[test]
+    fn creates_manifest() {
+        let file = File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start
+tags: intro
+---
+<<declare $gold = 5>>
+Hello there! #mood:happy
+<<jump Shop>>
+===
+title: Shop
+---
+You have {double($gold)} gold.
+==="
+            .to_string(),
+        };
+        let mut library = Library::new();
+        library.add_function("double", |value: f32| value * 2.0);
+        let compilation = Compiler::new()
+            .add_file(file)
+            .extend_library(library.clone())
+            .compile()
+            .unwrap();
+        let manifest = compilation.manifest(&library);
+
+        let node_names: Vec<_> = manifest.nodes.iter().map(|node| &node.name).collect();
+        assert_eq!(node_names, vec!["Shop", "Start"]);
+        let start = &manifest.nodes[1];
+        assert_eq!(start.file_name.as_deref(), Some("test.yarn"));
+        assert_eq!(start.tags, vec!["intro".to_owned()]);
+        assert_eq!(start.outgoing_edges, vec!["Shop".to_owned()]);
+        assert!(manifest.nodes[0].outgoing_edges.is_empty());
+
+        assert_eq!(manifest.lines.len(), 2);
+        let greeting = manifest
+            .lines
+            .iter()
+            .find(|line| line.node_name == "Start")
+            .unwrap();
+        assert_eq!(greeting.text, "Hello there!");
+        assert_eq!(greeting.metadata, vec!["mood:happy".to_owned()]);
+
+        assert_eq!(manifest.variables.len(), 1);
+        let gold = &manifest.variables[0];
+        assert_eq!(gold.name, "$gold");
+        assert_eq!(gold.type_name, "Number");
+        assert_eq!(gold.default_value, Some(5.0.into()));
+
+        let double = manifest
+            .functions
+            .iter()
+            .find(|function| function.name == "double")
+            .unwrap();
+        assert_eq!(double.parameter_types, vec!["Number".to_owned()]);
+        assert_eq!(double.return_type, "Number");
+        assert!(manifest
+            .functions
+            .iter()
+            .any(|function| function.name == "length"));
+    }
+
+    #[test]
+    fn imports_line_text() {
+        let source = "title: Start
+---
+Hello, {$name}! #line:greeting
+    -> [b]Buy[/b] something <<if $gold > 0>> #line:buy
+Goodbye. #line:bye
+===
+";
+        let edits = LineTextEdits::from_iter([
+            ("line:greeting".into(), "Welcome back, {0}!".to_owned()),
+            ("line:buy".into(), "[b]Purchase[/b] something".to_owned()),
+            ("line:bye".into(), "Goodbye.".to_owned()),
+            ("line:unknown".into(), "Not in this file".to_owned()),
+        ]);
+        let import = Compiler::import_line_text(source, &edits).unwrap();
+
+        assert_eq!(
+            import.source,
+            "title: Start
+---
+Welcome back, {$name}! #line:greeting
+    -> [b]Purchase[/b] something <<if $gold > 0>> #line:buy
+Goodbye. #line:bye
+===
+"
+        );
+        assert_eq!(import.changes.len(), 2);
+        assert_eq!(import.changes[0].line_number, 3);
+        assert_eq!(import.changes[0].old_text, "Hello, {0}!");
+        assert_eq!(
+            import.diff(),
+            "@@ line 3 (line:greeting) @@
+-Hello, {$name}! #line:greeting
++Welcome back, {$name}! #line:greeting
+@@ line 4 (line:buy) @@
+-    -> [b]Buy[/b] something <<if $gold > 0>> #line:buy
++    -> [b]Purchase[/b] something <<if $gold > 0>> #line:buy
+"
+        );
+    }
+
+    #[test]
+    fn rejects_line_text_with_unknown_expression() {
+        let source = "title: Start
+---
+Hello, {$name}! #line:greeting
+===
+";
+        let edits = LineTextEdits::from_iter([("line:greeting".into(), "Hello, {1}!".to_owned())]);
+        let result = Compiler::import_line_text(source, &edits);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn reads_line_text_edits_from_csv() {
+        let csv =
+            "language,id,text,file\nen,line:1,\"Hello, world\",a.yarn\nen,line:2,Bye,a.yarn\n";
+        let edits = LineTextEdits::from_csv(csv.as_bytes()).unwrap();
+        assert_eq!(edits.0.len(), 2);
+        assert_eq!(edits.0[&"line:1".into()], "Hello, world");
+        assert_eq!(edits.0[&"line:2".into()], "Bye");
+    }
+
+    #[test]
+    fn sorts_string_table_by_position() {
+        let files = ["b", "a"].map(|name| File {
+            file_name: format!("{name}.yarn"),
+            source: format!(
+                "title: Start_{name}
+---
+First #line:{name}3
+Second #line:{name}1
+-> Third #line:{name}2
+===
+"
+            ),
+        });
+        let compilation = Compiler::new()
+            .add_file(files[0].clone())
+            .add_file(files[1].clone())
+            .compile()
+            .unwrap();
+
+        let ids: Vec<_> = compilation
+            .sorted_string_table()
+            .map(|(id, _)| id.0.as_str())
+            .collect();
+        assert_eq!(
+            ids,
+            vec!["line:a3", "line:a1", "line:a2", "line:b3", "line:b1", "line:b2"]
+        );
+    }
+
+    #[test]
+    fn sorts_diagnostics_by_position() {
+        let diagnostic = |file_name: Option<&str>, line: usize| Diagnostic {
+            file_name: file_name.map(ToOwned::to_owned),
+            range: Some(Position { line, character: 0 }..Position { line, character: 1 }),
+            ..Diagnostic::from_message(format!("{file_name:?}:{line}"))
+        };
+        let mut diagnostics = vec![
+            diagnostic(Some("b.yarn"), 1),
+            diagnostic(Some("a.yarn"), 7),
+            diagnostic(None, 3),
+            diagnostic(Some("a.yarn"), 2),
+        ];
+        crate::listeners::sort_diagnostics_by_position(&mut diagnostics);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "None:3",
+                "Some(\"a.yarn\"):2",
+                "Some(\"a.yarn\"):7",
+                "Some(\"b.yarn\"):1"
+            ]
+        );
+    }
 }
@@ -33,7 +33,7 @@ impl Compiler {
         existing_line_tags: Vec<LineId>,
     ) -> crate::Result<Option<String>> {
         let contents = contents.into();
-        let chars: Vec<_> = contents.chars().map(|c| c as u32).collect();
+        let chars = source_to_chars(&contents);
         // First, get the parse tree for this source code.
         let file = File {
             file_name: "<input>".to_string(),
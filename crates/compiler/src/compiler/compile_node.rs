@@ -0,0 +1,39 @@
+//! Contains the implementation of [`Compiler::compile_node`].
+
+use crate::prelude::*;
+use crate::Result;
+
+pub(crate) fn compile_node(
+    compiler: &Compiler,
+    project: &Compilation,
+    file_name: String,
+    node_source: String,
+) -> Result<Compilation> {
+    let edited_node_names: Vec<_> = node_source
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("title:"))
+        .map(str::trim)
+        .collect();
+    // Declarations made by the edited nodes themselves will be found again while compiling them
+    let project_declarations = project.declarations.iter().filter(|declaration| {
+        let is_declared_by_edited_node = declaration
+            .source_node_name
+            .as_deref()
+            .is_some_and(|node_name| edited_node_names.contains(&node_name));
+        let is_already_known = compiler
+            .variable_declarations
+            .iter()
+            .any(|known| known.name == declaration.name);
+        !is_declared_by_edited_node && !is_already_known
+    });
+
+    let mut node_compiler = compiler.clone();
+    node_compiler
+        .variable_declarations
+        .extend(project_declarations.cloned());
+    node_compiler.files = vec![File {
+        file_name,
+        source: node_source,
+    }];
+    node_compiler.compile()
+}
@@ -0,0 +1,111 @@
+//! Contains the implementation of [`Compiler::evaluate_expression`].
+
+use crate::prelude::*;
+use crate::visitors::{ExpressionEvaluationVisitor, TypeCheckVisitor};
+use crate::Result;
+use antlr_rust::tree::ParseTreeVisitorCompat;
+use std::collections::HashMap;
+use yarnspinner_core::prelude::*;
+
+const EXPRESSION_FILE_NAME: &str = "<expression>";
+
+pub(crate) fn evaluate_expression(
+    compiler: &Compiler,
+    expression: &str,
+    variables: &HashMap<String, YarnValue>,
+) -> Result<YarnValue> {
+    if let Some(forbidden) = find_forbidden_text(expression) {
+        let message = format!("Expressions cannot contain {forbidden}: {expression}");
+        return Err(CompilerError(vec![
+            Diagnostic::from_message(message).with_file_name(EXPRESSION_FILE_NAME)
+        ]));
+    }
+
+    // Wrapping the expression in an inline expression of a line lets us reuse the regular parser
+    // instead of needing a dedicated entry rule for lone expressions.
+    let file = File {
+        file_name: EXPRESSION_FILE_NAME.to_owned(),
+        source: format!("title: Expression\n---\n{{{expression}}}\n===\n"),
+    };
+    let chars = source_to_chars(&file.source);
+    let mut diagnostics = Vec::new();
+    let parsed_file = parse_syntax_tree(&file, &chars, &mut diagnostics);
+    if diagnostics.has_errors() {
+        return Err(CompilerError(diagnostics));
+    }
+
+    let mut library = Library::standard_library();
+    library.extend(compiler.library.clone());
+    // The current values of variables take precedence over their declared default values
+    let variable_declarations = variables.iter().map(|(name, value)| {
+        Declaration::new(name.clone(), value).with_default_value(value.clone())
+    });
+    let declarations: Vec<_> = compiler
+        .variable_declarations
+        .iter()
+        .filter(|declaration| !variables.contains_key(&declaration.name))
+        .cloned()
+        .chain(variable_declarations)
+        .chain(get_declarations_from_library(&library))
+        .collect();
+
+    let mut type_check_visitor = TypeCheckVisitor::new(declarations.clone(), parsed_file.clone());
+    type_check_visitor.visit(parsed_file.tree.as_ref());
+    diagnostics.extend(type_check_visitor.diagnostics);
+    // There is no later pass that could resolve these, so they are errors right away.
+    diagnostics.extend(
+        type_check_visitor
+            .deferred_types
+            .into_iter()
+            .map(|deferred_type| deferred_type.diagnostic),
+    );
+    if diagnostics.has_errors() {
+        return Err(CompilerError(diagnostics));
+    }
+
+    let mut evaluation_visitor = ExpressionEvaluationVisitor::new(
+        &library,
+        &declarations,
+        &type_check_visitor.known_types,
+        parsed_file.clone(),
+    );
+    evaluation_visitor.visit(parsed_file.tree.as_ref());
+    diagnostics.extend(evaluation_visitor.diagnostics);
+    if diagnostics.has_errors() {
+        return Err(CompilerError(diagnostics));
+    }
+
+    let mut values = evaluation_visitor.values;
+    if values.len() != 1 {
+        let message = format!(
+            "Expected exactly one expression, but found {}: {expression}",
+            values.len()
+        );
+        diagnostics.push(Diagnostic::from_message(message).with_file_name(EXPRESSION_FILE_NAME));
+        return Err(CompilerError(diagnostics));
+    }
+    Ok(values.remove(0))
+}
+
+/// Finds text that would end the inline expression wrapping `expression` in [`evaluate_expression`], letting it smuggle in arbitrary Yarn.
+/// `}` and `>>` are fine inside string literals, which cannot contain line breaks, while an unterminated string literal
+/// would let the lexer read its contents as regular tokens.
+fn find_forbidden_text(expression: &str) -> Option<&'static str> {
+    if expression.contains(['\n', '\r']) {
+        return Some("line breaks");
+    }
+    let mut is_in_string = false;
+    let mut chars = expression.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '"' => is_in_string = !is_in_string,
+            '\\' if is_in_string => {
+                chars.next();
+            }
+            '}' if !is_in_string => return Some("\"}\""),
+            '>' if !is_in_string && chars.peek() == Some(&'>') => return Some("\">>\""),
+            _ => {}
+        }
+    }
+    is_in_string.then_some("unterminated string literals")
+}
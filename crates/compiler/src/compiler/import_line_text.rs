@@ -0,0 +1,103 @@
+//! Contains the implementation of [`Compiler::import_line_text`].
+
+use crate::listeners::{DiagnosticVec, LineTextEditListener};
+use crate::prelude::generated::yarnspinnerparser::YarnSpinnerParserTreeWalker;
+use crate::prelude::*;
+use std::fs;
+use std::path::Path;
+
+impl Compiler {
+    /// Given Yarn source code, replaces the text of all lines that have an entry in `edits` with the new text.
+    ///
+    /// Only the text itself is replaced. The inline expressions of the line are reinserted where the new text refers to them by
+    /// their index, e.g. `{0}`, and the hashtags, conditions and indentation of the line as well as all other source code stay untouched.
+    /// Lines are matched by their explicit `#line:` tag, so lines without one are never edited. Edits for line IDs that
+    /// do not appear in the source code are ignored, which allows applying the same edits to every file of a project.
+    ///
+    /// The source code is not written anywhere, so this can be used as a dry run by inspecting [`LineTextImport::diff`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the source code cannot be parsed, if a new text contains a line break,
+    /// or if it refers to an inline expression that the line does not have.
+    pub fn import_line_text(
+        contents: impl Into<String>,
+        edits: &LineTextEdits,
+    ) -> crate::Result<LineTextImport> {
+        let file = File {
+            file_name: "<input>".to_string(),
+            source: contents.into(),
+        };
+        import_line_text(file, edits)
+    }
+
+    /// Applies [`Compiler::import_line_text`] to the Yarn file at the given path.
+    ///
+    /// If `dry_run` is `true`, the file is left untouched and the returned [`LineTextImport`] only describes what would change.
+    /// Otherwise, the file is overwritten if any line changed.
+    pub fn import_line_text_into_file(
+        file_path: impl AsRef<Path>,
+        edits: &LineTextEdits,
+        dry_run: bool,
+    ) -> Result<LineTextImport, LineTextImportError> {
+        let file_path = file_path.as_ref();
+        let file = File {
+            file_name: file_path.to_string_lossy().to_string(),
+            source: fs::read_to_string(file_path)?,
+        };
+        let import = import_line_text(file, edits)?;
+        if !dry_run && import.has_changes() {
+            fs::write(file_path, &import.source)?;
+        }
+        Ok(import)
+    }
+}
+
+fn import_line_text(file: File, edits: &LineTextEdits) -> crate::Result<LineTextImport> {
+    let chars = source_to_chars(&file.source);
+    let mut diagnostics = Vec::new();
+    let parsed_file = parse_syntax_tree(&file, &chars, &mut diagnostics);
+    if diagnostics.has_errors() {
+        // We aren't confident in our ability to correctly replace text in a file we cannot parse.
+        return Err(CompilerError(diagnostics));
+    }
+    let tree = parsed_file.tree.clone();
+
+    let listener = Box::new(LineTextEditListener::new(edits.0.clone(), parsed_file));
+    let replacements = listener.replacements.clone();
+    let listener_diagnostics = listener.diagnostics.clone();
+    YarnSpinnerParserTreeWalker::walk(listener, tree.as_ref());
+
+    let diagnostics = listener_diagnostics.take();
+    if !diagnostics.is_empty() {
+        return Err(CompilerError(diagnostics));
+    }
+
+    let mut replacements = replacements.take();
+    let mut source: Vec<_> = file.source.chars().collect();
+    // Apply the replacements back to front so that the indices of the remaining ones stay valid
+    replacements.sort_by_key(|replacement| replacement.start);
+    for replacement in replacements.iter().rev() {
+        source.splice(
+            replacement.start..replacement.stop,
+            replacement.text.chars(),
+        );
+    }
+    let source: String = source.into_iter().collect();
+
+    // Line breaks are never added or removed, so line numbers stay the same
+    let old_lines: Vec<_> = file.source.lines().collect();
+    let new_lines: Vec<_> = source.lines().collect();
+    let changes = replacements
+        .into_iter()
+        .map(|replacement| {
+            let index = replacement.change.line_number - 1;
+            LineTextChange {
+                old_source_line: old_lines[index].to_owned(),
+                new_source_line: new_lines[index].to_owned(),
+                ..replacement.change
+            }
+        })
+        .collect();
+    Ok(LineTextImport { source, changes })
+}
@@ -12,10 +12,13 @@ pub(crate) fn compile(compiler: &Compiler) -> Result<Compilation> {
         &register_initial_variables,
         &parse_files,
         &register_strings,
+        &validate_hashtags,
+        &validate_review_status,
         &validate_unique_node_names,
         &break_on_job_with_only_strings,
         &get_declarations,
         &check_types,
+        &validate_explicit_declarations,
         &find_tracking_nodes,
         &create_declarations_for_tracking_nodes,
         &add_tracking_declarations,
@@ -28,7 +31,7 @@ pub(crate) fn compile(compiler: &Compiler) -> Result<Compilation> {
     let chars: Vec<Vec<u32>> = compiler
         .files
         .iter()
-        .map(|file| file.source.chars().map(|c| c as u32).collect())
+        .map(|file| source_to_chars(&file.source))
         .collect();
     let chars: Vec<_> = chars.iter().map(|c| c.as_slice()).collect();
     let initial = CompilationIntermediate::from_job(compiler, chars);
@@ -42,7 +45,16 @@ pub(crate) fn compile(compiler: &Compiler) -> Result<Compilation> {
     // Cleaning up diagnostics doesn't change the state but makes sure
     // that diagnostics are unique, there are no errors in the warnings, etc.
     // So we execute it even if we've had early breaks.
-    let result = clean_up_diagnostics(intermediate).result.unwrap();
+    let mut result = clean_up_diagnostics(intermediate).result.unwrap();
+    if let Ok(compilation) = result.as_mut() {
+        compilation
+            .base_language
+            .clone_from(&compiler.base_language);
+        compilation.content_hash = compilation.program.as_ref().map(Program::content_hash);
+        compilation.program_chunks = compiler
+            .program_split
+            .and_then(|split| compilation.split_program(split));
+    }
     result
 }
 
@@ -60,8 +72,12 @@ pub(crate) struct CompilationIntermediate<'input> {
     pub(crate) parsed_files: Vec<FileParseResult<'input>>,
     pub(crate) tracking_nodes: HashSet<String>,
     pub(crate) string_table: StringTableManager,
+    /// The [`Program`] generated for each file, keyed by file name. Only filled if code generation succeeded.
+    pub(crate) file_programs: HashMap<String, Program>,
     pub(crate) diagnostics: Vec<Diagnostic>,
     pub(crate) file_tags: HashMap<String, Vec<String>>,
+    /// The values of all line hashtags that matched a [`HashtagSchema`] registered in the job.
+    pub(crate) typed_metadata: HashMap<LineId, HashMap<String, YarnValue>>,
     pub(crate) known_types: KnownTypes,
     pub(crate) early_break: bool,
 }
@@ -78,8 +94,10 @@ impl<'input> CompilationIntermediate<'input> {
             parsed_files: Default::default(),
             tracking_nodes: Default::default(),
             string_table: Default::default(),
+            file_programs: Default::default(),
             diagnostics: Default::default(),
             file_tags: Default::default(),
+            typed_metadata: Default::default(),
             known_types: Default::default(),
             early_break: Default::default(),
         }
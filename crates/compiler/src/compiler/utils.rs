@@ -77,6 +77,49 @@ pub(crate) fn get_line_id_for_node_name(name: &str) -> LineId {
     format!("line:{name}").into()
 }
 
+/// Stands in for the `{` of a substitution provider reference like `{@player_name}` while lexing. From the Unicode private use area.
+const SUBSTITUTION_PROVIDER_START: char = '\u{E000}';
+/// Stands in for the `}` of a substitution provider reference like `{@player_name}` while lexing. From the Unicode private use area.
+const SUBSTITUTION_PROVIDER_END: char = '\u{E001}';
+
+/// Converts Yarn source code into the code points read by the lexer.
+///
+/// References to substitution providers, e.g. `{@player_name}`, are not expressions but are resolved by the runtime when a line is delivered.
+/// Since the lexer would treat their braces as the start of an inline expression, they are replaced by private use characters here
+/// and restored by [`unmask_substitution_providers`]. This keeps the number of chars the same, so the positions of diagnostics are unaffected.
+pub(crate) fn source_to_chars(source: &str) -> Vec<u32> {
+    let mut chars: Vec<char> = source.chars().collect();
+    let mut index = 0;
+    while index < chars.len() {
+        let is_reference_start = chars[index] == '{'
+            && chars.get(index + 1) == Some(&'@')
+            && (index == 0 || chars[index - 1] != '\\');
+        if !is_reference_start {
+            index += 1;
+            continue;
+        }
+        let name_len = chars[index + 2..]
+            .iter()
+            .take_while(|c| c.is_alphanumeric() || **c == '_')
+            .count();
+        let end = index + 2 + name_len;
+        if name_len > 0 && chars.get(end) == Some(&'}') {
+            chars[index] = SUBSTITUTION_PROVIDER_START;
+            chars[end] = SUBSTITUTION_PROVIDER_END;
+            index = end + 1;
+        } else {
+            index += 1;
+        }
+    }
+    chars.into_iter().map(|c| c as u32).collect()
+}
+
+/// Restores the references to substitution providers masked by [`source_to_chars`].
+pub(crate) fn unmask_substitution_providers(text: &str) -> String {
+    text.replace(SUBSTITUTION_PROVIDER_START, "{")
+        .replace(SUBSTITUTION_PROVIDER_END, "}")
+}
+
 /// Gets the text of the documentation comments that either immediately
 /// precede `context`, or are on the same line as `context`.
 ///
@@ -213,16 +256,40 @@ pub(crate) fn get_declarations_from_library(library: &Library) -> Vec<Declaratio
         // Operators are type checked by visitors instead
         .filter(|(name, _function)| !operators.contains(*name))
         .map(|(name, function)| {
+            let mut function_type = function_type_of(name, function);
+            for overload in function.overloads().into_iter().skip(1) {
+                function_type.add_overload(function_type_of(name, overload));
+            }
+            Declaration::new(name, function_type).with_source_file_name(DeclarationSource::External)
+        })
+        .collect()
+}
+
+/// Describes `function` without considering its overloads.
+fn function_type_of(name: &str, function: &dyn UntypedYarnFn) -> FunctionType {
+    let signature = FunctionSignature::from_function(name, function);
+    let mut function_type = FunctionType::default();
+    if signature.is_variadic {
+        // Variadic parameters accept values of any type
+        function_type.set_variadic_parameter_type(Type::Any);
+    }
+    function_type.set_optional_parameter_count(signature.optional_parameter_count);
+    function_type.parameters = signature.parameter_types.into_iter().map(Some).collect();
+    function_type.set_return_type(signature.return_type);
+    function_type
+}
+
+/// Declares `visited` and `visited_count`, which return whether and how often a node was visited.
+/// They are not part of [`Library::standard_library`] because the runtime implements them by reading the tracking variables
+/// generated in `add_tracking_declarations` from its variable storage, but their signatures are known to every compilation.
+pub(crate) fn get_visit_tracking_declarations() -> Vec<Declaration> {
+    [("visited", Type::Boolean), ("visited_count", Type::Number)]
+        .into_iter()
+        .map(|(name, return_type)| {
             let mut function_type = FunctionType::default();
-            let parameters = function
-                .parameter_types()
-                .into_iter()
-                .map(|t| Type::try_from(t).unwrap())
-                .map(Some)
-                .collect();
-            function_type.parameters = parameters;
-            let return_type = Type::try_from(function.return_type()).unwrap();
-            function_type.set_return_type(return_type);
+            function_type
+                .add_parameter(Type::String)
+                .set_return_type(return_type);
             Declaration::new(name, function_type).with_source_file_name(DeclarationSource::External)
         })
         .collect()
@@ -1,6 +1,9 @@
 mod compiler_listener;
 mod error_listener;
+mod line_text_edit_listener;
 mod untagged_line_listener;
 
 pub use self::error_listener::{Diagnostic, DiagnosticSeverity, DiagnosticVec};
-pub(crate) use self::{compiler_listener::*, error_listener::*, untagged_line_listener::*};
+pub(crate) use self::{
+    compiler_listener::*, error_listener::*, line_text_edit_listener::*, untagged_line_listener::*,
+};
@@ -179,6 +179,22 @@ impl DiagnosticVec for Vec<Diagnostic> {
     }
 }
 
+/// Sorts the [`Diagnostic`]s by [`Diagnostic::file_name`] and then by the start of [`Diagnostic::range`].
+/// Diagnostics without a file name or range come first. The sort is stable, so diagnostics at the same position keep their order.
+pub(crate) fn sort_diagnostics_by_position(diagnostics: &mut [Diagnostic]) {
+    diagnostics.sort_by(|lhs, rhs| {
+        let start = |diagnostic: &Diagnostic| {
+            diagnostic
+                .range
+                .as_ref()
+                .map(|range| (range.start.line, range.start.character))
+        };
+        lhs.file_name
+            .cmp(&rhs.file_name)
+            .then_with(|| start(lhs).cmp(&start(rhs)))
+    });
+}
+
 /// The severity of the issue.
 ///
 /// ## Implementation notes
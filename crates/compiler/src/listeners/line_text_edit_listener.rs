@@ -0,0 +1,183 @@
+//! Used by [`Compiler::import_line_text`] to find the text of the lines that should be replaced.
+
+use crate::parser::generated::yarnspinnerparser::Line_statementContext;
+use crate::prelude::generated::yarnspinnerparser::{
+    Line_formatted_textContextAttrs, Line_statementContextAttrs, YarnSpinnerParserContextType,
+};
+use crate::prelude::generated::yarnspinnerparserlistener::YarnSpinnerParserListener;
+use crate::prelude::*;
+use crate::visitors::generate_formatted_text;
+use antlr_rust::parser_rule_context::ParserRuleContext;
+use antlr_rust::token::Token;
+use antlr_rust::tree::ParseTreeListener;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A replacement of the text of a single line, in chars of the original source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LineTextReplacement {
+    /// The index of the first char to replace.
+    pub(crate) start: usize,
+    /// The index one past the last char to replace.
+    pub(crate) stop: usize,
+    /// The text that replaces the chars in `start..stop`, with the inline expressions of the original line reinserted.
+    pub(crate) text: String,
+    pub(crate) change: LineTextChange,
+}
+
+pub(crate) struct LineTextEditListener<'input> {
+    edits: HashMap<LineId, String>,
+    file: FileParseResult<'input>,
+    chars: Vec<char>,
+    pub(crate) replacements: Rc<RefCell<Vec<LineTextReplacement>>>,
+    pub(crate) diagnostics: Rc<RefCell<Vec<Diagnostic>>>,
+}
+
+impl<'input> LineTextEditListener<'input> {
+    pub(crate) fn new(edits: HashMap<LineId, String>, file: FileParseResult<'input>) -> Self {
+        let chars = unmask_substitution_providers(&file.tokens().get_all_text())
+            .chars()
+            .collect();
+        Self {
+            edits,
+            file,
+            chars,
+            replacements: Default::default(),
+            diagnostics: Default::default(),
+        }
+    }
+
+    fn text_between(&self, start: isize, stop: isize) -> String {
+        self.chars[start as usize..stop as usize].iter().collect()
+    }
+}
+
+impl<'input> ParseTreeListener<'input, YarnSpinnerParserContextType>
+    for LineTextEditListener<'input>
+{
+}
+
+impl<'input> YarnSpinnerParserListener<'input> for LineTextEditListener<'input> {
+    fn exit_line_statement(&mut self, ctx: &Line_statementContext<'input>) {
+        let Some(line_id_tag) = get_line_id_tag(&ctx.hashtag_all()) else {
+            // Lines without an explicit ID cannot be edited, as their implicit ID is not stable
+            return;
+        };
+        let line_id = LineId(line_id_tag.text.as_ref().unwrap().get_text().to_owned());
+        let Some(new_text) = self.edits.get(&line_id) else {
+            return;
+        };
+        let line_formatted_text = ctx.line_formatted_text().unwrap();
+
+        // The inner text of each inline expression, including the whitespace around it, but without the braces
+        let expressions: Vec<_> = line_formatted_text
+            .EXPRESSION_START_all()
+            .iter()
+            .zip(line_formatted_text.EXPRESSION_END_all())
+            .map(|(start, end)| {
+                self.text_between(start.symbol.get_stop() + 1, end.symbol.get_start())
+            })
+            .collect();
+
+        let diagnostic = |message: String| {
+            Diagnostic::from_message(message)
+                .with_file_name(&self.file.name)
+                .with_parser_context(ctx, self.file.tokens())
+        };
+        if new_text.contains(['\n', '\r']) {
+            self.diagnostics.borrow_mut().push(diagnostic(format!(
+                "The new text of line {line_id} contains a line break"
+            )));
+            return;
+        }
+        let text = match reinsert_expressions(new_text, &expressions) {
+            Ok(text) => text,
+            Err(index) => {
+                self.diagnostics.borrow_mut().push(diagnostic(format!(
+                    "The new text of line {line_id} refers to expression {{{index}}}, but the line only has {} expression(s)",
+                    expressions.len()
+                )));
+                return;
+            }
+        };
+
+        // Keep the whitespace surrounding the text, e.g. the space before a hashtag
+        let start = line_formatted_text.start().get_start();
+        let stop = line_formatted_text.stop().get_stop() + 1;
+        let original = self.text_between(start, stop);
+        let leading_whitespace = original.chars().count() - original.trim_start().chars().count();
+        let trailing_whitespace = original.chars().count() - original.trim_end().chars().count();
+        let start = start as usize + leading_whitespace;
+        let stop = stop as usize - trailing_whitespace;
+
+        let old_text = generate_formatted_text(&line_formatted_text);
+        if &old_text == new_text {
+            return;
+        }
+        self.replacements.borrow_mut().push(LineTextReplacement {
+            start,
+            stop,
+            text,
+            change: LineTextChange {
+                line_id,
+                line_number: ctx.start().get_line_as_usize(),
+                old_text,
+                new_text: new_text.clone(),
+                // Filled in once all replacements have been applied
+                old_source_line: String::new(),
+                new_source_line: String::new(),
+            },
+        });
+    }
+}
+
+/// Replaces the placeholders `{0}`, `{1}`, etc. of a text from the string table with the inline expressions they stand for.
+/// Returns the index of the first placeholder that has no corresponding expression as the error.
+fn reinsert_expressions(text: &str, expressions: &[String]) -> std::result::Result<String, usize> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        result.push_str(&rest[..open]);
+        let after_open = &rest[open + 1..];
+        let placeholder = after_open
+            .find('}')
+            .and_then(|close| Some((after_open[..close].parse::<usize>().ok()?, close)));
+        match placeholder {
+            Some((index, close)) => {
+                let expression = expressions.get(index).ok_or(index)?;
+                result.push('{');
+                result.push_str(expression);
+                result.push('}');
+                rest = &after_open[close + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = after_open;
+            }
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reinserts_expressions() {
+        let expressions = vec!["$name".to_owned(), " 1 + 2 ".to_owned()];
+        let result = reinsert_expressions("Hi {0}, you have {1} coins {0}!", &expressions);
+        assert_eq!(
+            result,
+            Ok("Hi {$name}, you have { 1 + 2 } coins {$name}!".to_owned())
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_expressions() {
+        let result = reinsert_expressions("Hi {2}", &["$name".to_owned()]);
+        assert_eq!(result, Err(2));
+    }
+}
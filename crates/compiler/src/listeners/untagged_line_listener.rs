@@ -26,9 +26,7 @@ pub(crate) struct UntaggedLineListener<'input> {
 
 impl<'input> UntaggedLineListener<'input> {
     pub fn new(existing_line_tags: Vec<LineId>, file: FileParseResult<'input>) -> Self {
-        let original_source = file
-            .tokens()
-            .get_all_text()
+        let original_source = unmask_substitution_providers(&file.tokens().get_all_text())
             .lines()
             .map(|s| s.to_owned())
             .collect();
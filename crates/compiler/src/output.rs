@@ -1,15 +1,24 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner.Compiler/CompilationResult.cs>
 
 use crate::listeners::*;
-pub use crate::output::{debug_info::*, declaration::*, string_info::*};
+pub use crate::output::{
+    compilation_stats::*, debug_info::*, declaration::*, hashtag_schema::*, line_text_import::*,
+    program_chunks::*, project_manifest::*, review_report::*, string_info::*,
+};
 use crate::prelude::*;
 use std::collections::HashMap;
 use std::fmt::{Debug, Display};
 use thiserror::Error;
 use yarnspinner_core::prelude::*;
 
+mod compilation_stats;
 mod debug_info;
 mod declaration;
+mod hashtag_schema;
+mod line_text_import;
+mod program_chunks;
+mod project_manifest;
+mod review_report;
 mod string_info;
 
 /// The result of a compilation.
@@ -86,6 +95,57 @@ pub struct Compilation {
 
     /// The collection of [`DebugInfo`] objects for each node in [`Program`].
     pub debug_info: HashMap<String, DebugInfo>,
+
+    /// The [`Program`] generated for each file, keyed by file name.
+    /// Combining all of these results in [`Compilation::program`], except for the [`Program::initial_values`], which are only set on the combined program.
+    ///
+    /// This allows reloading only the nodes of a single file when it changes.
+    /// Use [`Compilation::file_name_of_node`] and [`Compilation::node_names_in_file`] to find out which nodes came from which file.
+    ///
+    /// This value will be empty if the [`Compiler`] object's
+    /// [`CompilationType`] value was not [`CompilationType::FullCompilation`].
+    ///
+    /// ## Implementation notes
+    ///
+    /// This field is not present in the original implementation.
+    pub file_programs: HashMap<String, Program>,
+
+    /// The parsed values of all line hashtags that matched a [`HashtagSchema`] registered with [`Compiler::register_hashtag_schema`].
+    ///
+    /// The keys of this dictionary are the line IDs, and the values map the [`HashtagSchema::name`] to the parsed value.
+    /// The raw hashtags are still available in [`StringInfo::metadata`].
+    ///
+    /// ## Implementation notes
+    ///
+    /// This field is not present in the original implementation.
+    pub typed_metadata: HashMap<LineId, HashMap<String, YarnValue>>,
+
+    /// The language the Yarn files are written in, as set by [`Compiler::with_base_language`].
+    /// Runtimes use it for language-dependent markup such as `plural` and `ordinal` when no other language is selected.
+    ///
+    /// ## Implementation notes
+    ///
+    /// This field is not present in the original implementation.
+    pub base_language: Option<String>,
+
+    /// The [`Program::content_hash`] of [`Compilation::program`], if there is one.
+    /// Store it alongside your build artifacts to check later that a loaded program is the one you expect,
+    /// e.g. with the runtime's `Dialogue::verify_program`.
+    ///
+    /// ## Implementation notes
+    ///
+    /// This field is not present in the original implementation.
+    pub content_hash: Option<ContentHash>,
+
+    /// The compiled project split into standalone programs that can be loaded on demand, as requested by [`Compiler::program_split`].
+    ///
+    /// This value will be [`None`] if [`Compiler::program_split`] was not set or if the
+    /// [`Compiler`] object's [`CompilationType`] value was not [`CompilationType::FullCompilation`].
+    ///
+    /// ## Implementation notes
+    ///
+    /// This field is not present in the original implementation.
+    pub program_chunks: Option<ProgramChunks>,
 }
 
 impl Compilation {
@@ -99,6 +159,7 @@ impl Compilation {
         let mut tags = HashMap::new();
         let mut diagnostics = Vec::new();
         let mut node_debug_infos = HashMap::new();
+        let mut file_programs = HashMap::new();
 
         for compilation in compilations {
             programs.push(compilation.program.unwrap());
@@ -106,6 +167,7 @@ impl Compilation {
             tags.extend(compilation.file_tags);
             diagnostics.extend(compilation.warnings);
             node_debug_infos.extend(compilation.debug_info);
+            file_programs.extend(compilation.file_programs);
         }
         let combined_program = Program::combine(programs);
         let contains_implicit_string_tags = string_table_manager.contains_implicit_string_tags();
@@ -117,8 +179,92 @@ impl Compilation {
             contains_implicit_string_tags,
             file_tags: tags,
             warnings: diagnostics,
+            file_programs,
+            typed_metadata: HashMap::new(),
+            base_language: None,
+            content_hash: None,
+            program_chunks: None,
         }
     }
+
+    /// Calculates [`CompilationStats`] about the size of the compiled script, such as its word count.
+    ///
+    /// Line, option and variable counts are only available if [`Compilation::program`] is set,
+    /// i.e. if the compilation was a [`CompilationType::FullCompilation`].
+    pub fn stats(&self) -> CompilationStats {
+        CompilationStats::new(self)
+    }
+
+    /// Creates a [`ReviewReport`] counting the lines in each [`ReviewStatus`], i.e. the lines tagged with `#draft`, `#approved` or `#needs_vo`.
+    pub fn review_report(&self) -> ReviewReport {
+        ReviewReport::new(self)
+    }
+
+    /// Creates a [`ProjectManifest`] describing the nodes, lines, variables and functions of the compiled project.
+    ///
+    /// Pass the same [`Library`] that was used for compilation, i.e. the one passed to [`Compiler::extend_library`],
+    /// so that the manifest contains the signatures of all functions the project can call.
+    pub fn manifest(&self, library: &Library) -> ProjectManifest {
+        ProjectManifest::new(self, library)
+    }
+
+    /// Splits the compiled project into standalone programs that can be loaded on demand. See [`ProgramChunks`].
+    /// Returns [`None`] if there is no [`Compilation::program`], i.e. if the compilation was not a [`CompilationType::FullCompilation`].
+    ///
+    /// Set [`Compiler::program_split`] to have this done during compilation instead.
+    pub fn split_program(&self, split: ProgramSplit) -> Option<ProgramChunks> {
+        self.program
+            .is_some()
+            .then(|| ProgramChunks::new(self, split))
+    }
+
+    /// Iterates over the [`Compilation::string_table`] ordered by [`StringInfo::file_name`], then by [`StringInfo::line_number`], and then by line ID.
+    /// Unlike iterating over the [`HashMap`] directly, the order is the same across compilations, so use this when writing files that are checked into version control.
+    pub fn sorted_string_table(&self) -> impl Iterator<Item = (&LineId, &StringInfo)> {
+        let mut entries: Vec<_> = self.string_table.iter().collect();
+        entries.sort_by(|(lhs_id, lhs), (rhs_id, rhs)| {
+            lhs.file_name
+                .cmp(&rhs.file_name)
+                .then(lhs.line_number.cmp(&rhs.line_number))
+                .then_with(|| lhs_id.0.cmp(&rhs_id.0))
+        });
+        entries.into_iter()
+    }
+
+    /// Returns the name of the file that contains the node with the given name, if any.
+    pub fn file_name_of_node(&self, node_name: &str) -> Option<&str> {
+        self.file_programs
+            .iter()
+            .find(|(_, program)| program.nodes.contains_key(node_name))
+            .map(|(file_name, _)| file_name.as_str())
+    }
+
+    /// Finds the instruction produced from the zero-indexed `position` in the file with the given name, e.g. the one under an editor's cursor.
+    /// Returns the name of the node containing it and its index, which can be used for a breakpoint in the runtime.
+    /// See [`DebugInfo::instruction_at_position`] for how the instruction is picked.
+    pub fn instruction_at_position(
+        &self,
+        file_name: &str,
+        position: Position,
+    ) -> Option<(&str, usize)> {
+        self.debug_info
+            .values()
+            .filter(|debug_info| debug_info.file_name == file_name)
+            .find_map(|debug_info| {
+                debug_info
+                    .instruction_at_position(position)
+                    .map(|instruction_number| (debug_info.node_name.as_str(), instruction_number))
+            })
+    }
+
+    /// Iterates over the names of all nodes that were compiled from the file with the given name.
+    /// The iterator is empty if no such file was compiled.
+    pub fn node_names_in_file(&self, file_name: &str) -> impl Iterator<Item = &str> {
+        self.file_programs
+            .get(file_name)
+            .into_iter()
+            .flat_map(|program| program.nodes.keys().map(String::as_str))
+    }
 }
 
 /// A collection of [`Diagnostic`] objects that describe problems that occurred during compilation.
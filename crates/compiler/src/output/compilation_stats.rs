@@ -0,0 +1,119 @@
+//! Provides statistics about the size of a compiled script.
+
+use crate::prelude::*;
+use std::collections::HashMap;
+use yarnspinner_core::prelude::*;
+
+/// Statistics about a [`Compilation`], useful for tracking the size of a script and its localization word counts.
+///
+/// Created by [`Compilation::stats`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct CompilationStats {
+    /// The statistics of each node, keyed by node name.
+    pub nodes: HashMap<String, NodeStats>,
+
+    /// The statistics of each variable used in the program, keyed by variable name.
+    /// Includes the variables the compiler generates for tracking node visits.
+    pub variables: HashMap<String, VariableStats>,
+}
+
+/// Statistics about a single node. Part of [`CompilationStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct NodeStats {
+    /// The number of lines that are run in this node, not including options.
+    pub line_count: usize,
+
+    /// The number of options that are offered in this node.
+    pub option_count: usize,
+
+    /// The number of words in all strings of this node, including options.
+    /// Words are separated by whitespace. Markup and inline expressions are counted as they appear in the source.
+    pub word_count: usize,
+}
+
+/// Statistics about a single variable. Part of [`CompilationStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct VariableStats {
+    /// The number of places in the program that read the variable.
+    pub read_count: usize,
+
+    /// The number of places in the program that write to the variable.
+    pub write_count: usize,
+}
+
+impl CompilationStats {
+    pub(crate) fn new(compilation: &Compilation) -> Self {
+        let mut stats = Self::default();
+        for string_info in compilation.string_table.values() {
+            let node = stats
+                .nodes
+                .entry(string_info.node_name.clone())
+                .or_default();
+            node.word_count += string_info.text.split_whitespace().count();
+        }
+
+        let Some(program) = compilation.program.as_ref() else {
+            return stats;
+        };
+        for (node_name, node) in &program.nodes {
+            let node_stats = stats.nodes.entry(node_name.clone()).or_default();
+            for instruction in &node.instructions {
+                match instruction.opcode() {
+                    OpCode::RunLine => node_stats.line_count += 1,
+                    OpCode::AddOption => node_stats.option_count += 1,
+                    OpCode::PushVariable => {
+                        let variable: String = instruction.read_operand(0);
+                        stats.variables.entry(variable).or_default().read_count += 1;
+                    }
+                    OpCode::StoreVariable => {
+                        let variable: String = instruction.read_operand(0);
+                        stats.variables.entry(variable).or_default().write_count += 1;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        stats
+    }
+
+    /// The number of nodes in the program.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The number of lines in all nodes, not including options.
+    pub fn line_count(&self) -> usize {
+        self.nodes.values().map(|node| node.line_count).sum()
+    }
+
+    /// The number of options in all nodes.
+    pub fn option_count(&self) -> usize {
+        self.nodes.values().map(|node| node.option_count).sum()
+    }
+
+    /// The number of words in all nodes, including options.
+    pub fn word_count(&self) -> usize {
+        self.nodes.values().map(|node| node.word_count).sum()
+    }
+}
@@ -60,6 +60,42 @@ impl DebugInfo {
                 position: *position,
             })
     }
+
+    /// Returns the index of the first instruction produced from the zero-indexed `line` in `file_name`,
+    /// e.g. for setting a breakpoint on that line. Returns `None` if no instruction was produced from that line.
+    pub fn first_instruction_on_line(&self, line: usize) -> Option<usize> {
+        self.line_positions
+            .iter()
+            .filter(|(_, position)| position.is_some_and(|position| position.line == line))
+            .map(|(instruction_number, _)| *instruction_number)
+            .min()
+    }
+
+    /// Returns the index of the instruction produced from the statement or expression at the zero-indexed `position` in `file_name`,
+    /// e.g. the one under an editor's cursor. This is the last instruction starting at or before `position` on the same line.
+    /// If the cursor is before every instruction on its line, this is [`DebugInfo::first_instruction_on_line`].
+    pub fn instruction_at_position(&self, position: Position) -> Option<usize> {
+        self.line_positions
+            .iter()
+            .filter_map(|(instruction_number, instruction_position)| {
+                instruction_position
+                    .filter(|instruction_position| {
+                        instruction_position.line == position.line
+                            && instruction_position.character <= position.character
+                    })
+                    .map(|instruction_position| {
+                        (instruction_position.character, *instruction_number)
+                    })
+            })
+            // The latest start wins, and the first instruction among those starting there
+            .max_by(|(lhs_character, lhs_index), (rhs_character, rhs_index)| {
+                lhs_character
+                    .cmp(rhs_character)
+                    .then(rhs_index.cmp(lhs_index))
+            })
+            .map(|(_, instruction_number)| instruction_number)
+            .or_else(|| self.first_instruction_on_line(position.line))
+    }
 }
 
 /// Contains positional information about an instruction.
@@ -173,6 +173,24 @@ impl Declaration {
     }
 }
 
+impl From<Declaration> for VariableDeclaration {
+    fn from(declaration: Declaration) -> Self {
+        Self {
+            name: declaration.name,
+            r#type: declaration.r#type,
+            default_value: declaration.default_value,
+            description: declaration.description,
+            is_implicit: declaration.is_implicit,
+        }
+    }
+}
+
+impl From<&Declaration> for VariableDeclaration {
+    fn from(declaration: &Declaration) -> Self {
+        Self::from(declaration.clone())
+    }
+}
+
 /// The source of a declaration.
 ///
 /// ## Implementation notes
@@ -0,0 +1,100 @@
+//! Allows users to tell the compiler which line hashtags they expect.
+
+#[cfg(any(feature = "bevy", feature = "serde"))]
+use crate::prelude::*;
+use yarnspinner_core::prelude::*;
+
+/// Describes a hashtag that lines may carry, e.g. `#mood:angry`, and what values it accepts.
+///
+/// Register schemas with [`Compiler::register_hashtag_schema`]. The compiler will then emit an error for every line
+/// that uses a hashtag with this [`HashtagSchema::name`] but an invalid value, and expose the parsed values of valid ones
+/// in [`Compilation::typed_metadata`]. Hashtags without a registered schema are left untouched.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct HashtagSchema {
+    /// The name of the hashtag, i.e. the part before the colon. For `#mood:angry`, this is `mood`.
+    pub name: String,
+
+    /// The values the hashtag accepts.
+    pub value_type: HashtagValueType,
+}
+
+/// The values accepted by a [`HashtagSchema`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum HashtagValueType {
+    /// The hashtag takes no value, e.g. `#important`. Its typed value is `true`.
+    Flag,
+    /// The hashtag takes a number, e.g. `#volume:0.5`.
+    Number,
+    /// The hashtag takes a boolean, e.g. `#skippable:false`.
+    Boolean,
+    /// The hashtag takes any string, e.g. `#speaker:Sally`.
+    String,
+    /// The hashtag takes one of the given strings, e.g. `#mood:angry`.
+    OneOf(Vec<String>),
+}
+
+impl HashtagSchema {
+    /// Creates a new [`HashtagSchema`] for the hashtag with the given name.
+    pub fn new(name: impl Into<String>, value_type: HashtagValueType) -> Self {
+        Self {
+            name: name.into(),
+            value_type,
+        }
+    }
+
+    /// Creates a new [`HashtagSchema`] for a hashtag that accepts only the given values.
+    pub fn one_of(
+        name: impl Into<String>,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self::new(
+            name,
+            HashtagValueType::OneOf(values.into_iter().map(Into::into).collect()),
+        )
+    }
+
+    /// Parses the value of a hashtag, i.e. the part after the colon, according to this schema.
+    /// Returns a message describing the problem if the value is not accepted.
+    pub fn parse_value(&self, value: Option<&str>) -> std::result::Result<YarnValue, String> {
+        match (&self.value_type, value) {
+            (HashtagValueType::Flag, None) => Ok(true.into()),
+            (HashtagValueType::Flag, Some(_)) => Err("expected no value".to_owned()),
+            (_, None) => Err("expected a value".to_owned()),
+            (HashtagValueType::Number, Some(value)) => value
+                .parse::<f32>()
+                .map(Into::into)
+                .map_err(|_| "expected a number".to_owned()),
+            (HashtagValueType::Boolean, Some(value)) => value
+                .parse::<bool>()
+                .map(Into::into)
+                .map_err(|_| "expected `true` or `false`".to_owned()),
+            (HashtagValueType::String, Some(value)) => Ok(value.into()),
+            (HashtagValueType::OneOf(values), Some(value)) => {
+                if values.iter().any(|v| v == value) {
+                    Ok(value.into())
+                } else {
+                    let values = values
+                        .iter()
+                        .map(|v| format!("`{v}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    Err(format!("expected one of {values}"))
+                }
+            }
+        }
+    }
+}
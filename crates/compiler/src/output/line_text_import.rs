@@ -0,0 +1,156 @@
+//! Types used by [`Compiler::import_line_text`] to merge edited line text back into Yarn files.
+
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io::Read;
+use thiserror::Error;
+use yarnspinner_core::prelude::*;
+
+/// New texts for lines, keyed by line ID, e.g. as edited by proofreaders in a spreadsheet.
+/// Applied to Yarn files with [`Compiler::import_line_text`].
+///
+/// The texts use the same format as [`StringInfo::text`], i.e. inline expressions are written as their index, e.g. `{0}`,
+/// and are replaced with the original expressions on import. Markup is imported as written.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LineTextEdits(pub HashMap<LineId, String>);
+
+impl LineTextEdits {
+    /// Reads the edits from CSV with a header row. The line IDs are read from the `id` column and the texts from the `text` column.
+    /// All other columns are ignored, so strings files exported for translation can be read as well.
+    pub fn from_csv(reader: impl Read) -> Result<Self, LineTextImportError> {
+        let mut reader = csv::Reader::from_reader(reader);
+        let headers = reader.headers()?.clone();
+        let column = |name: &'static str| {
+            headers
+                .iter()
+                .position(|header| header.trim() == name)
+                .ok_or(LineTextImportError::MissingColumn(name))
+        };
+        let id_column = column("id")?;
+        let text_column = column("text")?;
+        let mut edits = HashMap::new();
+        for record in reader.records() {
+            let record = record?;
+            let (Some(id), Some(text)) = (record.get(id_column), record.get(text_column)) else {
+                continue;
+            };
+            edits.insert(LineId(id.trim().to_owned()), text.to_owned());
+        }
+        Ok(Self(edits))
+    }
+
+    /// Reads the edits from JSON. Accepts either an object mapping line IDs to texts, e.g. `{"line:1": "Hello"}`,
+    /// or an array of objects with an `id` and a `text` field, such as the [`ProjectManifest::lines`].
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, LineTextImportError> {
+        #[derive(Deserialize)]
+        struct Record {
+            id: LineId,
+            text: String,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Edits {
+            Map(HashMap<LineId, String>),
+            Records(Vec<Record>),
+        }
+
+        let edits = match serde_json::from_str(json)? {
+            Edits::Map(edits) => edits,
+            Edits::Records(records) => records
+                .into_iter()
+                .map(|record| (record.id, record.text))
+                .collect(),
+        };
+        Ok(Self(edits))
+    }
+}
+
+impl FromIterator<(LineId, String)> for LineTextEdits {
+    fn from_iter<T: IntoIterator<Item = (LineId, String)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// The result of [`Compiler::import_line_text`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LineTextImport {
+    /// The source code with the new line texts applied.
+    pub source: String,
+
+    /// The lines whose text was changed, in the order they appear in the source code.
+    /// Lines whose new text is the same as the old one are not included.
+    pub changes: Vec<LineTextChange>,
+}
+
+/// A line changed by [`Compiler::import_line_text`]. Part of [`LineTextImport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineTextChange {
+    /// The ID of the line.
+    pub line_id: LineId,
+
+    /// The 1-indexed line number of the line in the source code.
+    pub line_number: usize,
+
+    /// The text of the line before the import, in the format of [`StringInfo::text`].
+    pub old_text: String,
+
+    /// The text of the line after the import, in the format of [`StringInfo::text`].
+    pub new_text: String,
+
+    /// The complete line of source code before the import, including the hashtags and any other syntax around the text.
+    pub old_source_line: String,
+
+    /// The complete line of source code after the import.
+    pub new_source_line: String,
+}
+
+impl LineTextImport {
+    /// Returns `true` if any line was changed.
+    pub fn has_changes(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    /// Formats the changes as a diff of the affected source code lines, e.g. for reviewing an import before writing it to disk.
+    pub fn diff(&self) -> String {
+        let mut diff = String::new();
+        for change in &self.changes {
+            writeln!(
+                diff,
+                "@@ line {} ({}) @@",
+                change.line_number, change.line_id
+            )
+            .unwrap();
+            writeln!(diff, "-{}", change.old_source_line).unwrap();
+            writeln!(diff, "+{}", change.new_source_line).unwrap();
+        }
+        diff
+    }
+}
+
+/// An error that occurred while importing line text with [`LineTextEdits`] or [`Compiler::import_line_text_into_file`].
+#[derive(Error, Debug)]
+pub enum LineTextImportError {
+    /// The edits could not be read as CSV.
+    #[error("Failed to read line text edits as CSV: {0}")]
+    Csv(#[from] csv::Error),
+
+    /// The CSV has no column with the given name.
+    #[error("The line text edits have no \"{0}\" column")]
+    MissingColumn(&'static str),
+
+    /// The edits could not be read as JSON.
+    #[cfg(feature = "serde")]
+    #[error("Failed to read line text edits as JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The Yarn file could not be read or written.
+    #[error("Failed to access the Yarn file: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The Yarn file could not be parsed or an edit could not be applied.
+    #[error(transparent)]
+    Compiler(#[from] CompilerError),
+}
@@ -0,0 +1,131 @@
+//! Splits a compiled project into standalone programs that can be loaded on demand.
+
+use crate::prelude::*;
+use std::collections::HashMap;
+use yarnspinner_core::prelude::*;
+
+/// How [`Compiler::program_split`] splits the compiled project into [`ProgramChunks`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum ProgramSplit {
+    /// One chunk per file, named after the file.
+    #[default]
+    PerFile,
+    /// One chunk per node, named after the node.
+    PerNode,
+}
+
+/// A compiled project split into standalone [`Program`]s, called chunks, so that only the chunks for the content currently needed
+/// have to be kept in memory. Created when [`Compiler::program_split`] is set, see [`Compilation::program_chunks`].
+///
+/// A chunk only contains nodes. To run chunks, first load [`ProgramChunkManifest::base_program`] into the dialogue,
+/// which holds the initial values of all variables, and then add and remove chunks as needed.
+/// Jumping to a node whose chunk is not loaded is an error, so use [`ProgramChunkManifest::chunk_of_node`] to find the chunk to load first.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct ProgramChunks {
+    /// The chunks, keyed by their name. See [`ProgramSplit`] for how they are named.
+    pub chunks: HashMap<String, Program>,
+
+    /// Describes which node is found in which chunk.
+    pub manifest: ProgramChunkManifest,
+}
+
+/// Describes the chunks of [`ProgramChunks`]. It is small, so keep it in memory while the chunks themselves are loaded on demand.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct ProgramChunkManifest {
+    /// How the project was split.
+    pub split: ProgramSplit,
+
+    /// The name of the chunk each node is found in, keyed by node name.
+    pub node_chunks: HashMap<String, String>,
+
+    /// The initial values of all variables of the project, i.e. the [`Program::initial_values`] of the whole program.
+    pub initial_values: HashMap<String, Operand>,
+}
+
+impl ProgramChunks {
+    pub(crate) fn new(compilation: &Compilation, split: ProgramSplit) -> Self {
+        let chunks: HashMap<_, _> = match split {
+            ProgramSplit::PerFile => compilation.file_programs.clone(),
+            ProgramSplit::PerNode => compilation
+                .file_programs
+                .values()
+                .flat_map(|program| program.nodes.iter())
+                .map(|(node_name, node)| {
+                    let program = Program {
+                        nodes: HashMap::from([(node_name.clone(), node.clone())]),
+                        ..Default::default()
+                    };
+                    (node_name.clone(), program)
+                })
+                .collect(),
+        };
+        let node_chunks = chunks
+            .iter()
+            .flat_map(|(chunk_name, program)| {
+                program
+                    .nodes
+                    .keys()
+                    .map(move |node_name| (node_name.clone(), chunk_name.clone()))
+            })
+            .collect();
+        let initial_values = compilation
+            .program
+            .as_ref()
+            .map(|program| program.initial_values.clone())
+            .unwrap_or_default();
+        Self {
+            chunks,
+            manifest: ProgramChunkManifest {
+                split,
+                node_chunks,
+                initial_values,
+            },
+        }
+    }
+}
+
+impl ProgramChunkManifest {
+    /// Returns the name of the chunk containing the node with the given name, if any.
+    pub fn chunk_of_node(&self, node_name: &str) -> Option<&str> {
+        self.node_chunks.get(node_name).map(String::as_str)
+    }
+
+    /// Iterates over the names of all nodes in the chunk with the given name.
+    /// The iterator is empty if there is no such chunk.
+    pub fn node_names_in_chunk<'a>(&'a self, chunk_name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.node_chunks
+            .iter()
+            .filter(move |(_, chunk)| chunk.as_str() == chunk_name)
+            .map(|(node_name, _)| node_name.as_str())
+    }
+
+    /// Returns a [`Program`] without nodes that holds the initial values of all variables.
+    /// Load it into a dialogue before adding any chunks.
+    pub fn base_program(&self) -> Program {
+        Program {
+            initial_values: self.initial_values.clone(),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Provides a single machine-readable overview of a compiled project for external tools.
+
+use crate::prelude::*;
+use std::collections::HashMap;
+use yarnspinner_core::prelude::*;
+use yarnspinner_core::types::Type;
+
+/// A machine-readable overview of a compiled project, intended for external tools such as quest editors or wikis.
+///
+/// Created by [`Compilation::manifest`]. All collections are sorted by name or ID so that the manifest is stable across compilations.
+/// With the `serde` feature enabled, [`ProjectManifest::to_json`] exports it as JSON.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct ProjectManifest {
+    /// The nodes of the project.
+    pub nodes: Vec<NodeManifest>,
+
+    /// The lines of the project, i.e. the entries of [`Compilation::string_table`].
+    pub lines: Vec<LineManifest>,
+
+    /// The variables declared in the project, both explicitly and implicitly.
+    /// Does not include the variables the compiler generates for tracking node visits.
+    pub variables: Vec<VariableManifest>,
+
+    /// The functions that can be called from the project, including those of [`Library::standard_library`].
+    pub functions: Vec<FunctionManifest>,
+}
+
+/// A node of a [`ProjectManifest`].
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct NodeManifest {
+    /// The name of the node.
+    pub name: String,
+
+    /// The name of the file the node was found in.
+    pub file_name: Option<String>,
+
+    /// The headers of the node, including `title` and `tags`.
+    pub headers: Vec<Header>,
+
+    /// The tags of the node.
+    pub tags: Vec<String>,
+
+    /// The names of the nodes this node can jump to, without duplicates.
+    /// Jumps to an expression, e.g. `<<jump {$destination}>>`, cannot be resolved at compile time and are not included.
+    pub outgoing_edges: Vec<String>,
+}
+
+/// A line of a [`ProjectManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct LineManifest {
+    /// The ID of the line.
+    pub id: LineId,
+
+    /// The text of the line, with inline expressions replaced by their index, e.g. `{0}`.
+    pub text: String,
+
+    /// The name of the node the line was found in.
+    pub node_name: String,
+
+    /// The name of the file the line was found in.
+    pub file_name: String,
+
+    /// The 1-indexed line number at which the line was found in the file.
+    pub line_number: usize,
+
+    /// The hashtags of the line, besides the `#line:` hashtag.
+    pub metadata: Vec<String>,
+}
+
+/// A variable of a [`ProjectManifest`].
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct VariableManifest {
+    /// The name of the variable, including the leading `$`.
+    pub name: String,
+
+    /// The name of the type of the variable, e.g. `Number`.
+    pub type_name: String,
+
+    /// The default value of the variable.
+    pub default_value: Option<YarnValue>,
+
+    /// The description of the variable, taken from the comment of its declaration.
+    pub description: Option<String>,
+
+    /// Whether the variable was inferred from usage instead of being declared with `<<declare>>`.
+    pub is_implicit: bool,
+}
+
+/// A function of a [`ProjectManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct FunctionManifest {
+    /// The name of the function.
+    pub name: String,
+
+    /// The names of the types of the parameters, e.g. `Number`.
+    pub parameter_types: Vec<String>,
+
+    /// The number of parameters at the end of [`FunctionManifest::parameter_types`] that may be omitted.
+    pub optional_parameter_count: usize,
+
+    /// The name of the type of the arguments a variadic function accepts after [`FunctionManifest::parameter_types`],
+    /// or `None` if the function is not variadic.
+    pub variadic_parameter_type: Option<String>,
+
+    /// The name of the return type, e.g. `Number`.
+    pub return_type: String,
+}
+
+impl ProjectManifest {
+    pub(crate) fn new(compilation: &Compilation, library: &Library) -> Self {
+        let mut nodes: Vec<_> = compilation
+            .program
+            .iter()
+            .flat_map(|program| program.nodes.values())
+            .map(|node| NodeManifest {
+                name: node.name.clone(),
+                file_name: compilation.file_name_of_node(&node.name).map(Into::into),
+                headers: node.headers.clone(),
+                tags: node.tags.clone(),
+                outgoing_edges: outgoing_edges(node),
+            })
+            .collect();
+        nodes.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+
+        let mut lines: Vec<_> = compilation
+            .string_table
+            .iter()
+            .map(|(id, string_info)| LineManifest {
+                id: id.clone(),
+                text: string_info.text.clone(),
+                node_name: string_info.node_name.clone(),
+                file_name: string_info.file_name.clone(),
+                line_number: string_info.line_number,
+                metadata: string_info.metadata.clone(),
+            })
+            .collect();
+        lines.sort_by(|lhs, rhs| lhs.id.0.cmp(&rhs.id.0));
+
+        let mut variables: Vec<_> = compilation
+            .declarations
+            .iter()
+            .filter(|declaration| !matches!(declaration.r#type, Type::Function(_)))
+            .filter(|declaration| {
+                !declaration
+                    .name
+                    .starts_with(&Library::generate_unique_visited_variable_for_node(""))
+            })
+            .map(|declaration| VariableManifest {
+                name: declaration.name.clone(),
+                type_name: declaration.r#type.to_string(),
+                default_value: declaration.default_value.clone(),
+                description: declaration.description.clone(),
+                is_implicit: declaration.is_implicit,
+            })
+            .collect();
+        variables.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+
+        // Later declarations override earlier ones, just like when importing libraries
+        let functions: HashMap<_, _> = get_declarations_from_library(&Library::standard_library())
+            .into_iter()
+            .chain(get_declarations_from_library(library))
+            .filter_map(|declaration| match declaration.r#type {
+                Type::Function(function_type) => Some(FunctionManifest {
+                    name: declaration.name.clone(),
+                    parameter_types: function_type
+                        .parameters
+                        .iter()
+                        .map(|parameter| type_name(parameter.as_ref()))
+                        .collect(),
+                    optional_parameter_count: function_type.optional_parameter_count,
+                    variadic_parameter_type: function_type
+                        .variadic_parameter_type
+                        .map(|r#type| r#type.to_string()),
+                    return_type: type_name(function_type.return_type.as_ref().as_ref()),
+                }),
+                _ => None,
+            })
+            .map(|function| (function.name.clone(), function))
+            .collect();
+        let mut functions: Vec<_> = functions.into_values().collect();
+        functions.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+
+        Self {
+            nodes,
+            lines,
+            variables,
+            functions,
+        }
+    }
+
+    /// Exports the manifest as pretty-printed JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Failed to serialize project manifest")
+    }
+}
+
+fn outgoing_edges(node: &Node) -> Vec<String> {
+    let mut edges = Vec::new();
+    for destination in node.jump_destinations() {
+        if !edges.contains(&destination) {
+            edges.push(destination);
+        }
+    }
+    edges
+}
+
+fn type_name(r#type: Option<&Type>) -> String {
+    r#type
+        .map(ToString::to_string)
+        .unwrap_or_else(|| "Any".to_owned())
+}
@@ -0,0 +1,102 @@
+//! Summarizes the editorial review status of lines, as tracked by workflow hashtags.
+
+use crate::prelude::*;
+use std::collections::HashMap;
+use yarnspinner_core::prelude::*;
+
+/// A step of the editorial workflow a line is in, marked by a hashtag on the line, e.g. `Hello! #draft`.
+/// A line may be in multiple steps at once, e.g. `#approved #needs_vo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum ReviewStatus {
+    /// `#draft`: the text is not final. See [`Compiler::forbid_draft_lines`].
+    Draft,
+    /// `#approved`: the text passed review.
+    Approved,
+    /// `#needs_vo`: the line still needs to be voiced.
+    NeedsVo,
+}
+
+impl ReviewStatus {
+    /// All review statuses.
+    pub const ALL: [ReviewStatus; 3] = [Self::Draft, Self::Approved, Self::NeedsVo];
+
+    /// The hashtag marking this status, without the leading `#`.
+    pub fn hashtag(self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Approved => "approved",
+            Self::NeedsVo => "needs_vo",
+        }
+    }
+
+    /// Returns the statuses marked by the given line metadata, e.g. [`StringInfo::metadata`].
+    pub fn from_metadata(metadata: &[String]) -> impl Iterator<Item = ReviewStatus> + '_ {
+        Self::ALL
+            .into_iter()
+            .filter(|status| metadata.iter().any(|tag| tag == status.hashtag()))
+    }
+}
+
+/// The number of lines in each [`ReviewStatus`], to keep track of the editorial progress of a project.
+///
+/// Created by [`Compilation::review_report`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct ReviewReport {
+    /// The number of lines in each status. Statuses no line is in are missing.
+    pub status_counts: HashMap<ReviewStatus, usize>,
+
+    /// The IDs of all lines marked with each status, sorted.
+    pub lines_by_status: HashMap<ReviewStatus, Vec<LineId>>,
+
+    /// The IDs of all lines without any status, sorted. Does not include the source text of `rawText` nodes.
+    pub lines_without_status: Vec<LineId>,
+}
+
+impl ReviewReport {
+    pub(crate) fn new(compilation: &Compilation) -> Self {
+        let mut report = Self::default();
+        for (line_id, string_info) in &compilation.string_table {
+            if *line_id == get_line_id_for_node_name(&string_info.node_name) {
+                // The source text of a `rawText` node is not a line
+                continue;
+            }
+            let mut has_status = false;
+            for status in ReviewStatus::from_metadata(&string_info.metadata) {
+                has_status = true;
+                *report.status_counts.entry(status).or_default() += 1;
+                report
+                    .lines_by_status
+                    .entry(status)
+                    .or_default()
+                    .push(line_id.clone());
+            }
+            if !has_status {
+                report.lines_without_status.push(line_id.clone());
+            }
+        }
+        for line_ids in report.lines_by_status.values_mut() {
+            line_ids.sort();
+        }
+        report.lines_without_status.sort();
+        report
+    }
+
+    /// The number of lines in the given status.
+    pub fn count(&self, status: ReviewStatus) -> usize {
+        self.status_counts.get(&status).copied().unwrap_or_default()
+    }
+}
@@ -43,4 +43,12 @@ pub struct StringInfo {
     /// This array will contain any hashtags associated with this
     /// string besides the `#line:` hashtag.
     pub metadata: Vec<String>,
+
+    /// The language set by the `language` header of the node this string was found in, if any.
+    ///
+    /// Nodes with this header are written in a different language than the rest of the project,
+    /// e.g. for characters that speak a foreign language.
+    /// Their lines use this language for markers such as `[plural]` and `[ordinal]` when they are run,
+    /// and are left out of the strings files of translations, as they are meant to be shown as written in every language.
+    pub language: Option<String>,
 }
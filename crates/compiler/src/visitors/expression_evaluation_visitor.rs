@@ -0,0 +1,289 @@
+//! Used by [`Compiler::evaluate_expression`] to fold a type-checked expression into a single [`YarnValue`].
+
+use crate::prelude::generated::yarnspinnerparser::*;
+use crate::prelude::generated::yarnspinnerparservisitor::YarnSpinnerParserVisitorCompat;
+use crate::prelude::*;
+use crate::visitors::{CodeGenerationVisitor, KnownTypes};
+use antlr_rust::tree::{ParseTree, ParseTreeVisitorCompat};
+use std::rc::Rc;
+use yarnspinner_core::prelude::*;
+use yarnspinner_core::types::Type;
+
+/// A visitor that evaluates the inline expressions of a line, given the types resolved by a [`TypeCheckVisitor`].
+///
+/// Operators and functions are resolved in the same way the [`CodeGenerationVisitor`] resolves them, i.e. by calling
+/// the canonical method of the operand type in the [`Library`]. Variables evaluate to the default value of their [`Declaration`].
+/// The evaluated values are collected in [`ExpressionEvaluationVisitor::values`].
+///
+/// [`TypeCheckVisitor`]: crate::visitors::TypeCheckVisitor
+pub(crate) struct ExpressionEvaluationVisitor<'a, 'input> {
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    pub(crate) values: Vec<YarnValue>,
+    library: &'a Library,
+    declarations: &'a [Declaration],
+    known_types: &'a KnownTypes,
+    file: FileParseResult<'input>,
+    _dummy: Option<YarnValue>,
+}
+
+impl<'a, 'input> ExpressionEvaluationVisitor<'a, 'input> {
+    pub(crate) fn new(
+        library: &'a Library,
+        declarations: &'a [Declaration],
+        known_types: &'a KnownTypes,
+        file: FileParseResult<'input>,
+    ) -> Self {
+        Self {
+            library,
+            declarations,
+            known_types,
+            file,
+            diagnostics: Default::default(),
+            values: Default::default(),
+            _dummy: Default::default(),
+        }
+    }
+}
+
+impl<'a, 'input> ParseTreeVisitorCompat<'input> for ExpressionEvaluationVisitor<'a, 'input> {
+    type Node = YarnSpinnerParserContextType;
+    type Return = Option<YarnValue>;
+
+    fn temp_result(&mut self) -> &mut Self::Return {
+        &mut self._dummy
+    }
+}
+
+impl<'a, 'input> YarnSpinnerParserVisitorCompat<'input>
+    for ExpressionEvaluationVisitor<'a, 'input>
+{
+    fn visit_line_formatted_text(
+        &mut self,
+        ctx: &Line_formatted_textContext<'input>,
+    ) -> Self::Return {
+        for expression in ctx.expression_all() {
+            if let Some(value) = self.visit(expression.as_ref()) {
+                self.values.push(value);
+            }
+        }
+        None
+    }
+
+    fn visit_expParens(&mut self, ctx: &ExpParensContext<'input>) -> Self::Return {
+        self.visit(ctx.expression().unwrap().as_ref())
+    }
+
+    fn visit_expMultDivMod(&mut self, ctx: &ExpMultDivModContext<'input>) -> Self::Return {
+        let operator = CodeGenerationVisitor::token_to_operator(ctx.op.as_ref()?.token_type)?;
+        let r#type = self.known_types.get(ctx)?.clone();
+        let expressions = vec![
+            ctx.expression(0).unwrap() as Rc<ActualParserContext<'input>>,
+            ctx.expression(1).unwrap(),
+        ];
+        self.evaluate_operation(operator, &r#type, &expressions)
+    }
+
+    fn visit_expComparison(&mut self, ctx: &ExpComparisonContext<'input>) -> Self::Return {
+        let operator = CodeGenerationVisitor::token_to_operator(ctx.op.as_ref()?.token_type)?;
+        let r#type = self.known_types.get(ctx)?.clone();
+        let expressions = vec![
+            ctx.expression(0).unwrap() as Rc<ActualParserContext<'input>>,
+            ctx.expression(1).unwrap(),
+        ];
+        self.evaluate_operation(operator, &r#type, &expressions)
+    }
+
+    fn visit_expNegative(&mut self, ctx: &ExpNegativeContext<'input>) -> Self::Return {
+        let r#type = self.known_types.get(ctx)?.clone();
+        let expressions = vec![ctx.expression().unwrap() as Rc<ActualParserContext<'input>>];
+        self.evaluate_operation(Operator::UnarySubtract, &r#type, &expressions)
+    }
+
+    fn visit_expAndOrXor(&mut self, ctx: &ExpAndOrXorContext<'input>) -> Self::Return {
+        let operator = CodeGenerationVisitor::token_to_operator(ctx.op.as_ref()?.token_type)?;
+        let r#type = self.known_types.get(ctx)?.clone();
+        let expressions = vec![
+            ctx.expression(0).unwrap() as Rc<ActualParserContext<'input>>,
+            ctx.expression(1).unwrap(),
+        ];
+        self.evaluate_operation(operator, &r#type, &expressions)
+    }
+
+    fn visit_expAddSub(&mut self, ctx: &ExpAddSubContext<'input>) -> Self::Return {
+        let operator = CodeGenerationVisitor::token_to_operator(ctx.op.as_ref()?.token_type)?;
+        let r#type = self.known_types.get(ctx)?.clone();
+        let expressions = vec![
+            ctx.expression(0).unwrap() as Rc<ActualParserContext<'input>>,
+            ctx.expression(1).unwrap(),
+        ];
+        self.evaluate_operation(operator, &r#type, &expressions)
+    }
+
+    fn visit_expNot(&mut self, ctx: &ExpNotContext<'input>) -> Self::Return {
+        let r#type = self.known_types.get(ctx)?.clone();
+        let expressions = vec![ctx.expression().unwrap() as Rc<ActualParserContext<'input>>];
+        self.evaluate_operation(Operator::Not, &r#type, &expressions)
+    }
+
+    fn visit_expValue(&mut self, ctx: &ExpValueContext<'input>) -> Self::Return {
+        self.visit(ctx.value().unwrap().as_ref())
+    }
+
+    fn visit_expEquality(&mut self, ctx: &ExpEqualityContext<'input>) -> Self::Return {
+        let operator = CodeGenerationVisitor::token_to_operator(ctx.op.as_ref()?.token_type)?;
+        let r#type = self.known_types.get(ctx)?.clone();
+        let expressions = vec![
+            ctx.expression(0).unwrap() as Rc<ActualParserContext<'input>>,
+            ctx.expression(1).unwrap(),
+        ];
+        self.evaluate_operation(operator, &r#type, &expressions)
+    }
+
+    fn visit_valueNumber(&mut self, ctx: &ValueNumberContext<'input>) -> Self::Return {
+        let number: f32 = ctx.NUMBER().unwrap().get_text().parse().ok()?;
+        Some(number.into())
+    }
+
+    fn visit_valueTrue(&mut self, _ctx: &ValueTrueContext<'input>) -> Self::Return {
+        Some(true.into())
+    }
+
+    fn visit_valueFalse(&mut self, _ctx: &ValueFalseContext<'input>) -> Self::Return {
+        Some(false.into())
+    }
+
+    fn visit_valueVar(&mut self, ctx: &ValueVarContext<'input>) -> Self::Return {
+        self.visit(ctx.variable().unwrap().as_ref())
+    }
+
+    fn visit_valueString(&mut self, ctx: &ValueStringContext<'input>) -> Self::Return {
+        let text = ctx.STRING().unwrap().get_text();
+        let text = text.strip_prefix('"')?.strip_suffix('"')?;
+        Some(unescape_string(text).into())
+    }
+
+    fn visit_valueNull(&mut self, _ctx: &ValueNullContext<'input>) -> Self::Return {
+        // The type checker already reported this
+        None
+    }
+
+    fn visit_valueFunc(&mut self, ctx: &ValueFuncContext<'input>) -> Self::Return {
+        self.visit(ctx.function_call().unwrap().as_ref())
+    }
+
+    fn visit_variable(&mut self, ctx: &VariableContext<'input>) -> Self::Return {
+        let variable_name = ctx.VAR_ID()?.get_text();
+        let default_value = self
+            .declarations
+            .iter()
+            .find(|decl| decl.name == variable_name)
+            .and_then(|decl| decl.default_value.clone());
+        if default_value.is_none() {
+            self.diagnostics.push(
+                Diagnostic::from_message(format!(
+                    "Cannot evaluate {variable_name} because its declaration has no default value"
+                ))
+                .with_file_name(&self.file.name)
+                .with_parser_context(ctx, self.file.tokens()),
+            );
+        }
+        default_value
+    }
+
+    fn visit_function_call(&mut self, ctx: &Function_callContext<'input>) -> Self::Return {
+        let parameters = ctx
+            .expression_all()
+            .iter()
+            .map(|parameter| self.visit(parameter.as_ref()))
+            .collect::<Option<Vec<_>>>()?;
+        let function_name = ctx.FUNC_ID().unwrap().get_text();
+        let Some(function) = self.library.get(&function_name) else {
+            self.diagnostics.push(
+                Diagnostic::from_message(format!(
+                    "Cannot evaluate a call to \"{function_name}\" because it is not implemented in the library"
+                ))
+                .with_file_name(&self.file.name)
+                .with_parser_context(ctx, self.file.tokens()),
+            );
+            return None;
+        };
+        if function.is_async() {
+            self.diagnostics.push(
+                Diagnostic::from_message(format!(
+                    "Cannot evaluate a call to \"{function_name}\" because it is an async function"
+                ))
+                .with_file_name(&self.file.name)
+                .with_parser_context(ctx, self.file.tokens()),
+            );
+            return None;
+        }
+        if function.uses_dialogue_context() {
+            self.diagnostics.push(
+                Diagnostic::from_message(format!(
+                    "Cannot evaluate a call to \"{function_name}\" because it reads the dialogue context"
+                ))
+                .with_file_name(&self.file.name)
+                .with_parser_context(ctx, self.file.tokens()),
+            );
+            return None;
+        }
+        match function.call_async(parameters) {
+            YarnFnOutput::Ready(value) => Some(value),
+            YarnFnOutput::Failed(error) => {
+                self.diagnostics.push(
+                    Diagnostic::from_message(format!(
+                        "Cannot evaluate a call to \"{function_name}\" because it failed: {error}"
+                    ))
+                    .with_file_name(&self.file.name)
+                    .with_parser_context(ctx, self.file.tokens()),
+                );
+                None
+            }
+            YarnFnOutput::Pending(_) => unreachable!("Async functions are handled above"),
+        }
+    }
+}
+
+impl<'a, 'input> ExpressionEvaluationVisitor<'a, 'input> {
+    /// Calls the method appropriate for the operator, analogous to [`CodeGenerationVisitor`]'s `generate_code_for_operation`.
+    fn evaluate_operation(
+        &mut self,
+        op: Operator,
+        r#type: &Type,
+        operands: &[Rc<ActualParserContext<'input>>],
+    ) -> Option<YarnValue> {
+        // Like in the VM, all operands are evaluated, i.e. there is no short-circuiting.
+        let parameters = operands
+            .iter()
+            .map(|operand| self.visit(operand.as_ref()))
+            .collect::<Option<Vec<_>>>()?;
+        let function_name = r#type.get_canonical_name_for_method(&op.to_string());
+        let Some(function) = self.library.get(&function_name) else {
+            self.diagnostics.push(
+                Diagnostic::from_message(format!(
+                    "Cannot evaluate {op} because the library has no implementation of it for {}",
+                    r#type.name()
+                ))
+                .with_file_name(&self.file.name),
+            );
+            return None;
+        };
+        Some(function.call(parameters))
+    }
+}
+
+/// Resolves the escape sequences `\"` and `\\` the lexer allows inside string literals.
+fn unescape_string(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        match (char, chars.clone().next()) {
+            ('\\', Some(escaped @ ('"' | '\\'))) => {
+                unescaped.push(escaped);
+                chars.next();
+            }
+            _ => unescaped.push(char),
+        }
+    }
+    unescaped
+}
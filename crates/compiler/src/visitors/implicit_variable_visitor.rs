@@ -0,0 +1,56 @@
+//! Reports every use of an implicitly declared variable
+//! when [`Compiler::strict_variable_declarations`] is enabled.
+
+use crate::prelude::generated::yarnspinnerparser::*;
+use crate::prelude::generated::yarnspinnerparservisitor::YarnSpinnerParserVisitorCompat;
+use crate::prelude::*;
+use antlr_rust::tree::{ParseTree, ParseTreeVisitorCompat};
+use std::collections::HashSet;
+
+pub(crate) struct ImplicitVariableVisitor<'a, 'input> {
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    implicit_variables: &'a HashSet<String>,
+    file: &'a FileParseResult<'input>,
+    _dummy: (),
+}
+
+impl<'a, 'input> ImplicitVariableVisitor<'a, 'input> {
+    pub(crate) fn new(
+        implicit_variables: &'a HashSet<String>,
+        file: &'a FileParseResult<'input>,
+    ) -> Self {
+        Self {
+            diagnostics: Default::default(),
+            implicit_variables,
+            file,
+            _dummy: (),
+        }
+    }
+}
+
+impl<'a, 'input> ParseTreeVisitorCompat<'input> for ImplicitVariableVisitor<'a, 'input> {
+    type Node = YarnSpinnerParserContextType;
+    type Return = ();
+
+    fn temp_result(&mut self) -> &mut Self::Return {
+        &mut self._dummy
+    }
+}
+
+impl<'a, 'input> YarnSpinnerParserVisitorCompat<'input> for ImplicitVariableVisitor<'a, 'input> {
+    fn visit_variable(&mut self, ctx: &VariableContext<'input>) -> Self::Return {
+        let Some(variable_name) = ctx.VAR_ID().map(|id| id.get_text()) else {
+            return;
+        };
+        if !self.implicit_variables.contains(&variable_name) {
+            return;
+        }
+        self.diagnostics.push(
+            Diagnostic::from_message(format!(
+                "Variable {variable_name} is used without being declared. Declare it with <<declare {variable_name} = ...>>"
+            ))
+            .with_file_name(&self.file.name)
+            .with_parser_context(ctx, self.file.tokens()),
+        );
+    }
+}
@@ -15,6 +15,7 @@ use std::rc::Rc;
 pub(crate) struct StringTableGeneratorVisitor<'input> {
     pub(crate) diagnostics: Vec<Diagnostic>,
     current_node_name: String,
+    current_node_language: Option<String>,
     pub(crate) string_table_manager: StringTableManager,
     file: FileParseResult<'input>,
     _dummy: (),
@@ -30,6 +31,7 @@ impl<'input> StringTableGeneratorVisitor<'input> {
             string_table_manager,
             diagnostics: Default::default(),
             current_node_name: Default::default(),
+            current_node_language: Default::default(),
             _dummy: (),
         }
     }
@@ -48,6 +50,7 @@ impl<'input> ParseTreeVisitorCompat<'input> for StringTableGeneratorVisitor<'inp
 impl<'input> YarnSpinnerParserVisitorCompat<'input> for StringTableGeneratorVisitor<'input> {
     fn visit_node(&mut self, ctx: &NodeContext<'input>) -> Self::Return {
         let mut tags = Vec::new();
+        self.current_node_language = None;
         for header in ctx.header_all() {
             let header_key = header.header_key.as_ref().unwrap().get_text();
             if header_key == "title" {
@@ -68,6 +71,12 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for StringTableGeneratorVisi
                     .split_whitespace()
                     .map(ToOwned::to_owned)
                     .collect();
+            } else if header_key == "language" {
+                self.current_node_language = header
+                    .header_value
+                    .as_ref()
+                    .map(|header| header.get_text().trim().to_owned())
+                    .filter(|language| !language.is_empty());
             }
         }
         if !self.current_node_name.is_empty() && tags.contains(&"rawText".to_owned()) {
@@ -77,10 +86,11 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for StringTableGeneratorVisi
             self.string_table_manager.insert(
                 line_id,
                 StringInfo {
-                    text: ctx.body().unwrap().get_text(),
+                    text: unmask_substitution_providers(&ctx.body().unwrap().get_text()),
                     node_name: self.current_node_name.clone(),
                     line_number: ctx.body().unwrap().start().line as usize,
                     file_name: self.file.name.clone(),
+                    language: self.current_node_language.clone(),
                     ..Default::default()
                 },
             );
@@ -129,6 +139,7 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for StringTableGeneratorVisi
                 line_number,
                 file_name: self.file.name.clone(),
                 metadata: hashtag_texts,
+                language: self.current_node_language.clone(),
                 ..Default::default()
             },
         );
@@ -143,7 +154,7 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for StringTableGeneratorVisi
 /// `Hi there { some_expression }, how are you { another_expression } doing?`
 /// and turns it into
 /// `Hi there {0}, how are you {1}? doing`
-fn generate_formatted_text(ctx: &Line_formatted_textContext) -> String {
+pub(crate) fn generate_formatted_text(ctx: &Line_formatted_textContext) -> String {
     let mut expression_count = 0;
     let mut composed_string = String::new();
     // First, visit all of the nodes, which are either terminal
@@ -152,7 +163,7 @@ fn generate_formatted_text(ctx: &Line_formatted_textContext) -> String {
     // final string.
     for child in ctx.get_children() {
         if child.get_child_count() == 0 {
-            composed_string.push_str(&child.get_text());
+            composed_string.push_str(&unmask_substitution_providers(&child.get_text()));
         } else {
             // Expressions in the final string are denoted as the
             // index of the expression, surrounded by braces { }.
@@ -275,6 +286,7 @@ a {1 + 3} cool expression
                 file_name: "test.yarn".to_string(),
                 is_implicit_tag: true,
                 metadata: vec![],
+                language: None,
             }
         );
         assert_eq!(
@@ -286,6 +298,7 @@ a {1 + 3} cool expression
                 file_name: "test.yarn".to_string(),
                 is_implicit_tag: true,
                 metadata: vec![],
+                language: None,
             }
         );
         assert_eq!(
@@ -297,6 +310,7 @@ a {1 + 3} cool expression
                 file_name: "test.yarn".to_string(),
                 is_implicit_tag: true,
                 metadata: vec![],
+                language: None,
             }
         );
     }
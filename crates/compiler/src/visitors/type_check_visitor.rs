@@ -321,19 +321,50 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for TypeCheckVisitor<'input>
         };
         // Check each parameter of the function
         let supplied_parameters = ctx.function_call().unwrap().expression_all();
+        // Overloaded functions are checked against the overload that accepts this many arguments
+        let function_type =
+            match function_type.overload_for_argument_count(supplied_parameters.len()) {
+                Some(overload) => overload.clone(),
+                None => {
+                    let diagnostic = Diagnostic::from_message(format!(
+                        "Function \"{}\" has no overload that expects {} parameters",
+                        function_name,
+                        supplied_parameters.len()
+                    ))
+                    .with_file_name(&self.file.name)
+                    .with_parser_context(ctx, self.file.tokens());
+                    self.diagnostics.push(diagnostic);
+                    return *function_type.return_type;
+                }
+            };
+        let required_parameter_count = function_type.required_parameter_count();
         let expected_parameter_types = function_type.parameters;
+        let variadic_parameter_type = function_type.variadic_parameter_type.map(|r#type| *r#type);
+        let has_too_few_parameters = supplied_parameters.len() < required_parameter_count;
+        let has_too_many_parameters = variadic_parameter_type.is_none()
+            && supplied_parameters.len() > expected_parameter_types.len();
 
-        if supplied_parameters.len() != expected_parameter_types.len() {
+        if has_too_few_parameters || has_too_many_parameters {
             // Wrong number of parameters supplied
-            let parameters = if expected_parameter_types.len() == 1 {
+            let has_fixed_parameter_count = variadic_parameter_type.is_none()
+                && required_parameter_count == expected_parameter_types.len();
+            let (bound, parameter_count) = if has_fixed_parameter_count {
+                ("", required_parameter_count)
+            } else if has_too_few_parameters {
+                ("at least ", required_parameter_count)
+            } else {
+                ("at most ", expected_parameter_types.len())
+            };
+            let parameters = if parameter_count == 1 {
                 "parameter"
             } else {
                 "parameters"
             };
             let diagnostic = Diagnostic::from_message(format!(
-                "Function \"{}\" expects {} {}, but received {}",
+                "Function \"{}\" expects {}{} {}, but received {}",
                 function_name,
-                expected_parameter_types.len(),
+                bound,
+                parameter_count,
                 parameters,
                 supplied_parameters.len()
             ))
@@ -378,6 +409,27 @@ impl<'input> YarnSpinnerParserVisitorCompat<'input> for TypeCheckVisitor<'input>
                 return *function_type.return_type;
             }
         }
+        let variadic_parameters = supplied_parameters
+            .iter()
+            .cloned()
+            .enumerate()
+            .skip(expected_parameter_types.len());
+        for (i, supplied_parameter) in variadic_parameters {
+            let supplied_type = self.visit(supplied_parameter.as_ref());
+            if !supplied_type.is_sub_type_of(&variadic_parameter_type) {
+                let diagnostic = Diagnostic::from_message(format!(
+                    "{} parameter {} expects a {}, not a {}",
+                    function_name,
+                    i + 1,
+                    variadic_parameter_type.format(),
+                    supplied_type.format()
+                ))
+                .with_file_name(&self.file.name)
+                .with_parser_context(ctx, self.file.tokens());
+                self.diagnostics.push(diagnostic);
+                return *function_type.return_type;
+            }
+        }
         // Cool, all the parameters check out!
 
         // Finally, return the return type of this function.
@@ -0,0 +1,199 @@
+//! Identifies compiled programs by their content,
+//! so that hosts can detect when a program is paired with artifacts built for another version of the script, see [`Program::content_hash`].
+
+use crate::prelude::*;
+use std::fmt::{Display, Formatter};
+
+/// A hash identifying the content of a [`Program`], as returned by [`Program::content_hash`].
+///
+/// The hash is stable across platforms, Rust versions and serialization round-trips,
+/// so it can be stored alongside build artifacts and compared at runtime.
+/// It is displayed as 16 hexadecimal digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct ContentHash(pub u64);
+
+impl Display for ContentHash {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl Program {
+    /// Calculates a [`ContentHash`] over everything that affects how this program runs:
+    /// its nodes with their instructions, labels, tags and headers, and its initial values.
+    /// [`Program::name`] is not included.
+    ///
+    /// The order of [`Program::nodes`] and [`Program::initial_values`] does not matter,
+    /// so the hash is the same for a program that was compiled from the same source twice or that was serialized and deserialized.
+    pub fn content_hash(&self) -> ContentHash {
+        let mut hasher = StableHasher::default();
+        let mut nodes: Vec<_> = self.nodes.iter().collect();
+        nodes.sort_unstable_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+        hasher.write_usize(nodes.len());
+        for (name, node) in nodes {
+            hasher.write_str(name);
+            hasher.write_node(node);
+        }
+        let mut initial_values: Vec<_> = self.initial_values.iter().collect();
+        initial_values.sort_unstable_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+        hasher.write_usize(initial_values.len());
+        for (name, value) in initial_values {
+            hasher.write_str(name);
+            hasher.write_operand(value);
+        }
+        ContentHash(hasher.finish())
+    }
+
+    /// Returns the IDs of all lines and options that this program can deliver, sorted and without duplicates.
+    /// A string table that belongs to this program contains an entry for each of them.
+    pub fn line_ids(&self) -> Vec<LineId> {
+        let mut line_ids: Vec<_> = self
+            .nodes
+            .values()
+            .flat_map(|node| &node.instructions)
+            .filter(|instruction| {
+                [OpCode::RunLine as i32, OpCode::AddOption as i32].contains(&instruction.opcode)
+            })
+            .filter_map(|instruction| {
+                // Both RunLine and AddOption have the string ID they want to show as their first operand
+                let id: String = instruction.operands.first()?.clone().try_into().ok()?;
+                Some(LineId(id))
+            })
+            .collect();
+        line_ids.sort_unstable();
+        line_ids.dedup();
+        line_ids
+    }
+}
+
+/// A 64-bit FNV-1a hasher. Unlike the hashers in [`std`], its output is guaranteed to stay the same across Rust versions.
+/// All numbers are written in little-endian and all variable-length data is prefixed with its length, so the output is platform-independent and unambiguous.
+#[derive(Debug)]
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write(&(value as u64).to_le_bytes());
+    }
+
+    fn write_str(&mut self, value: &str) {
+        self.write_usize(value.len());
+        self.write(value.as_bytes());
+    }
+
+    fn write_node(&mut self, node: &Node) {
+        self.write_str(&node.name);
+        self.write_usize(node.instructions.len());
+        for instruction in &node.instructions {
+            self.write(&instruction.opcode.to_le_bytes());
+            self.write_usize(instruction.operands.len());
+            for operand in &instruction.operands {
+                self.write_operand(operand);
+            }
+        }
+        let mut labels: Vec<_> = node.labels.iter().collect();
+        labels.sort_unstable();
+        self.write_usize(labels.len());
+        for (label, position) in labels {
+            self.write_str(label);
+            self.write(&position.to_le_bytes());
+        }
+        self.write_usize(node.tags.len());
+        for tag in &node.tags {
+            self.write_str(tag);
+        }
+        self.write_str(&node.source_text_string_id);
+        self.write_usize(node.headers.len());
+        for header in &node.headers {
+            self.write_str(&header.key);
+            self.write_str(&header.value);
+        }
+    }
+
+    fn write_operand(&mut self, operand: &Operand) {
+        match &operand.value {
+            Some(OperandValue::StringValue(value)) => {
+                self.write(&[0]);
+                self.write_str(value);
+            }
+            Some(OperandValue::FloatValue(value)) => {
+                self.write(&[1]);
+                self.write(&value.to_bits().to_le_bytes());
+            }
+            Some(OperandValue::BoolValue(value)) => self.write(&[2, u8::from(*value)]),
+            None => self.write(&[3]),
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(line_id: &str) -> Program {
+        let node = Node {
+            name: "Start".to_owned(),
+            instructions: vec![
+                Instruction {
+                    opcode: OpCode::RunLine.into(),
+                    operands: vec![line_id.to_owned().into(), 0.0_f32.into()],
+                },
+                Instruction {
+                    opcode: OpCode::Stop.into(),
+                    operands: vec![],
+                },
+            ],
+            ..Default::default()
+        };
+        Program {
+            name: "Program".to_owned(),
+            nodes: [("Start".to_owned(), node)].into(),
+            initial_values: [
+                ("$a".to_owned(), true.into()),
+                ("$b".to_owned(), 1.0_f32.into()),
+            ]
+            .into(),
+        }
+    }
+
+    #[test]
+    fn content_hash_depends_on_content_only() {
+        let hash = program("line:a").content_hash();
+        assert_eq!(hash, program("line:a").content_hash());
+        assert_ne!(hash, program("line:b").content_hash());
+
+        let mut renamed = program("line:a");
+        renamed.name = "Other".to_owned();
+        assert_eq!(hash, renamed.content_hash());
+        assert_eq!(hash.to_string().len(), 16);
+    }
+
+    #[test]
+    fn collects_line_ids() {
+        assert_eq!(program("line:a").line_ids(), vec![LineId::from("line:a")]);
+    }
+}
@@ -0,0 +1,132 @@
+//! Controls how the conversion functions of the standard library handle values that cannot be converted.
+
+use crate::prelude::*;
+use std::cell::Cell;
+
+thread_local! {
+    /// The behavior of the dialogue that is currently calling a function on this thread.
+    static CURRENT_BEHAVIOR: Cell<ConversionFailureBehavior> = const { Cell::new(ConversionFailureBehavior::Error) };
+}
+
+/// Determines what happens when one of the conversion functions of [`Library::standard_library`](crate::prelude::Library::standard_library),
+/// i.e. `number`, `bool` and `string`, is called with a value that cannot be converted, e.g. `number("twelve")`.
+///
+/// Set it with `Dialogue::set_conversion_failure_behavior`. Outside of a dialogue, e.g. when the compiler evaluates an expression,
+/// [`ConversionFailureBehavior::Error`] is used.
+///
+/// The conversions follow the original implementation:
+/// - `number` accepts numbers, booleans (`true` is `1`, `false` is `0`) and strings that can be parsed as a number.
+/// - `bool` accepts booleans, numbers (anything but `0` is `true`) and the strings `"true"` and `"false"`.
+/// - `string` accepts any value and never fails.
+///
+/// ## Implementation notes
+///
+/// The original implementation throws an exception when a conversion fails. Converting a boolean to a string results in `"true"` or `"false"`,
+/// while the original implementation results in `"True"` or `"False"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum ConversionFailureBehavior {
+    /// The conversion function fails, so `Dialogue::continue_` returns a `DialogueError::FunctionFailed`.
+    #[default]
+    Error,
+    /// The conversion returns the default value of the target type, i.e. `0`, `false` or `""`.
+    DefaultValue,
+}
+
+/// Converts `value` with `convert`, handling a failure according to the [`ConversionFailureBehavior`] of the calling dialogue.
+/// `type_name` is the name of the target type used in the error message.
+pub(crate) fn convert_or_fail<T: Default>(
+    value: YarnValue,
+    type_name: &str,
+    convert: impl FnOnce(YarnValue) -> Result<T, YarnValueCastError>,
+) -> Result<T, String> {
+    match (convert(value), CURRENT_BEHAVIOR.with(Cell::get)) {
+        (Ok(converted), _) => Ok(converted),
+        (Err(_), ConversionFailureBehavior::DefaultValue) => Ok(T::default()),
+        (Err(error), ConversionFailureBehavior::Error) => Err(format!(
+            "Failed to convert a Yarn value to a {type_name}: {error:?}"
+        )),
+    }
+}
+
+/// Runs `f` with `behavior` as the [`ConversionFailureBehavior`] of the conversion functions. Used by the `Dialogue` when calling functions.
+#[doc(hidden)]
+pub fn with_conversion_failure_behavior<R>(
+    behavior: ConversionFailureBehavior,
+    f: impl FnOnce() -> R,
+) -> R {
+    /// Restores the previous behavior even if `f` panics.
+    struct Restore(ConversionFailureBehavior);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT_BEHAVIOR.with(|current| current.set(self.0));
+        }
+    }
+
+    let _restore = Restore(CURRENT_BEHAVIOR.with(|current| current.replace(behavior)));
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, value: YarnValue) -> YarnFnOutput {
+        Library::standard_library()
+            .get(name)
+            .unwrap()
+            .call_async(vec![value])
+    }
+
+    fn ready(output: YarnFnOutput) -> YarnValue {
+        let YarnFnOutput::Ready(value) = output else {
+            panic!("Expected the conversion to succeed");
+        };
+        value
+    }
+
+    #[test]
+    fn converts_valid_values() {
+        for behavior in [
+            ConversionFailureBehavior::Error,
+            ConversionFailureBehavior::DefaultValue,
+        ] {
+            with_conversion_failure_behavior(behavior, || {
+                assert_eq!(ready(call("number", "12".into())), YarnValue::from(12.0));
+                assert_eq!(ready(call("bool", 1.into())), YarnValue::from(true));
+                assert_eq!(ready(call("string", true.into())), YarnValue::from("true"));
+            });
+        }
+    }
+
+    #[test]
+    fn fails_on_invalid_value() {
+        let YarnFnOutput::Failed(error) = call("number", "twelve".into()) else {
+            panic!("Expected the conversion to fail");
+        };
+        assert_eq!(
+            error.to_string(),
+            "Failed to convert a Yarn value to a number: ParseFloatError(ParseFloatError { kind: Invalid })"
+        );
+    }
+
+    #[test]
+    fn returns_default_value_on_invalid_value() {
+        with_conversion_failure_behavior(ConversionFailureBehavior::DefaultValue, || {
+            assert_eq!(ready(call("number", "twelve".into())), YarnValue::from(0.0));
+            assert_eq!(ready(call("bool", "yes".into())), YarnValue::from(false));
+        });
+        // The previous behavior is restored afterwards
+        assert!(matches!(
+            call("number", "twelve".into()),
+            YarnFnOutput::Failed(_)
+        ));
+    }
+}
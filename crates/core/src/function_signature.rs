@@ -0,0 +1,135 @@
+//! Describes the functions in a [`Library`] in terms of Yarn types,
+//! for tools like debug UIs and documentation generators, see [`Library::signatures`].
+
+use crate::prelude::*;
+use std::fmt::{self, Display, Formatter};
+
+/// The signature of a function in a [`Library`], as returned by [`Library::signature`] and [`Library::signatures`].
+///
+/// Displayed like `substring(String, Number, Number) -> String`, with optional parameters marked by a `?`
+/// and a variadic last parameter written as `...Any`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct FunctionSignature {
+    /// The name the function is registered under.
+    pub name: String,
+    /// The types of the parameters, not including a variadic last parameter.
+    /// Parameters that accept any [`YarnValue`] are reported as [`Type::Any`].
+    pub parameter_types: Vec<Type>,
+    /// The number of parameters at the end of [`FunctionSignature::parameter_types`] that may be omitted.
+    pub optional_parameter_count: usize,
+    /// Whether the function accepts any number of additional arguments of any type after [`FunctionSignature::parameter_types`].
+    pub is_variadic: bool,
+    /// The type of the value returned by the function.
+    pub return_type: Type,
+    /// Whether the function was registered with [`Library::add_async_function`].
+    pub is_async: bool,
+}
+
+impl FunctionSignature {
+    /// Describes the given function, registered under the given name.
+    pub fn from_function(name: impl Into<String>, function: &dyn UntypedYarnFn) -> Self {
+        let yarn_type = |type_id| Type::try_from(type_id).unwrap_or(Type::Any);
+        let is_variadic = function.is_variadic();
+        let mut parameter_types = function.parameter_types();
+        if is_variadic {
+            parameter_types.pop();
+        }
+        Self {
+            name: name.into(),
+            parameter_types: parameter_types.into_iter().map(yarn_type).collect(),
+            optional_parameter_count: function.optional_parameter_count(),
+            is_variadic,
+            return_type: yarn_type(function.return_type()),
+            is_async: function.is_async(),
+        }
+    }
+
+    /// The number of arguments that must be passed to the function.
+    pub fn required_parameter_count(&self) -> usize {
+        self.parameter_types.len() - self.optional_parameter_count
+    }
+
+    /// The maximum number of arguments that may be passed to the function, or [`None`] if it is variadic.
+    pub fn max_parameter_count(&self) -> Option<usize> {
+        (!self.is_variadic).then_some(self.parameter_types.len())
+    }
+
+    /// Returns `true` if the function may be called with the given number of arguments.
+    pub fn accepts_parameter_count(&self, count: usize) -> bool {
+        count >= self.required_parameter_count()
+            && self
+                .max_parameter_count()
+                .map_or(true, |max_parameter_count| count <= max_parameter_count)
+    }
+}
+
+impl Display for FunctionSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let required_parameter_count = self.required_parameter_count();
+        let mut parameters: Vec<_> = self
+            .parameter_types
+            .iter()
+            .enumerate()
+            .map(|(index, r#type)| {
+                if index >= required_parameter_count {
+                    format!("{type}?")
+                } else {
+                    r#type.to_string()
+                }
+            })
+            .collect();
+        if self.is_variadic {
+            parameters.push("...Any".to_owned());
+        }
+        write!(
+            f,
+            "{}({}) -> {}",
+            self.name,
+            parameters.join(", "),
+            self.return_type
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_functions_in_yarn_types() {
+        let mut library = Library::new();
+        library
+            .add_function("greet", |name: String, greeting: Option<String>| {
+                format!("{}, {name}", greeting.unwrap_or_default())
+            })
+            .add_function("sum", |first: f32, rest: &[YarnValue]| {
+                first + rest.len() as f32
+            });
+
+        let signatures = library.signatures();
+        assert_eq!(
+            signatures
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "greet(String, String?) -> String",
+                "sum(Number, ...Any) -> Number"
+            ]
+        );
+        assert_eq!(signatures[0].required_parameter_count(), 1);
+        assert!(signatures[0].accepts_parameter_count(2));
+        assert!(!signatures[0].accepts_parameter_count(3));
+        assert_eq!(signatures[1].max_parameter_count(), None);
+        assert!(signatures[1].accepts_parameter_count(5));
+        assert_eq!(library.signature("sum"), Some(signatures[1].clone()));
+        assert_eq!(library.signature("missing"), None);
+    }
+}
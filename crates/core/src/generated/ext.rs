@@ -1,6 +1,7 @@
 //! Contains extensions to generated types that in the original implementation are sprinkled around the repo via partial classes
 
 use crate::prelude::*;
+use std::collections::HashMap;
 use std::fmt::{Debug, Display};
 use thiserror::Error;
 
@@ -134,7 +135,54 @@ impl Program {
     }
 }
 
+impl Node {
+    /// Groups the headers of this node by their key. A key maps to multiple values if the header appears multiple times,
+    /// e.g. several `when` headers. The values are in the order they appear in the source.
+    pub fn headers_by_key(&self) -> HashMap<&str, Vec<&str>> {
+        self.headers
+            .iter()
+            .fold(HashMap::new(), |mut map: HashMap<_, Vec<_>>, header| {
+                map.entry(header.key.as_str())
+                    .or_default()
+                    .push(header.value.as_str());
+                map
+            })
+    }
+
+    /// Iterates over the values of all headers of this node with the given key, in the order they appear in the source.
+    pub fn header_values<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> {
+        self.headers
+            .iter()
+            .filter(move |header| header.key == key)
+            .map(|header| header.value.as_str())
+    }
+
+    /// Iterates over the destinations of all `<<jump>>`s to a fixed node name, which are compiled to a `PushString` followed by a `RunNode`.
+    /// Jumps to an expression, e.g. `<<jump {$destination}>>`, cannot be resolved without running the node and are not included.
+    /// A destination is returned once per jump, so it may appear multiple times.
+    pub fn jump_destinations(&self) -> impl Iterator<Item = String> + '_ {
+        self.instructions.windows(2).filter_map(|instructions| {
+            let [push, run] = instructions else {
+                unreachable!()
+            };
+            (push.opcode() == OpCode::PushString && run.opcode() == OpCode::RunNode)
+                .then(|| push.read_operand(0))
+        })
+    }
+}
+
 impl Instruction {
+    /// The first opcode of the range reserved for custom instructions, i.e. all opcodes from this value upwards.
+    /// Yarn Spinner will never use these for its own [`OpCode`]s, so custom compiler passes can emit them and
+    /// have them executed by an instruction handler registered on the runtime's `Dialogue`.
+    pub const FIRST_EXTENSION_OPCODE: i32 = 1000;
+
+    /// Returns `true` if this instruction's opcode is in the range reserved for custom instructions.
+    /// See [`Instruction::FIRST_EXTENSION_OPCODE`].
+    pub fn is_extension(&self) -> bool {
+        self.opcode >= Self::FIRST_EXTENSION_OPCODE
+    }
+
     pub fn read_operand<T>(&self, index: usize) -> T
     where
         T: TryFrom<Operand>,
@@ -145,4 +193,12 @@ impl Instruction {
             .try_into()
             .unwrap_or_else(|e| panic!("Failed to convert operand {index}: {e:?}",))
     }
+
+    /// Fallible version of [`Instruction::read_operand`]. Returns [`None`] if the operand is missing or cannot be converted to `T`.
+    pub fn try_read_operand<T>(&self, index: usize) -> Option<T>
+    where
+        T: TryFrom<Operand>,
+    {
+        self.operands.get(index)?.clone().try_into().ok()
+    }
 }
@@ -6,7 +6,10 @@
 //! - If you wish to write an adapter crate for an engine yourself, use the [`yarnspinner`](https://crates.io/crates/yarnspinner) crate.
 
 #![warn(missing_docs, missing_debug_implementations)]
+mod content_hash;
+mod conversion;
 mod feature_gates;
+mod function_signature;
 mod generated;
 mod internal_value;
 mod library;
@@ -14,6 +17,7 @@ mod line_id;
 mod operator;
 mod position;
 pub mod types;
+mod variable_declaration;
 mod yarn_fn;
 mod yarn_value;
 
@@ -23,6 +27,9 @@ pub mod prelude {
     pub use crate::feature_gates::*;
 
     pub use crate::{
+        content_hash::*,
+        conversion::{with_conversion_failure_behavior, ConversionFailureBehavior},
+        function_signature::*,
         generated::{
             instruction::OpCode, operand::Value as OperandValue, Header, Instruction,
             InvalidOpCodeError, Node, Operand, Program,
@@ -33,6 +40,7 @@ pub mod prelude {
         operator::*,
         position::*,
         types::Type,
+        variable_declaration::*,
         yarn_fn::*,
         yarn_value::*,
     };
@@ -1,9 +1,11 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner/Library.cs>
 
+use crate::conversion::convert_or_fail;
 use crate::prelude::*;
 use std::borrow::Cow;
 use std::collections::hash_map;
 use std::fmt::Display;
+use thiserror::Error;
 
 /// A collection of functions that can be called from Yarn scripts.
 ///
@@ -69,13 +71,133 @@ impl Library {
     /// - `string`: Converts a value to a string.
     /// - `number`: Converts a value to a number.
     /// - `bool`: Converts a value to a boolean.
+    /// - `length`: Returns the number of characters in a string.
+    /// - `string_length`: An alias of `length`.
+    /// - `substring`: Returns `length` characters of a string, starting at the character index `start`.
+    ///   Fractional arguments are truncated, and negative or out of bounds ones are clamped, e.g. `substring("abc", -1, 2)` is `"ab"`.
+    /// - `to_upper`: Converts a string to uppercase.
+    /// - `to_lower`: Converts a string to lowercase.
+    /// - `contains`: Returns whether a string contains another string.
+    /// - `split_count`: Returns the number of parts a string is split into by a separator. An empty separator does not split the string.
+    /// - `trim`: Removes leading and trailing whitespace from a string.
+    /// - `replace`: Replaces all occurrences of a pattern in a string with a replacement. An empty pattern leaves the string unchanged.
+    /// - `round`: Rounds a number to the nearest integer.
+    /// - `round_places`: Rounds a number to the given number of decimal places.
+    /// - `floor`: Rounds a number down to the nearest integer.
+    /// - `ceil`: Rounds a number up to the nearest integer.
+    /// - `inc`: Increments a number to the next integer. Numbers with a decimal part are rounded up instead.
+    /// - `dec`: Decrements a number to the previous integer. Numbers with a decimal part are rounded down instead.
+    /// - `decimal`: Returns the decimal part of a number.
+    /// - `int`: Returns the integer part of a number, i.e. rounds towards zero.
+    /// - `min`: Returns the smallest of one or more numbers. Fails the dialogue if one of the arguments after the first is not a number.
+    /// - `max`: Returns the largest of one or more numbers. Fails the dialogue if one of the arguments after the first is not a number.
+    /// - `clamp`: Restricts a number to the range between `min` and `max`.
+    /// - `lerp`: Linearly interpolates between `from` and `to` by the factor `t`. `t` is not clamped.
+    /// - `abs`: Returns the absolute value of a number.
+    /// - `sign`: Returns `-1` for negative numbers, `1` for positive numbers and `0` for zero.
+    /// - `pow`: Raises a number to the given power.
+    /// - `sqrt`: Returns the square root of a number.
+    /// - `sin`, `cos`, `tan`: The trigonometric functions of an angle in radians.
+    /// - `asin`, `acos`, `atan`: The inverse trigonometric functions, returning an angle in radians.
+    /// - `atan2`: Returns the angle in radians of the point (`x`, `y`), called as `atan2(y, x)`.
+    /// - `to_radians`, `to_degrees`: Convert an angle between degrees and radians.
     /// - Comparison operators for numbers, strings, and booleans. (`==`, `!=`, `<`, `<=`, `>`, `>=`)
+    ///
+    /// `visited` and `visited_count` are not included, as they need access to the variable storage.
+    /// They are added by the runtime's `Dialogue` and are always known to the compiler.
+    ///
+    /// ## Implementation notes
+    ///
+    /// The math functions are not part of the original `Library`, but of the Unity integration's `Functions` class.
+    /// They are included here so that they are available in every runtime. Just like in C#, rounding uses banker's rounding,
+    /// i.e. midpoints are rounded to the nearest even number.
+    /// `min` and `max` accept more than two arguments, and `clamp`, `lerp`, `abs`, `sign` and the trigonometric functions have no C# counterpart.
     pub fn standard_library() -> Self {
+        let length = |string: &str| string.chars().count();
         let mut library = yarn_library!(
             "string" => <String as From<YarnValue >>::from,
-            "number" => |value: YarnValue| f32::try_from(value).expect("Failed to convert a Yarn value to a number"),
-            "bool" => |value: YarnValue| bool::try_from(value).expect("Failed to convert a Yarn value to a bool"),
+            "length" => length,
+            "string_length" => length,
+            // Casting a float to an integer truncates it and saturates at 0, so this also handles negative numbers
+            "substring" => |string: &str, start: f32, length: f32| {
+                string.chars().skip(start as usize).take(length as usize).collect::<String>()
+            },
+            "to_upper" => |string: &str| string.to_uppercase(),
+            "to_lower" => |string: &str| string.to_lowercase(),
+            "contains" => |string: &str, substring: &str| string.contains(substring),
+            "split_count" => |string: &str, separator: &str| {
+                if separator.is_empty() {
+                    1
+                } else {
+                    string.split(separator).count()
+                }
+            },
+            "trim" => |string: &str| string.trim().to_owned(),
+            "replace" => |string: &str, pattern: &str, replacement: &str| {
+                if pattern.is_empty() {
+                    string.to_owned()
+                } else {
+                    string.replace(pattern, replacement)
+                }
+            },
+            "round" => |num: f32| num.round_ties_even() as i32,
+            "round_places" => |num: f32, places: u32| num.round_places(places),
+            "floor" => |num: f32| num.floor() as i32,
+            "ceil" => |num: f32| num.ceil() as i32,
+            "inc" => |num: f32| {
+                if num.fract() != 0.0 {
+                    num.ceil() as i32
+                } else {
+                    num as i32 + 1
+                }
+            },
+            "dec" => |num: f32| {
+                if num.fract() != 0.0 {
+                    num.floor() as i32
+                } else {
+                    num as i32 - 1
+                }
+            },
+            "decimal" => |num: f32| num.fract(),
+            "int" => |num: f32| num.trunc() as i32,
+            "clamp" => |num: f32, min: f32, max: f32| num.max(min).min(max),
+            "lerp" => |from: f32, to: f32, t: f32| from + (to - from) * t,
+            "abs" => |num: f32| num.abs(),
+            "sign" => |num: f32| {
+                if num == 0.0 {
+                    0
+                } else {
+                    num.signum() as i32
+                }
+            },
+            "pow" => |base: f32, exponent: f32| base.powf(exponent),
+            "sqrt" => |num: f32| num.sqrt(),
+            "sin" => |angle: f32| angle.sin(),
+            "cos" => |angle: f32| angle.cos(),
+            "tan" => |angle: f32| angle.tan(),
+            "asin" => |num: f32| num.asin(),
+            "acos" => |num: f32| num.acos(),
+            "atan" => |num: f32| num.atan(),
+            "atan2" => |y: f32, x: f32| y.atan2(x),
+            "to_radians" => |degrees: f32| degrees.to_radians(),
+            "to_degrees" => |radians: f32| radians.to_degrees(),
         );
+        // How a failed conversion is handled depends on the dialogue calling the function, see `ConversionFailureBehavior`
+        library
+            .add_fallible_function("number", |value: YarnValue| {
+                convert_or_fail(value, "number", f32::try_from)
+            })
+            .add_fallible_function("bool", |value: YarnValue| {
+                convert_or_fail(value, "bool", bool::try_from)
+            });
+        // The variadic arguments are not type checked, so passing a non-number fails the dialogue instead of panicking
+        library
+            .add_fallible_function("min", |first: f32, rest: &[YarnValue]| {
+                fold_numbers(first, rest, f32::min)
+            })
+            .add_fallible_function("max", |first: f32, rest: &[YarnValue]| {
+                fold_numbers(first, rest, f32::max)
+            });
         for r#type in [Type::Number, Type::String, Type::Boolean] {
             library.add_methods(r#type);
         }
@@ -122,6 +244,201 @@ impl Library {
         self
     }
 
+    /// Adds a function as an overload of the function already registered under the same name.
+    /// Yarn scripts can then call either of them, and the number of arguments decides which one is called,
+    /// e.g. `random()`, `random(max)` and `random(min, max)`. The compiler type checks each call against the matching overload.
+    /// If there is no function with the given name yet, this is the same as [`Library::add_function`].
+    ///
+    /// ## Panics
+    ///
+    /// Panics if an existing overload accepts a number of arguments that `function` accepts as well,
+    /// taking optional and variadic parameters into account, as calls to them could not be told apart.
+    /// Use [`Library::try_add_overload`] to handle this case instead.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use yarnspinner_core::prelude::*;
+    /// # let mut library = Library::default();
+    /// library
+    ///     .add_overload("random", || 0.5_f32)
+    ///     .add_overload("random", |max: f32| max / 2.0)
+    ///     .add_overload("random", |min: f32, max: f32| (min + max) / 2.0);
+    /// ```
+    pub fn add_overload<Marker, F>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        function: F,
+    ) -> &mut Self
+    where
+        Marker: 'static,
+        F: YarnFn<Marker> + 'static + Clone,
+        F::Out: IntoYarnValueFromNonYarnValue + 'static + Clone,
+    {
+        if let Err(error) = self.try_add_overload(name, function) {
+            panic!("{error}");
+        }
+        self
+    }
+
+    /// Like [`Library::add_overload`], but returns an error and leaves the library untouched
+    /// if an existing overload accepts a number of arguments that `function` accepts as well.
+    pub fn try_add_overload<Marker, F>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        function: F,
+    ) -> Result<&mut Self, AmbiguousOverloadError>
+    where
+        Marker: 'static,
+        F: YarnFn<Marker> + 'static + Clone,
+        F::Out: IntoYarnValueFromNonYarnValue + 'static + Clone,
+    {
+        self.0
+            .add_overload(name, Box::new(YarnFnWrapper::from(function)))?;
+        Ok(self)
+    }
+
+    /// Adds a function that returns a [`YarnFuture`], e.g. one that fetches data from an online service.
+    /// To Yarn, it looks like a regular function returning the type the future resolves to.
+    ///
+    /// When a [`Dialogue`](https://docs.rs/yarnspinner_runtime/latest/yarnspinner_runtime/struct.Dialogue.html) calls this function,
+    /// it polls the future once and, if it is not ready yet, pauses and emits a `DialogueEvent::FunctionPending`.
+    /// Every following call to `Dialogue::continue_` polls the future again, and the dialogue resumes once it is resolved.
+    ///
+    /// The compiler cannot evaluate async functions, so they cannot be used in the initial values of smart variables
+    /// or other expressions the compiler needs to know at compile time.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use yarnspinner_core::prelude::*;
+    /// # let mut library = Library::default();
+    /// library.add_async_function("fetch_player_name", fetch_player_name);
+    ///
+    /// fn fetch_player_name() -> YarnFuture<String> {
+    ///     YarnFuture::new(async { "Alice".to_owned() })
+    /// }
+    /// ```
+    pub fn add_async_function<Marker, F, T>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        function: F,
+    ) -> &mut Self
+    where
+        Marker: 'static,
+        F: YarnFn<Marker, Out = YarnFuture<T>> + 'static,
+        T: IntoYarnValueFromNonYarnValue + Send + 'static,
+    {
+        self.0
+            .add_boxed(name, Box::new(AsyncYarnFnWrapper::from(function)));
+        self
+    }
+
+    /// Adds a function that returns a [`Result`], e.g. one that parses its input or looks up a game object that might not exist.
+    /// To Yarn, it looks like a regular function returning the type of the [`Ok`] value.
+    ///
+    /// When the function returns an [`Err`], `Dialogue::continue_` fails with a `DialogueError::FunctionFailed`
+    /// that contains the error and the node and instruction that called the function, instead of the function having to panic or make up a value.
+    /// Any error type that can be converted into a [`YarnFnError`] is allowed, including [`String`] and all [`std::error::Error`]s.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use yarnspinner_core::prelude::*;
+    /// # let mut library = Library::default();
+    /// library.add_fallible_function("parse_number", parse_number);
+    ///
+    /// fn parse_number(text: &str) -> Result<f32, std::num::ParseFloatError> {
+    ///     text.parse()
+    /// }
+    /// ```
+    pub fn add_fallible_function<Marker, F, T, E>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        function: F,
+    ) -> &mut Self
+    where
+        Marker: 'static,
+        F: YarnFn<Marker, Out = Result<T, E>> + 'static,
+        T: IntoYarnValueFromNonYarnValue + 'static,
+        E: Into<YarnFnError> + 'static,
+    {
+        self.0
+            .add_boxed(name, Box::new(FallibleYarnFnWrapper::from(function)));
+        self
+    }
+
+    /// Replaces the implementation of a function that is already in the library, e.g. when gameplay code is hot patched at runtime.
+    /// Since a [`Dialogue`](https://docs.rs/yarnspinner_runtime/latest/yarnspinner_runtime/struct.Dialogue.html) looks up functions by name on every call,
+    /// the new implementation is used from the next call on, without recompiling or recreating anything.
+    ///
+    /// The new function must have the same signature as the old one in terms of Yarn types, as compiled Yarn files were type checked against it.
+    /// For example, replacing a function taking an `f32` with one taking an `i32` is fine, since both are a `Number` in Yarn.
+    /// For functions registered with [`Library::add_overload`], only the overload with the same signature is replaced.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error and leaves the library untouched if there is no function with the given name or if the signatures differ,
+    /// i.e. for overloaded functions, if none of the overloads has the same signature.
+    /// Use [`Library::add_function`] to register a function regardless of its previous signature.
+    pub fn replace_function<Marker, F>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        function: F,
+    ) -> Result<&mut Self, FunctionReplacementError>
+    where
+        Marker: 'static,
+        F: YarnFn<Marker> + 'static + Clone,
+        F::Out: IntoYarnValueFromNonYarnValue + 'static + Clone,
+    {
+        let name = name.into();
+        let Some(old_function) = self.get(&name) else {
+            return Err(FunctionReplacementError::FunctionNotFound {
+                function_name: name.into_owned(),
+            });
+        };
+        let new_function: Box<dyn UntypedYarnFn> = Box::new(YarnFnWrapper::from(function));
+        let new_signature = yarn_signature(new_function.as_ref());
+        let overloads = old_function.overloads();
+        if overloads.is_empty() {
+            let old_signature = yarn_signature(old_function);
+            if old_signature != new_signature {
+                return Err(FunctionReplacementError::SignatureMismatch {
+                    function_name: name.into_owned(),
+                    old_signature,
+                    new_signature,
+                });
+            }
+            self.0.add_boxed(name, new_function);
+            return Ok(self);
+        }
+
+        let old_signatures: Vec<_> = overloads
+            .iter()
+            .map(|overload| yarn_signature(*overload))
+            .collect();
+        let Some(index) = old_signatures
+            .iter()
+            .position(|old_signature| *old_signature == new_signature)
+        else {
+            return Err(FunctionReplacementError::SignatureMismatch {
+                function_name: name.into_owned(),
+                old_signature: old_signatures.join(" | "),
+                new_signature,
+            });
+        };
+        let mut new_overloads: Vec<_> = overloads
+            .into_iter()
+            .map(|overload| overload.clone_box())
+            .collect();
+        new_overloads[index] = new_function;
+        self.0.add_boxed(
+            name,
+            Box::new(OverloadedYarnFn::from_overloads(new_overloads)),
+        );
+        Ok(self)
+    }
+
     /// Returns `true` if the library contains a function with the given name.
     pub fn contains_function(&self, name: &str) -> bool {
         self.0.contains_function(name)
@@ -137,6 +454,36 @@ impl Library {
         self.0.functions()
     }
 
+    /// Returns the signature of the function with the given name, if there is one.
+    /// For functions with overloads, this is the overload with the fewest parameters. Use [`Library::signatures`] to get all of them.
+    pub fn signature(&self, name: &str) -> Option<FunctionSignature> {
+        self.get(name)
+            .map(|function| FunctionSignature::from_function(name, function))
+    }
+
+    /// Returns the signatures of all functions in the library, sorted by name.
+    /// Includes the operators of the built-in types, e.g. `Number.Add`.
+    /// Functions with overloads have one signature per overload, sorted by the number of parameters they require.
+    pub fn signatures(&self) -> Vec<FunctionSignature> {
+        let mut signatures: Vec<_> = self
+            .iter()
+            .flat_map(|(name, function)| {
+                let overloads = function.overloads();
+                let functions = if overloads.is_empty() {
+                    vec![function]
+                } else {
+                    overloads
+                };
+                functions
+                    .into_iter()
+                    .map(move |function| FunctionSignature::from_function(name, function))
+            })
+            .collect();
+        // Stable, so overloads stay in the order of their parameter counts
+        signatures.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+        signatures
+    }
+
     /// Registers the methods found inside a type.
     fn add_methods(&mut self, r#type: Type) {
         for (name, function) in r#type.methods().into_iter() {
@@ -159,6 +506,68 @@ impl Display for Library {
     }
 }
 
+/// Folds `first` and all `rest` into one number, failing if one of `rest` is not a number.
+fn fold_numbers(
+    first: f32,
+    rest: &[YarnValue],
+    fold: fn(f32, f32) -> f32,
+) -> Result<f32, YarnValueCastError> {
+    rest.iter().try_fold(first, |acc, value| {
+        Ok(fold(acc, f32::try_from(value.clone())?))
+    })
+}
+
+/// Formats the signature of a function in terms of Yarn types without its name, e.g. `(Number, String) -> Bool`.
+fn yarn_signature(function: &dyn UntypedYarnFn) -> String {
+    FunctionSignature::from_function("", function).to_string()
+}
+
+/// An error returned by [`Library::replace_function`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FunctionReplacementError {
+    /// There is no function with the given name to replace.
+    #[error("Cannot replace function \"{function_name}\" because it is not in the library")]
+    FunctionNotFound {
+        /// The name of the function.
+        function_name: String,
+    },
+
+    /// The new function has a different signature than the one it should replace.
+    #[error("Cannot replace function \"{function_name}\" because its signature changed from {old_signature} to {new_signature}. Yarn files calling it need to be recompiled")]
+    SignatureMismatch {
+        /// The name of the function.
+        function_name: String,
+        /// The signature of the registered function in Yarn types, e.g. `(Number) -> String`.
+        old_signature: String,
+        /// The signature of the new function in Yarn types.
+        new_signature: String,
+    },
+}
+
+/// An error returned by [`Library::try_add_overload`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Function \"{function_name}\" already has an overload that accepts the same number of arguments as the added one, so calls to them could not be told apart")]
+pub struct AmbiguousOverloadError {
+    /// The name of the function.
+    pub function_name: String,
+}
+
+trait FloatExt: Copy {
+    fn round_places(self, places: u32) -> Self;
+}
+
+impl FloatExt for f32 {
+    fn round_places(self, places: u32) -> Self {
+        let factor = 10_f32.powi(i32::try_from(places).unwrap_or(i32::MAX));
+        let scaled = self * factor;
+        // An f32 has no decimal places left to round at that scale
+        if !scaled.is_finite() {
+            return self;
+        }
+        scaled.round_ties_even() / factor
+    }
+}
+
 /// Create a [`Library`] from a list of named functions.
 ///
 /// ## Example
@@ -188,3 +597,187 @@ macro_rules! yarn_library {
     };
 }
 pub use yarn_library;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, parameters: Vec<YarnValue>) -> YarnValue {
+        Library::standard_library()
+            .get(name)
+            .unwrap()
+            .call(parameters)
+    }
+
+    #[test]
+    fn standard_library_has_string_functions() {
+        assert_eq!(call("length", vec!["héllo".into()]), 5.into());
+        assert_eq!(
+            call("substring", vec!["hello world".into(), 6.into(), 20.into()]),
+            "world".into()
+        );
+        assert_eq!(
+            call("substring", vec!["abc".into(), (-1).into(), 2.into()]),
+            "ab".into()
+        );
+        assert_eq!(
+            call("substring", vec!["abc".into(), 1.9.into(), (-2).into()]),
+            "".into()
+        );
+        assert_eq!(call("string_length", vec!["héllo".into()]), 5.into());
+        assert_eq!(call("to_upper", vec!["hello".into()]), "HELLO".into());
+        assert_eq!(call("to_lower", vec!["HeLLo".into()]), "hello".into());
+        assert_eq!(
+            call("contains", vec!["hello".into(), "ell".into()]),
+            true.into()
+        );
+        assert_eq!(
+            call("split_count", vec!["a,b,c".into(), ",".into()]),
+            3.into()
+        );
+        assert_eq!(call("split_count", vec!["abc".into(), "".into()]), 1.into());
+        assert_eq!(call("trim", vec!["  hello \n".into()]), "hello".into());
+        assert_eq!(
+            call("replace", vec!["a-b-c".into(), "-".into(), "+".into()]),
+            "a+b+c".into()
+        );
+        assert_eq!(
+            call("replace", vec!["abc".into(), "".into(), "+".into()]),
+            "abc".into()
+        );
+    }
+
+    #[test]
+    fn rounds_places() {
+        for (num, places, expected) in [
+            (1.0, 0, 1.0),
+            (1.2, 1, 1.2),
+            (0.4, 0, 0.0),
+            (43.132, 0, 43.0),
+            (1.1, 2, 1.1),
+            (123.123, 3, 123.123),
+            (-10.3, 1, -10.3),
+            (-11.99, 1, -12.0),
+            (2.5, 0, 2.0),
+        ] {
+            assert_eq!(expected, num.round_places(places));
+        }
+    }
+
+    #[test]
+    fn rounds_to_many_places_without_overflowing() {
+        assert!((1.5_f32.round_places(10) - 1.5).abs() < f32::EPSILON);
+        for places in [40, u32::MAX] {
+            assert_eq!(1.5, 1.5_f32.round_places(places));
+            assert_eq!(-123.123, (-123.123_f32).round_places(places));
+        }
+    }
+
+    #[test]
+    fn replaces_function_with_same_signature() {
+        let mut library = Library::new();
+        library.add_function("double", |value: f32| value * 2.0);
+        library
+            .replace_function("double", |value: i32| value * 3)
+            .unwrap();
+        let result = library.get("double").unwrap().call(vec![2.into()]);
+        assert_eq!(result, 6.into());
+    }
+
+    #[test]
+    fn rejects_replacement_with_different_signature() {
+        let mut library = Library::new();
+        library.add_function("double", |value: f32| value * 2.0);
+        let error = library
+            .replace_function("double", |value: String| value.repeat(2))
+            .unwrap_err();
+        assert_eq!(
+            error,
+            FunctionReplacementError::SignatureMismatch {
+                function_name: "double".to_owned(),
+                old_signature: "(Number) -> Number".to_owned(),
+                new_signature: "(String) -> String".to_owned(),
+            }
+        );
+        assert!(library
+            .replace_function("triple", |value: f32| value * 3.0)
+            .is_err());
+    }
+
+    #[test]
+    fn replaces_only_the_overload_with_the_same_signature() {
+        let mut library = Library::new();
+        library
+            .add_overload("random", || 1.0_f32)
+            .add_overload("random", |max: f32| max);
+        library
+            .replace_function("random", |max: f32| max * 2.0)
+            .unwrap();
+        let random = library.get("random").unwrap();
+        assert_eq!(random.overloads().len(), 2);
+        assert_eq!(random.call(vec![]), YarnValue::from(1.0));
+        assert_eq!(random.call(vec![3.0.into()]), YarnValue::from(6.0));
+
+        let error = library
+            .replace_function("random", |name: String| name)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            FunctionReplacementError::SignatureMismatch {
+                function_name: "random".to_owned(),
+                old_signature: "() -> Number | (Number) -> Number".to_owned(),
+                new_signature: "(String) -> String".to_owned(),
+            }
+        );
+        assert_eq!(library.get("random").unwrap().overloads().len(), 2);
+    }
+
+    #[test]
+    fn standard_library_has_math_functions() {
+        assert_eq!(call("round", vec![2.5.into()]), 2.into());
+        assert_eq!(call("round", vec![3.5.into()]), 4.into());
+        assert_eq!(call("floor", vec![(-1.5).into()]), (-2).into());
+        assert_eq!(call("ceil", vec![(-1.5).into()]), (-1).into());
+        assert_eq!(call("inc", vec![1.into()]), 2.into());
+        assert_eq!(call("inc", vec![(-1.5).into()]), (-1).into());
+        assert_eq!(call("dec", vec![1.into()]), 0.into());
+        assert_eq!(call("dec", vec![(-1.5).into()]), (-2).into());
+        assert_eq!(call("decimal", vec![(-1.25).into()]), (-0.25).into());
+        assert_eq!(call("int", vec![(-1.75).into()]), (-1).into());
+        assert_eq!(call("min", vec![1.into(), 2.into()]), 1.into());
+        assert_eq!(call("max", vec![1.into(), 2.into()]), 2.into());
+        assert_eq!(call("min", vec![3.into()]), 3.into());
+        assert_eq!(
+            call("max", vec![1.into(), 5.into(), (-2).into(), 4.into()]),
+            5.into()
+        );
+        let library = Library::standard_library();
+        assert!(matches!(
+            library
+                .get("max")
+                .unwrap()
+                .call_async(vec![1.into(), "five".into()]),
+            YarnFnOutput::Failed(_)
+        ));
+        assert_eq!(
+            call("clamp", vec![12.into(), 0.into(), 10.into()]),
+            10.into()
+        );
+        assert_eq!(
+            call("clamp", vec![(-1).into(), 0.into(), 10.into()]),
+            0.into()
+        );
+        assert_eq!(
+            call("lerp", vec![10.into(), 20.into(), 0.25.into()]),
+            12.5.into()
+        );
+        assert_eq!(call("abs", vec![(-3).into()]), 3.into());
+        assert_eq!(call("sign", vec![(-0.5).into()]), (-1).into());
+        assert_eq!(call("sign", vec![0.into()]), 0.into());
+        assert_eq!(call("pow", vec![2.into(), 3.into()]), 8.into());
+        assert_eq!(call("sqrt", vec![9.into()]), 3.into());
+        assert_eq!(call("sin", vec![0.into()]), 0.into());
+        assert_eq!(call("cos", vec![0.into()]), 1.into());
+        assert_eq!(call("atan2", vec![0.into(), 1.into()]), 0.into());
+    }
+}
@@ -30,10 +30,25 @@ pub struct FunctionType {
     /// (also known as the function's *arity*).
     pub parameters: Vec<Option<Type>>,
 
+    /// The number of parameters at the end of [`FunctionType::parameters`] that may be omitted when calling this function.
+    pub optional_parameter_count: usize,
+
+    #[cfg_attr(feature = "bevy", reflect(ignore))]
+    /// The type of the arguments that may follow the ones for [`FunctionType::parameters`], if this function is variadic.
+    /// A variadic function accepts any number of such arguments, including none.
+    // Needs to be on the heap because of type recursion
+    pub variadic_parameter_type: Option<Box<Type>>,
+
     #[cfg_attr(feature = "bevy", reflect(ignore))]
     ///The type of value that this function returns.
     // Needs to be on the heap because of type recursion
     pub return_type: Box<Option<Type>>,
+
+    #[cfg_attr(feature = "bevy", reflect(ignore))]
+    /// Other signatures this function can be called with, each accepting a different number of arguments.
+    /// Calls are checked against the signature that accepts the number of arguments passed, see [`FunctionType::overload_for_argument_count`].
+    /// Created for functions registered with [`Library::add_overload`].
+    pub overloads: Vec<FunctionType>,
 }
 
 impl From<FunctionType> for Type {
@@ -54,6 +69,52 @@ impl FunctionType {
         self.parameters.push(parameter.into());
         self
     }
+
+    /// Sets how many of the last parameters may be omitted, see [`FunctionType::optional_parameter_count`].
+    pub fn set_optional_parameter_count(&mut self, optional_parameter_count: usize) -> &mut Self {
+        self.optional_parameter_count = optional_parameter_count;
+        self
+    }
+
+    /// The number of parameters that must be passed when calling this function.
+    pub fn required_parameter_count(&self) -> usize {
+        self.parameters
+            .len()
+            .saturating_sub(self.optional_parameter_count)
+    }
+
+    /// Makes this function variadic, see [`FunctionType::variadic_parameter_type`].
+    pub fn set_variadic_parameter_type(
+        &mut self,
+        variadic_parameter_type: impl Into<Option<Type>>,
+    ) -> &mut Self {
+        self.variadic_parameter_type = variadic_parameter_type.into().map(Box::new);
+        self
+    }
+
+    /// Adds another signature this function can be called with, see [`FunctionType::overloads`].
+    pub fn add_overload(&mut self, overload: FunctionType) -> &mut Self {
+        self.overloads.push(overload);
+        self
+    }
+
+    /// Returns `true` if this signature accepts the given number of arguments. Does not consider [`FunctionType::overloads`].
+    pub fn accepts_argument_count(&self, argument_count: usize) -> bool {
+        argument_count >= self.required_parameter_count()
+            && (self.variadic_parameter_type.is_some() || argument_count <= self.parameters.len())
+    }
+
+    /// Returns the signature a call with the given number of arguments is checked against:
+    /// this one if it has no [`FunctionType::overloads`], otherwise the first of this one and its overloads that accepts the arguments.
+    /// Returns [`None`] if the function is overloaded but no signature accepts that many arguments.
+    pub fn overload_for_argument_count(&self, argument_count: usize) -> Option<&FunctionType> {
+        if self.overloads.is_empty() {
+            return Some(self);
+        }
+        std::iter::once(self)
+            .chain(&self.overloads)
+            .find(|signature| signature.accepts_argument_count(argument_count))
+    }
 }
 
 impl Display for FunctionType {
@@ -61,10 +122,26 @@ impl Display for FunctionType {
         let parameters = self
             .parameters
             .iter()
-            .map(TypeFormat::format)
+            .enumerate()
+            .map(|(index, parameter)| {
+                if index < self.required_parameter_count() {
+                    parameter.format()
+                } else {
+                    format!("{}?", parameter.format())
+                }
+            })
+            .chain(
+                self.variadic_parameter_type
+                    .iter()
+                    .map(|r#type| format!("...{type}")),
+            )
             .collect::<Vec<_>>()
             .join(", ");
         let return_type = self.return_type.as_ref().format();
-        write!(f, "Fn({}) -> {}", parameters, return_type)
+        write!(f, "Fn({}) -> {}", parameters, return_type)?;
+        for overload in &self.overloads {
+            write!(f, " | {overload}")?;
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,43 @@
+//! Lets the runtime know about the variables declared in Yarn
+//! without depending on the compiler.
+
+use crate::prelude::*;
+
+/// The prefix of the variables that Yarn Spinner uses internally, e.g. to track which nodes were visited.
+pub const INTERNAL_VARIABLE_PREFIX: &str = "$Yarn.Internal.";
+
+/// A variable declared in Yarn, e.g. with `<<declare $gold = 0 as number>>`, or implicitly by using it.
+/// Created from the compiler's `Declaration`s and passed to the runtime with `Dialogue::add_variable_declarations`,
+/// so that debug UIs can show what a variable is for and save systems can tell declared variables apart from internal ones.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct VariableDeclaration {
+    /// The name of the variable, including the `$`.
+    pub name: String,
+
+    /// The type of the variable.
+    pub r#type: Type,
+
+    /// The value the variable has until it is set.
+    pub default_value: Option<YarnValue>,
+
+    /// The description of the variable, taken from the comment above its declaration.
+    pub description: Option<String>,
+
+    /// Whether the variable was inferred from its usage instead of being declared with `<<declare>>`.
+    pub is_implicit: bool,
+}
+
+impl VariableDeclaration {
+    /// Returns `true` if the variable is used by Yarn Spinner internally instead of by the Yarn scripts,
+    /// e.g. `$Yarn.Internal.Visiting.Start`, which tracks how often the node `Start` was visited.
+    pub fn is_internal(&self) -> bool {
+        self.name.starts_with(INTERNAL_VARIABLE_PREFIX)
+    }
+}
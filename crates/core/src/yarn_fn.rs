@@ -2,9 +2,16 @@
 //! Inspired by how Bevy stores [`FnSystem`](https://docs.rs/bevy_ecs/0.10.1/bevy_ecs/system/struct.FnSystem.html)s.
 //! This is all here just to emulate the `Dictionary<string, Delegate>` used in Yarn Spinner's `Library` class.
 
+mod async_function;
+mod dialogue_context;
+mod fallible_function;
 mod function_registry;
 mod function_wrapping;
+mod overloaded_function;
 mod parameter_wrapping;
 
 pub(crate) use function_registry::*;
-pub use {function_wrapping::*, parameter_wrapping::*};
+pub use {
+    async_function::*, dialogue_context::*, fallible_function::*, function_wrapping::*,
+    overloaded_function::*, parameter_wrapping::*,
+};
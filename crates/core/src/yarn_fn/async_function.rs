@@ -0,0 +1,157 @@
+//! Allows functions to wait for something before returning a value to Yarn,
+//! see [`Library::add_async_function`].
+
+use crate::prelude::*;
+use std::any::TypeId;
+use std::fmt::{Debug, Display, Formatter};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+
+/// The return type of an async function registered with [`Library::add_async_function`].
+/// Wraps any [`Future`] that resolves to a type that a regular [`YarnFn`] could return.
+///
+/// See [`Library::add_async_function`] for an example.
+pub struct YarnFuture<T>(Mutex<Pin<Box<dyn Future<Output = T> + Send>>>);
+
+impl<T> YarnFuture<T> {
+    /// Wraps the given [`Future`].
+    pub fn new(future: impl Future<Output = T> + Send + 'static) -> Self {
+        // The mutex is only there to make this type `Sync` so that a `Dialogue` holding it can be shared between threads.
+        // We only ever poll through `&mut self`, so it is never actually locked.
+        Self(Mutex::new(Box::pin(future)))
+    }
+}
+
+impl<T> Future for YarnFuture<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let future = self
+            .get_mut()
+            .0
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        future.as_mut().poll(cx)
+    }
+}
+
+impl<T> Debug for YarnFuture<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("YarnFuture")
+            .field(&std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+/// The result of calling an [`UntypedYarnFn`] through [`UntypedYarnFn::call_async`].
+#[derive(Debug)]
+pub enum YarnFnOutput {
+    /// The function returned a value immediately.
+    Ready(YarnValue),
+    /// The function is async and its value will be available once the future resolves.
+    Pending(YarnFuture<YarnValue>),
+    /// The function was registered with [`Library::add_fallible_function`] and returned an error.
+    Failed(YarnFnError),
+}
+
+pub(crate) struct AsyncYarnFnWrapper<Marker, F, T>
+where
+    F: YarnFn<Marker, Out = YarnFuture<T>>,
+{
+    function: F,
+
+    // NOTE: PhantomData<fn()-> T> gives this safe Send/Sync impls
+    _marker: PhantomData<fn() -> (Marker, T)>,
+}
+
+impl<Marker, F, T> Clone for AsyncYarnFnWrapper<Marker, F, T>
+where
+    F: YarnFn<Marker, Out = YarnFuture<T>>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            function: self.function.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker, F, T> From<F> for AsyncYarnFnWrapper<Marker, F, T>
+where
+    F: YarnFn<Marker, Out = YarnFuture<T>>,
+{
+    fn from(function: F) -> Self {
+        Self {
+            function,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker, F, T> UntypedYarnFn for AsyncYarnFnWrapper<Marker, F, T>
+where
+    Marker: 'static,
+    F: YarnFn<Marker, Out = YarnFuture<T>> + 'static,
+    T: IntoYarnValueFromNonYarnValue + Send + 'static,
+{
+    fn call(&self, _input: Vec<YarnValue>) -> YarnValue {
+        panic!("The async function {self} cannot be called synchronously. It can only be called by a `Dialogue`.")
+    }
+
+    fn call_async(&self, input: Vec<YarnValue>) -> YarnFnOutput {
+        let future = self.function.call(input);
+        YarnFnOutput::Pending(YarnFuture::new(
+            async move { future.await.into_yarn_value() },
+        ))
+    }
+
+    fn is_async(&self) -> bool {
+        true
+    }
+
+    fn clone_box(&self) -> Box<dyn UntypedYarnFn> {
+        Box::new(self.clone())
+    }
+
+    fn parameter_types(&self) -> Vec<TypeId> {
+        self.function.parameter_types()
+    }
+
+    fn optional_parameter_count(&self) -> usize {
+        self.function.optional_parameter_count()
+    }
+
+    fn uses_dialogue_context(&self) -> bool {
+        self.function.uses_dialogue_context()
+    }
+
+    /// The type the future resolves to, as that is what the function returns from Yarn's point of view.
+    fn return_type(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+}
+
+impl<Marker, F, T> Debug for AsyncYarnFnWrapper<Marker, F, T>
+where
+    F: YarnFn<Marker, Out = YarnFuture<T>>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let signature = std::any::type_name::<Marker>();
+        let function_path = std::any::type_name::<F>();
+        let debug_message = format!("async {signature} {{{function_path}}}");
+        f.debug_struct(&debug_message).finish()
+    }
+}
+
+impl<Marker, F, T> Display for AsyncYarnFnWrapper<Marker, F, T>
+where
+    F: YarnFn<Marker, Out = YarnFuture<T>>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let signature = std::any::type_name::<Marker>();
+        write!(f, "async {signature}")
+    }
+}
@@ -0,0 +1,134 @@
+//! Lets functions read a value provided by the dialogue that calls them,
+//! e.g. the game state, without capturing it in every function.
+
+use crate::prelude::*;
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt::{self, Debug, Formatter};
+use std::ops::Deref;
+use std::sync::Arc;
+
+type SharedContext = Arc<dyn Any + Send + Sync>;
+
+thread_local! {
+    /// The context of the dialogue that is currently calling a function on this thread, if any.
+    static CURRENT_CONTEXT: RefCell<Option<SharedContext>> = const { RefCell::new(None) };
+}
+
+/// A parameter of a [`YarnFn`] that receives the context set with `Dialogue::set_context`.
+/// It is not passed from Yarn, so it does not count towards the parameters of the function and may appear anywhere in its parameter list.
+///
+/// Calling a function with this parameter panics if the dialogue has no context or its context is not a `T`.
+/// Because the compiler has no context, such functions are never evaluated at compile time.
+///
+/// ## Example
+///
+/// ```rust
+/// # use yarnspinner_core::prelude::*;
+/// struct Inventory {
+///     gold: usize,
+/// }
+///
+/// fn can_afford(inventory: DialogueContext<Inventory>, price: usize) -> bool {
+///     inventory.gold >= price
+/// }
+///
+/// let mut library = Library::new();
+/// library.add_function("can_afford", can_afford);
+/// ```
+///
+/// Which may be called from Yarn as `<<if can_afford(100)>>` after the game called `dialogue.set_context(Inventory { gold: 120 })`.
+pub struct DialogueContext<T>(Arc<T>);
+
+impl<T> DialogueContext<T>
+where
+    T: Send + Sync + 'static,
+{
+    fn current() -> Self {
+        let context = CURRENT_CONTEXT
+            .with(|current| current.borrow().clone())
+            .unwrap_or_else(|| {
+                panic!(
+                    "A function expected the dialogue context to be a {}, but the dialogue has no context. \
+                    Help: Set it with `Dialogue::set_context`.",
+                    std::any::type_name::<T>()
+                )
+            });
+        let context = context.downcast::<T>().unwrap_or_else(|_| {
+            panic!(
+                "A function expected the dialogue context to be a {}, but the dialogue's context has a different type.",
+                std::any::type_name::<T>()
+            )
+        });
+        Self(context)
+    }
+}
+
+impl<T> Deref for DialogueContext<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> Debug for DialogueContext<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DialogueContext")
+            .field(&std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<T> YarnFnParam for DialogueContext<T>
+where
+    T: Send + Sync + 'static,
+{
+    type Item<'new> = DialogueContext<T>;
+
+    const IS_CONTEXT: bool = true;
+
+    fn retrieve<'a>(_iter: &mut YarnValueWrapperIter<'a>) -> Self::Item<'a> {
+        DialogueContext::current()
+    }
+}
+
+/// Runs `f` with `context` as the value received by [`DialogueContext`] parameters. Used by the `Dialogue` when calling functions.
+#[doc(hidden)]
+pub fn with_dialogue_context<R>(context: Option<SharedContext>, f: impl FnOnce() -> R) -> R {
+    /// Restores the previous context even if `f` panics.
+    struct Restore(Option<SharedContext>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CURRENT_CONTEXT.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(CURRENT_CONTEXT.with(|current| current.replace(context)));
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::any::TypeId;
+
+    #[test]
+    fn context_parameters_are_not_passed_from_yarn() {
+        fn greet(name: DialogueContext<String>, greeting: &str) -> String {
+            format!("{greeting}, {}!", *name)
+        }
+        let mut library = Library::new();
+        library.add_function("greet", greet);
+        let greet = library.get("greet").unwrap();
+        assert_eq!(greet.parameter_types(), vec![TypeId::of::<&str>()]);
+        assert!(greet.uses_dialogue_context());
+
+        let context: SharedContext = Arc::new("Alex".to_owned());
+        let greeting =
+            with_dialogue_context(Some(context), || greet.call(vec![YarnValue::from("Hello")]));
+        assert_eq!(greeting, YarnValue::from("Hello, Alex!"));
+    }
+}
@@ -0,0 +1,110 @@
+//! Allows functions to report failures to the dialogue instead of panicking,
+//! see [`Library::add_fallible_function`].
+
+use crate::prelude::*;
+use std::any::TypeId;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+
+/// The error returned by a function registered with [`Library::add_fallible_function`], as seen by the dialogue.
+pub type YarnFnError = Box<dyn Error + Send + Sync>;
+
+pub(crate) struct FallibleYarnFnWrapper<Marker, F, T, E>
+where
+    F: YarnFn<Marker, Out = Result<T, E>>,
+{
+    function: F,
+
+    // NOTE: PhantomData<fn()-> T> gives this safe Send/Sync impls
+    _marker: PhantomData<fn() -> (Marker, T, E)>,
+}
+
+impl<Marker, F, T, E> Clone for FallibleYarnFnWrapper<Marker, F, T, E>
+where
+    F: YarnFn<Marker, Out = Result<T, E>>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            function: self.function.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker, F, T, E> From<F> for FallibleYarnFnWrapper<Marker, F, T, E>
+where
+    F: YarnFn<Marker, Out = Result<T, E>>,
+{
+    fn from(function: F) -> Self {
+        Self {
+            function,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker, F, T, E> UntypedYarnFn for FallibleYarnFnWrapper<Marker, F, T, E>
+where
+    Marker: 'static,
+    F: YarnFn<Marker, Out = Result<T, E>> + 'static,
+    T: IntoYarnValueFromNonYarnValue + 'static,
+    E: Into<YarnFnError> + 'static,
+{
+    fn call(&self, input: Vec<YarnValue>) -> YarnValue {
+        match self.function.call(input) {
+            Ok(value) => value.into_yarn_value(),
+            Err(error) => panic!("The function {self} failed: {}", error.into()),
+        }
+    }
+
+    fn call_async(&self, input: Vec<YarnValue>) -> YarnFnOutput {
+        match self.function.call(input) {
+            Ok(value) => YarnFnOutput::Ready(value.into_yarn_value()),
+            Err(error) => YarnFnOutput::Failed(error.into()),
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn UntypedYarnFn> {
+        Box::new(self.clone())
+    }
+
+    fn parameter_types(&self) -> Vec<TypeId> {
+        self.function.parameter_types()
+    }
+
+    /// The type of the successful value, as that is what the function returns from Yarn's point of view.
+    fn return_type(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn optional_parameter_count(&self) -> usize {
+        self.function.optional_parameter_count()
+    }
+
+    fn uses_dialogue_context(&self) -> bool {
+        self.function.uses_dialogue_context()
+    }
+}
+
+impl<Marker, F, T, E> Debug for FallibleYarnFnWrapper<Marker, F, T, E>
+where
+    F: YarnFn<Marker, Out = Result<T, E>>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let signature = std::any::type_name::<Marker>();
+        let function_path = std::any::type_name::<F>();
+        let debug_message = format!("{signature} {{{function_path}}}");
+        f.debug_struct(&debug_message).finish()
+    }
+}
+
+impl<Marker, F, T, E> Display for FallibleYarnFnWrapper<Marker, F, T, E>
+where
+    F: YarnFn<Marker, Out = Result<T, E>>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let signature = std::any::type_name::<Marker>();
+        f.write_str(signature)
+    }
+}
@@ -41,10 +41,8 @@ impl YarnFnRegistry {
         F: YarnFn<Marker> + 'static + Clone,
         F::Out: IntoYarnValueFromNonYarnValue + 'static + Clone,
     {
-        let name = name.into();
         let wrapped = YarnFnWrapper::from(function);
-        self.0.insert(name, Box::new(wrapped));
-        self
+        self.add_boxed(name, Box::new(wrapped))
     }
 
     /// Iterates over all functions in the registry.
@@ -60,10 +58,41 @@ impl YarnFnRegistry {
         function: Box<dyn UntypedYarnFn>,
     ) -> &mut Self {
         let name = name.into();
+        let has_variadic_parameter_before_last = function
+            .parameter_types()
+            .into_iter()
+            .rev()
+            .skip(1)
+            .any(is_variadic_parameter);
+        assert!(
+            !has_variadic_parameter_before_last,
+            "Function \"{name}\" has a variadic parameter that is not its last one, so the parameters after it would never receive an argument"
+        );
         self.0.insert(name, function);
         self
     }
 
+    /// Adds `function` as an overload of the function registered under `name`, see [`Library::try_add_overload`].
+    pub(crate) fn add_overload(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        function: Box<dyn UntypedYarnFn>,
+    ) -> Result<&mut Self, AmbiguousOverloadError> {
+        let name = name.into();
+        if let Some(existing) = self.get(&name) {
+            if OverloadedYarnFn::is_ambiguous(existing, function.as_ref()) {
+                return Err(AmbiguousOverloadError {
+                    function_name: name.into_owned(),
+                });
+            }
+        }
+        let function: Box<dyn UntypedYarnFn> = match self.0.remove(&name) {
+            Some(existing) => Box::new(OverloadedYarnFn::new(existing, function)),
+            None => function,
+        };
+        Ok(self.add_boxed(name, function))
+    }
+
     /// Returns `true` if the registry contains a function with the given name.
     pub(crate) fn contains_function(&self, name: &str) -> bool {
         self.get(name).is_some()
@@ -189,6 +218,44 @@ mod tests {
         assert_eq!(result4, "abctrue1".to_string());
     }
 
+    #[test]
+    fn can_call_variadic_fn_with_any_number_of_args() {
+        let mut functions = YarnFnRegistry::default();
+        functions.register_function("sum", |first: f32, rest: &[YarnValue]| {
+            rest.iter()
+                .map(|value| f32::try_from(value.clone()).unwrap())
+                .fold(first, |sum, value| sum + value)
+        });
+        functions.register_function("count", |values: Vec<YarnValue>| values.len());
+
+        let sum = functions.get("sum").unwrap();
+        let count = functions.get("count").unwrap();
+        assert!(sum.is_variadic());
+        assert!(count.is_variadic());
+
+        let result: f32 = sum.call(to_function_params([1.0])).try_into().unwrap();
+        assert_eq!(result, 1.0);
+        let result: f32 = sum
+            .call(to_function_params([1.0, 2.0, 3.0]))
+            .try_into()
+            .unwrap();
+        assert_eq!(result, 6.0);
+        let result: usize = count.call(vec![]).try_into().unwrap();
+        assert_eq!(result, 0);
+        let result: usize = count
+            .call(to_function_params([YarnValue::from("a"), true.into()]))
+            .try_into()
+            .unwrap();
+        assert_eq!(result, 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_variadic_param_before_last() {
+        let mut functions = YarnFnRegistry::default();
+        functions.register_function("test", |_values: Vec<YarnValue>, a: f32| a);
+    }
+
     fn to_function_params(
         params: impl IntoIterator<Item = impl Into<YarnValue>>,
     ) -> Vec<YarnValue> {
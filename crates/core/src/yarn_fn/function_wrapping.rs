@@ -13,12 +13,18 @@ use yarnspinner_macros::all_tuples;
 ///   - [`String`] (for a reference, [`&str`] may be used instead of `&String`)
 ///   - [`YarnValue`], which means that a parameter may be any of the above types
 ///   - Tuples of the above types.
+/// - Any of its parameters may be a [`DialogueContext`], which receives the context of the calling dialogue and is not passed from Yarn.
+/// - Its last parameters may be [`Option`]s of the above types, which are `None` when the function is called without them.
+///   Use e.g. [`Option::unwrap_or`] to give such a parameter a default value.
+/// - Its last parameter may be `&[YarnValue]` or `Vec<YarnValue>`, which receives all remaining arguments.
+///   Such a function is *variadic* and can be called from Yarn with any number of arguments in place of that parameter.
 /// - It must return a value.
 /// - Its return type must be one of the following types:
 ///   - [`bool`]
 ///   - A numeric type, i.e. one of [`f32`], [`f64`], [`i8`], [`i16`], [`i32`], [`i64`], [`i128`], [`u8`], [`u16`], [`u32`], [`u64`], [`u128`], [`usize`], [`isize`]
 ///   - [`String`]
 /// Note that in particular, no references can be returned.
+/// Functions that need to wait for something, e.g. a web request, can return a [`YarnFuture`] instead. They must be registered with [`Library::add_async_function`].
 /// ## Examples
 /// ```rust
 /// fn give_summary(name: &str, age: usize, is_cool: bool) -> String {
@@ -32,17 +38,45 @@ use yarnspinner_macros::all_tuples;
 /// <<set $is_cool to true>>
 /// Narrator: {give_summary($name, $age, $is_cool)}
 /// ```
+/// A variadic function:
+/// ```rust
+/// # use yarnspinner_core::prelude::*;
+/// fn max(first: f32, rest: &[YarnValue]) -> f32 {
+///     rest.iter()
+///         .map(|value| f32::try_from(value.clone()).unwrap())
+///         .fold(first, f32::max)
+/// }
+/// ```
+/// Which may be called from Yarn as `{max(1)}`, `{max(1, 2)}`, `{max(1, 2, 3)}`, etc.
+///
+/// A function with an optional parameter:
+/// ```rust
+/// fn greet(name: &str, greeting: Option<String>) -> String {
+///     let greeting = greeting.unwrap_or_else(|| "Hello".to_owned());
+///     format!("{greeting}, {name}!")
+/// }
+/// ```
+/// Which may be called from Yarn as both `{greet("Alice")}` and `{greet("Alice", "Welcome")}`.
 pub trait YarnFn<Marker>: Clone + Send + Sync {
     /// The type of the value returned by this function. See [`YarnFn`] for more information about what is allowed.
-    type Out: IntoYarnValueFromNonYarnValue + 'static;
+    type Out: 'static;
     #[doc(hidden)]
     fn call(&self, input: Vec<YarnValue>) -> Self::Out;
-    /// The [`TypeId`]s of the parameters of this function.
+    /// The [`TypeId`]s of the parameters of this function. Optional parameters report the [`TypeId`] of the type they wrap.
     fn parameter_types(&self) -> Vec<TypeId>;
     /// The [`TypeId`] of the return type of this function.
     fn return_type(&self) -> TypeId {
         TypeId::of::<Self::Out>()
     }
+    /// The number of parameters at the end of [`YarnFn::parameter_types`] that may be omitted when calling this function,
+    /// not counting a variadic last parameter. These are the trailing parameters of type [`Option`].
+    fn optional_parameter_count(&self) -> usize {
+        0
+    }
+    /// Returns `true` if one of the parameters is a [`DialogueContext`].
+    fn uses_dialogue_context(&self) -> bool {
+        false
+    }
 }
 
 /// A [`YarnFn`] with the `Marker` type parameter erased.
@@ -50,12 +84,43 @@ pub trait YarnFn<Marker>: Clone + Send + Sync {
 pub trait UntypedYarnFn: Debug + Display + Send + Sync {
     #[doc(hidden)]
     fn call(&self, input: Vec<YarnValue>) -> YarnValue;
+    /// Like [`UntypedYarnFn::call`], but returns a [`YarnFnOutput::Pending`] for functions registered with [`Library::add_async_function`] instead of panicking.
+    #[doc(hidden)]
+    fn call_async(&self, input: Vec<YarnValue>) -> YarnFnOutput {
+        YarnFnOutput::Ready(self.call(input))
+    }
+    /// Returns `true` if this function was registered with [`Library::add_async_function`], i.e. it cannot be evaluated immediately.
+    fn is_async(&self) -> bool {
+        false
+    }
     #[doc(hidden)]
     fn clone_box(&self) -> Box<dyn UntypedYarnFn>;
     /// The [`TypeId`]s of the parameters of this function.
     fn parameter_types(&self) -> Vec<TypeId>;
     /// The [`TypeId`] of the return type of this function.
     fn return_type(&self) -> TypeId;
+    /// The number of parameters at the end of [`UntypedYarnFn::parameter_types`] that may be omitted when calling this function,
+    /// not counting a variadic last parameter. See [`YarnFn::optional_parameter_count`].
+    fn optional_parameter_count(&self) -> usize {
+        0
+    }
+    /// Returns `true` if one of the parameters is a [`DialogueContext`], i.e. the function can only be called by a dialogue that has a context.
+    fn uses_dialogue_context(&self) -> bool {
+        false
+    }
+    /// Returns `true` if the last parameter of this function takes all remaining arguments, see [`is_variadic_parameter`].
+    /// Such a function accepts any number of arguments from the last parameter on, including none.
+    fn is_variadic(&self) -> bool {
+        self.parameter_types()
+            .last()
+            .is_some_and(|type_id| is_variadic_parameter(*type_id))
+    }
+    /// The functions registered under this function's name with [`Library::add_overload`], each accepting a different number of arguments.
+    /// Empty for functions that are not overloaded. Otherwise, the other methods describe the overload with the fewest parameters,
+    /// and calls are forwarded to the overload that accepts the number of arguments passed, see [`resolve_overload`].
+    fn overloads(&self) -> Vec<&dyn UntypedYarnFn> {
+        Vec::new()
+    }
 }
 
 impl Clone for Box<dyn UntypedYarnFn> {
@@ -86,6 +151,14 @@ where
     fn return_type(&self) -> TypeId {
         self.function.return_type()
     }
+
+    fn optional_parameter_count(&self) -> usize {
+        self.function.optional_parameter_count()
+    }
+
+    fn uses_dialogue_context(&self) -> bool {
+        self.function.uses_dialogue_context()
+    }
 }
 
 pub(crate) struct YarnFnWrapper<Marker, F>
@@ -161,8 +234,8 @@ impl Eq for Box<dyn UntypedYarnFn> {}
 /// This is useful when registering functions in a [`Library`] with [`Library::add_function`].
 #[macro_export]
 macro_rules! yarn_fn_type {
-    (impl Fn($($param:ty),+) -> $ret:ty) => {
-        impl $crate::prelude::YarnFn<fn($($param),+) -> $ret, Out = $ret>
+    (impl Fn($($param:ty),*) -> $ret:ty) => {
+        impl $crate::prelude::YarnFn<fn($($param),*) -> $ret, Out = $ret>
     };
 }
 pub use yarn_fn_type;
@@ -177,7 +250,7 @@ macro_rules! impl_yarn_fn_tuple {
                 Send + Sync + Clone +
                 Fn($($param,)*) -> O +
                 Fn($(<$param as YarnFnParam>::Item<'a>,)*) -> O,
-            O: IntoYarnValueFromNonYarnValue + 'static,
+            O: 'static,
             $($param: YarnFnParam + 'static,)*
             {
                 type Out = O;
@@ -199,7 +272,32 @@ macro_rules! impl_yarn_fn_tuple {
                 }
 
                 fn parameter_types(&self) -> Vec<TypeId> {
-                    vec![$(TypeId::of::<$param>()),*]
+                    let parameters: Vec<(bool, TypeId)> = vec![$(($param::IS_CONTEXT, $param::parameter_type())),*];
+                    parameters
+                        .into_iter()
+                        .filter(|(is_context, _)| !is_context)
+                        .map(|(_, type_id)| type_id)
+                        .collect()
+                }
+
+                fn optional_parameter_count(&self) -> usize {
+                    let parameters: Vec<(bool, bool)> = vec![$(($param::IS_CONTEXT, $param::IS_OPTIONAL)),*];
+                    let is_optional = parameters
+                        .into_iter()
+                        .filter(|(is_context, _)| !is_context)
+                        .map(|(_, is_optional)| is_optional);
+                    let is_variadic = self.parameter_types().last().is_some_and(|type_id| is_variadic_parameter(*type_id));
+                    is_optional
+                        .into_iter()
+                        .rev()
+                        .skip(usize::from(is_variadic))
+                        .take_while(|&is_optional| is_optional)
+                        .count()
+                }
+
+                fn uses_dialogue_context(&self) -> bool {
+                    let is_context: Vec<bool> = vec![$($param::IS_CONTEXT),*];
+                    is_context.contains(&true)
                 }
             }
     };
@@ -338,6 +436,35 @@ mod tests {
         accept_yarn_fn(f);
     }
 
+    #[test]
+    fn fills_omitted_optional_params_with_none() {
+        fn f(a: usize, b: Option<usize>, c: Option<&str>) -> String {
+            format!("{a} {b:?} {c:?}")
+        }
+        assert_eq!(f.optional_parameter_count(), 2);
+        assert_eq!(
+            f.parameter_types(),
+            vec![
+                TypeId::of::<usize>(),
+                TypeId::of::<usize>(),
+                TypeId::of::<&str>()
+            ]
+        );
+        assert_eq!(apply_yarn_fn(f, vec![1.into()]), "1 None None".to_owned());
+        assert_eq!(
+            apply_yarn_fn(f, vec![1.into(), 2.into(), "c".into()]),
+            "1 Some(2) Some(\"c\")".to_owned()
+        );
+    }
+
+    #[test]
+    fn only_counts_trailing_optional_params() {
+        fn f(_: Option<usize>, _: usize, _: Option<usize>, _: &[YarnValue]) -> bool {
+            true
+        }
+        assert_eq!(f.optional_parameter_count(), 1);
+    }
+
     fn accept_yarn_fn<Marker>(_: impl YarnFn<Marker>) {}
 
     fn apply_yarn_fn<T, Marker>(f: T, input: Vec<YarnValue>) -> T::Out
@@ -0,0 +1,198 @@
+//! Allows registering multiple functions under the same name
+//! that are told apart by the number of arguments they are called with, see [`Library::add_overload`].
+
+use crate::prelude::*;
+use std::any::TypeId;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Returns the overload of `function` that accepts the given number of arguments, see [`UntypedYarnFn::overloads`].
+/// Returns `function` itself if it is not overloaded or if no overload accepts that many arguments,
+/// so that the caller reports the mismatch against the function as a whole.
+pub fn resolve_overload(function: &dyn UntypedYarnFn, argument_count: usize) -> &dyn UntypedYarnFn {
+    function
+        .overloads()
+        .into_iter()
+        .find(|overload| accepts_argument_count(*overload, argument_count))
+        .unwrap_or(function)
+}
+
+/// The functions registered under one name with [`Library::add_overload`], sorted by the number of parameters they require.
+#[derive(Clone)]
+pub(crate) struct OverloadedYarnFn(Vec<Box<dyn UntypedYarnFn>>);
+
+impl OverloadedYarnFn {
+    /// Combines `existing`, which may already be overloaded, with `added`.
+    /// Check [`OverloadedYarnFn::is_ambiguous`] first, as calls to overloads accepting the same number of arguments could not be told apart.
+    pub(crate) fn new(existing: Box<dyn UntypedYarnFn>, added: Box<dyn UntypedYarnFn>) -> Self {
+        let mut overloads: Vec<_> = if existing.overloads().is_empty() {
+            vec![existing]
+        } else {
+            existing
+                .overloads()
+                .into_iter()
+                .map(|overload| overload.clone_box())
+                .collect()
+        };
+        overloads.push(added);
+        Self::from_overloads(overloads)
+    }
+
+    /// Combines `overloads`, none of which may accept the same number of arguments as another one.
+    pub(crate) fn from_overloads(mut overloads: Vec<Box<dyn UntypedYarnFn>>) -> Self {
+        overloads.sort_by_key(|overload| argument_count_range(overload.as_ref()).0);
+        Self(overloads)
+    }
+
+    /// Returns `true` if `added` accepts a number of arguments that `existing` or one of its overloads accepts as well.
+    pub(crate) fn is_ambiguous(existing: &dyn UntypedYarnFn, added: &dyn UntypedYarnFn) -> bool {
+        let overloads = match existing.overloads() {
+            overloads if overloads.is_empty() => vec![existing],
+            overloads => overloads,
+        };
+        let (added_min, added_max) = argument_count_range(added);
+        overloads.into_iter().any(|overload| {
+            let (min, max) = argument_count_range(overload);
+            added_min <= max.unwrap_or(usize::MAX) && min <= added_max.unwrap_or(usize::MAX)
+        })
+    }
+
+    fn first(&self) -> &dyn UntypedYarnFn {
+        self.0[0].as_ref()
+    }
+}
+
+/// The smallest and largest number of arguments `function` accepts. The largest is [`None`] if it is variadic.
+fn argument_count_range(function: &dyn UntypedYarnFn) -> (usize, Option<usize>) {
+    let is_variadic = function.is_variadic();
+    let fixed_parameter_count = function.parameter_types().len() - usize::from(is_variadic);
+    (
+        fixed_parameter_count - function.optional_parameter_count(),
+        (!is_variadic).then_some(fixed_parameter_count),
+    )
+}
+
+fn accepts_argument_count(function: &dyn UntypedYarnFn, argument_count: usize) -> bool {
+    let (min, max) = argument_count_range(function);
+    argument_count >= min && max.map_or(true, |max| argument_count <= max)
+}
+
+impl UntypedYarnFn for OverloadedYarnFn {
+    fn call(&self, input: Vec<YarnValue>) -> YarnValue {
+        resolve_overload(self, input.len()).call(input)
+    }
+
+    fn call_async(&self, input: Vec<YarnValue>) -> YarnFnOutput {
+        resolve_overload(self, input.len()).call_async(input)
+    }
+
+    fn is_async(&self) -> bool {
+        self.0.iter().any(|overload| overload.is_async())
+    }
+
+    fn clone_box(&self) -> Box<dyn UntypedYarnFn> {
+        Box::new(self.clone())
+    }
+
+    fn parameter_types(&self) -> Vec<TypeId> {
+        self.first().parameter_types()
+    }
+
+    fn return_type(&self) -> TypeId {
+        self.first().return_type()
+    }
+
+    fn optional_parameter_count(&self) -> usize {
+        self.first().optional_parameter_count()
+    }
+
+    fn is_variadic(&self) -> bool {
+        self.first().is_variadic()
+    }
+
+    fn uses_dialogue_context(&self) -> bool {
+        self.0
+            .iter()
+            .any(|overload| overload.uses_dialogue_context())
+    }
+
+    fn overloads(&self) -> Vec<&dyn UntypedYarnFn> {
+        self.0.iter().map(|overload| overload.as_ref()).collect()
+    }
+}
+
+impl Debug for OverloadedYarnFn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OverloadedYarnFn").field(&self.0).finish()
+    }
+}
+
+impl Display for OverloadedYarnFn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let overloads: Vec<_> = self.0.iter().map(ToString::to_string).collect();
+        f.write_str(&overloads.join(" | "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_the_overload_for_the_argument_count() {
+        let mut library = Library::new();
+        library
+            .add_overload("random", |min: f32, max: f32| min + max)
+            .add_overload("random", || 1.0_f32)
+            .add_overload("random", |max: f32| max);
+        let random = library.get("random").unwrap();
+
+        assert_eq!(random.overloads().len(), 3);
+        assert_eq!(random.call(vec![]), YarnValue::from(1.0));
+        assert_eq!(random.call(vec![6.0.into()]), YarnValue::from(6.0));
+        assert_eq!(
+            random.call(vec![2.0.into(), 6.0.into()]),
+            YarnValue::from(8.0)
+        );
+        assert_eq!(
+            library
+                .signatures()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "random() -> Number",
+                "random(Number) -> Number",
+                "random(Number, Number) -> Number"
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_on_ambiguous_overloads() {
+        Library::new()
+            .add_overload("greet", |_name: String| true)
+            .add_overload("greet", |_name: String, _greeting: Option<String>| false);
+    }
+
+    #[test]
+    fn rejects_ambiguous_overloads_without_changing_the_library() {
+        let mut library = Library::new();
+        library.add_overload("greet", |_name: String| true);
+        let result =
+            library.try_add_overload("greet", |_name: String, _greeting: Option<String>| false);
+
+        assert_eq!(
+            result.unwrap_err(),
+            AmbiguousOverloadError {
+                function_name: "greet".to_owned()
+            }
+        );
+        assert_eq!(library.get("greet").unwrap().parameter_types().len(), 1);
+        assert!(library
+            .try_add_overload("greet", |_name: String, _greeting: String, _times: f32| {
+                false
+            })
+            .is_ok());
+    }
+}
@@ -3,7 +3,7 @@
 //! Inspired by <https://promethia-27.github.io/dependency_injection_like_bevy_from_scratch/chapter2/passing_references.html>
 
 use crate::prelude::*;
-use std::any::Any;
+use std::any::{Any, TypeId};
 use std::borrow::Borrow;
 use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
@@ -50,11 +50,31 @@ impl YarnValueWrapper {
 /// - [`String`] (for a reference, [`&str`] may be used instead of `&String`)
 /// - [`YarnValue`], which means that a parameter may be any of the above types
 /// - Tuples of the above types.
+/// - `&[YarnValue]` or `Vec<YarnValue>`, which makes the function variadic, see [`is_variadic_parameter`].
+/// - [`Option`] of the above types, which makes the parameter optional if it is one of the last ones, see [`YarnFn::optional_parameter_count`].
+/// - [`DialogueContext`], which receives the context of the calling dialogue instead of a value from Yarn.
 pub trait YarnFnParam {
     /// The item type returned when constructing this [`YarnFn`] param. The value of this associated type should be `Self`, instantiated with a new lifetime.
     /// You could think of `YarnFnParam::Item<'new>` as being an operation that changes the lifetime bound to `Self`.
     type Item<'new>: YarnFnParam;
 
+    /// Whether this parameter may be omitted, in which case it is retrieved as `None`.
+    #[doc(hidden)]
+    const IS_OPTIONAL: bool = false;
+
+    /// Whether this parameter is a [`DialogueContext`], which is not passed from Yarn and thus left out of [`YarnFn::parameter_types`].
+    #[doc(hidden)]
+    const IS_CONTEXT: bool = false;
+
+    /// The [`TypeId`] reported by [`YarnFn::parameter_types`] for this parameter.
+    #[doc(hidden)]
+    fn parameter_type() -> TypeId
+    where
+        Self: 'static,
+    {
+        TypeId::of::<Self>()
+    }
+
     #[doc(hidden)]
     fn retrieve<'a>(iter: &mut YarnValueWrapperIter<'a>) -> Self::Item<'a>;
 }
@@ -220,6 +240,65 @@ macro_rules! impl_yarn_fn_param_inner {
     };
 }
 
+/// Returns `true` if the parameter type with the given [`TypeId`] takes all remaining arguments of a function call,
+/// i.e. is `&[YarnValue]` or `Vec<YarnValue>`. Such a parameter must be the last one of a [`YarnFn`].
+pub fn is_variadic_parameter(type_id: TypeId) -> bool {
+    [TypeId::of::<&[YarnValue]>(), TypeId::of::<Vec<YarnValue>>()].contains(&type_id)
+}
+
+impl YarnValueWrapper {
+    /// Collects the raw values of this and all remaining arguments, storing them in this wrapper.
+    fn collect_remaining(&mut self, iter: &mut YarnValueWrapperIter<'_>) {
+        let mut values = vec![std::mem::take(&mut self.raw).unwrap()];
+        values.extend(iter.map(|value| std::mem::take(&mut value.raw).unwrap()));
+        self.converted.replace(Box::new(values));
+    }
+}
+
+impl YarnFnParam for &[YarnValue] {
+    type Item<'new> = &'new [YarnValue];
+
+    fn retrieve<'a>(iter: &mut YarnValueWrapperIter<'a>) -> Self::Item<'a> {
+        let Some(value) = iter.next() else {
+            return &[];
+        };
+        value.collect_remaining(iter);
+        let value: &'a YarnValueWrapper = value;
+        let converted = value.converted.as_ref().unwrap();
+        converted.downcast_ref::<Vec<YarnValue>>().unwrap()
+    }
+}
+
+impl<T> YarnFnParam for Option<T>
+where
+    T: YarnFnParam,
+{
+    type Item<'new> = Option<T::Item<'new>>;
+
+    const IS_OPTIONAL: bool = true;
+
+    /// Optional parameters have the same type as their inner value from Yarn's point of view.
+    fn parameter_type() -> TypeId
+    where
+        Self: 'static,
+    {
+        T::parameter_type()
+    }
+
+    fn retrieve<'a>(iter: &mut YarnValueWrapperIter<'a>) -> Self::Item<'a> {
+        (iter.len() > 0).then(|| T::retrieve(iter))
+    }
+}
+
+impl YarnFnParam for Vec<YarnValue> {
+    type Item<'new> = Vec<YarnValue>;
+
+    fn retrieve<'a>(iter: &mut YarnValueWrapperIter<'a>) -> Self::Item<'a> {
+        iter.map(|value| std::mem::take(&mut value.raw).unwrap())
+            .collect()
+    }
+}
+
 impl_yarn_fn_param! {
     [str => String, YarnValue, bool, f32, f64, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize]: YarnFnParam
 }
@@ -2,6 +2,7 @@
 #[cfg(any(feature = "bevy", feature = "serde"))]
 use crate::prelude::*;
 use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
 use thiserror::Error;
 
 /// Represents a Yarn value. The chosen variant corresponds to the last assignment of the value,
@@ -13,10 +14,13 @@ use thiserror::Error;
 /// ## Implementation Notes
 ///
 /// Corresponds to C#'s [`Convert`](https://docs.microsoft.com/en-us/dotnet/api/system.convert?view=net-5.0) class.
+///
+/// [`Hash`] is implemented so that values that compare equal hash the same, e.g. `0.0` and `-0.0`.
+/// [`Eq`] is not, because a `NaN` is never equal to anything, not even itself.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "bevy", derive(Reflect))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
 #[cfg_attr(
     all(feature = "bevy", feature = "serde"),
     reflect(Serialize, Deserialize)
@@ -40,6 +44,20 @@ pub trait IntoYarnValueFromNonYarnValue {
     fn into_yarn_value(self) -> YarnValue;
 }
 
+impl Hash for YarnValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        match self {
+            // All NaNs hash the same, no matter their payload
+            Self::Number(number) if number.is_nan() => f32::NAN.to_bits().hash(state),
+            // Adding 0.0 turns -0.0 into 0.0, which compare equal and so must hash the same
+            Self::Number(number) => (number + 0.0).to_bits().hash(state),
+            Self::String(string) => string.hash(state),
+            Self::Boolean(boolean) => boolean.hash(state),
+        }
+    }
+}
+
 impl YarnValue {
     /// Checks if two [`YarnValue`]s are equal, with a given epsilon for two [`YarnValue::Number`]s.
     /// Note that all equality operations are type-safe, i.e. comparing a [`YarnValue::Number`] to a [`YarnValue::String`] will always return `false`.
@@ -32,6 +32,11 @@
 //! - Type out the text faster: Same as above, but hold press before the text is done typing.
 //! - Select an option: press the number key corresponding to the option you want to select or click/tap the option.
 //!
+//! ## Long lines
+//!
+//! Insert a [`DialogueTextMeasurement`] to split lines that don't fit into the dialogue box into multiple pages.
+//! The player continues to the next page like they would continue to the next line.
+//!
 //! ## Limitations
 //!
 //! This dialogue view expects only a single instance of [`DialogueRunner`](bevy_yarnspinner::prelude::DialogueRunner) to be running.
@@ -43,13 +48,14 @@
 use bevy::prelude::*;
 use bevy_yarnspinner::prelude::YarnSpinnerPlugin;
 pub use setup::UiRootNode;
+pub use text_measurement::DialogueTextMeasurement;
 pub use updating::SpeakerChangeEvent;
 
 pub mod prelude {
     //! Everything you need to get starting using this example Yarn Spinner dialogue view.
     pub use crate::{
-        ExampleYarnSpinnerDialogueViewPlugin, ExampleYarnSpinnerDialogueViewSystemSet,
-        SpeakerChangeEvent,
+        DialogueTextMeasurement, ExampleYarnSpinnerDialogueViewPlugin,
+        ExampleYarnSpinnerDialogueViewSystemSet, SpeakerChangeEvent,
     };
 }
 
@@ -73,6 +79,7 @@ impl ExampleYarnSpinnerDialogueViewPlugin {
 mod assets;
 mod option_selection;
 mod setup;
+mod text_measurement;
 mod typewriter;
 mod updating;
 
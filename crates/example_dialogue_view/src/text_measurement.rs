@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// Tells the dialogue view whether a text fits into its dialogue box.
+/// Insert this resource to have lines that are too long, e.g. because their translation is much longer than the original,
+/// split into multiple pages instead of overflowing the box. The player advances to the next page the same way they advance the dialogue.
+///
+/// Lines are split between words where possible and markup is split alongside the text,
+/// see [`LocalizedLine::split_to_fit`](bevy_yarnspinner::prelude::LocalizedLine::split_to_fit).
+/// The character name is displayed separately and is not part of the measured text.
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use bevy_yarnspinner_example_dialogue_view::prelude::*;
+///
+/// # let mut app = App::new();
+/// // A crude measurement assuming a monospaced font. Use your font's metrics for a real game.
+/// app.insert_resource(DialogueTextMeasurement::new(|text| {
+///     text.chars().count() <= 200
+/// }));
+/// ```
+#[derive(Clone, Resource)]
+pub struct DialogueTextMeasurement(Arc<dyn Fn(&str) -> bool + Send + Sync>);
+
+impl DialogueTextMeasurement {
+    /// Creates a new measurement from a function that returns `true` if the passed text fits into the dialogue box.
+    pub fn new(fits: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(fits))
+    }
+
+    /// Returns `true` if the passed text fits into the dialogue box.
+    pub fn fits(&self, text: &str) -> bool {
+        (self.0)(text)
+    }
+}
+
+impl Debug for DialogueTextMeasurement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("DialogueTextMeasurement")
+            .field(&"<function>")
+            .finish()
+    }
+}
@@ -4,7 +4,7 @@ use crate::setup::{
     INITIAL_DIALOGUE_CONTINUE_BOTTOM,
 };
 use crate::updating::SpeakerChangeEvent;
-use crate::ExampleYarnSpinnerDialogueViewSystemSet;
+use crate::{DialogueTextMeasurement, ExampleYarnSpinnerDialogueViewSystemSet};
 use bevy::prelude::*;
 use bevy::utils::Instant;
 use bevy_yarnspinner::{events::*, prelude::*};
@@ -37,6 +37,7 @@ pub(crate) struct Typewriter {
     pub(crate) current_text: String,
     pub(crate) graphemes_left: Vec<String>,
     pub(crate) last_before_options: bool,
+    remaining_line: Option<LocalizedLine>,
     elapsed: f32,
     start: Instant,
     fast_typing: bool,
@@ -49,6 +50,7 @@ impl Default for Typewriter {
             current_text: default(),
             graphemes_left: default(),
             last_before_options: default(),
+            remaining_line: default(),
             elapsed: default(),
             start: Instant::now(),
             fast_typing: default(),
@@ -57,30 +59,59 @@ impl Default for Typewriter {
 }
 
 impl Typewriter {
-    pub(crate) fn set_line(&mut self, line: &LocalizedLine) {
+    pub(crate) fn set_line(
+        &mut self,
+        line: &LocalizedLine,
+        measurement: Option<&DialogueTextMeasurement>,
+    ) {
         *self = Self {
             character_name: line.character_name().map(|s| s.to_string()),
-            current_text: String::new(),
-            graphemes_left: line
-                .text_without_character_name()
-                .graphemes(true)
-                .map(|s| s.to_string())
-                .collect(),
             last_before_options: line.is_last_line_before_options(),
             ..default()
         };
+        self.set_page(line.without_character_name(), measurement);
     }
 
+    /// Shows the next page of the current line. Does nothing if the whole line has already been shown.
+    pub(crate) fn show_next_page(&mut self, measurement: Option<&DialogueTextMeasurement>) {
+        if let Some(line) = self.remaining_line.take() {
+            self.set_page(line, measurement);
+        }
+    }
+
+    fn set_page(&mut self, line: LocalizedLine, measurement: Option<&DialogueTextMeasurement>) {
+        let (page, rest) = match measurement {
+            Some(measurement) => line.split_to_fit(|text| measurement.fits(text)),
+            None => (line, None),
+        };
+        self.current_text = String::new();
+        self.graphemes_left = page.text.graphemes(true).map(|s| s.to_string()).collect();
+        self.remaining_line = rest;
+        self.elapsed = default();
+        self.start = Instant::now();
+        self.fast_typing = false;
+    }
+
+    /// Returns `true` if the whole line has been written out.
     pub(crate) fn is_finished(&self) -> bool {
+        self.is_page_finished() && !self.has_next_page()
+    }
+
+    /// Returns `true` if the current page has been written out, regardless of whether more pages follow.
+    pub(crate) fn is_page_finished(&self) -> bool {
         self.graphemes_left.is_empty() && !self.current_text.is_empty()
     }
 
+    pub(crate) fn has_next_page(&self) -> bool {
+        self.remaining_line.is_some()
+    }
+
     pub(crate) fn fast_forward(&mut self) {
         self.fast_typing = true;
     }
 
     fn update_current_text(&mut self) {
-        if self.is_finished() {
+        if self.is_page_finished() {
             return;
         }
         self.elapsed += self.start.elapsed().as_secs_f32();
@@ -114,7 +145,7 @@ fn write_text(
         *text = default();
         return;
     }
-    if typewriter.is_finished() {
+    if typewriter.is_page_finished() {
         return;
     }
     if !typewriter.last_before_options {
@@ -141,11 +172,12 @@ fn show_continue(
     mut visibility: Query<&mut Visibility, With<DialogueContinueNode>>,
     mut typewriter_finished_event: EventReader<TypewriterFinishedEvent>,
 ) {
-    for _event in typewriter_finished_event.read() {
-        if !typewriter.last_before_options {
-            let mut visibility = visibility.single_mut();
-            *visibility = Visibility::Inherited;
-        }
+    let has_finished_page = typewriter.is_page_finished() && typewriter.has_next_page();
+    let has_finished_line =
+        typewriter_finished_event.read().count() > 0 && !typewriter.last_before_options;
+    if has_finished_page || has_finished_line {
+        let mut visibility = visibility.single_mut();
+        *visibility = Visibility::Inherited;
     }
 }
 
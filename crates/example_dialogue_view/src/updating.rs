@@ -1,7 +1,7 @@
 use crate::option_selection::OptionSelection;
 use crate::setup::{DialogueContinueNode, DialogueNameNode, UiRootNode};
 use crate::typewriter::{self, Typewriter};
-use crate::ExampleYarnSpinnerDialogueViewSystemSet;
+use crate::{DialogueTextMeasurement, ExampleYarnSpinnerDialogueViewSystemSet};
 use bevy::prelude::*;
 use bevy_yarnspinner::{events::*, prelude::*};
 
@@ -57,6 +57,7 @@ fn present_line(
     mut speaker_change_events: EventWriter<SpeakerChangeEvent>,
     mut typewriter: ResMut<Typewriter>,
     mut name_node: Query<&mut Text, With<DialogueNameNode>>,
+    measurement: Option<Res<DialogueTextMeasurement>>,
 ) {
     for event in line_events.read() {
         let name = if let Some(name) = event.line.character_name() {
@@ -69,7 +70,7 @@ fn present_line(
             String::new()
         };
         name_node.single_mut().sections[0].value = name;
-        typewriter.set_line(&event.line);
+        typewriter.set_line(&event.line, measurement.as_deref());
     }
 }
 
@@ -87,6 +88,7 @@ fn continue_dialogue(
     mut dialogue_runners: Query<&mut DialogueRunner>,
     mut typewriter: ResMut<Typewriter>,
     option_selection: Option<Res<OptionSelection>>,
+    measurement: Option<Res<DialogueTextMeasurement>>,
     mut root_visibility: Query<&mut Visibility, With<UiRootNode>>,
     mut continue_visibility: Query<
         &mut Visibility,
@@ -97,10 +99,17 @@ fn continue_dialogue(
         || keys.just_pressed(KeyCode::Enter)
         || mouse_buttons.just_pressed(MouseButton::Left)
         || touches.any_just_pressed();
-    if explicit_continue && !typewriter.is_finished() {
+    if explicit_continue && !typewriter.is_page_finished() {
         typewriter.fast_forward();
         return;
     }
+    if typewriter.has_next_page() {
+        if explicit_continue {
+            typewriter.show_next_page(measurement.as_deref());
+            *continue_visibility.single_mut() = Visibility::Hidden;
+        }
+        return;
+    }
     if (explicit_continue || typewriter.last_before_options) && option_selection.is_none() {
         for mut dialogue_runner in dialogue_runners.iter_mut() {
             if !dialogue_runner.is_waiting_for_option_selection() && dialogue_runner.is_running() {
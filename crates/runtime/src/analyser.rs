@@ -1,16 +1,61 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner/Analyser.cs>
 
 pub(crate) use self::default_analysers::*;
-pub use self::{context::*, diagnosis::*};
+pub use self::{
+    always_false_condition_checker::*, context::*, diagnosis::*, unreachable_node_checker::*,
+};
 use std::fmt::Debug;
 use yarnspinner_core::prelude::*;
 
+mod always_false_condition_checker;
 mod context;
 pub(crate) mod default_analysers;
 mod diagnosis;
+mod unreachable_node_checker;
 
 /// A trait for analysing a compiled Yarn program. Can be used by adding them to a [`Context`] with [`Context::add_analyser`] and then applied to a
 /// compiled Yarn program with [`Dialogue::analyse`](crate::prelude::Dialogue).
+///
+/// Besides the default analysers of [`Context::default_analysers`], you can implement this trait yourself to enforce the conventions of your project.
+///
+/// ## Example
+///
+/// An analyser that reports every node that does not set the variable `$last_npc`:
+///
+/// ```rust
+/// # use yarnspinner_runtime::prelude::*;
+/// # use yarnspinner_core::prelude::*;
+/// #[derive(Debug, Default)]
+/// struct LastNpcChecker {
+///     nodes_without_last_npc: Vec<String>,
+/// }
+///
+/// impl CompiledProgramAnalyser for LastNpcChecker {
+///     fn diagnose(&mut self, program: &Program) {
+///         for (node_name, node) in &program.nodes {
+///             let sets_last_npc = node.instructions.iter().any(|instruction| {
+///                 instruction.opcode() == OpCode::StoreVariable
+///                     && instruction.try_read_operand::<String>(0).as_deref() == Some("$last_npc")
+///             });
+///             if !sets_last_npc {
+///                 self.nodes_without_last_npc.push(node_name.clone());
+///             }
+///         }
+///     }
+///
+///     fn collect_diagnoses(&self) -> Vec<Diagnosis> {
+///         self.nodes_without_last_npc
+///             .iter()
+///             .map(|node_name| {
+///                 Diagnosis::new(DiagnosisSeverity::Error, "Node does not set $last_npc".to_owned())
+///                     .with_node_name(node_name)
+///             })
+///             .collect()
+///     }
+/// }
+///
+/// let context = Context::default_analysers().add_analyser(LastNpcChecker::default());
+/// ```
 pub trait CompiledProgramAnalyser: Debug {
     /// Reads data from the provided program that is later used in [`CompiledProgramAnalyser::collect_diagnoses`].
     fn diagnose(&mut self, program: &Program);
@@ -21,3 +66,13 @@ pub trait CompiledProgramAnalyser: Debug {
     /// Corresponds to the original `GatherDiagnoses`, but was renamed to `collect_diagnoses` because that terminology is more idiomatic in Rust.
     fn collect_diagnoses(&self) -> Vec<Diagnosis>;
 }
+
+impl<T: CompiledProgramAnalyser + ?Sized> CompiledProgramAnalyser for Box<T> {
+    fn diagnose(&mut self, program: &Program) {
+        (**self).diagnose(program);
+    }
+
+    fn collect_diagnoses(&self) -> Vec<Diagnosis> {
+        (**self).collect_diagnoses()
+    }
+}
@@ -0,0 +1,169 @@
+//! Finds options that can never be selected because their condition is constant.
+
+use crate::prelude::*;
+use std::collections::{HashMap, HashSet};
+use yarnspinner_core::prelude::*;
+
+/// A [`CompiledProgramAnalyser`] that adds a [`DiagnosisSeverity::Warning`] diagnosis for each option whose condition is always false,
+/// e.g. `-> Cheat <<if false>>`, which usually is leftover debug code or a typo.
+/// Each diagnosis carries the location of the option's instruction as [`DiagnosisData::Instruction`].
+///
+/// Conditions are evaluated as far as they consist of literals, operators and the functions of [`Library::standard_library`].
+/// Variables that are declared but never assigned to in any of the analysed programs are treated as constants with their declared default value.
+/// Since such variables might still be set by the game through its [`VariableStorage`], this analyser is not part of [`Context::default_analysers`].
+///
+/// ```rust
+/// # use yarnspinner_runtime::prelude::*;
+/// let context = Context::default_analysers().add_analyser(AlwaysFalseConditionChecker::new());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AlwaysFalseConditionChecker {
+    nodes: Vec<Node>,
+    initial_values: HashMap<String, YarnValue>,
+    assigned_variables: HashSet<String>,
+}
+
+impl AlwaysFalseConditionChecker {
+    /// Creates a new [`AlwaysFalseConditionChecker`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn constant_variable(&self, name: &str) -> Option<YarnValue> {
+        if self.assigned_variables.contains(name) {
+            return None;
+        }
+        self.initial_values.get(name).cloned()
+    }
+
+    /// Evaluates the node as far as the values on the stack are known and returns the indices of the `AddOption` instructions
+    /// whose condition is known to be false.
+    fn always_false_options(&self, node: &Node, library: &Library) -> Vec<usize> {
+        let labels: HashSet<_> = node.labels.values().map(|&index| index as usize).collect();
+        // The top of the stack, as far as it is known. Values below it are unknown.
+        let mut stack: Vec<Option<YarnValue>> = Vec::new();
+        let mut always_false = Vec::new();
+        for (index, instruction) in node.instructions.iter().enumerate() {
+            if labels.contains(&index) {
+                // Other paths through the node may arrive here with a different stack
+                stack.clear();
+            }
+            match instruction.opcode() {
+                OpCode::PushString | OpCode::PushFloat | OpCode::PushBool => {
+                    stack.push(Some(instruction.operands[0].clone().into()));
+                }
+                OpCode::PushNull => stack.push(None),
+                OpCode::PushVariable => {
+                    let name: Option<String> = instruction.try_read_operand(0);
+                    stack.push(name.and_then(|name| self.constant_variable(&name)));
+                }
+                OpCode::StoreVariable | OpCode::JumpIfFalse | OpCode::JumpTo => {}
+                OpCode::Pop | OpCode::Jump => pop(&mut stack, 1),
+                OpCode::RunLine | OpCode::RunCommand => {
+                    let count = instruction.try_read_operand::<usize>(1).unwrap_or_default();
+                    pop(&mut stack, count);
+                }
+                OpCode::AddOption => {
+                    let count = instruction.try_read_operand::<usize>(2).unwrap_or_default();
+                    pop(&mut stack, count);
+                    if instruction.try_read_operand::<bool>(3).unwrap_or_default() {
+                        let condition = stack.pop().flatten();
+                        if condition == Some(YarnValue::Boolean(false)) {
+                            always_false.push(index);
+                        }
+                    }
+                }
+                OpCode::CallFunc => {
+                    let result = evaluate_function_call(instruction, &mut stack, library);
+                    stack.push(result);
+                }
+                _ => stack.clear(),
+            }
+        }
+        always_false
+    }
+}
+
+fn pop(stack: &mut Vec<Option<YarnValue>>, count: usize) {
+    stack.truncate(stack.len().saturating_sub(count));
+}
+
+/// Pops the arguments of a `CallFunc` and returns its result if the function is pure and all arguments are known.
+/// Clears the stack if the number of arguments is unknown.
+fn evaluate_function_call(
+    instruction: &Instruction,
+    stack: &mut Vec<Option<YarnValue>>,
+    library: &Library,
+) -> Option<YarnValue> {
+    let Some(Some(YarnValue::Number(parameter_count))) = stack.pop() else {
+        stack.clear();
+        return None;
+    };
+    let parameter_count = parameter_count as usize;
+    if parameter_count > stack.len() {
+        stack.clear();
+        return None;
+    }
+    let parameters: Option<Vec<_>> = stack
+        .split_off(stack.len() - parameter_count)
+        .into_iter()
+        .collect();
+    let function_name: String = instruction.try_read_operand(0)?;
+    // The conversion functions like `bool` panic on invalid input, so only operators are evaluated
+    if !function_name.contains('.') {
+        return None;
+    }
+    let function = library.get(&function_name)?;
+    let takes_parameters = !function.is_variadic()
+        && function.optional_parameter_count() == 0
+        && function.parameter_types().len() == parameter_count;
+    let parameters = parameters?;
+    takes_parameters.then(|| function.call(parameters))
+}
+
+impl CompiledProgramAnalyser for AlwaysFalseConditionChecker {
+    fn diagnose(&mut self, program: &Program) {
+        self.initial_values.extend(
+            program
+                .initial_values
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone().into())),
+        );
+        for node in program.nodes.values() {
+            let assigned = node
+                .instructions
+                .iter()
+                .filter(|instruction| instruction.opcode() == OpCode::StoreVariable)
+                .filter_map(|instruction| instruction.try_read_operand(0));
+            self.assigned_variables.extend(assigned);
+            self.nodes.push(node.clone());
+        }
+    }
+
+    fn collect_diagnoses(&self) -> Vec<Diagnosis> {
+        let library = Library::standard_library();
+        let mut nodes: Vec<_> = self.nodes.iter().collect();
+        nodes.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+        nodes
+            .into_iter()
+            .flat_map(|node| {
+                self.always_false_options(node, &library)
+                    .into_iter()
+                    .map(|instruction_index| {
+                        let line_id: String = node.instructions[instruction_index]
+                            .try_read_operand(0)
+                            .unwrap_or_default();
+                        Diagnosis::new(
+                            DiagnosisSeverity::Warning,
+                            format!("Option {line_id} is never available, because its condition is always false"),
+                        )
+                        .with_node_name(&node.name)
+                        .with_data(DiagnosisData::Instruction(InstructionLocation {
+                            node_name: node.name.clone(),
+                            instruction_index,
+                        }))
+                    })
+            })
+            .collect()
+    }
+}
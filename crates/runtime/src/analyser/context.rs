@@ -33,7 +33,10 @@ impl Context {
 
     /// Sets up a [`Context`] with the default analysers. These are:
     /// - Variable Lister: Adds a [`DiagnosisSeverity::Note`] diagnosis for each variable in the program.
-    /// - Unused Variable Checker: Adds a [`DiagnosisSeverity::Warning`] diagnosis for each unused variable in the program.
+    /// - Unused Variable Checker: Adds a [`DiagnosisSeverity::Warning`] diagnosis for each variable in the program that is assigned, but never read from,
+    ///   or read from, but never assigned to. The diagnosis carries the [`VariableUses`] of the variable as [`Diagnosis::data`].
+    ///   Also adds a [`DiagnosisSeverity::Warning`] diagnosis for each assignment whose value is overwritten on every path before it is read,
+    ///   carrying the location of the assignment as [`DiagnosisData::Instruction`].
     #[must_use]
     pub fn default_analysers() -> Self {
         let mut context = Self::empty();
@@ -43,10 +46,11 @@ impl Context {
         context
     }
 
-    /// Adds an analyser to the [`Context`].
+    /// Adds an analyser to the [`Context`], e.g. one of your own [`CompiledProgramAnalyser`] implementations.
+    /// Its diagnoses are returned by [`Context::finish_analysis`] after those of the analysers added before it.
     #[must_use]
-    pub fn add_analyser(mut self, analyser: Box<dyn CompiledProgramAnalyser>) -> Self {
-        self.0.push(analyser);
+    pub fn add_analyser(mut self, analyser: impl CompiledProgramAnalyser + 'static) -> Self {
+        self.0.push(Box::new(analyser));
         self
     }
 
@@ -1,14 +1,20 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner/Analyser.cs>,
 //! which was split into multiple files.
+//!
+//! ## Implementation notes
+//!
+//! In addition to the original checks, this also reports variables that are read from but never assigned to,
+//! and attaches the [`VariableUses`] of the variable to each diagnosis.
+//! It also reports each assignment whose value is overwritten on every path before it is read, following jumps into other nodes.
 
 use crate::prelude::*;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use yarnspinner_core::prelude::*;
 
 #[derive(Debug, Default)]
 pub(crate) struct UnusedVariableChecker {
-    read_variables: HashSet<String>,
-    written_variables: HashSet<String>,
+    variable_uses: HashMap<String, VariableUses>,
+    nodes: HashMap<String, Node>,
 }
 
 impl UnusedVariableChecker {
@@ -20,40 +26,300 @@ impl UnusedVariableChecker {
 impl CompiledProgramAnalyser for UnusedVariableChecker {
     fn diagnose(&mut self, program: &Program) {
         // In each node, find all reads and writes to variables
-        let new_variables = program.nodes.values().flat_map(|node| {
-            node.instructions
-                .iter()
-                .filter_map(|instruction| match instruction.opcode() {
-                    OpCode::PushVariable | OpCode::StoreVariable => {
-                        Some((instruction.opcode(), instruction.operands[0].clone()))
-                    }
-                    _ => None,
-                })
-                .map(|(opcode, operand)| (opcode, operand.try_into().unwrap()))
-        });
-        for (opcode, variable) in new_variables {
-            match opcode {
-                OpCode::PushVariable => {
-                    self.read_variables.insert(variable);
+        for node in program.nodes.values() {
+            self.nodes.insert(node.name.clone(), node.clone());
+            for (instruction_index, instruction) in node.instructions.iter().enumerate() {
+                let opcode = instruction.opcode();
+                if !matches!(opcode, OpCode::PushVariable | OpCode::StoreVariable) {
+                    continue;
                 }
-                OpCode::StoreVariable => {
-                    self.written_variables.insert(variable);
+                let variable: String = instruction.operands[0].clone().try_into().unwrap();
+                let uses = self
+                    .variable_uses
+                    .entry(variable.clone())
+                    .or_insert_with(|| VariableUses {
+                        variable_name: variable,
+                        ..Default::default()
+                    });
+                let location = InstructionLocation {
+                    node_name: node.name.clone(),
+                    instruction_index,
+                };
+                match opcode {
+                    OpCode::PushVariable => uses.reads.push(location),
+                    OpCode::StoreVariable => uses.writes.push(location),
+                    _ => unreachable!(),
                 }
-                _ => unreachable!(),
             }
         }
     }
 
     fn collect_diagnoses(&self) -> Vec<Diagnosis> {
-        // Report the write-only variables
-        self.written_variables
-            .difference(&self.read_variables)
-            .map(|variable| {
+        let mut variable_uses: Vec<_> = self.variable_uses.values().collect();
+        variable_uses.sort_by(|lhs, rhs| lhs.variable_name.cmp(&rhs.variable_name));
+        variable_uses
+            .into_iter()
+            .filter_map(|uses| {
+                let variable = &uses.variable_name;
+                let message = if uses.reads.is_empty() {
+                    // Report the write-only variables
+                    format!("Variable {variable} is assigned, but never read from")
+                } else if uses.writes.is_empty() {
+                    // Report the read-only variables, which always have their default value unless they are set by the game
+                    format!("Variable {variable} is read from, but never assigned to")
+                } else {
+                    return None;
+                };
+                let diagnosis = Diagnosis::new(DiagnosisSeverity::Warning, message)
+                    .with_data(DiagnosisData::VariableUses(uses.clone()));
+                Some(diagnosis)
+            })
+            .chain(self.dead_stores().into_iter().map(|(variable, location)| {
                 Diagnosis::new(
                     DiagnosisSeverity::Warning,
-                    format!("Variable {variable} is assigned, but never read from"),
+                    format!("Variable {variable} is assigned, but the value is always overwritten before it is read"),
                 )
+                .with_node_name(&location.node_name)
+                .with_data(DiagnosisData::Instruction(location))
+            }))
+            .collect()
+    }
+}
+
+/// The instruction that runs after another one, see [`UnusedVariableChecker::dead_stores`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Successor {
+    /// The instruction with the given index in the list of all analysed instructions.
+    Instruction(usize),
+    /// The dialogue stops, or continues somewhere that cannot be determined without running it.
+    Exit,
+}
+
+impl UnusedVariableChecker {
+    /// Finds the assignments whose value is overwritten on every path before it is read, sorted by their location.
+    /// Leaving the dialogue counts as reading every variable, since the game can still read them from the [`VariableStorage`].
+    /// Reads by the game while the dialogue runs, e.g. in a command, are not taken into account.
+    fn dead_stores(&self) -> Vec<(String, InstructionLocation)> {
+        let mut nodes: Vec<_> = self.nodes.values().collect();
+        nodes.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+        let mut node_starts = HashMap::new();
+        let mut instructions = Vec::new();
+        for node in &nodes {
+            node_starts.insert(node.name.as_str(), instructions.len());
+            instructions.extend(
+                node.instructions
+                    .iter()
+                    .enumerate()
+                    .map(|(index, instruction)| (*node, index, instruction)),
+            );
+        }
+        let mut variables: Vec<String> = self.variable_uses.keys().cloned().collect();
+        variables.sort();
+        let variable_indices: HashMap<_, _> = variables
+            .iter()
+            .enumerate()
+            .map(|(index, variable)| (variable.as_str(), index))
+            .collect();
+        let all_variables: HashSet<usize> = (0..variables.len()).collect();
+        let variable_of = |instruction: &Instruction| {
+            let variable: String = instruction.try_read_operand(0)?;
+            variable_indices.get(variable.as_str()).copied()
+        };
+
+        let successors: Vec<Vec<Successor>> = instructions
+            .iter()
+            .enumerate()
+            .map(|(position, (node, index, instruction))| {
+                let label = |operand: usize| {
+                    let label: String = instruction.try_read_operand(operand)?;
+                    let target = *node.labels.get(&label)?;
+                    Some(Successor::Instruction(
+                        node_starts[node.name.as_str()] + target as usize,
+                    ))
+                };
+                let next = if index + 1 < node.instructions.len() {
+                    Successor::Instruction(position + 1)
+                } else {
+                    Successor::Exit
+                };
+                match instruction.opcode() {
+                    OpCode::JumpTo => vec![label(0).unwrap_or(Successor::Exit)],
+                    OpCode::JumpIfFalse => vec![next, label(0).unwrap_or(Successor::Exit)],
+                    // Jumps to the destination of the selected option, which may be any label
+                    OpCode::Jump => node
+                        .labels
+                        .values()
+                        .map(|&target| {
+                            Successor::Instruction(
+                                node_starts[node.name.as_str()] + target as usize,
+                            )
+                        })
+                        .collect(),
+                    OpCode::RunNode => {
+                        let destination = index
+                            .checked_sub(1)
+                            .map(|previous| &node.instructions[previous])
+                            .filter(|previous| previous.opcode() == OpCode::PushString)
+                            .and_then(|previous| previous.try_read_operand::<String>(0))
+                            .and_then(|destination| self.nodes.get(&destination))
+                            .filter(|destination| !destination.instructions.is_empty())
+                            .map(|destination| node_starts[destination.name.as_str()]);
+                        vec![destination.map_or(Successor::Exit, Successor::Instruction)]
+                    }
+                    OpCode::Stop => vec![Successor::Exit],
+                    _ => vec![next],
+                }
+            })
+            .collect();
+
+        // Backwards liveness analysis: which variables may still be read before they are overwritten
+        let live_out = |live_in: &[HashSet<usize>], position: usize| -> HashSet<usize> {
+            successors[position]
+                .iter()
+                .flat_map(|successor| match successor {
+                    Successor::Instruction(next) => live_in[*next].clone(),
+                    Successor::Exit => all_variables.clone(),
+                })
+                .collect()
+        };
+        let mut live_in = vec![HashSet::new(); instructions.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for position in (0..instructions.len()).rev() {
+                let (_, _, instruction) = instructions[position];
+                let mut live = live_out(&live_in, position);
+                match instruction.opcode() {
+                    OpCode::PushVariable => live.extend(variable_of(instruction)),
+                    OpCode::StoreVariable => {
+                        if let Some(variable) = variable_of(instruction) {
+                            live.remove(&variable);
+                        }
+                    }
+                    _ => {}
+                }
+                if live != live_in[position] {
+                    live_in[position] = live;
+                    changed = true;
+                }
+            }
+        }
+
+        instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, _, instruction))| instruction.opcode() == OpCode::StoreVariable)
+            .filter_map(|(position, (node, index, instruction))| {
+                let variable = variable_of(instruction)?;
+                let is_internal = variables[variable].starts_with("$Yarn.Internal.");
+                (!is_internal && !live_out(&live_in, position).contains(&variable)).then(|| {
+                    let location = InstructionLocation {
+                        node_name: node.name.clone(),
+                        instruction_index: *index,
+                    };
+                    (variables[variable].clone(), location)
+                })
             })
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_write_only_and_read_only_variables() {
+        let node = Node {
+            name: "Start".to_owned(),
+            instructions: vec![
+                instruction(OpCode::PushVariable, "$read_only"),
+                instruction(OpCode::StoreVariable, "$write_only"),
+                instruction(OpCode::PushVariable, "$used"),
+                instruction(OpCode::StoreVariable, "$used"),
+            ],
+            ..Default::default()
+        };
+        let program = Program {
+            nodes: [(node.name.clone(), node)].into_iter().collect(),
+            ..Default::default()
+        };
+        let mut checker = UnusedVariableChecker::new();
+        checker.diagnose(&program);
+        let diagnoses = checker.collect_diagnoses();
+
+        assert_eq!(diagnoses.len(), 2);
+        assert_eq!(
+            diagnoses[0].message,
+            "Variable $read_only is read from, but never assigned to"
+        );
+        assert_eq!(
+            diagnoses[0].data,
+            Some(DiagnosisData::VariableUses(VariableUses {
+                variable_name: "$read_only".to_owned(),
+                reads: vec![InstructionLocation {
+                    node_name: "Start".to_owned(),
+                    instruction_index: 0,
+                }],
+                writes: vec![],
+            }))
+        );
+        assert_eq!(
+            diagnoses[1].message,
+            "Variable $write_only is assigned, but never read from"
+        );
+    }
+
+    #[test]
+    fn reports_assignments_that_are_always_overwritten() {
+        let start = Node {
+            name: "Start".to_owned(),
+            instructions: vec![
+                instruction(OpCode::PushBool, ""),
+                instruction(OpCode::StoreVariable, "$mood"),
+                instruction(OpCode::PushString, "End"),
+                instruction(OpCode::RunNode, ""),
+            ],
+            ..Default::default()
+        };
+        let end = Node {
+            name: "End".to_owned(),
+            instructions: vec![
+                instruction(OpCode::PushBool, ""),
+                instruction(OpCode::StoreVariable, "$mood"),
+                instruction(OpCode::PushVariable, "$mood"),
+                instruction(OpCode::StoreVariable, "$mood"),
+            ],
+            ..Default::default()
+        };
+        let program = Program {
+            nodes: [(start.name.clone(), start), (end.name.clone(), end)]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let mut checker = UnusedVariableChecker::new();
+        checker.diagnose(&program);
+        let diagnoses = checker.collect_diagnoses();
+
+        assert_eq!(diagnoses.len(), 1);
+        assert_eq!(
+            diagnoses[0].message,
+            "Variable $mood is assigned, but the value is always overwritten before it is read"
+        );
+        assert_eq!(
+            diagnoses[0].data,
+            Some(DiagnosisData::Instruction(InstructionLocation {
+                node_name: "Start".to_owned(),
+                instruction_index: 1,
+            }))
+        );
+    }
+
+    fn instruction(opcode: OpCode, variable: &str) -> Instruction {
+        Instruction {
+            opcode: opcode.into(),
+            operands: vec![variable.to_owned().into()],
+        }
+    }
+}
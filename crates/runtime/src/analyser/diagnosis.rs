@@ -27,6 +27,70 @@ pub struct Diagnosis {
     pub line: Option<usize>,
     /// The 1-indexed column number, i.e. the character index in the line, of the node that caused the diagnosis, if any.
     pub column: Option<usize>,
+    /// Structured data about the cause of the diagnosis, if any. Allows tools to offer automated fixes.
+    ///
+    /// ## Implementation notes
+    ///
+    /// This field is not present in the original implementation.
+    pub data: Option<DiagnosisData>,
+}
+
+/// Structured data attached to a [`Diagnosis`] by the [`CompiledProgramAnalyser`] that created it.
+///
+/// ## Implementation notes
+///
+/// This type is not present in the original implementation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum DiagnosisData {
+    /// All places in the program that use a variable. Attached to the diagnoses about variables that are never read from or never assigned to.
+    VariableUses(VariableUses),
+    /// The location of the instruction that caused the diagnosis.
+    Instruction(InstructionLocation),
+}
+
+/// All places in the analysed programs that read or write a variable. Part of [`DiagnosisData`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct VariableUses {
+    /// The name of the variable, including the leading `$`.
+    pub variable_name: String,
+    /// The instructions that read the variable.
+    pub reads: Vec<InstructionLocation>,
+    /// The instructions that assign to the variable.
+    pub writes: Vec<InstructionLocation>,
+}
+
+/// The location of an instruction in a compiled program.
+///
+/// The position in the source file can be looked up in the `debug_info` of the compilation that produced the program,
+/// using the [`InstructionLocation::node_name`] as key and the [`InstructionLocation::instruction_index`] as instruction number.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct InstructionLocation {
+    /// The name of the node that contains the instruction.
+    pub node_name: String,
+    /// The 0-indexed position of the instruction in the node.
+    pub instruction_index: usize,
 }
 
 /// The severity of a [`Diagnosis`], as reported by a [`CompiledProgramAnalyser`].
@@ -67,6 +131,7 @@ impl Diagnosis {
             node_name: Default::default(),
             line: Default::default(),
             column: Default::default(),
+            data: Default::default(),
         }
     }
 
@@ -90,6 +155,13 @@ impl Diagnosis {
         self.column = Some(column);
         self
     }
+
+    /// Sets the structured data of the diagnosis. By default, this is `None`.
+    #[must_use]
+    pub fn with_data(mut self, data: DiagnosisData) -> Self {
+        self.data = Some(data);
+        self
+    }
 }
 
 impl Display for Diagnosis {
@@ -0,0 +1,153 @@
+//! Finds nodes that no path through the program leads to.
+
+use crate::prelude::*;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use yarnspinner_core::prelude::*;
+
+/// A [`CompiledProgramAnalyser`] that follows the `<<jump>>`s of the analysed programs, starting from the given entry nodes,
+/// and adds a [`DiagnosisSeverity::Warning`] diagnosis for each node that can never be reached.
+/// The entry nodes are the nodes your game starts dialogue at with [`Dialogue::set_node`].
+///
+/// Jumps to an expression, e.g. `<<jump {$destination}>>`, cannot be followed without running the node.
+/// If a reachable node contains one, the diagnoses mention that the nodes might still be reached through it.
+///
+/// Not part of the default analysers, since the entry nodes depend on the game. Add it to a [`Context`] with [`Context::add_analyser`]:
+///
+/// ```rust
+/// # use yarnspinner_runtime::prelude::*;
+/// let context = Context::default_analysers().add_analyser(UnreachableNodeChecker::new(["Start", "Shop"]));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UnreachableNodeChecker {
+    entry_nodes: Vec<String>,
+    jump_destinations: HashMap<String, Vec<String>>,
+    nodes_with_dynamic_jumps: HashSet<String>,
+}
+
+impl UnreachableNodeChecker {
+    /// Creates a checker that considers the given nodes reachable, along with all nodes they lead to.
+    pub fn new(entry_nodes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            entry_nodes: entry_nodes.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// The names of all analysed nodes that can be reached from the entry nodes.
+    pub fn reachable_nodes(&self) -> HashSet<String> {
+        let mut reachable: HashSet<_> = self
+            .entry_nodes
+            .iter()
+            .filter(|node_name| self.jump_destinations.contains_key(*node_name))
+            .cloned()
+            .collect();
+        let mut queue: Vec<_> = reachable.iter().cloned().collect();
+        while let Some(node_name) = queue.pop() {
+            for destination in &self.jump_destinations[&node_name] {
+                if self.jump_destinations.contains_key(destination)
+                    && reachable.insert(destination.clone())
+                {
+                    queue.push(destination.clone());
+                }
+            }
+        }
+        reachable
+    }
+}
+
+impl CompiledProgramAnalyser for UnreachableNodeChecker {
+    fn diagnose(&mut self, program: &Program) {
+        for (node_name, node) in &program.nodes {
+            let destinations: Vec<_> = node.jump_destinations().collect();
+            let jump_count = node
+                .instructions
+                .iter()
+                .filter(|instruction| instruction.opcode() == OpCode::RunNode)
+                .count();
+            if jump_count > destinations.len() {
+                self.nodes_with_dynamic_jumps.insert(node_name.clone());
+            }
+            self.jump_destinations
+                .insert(node_name.clone(), destinations);
+        }
+    }
+
+    fn collect_diagnoses(&self) -> Vec<Diagnosis> {
+        let reachable = self.reachable_nodes();
+        let has_dynamic_jumps = reachable
+            .iter()
+            .any(|node_name| self.nodes_with_dynamic_jumps.contains(node_name));
+        let unreachable: BTreeSet<_> = self
+            .jump_destinations
+            .keys()
+            .filter(|node_name| !reachable.contains(*node_name))
+            .collect();
+        unreachable
+            .into_iter()
+            .map(|node_name| {
+                let mut message = format!(
+                    "Node {node_name} can never be reached from the entry nodes {}",
+                    self.entry_nodes.join(", ")
+                );
+                if has_dynamic_jumps {
+                    message.push_str(", unless a jump to an expression leads to it");
+                }
+                Diagnosis::new(DiagnosisSeverity::Warning, message).with_node_name(node_name)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, destinations: &[&str]) -> (String, Node) {
+        let instructions = destinations
+            .iter()
+            .flat_map(|destination| {
+                [
+                    Instruction {
+                        opcode: OpCode::PushString.into(),
+                        operands: vec![destination.to_string().into()],
+                    },
+                    Instruction {
+                        opcode: OpCode::RunNode.into(),
+                        operands: vec![],
+                    },
+                ]
+            })
+            .collect();
+        let node = Node {
+            name: name.to_owned(),
+            instructions,
+            ..Default::default()
+        };
+        (name.to_owned(), node)
+    }
+
+    #[test]
+    fn reports_nodes_not_reachable_from_entry_nodes() {
+        let program = Program {
+            nodes: [
+                node("Start", &["Shop"]),
+                node("Shop", &["Start"]),
+                node("Tavern", &[]),
+                node("Secret", &["Shop"]),
+                node("Orphan", &["Secret"]),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let mut checker = UnreachableNodeChecker::new(["Start", "Tavern"]);
+        checker.diagnose(&program);
+
+        let unreachable: Vec<_> = checker
+            .collect_diagnoses()
+            .into_iter()
+            .map(|diagnosis| diagnosis.node_name.unwrap())
+            .collect();
+        assert_eq!(unreachable, ["Orphan", "Secret"]);
+    }
+}
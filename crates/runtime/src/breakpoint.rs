@@ -0,0 +1,35 @@
+//! Used by the debugging functionality of [`Dialogue`](crate::prelude::Dialogue), such as [`Dialogue::add_breakpoint`](crate::prelude::Dialogue::add_breakpoint).
+
+#[cfg(any(feature = "bevy", feature = "serde"))]
+use crate::prelude::*;
+
+/// A point in a [`Program`](yarnspinner_core::prelude::Program) at which [`Dialogue::continue_`](crate::prelude::Dialogue::continue_) pauses and emits [`DialogueEvent::BreakpointHit`](crate::prelude::DialogueEvent::BreakpointHit).
+/// Registered with [`Dialogue::add_breakpoint`](crate::prelude::Dialogue::add_breakpoint).
+///
+/// Breakpoints refer to instructions rather than lines of source code, since the runtime knows nothing about the source code.
+/// The compiler's `DebugInfo::first_instruction_on_line` translates a line in a Yarn file to the instruction index needed here.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct Breakpoint {
+    /// The name of the node containing the instruction.
+    pub node_name: String,
+
+    /// The index of the instruction in the node. The dialogue pauses right before running it.
+    pub instruction_index: usize,
+}
+
+impl Breakpoint {
+    /// Creates a new [`Breakpoint`] before the given instruction of the given node.
+    pub fn new(node_name: impl Into<String>, instruction_index: usize) -> Self {
+        Self {
+            node_name: node_name.into(),
+            instruction_index,
+        }
+    }
+}
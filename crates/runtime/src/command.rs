@@ -4,15 +4,14 @@
 //! The original delegates command parsing to the Unity plugin, but we think it's foundational enough to do it directly in the runtime.
 
 use crate::markup::normalize;
-#[cfg(any(feature = "bevy", feature = "serde"))]
 use crate::prelude::*;
 use yarnspinner_core::prelude::YarnValue;
 
 /// A custom command found in a Yarn file within the `<<` and `>>` characters.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Hash)]
 #[cfg_attr(feature = "bevy", derive(Reflect))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
 #[cfg_attr(
     all(feature = "bevy", feature = "serde"),
     reflect(Serialize, Deserialize)
@@ -23,41 +22,96 @@ pub struct Command {
     pub name: String,
 
     /// The parameters passed to the command. Strings that are surrounded by quotes are passed as a single parameter.
+    /// Expressions in curly braces, e.g. `<<give_gold {$gold * 2}>>`, have already been evaluated and substituted.
+    ///
+    /// Parameters are typed as follows:
+    /// - Quoted parameters are always [`YarnValue::String`]s.
+    /// - `true` and `false` are [`YarnValue::Boolean`]s.
+    /// - Finite numbers such as `12`, `-3.5` or `1e3` are [`YarnValue::Number`]s.
+    /// - Everything else is a [`YarnValue::String`].
     ///
     /// ## Examples
     ///
-    /// - The command `<<set_sprite ship "happy">>` has the parameters `["ship", "happy"]`.
-    /// - The command `<<set_sprite ship "very happy">>`, the parameters are `["ship", "very happy"]`.
+    /// - The command `<<set_sprite ship "happy">>` has the parameters `[String("ship"), String("happy")]`.
+    /// - The command `<<set_sprite ship "very happy">>`, the parameters are `[String("ship"), String("very happy")]`.
+    /// - The command `<<move ship 12.5 true "7">>` has the parameters `[String("ship"), Number(12.5), Boolean(true), String("7")]`.
     ///
     /// ## Return value
     ///
-    /// The parameters are returned without underlying type information, so you will have to convert them using `YarnValue::try_into`.
+    /// Convert the parameters into the types you need using `YarnValue::try_into`.
+    /// Since [`YarnValue`]s convert into each other where possible, a parameter that was parsed as a number can still be read as a string.
+    /// Quote parameters whose exact text matters, e.g. `"007"`, since `007` would be read as the number `7`.
     pub parameters: Vec<YarnValue>,
 
     /// The raw, unprocessed command as it appeared in the Yarn file between the `<<` and `>>` characters.
     pub raw: String,
+
+    /// The name of the node that ran the command, e.g. to tell the writers where a command handler failed.
+    /// [`None`] for commands created with [`Command::try_parse`].
+    pub node_name: Option<String>,
+
+    /// The place in the Yarn source code the command was compiled from.
+    /// Only available for nodes whose positions were added with [`Dialogue::add_source_positions`](crate::prelude::Dialogue::add_source_positions).
+    pub source_location: Option<SourceLocation>,
 }
 
 impl Command {
-    pub(crate) fn parse(input: String) -> Self {
-        assert!(!input.trim().is_empty(), "Failed to parse the command \"{input}\" because it is composed entirely of whitespace. \
-            Help: You might have passed an expression that evaluates to whitespace, e.g. `{{0}} {{\"  \"}}`. \
-            If you think this is a bug, please report it at https://github.com/YarnSpinnerTool/YarnSpinner-Rust/issues/new");
-
+    /// Parses the text of a command, i.e. what appears between the `<<` and `>>` characters after expressions were substituted,
+    /// into its [`Command::name`] and typed [`Command::parameters`]. The [`Dialogue`](crate::prelude::Dialogue) already does this for every
+    /// [`DialogueEvent::Command`](crate::prelude::DialogueEvent::Command) it emits, so this is only needed for command text from other sources,
+    /// e.g. when running a [`Program`](yarnspinner_core::prelude::Program) with a custom interpreter, and ensures such text is parsed the same way.
+    ///
+    /// Returns [`None`] if the input contains no command name, e.g. because it is empty or consists only of whitespace.
+    pub fn try_parse(input: impl Into<String>) -> Option<Self> {
+        let input = input.into();
         let mut components = split_command_text(&input);
-        assert!(
-            !components.is_empty(),
-            "Parsing the command \"{}\" resulted in an empty list of components. \
-            This is a bug. Please report it at https://github.com/YarnSpinnerTool/YarnSpinner-Rust/issues/new",
-            input
-        );
-        let name = components.remove(0);
-        let parameters = components.into_iter().map(YarnValue::from).collect();
-        Self {
+        if components.is_empty() {
+            return None;
+        }
+        let (name, _) = components.remove(0);
+        let parameters = components
+            .into_iter()
+            .map(|(component, is_quoted)| parse_parameter(component, is_quoted))
+            .collect();
+        Some(Self {
             name,
             parameters,
             raw: input,
-        }
+            node_name: None,
+            source_location: None,
+        })
+    }
+
+    /// Like [`Command::try_parse`], but for commands emitted by the [`Dialogue`](crate::prelude::Dialogue), where empty input is a bug in the Yarn script.
+    pub(crate) fn parse(input: impl Into<String>) -> Self {
+        let input = input.into();
+        assert!(!input.trim().is_empty(), "Failed to parse the command \"{input}\" because it is composed entirely of whitespace. \
+            Help: You might have passed an expression that evaluates to whitespace, e.g. `{{0}} {{\"  \"}}`. \
+            If you think this is a bug, please report it at https://github.com/YarnSpinnerTool/YarnSpinner-Rust/issues/new");
+        Self::try_parse(input.clone()).unwrap_or_else(|| {
+            panic!(
+                "Parsing the command \"{input}\" resulted in an empty list of components. \
+                This is a bug. Please report it at https://github.com/YarnSpinnerTool/YarnSpinner-Rust/issues/new"
+            )
+        })
+    }
+}
+
+/// Infers the type of a parameter as documented in [`Command::parameters`].
+fn parse_parameter(component: String, is_quoted: bool) -> YarnValue {
+    if is_quoted {
+        return component.into();
+    }
+    if let Ok(boolean) = component.parse::<bool>() {
+        boolean.into()
+    } else if let Some(number) = component
+        .parse::<f32>()
+        .ok()
+        .filter(|number| number.is_finite())
+    {
+        number.into()
+    } else {
+        component.into()
     }
 }
 
@@ -75,7 +129,13 @@ impl Command {
 /// had been terminated at the end of the input.)
 /// - When inside a pair of double-quote characters, the string
 /// `\\` will be converted to `\`, and the string `\"` will be converted to `"`.
-fn split_command_text(input: &str) -> Vec<String> {
+///
+/// Each sub-string is returned alongside whether it was double-quoted.
+///
+/// ## Implementation note
+///
+/// The original only returns the sub-strings. We also return whether they were quoted so that [`Command::parameters`] can be typed.
+fn split_command_text(input: &str) -> Vec<(String, bool)> {
     let input = normalize(input);
     let mut chars = input.chars().peekable();
     let mut results = Vec::new();
@@ -87,7 +147,7 @@ fn split_command_text(input: &str) -> Vec<String> {
                     // We've reached the end of a run of visible
                     // characters. Add this run to the result list and
                     // prepare for the next one.
-                    results.push(std::mem::take(&mut current_component));
+                    results.push((std::mem::take(&mut current_component), false));
                 } else {
                     // We encountered a whitespace character, but
                     // didn't have any characters queued up. Skip this
@@ -103,7 +163,7 @@ fn split_command_text(input: &str) -> Vec<String> {
                             // Oops, we ended the input while parsing a
                             // quoted string! Dump our current word
                             // immediately and return.
-                            results.push(current_component);
+                            results.push((current_component, true));
                             return results;
                         }
                     };
@@ -133,7 +193,7 @@ fn split_command_text(input: &str) -> Vec<String> {
                         }
                     }
                 }
-                results.push(std::mem::take(&mut current_component));
+                results.push((std::mem::take(&mut current_component), true));
             }
             _ => {
                 current_component.push(char);
@@ -141,7 +201,7 @@ fn split_command_text(input: &str) -> Vec<String> {
         }
     }
     if !current_component.is_empty() {
-        results.push(current_component);
+        results.push((current_component, false));
     }
     results
 }
@@ -175,7 +235,10 @@ mod tests {
             ),
             ("one      two", vec!["one", "two"]),
         ] {
-            let parsed_components = split_command_text(input);
+            let parsed_components: Vec<_> = split_command_text(input)
+                .into_iter()
+                .map(|(component, _)| component)
+                .collect();
 
             assert_eq!(expected_components, parsed_components);
         }
@@ -190,6 +253,8 @@ mod tests {
                     name: "foo".to_string(),
                     parameters: vec!["bar".into()],
                     raw: "foo bar".to_string(),
+                    node_name: None,
+                    source_location: None,
                 },
             ),
             (
@@ -198,6 +263,8 @@ mod tests {
                     name: "ayy".to_string(),
                     parameters: vec![],
                     raw: "ayy".to_string(),
+                    node_name: None,
+                    source_location: None,
                 },
             ),
             (
@@ -206,14 +273,34 @@ mod tests {
                     name: "foo".to_string(),
                     parameters: vec!["bar baz".into()],
                     raw: "foo \"bar baz\"".to_string(),
+                    node_name: None,
+                    source_location: None,
                 },
             ),
             (
                 "set_sprite ship \"very happy\" 12.3",
                 Command {
                     name: "set_sprite".to_string(),
-                    parameters: vec!["ship".into(), "very happy".into(), "12.3".into()],
+                    parameters: vec!["ship".into(), "very happy".into(), 12.3.into()],
                     raw: "set_sprite ship \"very happy\" 12.3".to_string(),
+                    node_name: None,
+                    source_location: None,
+                },
+            ),
+            (
+                "give 10 -2.5 true \"10\" infinity",
+                Command {
+                    name: "give".to_string(),
+                    parameters: vec![
+                        10.0.into(),
+                        (-2.5).into(),
+                        true.into(),
+                        "10".into(),
+                        "infinity".into(),
+                    ],
+                    raw: "give 10 -2.5 true \"10\" infinity".to_string(),
+                    node_name: None,
+                    source_location: None,
                 },
             ),
             (
@@ -222,6 +309,8 @@ mod tests {
                     name: "!@#$%^&*()⁄€‹›ﬁﬂ‡°·‚‘-=_+".to_string(),
                     parameters: vec![],
                     raw: "!@#$%^&*()⁄€‹›ﬁﬂ‡°·‚‘-=_+".to_string(),
+                    node_name: None,
+                    source_location: None,
                 },
             ),
             (
@@ -230,6 +319,8 @@ mod tests {
                     name: "A long name".to_string(),
                     parameters: vec![],
                     raw: "\"A long name\"".to_string(),
+                    node_name: None,
+                    source_location: None,
                 },
             ),
         ] {
@@ -238,4 +329,15 @@ mod tests {
             assert_eq!(expected_command, parsed_command);
         }
     }
+
+    #[test]
+    fn does_not_parse_whitespace() {
+        for input in ["", "   ", "\t\n"] {
+            assert_eq!(None, Command::try_parse(input));
+        }
+        assert_eq!(
+            Some(Command::parse("foo bar")),
+            Command::try_parse("foo bar")
+        );
+    }
 }
@@ -0,0 +1,324 @@
+//! Lets scripts express how long a variable lives through its name,
+//! e.g. `$temp.` variables that are forgotten after each conversation and `$save.` variables that go into the save game.
+
+use crate::prelude::*;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use yarnspinner_core::prelude::*;
+
+/// A [`VariableStorage`] that routes each variable to one of several storages by the prefix of its name.
+/// Variables whose name starts with the prefix of a route are kept in that route's storage. If several prefixes match, the longest one wins.
+/// All other variables are kept in the default storage.
+///
+/// [`VariableStorage::clear`] clears all storages. To reset only some of them, e.g. at the end of a conversation,
+/// clear them through [`CompositeVariableStorage::route_mut`].
+///
+/// Shallow clones share the same storages, as long as the storages passed in share their variables with their shallow clones.
+/// Routes added after cloning are not shared.
+///
+/// [`VariableStorage::add_observer`] only succeeds if all storages support observers.
+/// Transactions are begun, committed and rolled back on each storage in turn. Beginning them is all-or-nothing,
+/// but committing them is not atomic: if committing one storage fails, the storages before it stay committed
+/// and the ones after it stay in their transaction, so that the caller can still commit or roll them back.
+///
+/// ## Example
+///
+/// ```rust
+/// # use yarnspinner_runtime::prelude::*;
+/// let save_game = MemoryVariableStorage::new();
+/// let mut storage = CompositeVariableStorage::new(Box::new(MemoryVariableStorage::new()));
+/// storage
+///     .add_route("$temp.", Box::new(MemoryVariableStorage::new()))
+///     .add_route("$save.", save_game.clone_shallow());
+///
+/// storage.set("$save.gold".to_owned(), 10.into()).unwrap();
+/// storage.set("$temp.mood".to_owned(), "grumpy".into()).unwrap();
+/// assert!(save_game.contains("$save.gold"));
+///
+/// // At the end of a conversation
+/// storage.route_mut("$temp.").unwrap().clear();
+/// assert!(!storage.contains("$temp.mood"));
+/// assert!(storage.contains("$save.gold"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompositeVariableStorage {
+    default_storage: Box<dyn VariableStorage>,
+    /// Sorted by descending prefix length, so that the first matching route is the longest one.
+    routes: Vec<(String, Box<dyn VariableStorage>)>,
+}
+
+impl CompositeVariableStorage {
+    /// Creates a storage that keeps all variables in `default_storage` until routes are added.
+    pub fn new(default_storage: Box<dyn VariableStorage>) -> Self {
+        Self {
+            default_storage,
+            routes: Vec::new(),
+        }
+    }
+
+    /// Keeps all variables whose name starts with `prefix`, e.g. `$temp.`, in `storage`.
+    /// Replaces the storage of a route previously added with the same prefix.
+    /// Variables that were already set are not moved.
+    pub fn add_route(
+        &mut self,
+        prefix: impl Into<String>,
+        storage: Box<dyn VariableStorage>,
+    ) -> &mut Self {
+        let prefix = prefix.into();
+        self.routes
+            .retain(|(route_prefix, _)| *route_prefix != prefix);
+        self.routes.push((prefix, storage));
+        self.routes
+            .sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        self
+    }
+
+    /// The storage of the route with the given prefix, if there is one.
+    pub fn route(&self, prefix: &str) -> Option<&dyn VariableStorage> {
+        self.routes
+            .iter()
+            .find(|(route_prefix, _)| route_prefix == prefix)
+            .map(|(_, storage)| storage.as_ref())
+    }
+
+    /// The storage of the route with the given prefix, if there is one.
+    pub fn route_mut(&mut self, prefix: &str) -> Option<&mut dyn VariableStorage> {
+        self.routes
+            .iter_mut()
+            .find(|(route_prefix, _)| route_prefix == prefix)
+            .map(|(_, storage)| storage.as_mut())
+    }
+
+    /// The storage that keeps all variables not matched by any route.
+    pub fn default_storage(&self) -> &dyn VariableStorage {
+        self.default_storage.as_ref()
+    }
+
+    /// The storage that keeps all variables not matched by any route.
+    pub fn default_storage_mut(&mut self) -> &mut dyn VariableStorage {
+        self.default_storage.as_mut()
+    }
+
+    /// The index into `routes` of the route the variable belongs to, or [`None`] for the default storage.
+    fn route_index(&self, name: &str) -> Option<usize> {
+        self.routes
+            .iter()
+            .position(|(prefix, _)| name.starts_with(prefix.as_str()))
+    }
+
+    fn storage_for(&self, name: &str) -> &dyn VariableStorage {
+        match self.route_index(name) {
+            Some(index) => self.routes[index].1.as_ref(),
+            None => self.default_storage.as_ref(),
+        }
+    }
+
+    fn storage_for_mut(&mut self, name: &str) -> &mut dyn VariableStorage {
+        match self.route_index(name) {
+            Some(index) => self.routes[index].1.as_mut(),
+            None => self.default_storage.as_mut(),
+        }
+    }
+
+    fn storages_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn VariableStorage>> {
+        std::iter::once(&mut self.default_storage)
+            .chain(self.routes.iter_mut().map(|(_, storage)| storage))
+    }
+
+    fn validate_name(name: &str) -> Result<()> {
+        if name.starts_with('$') {
+            Ok(())
+        } else {
+            Err(VariableStorageError::InvalidVariableName {
+                name: name.to_string(),
+            })
+        }
+    }
+}
+
+impl VariableStorage for CompositeVariableStorage {
+    fn clone_shallow(&self) -> Box<dyn VariableStorage> {
+        Box::new(self.clone())
+    }
+
+    fn set(&mut self, name: String, value: YarnValue) -> Result<()> {
+        Self::validate_name(&name)?;
+        self.storage_for_mut(&name).set(name, value)
+    }
+
+    fn get(&self, name: &str) -> Result<YarnValue> {
+        Self::validate_name(name)?;
+        self.storage_for(name).get(name)
+    }
+
+    fn extend(&mut self, values: HashMap<String, YarnValue>) -> Result<()> {
+        for name in values.keys() {
+            Self::validate_name(name)?;
+        }
+        let mut values_by_route: HashMap<Option<usize>, HashMap<String, YarnValue>> =
+            HashMap::new();
+        for (name, value) in values {
+            values_by_route
+                .entry(self.route_index(&name))
+                .or_default()
+                .insert(name, value);
+        }
+        for (route_index, values) in values_by_route {
+            match route_index {
+                Some(index) => self.routes[index].1.extend(values)?,
+                None => self.default_storage.extend(values)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn variables(&self) -> HashMap<String, YarnValue> {
+        // Only report the variables that are routed to the storage they are in,
+        // in case a storage was shared or filled before its route was added
+        let mut variables: HashMap<_, _> = self
+            .default_storage
+            .variables()
+            .into_iter()
+            .filter(|(name, _)| self.route_index(name).is_none())
+            .collect();
+        for (index, (_, storage)) in self.routes.iter().enumerate() {
+            variables.extend(
+                storage
+                    .variables()
+                    .into_iter()
+                    .filter(|(name, _)| self.route_index(name) == Some(index)),
+            );
+        }
+        variables
+    }
+
+    fn clear(&mut self) {
+        for storage in self.storages_mut() {
+            storage.clear();
+        }
+    }
+
+    fn add_observer(&mut self, observer: VariableObserver) -> Result<()> {
+        // Observers cannot be removed again, so the observer stays silent until every storage accepted it.
+        // Otherwise a storage that doesn't support observers would leave only some variables observed.
+        let observer = Arc::new(observer);
+        let is_registered = Arc::new(AtomicBool::new(false));
+        for storage in self.storages_mut() {
+            let observer = observer.clone();
+            let is_registered = is_registered.clone();
+            storage.add_observer(Box::new(move |name, previous_value, value| {
+                if is_registered.load(Ordering::Acquire) {
+                    observer(name, previous_value, value)
+                }
+            }))?;
+        }
+        is_registered.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    fn begin_transaction(&mut self) -> Result<()> {
+        let mut begun = 0;
+        let mut result = Ok(());
+        for storage in self.storages_mut() {
+            result = storage.begin_transaction();
+            if result.is_err() {
+                break;
+            }
+            begun += 1;
+        }
+        if result.is_err() {
+            // Leave the storages as they were
+            for storage in self.storages_mut().take(begun) {
+                storage.rollback_transaction()?;
+            }
+        }
+        result
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        for storage in self.storages_mut() {
+            storage.commit_transaction()?;
+        }
+        Ok(())
+    }
+
+    fn rollback_transaction(&mut self) -> Result<()> {
+        for storage in self.storages_mut() {
+            storage.rollback_transaction()?;
+        }
+        Ok(())
+    }
+
+    fn is_in_transaction(&self) -> bool {
+        self.default_storage.is_in_transaction()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_variables_by_the_longest_matching_prefix() {
+        let default_storage = MemoryVariableStorage::new();
+        let temp = MemoryVariableStorage::new();
+        let temp_quest = MemoryVariableStorage::new();
+        let mut storage = CompositeVariableStorage::new(default_storage.clone_shallow());
+        storage
+            .add_route("$temp.", temp.clone_shallow())
+            .add_route("$temp.quest.", temp_quest.clone_shallow());
+
+        storage
+            .extend(HashMap::from([
+                ("$gold".to_owned(), 10.into()),
+                ("$temp.mood".to_owned(), "grumpy".into()),
+                ("$temp.quest.stage".to_owned(), 2.into()),
+            ]))
+            .unwrap();
+        assert_eq!(default_storage.variables().len(), 1);
+        assert!(temp.contains("$temp.mood"));
+        assert!(temp_quest.contains("$temp.quest.stage"));
+        assert_eq!(storage.variables().len(), 3);
+        assert!(storage.set("gold".to_owned(), 0.into()).is_err());
+
+        storage.clear();
+        assert!(storage.variables().is_empty());
+        assert!(default_storage.variables().is_empty());
+    }
+
+    #[test]
+    fn observers_are_only_added_if_all_storages_support_them() {
+        let default_storage = MemoryVariableStorage::new();
+        let mut storage = CompositeVariableStorage::new(default_storage.clone_shallow());
+        // Does not support observers
+        storage.add_route(
+            "$preview.",
+            Box::new(CopyOnWriteVariableStorage::new(Box::new(
+                MemoryVariableStorage::new(),
+            ))),
+        );
+        let is_observed = Arc::new(AtomicBool::new(false));
+        let observed = is_observed.clone();
+        let result = storage.add_observer(Box::new(move |_name, _previous_value, _value| {
+            observed.store(true, Ordering::SeqCst);
+        }));
+        assert!(matches!(
+            result,
+            Err(VariableStorageError::Unsupported { .. })
+        ));
+
+        storage.set("$gold".to_owned(), 10.into()).unwrap();
+        assert!(default_storage.contains("$gold"));
+        assert!(!is_observed.load(Ordering::SeqCst));
+    }
+}
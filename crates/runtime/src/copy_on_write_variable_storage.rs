@@ -0,0 +1,197 @@
+//! Lets a [`Dialogue::fork`](crate::prelude::Dialogue::fork) change variables
+//! without touching the variable storage of the dialogue it was forked from.
+
+use crate::prelude::*;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+use yarnspinner_core::prelude::*;
+
+/// A [`VariableStorage`] that reads from another variable storage, but keeps all changes to itself.
+/// Setting a variable never writes to the underlying storage, and clearing this storage hides the underlying variables instead of removing them.
+///
+/// Used by [`Dialogue::fork`] to let previews and tools run a dialogue without affecting the game state.
+/// Variables that were not set through this storage are read from the underlying storage when they are accessed,
+/// so they reflect changes made to it after this storage was created.
+///
+/// Shallow clones share the same changes, just like shallow clones of a [`MemoryVariableStorage`] share their variables.
+#[derive(Debug, Clone)]
+pub struct CopyOnWriteVariableStorage {
+    base: Box<dyn VariableStorage>,
+    changes: Arc<RwLock<Changes>>,
+}
+
+#[derive(Debug, Default)]
+struct Changes {
+    values: HashMap<String, YarnValue>,
+    /// Set by [`VariableStorage::clear`], after which the underlying storage is no longer read.
+    is_cleared: bool,
+}
+
+impl CopyOnWriteVariableStorage {
+    /// Creates a storage that reads from `base`, usually a shallow clone of another storage, until a variable is set.
+    pub fn new(base: Box<dyn VariableStorage>) -> Self {
+        Self {
+            base,
+            changes: Default::default(),
+        }
+    }
+
+    /// The underlying storage the unchanged variables are read from.
+    pub fn base(&self) -> &dyn VariableStorage {
+        self.base.as_ref()
+    }
+
+    /// Returns the variables that were set through this storage and thus differ from the underlying storage.
+    pub fn changes(&self) -> HashMap<String, YarnValue> {
+        self.changes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values
+            .clone()
+    }
+
+    /// Returns `true` if any variable was set or the storage was cleared, i.e. if it no longer reads exactly like the underlying storage.
+    pub fn has_changes(&self) -> bool {
+        let changes = self.changes.read().unwrap_or_else(PoisonError::into_inner);
+        changes.is_cleared || !changes.values.is_empty()
+    }
+
+    /// Forgets all changes, so that every variable is read from the underlying storage again,
+    /// e.g. to restart a preview from the current game state without creating a new storage.
+    pub fn discard_changes(&mut self) {
+        *self.changes.write().unwrap_or_else(PoisonError::into_inner) = Changes::default();
+    }
+
+    /// Writes the changes to `target`, e.g. a shallow clone of the underlying storage once the player commits to a previewed branch.
+    /// If this storage was cleared, `target` is cleared first. The changes are kept, see [`CopyOnWriteVariableStorage::discard_changes`].
+    pub fn apply_changes(&self, target: &mut dyn VariableStorage) -> Result<()> {
+        let changes = self.changes.read().unwrap_or_else(PoisonError::into_inner);
+        if changes.is_cleared {
+            target.clear();
+        }
+        target.extend(changes.values.clone())
+    }
+
+    fn validate_name(name: &str) -> Result<()> {
+        if name.starts_with('$') {
+            Ok(())
+        } else {
+            Err(VariableStorageError::InvalidVariableName {
+                name: name.to_string(),
+            })
+        }
+    }
+}
+
+impl VariableStorage for CopyOnWriteVariableStorage {
+    fn clone_shallow(&self) -> Box<dyn VariableStorage> {
+        Box::new(self.clone())
+    }
+
+    fn set(&mut self, name: String, value: YarnValue) -> Result<()> {
+        Self::validate_name(&name)?;
+        self.changes
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values
+            .insert(name, value);
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Result<YarnValue> {
+        Self::validate_name(name)?;
+        let changes = self.changes.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(value) = changes.values.get(name) {
+            return Ok(value.clone());
+        }
+        if changes.is_cleared {
+            return Err(VariableStorageError::VariableNotFound {
+                name: name.to_string(),
+            });
+        }
+        self.base.get(name)
+    }
+
+    fn extend(&mut self, values: HashMap<String, YarnValue>) -> Result<()> {
+        for name in values.keys() {
+            Self::validate_name(name)?;
+        }
+        self.changes
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values
+            .extend(values);
+        Ok(())
+    }
+
+    fn variables(&self) -> HashMap<String, YarnValue> {
+        let changes = self.changes.read().unwrap_or_else(PoisonError::into_inner);
+        let mut variables = if changes.is_cleared {
+            HashMap::new()
+        } else {
+            self.base.variables()
+        };
+        variables.extend(changes.values.clone());
+        variables
+    }
+
+    fn clear(&mut self) {
+        let mut changes = self.changes.write().unwrap_or_else(PoisonError::into_inner);
+        changes.values.clear();
+        changes.is_cleared = true;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_changes_away_from_the_base_storage() {
+        let mut base = MemoryVariableStorage::new();
+        base.set("$gold".to_owned(), 10.into()).unwrap();
+        base.set("$name".to_owned(), "Alex".into()).unwrap();
+        let mut storage = CopyOnWriteVariableStorage::new(base.clone_shallow());
+
+        storage.set("$gold".to_owned(), 0.into()).unwrap();
+        assert_eq!(storage.get("$gold").unwrap(), YarnValue::from(0));
+        assert_eq!(storage.get("$name").unwrap(), YarnValue::from("Alex"));
+        assert_eq!(base.get("$gold").unwrap(), YarnValue::from(10));
+        assert_eq!(
+            storage.changes(),
+            HashMap::from([("$gold".to_owned(), YarnValue::from(0))])
+        );
+
+        storage.clear();
+        assert!(!storage.contains("$name"));
+        assert!(storage.variables().is_empty());
+        assert_eq!(base.variables().len(), 2);
+    }
+
+    #[test]
+    fn changes_can_be_discarded_or_applied() {
+        let mut base = MemoryVariableStorage::new();
+        base.set("$gold".to_owned(), 10.into()).unwrap();
+        let mut storage = CopyOnWriteVariableStorage::new(base.clone_shallow());
+        assert!(!storage.has_changes());
+
+        storage.set("$gold".to_owned(), 0.into()).unwrap();
+        assert!(storage.has_changes());
+        storage.discard_changes();
+        assert!(!storage.has_changes());
+        assert_eq!(storage.get("$gold").unwrap(), YarnValue::from(10));
+
+        storage.set("$gold".to_owned(), 5.into()).unwrap();
+        storage.apply_changes(&mut base).unwrap();
+        assert_eq!(base.get("$gold").unwrap(), YarnValue::from(5));
+    }
+}
@@ -1,20 +1,41 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner/Dialogue.cs>
 
-use crate::markup::{DialogueTextProcessor, LineParser, MarkupParseError};
+use crate::markup::{
+    AttributeMarkerProcessor, ButtonGlyphProcessor, ButtonGlyphs, DialogueTextProcessor,
+    LineParser, MarkupParseError, MarkupStyles, MarkupWhitespaceTrimming, SharedButtonGlyphs,
+    BUTTON_ATTRIBUTE,
+};
 use crate::prelude::*;
 use log::error;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::sync::{Arc, Mutex, PoisonError};
 use thiserror::Error;
 use yarnspinner_core::prelude::*;
 
 /// Co-ordinates the execution of Yarn programs.
 ///
 /// The main functions of interest are [`Dialogue::continue_`] and [`Dialogue::set_selected_option`].
+///
+/// ## Sharing between dialogues
+///
+/// Games with many characters often run one dialogue per character over the same script. The state of a dialogue is cheap,
+/// but the following parts are worth sharing between them:
+/// - The [`Program`] is stored behind an [`Arc`]. Load it once with `Arc::new(program)` and pass clones of the [`Arc`] to [`Dialogue::replace_program`].
+///   Methods that change the program, such as [`Dialogue::replace_nodes`], copy it first, so the other dialogues are not affected.
+/// - A [`VariableStorage`] can be shared with [`VariableStorage::clone_shallow`]. All dialogues then read and write the same variables,
+///   including the ones counting node visits, so `visited("Node")` is true for all of them once any of them visited the node.
+///   Use separate storages if each character should keep its own state.
+/// - A [`Library`] is cloned into every dialogue via [`Library::extend`] on [`Dialogue::library_mut`]. Each function is cloned with it,
+///   so functions that need to share state between dialogues must keep it behind an [`Arc`] themselves.
+///   Every dialogue also has its own `visited`, `visited_count` and random functions, which are bound to its storage and random number generator.
 #[derive(Debug)]
 pub struct Dialogue {
     vm: VirtualMachine,
     language_code: Option<Language>,
+    rng: SharedRng,
+    button_glyphs: SharedButtonGlyphs,
+    saliency_strategy: Mutex<Box<dyn ContentSaliencyStrategy>>,
 }
 
 #[allow(missing_docs)]
@@ -53,6 +74,63 @@ pub enum DialogueError {
         function_name: String,
         library: Library,
     },
+    #[error("Function \"{function_name}\" failed in node \"{node_name}\" at instruction {instruction_index}: {source}")]
+    FunctionFailed {
+        function_name: String,
+        node_name: String,
+        /// Can be mapped to a line of the Yarn source code with the compiler's `DebugInfo::get_line_info`.
+        instruction_index: usize,
+        source: YarnFnError,
+    },
+    #[error("The snapshot does not match the node \"{node_name}\" of the loaded program. Was the program changed since the snapshot was taken?")]
+    IncompatibleSnapshot { node_name: String },
+    #[error("Node \"{node_name}\" contains an instruction with opcode {opcode}, but no instruction handler is registered for it.")]
+    UnknownInstruction { opcode: i32, node_name: String },
+    #[error(
+        "The instruction handler for opcode {opcode} failed in node \"{node_name}\": {source}"
+    )]
+    InstructionHandlerError {
+        opcode: i32,
+        node_name: String,
+        source: InstructionHandlerError,
+    },
+    #[error("Cannot register an instruction handler for opcode {opcode}. Only opcodes from {} upwards are reserved for custom instructions.", Instruction::FIRST_EXTENSION_OPCODE)]
+    ReservedOpCode { opcode: i32 },
+    #[error("Cannot evaluate the condition \"{condition}\" of node \"{node_name}\"")]
+    InvalidNodeCondition {
+        node_name: String,
+        condition: String,
+    },
+    #[error("The loaded program has the content hash {actual}, but {expected} was expected. Is an outdated program being loaded?")]
+    ProgramHashMismatch {
+        expected: ContentHash,
+        actual: ContentHash,
+    },
+    #[error("The text provider has no text for {} of the {line_count} lines of the loaded program with content hash {program_hash}, e.g. \"{}\". \
+            Is the strings table outdated or was it built for another version of the program?", .missing_line_ids.len(), .missing_line_ids[0])]
+    StringTableMismatch {
+        program_hash: ContentHash,
+        line_count: usize,
+        missing_line_ids: Vec<LineId>,
+    },
+    #[error("Ran {limit} instructions without reaching a line, options, a command or the end of the dialogue, stopped in node \"{node_name}\". Does the node jump back to itself?")]
+    InstructionLimitExceeded { limit: usize, node_name: String },
+    #[error("Invalid instruction {instruction_index} in node \"{node_name}\"{}: {reason}", .location.as_ref().map(|location| format!(" at {location}")).unwrap_or_default())]
+    InvalidInstruction {
+        node_name: String,
+        /// Can be mapped to a line of the Yarn source code with the compiler's `DebugInfo::get_line_info`.
+        instruction_index: usize,
+        /// Only available if the node's positions were added with [`Dialogue::add_source_positions`].
+        location: Option<SourceLocation>,
+        reason: String,
+    },
+    #[error("Cannot fork the dialogue while it is waiting for the function \"{function_name}\" to finish.")]
+    ForkWhileFunctionPending { function_name: String },
+    #[error("Cannot rewind the dialogue by {steps} step(s), as only {available_steps} step(s) are in its history.")]
+    RewindOutOfRange {
+        steps: usize,
+        available_steps: usize,
+    },
 }
 
 impl Dialogue {
@@ -67,42 +145,64 @@ impl Dialogue {
         text_provider: Box<dyn TextProvider>,
     ) -> Self {
         let mut library = Library::standard_library();
-        library
-            .add_function("visited", visited(variable_storage.clone()))
-            .add_function("visited_count", visited_count(variable_storage.clone()));
+        let rng = SharedRng::from_entropy();
+        add_dialogue_functions(&mut library, variable_storage.as_ref(), &rng);
 
         let dialogue_text_processor = Box::new(DialogueTextProcessor::new());
+        let button_glyphs = SharedButtonGlyphs::default();
         let line_parser = LineParser::new()
             .register_marker_processor("select", dialogue_text_processor.clone())
             .register_marker_processor("plural", dialogue_text_processor.clone())
-            .register_marker_processor("ordinal", dialogue_text_processor);
+            .register_marker_processor("ordinal", dialogue_text_processor)
+            .register_marker_processor(
+                BUTTON_ATTRIBUTE,
+                Box::new(ButtonGlyphProcessor::new(button_glyphs.clone())),
+            );
 
         Self {
             vm: VirtualMachine::new(library, variable_storage, line_parser, text_provider),
             language_code: Default::default(),
+            rng,
+            button_glyphs,
+            saliency_strategy: Mutex::new(Box::new(BestSaliencyStrategy)),
         }
     }
 }
 
+/// Registers the functions that are bound to a [`Dialogue`]'s variable storage and random number generator.
+fn add_dialogue_functions(
+    library: &mut Library,
+    variable_storage: &dyn VariableStorage,
+    rng: &SharedRng,
+) {
+    library
+        .add_function("visited", visited(variable_storage.clone_shallow()))
+        .add_function(
+            "visited_count",
+            visited_count(variable_storage.clone_shallow()),
+        );
+    rng.add_functions_to(library);
+}
+
+/// Takes the program out of the [`Arc`] if this is the only reference to it, or clones it otherwise.
+fn unwrap_or_clone(program: Arc<Program>) -> Program {
+    Arc::try_unwrap(program).unwrap_or_else(|program| program.as_ref().clone())
+}
+
 fn visited(storage: Box<dyn VariableStorage>) -> yarn_fn_type! { impl Fn(String) -> bool } {
-    move |node: String| -> bool {
-        let name = Library::generate_unique_visited_variable_for_node(&node);
-        if let Ok(YarnValue::Number(count)) = storage.get(&name) {
-            count > 0.0
-        } else {
-            false
-        }
-    }
+    move |node: String| -> bool { visit_count(storage.as_ref(), &node) > 0.0 }
 }
 
 fn visited_count(storage: Box<dyn VariableStorage>) -> yarn_fn_type! { impl Fn(String) -> f32 } {
-    move |node: String| {
-        let name = Library::generate_unique_visited_variable_for_node(&node);
-        if let Ok(YarnValue::Number(count)) = storage.get(&name) {
-            count
-        } else {
-            0.0
-        }
+    move |node: String| visit_count(storage.as_ref(), &node)
+}
+
+/// Reads the number of times the given node was visited from the variable generated by [`Library::generate_unique_visited_variable_for_node`].
+pub(crate) fn visit_count(storage: &dyn VariableStorage, node_name: &str) -> f32 {
+    let name = Library::generate_unique_visited_variable_for_node(node_name);
+    match storage.get(&name) {
+        Ok(YarnValue::Number(count)) => count,
+        _ => 0.0,
     }
 }
 
@@ -136,6 +236,9 @@ impl Dialogue {
 
     /// Sets the [`Dialogue`]'s language. A value of `None` means that you are using the base language, i.e. the one the Yarn files are written in.
     /// Returns the last language code.
+    ///
+    /// The new language applies to the next line or options that are delivered.
+    /// To also show the current line or options in the new language, use [`Dialogue::switch_language`] instead.
     pub fn set_language_code(
         &mut self,
         language_code: impl Into<Option<Language>>,
@@ -145,6 +248,38 @@ impl Dialogue {
         std::mem::replace(&mut self.language_code, language_code)
     }
 
+    /// Sets the [`Dialogue`]'s language like [`Dialogue::set_language_code`] and returns the line or options currently shown, resolved in the new language,
+    /// so that the game can replace them without restarting the conversation, e.g. when the player changes the language in the settings menu.
+    ///
+    /// Returns a [`DialogueEvent::Line`] if the dialogue is waiting to continue after a line, a [`DialogueEvent::Options`] if it is waiting for an option to be selected,
+    /// and no events otherwise. The dialogue does not advance. A line that was split into pages by [`Dialogue::set_line_pagination`] starts over at its first page.
+    ///
+    /// ## Errors
+    ///
+    /// Fails if the [`TextProvider`] has no text for the current line or options in the new language, e.g. because it was not loaded yet.
+    /// The language is changed regardless.
+    pub fn switch_language(
+        &mut self,
+        language_code: impl Into<Option<Language>>,
+    ) -> Result<Vec<DialogueEvent>> {
+        self.set_language_code(language_code);
+        self.vm.redeliver_displayed_content()
+    }
+
+    /// The language the Yarn files are written in, usually taken from the `base_language` of the compiler's `Compilation`.
+    /// While [`Dialogue::language_code`] is [`None`], markers such as `plural` and `ordinal` use the rules of this language.
+    /// The default is [`None`].
+    #[must_use]
+    pub fn base_language(&self) -> Option<&Language> {
+        self.vm.base_language()
+    }
+
+    /// Sets the language the Yarn files are written in. See [`Dialogue::base_language`].
+    pub fn set_base_language(&mut self, base_language: impl Into<Option<Language>>) -> &mut Self {
+        self.vm.set_base_language(base_language);
+        self
+    }
+
     /// Gets the [`Library`] that this Dialogue uses to locate functions.
     ///
     /// When the Dialogue is constructed, the Library is initialized with
@@ -174,6 +309,389 @@ impl Dialogue {
         self
     }
 
+    /// Gets how many `<<jump>>`s deep the [`DialogueEvent::LineHints`] look ahead, see [`Dialogue::set_line_hint_lookahead`].
+    #[must_use]
+    pub fn line_hint_lookahead(&self) -> usize {
+        self.vm.line_hint_lookahead
+    }
+
+    /// Sets how many `<<jump>>`s deep the [`DialogueEvent::LineHints`] sent by [`Dialogue::set_node`] look ahead.
+    /// With a lookahead of `0`, only the lines and options of the node about to run are included.
+    /// With a lookahead of `n`, the lines and options of every node that can be reached with at most `n` jumps are included as well,
+    /// including jumps inside options, so that voice-over or texture streaming systems can start loading before the nodes are entered.
+    /// Lines of closer nodes come first. Jumps to a node name computed from an expression cannot be followed.
+    ///
+    /// The default is `0`. See also [`Dialogue::get_line_ids_for_reachable_nodes`] for following all jumps.
+    pub fn set_line_hint_lookahead(&mut self, depth: usize) -> &mut Self {
+        self.vm.line_hint_lookahead = depth;
+        self
+    }
+
+    /// Gets whether [`Dialogue::continue_`] emits a [`DialogueEvent::VariableChanged`] for every `<<set>>`.
+    /// The default is `false`.
+    #[must_use]
+    pub fn variable_change_events_enabled(&self) -> bool {
+        self.vm.variable_change_events_enabled
+    }
+
+    /// Sets whether [`Dialogue::continue_`] emits a [`DialogueEvent::VariableChanged`] for every `<<set>>`.
+    /// The default is `false`.
+    pub fn set_variable_change_events_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.vm.variable_change_events_enabled = enabled;
+        self
+    }
+
+    /// Gets whether errors panic instead of being returned, see [`Dialogue::set_panic_on_error`].
+    #[must_use]
+    pub fn panic_on_error(&self) -> bool {
+        self.vm.panic_on_error
+    }
+
+    /// Sets whether [`Dialogue::continue_`], [`Dialogue::set_node`], [`Dialogue::set_selected_option`] and [`Dialogue::step_instruction`]
+    /// panic with the error message instead of returning a [`DialogueError`].
+    /// This restores the behavior of earlier versions, which panicked when running an invalid program, e.g. one with a missing operand.
+    /// The default is `false`.
+    pub fn set_panic_on_error(&mut self, panic_on_error: bool) -> &mut Self {
+        self.vm.panic_on_error = panic_on_error;
+        self
+    }
+
+    /// Adds the source positions of a node's instructions, so that a [`DialogueError::InvalidInstruction`] in it can name the offending line.
+    /// The arguments correspond to the fields of the compiler's `DebugInfo`, which is available for every node in `Compilation::debug_info`,
+    /// so a compiled project's positions are added with `dialogue.add_source_positions(&info.node_name, &info.file_name, info.line_positions.clone())`
+    /// for each of its `DebugInfo`s.
+    /// Positions previously added for the same node are replaced.
+    pub fn add_source_positions(
+        &mut self,
+        node_name: impl Into<String>,
+        file_name: impl Into<String>,
+        positions: HashMap<usize, Option<Position>>,
+    ) -> &mut Self {
+        self.vm
+            .source_map
+            .insert(node_name.into(), file_name.into(), positions);
+        self
+    }
+
+    /// Adds the metadata of lines, i.e. the hashtags after them other than `#line:`, so that they are delivered in [`Line::metadata`].
+    /// The compiler's `StringInfo::metadata` has exactly this format, so a compiled project's metadata is added with
+    /// `dialogue.add_line_metadata(compilation.string_table.iter().map(|(id, info)| (id.clone(), info.metadata.clone())))`.
+    /// Metadata previously added for the same line is replaced.
+    pub fn add_line_metadata(
+        &mut self,
+        metadata: impl IntoIterator<Item = (LineId, Vec<String>)>,
+    ) -> &mut Self {
+        self.vm.line_metadata.extend(metadata);
+        self
+    }
+
+    /// Adds the declarations of the variables used by the loaded programs, so that they can be looked up with [`Dialogue::variable_declaration`].
+    /// The compiler's `Declaration`s convert into [`VariableDeclaration`]s, so a compiled project's declarations are added with
+    /// `dialogue.add_variable_declarations(compilation.declarations.iter().map(Into::into))`.
+    /// Declarations of functions, whose names don't start with a `$`, are ignored.
+    /// Declarations previously added for the same variable are replaced.
+    pub fn add_variable_declarations(
+        &mut self,
+        declarations: impl IntoIterator<Item = VariableDeclaration>,
+    ) -> &mut Self {
+        self.vm.variable_declarations.extend(
+            declarations
+                .into_iter()
+                .filter(|declaration| declaration.name.starts_with('$'))
+                .map(|declaration| (declaration.name.clone(), declaration)),
+        );
+        self
+    }
+
+    /// Gets the declaration of a variable added with [`Dialogue::add_variable_declarations`], e.g. to show its description in a debug UI.
+    #[must_use]
+    pub fn variable_declaration(&self, name: &str) -> Option<&VariableDeclaration> {
+        self.vm.variable_declarations.get(name)
+    }
+
+    /// Gets all declarations added with [`Dialogue::add_variable_declarations`], in no particular order.
+    /// Save systems can use this to tell the variables declared in Yarn apart from the ones Yarn Spinner uses internally,
+    /// see [`VariableDeclaration::is_internal`].
+    pub fn variable_declarations(&self) -> impl Iterator<Item = &VariableDeclaration> {
+        self.vm.variable_declarations.values()
+    }
+
+    /// Gets the maximum number of instructions a single call to [`Dialogue::continue_`] may run, see [`Dialogue::set_instruction_limit`].
+    #[must_use]
+    pub fn instruction_limit(&self) -> Option<usize> {
+        self.vm.instruction_limit
+    }
+
+    /// Limits the number of instructions a single call to [`Dialogue::continue_`] may run before it returns.
+    /// When the limit is reached, [`Dialogue::continue_`] returns [`DialogueError::InstructionLimitExceeded`] naming the node it stopped in,
+    /// instead of hanging forever on a scripting mistake like a node that does nothing but `<<jump>>` to itself.
+    /// Calling [`Dialogue::continue_`] again resumes where the dialogue stopped, with a fresh limit.
+    ///
+    /// Choose a limit well above what your longest stretch of logic between two lines needs. The default is [`None`], i.e. no limit.
+    pub fn set_instruction_limit(&mut self, limit: impl Into<Option<usize>>) -> &mut Self {
+        self.vm.instruction_limit = limit.into();
+        self
+    }
+
+    /// Splits lines that don't fit into the game's text box into multiple pages, which are delivered as successive [`DialogueEvent::Line`]s.
+    /// Each page waits for a call to [`Dialogue::continue_`] like a regular line would, so views never have to clip or scroll a line.
+    ///
+    /// `fits` measures the text, e.g. by laying it out with the font and box size used by the view, and returns whether it can be displayed at once.
+    /// See [`Line::paginate`] for how lines are split. All pages share the [`Line::id`] of the original line,
+    /// while [`Dialogue::history`] records the line only once and unsplit.
+    pub fn set_line_pagination(
+        &mut self,
+        fits: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.vm.page_measurement = Some(PageMeasurement::new(fits));
+        self
+    }
+
+    /// Stops splitting lines into pages. See [`Dialogue::set_line_pagination`].
+    /// Pages of a line that was already split are still delivered.
+    pub fn disable_line_pagination(&mut self) -> &mut Self {
+        self.vm.page_measurement = None;
+        self
+    }
+
+    /// Adds a substitution provider, which supplies the text for every `{@name}` in a line, e.g. `{@player_name}` or `{@platform_button_confirm}`.
+    /// Replaces any provider previously added under the same name.
+    ///
+    /// Unlike variables, providers are called every time a line referring to them is delivered, after the inline expressions were substituted,
+    /// so they are a good fit for values that change per device or profile and should neither live in the [`VariableStorage`] nor be translated.
+    /// References to providers that were never added are delivered unchanged and logged as errors.
+    pub fn add_substitution_provider(
+        &mut self,
+        name: impl Into<String>,
+        provider: impl Fn() -> String + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.vm.substitution_providers.insert(name.into(), provider);
+        self
+    }
+
+    /// Removes the substitution provider with the given name, see [`Dialogue::add_substitution_provider`].
+    /// Returns `true` if there was such a provider.
+    pub fn remove_substitution_provider(&mut self, name: &str) -> bool {
+        self.vm.substitution_providers.remove(name)
+    }
+
+    /// Gets whether a substitution provider with the given name was added, see [`Dialogue::add_substitution_provider`].
+    #[must_use]
+    pub fn has_substitution_provider(&self, name: &str) -> bool {
+        self.vm.substitution_providers.contains(name)
+    }
+
+    /// Registers a processor that produces the replacement text for every marker with the given name, e.g. `[player_name/]` or `[icon=sword/]`.
+    /// Replaces any processor previously added under the same name, including the built-in ones for `select`, `plural`, `ordinal` and `button`.
+    ///
+    /// The processor is asked for the replacement text whenever a line or option containing the marker is delivered.
+    /// Markers without a processor are kept as attributes and their text is left unchanged.
+    pub fn add_markup_processor(
+        &mut self,
+        marker_name: impl Into<String>,
+        processor: impl AttributeMarkerProcessor + 'static,
+    ) -> &mut Self {
+        self.vm
+            .add_markup_processor(marker_name.into(), Box::new(processor));
+        self
+    }
+
+    /// Sets the glyphs used for `[button=action/]` markers, which show the input that triggers an action, e.g. `Press [button=confirm/] to talk.`
+    /// Depending on the [`ButtonGlyph`](crate::markup::ButtonGlyph), the marker is replaced by text or left as an attribute for the view to render as an icon.
+    /// Call this again whenever the player switches input devices; lines are resolved with the glyphs set at the time they are delivered.
+    ///
+    /// Without glyphs, `[button]` markers are removed from the text and left as plain attributes.
+    pub fn set_button_glyphs(&mut self, glyphs: impl Into<Option<ButtonGlyphs>>) -> &mut Self {
+        self.button_glyphs.set(glyphs);
+        self
+    }
+
+    /// Gets the glyphs used for `[button=action/]` markers, see [`Dialogue::set_button_glyphs`].
+    #[must_use]
+    pub fn button_glyphs(&self) -> Option<ButtonGlyphs> {
+        self.button_glyphs.get()
+    }
+
+    /// Gets whether lines with markup that cannot be parsed are delivered as plain text, see [`Dialogue::set_lenient_markup`].
+    /// The default is `false`.
+    #[must_use]
+    pub fn lenient_markup(&self) -> bool {
+        self.vm.lenient_markup
+    }
+
+    /// Sets whether lines with markup that cannot be parsed are delivered as plain text instead of failing, so that one bad tag doesn't blank a subtitle.
+    /// Such lines have their unparsed text as [`Line::text`], no attributes, and the error in [`Line::markup_error`].
+    /// The default is `false`, in which case [`Dialogue::continue_`] returns a [`DialogueError::MarkupParseError`].
+    pub fn set_lenient_markup(&mut self, lenient: bool) -> &mut Self {
+        self.vm.lenient_markup = lenient;
+        self
+    }
+
+    /// Sets how the whitespace around markers is trimmed when lines are parsed, see [`MarkupWhitespaceTrimming`].
+    /// The default is [`MarkupWhitespaceTrimming::YarnSpinner`], which matches the other Yarn Spinner implementations.
+    pub fn set_markup_whitespace_trimming(
+        &mut self,
+        whitespace_trimming: MarkupWhitespaceTrimming,
+    ) -> &mut Self {
+        self.vm.set_markup_whitespace_trimming(whitespace_trimming);
+        self
+    }
+
+    /// Gets how the whitespace around markers is trimmed, see [`Dialogue::set_markup_whitespace_trimming`].
+    #[must_use]
+    pub fn markup_whitespace_trimming(&self) -> MarkupWhitespaceTrimming {
+        self.vm.markup_whitespace_trimming()
+    }
+
+    /// Sets the styles that `[style=...]` markers expand to, e.g. `[style=shouty]Halt![/style]`.
+    /// See [`MarkupStyles`] for details.
+    pub fn set_markup_styles(&mut self, styles: MarkupStyles) -> &mut Self {
+        self.vm.set_markup_styles(styles);
+        self
+    }
+
+    /// Gets the styles that `[style=...]` markers expand to, see [`Dialogue::set_markup_styles`].
+    #[must_use]
+    pub fn markup_styles(&self) -> &MarkupStyles {
+        self.vm.markup_styles()
+    }
+
+    /// Gets whether lines are split into pages, see [`Dialogue::set_line_pagination`].
+    /// The default is `false`.
+    #[must_use]
+    pub fn line_pagination_enabled(&self) -> bool {
+        self.vm.page_measurement.is_some()
+    }
+
+    /// Returns `true` if the last delivered [`DialogueEvent::Line`] was split into pages and not all of them have been delivered yet.
+    /// See [`Dialogue::set_line_pagination`].
+    #[must_use]
+    pub fn has_pending_pages(&self) -> bool {
+        self.vm.has_pending_pages()
+    }
+
+    /// Seeds the random number generator used by the functions `random`, `random_range` and `dice`,
+    /// so that they return the same sequence of values every time, e.g. for tests, replays or to tie dialogue randomness to a world seed.
+    /// By default, the generator is seeded from the operating system's entropy.
+    pub fn set_rng_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng.set_seed(seed);
+        self
+    }
+
+    /// Replaces the random number generator used by the functions `random`, `random_range` and `dice` with the given one,
+    /// e.g. one shared with the rest of the game. See [`Dialogue::set_rng_seed`].
+    pub fn set_rng(&mut self, rng: impl rand::RngCore + Send + 'static) -> &mut Self {
+        self.rng.set(rng);
+        self
+    }
+
+    /// Gets whether delivered lines and options are recorded in [`Dialogue::history`].
+    /// The default is `false`.
+    #[must_use]
+    pub fn history_enabled(&self) -> bool {
+        self.vm.history_enabled
+    }
+
+    /// Sets whether delivered lines and options are recorded in [`Dialogue::history`].
+    /// The default is `false`. Disabling the history keeps the entries recorded so far.
+    pub fn set_history_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.vm.history_enabled = enabled;
+        self
+    }
+
+    /// Sets the value that functions receive through a [`DialogueContext`] parameter, e.g. the game state they should read.
+    /// Replaces the previous context, even if it has a different type.
+    pub fn set_context<T>(&mut self, context: T) -> &mut Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.vm.context = Some(Arc::new(context));
+        self
+    }
+
+    /// Returns the context set with [`Dialogue::set_context`], or [`None`] if there is none or it is not a `T`.
+    #[must_use]
+    pub fn context<T>(&self) -> Option<&T>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.vm.context.as_deref()?.downcast_ref()
+    }
+
+    /// Returns the context set with [`Dialogue::set_context`] for updating it, or [`None`] if there is none or it is not a `T`.
+    /// Also returns [`None`] while an async function that received the context is still running, or when the context is shared with a [`Dialogue::fork`].
+    pub fn context_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Send + Sync + 'static,
+    {
+        Arc::get_mut(self.vm.context.as_mut()?)?.downcast_mut()
+    }
+
+    /// Removes the context set with [`Dialogue::set_context`]. Functions with a [`DialogueContext`] parameter panic until a new one is set.
+    pub fn remove_context(&mut self) -> &mut Self {
+        self.vm.context = None;
+        self
+    }
+
+    /// Gets whether the [`Dialogue`] collects [`Dialogue::metrics`].
+    /// The default is `false`.
+    #[must_use]
+    pub fn metrics_enabled(&self) -> bool {
+        self.vm.metrics_enabled
+    }
+
+    /// Sets whether the [`Dialogue`] collects [`Dialogue::metrics`]. Measuring the time spent per node has a small cost for every instruction,
+    /// so this is meant for profiling builds. The default is `false`. Disabling the metrics keeps the numbers collected so far.
+    pub fn set_metrics_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.vm.metrics_enabled = enabled;
+        self
+    }
+
+    /// Returns the work done while [`Dialogue::metrics_enabled`] was `true`, since the [`Dialogue`] was created or [`Dialogue::reset_metrics`] was called.
+    #[must_use]
+    pub fn metrics(&self) -> &DialogueMetrics {
+        &self.vm.metrics
+    }
+
+    /// Starts collecting [`Dialogue::metrics`] from zero.
+    pub fn reset_metrics(&mut self) -> &mut Self {
+        self.vm.metrics = DialogueMetrics::default();
+        self
+    }
+
+    /// Gets how many lines, commands and option prompts the [`Dialogue`] remembers for [`Dialogue::rewind`].
+    /// The default is 0, which disables rewinding.
+    #[must_use]
+    pub fn rewind_capacity(&self) -> usize {
+        self.vm.rewind_history.capacity
+    }
+
+    /// Sets how many lines, commands and option prompts the [`Dialogue`] remembers for [`Dialogue::rewind`].
+    /// The default is 0, which disables rewinding. If the rewind history already holds more entries, the oldest ones are dropped.
+    ///
+    /// Every entry contains a copy of all variables in the [`VariableStorage`], so keep this in mind when choosing a large capacity.
+    pub fn set_rewind_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.vm.rewind_history.set_capacity(capacity);
+        self
+    }
+
+    /// Gets what happens when a conversion function such as `number` fails.
+    /// The default is [`ConversionFailureBehavior::Error`].
+    #[must_use]
+    pub fn conversion_failure_behavior(&self) -> ConversionFailureBehavior {
+        self.vm.conversion_failure_behavior
+    }
+
+    /// Sets what happens when a conversion function such as `number` fails.
+    /// The default is [`ConversionFailureBehavior::Error`].
+    pub fn set_conversion_failure_behavior(
+        &mut self,
+        behavior: ConversionFailureBehavior,
+    ) -> &mut Self {
+        self.vm.conversion_failure_behavior = behavior;
+        self
+    }
+
     /// Gets the currently registered [`TextProvider`].
     pub fn text_provider(&self) -> &dyn TextProvider {
         self.vm.text_provider()
@@ -209,6 +727,7 @@ impl Dialogue {
     /// See the documentation of [`DialogueEvent`] for more information on how to handle each event.
     ///
     /// The [`Iterator`] implementation of [`Dialogue`] is a convenient way to call [`Dialogue::next`] repeatedly, although it panics if an error occurs.
+    /// To handle one event at a time instead of whole batches, use [`Dialogue::events`].
     ///
     /// ## Implementation Notes
     ///
@@ -216,20 +735,45 @@ impl Dialogue {
     /// Specifically, we cannot guarantee [`Send`] and [`Sync`] properly without a lot of [`std::sync::RwLock`] boilerplate. The original implementation
     /// also allows unsound parallel mutation of [`Dialogue`]'s state, which would result in a deadlock in our case.
     pub fn continue_(&mut self) -> Result<Vec<DialogueEvent>> {
-        self.vm.continue_()
+        let result = self.vm.continue_();
+        self.panic_if_enabled(result)
+    }
+
+    /// Returns an [`Iterator`] that yields the [`DialogueEvent`]s one at a time, calling [`Dialogue::continue_`] whenever the last batch was used up.
+    /// See [`DialogueEvents`] for how to select options while iterating.
+    pub fn events(&mut self) -> DialogueEvents<'_> {
+        DialogueEvents::new(self)
+    }
+
+    /// Sets the waker that async functions are polled with, see [`DialogueStream`].
+    #[cfg(feature = "futures")]
+    pub(crate) fn set_waker(&mut self, waker: Option<std::task::Waker>) {
+        self.vm.waker = waker;
     }
 
     /// Sets or replaces the [`Dialogue`]'s current [`Program`]. The program is replaced, all current state is reset.
-    pub fn replace_program(&mut self, program: Program) -> &mut Self {
-        self.vm.program.replace(program);
+    ///
+    /// Pass an [`Arc<Program>`] to share one program between many dialogues without copying it, e.g. one per NPC.
+    /// See the [`Dialogue`] docs for details.
+    pub fn replace_program(&mut self, program: impl Into<Arc<Program>>) -> &mut Self {
+        self.vm.program.replace(program.into());
         self.vm.reset_state();
+        self.vm.rewind_history.clear();
         self
     }
 
     /// Merges the currently set [`Program`] with the given one. If there is no program set, the given one is set.
-    pub fn add_program(&mut self, program: Program) -> &mut Self {
+    ///
+    /// Merging creates a new program owned by this dialogue, so it is no longer shared with other dialogues.
+    pub fn add_program(&mut self, program: impl Into<Arc<Program>>) -> &mut Self {
+        let program = program.into();
         if let Some(existing_program) = self.vm.program.as_mut() {
-            *existing_program = Program::combine(vec![existing_program.clone(), program]).unwrap();
+            let combined = Program::combine(vec![
+                existing_program.as_ref().clone(),
+                unwrap_or_clone(program),
+            ])
+            .unwrap();
+            *existing_program = Arc::new(combined);
         } else {
             self.vm.program.replace(program);
             self.vm.reset_state();
@@ -238,6 +782,127 @@ impl Dialogue {
         self
     }
 
+    /// Adds the nodes of the given [`Program`] to the currently set one, replacing existing nodes with the same name, e.g. after editing them in a live editor.
+    /// The program's initial values are merged as well. If there is no program set, the given one is set.
+    ///
+    /// Unlike [`Dialogue::replace_program`], this keeps all current state. If the node that is currently running is replaced,
+    /// it keeps running its old version, and the new version is used the next time it is entered.
+    /// If the current program is shared with other dialogues, it is copied first, so the other dialogues are not affected.
+    pub fn replace_nodes(&mut self, program: impl Into<Arc<Program>>) -> &mut Self {
+        let Some(existing_program) = self.vm.program.as_mut() else {
+            return self.replace_program(program);
+        };
+        let program = unwrap_or_clone(program.into());
+        let existing_program = Arc::make_mut(existing_program);
+        existing_program.nodes.extend(program.nodes);
+        existing_program
+            .initial_values
+            .extend(program.initial_values);
+        self
+    }
+
+    /// Replaces the currently set [`Program`] with the given one like [`Dialogue::replace_program`], but keeps all current state,
+    /// e.g. to apply a hot reload without interrupting the player. If there is no program set, the given one is set.
+    ///
+    /// Returns the names of all nodes that were added, changed or removed, in no particular order.
+    /// If the node that is currently running is among them, it keeps running its old version, so consider restarting it.
+    pub fn patch_program(&mut self, program: impl Into<Arc<Program>>) -> Vec<String> {
+        let program = program.into();
+        let Some(existing_program) = self.vm.program.as_ref() else {
+            let changed_nodes = program.nodes.keys().cloned().collect();
+            self.replace_program(program);
+            return changed_nodes;
+        };
+        let removed_nodes = existing_program
+            .nodes
+            .keys()
+            .filter(|node_name| !program.nodes.contains_key(*node_name));
+        let changed_nodes = program
+            .nodes
+            .iter()
+            .filter(|(node_name, node)| existing_program.nodes.get(*node_name) != Some(node))
+            .map(|(node_name, _)| node_name);
+        let changed_nodes = removed_nodes.chain(changed_nodes).cloned().collect();
+        self.vm.program.replace(program);
+        changed_nodes
+    }
+
+    /// Removes the nodes with the given names from the currently set [`Program`], e.g. to unload content that is no longer needed.
+    /// Names of nodes that do not exist are ignored. The initial values of variables and all current state are kept.
+    ///
+    /// The node that is currently running is never removed, so that the dialogue can finish it.
+    /// If the current program is shared with other dialogues, it is copied first, so the other dialogues are not affected.
+    pub fn remove_nodes(
+        &mut self,
+        node_names: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> &mut Self {
+        let current_node = self.vm.current_node();
+        if let Some(program) = self.vm.program.as_mut() {
+            let program = Arc::make_mut(program);
+            for node_name in node_names {
+                let node_name = node_name.as_ref();
+                if current_node.as_deref() != Some(node_name) {
+                    program.nodes.remove(node_name);
+                }
+            }
+        }
+        self
+    }
+
+    /// Returns the currently set [`Program`], if any. Clone the [`Arc`] to share the program with another dialogue via [`Dialogue::replace_program`].
+    #[must_use]
+    pub fn program(&self) -> Option<&Arc<Program>> {
+        self.vm.program.as_ref()
+    }
+
+    /// Checks that the loaded [`Program`] is the expected one and that the [`TextProvider`] has a text for every line and option it can deliver.
+    /// Call this after loading a program and its strings, e.g. after a hot reload, to get a clear error instead of missing lines later on.
+    ///
+    /// If `expected_hash` is set, the [`Program::content_hash`] of the loaded program must match it.
+    /// Get the expected hash from the compiler's `Compilation::content_hash` when building the program.
+    /// The text provider is checked for its current language and must have its lines available.
+    ///
+    /// Returns the content hash of the loaded program on success.
+    ///
+    /// ## Errors
+    ///
+    /// - [`DialogueError::NoProgramLoaded`] if no program is loaded.
+    /// - [`DialogueError::ProgramHashMismatch`] if the hash of the program does not match `expected_hash`.
+    /// - [`DialogueError::StringTableMismatch`] if the text provider is missing lines of the program, which usually means that a stale strings table was paired with a newer program.
+    pub fn verify_program(
+        &self,
+        expected_hash: impl Into<Option<ContentHash>>,
+    ) -> Result<ContentHash> {
+        let program = self
+            .vm
+            .program
+            .as_ref()
+            .ok_or(DialogueError::NoProgramLoaded)?;
+        let program_hash = program.content_hash();
+        if let Some(expected) = expected_hash.into() {
+            if expected != program_hash {
+                return Err(DialogueError::ProgramHashMismatch {
+                    expected,
+                    actual: program_hash,
+                });
+            }
+        }
+        let line_ids = program.line_ids();
+        let missing_line_ids: Vec<_> = line_ids
+            .iter()
+            .filter(|id| self.vm.text_provider().get_text(id).is_none())
+            .cloned()
+            .collect();
+        if !missing_line_ids.is_empty() {
+            return Err(DialogueError::StringTableMismatch {
+                program_hash,
+                line_count: line_ids.len(),
+                missing_line_ids,
+            });
+        }
+        Ok(program_hash)
+    }
+
     /// Prepares the [`Dialogue`] that the user intends to start running a node.
     ///
     /// After this method is called, you call [`Dialogue::next`] to start executing it.
@@ -249,7 +914,9 @@ impl Dialogue {
     ///
     /// Returns an error if no node with the value of `node_name` has been loaded.
     pub fn set_node(&mut self, node_name: impl Into<String>) -> Result<&mut Self> {
-        self.vm.set_node(node_name)?;
+        let result = self.vm.set_node(node_name);
+        self.panic_if_enabled(result)?;
+        self.vm.rewind_history.clear();
         Ok(self)
     }
 
@@ -329,6 +996,56 @@ impl Dialogue {
         })
     }
 
+    /// Returns the headers for the node `node_name`, grouped by key.
+    /// Unlike [`Dialogue::get_headers_for_node`], headers that appear multiple times, such as `when`, keep all of their values in source order.
+    ///
+    /// Returns [`None`] if the node is not present in the program. Does not log an error in that case, so it can be used to probe for nodes.
+    #[must_use]
+    pub fn headers_for_node(&self, node_name: &str) -> Option<HashMap<&str, Vec<&str>>> {
+        self.node(node_name).map(Node::headers_by_key)
+    }
+
+    /// Returns the compiled node `node_name`, giving access to its tags, headers and instructions without cloning them,
+    /// e.g. to build a node picker or a debug view.
+    ///
+    /// Returns [`None`] if the node is not present in the program. Does not log an error in that case, so it can be used to probe for nodes.
+    #[must_use]
+    pub fn node(&self, node_name: &str) -> Option<&Node> {
+        self.vm.program.as_ref()?.nodes.get(node_name)
+    }
+
+    /// Iterates over the compiled nodes of the currently loaded program, in no particular order.
+    /// The iterator is empty if no program is loaded. See [`Dialogue::node`].
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> {
+        self.vm
+            .program
+            .iter()
+            .flat_map(|program| program.nodes.values())
+    }
+
+    /// Returns the IDs of the lines and options in the node `node_name`,
+    /// i.e. the same IDs that [`Dialogue::set_node`] announces in its [`DialogueEvent::LineHints`].
+    /// Useful for loading the assets of a node before it is started.
+    ///
+    /// Returns [`None`] if the node is not present in the program.
+    #[must_use]
+    pub fn get_line_ids_for_node(&self, node_name: &str) -> Option<Vec<LineId>> {
+        self.get_node_logging_errors(node_name)
+            .map(|node| line_hints_for_node(&node))
+    }
+
+    /// Returns the IDs of the lines and options in the node `node_name` and in all nodes reachable from it,
+    /// i.e. the nodes it `<<jump>>`s to, the nodes those jump to, and so on. Nodes closer to `node_name` come first.
+    /// Only jumps to a fixed node name can be followed, jumps to a node name computed from an expression are ignored.
+    ///
+    /// Returns [`None`] if the node is not present in the program.
+    #[must_use]
+    pub fn get_line_ids_for_reachable_nodes(&self, node_name: &str) -> Option<Vec<LineId>> {
+        self.get_node_logging_errors(node_name)?;
+        let program = self.vm.program.as_ref()?;
+        Some(line_hints_for_reachable_nodes(program, node_name, None))
+    }
+
     /// Gets a value indicating whether a specified node exists in the [`Program`].
     #[must_use]
     pub fn node_exists(&self, node_name: &str) -> bool {
@@ -349,6 +1066,145 @@ impl Dialogue {
         self.vm.current_node()
     }
 
+    /// Returns how many times the node with the given name was visited, which is what the Yarn function `visited_count` returns.
+    /// A visit is counted when the dialogue leaves the node.
+    /// Visits are only tracked for nodes that are passed to `visited` or `visited_count` somewhere in the scripts, or that have a `tracking: always` header.
+    /// Returns 0 for nodes that were never visited, that do not exist, or whose visits are not tracked.
+    ///
+    /// This reads the internal variables that track the visits from the [`VariableStorage`], so visits restored from a save game are included.
+    #[must_use]
+    pub fn visit_count(&self, node_name: &str) -> usize {
+        visit_count(self.variable_storage(), node_name) as usize
+    }
+
+    /// Returns `true` if the node with the given name was visited at least once, which is what the Yarn function `visited` returns.
+    /// See [`Dialogue::visit_count`].
+    #[must_use]
+    pub fn has_visited(&self, node_name: &str) -> bool {
+        self.visit_count(node_name) > 0
+    }
+
+    /// Chooses the node a conversation should start with, e.g. when the player talks to an NPC that has several conversations to offer.
+    ///
+    /// A candidate is eligible if all of its `when` headers are met. Each header holds a single condition, which is one of:
+    /// - `always`
+    /// - a boolean variable such as `$met_alice`, optionally preceded by `not`
+    /// - a comparison of two variables or literals such as `$gold >= 10` or `$name == "Bob"`, using the same operators as Yarn
+    ///
+    /// Among the eligible candidates, the [`ContentSaliencyStrategy`] set with [`Dialogue::set_content_saliency_strategy`] decides,
+    /// scoring each candidate by its number of conditions other than `always`. By default, this is the [`BestSaliencyStrategy`]:
+    /// the candidate with the most conditions wins, as it is the most specific, and ties go to the node that was visited less often, then to the one listed first.
+    /// Returns `None` if no candidate is eligible.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if no program is loaded, a candidate does not exist, or a `when` header cannot be evaluated.
+    ///
+    /// ## Example
+    ///
+    /// ```text
+    /// title: Shopkeeper_Rich
+    /// when: $gold >= 100
+    /// when: not $robbed_shop
+    /// ---
+    /// ```
+    pub fn choose_start_node<'a>(
+        &self,
+        candidates: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Option<String>> {
+        let program = self
+            .vm
+            .program
+            .as_ref()
+            .ok_or(DialogueError::NoProgramLoaded)?;
+        let mut scored_candidates = Vec::new();
+        'candidates: for node_name in candidates {
+            let node = program
+                .nodes
+                .get(node_name)
+                .ok_or_else(|| DialogueError::InvalidNode {
+                    node_name: node_name.to_owned(),
+                })?;
+            let mut condition_count = 0;
+            for condition in node_conditions(node) {
+                let is_met = evaluate_condition(condition, |name| self.vm.variable_value(name))
+                    .ok_or_else(|| DialogueError::InvalidNodeCondition {
+                        node_name: node_name.to_owned(),
+                        condition: condition.to_owned(),
+                    })?;
+                if !is_met {
+                    continue 'candidates;
+                }
+                if condition.trim() != "always" {
+                    condition_count += 1;
+                }
+            }
+            scored_candidates.push((node_name.to_owned(), condition_count as f32));
+        }
+        Ok(choose_best_candidate(
+            scored_candidates,
+            self.variable_storage(),
+            self.saliency_strategy
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .as_mut(),
+        ))
+    }
+
+    /// Sets the [`ContentSaliencyStrategy`] used by [`Dialogue::choose_start_node`] and [`Dialogue::choose_start_node_by`].
+    /// The default is the [`BestSaliencyStrategy`].
+    pub fn set_content_saliency_strategy(
+        &mut self,
+        strategy: impl ContentSaliencyStrategy + 'static,
+    ) -> &mut Self {
+        self.saliency_strategy = Mutex::new(Box::new(strategy));
+        self
+    }
+
+    /// Like [`Dialogue::choose_start_node`], but scores the candidates with `score` instead of evaluating their `when` headers.
+    /// `score` receives the name and headers of a candidate and returns `None` if it is not eligible, or its weight otherwise.
+    /// The [`ContentSaliencyStrategy`] chooses among the eligible candidates, using their weights as scores, the same way as in [`Dialogue::choose_start_node`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if no program is loaded or a candidate does not exist.
+    pub fn choose_start_node_by<'a>(
+        &self,
+        candidates: impl IntoIterator<Item = &'a str>,
+        mut score: impl FnMut(&str, &HashMap<String, String>) -> Option<f32>,
+    ) -> Result<Option<String>> {
+        let program = self
+            .vm
+            .program
+            .as_ref()
+            .ok_or(DialogueError::NoProgramLoaded)?;
+        let mut scored_candidates = Vec::new();
+        for node_name in candidates {
+            let node = program
+                .nodes
+                .get(node_name)
+                .ok_or_else(|| DialogueError::InvalidNode {
+                    node_name: node_name.to_owned(),
+                })?;
+            let headers = node
+                .headers
+                .iter()
+                .map(|header| (header.key.clone(), header.value.clone()))
+                .collect();
+            if let Some(weight) = score(node_name, &headers) {
+                scored_candidates.push((node_name.to_owned(), weight));
+            }
+        }
+        Ok(choose_best_candidate(
+            scored_candidates,
+            self.variable_storage(),
+            self.saliency_strategy
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .as_mut(),
+        ))
+    }
+
     /// Analyses the currently loaded Yarn program with the given [`Context`]. Call [`Context::finish_analysis`] afterwards to get the results.
     pub fn analyse(&self, context: &mut Context) -> &Self {
         let program = self
@@ -360,6 +1216,16 @@ impl Dialogue {
         self
     }
 
+    /// Panics with the error of `result` if [`Dialogue::panic_on_error`] is enabled. Returns `result` unchanged otherwise.
+    fn panic_if_enabled<T>(&self, result: Result<T>) -> Result<T> {
+        match result {
+            Err(error) if self.vm.panic_on_error => {
+                panic!("Encountered error while running dialogue: {error}")
+            }
+            result => result,
+        }
+    }
+
     fn get_node_logging_errors(&self, node_name: &str) -> Option<Node> {
         if let Some(program) = self.vm.program.as_ref() {
             if program.nodes.is_empty() {
@@ -391,10 +1257,241 @@ impl Dialogue {
     /// ## See Also
     /// - [`Dialogue::continue_`]
     pub fn set_selected_option(&mut self, selected_option_id: OptionId) -> Result<&mut Self> {
-        self.vm.set_selected_option(selected_option_id)?;
+        let result = self.vm.set_selected_option(selected_option_id);
+        self.panic_if_enabled(result)?;
         Ok(self)
     }
 
+    /// Creates an independent copy of this [`Dialogue`] that continues from the same point, e.g. to preview what happens if the player picks another option
+    /// without affecting the game. The fork delivers its lines with the given [`TextProvider`], which usually holds the same strings as this dialogue's.
+    ///
+    /// The fork runs on a [`CopyOnWriteVariableStorage`] on top of this dialogue's variable storage: it sees the current variables,
+    /// but the variables it sets are only visible to the fork. The loaded program is shared, so forking is cheap.
+    /// The fork takes over the functions, settings, language, markup processors, breakpoints and triggers of this dialogue.
+    /// `visited`, `visited_count` and the random functions are bound to the fork's variable storage and its own random number generator,
+    /// which is seeded from entropy and can be set with [`Dialogue::set_rng_seed`].
+    /// Instruction handlers, substitution providers, the line pagination, the line history and the content saliency strategy are not taken over,
+    /// as they cannot be copied or belong to the game.
+    ///
+    /// Functions that have side effects on the game, e.g. ones that read or change game objects, have them when called by the fork as well.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error while waiting for an async function, see [`DialogueEvent::FunctionPending`], as the pending call cannot be copied.
+    pub fn fork(&self, text_provider: Box<dyn TextProvider>) -> Result<Dialogue> {
+        let variable_storage =
+            CopyOnWriteVariableStorage::new(self.vm.variable_storage.clone_shallow());
+        let mut fork = Dialogue::new(Box::new(variable_storage), text_provider);
+        fork.vm.library.import(self.vm.library.clone());
+        add_dialogue_functions(
+            &mut fork.vm.library,
+            fork.vm.variable_storage.as_ref(),
+            &fork.rng,
+        );
+        self.vm.fork_into(&mut fork.vm)?;
+        fork.set_language_code(self.language_code.clone());
+        fork.set_button_glyphs(self.button_glyphs());
+        fork.set_markup_styles(self.markup_styles().clone());
+        fork.set_markup_whitespace_trimming(self.markup_whitespace_trimming());
+        Ok(fork)
+    }
+
+    /// Captures the execution state of the [`Dialogue`] in the middle of a node, so that it can be resumed later with [`Dialogue::restore_snapshot`],
+    /// e.g. after loading a save game.
+    ///
+    /// Returns [`None`] if the dialogue is not waiting for [`Dialogue::continue_`] or [`Dialogue::set_selected_option`] in a node,
+    /// i.e. if it is stopped or if it has not started running the node set by [`Dialogue::set_node`] yet.
+    /// Also returns [`None`] while waiting for an async function, see [`DialogueEvent::FunctionPending`].
+    #[must_use]
+    pub fn state_snapshot(&self) -> Option<DialogueSnapshot> {
+        self.vm.snapshot()
+    }
+
+    /// Resumes the execution state captured by [`Dialogue::state_snapshot`]. The [`Program`] that contains the snapshot's node must already be loaded.
+    ///
+    /// Returns the events needed to present the restored state again. If the dialogue was waiting for an option selection, this is
+    /// a [`DialogueEvent::Options`] with the pending options, and the game should call [`Dialogue::set_selected_option`] next.
+    /// Otherwise, the list is empty and the game should call [`Dialogue::continue_`] next.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the snapshot's node is not loaded or if its instructions changed since the snapshot was taken.
+    pub fn restore_snapshot(&mut self, snapshot: DialogueSnapshot) -> Result<Vec<DialogueEvent>> {
+        self.vm.restore_snapshot(snapshot)
+    }
+
+    /// Steps the [`Dialogue`] back by `steps` lines, commands or option prompts, e.g. for a "back" button in a visual novel.
+    /// A value of 0 returns to the start of the most recent one, e.g. to show the options again after one was selected.
+    /// Requires a [`Dialogue::rewind_capacity`] greater than 0.
+    ///
+    /// Returns the event to present again, i.e. a [`DialogueEvent::Line`], [`DialogueEvent::Command`] or [`DialogueEvent::Options`].
+    /// Afterwards, the game should call [`Dialogue::continue_`] or, for options, [`Dialogue::set_selected_option`] as usual.
+    ///
+    /// The [`VariableStorage`] is reverted to the values it had at that point as well: variables that changed since are set back to their remembered values.
+    /// If variables were set for the first time since then, the storage is cleared and refilled with the remembered variables instead,
+    /// which clears every route of a [`CompositeVariableStorage`] as well, including persistent ones.
+    /// Side effects of commands and functions outside of the variable storage cannot be reverted and are the game's responsibility.
+    /// The rewind history is cleared by [`Dialogue::set_node`], [`Dialogue::replace_program`] and [`Dialogue::restore_snapshot`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the rewind history does not reach back `steps` steps, or if the remembered point cannot be restored,
+    /// e.g. because its node was removed by [`Dialogue::patch_program`]. The rewind history is left unchanged in that case.
+    pub fn rewind(&mut self, steps: usize) -> Result<Vec<DialogueEvent>> {
+        self.vm.rewind(steps)
+    }
+
+    /// Returns how many steps [`Dialogue::rewind`] can currently go back.
+    #[must_use]
+    pub fn rewindable_steps(&self) -> usize {
+        self.vm.rewind_history.available_steps()
+    }
+
+    /// Returns every line and options prompt delivered while [`Dialogue::history_enabled`] was `true`, oldest first,
+    /// e.g. for showing a backlog of the conversation.
+    ///
+    /// Lines are recorded with their final text, i.e. after substitutions and markup parsing in the language that was active at the time.
+    /// Options prompts record which option was selected once [`Dialogue::set_selected_option`] is called.
+    /// The history spans all nodes and is never cleared automatically, not even by [`Dialogue::rewind`]. Use [`Dialogue::clear_history`] for that.
+    #[must_use]
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.vm.history
+    }
+
+    /// Removes all entries from [`Dialogue::history`].
+    pub fn clear_history(&mut self) -> &mut Self {
+        self.vm.history.clear();
+        self
+    }
+
+    /// Registers an [`InstructionHandler`] that executes all instructions with the given `opcode`, replacing any handler previously registered for it.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if `opcode` is lower than [`Instruction::FIRST_EXTENSION_OPCODE`], since those opcodes are reserved for Yarn Spinner itself.
+    pub fn register_instruction_handler(
+        &mut self,
+        opcode: i32,
+        handler: impl InstructionHandler + 'static,
+    ) -> Result<&mut Self> {
+        if opcode < Instruction::FIRST_EXTENSION_OPCODE {
+            return Err(DialogueError::ReservedOpCode { opcode });
+        }
+        self.vm
+            .instruction_handlers
+            .insert(opcode, Box::new(handler));
+        Ok(self)
+    }
+
+    /// Removes the [`InstructionHandler`] registered for `opcode` with [`Dialogue::register_instruction_handler`]. Returns `true` if there was one.
+    pub fn remove_instruction_handler(&mut self, opcode: i32) -> bool {
+        self.vm.instruction_handlers.remove(&opcode).is_some()
+    }
+
+    /// Pauses [`Dialogue::continue_`] right before the instruction at the given [`Breakpoint`] is run, emitting a [`DialogueEvent::BreakpointHit`].
+    /// The next call to [`Dialogue::continue_`] or [`Dialogue::step_instruction`] resumes from there.
+    ///
+    /// Use the compiler's `DebugInfo::first_instruction_on_line` to find the instruction index for a line of a Yarn file.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> &mut Self {
+        self.vm.breakpoints.insert(breakpoint);
+        self
+    }
+
+    /// Removes a breakpoint added by [`Dialogue::add_breakpoint`]. Returns `true` if it was present.
+    pub fn remove_breakpoint(&mut self, breakpoint: &Breakpoint) -> bool {
+        self.vm.breakpoints.remove(breakpoint)
+    }
+
+    /// Removes all breakpoints added by [`Dialogue::add_breakpoint`].
+    pub fn clear_breakpoints(&mut self) -> &mut Self {
+        self.vm.breakpoints.clear();
+        self
+    }
+
+    /// Returns all breakpoints added by [`Dialogue::add_breakpoint`], in no particular order.
+    pub fn breakpoints(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.vm.breakpoints.iter()
+    }
+
+    /// Registers a [`Trigger`] named `name` that emits a [`DialogueEvent::TriggerFired`] the first time `condition` is met,
+    /// e.g. to unlock an achievement when the player first hears a specific line. Replaces any trigger of the same name.
+    ///
+    /// Whether a trigger has fired is remembered in the [`VariableStorage`] under [`Trigger::variable_name`],
+    /// so a trigger does not fire again after the game is saved and loaded as long as the variables are saved as well.
+    /// Use [`Dialogue::reset_trigger`] to make it fire again.
+    pub fn add_trigger(
+        &mut self,
+        name: impl Into<String>,
+        condition: TriggerCondition,
+    ) -> &mut Self {
+        let trigger = Trigger::new(name, condition);
+        self.vm
+            .triggers
+            .retain(|existing| existing.name != trigger.name);
+        self.vm.triggers.push(trigger);
+        self
+    }
+
+    /// Removes the [`Trigger`] registered with [`Dialogue::add_trigger`] under `name`. Returns `true` if there was one.
+    pub fn remove_trigger(&mut self, name: &str) -> bool {
+        let trigger_count = self.vm.triggers.len();
+        self.vm.triggers.retain(|trigger| trigger.name != name);
+        self.vm.triggers.len() != trigger_count
+    }
+
+    /// Returns all triggers registered with [`Dialogue::add_trigger`], in the order they were added.
+    pub fn triggers(&self) -> impl Iterator<Item = &Trigger> {
+        self.vm.triggers.iter()
+    }
+
+    /// Returns `true` if the [`Trigger`] registered under `name` has already fired.
+    #[must_use]
+    pub fn has_trigger_fired(&self, name: &str) -> bool {
+        self.vm
+            .triggers
+            .iter()
+            .find(|trigger| trigger.name == name)
+            .is_some_and(|trigger| trigger.has_fired(self.variable_storage()))
+    }
+
+    /// Forgets that the [`Trigger`] registered under `name` has fired, so that it fires again the next time its condition is met.
+    pub fn reset_trigger(&mut self, name: &str) -> Result<&mut Self> {
+        self.variable_storage_mut()
+            .set(Trigger::variable_name(name), false.into())?;
+        Ok(self)
+    }
+
+    /// Runs only the next instruction of the current node, ignoring breakpoints. Returns the events that instruction produced, if any.
+    ///
+    /// Can be called whenever [`Dialogue::continue_`] can be, and the two can be mixed freely.
+    /// Use [`Dialogue::current_instruction`] and [`Dialogue::stack`] to inspect the state between steps.
+    ///
+    /// ## Errors
+    ///
+    /// Same as [`Dialogue::continue_`].
+    pub fn step_instruction(&mut self) -> Result<Vec<DialogueEvent>> {
+        let result = self.vm.step_instruction();
+        self.panic_if_enabled(result)
+    }
+
+    /// Returns the instruction that will be run next, or `None` if no node is running.
+    #[must_use]
+    pub fn current_instruction(&self) -> Option<&Instruction> {
+        self.vm.current_instruction()
+    }
+
+    /// Returns the index of the instruction that will be run next within [`Dialogue::current_node`], or `None` if no node is running.
+    #[must_use]
+    pub fn instruction_index(&self) -> Option<usize> {
+        self.vm.program_counter()
+    }
+
+    /// Returns a copy of the VM's value stack, from bottom to top.
+    #[must_use]
+    pub fn stack(&self) -> Vec<YarnValue> {
+        self.vm.stack()
+    }
+
     /// Gets a value indicating whether the Dialogue is currently executing Yarn instructions.
     #[must_use]
     pub fn is_active(&self) -> bool {
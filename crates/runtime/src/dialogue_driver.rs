@@ -0,0 +1,243 @@
+//! Splits a [`Dialogue`] into a [`DialogueDriver`] that owns it and any number of [`DialogueHandle`]s
+//! that talk to the driver over channels, so that the virtual machine can run on another thread than the game.
+
+use crate::prelude::*;
+use crate::Result;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use thiserror::Error;
+
+/// A request sent from a [`DialogueHandle`] to its [`DialogueDriver`]. Each variant corresponds to a method of [`Dialogue`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DialogueRequest {
+    /// See [`Dialogue::set_node`]. The [`DialogueEvent::NodeStart`] is sent after the next [`DialogueRequest::Continue`].
+    SetNode(String),
+    /// See [`Dialogue::continue_`].
+    Continue,
+    /// See [`Dialogue::set_selected_option`]. Like on a [`Dialogue`], send a [`DialogueRequest::Continue`] afterwards to run the selected option.
+    SelectOption(OptionId),
+    /// Sets a variable in the [`Dialogue::variable_storage_mut`].
+    SetVariable {
+        /// The name of the variable, including the leading `$`.
+        name: String,
+        /// The new value of the variable.
+        value: YarnValue,
+    },
+    /// See [`Dialogue::stop`].
+    Stop,
+}
+
+/// Returned by the methods of [`DialogueHandle`] when its [`DialogueDriver`] was dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("The dialogue driver was dropped, so the dialogue cannot be reached anymore")]
+pub struct DialogueDriverDisconnected;
+
+/// Owns a [`Dialogue`] and runs it on behalf of its [`DialogueHandle`]s. Create both with [`DialogueDriver::new`].
+///
+/// Move the driver to the thread that should run the virtual machine. There, either call [`DialogueDriver::run`] to handle requests until
+/// all handles are dropped, or call [`DialogueDriver::process_requests`] from your own loop.
+///
+/// Every event the dialogue returns is sent to the handles individually, in order. Errors returned by the dialogue are sent in place of the events
+/// of the failed request, so the driver keeps running.
+///
+/// ## Example
+///
+/// ```rust
+/// # use yarnspinner_runtime::prelude::*;
+/// # fn run(dialogue: Dialogue) -> Result<(), DialogueDriverDisconnected> {
+/// let (driver, handle) = DialogueDriver::new(dialogue);
+/// let vm_thread = std::thread::spawn(move || driver.run());
+///
+/// handle.set_node("Start")?;
+/// handle.continue_()?;
+/// while let Some(event) = handle.recv() {
+///     match event {
+///         Ok(DialogueEvent::Line(line)) => {
+///             println!("{}", line.text);
+///             handle.continue_()?;
+///         }
+///         Ok(DialogueEvent::Options(options)) => {
+///             handle.select_option(options[0].id)?;
+///             handle.continue_()?;
+///         }
+///         Ok(DialogueEvent::DialogueComplete) => break,
+///         Ok(_) => {}
+///         Err(error) => eprintln!("{error}"),
+///     }
+/// }
+/// drop(handle);
+/// let _dialogue = vm_thread.join().unwrap();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DialogueDriver {
+    dialogue: Dialogue,
+    requests: Receiver<DialogueRequest>,
+    events: Sender<Result<DialogueEvent>>,
+}
+
+/// A cheap, cloneable handle to a [`Dialogue`] owned by a [`DialogueDriver`], possibly on another thread.
+///
+/// Requests are handled by the driver in the order they were sent. All clones share the same queue of events,
+/// so every event is received by exactly one of them.
+#[derive(Debug, Clone)]
+pub struct DialogueHandle {
+    requests: Sender<DialogueRequest>,
+    events: Arc<Mutex<Receiver<Result<DialogueEvent>>>>,
+}
+
+impl DialogueDriver {
+    /// Moves the `dialogue` into a new driver and returns it alongside the first handle to it.
+    pub fn new(dialogue: Dialogue) -> (Self, DialogueHandle) {
+        let (request_sender, request_receiver) = mpsc::channel();
+        let (event_sender, event_receiver) = mpsc::channel();
+        let driver = Self {
+            dialogue,
+            requests: request_receiver,
+            events: event_sender,
+        };
+        let handle = DialogueHandle {
+            requests: request_sender,
+            events: Arc::new(Mutex::new(event_receiver)),
+        };
+        (driver, handle)
+    }
+
+    /// Handles requests as they arrive, blocking while there are none, until all [`DialogueHandle`]s are dropped.
+    /// Returns the [`Dialogue`] afterwards, so its state can still be inspected or saved.
+    pub fn run(mut self) -> Dialogue {
+        while let Ok(request) = self.requests.recv() {
+            self.handle_request(request);
+        }
+        self.dialogue
+    }
+
+    /// Handles all requests that have arrived so far without blocking and returns how many there were.
+    pub fn process_requests(&mut self) -> usize {
+        let mut processed = 0;
+        while let Ok(request) = self.requests.try_recv() {
+            self.handle_request(request);
+            processed += 1;
+        }
+        processed
+    }
+
+    /// The [`Dialogue`] owned by this driver.
+    #[must_use]
+    pub fn dialogue(&self) -> &Dialogue {
+        &self.dialogue
+    }
+
+    /// The [`Dialogue`] owned by this driver, e.g. to register functions or load programs.
+    #[must_use]
+    pub fn dialogue_mut(&mut self) -> &mut Dialogue {
+        &mut self.dialogue
+    }
+
+    /// Returns the [`Dialogue`] owned by this driver. [`DialogueHandle`]s to it are disconnected.
+    #[must_use]
+    pub fn into_inner(self) -> Dialogue {
+        self.dialogue
+    }
+
+    fn handle_request(&mut self, request: DialogueRequest) {
+        let events = match request {
+            DialogueRequest::SetNode(node_name) => {
+                self.dialogue.set_node(node_name).map(|_| Vec::new())
+            }
+            DialogueRequest::Continue => self.dialogue.continue_(),
+            DialogueRequest::SelectOption(option_id) => self
+                .dialogue
+                .set_selected_option(option_id)
+                .map(|_| Vec::new()),
+            DialogueRequest::SetVariable { name, value } => self
+                .dialogue
+                .variable_storage_mut()
+                .set(name, value)
+                .map(|_| Vec::new())
+                .map_err(DialogueError::from),
+            DialogueRequest::Stop => Ok(self.dialogue.stop()),
+        };
+        match events {
+            // The handles being dropped is not an error: the requests were still handled
+            Ok(events) => {
+                for event in events {
+                    let _ = self.events.send(Ok(event));
+                }
+            }
+            Err(error) => {
+                let _ = self.events.send(Err(error));
+            }
+        }
+    }
+}
+
+impl DialogueHandle {
+    /// Sends a [`DialogueRequest::SetNode`].
+    pub fn set_node(
+        &self,
+        node_name: impl Into<String>,
+    ) -> std::result::Result<(), DialogueDriverDisconnected> {
+        self.send(DialogueRequest::SetNode(node_name.into()))
+    }
+
+    /// Sends a [`DialogueRequest::Continue`].
+    pub fn continue_(&self) -> std::result::Result<(), DialogueDriverDisconnected> {
+        self.send(DialogueRequest::Continue)
+    }
+
+    /// Sends a [`DialogueRequest::SelectOption`].
+    pub fn select_option(
+        &self,
+        option_id: OptionId,
+    ) -> std::result::Result<(), DialogueDriverDisconnected> {
+        self.send(DialogueRequest::SelectOption(option_id))
+    }
+
+    /// Sends a [`DialogueRequest::SetVariable`].
+    pub fn set_variable(
+        &self,
+        name: impl Into<String>,
+        value: impl Into<YarnValue>,
+    ) -> std::result::Result<(), DialogueDriverDisconnected> {
+        self.send(DialogueRequest::SetVariable {
+            name: name.into(),
+            value: value.into(),
+        })
+    }
+
+    /// Sends a [`DialogueRequest::Stop`].
+    pub fn stop(&self) -> std::result::Result<(), DialogueDriverDisconnected> {
+        self.send(DialogueRequest::Stop)
+    }
+
+    /// Sends any [`DialogueRequest`] to the driver.
+    pub fn send(
+        &self,
+        request: DialogueRequest,
+    ) -> std::result::Result<(), DialogueDriverDisconnected> {
+        self.requests
+            .send(request)
+            .map_err(|_| DialogueDriverDisconnected)
+    }
+
+    /// Returns the next event without blocking, or [`None`] if there is none yet or the driver was dropped.
+    #[must_use]
+    pub fn try_recv(&self) -> Option<Result<DialogueEvent>> {
+        match self.receiver().try_recv() {
+            Ok(event) => Some(event),
+            Err(TryRecvError::Empty | TryRecvError::Disconnected) => None,
+        }
+    }
+
+    /// Blocks until the next event arrives. Returns [`None`] once the driver was dropped and all of its events were received.
+    #[must_use]
+    pub fn recv(&self) -> Option<Result<DialogueEvent>> {
+        self.receiver().recv().ok()
+    }
+
+    fn receiver(&self) -> MutexGuard<'_, Receiver<Result<DialogueEvent>>> {
+        self.events.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,81 @@
+//! Lets a [`Dialogue`] be consumed one [`DialogueEvent`] at a time instead of in batches.
+
+use crate::prelude::*;
+use crate::Result;
+use std::collections::VecDeque;
+
+/// An [`Iterator`] that yields the [`DialogueEvent`]s of a [`Dialogue`] one at a time, created by [`Dialogue::events`].
+///
+/// Whenever all events of the last batch were yielded, the iterator calls [`Dialogue::continue_`] to get the next batch.
+/// It ends when the dialogue has no current node, i.e. after [`DialogueEvent::DialogueComplete`] was yielded or before [`Dialogue::set_node`] was called.
+///
+/// The rules of [`Dialogue::continue_`] still apply: after a [`DialogueEvent::Options`], an option must be selected with
+/// [`DialogueEvents::select_option`] before the next event is requested. Otherwise, the iterator yields a [`DialogueError::ContinueOnOptionSelectionError`].
+/// Since selecting an option needs access to the iterator, use a `while let` loop instead of a `for` loop.
+///
+/// ## Example
+///
+/// ```rust
+/// # use yarnspinner_runtime::prelude::*;
+/// # fn run(dialogue: &mut Dialogue) -> yarnspinner_runtime::Result<()> {
+/// let mut events = dialogue.events();
+/// while let Some(event) = events.next() {
+///     match event? {
+///         DialogueEvent::Line(line) => println!("{}", line.text),
+///         DialogueEvent::Options(options) => {
+///             events.select_option(options[0].id)?;
+///         }
+///         _ => {}
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DialogueEvents<'a> {
+    dialogue: &'a mut Dialogue,
+    buffered_events: VecDeque<DialogueEvent>,
+}
+
+impl<'a> DialogueEvents<'a> {
+    pub(crate) fn new(dialogue: &'a mut Dialogue) -> Self {
+        Self {
+            dialogue,
+            buffered_events: VecDeque::new(),
+        }
+    }
+
+    /// Selects the option the user chose after a [`DialogueEvent::Options`]. See [`Dialogue::set_selected_option`].
+    pub fn select_option(&mut self, selected_option_id: OptionId) -> Result<&mut Self> {
+        self.dialogue.set_selected_option(selected_option_id)?;
+        Ok(self)
+    }
+
+    /// The [`Dialogue`] the events are taken from.
+    #[must_use]
+    pub fn dialogue(&self) -> &Dialogue {
+        self.dialogue
+    }
+
+    /// The [`Dialogue`] the events are taken from, e.g. to access its [`VariableStorage`] while handling a [`DialogueEvent::Command`].
+    /// Events that were already taken from the dialogue are still yielded, even if the dialogue is changed in the meantime.
+    #[must_use]
+    pub fn dialogue_mut(&mut self) -> &mut Dialogue {
+        self.dialogue
+    }
+}
+
+impl Iterator for DialogueEvents<'_> {
+    type Item = Result<DialogueEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.buffered_events.is_empty() {
+            self.dialogue.current_node()?;
+            match self.dialogue.continue_() {
+                Ok(events) => self.buffered_events.extend(events),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+        self.buffered_events.pop_front().map(Ok)
+    }
+}
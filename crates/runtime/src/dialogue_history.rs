@@ -0,0 +1,39 @@
+//! Contains the entries of the log returned by [`Dialogue::history`].
+
+use crate::prelude::*;
+
+/// Content that was delivered by a [`Dialogue`], as recorded in [`Dialogue::history`] when [`Dialogue::history_enabled`] is `true`.
+/// Intended for backlog UIs that show the player what was said so far.
+#[derive(Debug, Clone, PartialEq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum HistoryEntry {
+    /// A line that was delivered via [`DialogueEvent::Line`], with its substitutions applied and markup parsed.
+    Line(Line),
+
+    /// Options that were delivered via [`DialogueEvent::Options`].
+    Options {
+        /// The options that were presented, including unavailable ones.
+        options: Vec<DialogueOption>,
+        /// The option that was passed to [`Dialogue::set_selected_option`], or [`None`] if none was selected yet.
+        selected_option: Option<OptionId>,
+    },
+}
+
+impl HistoryEntry {
+    /// Returns the selected [`DialogueOption`] if this entry is [`HistoryEntry::Options`] and an option was selected.
+    pub fn selected_option(&self) -> Option<&DialogueOption> {
+        match self {
+            HistoryEntry::Options {
+                options,
+                selected_option: Some(id),
+            } => options.iter().find(|option| option.id == *id),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Collects the numbers returned by [`Dialogue::metrics`],
+//! which help finding scripts that are too expensive for low-end hardware.
+
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How much work a [`Dialogue`] did while [`Dialogue::metrics_enabled`] was `true`.
+/// Retrieve it with [`Dialogue::metrics`] and start over with [`Dialogue::reset_metrics`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct DialogueMetrics {
+    /// The number of instructions that were executed, across all nodes.
+    pub instructions_executed: usize,
+
+    /// How often each function was called, by function name. Functions that were never called are missing.
+    /// Includes the functions the compiler generates calls to, e.g. `Number.Add` for `+`.
+    pub function_calls: HashMap<String, usize>,
+
+    /// The time spent executing the instructions of each node, by node name. Nodes that never ran are missing.
+    /// Includes the time spent in the functions the node called, but not the time the dialogue waited for the game,
+    /// e.g. for [`Dialogue::continue_`] after a line or for an async function to finish.
+    pub time_per_node: HashMap<String, Duration>,
+}
+
+impl DialogueMetrics {
+    /// The time spent executing instructions, across all nodes.
+    pub fn total_time(&self) -> Duration {
+        self.time_per_node.values().sum()
+    }
+
+    /// The nodes sorted by the time spent executing them, most expensive first.
+    pub fn nodes_by_time(&self) -> Vec<(&str, Duration)> {
+        let mut nodes: Vec<_> = self
+            .time_per_node
+            .iter()
+            .map(|(name, &time)| (name.as_str(), time))
+            .collect();
+        nodes.sort_by(|(lhs_name, lhs_time), (rhs_name, rhs_time)| {
+            rhs_time.cmp(lhs_time).then_with(|| lhs_name.cmp(rhs_name))
+        });
+        nodes
+    }
+
+    pub(crate) fn record_instruction(&mut self, node_name: &str, time: Duration) {
+        self.instructions_executed += 1;
+        match self.time_per_node.get_mut(node_name) {
+            Some(total) => *total += time,
+            None => {
+                self.time_per_node.insert(node_name.to_owned(), time);
+            }
+        }
+    }
+
+    pub(crate) fn record_function_call(&mut self, function_name: &str) {
+        match self.function_calls.get_mut(function_name) {
+            Some(count) => *count += 1,
+            None => {
+                self.function_calls.insert(function_name.to_owned(), 1);
+            }
+        }
+    }
+}
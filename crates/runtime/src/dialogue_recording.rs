@@ -0,0 +1,346 @@
+//! Records the inputs of a playthrough so that it can be replayed later,
+//! e.g. to reproduce a bug report or to verify that a script change does not break a golden path.
+//!
+//! Like [`dialogue_sync`](crate::prelude::DialogueSyncHost), this relies on the VM being deterministic:
+//! the inputs and the numbers drawn by the random functions are all that is needed to arrive at the same events again.
+//! Other Yarn functions and the variable storage must behave the same during the replay as during the recording.
+
+use crate::prelude::*;
+use rand::rngs::SmallRng;
+use rand::{RngCore, SeedableRng};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+use thiserror::Error;
+use yarnspinner_core::prelude::*;
+
+/// A playthrough recorded by a [`DialogueRecorder`], to be replayed with [`replay_recording`].
+/// With the `serde` feature enabled, this can be saved to a file, e.g. attached to a bug report or checked in as a test case.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct DialogueRecording {
+    /// The inputs to the [`Dialogue`], in the order they were made.
+    pub inputs: Vec<RecordedInput>,
+    /// The raw numbers drawn from the random number generator by the random functions, e.g. `dice`, in the order they were drawn.
+    pub random_draws: Vec<u64>,
+    /// The events the [`Dialogue`] emitted during the playthrough, in the order they were emitted.
+    pub events: Vec<DialogueEvent>,
+}
+
+/// An input to a [`Dialogue`] as recorded in a [`DialogueRecording`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum RecordedInput {
+    /// [`Dialogue::set_node`] was called with the given node name.
+    SetNode(String),
+    /// [`Dialogue::continue_`] was called.
+    Continue,
+    /// [`Dialogue::set_selected_option`] was called.
+    SelectOption {
+        /// The ID of the selected option.
+        id: OptionId,
+        /// The line of the selected option. During a replay, the option with this line is selected,
+        /// so that the replay still works if options were added or reordered in the meantime.
+        /// [`None`] if the options were not delivered through the [`DialogueRecorder`], in which case the option with [`RecordedInput::SelectOption::id`] is selected.
+        line_id: Option<LineId>,
+    },
+    /// [`Dialogue::stop`] was called.
+    Stop,
+}
+
+/// The result of [`replay_recording`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    /// The events the [`Dialogue`] emitted during the replay.
+    pub events: Vec<DialogueEvent>,
+    /// Whether the random functions drew exactly as many numbers as during the recording.
+    /// If they drew more, the missing numbers were generated from a fixed seed.
+    pub random_draws_match: bool,
+}
+
+impl Replay {
+    /// Returns `true` if the replay emitted the same events as the recorded playthrough and used up exactly the recorded random draws.
+    /// Use this to verify that a golden path still plays out the same after changing the script.
+    pub fn matches(&self, recording: &DialogueRecording) -> bool {
+        self.random_draws_match && self.events == recording.events
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("Input {input_index} of the recording selects the option with the line {line_id}, but no such option was offered.")]
+    OptionNotFound { input_index: usize, line_id: LineId },
+    #[error("Input {input_index} of the recording failed: {source}")]
+    DialogueError {
+        input_index: usize,
+        #[source]
+        source: DialogueError,
+    },
+}
+
+/// Drives a [`Dialogue`] and records its inputs and random draws in a [`DialogueRecording`].
+///
+/// Call the methods of this type instead of the ones of the same name on [`Dialogue`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use yarnspinner_runtime::prelude::*;
+/// # use yarnspinner_core::prelude::*;
+/// fn record_playthrough(dialogue: &mut Dialogue) -> Result<DialogueRecording, DialogueError> {
+///     let mut recorder = DialogueRecorder::new(dialogue);
+///     recorder.set_node(dialogue, "Start")?;
+///     while !recorder.continue_(dialogue)?.contains(&DialogueEvent::DialogueComplete) {}
+///     Ok(recorder.recording())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DialogueRecorder {
+    recording: DialogueRecording,
+    random_draws: Arc<Mutex<Vec<u64>>>,
+    current_options: Vec<DialogueOption>,
+}
+
+impl DialogueRecorder {
+    /// Creates a recorder and replaces the random number generator of the dialogue with one that records its draws.
+    /// The draws themselves are still random.
+    #[must_use]
+    pub fn new(dialogue: &mut Dialogue) -> Self {
+        let random_draws = Arc::new(Mutex::new(Vec::new()));
+        dialogue.set_rng(RecordingRng {
+            rng: SmallRng::from_entropy(),
+            draws: random_draws.clone(),
+        });
+        Self {
+            recording: DialogueRecording::default(),
+            random_draws,
+            current_options: Vec::new(),
+        }
+    }
+
+    /// Returns everything recorded so far.
+    #[must_use]
+    pub fn recording(&self) -> DialogueRecording {
+        DialogueRecording {
+            random_draws: self
+                .random_draws
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+            ..self.recording.clone()
+        }
+    }
+
+    /// Calls [`Dialogue::set_node`] and records it.
+    pub fn set_node(
+        &mut self,
+        dialogue: &mut Dialogue,
+        node_name: impl Into<String>,
+    ) -> crate::Result<()> {
+        let node_name = node_name.into();
+        dialogue.set_node(node_name.clone())?;
+        self.recording
+            .inputs
+            .push(RecordedInput::SetNode(node_name));
+        Ok(())
+    }
+
+    /// Calls [`Dialogue::continue_`], records it and returns its events.
+    pub fn continue_(&mut self, dialogue: &mut Dialogue) -> crate::Result<Vec<DialogueEvent>> {
+        let events = dialogue.continue_()?;
+        self.recording.inputs.push(RecordedInput::Continue);
+        self.record_events(&events);
+        Ok(events)
+    }
+
+    /// Calls [`Dialogue::set_selected_option`] and records it.
+    pub fn set_selected_option(
+        &mut self,
+        dialogue: &mut Dialogue,
+        id: OptionId,
+    ) -> crate::Result<()> {
+        dialogue.set_selected_option(id)?;
+        let line_id = self
+            .current_options
+            .iter()
+            .find(|option| option.id == id)
+            .map(|option| option.line.id.clone());
+        self.recording
+            .inputs
+            .push(RecordedInput::SelectOption { id, line_id });
+        Ok(())
+    }
+
+    /// Calls [`Dialogue::stop`], records it and returns its events.
+    pub fn stop(&mut self, dialogue: &mut Dialogue) -> Vec<DialogueEvent> {
+        let events = dialogue.stop();
+        self.recording.inputs.push(RecordedInput::Stop);
+        self.record_events(&events);
+        events
+    }
+
+    fn record_events(&mut self, events: &[DialogueEvent]) {
+        for event in events {
+            if let DialogueEvent::Options(options) = event {
+                self.current_options = options.clone();
+            }
+        }
+        self.recording.events.extend_from_slice(events);
+    }
+}
+
+/// Replays a [`DialogueRecording`] on the given dialogue and returns the events it emitted.
+/// The dialogue should be in the same state as the recorded one was when the [`DialogueRecorder`] was created,
+/// but may run an updated version of the program.
+///
+/// Replaces the random number generator of the dialogue with one that returns the recorded draws.
+///
+/// ## Errors
+///
+/// Returns an error if a recorded input cannot be applied anymore, e.g. because the recorded option is not offered by the updated program.
+pub fn replay_recording(
+    dialogue: &mut Dialogue,
+    recording: &DialogueRecording,
+) -> Result<Replay, ReplayError> {
+    let state = Arc::new(Mutex::new(ReplayRngState {
+        draws: recording.random_draws.iter().copied().collect(),
+        exhausted: false,
+    }));
+    dialogue.set_rng(ReplayRng {
+        state: state.clone(),
+        fallback: SmallRng::seed_from_u64(0),
+    });
+
+    let mut events = Vec::new();
+    let mut current_options = Vec::new();
+    for (input_index, input) in recording.inputs.iter().enumerate() {
+        let error = |source| ReplayError::DialogueError {
+            input_index,
+            source,
+        };
+        let new_events = match input {
+            RecordedInput::SetNode(node_name) => {
+                dialogue.set_node(node_name.clone()).map_err(error)?;
+                Vec::new()
+            }
+            RecordedInput::Continue => dialogue.continue_().map_err(error)?,
+            RecordedInput::SelectOption { id, line_id: None } => {
+                dialogue.set_selected_option(*id).map_err(error)?;
+                Vec::new()
+            }
+            RecordedInput::SelectOption {
+                line_id: Some(line_id),
+                ..
+            } => {
+                let option: &DialogueOption = current_options
+                    .iter()
+                    .find(|option: &&DialogueOption| option.line.id == *line_id)
+                    .ok_or_else(|| ReplayError::OptionNotFound {
+                        input_index,
+                        line_id: line_id.clone(),
+                    })?;
+                dialogue.set_selected_option(option.id).map_err(error)?;
+                Vec::new()
+            }
+            RecordedInput::Stop => dialogue.stop(),
+        };
+        for event in &new_events {
+            if let DialogueEvent::Options(options) = event {
+                current_options = options.clone();
+            }
+        }
+        events.extend(new_events);
+    }
+
+    let state = state.lock().unwrap_or_else(PoisonError::into_inner);
+    Ok(Replay {
+        events,
+        random_draws_match: state.draws.is_empty() && !state.exhausted,
+    })
+}
+
+/// Passes on the numbers of another generator and remembers them.
+#[derive(Debug)]
+struct RecordingRng {
+    rng: SmallRng,
+    draws: Arc<Mutex<Vec<u64>>>,
+}
+
+impl RngCore for RecordingRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let draw = self.rng.next_u64();
+        self.draws
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(draw);
+        draw
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes_via_next_u64(self, dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct ReplayRngState {
+    draws: VecDeque<u64>,
+    exhausted: bool,
+}
+
+/// Returns the numbers of a [`DialogueRecording`], then those of a fixed seed.
+#[derive(Debug)]
+struct ReplayRng {
+    state: Arc<Mutex<ReplayRngState>>,
+    fallback: SmallRng,
+}
+
+impl RngCore for ReplayRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.draws.pop_front().unwrap_or_else(|| {
+            state.exhausted = true;
+            self.fallback.next_u64()
+        })
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes_via_next_u64(self, dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+fn fill_bytes_via_next_u64(rng: &mut impl RngCore, dest: &mut [u8]) {
+    for chunk in dest.chunks_mut(8) {
+        let bytes = rng.next_u64().to_le_bytes();
+        chunk.copy_from_slice(&bytes[..chunk.len()]);
+    }
+}
@@ -0,0 +1,40 @@
+//! Allows saving a [`Dialogue`] in the middle of a node.
+
+use crate::prelude::*;
+
+/// The execution state of a [`Dialogue`] in the middle of a node, i.e. the current node, the position in it, the value stack and any pending options.
+/// Created with [`Dialogue::state_snapshot`] and applied with [`Dialogue::restore_snapshot`].
+///
+/// With the `serde` feature enabled, this can be stored in a save game so that a conversation resumes exactly where it was left off after loading.
+/// The snapshot does not contain any variables, as those are persisted by the [`VariableStorage`], nor the loaded [`Program`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct DialogueSnapshot {
+    pub(crate) node_name: String,
+    /// Used to detect whether the node changed since the snapshot was taken.
+    pub(crate) instruction_count: usize,
+    pub(crate) state: State,
+    pub(crate) execution_state: ExecutionState,
+}
+
+impl DialogueSnapshot {
+    /// The name of the node that was running when the snapshot was taken.
+    pub fn node_name(&self) -> &str {
+        &self.node_name
+    }
+
+    /// The options that were waiting for a selection when the snapshot was taken, if any.
+    pub fn pending_options(&self) -> &[DialogueOption] {
+        if self.execution_state == ExecutionState::WaitingOnOptionSelection {
+            &self.state.current_options
+        } else {
+            &[]
+        }
+    }
+}
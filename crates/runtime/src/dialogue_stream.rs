@@ -0,0 +1,115 @@
+//! Lets async code drive a [`Dialogue`] with `.await` instead of calling [`Dialogue::continue_`] in a loop.
+//! Requires the `futures` feature.
+
+use crate::prelude::*;
+use crate::Result;
+use futures_core::Stream;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A [`Stream`] that yields the [`DialogueEvent`]s of a [`Dialogue`] one at a time. Requires the `futures` feature.
+///
+/// Works like [`Dialogue::events`], but owns the dialogue so that it can be moved into async tasks,
+/// e.g. one per connected player on a server. The stream ends when the dialogue has no current node,
+/// i.e. after [`DialogueEvent::DialogueComplete`] was yielded or before [`Dialogue::set_node`] was called.
+///
+/// After a [`DialogueEvent::Options`], an option must be selected with [`DialogueStream::select_option`] before the next event is requested.
+/// Otherwise, the stream yields a [`DialogueError::ContinueOnOptionSelectionError`].
+///
+/// While an async function registered with `Library::add_async_function` is running, the stream is pending instead of
+/// yielding [`DialogueEvent::FunctionPending`], and it is woken as soon as the function finishes.
+/// The same goes for [`DialogueEvent::TextPending`] while the [`TextProvider`](crate::prelude::TextProvider) is still loading a line.
+///
+/// ## Example
+///
+/// ```rust
+/// # use yarnspinner_runtime::prelude::*;
+/// use futures_lite::StreamExt;
+///
+/// async fn run(dialogue: Dialogue) -> yarnspinner_runtime::Result<Dialogue> {
+///     let mut events = DialogueStream::new(dialogue);
+///     while let Some(event) = events.next().await {
+///         match event? {
+///             DialogueEvent::Line(line) => println!("{}", line.text),
+///             DialogueEvent::Options(options) => {
+///                 events.select_option(options[0].id).await?;
+///             }
+///             _ => {}
+///         }
+///     }
+///     Ok(events.into_inner())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DialogueStream {
+    dialogue: Dialogue,
+    buffered_events: VecDeque<DialogueEvent>,
+}
+
+impl DialogueStream {
+    /// Creates a new stream over the events of the `dialogue`. Call [`Dialogue::set_node`] before, or via [`DialogueStream::dialogue_mut`].
+    pub fn new(dialogue: Dialogue) -> Self {
+        Self {
+            dialogue,
+            buffered_events: VecDeque::new(),
+        }
+    }
+
+    /// Selects the option the user chose after a [`DialogueEvent::Options`]. See [`Dialogue::set_selected_option`].
+    ///
+    /// Selecting an option never has to wait, but this is `async` so that it reads like the rest of the code driving the stream
+    /// and so that waiting can be added without breaking callers.
+    pub async fn select_option(&mut self, selected_option_id: OptionId) -> Result<()> {
+        self.dialogue.set_selected_option(selected_option_id)?;
+        Ok(())
+    }
+
+    /// The [`Dialogue`] the events are taken from.
+    #[must_use]
+    pub fn dialogue(&self) -> &Dialogue {
+        &self.dialogue
+    }
+
+    /// The [`Dialogue`] the events are taken from, e.g. to access its [`VariableStorage`] while handling a [`DialogueEvent::Command`].
+    /// Events that were already taken from the dialogue are still yielded, even if the dialogue is changed in the meantime.
+    #[must_use]
+    pub fn dialogue_mut(&mut self) -> &mut Dialogue {
+        &mut self.dialogue
+    }
+
+    /// Returns the wrapped [`Dialogue`]. Events that were taken from it but not yielded yet are lost.
+    #[must_use]
+    pub fn into_inner(mut self) -> Dialogue {
+        self.dialogue.set_waker(None);
+        self.dialogue
+    }
+}
+
+impl Stream for DialogueStream {
+    type Item = Result<DialogueEvent>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match this.buffered_events.pop_front() {
+                // The pending function or text was polled with our waker, which is called once it finishes.
+                Some(DialogueEvent::FunctionPending(_) | DialogueEvent::TextPending(_))
+                    if this.buffered_events.is_empty() =>
+                {
+                    return Poll::Pending;
+                }
+                Some(event) => return Poll::Ready(Some(Ok(event))),
+                None => {}
+            }
+            if this.dialogue.current_node().is_none() {
+                return Poll::Ready(None);
+            }
+            this.dialogue.set_waker(Some(cx.waker().clone()));
+            match this.dialogue.continue_() {
+                Ok(events) => this.buffered_events.extend(events),
+                Err(error) => return Poll::Ready(Some(Err(error))),
+            }
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Keeps a [`Dialogue`] synchronized between a host and any number of replicas,
+//! e.g. for a conversation that all players of a co-op game take part in.
+//!
+//! The VM is deterministic, so the replicas only need the host's inputs to arrive at the same state:
+//! which node was started, when the dialogue was continued and which options were selected.
+//! The only other state that needs replicating are changes to variables that happened outside of the dialogue, e.g. by game code.
+//! Yarn functions must be deterministic for this to work, i.e. return the same value on every machine for the same input.
+
+use crate::prelude::*;
+use std::collections::HashMap;
+use thiserror::Error;
+
+type Result<T> = std::result::Result<T, DialogueSyncError>;
+
+/// A message sent from a [`DialogueSyncHost`] to a [`DialogueSyncReplica`]. With the `serde` feature enabled, this can be sent over the network.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct SyncMessage {
+    /// Increases by one with every message, so that replicas can detect lost or reordered messages.
+    /// A [`SyncAction::Snapshot`] carries the sequence number of the message that was sent last, since it does not advance the dialogue.
+    pub sequence: u64,
+    /// The variables that changed on the host since the last message, to be applied before [`SyncMessage::action`].
+    /// For a [`SyncAction::Snapshot`], this contains all variables instead.
+    pub variable_changes: HashMap<String, YarnValue>,
+    /// What the host did to its [`Dialogue`].
+    pub action: SyncAction,
+}
+
+/// An input to a [`Dialogue`] that is replicated by a [`SyncMessage`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum SyncAction {
+    /// [`Dialogue::set_node`] was called with the given node name.
+    SetNode(String),
+    /// [`Dialogue::continue_`] was called.
+    Continue,
+    /// [`Dialogue::set_selected_option`] was called with the given option.
+    SelectOption(OptionId),
+    /// [`Dialogue::stop`] was called.
+    Stop,
+    /// The complete state of the host's dialogue, for replicas that join late. `None` if no node was running.
+    Snapshot(Option<DialogueSnapshot>),
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum DialogueSyncError {
+    #[error("Expected sync message {expected}, but received {received}. A message was lost or reordered.")]
+    OutOfOrder { expected: u64, received: u64 },
+    #[error("Received sync message {sequence} before a snapshot. Replicas must be initialized with a snapshot from `DialogueSyncHost::snapshot` first.")]
+    MissingSnapshot { sequence: u64 },
+    #[error(transparent)]
+    DialogueError(#[from] DialogueError),
+    #[error(transparent)]
+    VariableStorageError(#[from] VariableStorageError),
+}
+
+/// Drives the authoritative [`Dialogue`] and produces the [`SyncMessage`]s that replicate it.
+///
+/// Call the methods of this type instead of the ones of the same name on [`Dialogue`] and send the returned messages to all replicas in order.
+#[derive(Debug, Clone, Default)]
+pub struct DialogueSyncHost {
+    sequence: u64,
+    replicated_variables: HashMap<String, YarnValue>,
+}
+
+impl DialogueSyncHost {
+    /// Creates a new host. Replicas must be initialized with [`DialogueSyncHost::snapshot`] before they can apply other messages.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The sequence number of the last message produced.
+    #[must_use]
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Calls [`Dialogue::set_node`] and returns the message replicating it.
+    pub fn set_node(
+        &mut self,
+        dialogue: &mut Dialogue,
+        node_name: impl Into<String>,
+    ) -> Result<SyncMessage> {
+        let node_name = node_name.into();
+        let variable_changes = self.take_variable_changes(dialogue);
+        dialogue.set_node(node_name.clone())?;
+        Ok(self.message(dialogue, variable_changes, SyncAction::SetNode(node_name)))
+    }
+
+    /// Calls [`Dialogue::continue_`] and returns its events alongside the message replicating it.
+    pub fn continue_(
+        &mut self,
+        dialogue: &mut Dialogue,
+    ) -> Result<(Vec<DialogueEvent>, SyncMessage)> {
+        let variable_changes = self.take_variable_changes(dialogue);
+        let events = dialogue.continue_()?;
+        Ok((
+            events,
+            self.message(dialogue, variable_changes, SyncAction::Continue),
+        ))
+    }
+
+    /// Calls [`Dialogue::set_selected_option`] and returns the message replicating it.
+    pub fn set_selected_option(
+        &mut self,
+        dialogue: &mut Dialogue,
+        option: OptionId,
+    ) -> Result<SyncMessage> {
+        let variable_changes = self.take_variable_changes(dialogue);
+        dialogue.set_selected_option(option)?;
+        Ok(self.message(dialogue, variable_changes, SyncAction::SelectOption(option)))
+    }
+
+    /// Calls [`Dialogue::stop`] and returns its events alongside the message replicating it.
+    pub fn stop(&mut self, dialogue: &mut Dialogue) -> (Vec<DialogueEvent>, SyncMessage) {
+        let variable_changes = self.take_variable_changes(dialogue);
+        let events = dialogue.stop();
+        (
+            events,
+            self.message(dialogue, variable_changes, SyncAction::Stop),
+        )
+    }
+
+    /// Returns a message containing the complete state of the dialogue and all variables, for replicas that join late.
+    /// Does not advance the sequence number, so it can be sent to a single replica without affecting the others.
+    #[must_use]
+    pub fn snapshot(&self, dialogue: &Dialogue) -> SyncMessage {
+        SyncMessage {
+            sequence: self.sequence,
+            variable_changes: dialogue.variable_storage().variables(),
+            action: SyncAction::Snapshot(dialogue.state_snapshot()),
+        }
+    }
+
+    fn take_variable_changes(&self, dialogue: &Dialogue) -> HashMap<String, YarnValue> {
+        dialogue
+            .variable_storage()
+            .variables()
+            .into_iter()
+            .filter(|(name, value)| self.replicated_variables.get(name) != Some(value))
+            .collect()
+    }
+
+    fn message(
+        &mut self,
+        dialogue: &Dialogue,
+        variable_changes: HashMap<String, YarnValue>,
+        action: SyncAction,
+    ) -> SyncMessage {
+        // Replicas run the same action, so they end up with the same variables as the host has now
+        self.replicated_variables = dialogue.variable_storage().variables();
+        self.sequence += 1;
+        SyncMessage {
+            sequence: self.sequence,
+            variable_changes,
+            action,
+        }
+    }
+}
+
+/// Applies the [`SyncMessage`]s of a [`DialogueSyncHost`] to a local [`Dialogue`].
+///
+/// The local dialogue must have the same [`Program`] and [`Library`] loaded as the host's.
+/// Its events should be presented as usual, but the replica should not call [`Dialogue::continue_`] or [`Dialogue::set_selected_option`] itself,
+/// and should instead forward the player's input to the host.
+#[derive(Debug, Clone, Default)]
+pub struct DialogueSyncReplica {
+    sequence: Option<u64>,
+}
+
+impl DialogueSyncReplica {
+    /// Creates a new replica. The first message applied must be a [`SyncAction::Snapshot`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The sequence number of the last message applied, or `None` if no snapshot was applied yet.
+    #[must_use]
+    pub fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+
+    /// Applies a message to the local dialogue and returns the resulting events.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if a message was skipped or arrived out of order, if no snapshot was applied yet, or if the dialogue rejected the action.
+    /// After an error, the replica should be reinitialized with a fresh [`DialogueSyncHost::snapshot`].
+    pub fn apply(
+        &mut self,
+        dialogue: &mut Dialogue,
+        message: SyncMessage,
+    ) -> Result<Vec<DialogueEvent>> {
+        if let SyncAction::Snapshot(snapshot) = message.action {
+            dialogue.stop();
+            let storage = dialogue.variable_storage_mut();
+            storage.clear();
+            storage.extend(message.variable_changes)?;
+            let events = match snapshot {
+                Some(snapshot) => dialogue.restore_snapshot(snapshot)?,
+                None => Vec::new(),
+            };
+            self.sequence = Some(message.sequence);
+            return Ok(events);
+        }
+
+        let Some(sequence) = self.sequence else {
+            return Err(DialogueSyncError::MissingSnapshot {
+                sequence: message.sequence,
+            });
+        };
+        if message.sequence != sequence + 1 {
+            return Err(DialogueSyncError::OutOfOrder {
+                expected: sequence + 1,
+                received: message.sequence,
+            });
+        }
+        dialogue
+            .variable_storage_mut()
+            .extend(message.variable_changes)?;
+        let events = match message.action {
+            SyncAction::SetNode(node_name) => {
+                dialogue.set_node(node_name)?;
+                Vec::new()
+            }
+            SyncAction::Continue => dialogue.continue_()?,
+            SyncAction::SelectOption(option) => {
+                dialogue.set_selected_option(option)?;
+                Vec::new()
+            }
+            SyncAction::Stop => dialogue.stop(),
+            SyncAction::Snapshot(_) => unreachable!("Snapshots are handled above"),
+        };
+        self.sequence = Some(message.sequence);
+        Ok(events)
+    }
+}
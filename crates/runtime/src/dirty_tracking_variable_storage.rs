@@ -0,0 +1,190 @@
+//! Lets autosave systems write only the variables that changed
+//! instead of the full variable set every time.
+
+use crate::prelude::*;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, PoisonError, RwLock};
+use yarnspinner_core::prelude::*;
+
+/// A [`VariableStorage`] that remembers which variables changed since the last call to [`DirtyTrackingVariableStorage::drain_dirty`].
+/// The variables themselves are kept in an underlying storage, which is a [`MemoryVariableStorage`] by default.
+///
+/// Shallow clones share the same variables and the same record of changes.
+///
+/// ## Example
+///
+/// ```rust
+/// # use yarnspinner_runtime::prelude::*;
+/// let mut storage = DirtyTrackingVariableStorage::new();
+/// storage.set("$gold".to_owned(), 10.into()).unwrap();
+/// storage.set("$name".to_owned(), "Alex".into()).unwrap();
+/// storage.drain_dirty();
+///
+/// storage.set("$gold".to_owned(), 12.into()).unwrap();
+/// let delta = storage.drain_dirty();
+/// assert_eq!(delta.len(), 1);
+/// assert_eq!(delta["$gold"], Some(YarnValue::from(12)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DirtyTrackingVariableStorage {
+    base: Box<dyn VariableStorage>,
+    dirty: Arc<RwLock<HashSet<String>>>,
+}
+
+impl Default for DirtyTrackingVariableStorage {
+    fn default() -> Self {
+        Self::with_base(Box::new(MemoryVariableStorage::new()))
+    }
+}
+
+impl DirtyTrackingVariableStorage {
+    /// Creates an empty storage that keeps its variables in memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a storage that keeps its variables in `base` and tracks the changes made through it.
+    /// Changes made to `base` directly are not tracked.
+    pub fn with_base(base: Box<dyn VariableStorage>) -> Self {
+        Self {
+            base,
+            dirty: Default::default(),
+        }
+    }
+
+    /// The underlying storage the variables are kept in.
+    pub fn base(&self) -> &dyn VariableStorage {
+        self.base.as_ref()
+    }
+
+    /// Returns `true` if any variable changed since the last call to [`DirtyTrackingVariableStorage::drain_dirty`].
+    pub fn is_dirty(&self) -> bool {
+        !self
+            .dirty
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_empty()
+    }
+
+    /// Returns the variables that changed since the last call, i.e. since the last checkpoint, and starts tracking anew.
+    /// Each variable is mapped to its current value, or to [`None`] if it was removed by [`VariableStorage::clear`].
+    /// Variables that were set to the value they already had count as changed.
+    pub fn drain_dirty(&mut self) -> HashMap<String, Option<YarnValue>> {
+        let dirty =
+            std::mem::take(&mut *self.dirty.write().unwrap_or_else(PoisonError::into_inner));
+        dirty
+            .into_iter()
+            .map(|name| {
+                let value = self.base.get(&name).ok();
+                (name, value)
+            })
+            .collect()
+    }
+
+    fn mark_dirty(&self, names: impl IntoIterator<Item = String>) {
+        self.dirty
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(names);
+    }
+}
+
+impl VariableStorage for DirtyTrackingVariableStorage {
+    fn clone_shallow(&self) -> Box<dyn VariableStorage> {
+        Box::new(self.clone())
+    }
+
+    fn set(&mut self, name: String, value: YarnValue) -> Result<()> {
+        self.base.set(name.clone(), value)?;
+        self.mark_dirty([name]);
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Result<YarnValue> {
+        self.base.get(name)
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.base.contains(name)
+    }
+
+    fn extend(&mut self, values: HashMap<String, YarnValue>) -> Result<()> {
+        let names: Vec<_> = values.keys().cloned().collect();
+        self.base.extend(values)?;
+        self.mark_dirty(names);
+        Ok(())
+    }
+
+    fn variables(&self) -> HashMap<String, YarnValue> {
+        self.base.variables()
+    }
+
+    fn clear(&mut self) {
+        self.mark_dirty(self.base.variables().into_keys());
+        self.base.clear();
+    }
+
+    fn add_observer(&mut self, observer: VariableObserver) -> Result<()> {
+        self.base.add_observer(observer)
+    }
+
+    fn begin_transaction(&mut self) -> Result<()> {
+        self.base.begin_transaction()
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        self.base.commit_transaction()
+    }
+
+    fn rollback_transaction(&mut self) -> Result<()> {
+        self.base.rollback_transaction()
+    }
+
+    fn is_in_transaction(&self) -> bool {
+        self.base.is_in_transaction()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drains_the_variables_changed_since_the_last_checkpoint() {
+        let mut storage = DirtyTrackingVariableStorage::new();
+        let mut shallow_clone = storage.clone_shallow();
+        storage.set("$gold".to_owned(), 10.into()).unwrap();
+        shallow_clone
+            .extend(HashMap::from([("$name".to_owned(), "Alex".into())]))
+            .unwrap();
+        assert!(storage.set("gold".to_owned(), 0.into()).is_err());
+        assert!(storage.is_dirty());
+        assert_eq!(
+            storage.drain_dirty(),
+            HashMap::from([
+                ("$gold".to_owned(), Some(10.into())),
+                ("$name".to_owned(), Some("Alex".into())),
+            ])
+        );
+        assert!(!storage.is_dirty());
+
+        shallow_clone.clear();
+        storage.set("$gold".to_owned(), 1.into()).unwrap();
+        assert_eq!(
+            storage.drain_dirty(),
+            HashMap::from([
+                ("$gold".to_owned(), Some(1.into())),
+                ("$name".to_owned(), None),
+            ])
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Chooses which node a conversation should start with,
+//! see [`Dialogue::choose_start_node`](crate::prelude::Dialogue::choose_start_node).
+
+use crate::dialogue::visit_count;
+use crate::prelude::*;
+
+/// The header that holds a condition of a node. A node may have any number of them, all of which must be met.
+pub(crate) const CONDITION_HEADER: &str = "when";
+
+/// Returns the candidate chosen by the `strategy` and tells it about the choice.
+pub(crate) fn choose_best_candidate(
+    scored_candidates: impl IntoIterator<Item = (String, f32)>,
+    variable_storage: &dyn VariableStorage,
+    strategy: &mut dyn ContentSaliencyStrategy,
+) -> Option<String> {
+    let candidates: Vec<_> = scored_candidates
+        .into_iter()
+        .map(|(node_name, score)| SaliencyCandidate {
+            view_count: visit_count(variable_storage, &node_name) as usize,
+            content_id: node_name,
+            score,
+        })
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+    let candidate = strategy.query_best_content(&candidates)?;
+    strategy.content_was_selected(candidate);
+    Some(candidate.content_id.clone())
+}
+
+/// Evaluates a single `when` header. Supported are `always`, a boolean operand, `not` followed by a boolean operand,
+/// and the comparison of two operands. Operands are variables, numbers, booleans and quoted strings.
+///
+/// Returns `None` if the condition is malformed or compares values of different types.
+pub(crate) fn evaluate_condition(
+    condition: &str,
+    variable_value: impl Fn(&str) -> Option<YarnValue>,
+) -> Option<bool> {
+    let tokens = tokenize(condition)?;
+    let operand = |token: &str| -> Option<YarnValue> {
+        if token.starts_with('$') {
+            variable_value(token)
+        } else if let Some(string) = token
+            .strip_prefix('"')
+            .and_then(|token| token.strip_suffix('"'))
+        {
+            Some(string.to_owned().into())
+        } else if let Ok(boolean) = token.parse::<bool>() {
+            Some(boolean.into())
+        } else {
+            token.parse::<f32>().ok().map(Into::into)
+        }
+    };
+    let boolean = |token: &str| match operand(token)? {
+        YarnValue::Boolean(value) => Some(value),
+        _ => None,
+    };
+    match tokens.as_slice() {
+        [always] if always == "always" => Some(true),
+        [value] => boolean(value),
+        [not, value] if not == "not" || not == "!" => boolean(value).map(|value| !value),
+        [lhs, operator, rhs] => compare(&operand(lhs)?, operator, &operand(rhs)?),
+        _ => None,
+    }
+}
+
+fn compare(lhs: &YarnValue, operator: &str, rhs: &YarnValue) -> Option<bool> {
+    match (lhs, rhs) {
+        (YarnValue::Number(lhs), YarnValue::Number(rhs)) => match operator {
+            "==" | "is" | "eq" => Some(lhs == rhs),
+            "!=" | "neq" => Some(lhs != rhs),
+            "<" | "lt" => Some(lhs < rhs),
+            "<=" | "lte" => Some(lhs <= rhs),
+            ">" | "gt" => Some(lhs > rhs),
+            ">=" | "gte" => Some(lhs >= rhs),
+            _ => None,
+        },
+        (YarnValue::String(_), YarnValue::String(_))
+        | (YarnValue::Boolean(_), YarnValue::Boolean(_)) => match operator {
+            "==" | "is" | "eq" => Some(lhs == rhs),
+            "!=" | "neq" => Some(lhs != rhs),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Splits a condition into operands and operators. Quoted strings stay together and symbolic operators don't need surrounding whitespace.
+fn tokenize(condition: &str) -> Option<Vec<String>> {
+    let is_operator_char = |c: char| matches!(c, '=' | '!' | '<' | '>');
+    let mut tokens = Vec::new();
+    let mut chars = condition.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut token = String::from(c);
+        if c == '"' {
+            loop {
+                let next = chars.next()?;
+                token.push(next);
+                if next == '"' {
+                    break;
+                }
+            }
+        } else {
+            let is_operator = is_operator_char(c);
+            while let Some(&next) = chars.peek() {
+                if next.is_whitespace() || next == '"' || is_operator_char(next) != is_operator {
+                    break;
+                }
+                token.push(next);
+                chars.next();
+            }
+        }
+        tokens.push(token);
+    }
+    Some(tokens)
+}
+
+/// Returns the conditions of a node, i.e. the values of all its `when` headers.
+pub(crate) fn node_conditions(node: &Node) -> impl Iterator<Item = &str> {
+    node.headers
+        .iter()
+        .filter(|header| header.key == CONDITION_HEADER)
+        .map(|header| header.value.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn evaluates_conditions() {
+        let variables: HashMap<&str, YarnValue> = HashMap::from([
+            ("$gold", 10.0.into()),
+            ("$met_alice", true.into()),
+            ("$name", "Bob".into()),
+        ]);
+        let evaluate =
+            |condition: &str| evaluate_condition(condition, |name| variables.get(name).cloned());
+
+        assert_eq!(evaluate("always"), Some(true));
+        assert_eq!(evaluate("$met_alice"), Some(true));
+        assert_eq!(evaluate("not $met_alice"), Some(false));
+        assert_eq!(evaluate("$gold >= 10"), Some(true));
+        assert_eq!(evaluate("$gold<5"), Some(false));
+        assert_eq!(evaluate("$gold gt 5"), Some(true));
+        assert_eq!(evaluate("$name == \"Bob\""), Some(true));
+        assert_eq!(evaluate("$name != \"Bob Smith\""), Some(true));
+        assert_eq!(evaluate("$gold == \"10\""), None);
+        assert_eq!(evaluate("$unknown"), None);
+        assert_eq!(evaluate("$gold"), None);
+        assert_eq!(evaluate("$gold >= 10 and $met_alice"), None);
+    }
+}
@@ -8,10 +8,10 @@
 use crate::prelude::*;
 use yarnspinner_core::prelude::*;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Hash)]
 #[cfg_attr(feature = "bevy", derive(Reflect))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
 #[cfg_attr(
     all(feature = "bevy", feature = "serde"),
     reflect(Serialize, Deserialize)
@@ -27,6 +27,8 @@ pub enum DialogueEvent {
     /// A list of [`DialogueOption`]s should be presented to the user, who in turns must select one of them.
     /// The selected option must be communicated to the [`Dialogue`] via [`Dialogue::set_selected_option`] before calling [`Dialogue::continue_`] again.
     Options(Vec<DialogueOption>),
+    /// The [`DialogueEvent::Options`] that follows this event should only be shown for a limited time, as set by the [`OPTIONS_TIMEOUT_COMMAND`].
+    OptionsTimeout(OptionsTimeout),
     /// A [`Command`] should be executed.
     ///
     /// It is not specified whether the command should be finished executing before calling [`Dialogue::continue_`] again or it is run in parallel.
@@ -47,4 +49,31 @@ pub enum DialogueEvent {
     LineHints(Vec<LineId>),
     /// The dialogue was completed. Set it to a new node via [`Dialogue::set_node`] before calling [`Dialogue::continue_`] again.
     DialogueComplete,
+    /// The dialogue reached a [`Breakpoint`] registered with [`Dialogue::add_breakpoint`] and paused right before running its instruction.
+    /// Calling [`Dialogue::continue_`] resumes the dialogue, while [`Dialogue::step_instruction`] runs only the next instruction.
+    BreakpointHit(Breakpoint),
+    /// The dialogue called the async function with the given name, registered with `Library::add_async_function`, and is waiting for its result.
+    /// Call [`Dialogue::continue_`] again, e.g. on the next frame, to check whether the result is available and resume the dialogue if it is.
+    /// Until then, every call to [`Dialogue::continue_`] returns this event again.
+    FunctionPending(String),
+    /// The [`TextProvider`] is still loading the text of the line or option with the given ID, see [`TextProvider::poll_text`].
+    /// Call [`Dialogue::continue_`] again, e.g. on the next frame, to check whether the text is available and resume the dialogue if it is.
+    /// Until then, every call to [`Dialogue::continue_`] returns this event again.
+    TextPending(LineId),
+    /// The [`Trigger`] with the given name, registered with [`Dialogue::add_trigger`], fired because its condition was met for the first time.
+    /// Emitted right after the event that met the condition.
+    TriggerFired(String),
+    /// A `<<set>>` statement stored a value in a variable. Only emitted if [`Dialogue::variable_change_events_enabled`] is set.
+    /// Lets views react to changed stats without wrapping the [`VariableStorage`].
+    ///
+    /// Emitted for every `<<set>>`, even if the value stays the same. Changes made directly through [`Dialogue::variable_storage_mut`] are not reported,
+    /// and neither are the internal variables used for tracking node visits.
+    VariableChanged {
+        /// The name of the variable, including the leading `$`.
+        name: String,
+        /// The value before the `<<set>>`, i.e. the value in the [`VariableStorage`] or, if there is none, the variable's initial value in the program.
+        old: Option<YarnValue>,
+        /// The value that was stored.
+        new: YarnValue,
+    },
 }
@@ -0,0 +1,265 @@
+//! Chains multiple [`TextProvider`]s, so that mods and partial translations
+//! can override single lines and fall back to the official text for everything else.
+
+use crate::prelude::*;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::task::{Context, Poll};
+use yarnspinner_core::prelude::*;
+
+/// A [`TextProvider`] that asks a prioritized list of named [`TextProvider`]s for each line and uses the text of the first one that has it,
+/// e.g. a user-made translation, then the official translation, then the base language.
+/// It remembers which provider delivered each line, see [`FallbackTextProvider::line_source`].
+///
+/// The language set with [`TextProvider::set_language`] is passed on to every provider.
+/// Line hints are passed on to every provider as well.
+///
+/// ## Example
+///
+/// ```rust
+/// # use yarnspinner_runtime::prelude::*;
+/// # use yarnspinner_core::prelude::*;
+/// # use std::collections::HashMap;
+/// let mut official = StringTableTextProvider::new();
+/// official.extend_base_language(HashMap::from([
+///     (LineId("line:hello".to_owned()), "Hello!".to_owned()),
+///     (LineId("line:bye".to_owned()), "Goodbye!".to_owned()),
+/// ]));
+/// let mut community_patch = StringTableTextProvider::new();
+/// community_patch.extend_base_language(HashMap::from([(
+///     LineId("line:bye".to_owned()),
+///     "Farewell!".to_owned(),
+/// )]));
+///
+/// let mut text_provider = FallbackTextProvider::new();
+/// text_provider
+///     .add_provider("community_patch", community_patch)
+///     .add_provider("official", official);
+///
+/// assert_eq!(text_provider.get_text(&LineId("line:bye".to_owned())).unwrap(), "Farewell!");
+/// assert_eq!(text_provider.get_text(&LineId("line:hello".to_owned())).unwrap(), "Hello!");
+/// assert_eq!(text_provider.line_source(&LineId("line:hello".to_owned())).unwrap(), "official");
+/// ```
+#[derive(Debug, Default)]
+pub struct FallbackTextProvider {
+    providers: Vec<(String, Box<dyn TextProvider>)>,
+    language: Option<Language>,
+    line_sources: Mutex<HashMap<LineId, String>>,
+}
+
+impl FallbackTextProvider {
+    /// Creates a [`FallbackTextProvider`] without any providers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a provider with a lower priority than all providers added before it.
+    /// If a provider with the same name was already added, it is replaced and keeps its priority.
+    pub fn add_provider(
+        &mut self,
+        name: impl Into<String>,
+        mut provider: impl TextProvider + 'static,
+    ) -> &mut Self {
+        let name = name.into();
+        provider.set_language(self.language.clone());
+        let provider: Box<dyn TextProvider> = Box::new(provider);
+        match self
+            .providers
+            .iter_mut()
+            .find(|(existing, _)| *existing == name)
+        {
+            Some((_, existing)) => *existing = provider,
+            None => self.providers.push((name, provider)),
+        }
+        self
+    }
+
+    /// Removes the provider with the given name and returns it, if there is one.
+    pub fn remove_provider(&mut self, name: &str) -> Option<Box<dyn TextProvider>> {
+        let index = self
+            .providers
+            .iter()
+            .position(|(existing, _)| existing == name)?;
+        Some(self.providers.remove(index).1)
+    }
+
+    /// Returns the provider with the given name, if there is one.
+    pub fn provider(&self, name: &str) -> Option<&dyn TextProvider> {
+        self.providers
+            .iter()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, provider)| provider.as_ref())
+    }
+
+    /// Returns the provider with the given name mutably, if there is one.
+    pub fn provider_mut(&mut self, name: &str) -> Option<&mut (dyn TextProvider + 'static)> {
+        self.providers
+            .iter_mut()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, provider)| provider.as_mut())
+    }
+
+    /// The names of all providers, from the highest to the lowest priority.
+    pub fn provider_names(&self) -> impl Iterator<Item = &str> {
+        self.providers.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the name of the provider that delivered the text of the given line the last time it was requested.
+    /// Returns [`None`] if the line was not requested yet or no provider had text for it.
+    pub fn line_source(&self, id: &LineId) -> Option<String> {
+        self.lock_line_sources().get(id).cloned()
+    }
+
+    /// Returns the names of the providers that delivered each line requested so far, see [`FallbackTextProvider::line_source`].
+    pub fn line_sources(&self) -> HashMap<LineId, String> {
+        self.lock_line_sources().clone()
+    }
+
+    fn lock_line_sources(&self) -> std::sync::MutexGuard<'_, HashMap<LineId, String>> {
+        self.line_sources
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn record_line_source(&self, id: &LineId, name: Option<&str>) {
+        let mut line_sources = self.lock_line_sources();
+        match name {
+            Some(name) => line_sources.insert(id.clone(), name.to_owned()),
+            None => line_sources.remove(id),
+        };
+    }
+}
+
+impl TextProvider for FallbackTextProvider {
+    fn accept_line_hints(&mut self, line_ids: &[LineId]) {
+        for (_, provider) in &mut self.providers {
+            provider.accept_line_hints(line_ids);
+        }
+    }
+
+    fn get_text(&self, id: &LineId) -> Option<String> {
+        let found = self
+            .providers
+            .iter()
+            .find_map(|(name, provider)| Some((name, provider.get_text(id)?)));
+        self.record_line_source(id, found.as_ref().map(|(name, _)| name.as_str()));
+        found.map(|(_, text)| text)
+    }
+
+    /// Polls the providers in order of priority. If a provider is still loading the line,
+    /// the lines of the providers with a lower priority are not used until it is done.
+    fn poll_text(&mut self, id: &LineId, context: &mut Context<'_>) -> Poll<Option<String>> {
+        let mut found = None;
+        for (name, provider) in &mut self.providers {
+            match provider.poll_text(id, context) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(text)) => {
+                    found = Some((name.clone(), text));
+                    break;
+                }
+                Poll::Ready(None) => {}
+            }
+        }
+        self.record_line_source(id, found.as_ref().map(|(name, _)| name.as_str()));
+        Poll::Ready(found.map(|(_, text)| text))
+    }
+
+    fn set_language(&mut self, language: Option<Language>) {
+        for (_, provider) in &mut self.providers {
+            provider.set_language(language.clone());
+        }
+        self.language = language;
+    }
+
+    fn get_language(&self) -> Option<Language> {
+        self.language.clone()
+    }
+
+    /// Returns `true` if any provider has its lines available, since the others might not cover the current language at all.
+    fn are_lines_available(&self) -> bool {
+        self.providers
+            .iter()
+            .any(|(_, provider)| provider.are_lines_available())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_id(id: &str) -> LineId {
+        LineId(id.to_owned())
+    }
+
+    fn provider(language: &str, lines: &[(&str, &str)]) -> StringTableTextProvider {
+        let table: StringTable = lines
+            .iter()
+            .map(|(id, text)| (line_id(id), text.to_string()))
+            .collect();
+        let mut provider = StringTableTextProvider::new();
+        provider.extend_translation(language, table);
+        provider
+    }
+
+    #[test]
+    fn falls_back_in_order_of_priority() {
+        let mut base = StringTableTextProvider::new();
+        base.extend_base_language(HashMap::from([
+            (line_id("line:a"), "Hello".to_owned()),
+            (line_id("line:b"), "Bye".to_owned()),
+            (line_id("line:c"), "Thanks".to_owned()),
+        ]));
+        let mut text_provider = FallbackTextProvider::new();
+        text_provider
+            .add_provider("mod", provider("de", &[("line:a", "Servus")]))
+            .add_provider(
+                "official",
+                provider("de", &[("line:a", "Hallo"), ("line:b", "Tschüss")]),
+            )
+            .add_provider("base", base);
+        text_provider.set_language(Some("de".into()));
+
+        assert!(text_provider.are_lines_available());
+        assert_eq!(
+            text_provider.get_text(&line_id("line:a")).unwrap(),
+            "Servus"
+        );
+        assert_eq!(
+            text_provider.get_text(&line_id("line:b")).unwrap(),
+            "Tschüss"
+        );
+        assert_eq!(
+            text_provider.get_text(&line_id("line:c")).unwrap(),
+            "Thanks"
+        );
+        assert_eq!(text_provider.get_text(&line_id("line:d")), None);
+        assert_eq!(
+            text_provider.line_sources(),
+            HashMap::from([
+                (line_id("line:a"), "mod".to_owned()),
+                (line_id("line:b"), "official".to_owned()),
+                (line_id("line:c"), "base".to_owned()),
+            ])
+        );
+
+        text_provider.remove_provider("mod");
+        assert_eq!(text_provider.get_text(&line_id("line:a")).unwrap(), "Hallo");
+        assert_eq!(
+            text_provider.line_source(&line_id("line:a")).unwrap(),
+            "official"
+        );
+        assert_eq!(
+            text_provider.provider_names().collect::<Vec<_>>(),
+            ["official", "base"]
+        );
+    }
+}
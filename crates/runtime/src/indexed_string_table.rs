@@ -0,0 +1,326 @@
+//! Provides a binary string table format that can be read in place, e.g. from a memory-mapped file,
+//! so that large localizations do not need to be decoded into [`String`]s up front.
+
+use crate::prelude::*;
+use log::error;
+use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use thiserror::Error;
+use yarnspinner_core::prelude::*;
+
+const MAGIC: &[u8; 4] = b"YSST";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 12;
+const ENTRY_LEN: usize = 16;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum IndexedStringTableError {
+    #[error(
+        "The data is not an indexed string table: it does not start with the expected header."
+    )]
+    InvalidHeader,
+    #[error("The indexed string table has version {0}, but only version {VERSION} is supported.")]
+    UnsupportedVersion(u32),
+    #[error("The indexed string table is truncated or corrupt: entry {index} points outside of the data.")]
+    OutOfBounds { index: usize },
+}
+
+/// A string table stored in a binary format that is read in place instead of being decoded when loaded.
+/// Looking up a line is a binary search over the index, and only the text of the requested line is turned into a [`String`].
+///
+/// Create the binary data with [`IndexedStringTable::encode`], e.g. in a build script, and load it with [`IndexedStringTable::from_bytes`].
+/// The bytes can come from anything that can be viewed as a `[u8]`, such as a [`Vec<u8>`] or a memory-mapped file like `memmap2::Mmap`,
+/// in which case the operating system only pages in the parts of the file that are actually read.
+///
+/// ## Format
+///
+/// All integers are little-endian `u32`s.
+/// - A header made of the magic bytes `YSST`, the format version and the number of entries.
+/// - One index entry per line, sorted by the bytes of the line ID: the offset and length of the line ID, followed by the offset and length of the text.
+///   Offsets are relative to the start of the data section.
+/// - The data section, containing the UTF-8 encoded line IDs and texts.
+pub struct IndexedStringTable {
+    bytes: Box<dyn AsRef<[u8]> + Send + Sync>,
+    len: usize,
+}
+
+impl Debug for IndexedStringTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexedStringTable")
+            .field("bytes", &self.bytes().len())
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl IndexedStringTable {
+    /// Encodes the given string table into the binary format read by [`IndexedStringTable::from_bytes`].
+    pub fn encode(string_table: &StringTable) -> Vec<u8> {
+        let mut entries: Vec<_> = string_table.iter().collect();
+        entries.sort_by(|(lhs, _), (rhs, _)| lhs.0.as_bytes().cmp(rhs.0.as_bytes()));
+
+        let mut index = Vec::with_capacity(HEADER_LEN + entries.len() * ENTRY_LEN);
+        index.extend_from_slice(MAGIC);
+        index.extend_from_slice(&VERSION.to_le_bytes());
+        index.extend_from_slice(&to_u32(entries.len()).to_le_bytes());
+        let mut data = Vec::new();
+        for (line_id, text) in entries {
+            for part in [line_id.0.as_bytes(), text.as_bytes()] {
+                index.extend_from_slice(&to_u32(data.len()).to_le_bytes());
+                index.extend_from_slice(&to_u32(part.len()).to_le_bytes());
+                data.extend_from_slice(part);
+            }
+        }
+        index.extend(data);
+        index
+    }
+
+    /// Loads a string table created by [`IndexedStringTable::encode`]. Only the header and the index are checked, the texts are not read until they are requested.
+    pub fn from_bytes(
+        bytes: impl AsRef<[u8]> + Send + Sync + 'static,
+    ) -> Result<Self, IndexedStringTableError> {
+        let data = bytes.as_ref();
+        if data.len() < HEADER_LEN || &data[..4] != MAGIC {
+            return Err(IndexedStringTableError::InvalidHeader);
+        }
+        let version = read_u32(data, 4);
+        if version != VERSION {
+            return Err(IndexedStringTableError::UnsupportedVersion(version));
+        }
+        let len = read_u32(data, 8) as usize;
+        let data_start = len
+            .checked_mul(ENTRY_LEN)
+            .and_then(|index_len| index_len.checked_add(HEADER_LEN))
+            .filter(|data_start| *data_start <= data.len())
+            .ok_or(IndexedStringTableError::OutOfBounds { index: 0 })?;
+        let data_len = data.len() - data_start;
+        for index in 0..len {
+            let entry = HEADER_LEN + index * ENTRY_LEN;
+            let is_in_bounds = [entry, entry + 8].into_iter().all(|position| {
+                let offset = read_u32(data, position) as usize;
+                let length = read_u32(data, position + 4) as usize;
+                offset
+                    .checked_add(length)
+                    .is_some_and(|end| end <= data_len)
+            });
+            if !is_in_bounds {
+                return Err(IndexedStringTableError::OutOfBounds { index });
+            }
+        }
+        Ok(Self {
+            bytes: Box::new(bytes),
+            len,
+        })
+    }
+
+    /// Returns the text of the line with the given ID, or [`None`] if the table does not contain it.
+    pub fn get(&self, line_id: &LineId) -> Option<&str> {
+        let target = line_id.0.as_bytes();
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.part(middle, 0).cmp(target) {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => {
+                    return std::str::from_utf8(self.part(middle, 8))
+                        .map_err(|_| error!("The text of line {line_id} in the indexed string table is not valid UTF-8."))
+                        .ok();
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the number of lines in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the table contains no lines.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn bytes(&self) -> &[u8] {
+        (*self.bytes).as_ref()
+    }
+
+    /// Returns the line ID (`field` 0) or text (`field` 8) of the entry at `index`. The bounds were checked in [`IndexedStringTable::from_bytes`].
+    fn part(&self, index: usize, field: usize) -> &[u8] {
+        let bytes = self.bytes();
+        let position = HEADER_LEN + index * ENTRY_LEN + field;
+        let data_start = HEADER_LEN + self.len * ENTRY_LEN;
+        let offset = data_start + read_u32(bytes, position) as usize;
+        let length = read_u32(bytes, position + 4) as usize;
+        &bytes[offset..offset + length]
+    }
+}
+
+fn read_u32(bytes: &[u8], position: usize) -> u32 {
+    u32::from_le_bytes(bytes[position..position + 4].try_into().unwrap())
+}
+
+fn to_u32(value: usize) -> u32 {
+    u32::try_from(value).expect("Indexed string tables cannot be larger than 4 GiB")
+}
+
+/// A [`TextProvider`] that reads its text from [`IndexedStringTable`]s, so that only the lines that are actually shown are turned into [`String`]s.
+/// Works like [`StringTableTextProvider`], but can keep the tables of several languages loaded at once without holding all of their text on the heap.
+#[derive(Debug, Default)]
+pub struct IndexedStringTableTextProvider {
+    base_language_table: Option<IndexedStringTable>,
+    translation_tables: HashMap<Language, IndexedStringTable>,
+    /// Set to `None` to select base language.
+    translation_language: Option<Language>,
+}
+
+impl IndexedStringTableTextProvider {
+    /// Creates a new [`IndexedStringTableTextProvider`] without any tables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the table for the base language, i.e. the language that the Yarn files are written in.
+    pub fn set_base_language_table(&mut self, table: IndexedStringTable) -> &mut Self {
+        self.base_language_table.replace(table);
+        self
+    }
+
+    /// Sets the table for a translation. Replaces any table previously set for the same language.
+    pub fn set_translation_table(
+        &mut self,
+        language: impl Into<Language>,
+        table: IndexedStringTable,
+    ) -> &mut Self {
+        self.translation_tables.insert(language.into(), table);
+        self
+    }
+
+    /// Removes the table for a translation, e.g. to release a memory-mapped file. Returns the table if there was one.
+    pub fn remove_translation_table(&mut self, language: &Language) -> Option<IndexedStringTable> {
+        self.translation_tables.remove(language)
+    }
+}
+
+impl TextProvider for IndexedStringTableTextProvider {
+    fn accept_line_hints(&mut self, _line_ids: &[LineId]) {
+        // no-op
+    }
+
+    fn get_text(&self, id: &LineId) -> Option<String> {
+        if let Some(language) = self.translation_language.as_ref() {
+            match self.translation_tables.get(language) {
+                None => error!(
+                    "Didn't find language {language} in translations, falling back to base language."
+                ),
+                Some(table) => match table.get(id) {
+                    Some(line) => return Some(line.to_owned()),
+                    None => error!("No translation found for line {id} in language {language}, falling back to base language."),
+                },
+            }
+        }
+        self.base_language_table
+            .as_ref()?
+            .get(id)
+            .map(ToOwned::to_owned)
+    }
+
+    fn set_language(&mut self, language_code: Option<Language>) {
+        self.translation_language = language_code;
+    }
+
+    fn get_language(&self) -> Option<Language> {
+        self.translation_language.clone()
+    }
+
+    fn are_lines_available(&self) -> bool {
+        match self.translation_language.as_ref() {
+            Some(language) => self.translation_tables.contains_key(language),
+            None => self
+                .base_language_table
+                .as_ref()
+                .is_some_and(|table| !table.is_empty()),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(lines: &[(&str, &str)]) -> StringTable {
+        lines
+            .iter()
+            .map(|(id, text)| (LineId(id.to_string()), text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn reads_encoded_lines() {
+        let bytes = IndexedStringTable::encode(&table(&[
+            ("line:b", "Bonjour"),
+            ("line:a", "Grüß dich"),
+            ("line:c", ""),
+        ]));
+        let table = IndexedStringTable::from_bytes(bytes).unwrap();
+
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.get(&LineId("line:a".to_owned())), Some("Grüß dich"));
+        assert_eq!(table.get(&LineId("line:b".to_owned())), Some("Bonjour"));
+        assert_eq!(table.get(&LineId("line:c".to_owned())), Some(""));
+        assert_eq!(table.get(&LineId("line:d".to_owned())), None);
+    }
+
+    #[test]
+    fn rejects_corrupt_data() {
+        assert!(matches!(
+            IndexedStringTable::from_bytes(b"nope".to_vec()),
+            Err(IndexedStringTableError::InvalidHeader)
+        ));
+        let mut bytes = IndexedStringTable::encode(&table(&[("line:a", "Hello")]));
+        bytes.truncate(bytes.len() - 1);
+        assert!(matches!(
+            IndexedStringTable::from_bytes(bytes),
+            Err(IndexedStringTableError::OutOfBounds { index: 0 })
+        ));
+    }
+
+    #[test]
+    fn provides_text_for_selected_language() {
+        let base = IndexedStringTable::encode(&table(&[("line:a", "Hello"), ("line:b", "Bye")]));
+        let german = IndexedStringTable::encode(&table(&[("line:a", "Hallo")]));
+        let mut text_provider = IndexedStringTableTextProvider::new();
+        text_provider
+            .set_base_language_table(IndexedStringTable::from_bytes(base).unwrap())
+            .set_translation_table("de", IndexedStringTable::from_bytes(german).unwrap());
+
+        assert!(text_provider.are_lines_available());
+        text_provider.set_language(Some("de".into()));
+        assert_eq!(
+            text_provider
+                .get_text(&LineId("line:a".to_owned()))
+                .unwrap(),
+            "Hallo"
+        );
+        assert_eq!(
+            text_provider
+                .get_text(&LineId("line:b".to_owned()))
+                .unwrap(),
+            "Bye"
+        );
+        text_provider.set_language(Some("fr".into()));
+        assert!(!text_provider.are_lines_available());
+    }
+}
@@ -0,0 +1,97 @@
+//! Allows executing custom instructions, see [`InstructionHandler`].
+
+use crate::prelude::*;
+use std::error::Error;
+use std::fmt::Debug;
+
+/// The error type returned by [`InstructionHandler::execute`].
+pub type InstructionHandlerError = Box<dyn Error + Send + Sync>;
+
+/// Executes custom instructions, i.e. instructions with an opcode of at least [`Instruction::FIRST_EXTENSION_OPCODE`].
+/// Register an implementation with [`Dialogue::register_instruction_handler`](crate::prelude::Dialogue::register_instruction_handler).
+///
+/// Yarn Spinner's compiler never emits such instructions by itself. They are meant for engine-specific features
+/// that are injected into a compiled [`Program`] by a custom compiler pass, e.g. by editing the instructions of a node
+/// in the program returned by the compiler before handing it to the [`Dialogue`](crate::prelude::Dialogue).
+pub trait InstructionHandler: Debug + Send + Sync {
+    /// Runs the given instruction. The [`InstructionContext`] gives access to the VM's stack and variables.
+    ///
+    /// After this returns, the dialogue continues with the next instruction unless [`InstructionContext::wait_for_continue`] was called.
+    /// Returning an error aborts the current call to [`Dialogue::continue_`](crate::prelude::Dialogue::continue_) with [`DialogueError::InstructionHandlerError`].
+    fn execute(
+        &mut self,
+        instruction: &Instruction,
+        context: &mut InstructionContext,
+    ) -> std::result::Result<(), InstructionHandlerError>;
+}
+
+/// The part of the VM's state that an [`InstructionHandler`] may read and modify.
+#[derive(Debug)]
+pub struct InstructionContext<'a> {
+    node_name: &'a str,
+    stack: &'a mut Vec<InternalValue>,
+    variable_storage: &'a mut dyn VariableStorage,
+    events: &'a mut Vec<DialogueEvent>,
+    waiting_for_continue: bool,
+}
+
+impl<'a> InstructionContext<'a> {
+    pub(crate) fn new(
+        node_name: &'a str,
+        stack: &'a mut Vec<InternalValue>,
+        variable_storage: &'a mut dyn VariableStorage,
+        events: &'a mut Vec<DialogueEvent>,
+    ) -> Self {
+        Self {
+            node_name,
+            stack,
+            variable_storage,
+            events,
+            waiting_for_continue: false,
+        }
+    }
+
+    /// The name of the node containing the instruction.
+    pub fn node_name(&self) -> &str {
+        self.node_name
+    }
+
+    /// Pushes a value onto the VM's stack, e.g. as the result of a custom expression.
+    pub fn push(&mut self, value: impl Into<YarnValue>) -> &mut Self {
+        self.stack.push(value.into().into());
+        self
+    }
+
+    /// Pops the topmost value off the VM's stack. Returns `None` if the stack is empty.
+    pub fn pop(&mut self) -> Option<YarnValue> {
+        self.stack.pop().map(Into::into)
+    }
+
+    /// Returns the topmost value of the VM's stack without removing it.
+    pub fn peek(&self) -> Option<YarnValue> {
+        self.stack.last().cloned().map(Into::into)
+    }
+
+    /// The [`VariableStorage`] of the running [`Dialogue`](crate::prelude::Dialogue).
+    pub fn variable_storage(&mut self) -> &mut dyn VariableStorage {
+        self.variable_storage
+    }
+
+    /// Adds an event to the ones returned by the current call to [`Dialogue::continue_`](crate::prelude::Dialogue::continue_),
+    /// e.g. a [`DialogueEvent::Command`].
+    pub fn emit(&mut self, event: DialogueEvent) -> &mut Self {
+        self.events.push(event);
+        self
+    }
+
+    /// Makes the dialogue wait after this instruction until [`Dialogue::continue_`](crate::prelude::Dialogue::continue_) is called again,
+    /// the same way it waits after delivering a line or command.
+    pub fn wait_for_continue(&mut self) -> &mut Self {
+        self.waiting_for_continue = true;
+        self
+    }
+
+    pub(crate) fn is_waiting_for_continue(&self) -> bool {
+        self.waiting_for_continue
+    }
+}
@@ -1,11 +1,12 @@
 #[cfg(any(feature = "bevy", feature = "serde"))]
 use crate::prelude::*;
 use core::fmt::Display;
-use icu_locid::LanguageIdentifier;
+use core::str::FromStr;
+use icu_locid::{LanguageIdentifier, ParserError};
 
 /// IETF BCP 47 code.
 /// The default is "en-US".
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[non_exhaustive]
 pub struct Language(pub(crate) LanguageIdentifier);
@@ -23,6 +24,15 @@ impl Display for Language {
     }
 }
 
+impl FromStr for Language {
+    type Err = ParserError;
+
+    /// Fallible version of [`Language::new`].
+    fn from_str(language: &str) -> Result<Self, Self::Err> {
+        language.parse().map(Self)
+    }
+}
+
 impl Default for Language {
     fn default() -> Self {
         "en-US".into()
@@ -4,18 +4,50 @@
 //! - If you're a game developer, you'll want to use a crate that is already designed for your game engine of choice,
 //! such as [`bevy_yarnspinner`](https://crates.io/crates/bevy_yarnspinner) for the [Bevy engine](https://bevyengine.org/).
 //! - If you wish to write an adapter crate for an engine yourself, use the [`yarnspinner`](https://crates.io/crates/yarnspinner) crate.
+//!
+//! With the `tracing` feature, the [`Dialogue`](prelude::Dialogue) emits a `yarn_node` span for every node it runs,
+//! and events for the lines it delivers, the commands it dispatches and the errors it stops with.
+//! These are recorded by whatever [`tracing`](https://docs.rs/tracing) subscriber your game already uses.
 
 #![warn(missing_docs, missing_debug_implementations)]
 mod analyser;
+mod breakpoint;
 mod command;
+mod composite_variable_storage;
+mod copy_on_write_variable_storage;
 mod dialogue;
+mod dialogue_driver;
+mod dialogue_events;
+mod dialogue_history;
+mod dialogue_metrics;
 mod dialogue_option;
+mod dialogue_recording;
+mod dialogue_snapshot;
+#[cfg(feature = "futures")]
+mod dialogue_stream;
+mod dialogue_sync;
+mod dirty_tracking_variable_storage;
+mod entry_point;
 mod events;
+mod fallback_text_provider;
+mod indexed_string_table;
+mod instruction_handler;
 mod language;
 mod line;
+mod line_breaking;
 pub mod markup;
+mod options_timeout;
 mod pluralization;
+mod random;
+mod saliency;
+mod source_location;
+#[cfg(feature = "sqlite")]
+mod sqlite_variable_storage;
+mod substitution_providers;
 mod text_provider;
+mod trigger;
+mod typed_variable_storage;
+mod variable_snapshot;
 mod variable_storage;
 mod virtual_machine;
 
@@ -23,18 +55,45 @@ pub use dialogue::Result;
 
 pub mod prelude {
     //! Everything you need to get starting using the Yarn Spinner runtime.
+    #[cfg(feature = "futures")]
+    pub use crate::dialogue_stream::*;
+    #[cfg(feature = "sqlite")]
+    pub use crate::sqlite_variable_storage::*;
     pub use crate::{
         analyser::*,
+        breakpoint::*,
         command::*,
+        composite_variable_storage::*,
+        copy_on_write_variable_storage::*,
         dialogue::{Dialogue, DialogueError},
+        dialogue_driver::*,
+        dialogue_events::*,
+        dialogue_history::*,
+        dialogue_metrics::*,
         dialogue_option::*,
+        dialogue_recording::*,
+        dialogue_snapshot::*,
+        dialogue_sync::*,
+        dirty_tracking_variable_storage::*,
         events::*,
+        fallback_text_provider::*,
+        indexed_string_table::*,
+        instruction_handler::*,
         language::*,
         line::*,
         markup::MarkupParseError,
+        options_timeout::*,
+        saliency::*,
+        source_location::SourceLocation,
         text_provider::*,
+        trigger::*,
+        typed_variable_storage::*,
+        variable_snapshot::*,
         variable_storage::*,
     };
-    pub(crate) use crate::{pluralization::*, virtual_machine::*};
+    pub(crate) use crate::{
+        entry_point::*, line_breaking::*, pluralization::*, random::*, source_location::SourceMap,
+        substitution_providers::*, virtual_machine::*,
+    };
     pub(crate) use yarnspinner_core::prelude::*;
 }
@@ -3,10 +3,14 @@
 //! ## Implementation notes
 //! Introduced `LineId` newtype for better type safety
 
+use crate::markup::grapheme_boundaries;
 use crate::markup::{
     MarkupAttribute, MarkupValue, CHARACTER_ATTRIBUTE, CHARACTER_ATTRIBUTE_NAME_PROPERTY,
 };
 use crate::prelude::*;
+use std::cmp::Reverse;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
 
 /// A line of dialogue, sent from the [`Dialogue`] to the game.
 ///
@@ -22,10 +26,10 @@ use crate::prelude::*;
 /// ## Implementation Notes
 ///
 /// `MarkupParseResult` and `ExpandSubstitutions` were merged into this because we don't require consumers to manually fetch from string tables.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Hash)]
 #[cfg_attr(feature = "bevy", derive(Reflect))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
 #[cfg_attr(
     all(feature = "bevy", feature = "serde"),
     reflect(Serialize, Deserialize)
@@ -35,8 +39,30 @@ pub struct Line {
     pub id: LineId,
     /// The original text, with all parsed markers removed.
     pub text: String,
+    /// The text before markup was parsed, with substitutions applied.
+    /// Markers and escaped brackets are kept exactly as written, e.g. `Take the \[Sword\] [b]now[/b]`,
+    /// for games that render markup themselves or need to display the line losslessly.
+    /// Lines split off or edited from another line, e.g. by [`Line::delete_range`], keep the raw text of the original line.
+    pub raw_text: String,
+    /// Why the markup of this line could not be parsed. Only set if [`Dialogue::set_lenient_markup`] is enabled,
+    /// in which case [`Line::text`] is the unparsed [`Line::raw_text`] and the line has no attributes.
+    pub markup_error: Option<MarkupParseError>,
     /// The list of [`MarkupAttribute`] in this parse result.
     pub attributes: Vec<MarkupAttribute>,
+    /// The metadata of the line, i.e. the hashtags after it other than `#line:`, without the `#`.
+    /// For `Stop right there! #shout #line:guard_1`, this is `["shout"]`. Use [`Line::has_metadata`] to check for a tag.
+    /// Only available for lines whose metadata was added with [`Dialogue::add_line_metadata`].
+    ///
+    /// ## Implementation notes
+    ///
+    /// Not part of the original implementation, which leaves looking up the metadata of a line to the game.
+    pub metadata: Vec<String>,
+    /// The name of the node the line was delivered from.
+    /// Together with [`Line::source_location`], lets error messages point to the line in the Yarn files.
+    pub node_name: String,
+    /// The place in the Yarn source code the line was compiled from.
+    /// Only available for nodes whose positions were added with [`Dialogue::add_source_positions`].
+    pub source_location: Option<SourceLocation>,
 }
 
 impl Line {
@@ -49,6 +75,11 @@ impl Line {
         self.attributes.iter().find(|attr| attr.name == name)
     }
 
+    /// Returns `true` if the line was tagged with the given metadata, e.g. `line.has_metadata("shout")` for a line ending in `#shout`.
+    pub fn has_metadata(&self, tag: &str) -> bool {
+        self.metadata.iter().any(|metadata| metadata == tag)
+    }
+
     /// The name of the character, if present.
     /// ## Examples
     /// When there is a name:
@@ -60,6 +91,8 @@ impl Line {
     /// # let line = Line {
     /// #    id: "line".into(),
     /// #    text: "Alice: Hello! How are you today?".to_owned(),
+    /// #    raw_text: "Alice: Hello! How are you today?".to_owned(),
+    /// #    markup_error: None,
     /// #    attributes: vec![MarkupAttribute {
     /// #        name: "character".to_owned(),
     /// #        position: 0,
@@ -67,6 +100,9 @@ impl Line {
     /// #        properties: HashMap::from([("name".to_owned(), "Alice".into())]),
     /// #        source_position: 0,
     /// #    }],
+    /// #    metadata: vec![],
+    /// #    node_name: "Start".to_owned(),
+    /// #    source_location: None,
     /// # };
     /// assert_eq!("Alice: Hello! How are you today?", line.text);
     /// assert_eq!(Some("Alice"), line.character_name());
@@ -81,7 +117,12 @@ impl Line {
     /// # let line = Line {
     /// #    id: "line".into(),
     /// #    text: "Great, thanks".to_owned(),
+    /// #    raw_text: "Great, thanks".to_owned(),
+    /// #    markup_error: None,
     /// #    attributes: vec![],
+    /// #    metadata: vec![],
+    /// #    node_name: "Start".to_owned(),
+    /// #    source_location: None,
     /// # };
     /// assert_eq!("Great, thanks", line.text);
     /// assert!(line.character_name().is_none());
@@ -112,6 +153,8 @@ impl Line {
     /// # let line = Line {
     /// #    id: "line".into(),
     /// #    text: "Alice: Hello! How are you today?".to_owned(),
+    /// #    raw_text: "Alice: Hello! How are you today?".to_owned(),
+    /// #    markup_error: None,
     /// #    attributes: vec![MarkupAttribute {
     /// #        name: "character".to_owned(),
     /// #        position: 0,
@@ -119,6 +162,9 @@ impl Line {
     /// #        properties: HashMap::from([("name".to_owned(), "Alice".into())]),
     /// #        source_position: 0,
     /// #    }],
+    /// #    metadata: vec![],
+    /// #    node_name: "Start".to_owned(),
+    /// #    source_location: None,
     /// # };
     /// assert_eq!("Alice: Hello! How are you today?", line.text);
     /// assert_eq!("Hello! How are you today?", &line.text_without_character_name());
@@ -133,7 +179,12 @@ impl Line {
     /// # let line = Line {
     /// #    id: "line".into(),
     /// #    text: "Great, thanks".to_owned(),
+    /// #    raw_text: "Great, thanks".to_owned(),
+    /// #    markup_error: None,
     /// #    attributes: vec![],
+    /// #    metadata: vec![],
+    /// #    node_name: "Start".to_owned(),
+    /// #    source_location: None,
     /// # };
     /// assert_eq!("Great, thanks", line.text);
     /// assert_eq!("Great, thanks", &line.text_without_character_name());
@@ -146,14 +197,78 @@ impl Line {
     }
 
     /// Returns the substring of [`Line::text`] covered by the passed `attribute`s [`MarkupAttribute::position`] and [`MarkupAttribute::length`] fields.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the attribute extends past the end of [`Line::text`]. Use [`MarkupAttribute::byte_range_in`] to handle that case.
     pub fn text_for_attribute(&self, attribute: &MarkupAttribute) -> &str {
+        let range = attribute.byte_range_in(&self.text);
         assert!(
-            self.text.len() <= attribute.position + attribute.length,
+            range.is_some(),
             "Attribute \"{attribute}\" represents a range not representable by this text: \"{}\". \
         Does this MarkupAttribute belong to this MarkupParseResult?",
             self.text
         );
-        &self.text[attribute.position..attribute.position + attribute.length]
+        &self.text[range.unwrap()]
+    }
+
+    /// Returns each attribute of this line together with the range of UTF-8 bytes of [`Line::text`] it covers,
+    /// so that the text can be sliced with `&line.text[range]`.
+    /// Attributes that extend past the end of the text are left out.
+    ///
+    /// Unlike calling [`MarkupAttribute::byte_range_in`] for each attribute, this walks the text only once.
+    pub fn attribute_byte_ranges(&self) -> Vec<(&MarkupAttribute, Range<usize>)> {
+        let boundaries: Vec<_> = grapheme_boundaries(&self.text).collect();
+        self.attributes
+            .iter()
+            .filter_map(|attribute| {
+                let start = *boundaries.get(attribute.position)?;
+                let end = *boundaries.get(attribute.position + attribute.length)?;
+                Some((attribute, start..end))
+            })
+            .collect()
+    }
+
+    /// Returns the attributes that cover every character in `range`, where characters are counted like [`MarkupAttribute::position`].
+    /// Pass a range of a single character, e.g. `4..5`, to find the attributes that apply to the character revealed by a typewriter effect.
+    /// An empty range, e.g. `4..4`, also finds the attributes without a length positioned there.
+    pub fn attributes_covering(
+        &self,
+        range: Range<usize>,
+    ) -> impl Iterator<Item = &MarkupAttribute> + '_ {
+        self.attributes.iter().filter(move |attribute| {
+            attribute.position <= range.start && range.end <= attribute.position + attribute.length
+        })
+    }
+
+    /// Returns the attributes of this line in document order together with their nesting depth.
+    ///
+    /// Attributes are ordered by their start, and attributes starting at the same character by their length, longest first,
+    /// so that enclosing attributes come before the attributes they contain.
+    /// The depth of an attribute is the number of attributes before it that contain it, so top-level attributes have a depth of 0.
+    /// Attributes that overlap without one containing the other do not count towards each other's depth.
+    ///
+    /// ## Example
+    ///
+    /// For `[b]Take [i]the [item/][/i]![/b] [u]Go[/u]`, this returns `b` at depth 0, `i` at depth 1, `item` at depth 2 and `u` at depth 0.
+    pub fn attributes_with_depth(&self) -> Vec<(usize, &MarkupAttribute)> {
+        let mut attributes: Vec<_> = self.attributes.iter().collect();
+        attributes.sort_by_key(|attribute| (attribute.position, Reverse(attribute.length)));
+        attributes
+            .iter()
+            .enumerate()
+            .map(|(index, attribute)| {
+                let end = attribute.position + attribute.length;
+                let depth = attributes[..index]
+                    .iter()
+                    .filter(|enclosing| {
+                        enclosing.position <= attribute.position
+                            && end <= enclosing.position + enclosing.length
+                    })
+                    .count();
+                (depth, *attribute)
+            })
+            .collect()
     }
 
     /// Deletes an attribute from this markup.
@@ -199,14 +314,22 @@ impl Line {
             return Line {
                 id: self.id.clone(),
                 text: self.text.to_string(),
+                raw_text: self.raw_text.clone(),
+                markup_error: self.markup_error.clone(),
                 attributes,
+                metadata: self.metadata.clone(),
+                node_name: self.node_name.clone(),
+                source_location: self.source_location.clone(),
             };
         }
         let deletion_start = attribute_to_delete.position;
         let deletion_end = attribute_to_delete.position + attribute_to_delete.length;
         let edited_substring = {
             let mut text = self.text.to_string();
-            text.replace_range(deletion_start..deletion_end, "");
+            let byte_range = attribute_to_delete.byte_range_in(&self.text).expect(
+                "Attribute to delete represents a range not representable by this line's text",
+            );
+            text.replace_range(byte_range, "");
             text
         };
         let attributes = self
@@ -274,7 +397,90 @@ impl Line {
         Line {
             id: self.id.clone(),
             text: edited_substring,
+            raw_text: self.raw_text.clone(),
+            markup_error: self.markup_error.clone(),
+            attributes,
+            metadata: self.metadata.clone(),
+            node_name: self.node_name.clone(),
+            source_location: self.source_location.clone(),
+        }
+    }
+    /// Replaces the text covered by an attribute with `replacement`, e.g. to substitute the position of an `[item/]` marker with the name of an item.
+    /// The replaced attribute is kept and covers `replacement` afterwards. The other attributes in this markup are updated as follows:
+    ///
+    /// - Attributes that end before or where the replaced attribute starts are unmodified.
+    /// - Attributes that contain the replaced attribute, including attributes with the same position and length, grow or shrink with it.
+    /// Attributes with a length that start where a replaced attribute without a length is positioned count as containing it.
+    /// - Attributes that start after the replaced attribute have their start point adjusted to account for the replaced text.
+    /// - Attributes that start before the replaced attribute and end inside it are truncated to end where it starts.
+    /// - Attributes that start inside the replaced attribute and end outside it have their start moved to the end of the replacement.
+    /// - Attributes with a length that start and end within the replaced attribute are deleted.
+    /// Attributes without a length within it are moved to its start.
+    ///
+    /// This method does not modify the current object. A new [`Line`] is returned.
+    ///
+    /// ## Panics
+    /// Panics if `attribute_to_replace` is not an attribute of this [`Line::attribute`].
+    pub fn replace_range(&self, attribute_to_replace: &MarkupAttribute, replacement: &str) -> Self {
+        assert!(
+            self.attributes.contains(attribute_to_replace),
+            "Attribute to replace is not an attribute of this line"
+        );
+        let byte_range = attribute_to_replace.byte_range_in(&self.text).expect(
+            "Attribute to replace represents a range not representable by this line's text",
+        );
+        let mut text = self.text.clone();
+        text.replace_range(byte_range, replacement);
+
+        let replaced_start = attribute_to_replace.position;
+        let replaced_end = replaced_start + attribute_to_replace.length;
+        let replacement_length = replacement.graphemes(true).count();
+        let replacement_end = replaced_start + replacement_length;
+        let mut has_replaced = false;
+        let attributes = self
+            .attributes
+            .iter()
+            .filter_map(|attribute| {
+                let mut attribute = attribute.clone();
+                let start = attribute.position;
+                let end = attribute.position + attribute.length;
+                if !has_replaced && attribute == *attribute_to_replace {
+                    has_replaced = true;
+                    attribute.length = replacement_length;
+                } else if end <= replaced_start {
+                    // Entirely before the replaced text
+                } else if start <= replaced_start && end >= replaced_end {
+                    // Contains the replaced text
+                    attribute.length =
+                        attribute.length - attribute_to_replace.length + replacement_length;
+                } else if start >= replaced_end {
+                    // Entirely after the replaced text
+                    attribute.position = start - replaced_end + replacement_end;
+                } else if start < replaced_start {
+                    // Ends inside the replaced text
+                    attribute.length = replaced_start - start;
+                } else if end > replaced_end {
+                    // Starts inside the replaced text
+                    attribute.position = replacement_end;
+                    attribute.length = end - replaced_end;
+                } else if attribute.length == 0 {
+                    attribute.position = replaced_start;
+                } else {
+                    // Entirely inside the replaced text
+                    return None;
+                }
+                Some(attribute)
+            })
+            .collect();
+        Line {
+            id: self.id.clone(),
+            text,
+            raw_text: self.raw_text.clone(),
+            markup_error: self.markup_error.clone(),
             attributes,
+            metadata: self.metadata.clone(),
+            node_name: self.node_name.clone(),
+            source_location: self.source_location.clone(),
         }
     }
 }
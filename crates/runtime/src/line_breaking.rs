@@ -0,0 +1,355 @@
+//! Lets views split a [`Line`] that does not fit into their text box,
+//! see [`Line::split_to_fit`], [`Line::paginate`] and [`Dialogue::set_line_pagination`].
+
+use crate::markup::CHARACTER_ATTRIBUTE;
+use crate::prelude::*;
+use std::collections::HashSet;
+use std::fmt::{Debug, Formatter};
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+impl Line {
+    /// Splits this line into the longest beginning that still fits into a text box and the rest of the line, if any.
+    ///
+    /// The host measures the text: `fits` is called with candidate texts and returns whether they can be displayed in one go,
+    /// e.g. by laying them out with the font and box size used by the view.
+    /// The line is preferably split between words. If not even the first word fits, it is split between graphemes instead,
+    /// and if not even a single grapheme fits, that grapheme is used anyway so that the line is guaranteed to get shorter.
+    /// Whitespace at the split is dropped.
+    ///
+    /// [`Line::attributes`] are split alongside the text, so both returned lines are valid parse results on their own:
+    /// attributes spanning the split are clipped to the part they cover in each line and positions are relative to the new text.
+    /// Since the returned rest is a regular [`Line`] with the same [`Line::id`], a long line can be paginated by calling this repeatedly.
+    ///
+    /// Note that the `character` attribute is treated like any other attribute, so you probably want to call this on a line where it was removed
+    /// with [`Line::delete_range`] if your view displays the character's name separately.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// # use yarnspinner_core::prelude::*;
+    /// # use yarnspinner_runtime::prelude::*;
+    /// # let line = Line {
+    /// #    id: "line".into(),
+    /// #    text: "The quick brown fox".to_owned(),
+    /// #    raw_text: "The quick brown fox".to_owned(),
+    /// #    markup_error: None,
+    /// #    attributes: vec![],
+    /// #    metadata: vec![],
+    /// #    node_name: "Start".to_owned(),
+    /// #    source_location: None,
+    /// # };
+    /// let (page, rest) = line.split_to_fit(|text| text.chars().count() <= 10);
+    /// assert_eq!("The quick", page.text);
+    /// assert_eq!("brown fox", rest.unwrap().text);
+    /// ```
+    pub fn split_to_fit(&self, mut fits: impl FnMut(&str) -> bool) -> (Line, Option<Line>) {
+        if fits(&self.text) {
+            return (self.clone(), None);
+        }
+        let graphemes: Vec<(usize, &str)> = self.text.grapheme_indices(true).collect();
+        let grapheme_count = graphemes.len();
+        let text_until = |end: usize| -> String {
+            graphemes[..end]
+                .iter()
+                .map(|(_, grapheme)| *grapheme)
+                .collect()
+        };
+        let is_whitespace = |index: usize| {
+            graphemes
+                .get(index)
+                .is_some_and(|(_, grapheme)| grapheme.chars().all(char::is_whitespace))
+        };
+        let starts_word = |index: usize| {
+            graphemes
+                .get(index)
+                .and_then(|(_, grapheme)| grapheme.chars().next())
+                .is_some_and(char::is_alphanumeric)
+        };
+
+        // A line may be broken at a word boundary if the previous grapheme is visible
+        // and the next one is either whitespace or the start of a new word.
+        // The latter allows breaking after hyphens and between characters of scripts without spaces.
+        let word_boundaries: HashSet<usize> = self
+            .text
+            .split_word_bound_indices()
+            .map(|(byte_index, _)| byte_index)
+            .collect();
+        let mut word_breaks = (1..grapheme_count).filter(|&index| {
+            word_boundaries.contains(&graphemes[index].0)
+                && !is_whitespace(index - 1)
+                && (is_whitespace(index) || starts_word(index))
+        });
+        let longest_fitting = |candidates: &mut dyn Iterator<Item = usize>,
+                               fits: &mut dyn FnMut(&str) -> bool| {
+            candidates.take_while(|&end| fits(&text_until(end))).last()
+        };
+        let page_end = longest_fitting(&mut word_breaks, &mut fits)
+            .or_else(|| longest_fitting(&mut (1..grapheme_count), &mut fits))
+            .unwrap_or(1);
+
+        let rest_start = (page_end..grapheme_count)
+            .find(|&index| !is_whitespace(index))
+            .unwrap_or(grapheme_count);
+        let page = self.sub_line(0..page_end, 0..rest_start);
+        let rest = (rest_start < grapheme_count)
+            .then(|| self.sub_line(rest_start..grapheme_count, rest_start..grapheme_count + 1));
+        (page, rest)
+    }
+
+    /// Splits this line into as many pages as needed for each of them to fit into a text box.
+    /// Every page is a valid parse result on its own, with its attributes clipped and moved to match its text.
+    /// See [`Line::split_to_fit`] for how `fits` is used and where the line is split.
+    ///
+    /// If the line starts with a `character` attribute, e.g. `Alice: `, it is not part of the measured text and is repeated at the start of every page,
+    /// so that [`Line::character_name`] and [`Line::text_without_character_name`] keep working on each of them.
+    ///
+    /// Always returns at least one page.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// # use yarnspinner_core::prelude::*;
+    /// # use yarnspinner_runtime::markup::*;
+    /// # use yarnspinner_runtime::prelude::*;
+    /// # let line = Line {
+    /// #    id: "line".into(),
+    /// #    text: "Alice: Hello! How are you today?".to_owned(),
+    /// #    raw_text: "Alice: Hello! How are you today?".to_owned(),
+    /// #    markup_error: None,
+    /// #    attributes: vec![MarkupAttribute {
+    /// #        name: "character".to_owned(),
+    /// #        position: 0,
+    /// #        length: 7,
+    /// #        properties: HashMap::from([("name".to_owned(), "Alice".into())]),
+    /// #        source_position: 0,
+    /// #    }],
+    /// #    metadata: vec![],
+    /// #    node_name: "Start".to_owned(),
+    /// #    source_location: None,
+    /// # };
+    /// let pages = line.paginate(|text| text.chars().count() <= 12);
+    /// let texts: Vec<_> = pages.iter().map(|page| page.text.as_str()).collect();
+    /// assert_eq!(vec!["Alice: Hello! How", "Alice: are you", "Alice: today?"], texts);
+    /// assert!(pages.iter().all(|page| page.character_name() == Some("Alice")));
+    /// ```
+    pub fn paginate(&self, mut fits: impl FnMut(&str) -> bool) -> Vec<Line> {
+        let character_attribute = self
+            .attribute(CHARACTER_ATTRIBUTE)
+            .filter(|attribute| attribute.position == 0 && attribute.length > 0);
+        let mut remaining = Some(match character_attribute {
+            Some(attribute) => self.delete_range(attribute),
+            None => self.clone(),
+        });
+        let mut pages = Vec::new();
+        while let Some(line) = remaining {
+            let (page, rest) = line.split_to_fit(&mut fits);
+            pages.push(page);
+            remaining = rest;
+        }
+
+        let Some(character_attribute) = character_attribute else {
+            return pages;
+        };
+        let prefix: String = self
+            .text
+            .graphemes(true)
+            .take(character_attribute.length)
+            .collect();
+        pages
+            .into_iter()
+            .map(|mut page| {
+                page.text.insert_str(0, &prefix);
+                for attribute in &mut page.attributes {
+                    attribute.position += character_attribute.length;
+                }
+                page.attributes.insert(0, character_attribute.clone());
+                page
+            })
+            .collect()
+    }
+
+    /// Creates a line containing only the graphemes in `text_range`.
+    /// Attributes with a length are clipped to `text_range`,
+    /// while attributes without one are kept if they are positioned in `zero_length_range`.
+    fn sub_line(&self, text_range: Range<usize>, zero_length_range: Range<usize>) -> Line {
+        let text = self
+            .text
+            .graphemes(true)
+            .skip(text_range.start)
+            .take(text_range.len())
+            .collect();
+        let attributes = self
+            .attributes
+            .iter()
+            .filter_map(|attribute| {
+                let start = attribute.position;
+                let end = attribute.position + attribute.length;
+                let mut attribute = attribute.clone();
+                if attribute.length == 0 {
+                    if !zero_length_range.contains(&start) {
+                        return None;
+                    }
+                    attribute.position =
+                        start.clamp(text_range.start, text_range.end) - text_range.start;
+                } else {
+                    let clipped_start = start.max(text_range.start);
+                    let clipped_end = end.min(text_range.end);
+                    if clipped_start >= clipped_end {
+                        return None;
+                    }
+                    attribute.position = clipped_start - text_range.start;
+                    attribute.length = clipped_end - clipped_start;
+                }
+                Some(attribute)
+            })
+            .collect();
+        Line {
+            id: self.id.clone(),
+            text,
+            raw_text: self.raw_text.clone(),
+            markup_error: self.markup_error.clone(),
+            attributes,
+            metadata: self.metadata.clone(),
+            node_name: self.node_name.clone(),
+            source_location: self.source_location.clone(),
+        }
+    }
+}
+
+/// The measurement passed to [`Dialogue::set_line_pagination`].
+pub(crate) struct PageMeasurement(Box<dyn Fn(&str) -> bool + Send + Sync>);
+
+impl PageMeasurement {
+    pub(crate) fn new(fits: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        Self(Box::new(fits))
+    }
+
+    pub(crate) fn fits(&self, text: &str) -> bool {
+        (self.0)(text)
+    }
+}
+
+impl Debug for PageMeasurement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("PageMeasurement")
+            .field(&"<function>")
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::{MarkupAttribute, MarkupValue};
+    use std::collections::HashMap;
+
+    fn attribute(name: &str, position: usize, length: usize) -> MarkupAttribute {
+        MarkupAttribute {
+            name: name.to_owned(),
+            position,
+            length,
+            properties: HashMap::new(),
+            source_position: 0,
+        }
+    }
+
+    fn new_line(text: &str, attributes: Vec<MarkupAttribute>) -> Line {
+        Line {
+            id: "line:test".into(),
+            text: text.to_owned(),
+            raw_text: text.to_owned(),
+            markup_error: None,
+            attributes,
+            metadata: vec![],
+            node_name: "Start".to_owned(),
+            source_location: None,
+        }
+    }
+
+    fn fits_graphemes(max: usize) -> impl FnMut(&str) -> bool {
+        move |text| text.graphemes(true).count() <= max
+    }
+
+    #[test]
+    fn keeps_lines_that_fit() {
+        let line = new_line("Hello there", vec![attribute("b", 0, 5)]);
+        let (page, rest) = line.split_to_fit(fits_graphemes(20));
+        assert_eq!(page, line);
+        assert!(rest.is_none());
+    }
+
+    #[test]
+    fn splits_between_words_and_clips_attributes() {
+        let line = new_line(
+            "Hello there, general Kenobi",
+            vec![
+                attribute("wave", 0, 5),
+                attribute("b", 6, 14),
+                attribute("pause", 13, 0),
+            ],
+        );
+        let (page, rest) = line.split_to_fit(fits_graphemes(13));
+        let rest = rest.unwrap();
+
+        assert_eq!(page.text, "Hello there,");
+        assert_eq!(
+            page.attributes,
+            vec![attribute("wave", 0, 5), attribute("b", 6, 6)]
+        );
+        assert_eq!(rest.text, "general Kenobi");
+        assert_eq!(
+            rest.attributes,
+            vec![attribute("b", 0, 7), attribute("pause", 0, 0)]
+        );
+        assert_eq!(rest.id, line.id);
+    }
+
+    #[test]
+    fn splits_between_graphemes_if_no_word_fits() {
+        let line = new_line("Supercalifragilistic", vec![]);
+        let (page, rest) = line.split_to_fit(fits_graphemes(5));
+        assert_eq!(page.text, "Super");
+        assert_eq!(rest.unwrap().text, "califragilistic");
+
+        let line = new_line("e\u{301}e\u{301}", vec![]);
+        let (page, rest) = line.split_to_fit(|_| false);
+        assert_eq!(page.text, "e\u{301}");
+        assert_eq!(rest.unwrap().text, "e\u{301}");
+    }
+
+    #[test]
+    fn paginates_lines_and_repeats_character_name() {
+        let mut character = attribute("character", 0, 5);
+        character
+            .properties
+            .insert("name".to_owned(), MarkupValue::String("Bob".to_owned()));
+        let line = new_line(
+            "Bob: one two three four",
+            vec![character.clone(), attribute("b", 9, 9)],
+        );
+        let pages = line.paginate(fits_graphemes(8));
+
+        let texts: Vec<_> = pages.iter().map(|page| page.text.as_str()).collect();
+        assert_eq!(texts, vec!["Bob: one two", "Bob: three", "Bob: four"]);
+        assert_eq!(
+            pages[0].attributes,
+            vec![character.clone(), attribute("b", 9, 3)]
+        );
+        assert_eq!(
+            pages[1].attributes,
+            vec![character.clone(), attribute("b", 5, 5)]
+        );
+        assert_eq!(pages[2].attributes, vec![character]);
+        assert!(pages
+            .iter()
+            .all(|page| page.character_name() == Some("Bob")));
+    }
+
+    #[test]
+    fn splits_text_without_spaces_between_characters() {
+        let line = new_line("こんにちは世界", vec![]);
+        let (page, rest) = line.split_to_fit(fits_graphemes(3));
+        assert_eq!(page.text.graphemes(true).count(), 3);
+        assert_eq!(format!("{}{}", page.text, rest.unwrap().text), line.text);
+    }
+}
@@ -6,15 +6,22 @@
 //! ```
 //! The parsing extracts the information that "Mae" and "Greg" are characters, that "shout" and "size" are attributes, and that "size" has a value of "12".
 mod attribute_marker_processor;
+mod button_glyphs;
 mod line_parser;
 mod markup_parse_error;
+mod markup_styles;
 mod parsed_markup;
+mod rich_text;
 
+pub use self::attribute_marker_processor::AttributeMarkerProcessor;
 pub use self::line_parser::{
-    CHARACTER_ATTRIBUTE, CHARACTER_ATTRIBUTE_NAME_PROPERTY, TRIM_WHITESPACE_PROPERTY,
+    MarkupWhitespaceTrimming, CHARACTER_ATTRIBUTE, CHARACTER_ATTRIBUTE_NAME_PROPERTY,
+    TRIM_WHITESPACE_PROPERTY,
 };
 pub(crate) use self::{attribute_marker_processor::*, line_parser::*};
-pub use self::{markup_parse_error::*, parsed_markup::*};
+pub use self::{
+    button_glyphs::*, markup_parse_error::*, markup_styles::*, parsed_markup::*, rich_text::*,
+};
 
 #[cfg(test)]
 mod tests {
@@ -95,6 +102,67 @@ mod tests {
         assert_eq!(1, trimmed_markup.attributes[3].length);
     }
 
+    #[test]
+    fn test_attribute_replacement() {
+        let markup = line_parser()
+            .parse_markup("[b]Take [i]the [item/][/i]![/b] [u]Go[/u]")
+            .unwrap();
+        let line = markup.as_line();
+        assert_eq!("Take the ! Go", line.text);
+
+        let item = line.attribute("item").unwrap();
+        let replaced = line.replace_range(item, "Sword");
+
+        assert_eq!("Take the Sword! Go", replaced.text);
+        for (name, expected_text) in [
+            ("b", "Take the Sword!"),
+            ("i", "the "),
+            ("item", "Sword"),
+            ("u", "Go"),
+        ] {
+            let attribute = replaced.attribute(name).unwrap();
+            assert_eq!(expected_text, replaced.text_for_attribute(attribute));
+        }
+
+        let bold = replaced.attribute("b").unwrap();
+        let replaced = replaced.replace_range(bold, "Run");
+        assert_eq!("Run Go", replaced.text);
+        assert!(replaced.attribute("i").is_none());
+        assert_eq!(4, replaced.attribute("u").unwrap().position);
+    }
+
+    #[test]
+    fn test_attribute_overlap_queries() {
+        let markup = line_parser()
+            .parse_markup("[b]Take [i]the [item/][/i]![/b] [u]Go[/u]")
+            .unwrap();
+        let line = markup.as_line();
+        assert_eq!("Take the ! Go", line.text);
+
+        let names = |attributes: Vec<&MarkupAttribute>| -> Vec<String> {
+            let mut names: Vec<_> = attributes.into_iter().map(|a| a.name.clone()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            vec!["b", "i"],
+            names(line.attributes_covering(5..6).collect())
+        );
+        assert_eq!(vec!["b"], names(line.attributes_covering(0..9).collect()));
+        assert_eq!(
+            vec!["b", "i", "item"],
+            names(line.attributes_covering(9..9).collect())
+        );
+        assert!(line.attributes_covering(9..12).next().is_none());
+
+        let depths: Vec<_> = line
+            .attributes_with_depth()
+            .into_iter()
+            .map(|(depth, attribute)| (attribute.name.as_str(), depth))
+            .collect();
+        assert_eq!(vec![("b", 0), ("i", 1), ("item", 2), ("u", 0)], depths);
+    }
+
     #[test]
     fn test_finding_attributes() {
         let line = "A [b]B[/b] [b]C[/b]";
@@ -137,6 +205,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_errors_point_to_the_offending_character() {
+        let error = line_parser()
+            .parse_markup("Hi [a x=\"1\\q\"]there[/a]")
+            .unwrap_err();
+        assert_eq!(
+            MarkupParseError::InvalidEscapeSequence {
+                input: "Hi [a x=\"1\\q\"]there[/a]".to_owned(),
+                column: 11,
+                found: 'q',
+            },
+            error
+        );
+
+        let error = line_parser().parse_markup("Hi [a]there[/b]").unwrap_err();
+        assert_eq!("Hi [a]there[/b]", error.input());
+        assert_eq!(11, error.column());
+
+        let error = line_parser().parse_markup("Hi [!]").unwrap_err();
+        assert!(matches!(
+            error,
+            MarkupParseError::NoIdentifierFound {
+                column: 4,
+                found: '!',
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_markup_shortcut_property_parsing() {
         let line = "[a=1]s[/a]";
@@ -250,6 +347,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_whitespace_trimming_modes() {
+        for (input, yarn_spinner, preserve_all, collapse) in [
+            ("A [a/] B", "A B", "A  B", "A B"),
+            ("A [a/]   B", "A   B", "A    B", "A B"),
+            ("[pause/] Hello", "Hello", " Hello", "Hello"),
+            ("A [nomarkup/] B", "A  B", "A  B", "A B"),
+            (
+                "Hello [b] world[/b]",
+                "Hello  world",
+                "Hello  world",
+                "Hello world",
+            ),
+            ("A [a trimwhitespace=false/] B", "A  B", "A  B", "A  B"),
+            ("A [a trimwhitespace=true/] B", "A B", "A B", "A B"),
+        ] {
+            for (whitespace_trimming, expected_text) in [
+                (MarkupWhitespaceTrimming::YarnSpinner, yarn_spinner),
+                (MarkupWhitespaceTrimming::PreserveAll, preserve_all),
+                (MarkupWhitespaceTrimming::Collapse, collapse),
+            ] {
+                let mut line_parser = line_parser();
+                line_parser.set_whitespace_trimming(whitespace_trimming);
+                let markup = line_parser.parse_markup(input).unwrap();
+
+                assert_eq!(
+                    expected_text, markup.text,
+                    "{input:?} with {whitespace_trimming:?}"
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_implicit_character_attribute_parsing() {
         for input in [
@@ -276,6 +406,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_character_attribute_with_marked_up_multibyte_name() {
+        let markup = line_parser().parse_markup("[b]Zoë[/b]: Wow!").unwrap();
+        let line = markup.as_line();
+
+        assert_eq!("Zoë: Wow!", line.text);
+        assert_eq!(Some("Zoë"), line.character_name());
+        assert_eq!("Wow!", line.text_without_character_name());
+
+        let attribute = line.attribute("character").unwrap();
+        assert_eq!(0, attribute.position);
+        assert_eq!(5, attribute.length);
+        assert_eq!("Zoë: ", line.text_for_attribute(attribute));
+    }
+
+    #[test]
+    fn test_attribute_byte_ranges() {
+        let markup = line_parser()
+            .parse_markup("Zoë: [b]Café[/b] or 🇯🇵 [i]tea[/i]")
+            .unwrap();
+        let line = markup.as_line();
+
+        let slices: Vec<_> = line
+            .attribute_byte_ranges()
+            .into_iter()
+            .map(|(attribute, range)| (attribute.name.as_str(), &line.text[range]))
+            .collect();
+        assert_eq!(
+            vec![("b", "Café"), ("i", "tea"), ("character", "Zoë: ")],
+            slices
+        );
+        for attribute in &line.attributes {
+            let range = attribute.byte_range_in(&line.text).unwrap();
+            assert_eq!(line.text_for_attribute(attribute), &line.text[range]);
+        }
+    }
+
     #[test]
     fn test_no_markup_mode_parsing() {
         let line = "S [a]S[/a] [nomarkup][a]S;][/a][/nomarkup]";
@@ -308,6 +475,68 @@ mod tests {
         assert_eq!(18, markup.attributes[0].length);
     }
 
+    #[test]
+    fn test_literal_brackets_in_no_markup_mode_and_escapes() {
+        let line =
+            r"Take the \[Sword\] ¯\_(ツ)_/¯ [nomarkup][b]★\[/nomarkup][/nomarkup] [a]!\][/a]";
+        let markup = line_parser().parse_markup(line).unwrap();
+
+        assert_eq!(
+            r"Take the [Sword] ¯\_(ツ)_/¯ [b]★[/nomarkup] !]",
+            markup.text
+        );
+
+        assert_eq!(2, markup.attributes.len());
+
+        assert_eq!("nomarkup", markup.attributes[0].name);
+        assert_eq!(27, markup.attributes[0].position);
+        assert_eq!(15, markup.attributes[0].length);
+
+        assert_eq!("a", markup.attributes[1].name);
+        assert_eq!(43, markup.attributes[1].position);
+        assert_eq!(2, markup.attributes[1].length);
+    }
+
+    #[test]
+    fn test_markup_values_convert_to_and_from_yarn_values() {
+        use yarnspinner_core::prelude::YarnValue;
+
+        let markup = line_parser()
+            .parse_markup(r#"[a n=3 f=1.5 g=2.0 s="text" b=true /]"#)
+            .unwrap();
+        let properties = &markup.attributes[0].properties;
+
+        for (name, expected) in [
+            ("f", YarnValue::Number(1.5)),
+            ("g", YarnValue::Number(2.0)),
+            ("s", YarnValue::String("text".to_owned())),
+            ("b", YarnValue::Boolean(true)),
+        ] {
+            let value = &properties[name];
+            assert_eq!(expected, YarnValue::try_from(value).unwrap());
+            assert_eq!(value, &MarkupValue::from(expected));
+        }
+        // Integers keep their value, but come back as floats
+        assert_eq!(
+            YarnValue::Number(3.0),
+            YarnValue::try_from(&properties["n"]).unwrap()
+        );
+        assert_eq!(
+            MarkupValue::Float(3.0),
+            MarkupValue::from(YarnValue::Number(3.0))
+        );
+        assert_eq!(
+            YarnValue::Number(16_777_216.0),
+            YarnValue::try_from(MarkupValue::Integer(16_777_216)).unwrap()
+        );
+        for integer in [16_777_217, u32::MAX] {
+            assert_eq!(
+                Err(MarkupValueConversionError { integer }),
+                YarnValue::try_from(MarkupValue::Integer(integer))
+            );
+        }
+    }
+
     #[test]
     fn test_numeric_properties() {
         let line = "[select value=1 1=one 2=two 3=three /]";
@@ -339,6 +568,23 @@ mod tests {
         assert_eq!("one", markup.text);
     }
 
+    #[test]
+    fn test_select_with_arbitrary_keys_and_fallback() {
+        for (value, expected) in [
+            ("he", "Ask him."),
+            ("she", "Ask her."),
+            ("they", "Ask them."),
+            ("\"the guard\"", "Ask the guard."),
+        ] {
+            let line = format!(
+                "Ask [select value={value} he=\"him\" she=\"her\" they=\"them\" other=\"%\" /]."
+            );
+            let markup = line_parser().parse_markup(&line).unwrap();
+
+            assert_eq!(expected, markup.text);
+        }
+    }
+
     #[test]
     fn test_number_pluralisation() {
         for (value, locale, expected) in [
@@ -359,6 +605,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pluralisation_uses_cldr_categories_of_the_language() {
+        for (marker, value, locale, expected) in [
+            ("plural", 1, "pl", "1 kot"),
+            ("plural", 3, "pl", "3 koty"),
+            ("plural", 5, "pl", "5 kotów"),
+            ("plural", 22, "pl", "22 koty"),
+            ("plural", 3, "ru", "3 кота"),
+            ("plural", 11, "ru", "11 котов"),
+            ("ordinal", 2, "en", "2nd"),
+            ("ordinal", 13, "en", "13th"),
+            ("ordinal", 23, "en", "23rd"),
+        ] {
+            let line = match locale {
+                "pl" => format!("[{marker} value={value} one=\"% kot\" few=\"% koty\" many=\"% kotów\" other=\"% kota\"/]"),
+                "ru" => format!("[{marker} value={value} one=\"% кот\" few=\"% кота\" many=\"% котов\" other=\"% кота\"/]"),
+                _ => format!("[{marker} value={value} one=\"%st\" two=\"%nd\" few=\"%rd\" other=\"%th\"/]"),
+            };
+
+            let mut line_parser = line_parser();
+            line_parser.set_language_code(Language::from(locale));
+            let markup = line_parser.parse_markup(&line).unwrap();
+
+            assert_eq!(expected, markup.text, "{marker} {value} in {locale}");
+        }
+    }
+
+    #[test]
+    fn test_pluralisation_falls_back_to_other() {
+        let mut line_parser = line_parser();
+        line_parser.set_language_code(Language::from("pl"));
+        let markup = line_parser
+            .parse_markup("[plural value=3 one=\"a cat\" other=\"% cats\"/]")
+            .unwrap();
+
+        assert_eq!("3 cats", markup.text);
+    }
+
     fn line_parser() -> LineParser {
         let dialogue_text_processor = Box::new(DialogueTextProcessor::new());
 
@@ -393,7 +677,12 @@ mod tests {
             Line {
                 id: "test".into(),
                 text: self.text.clone(),
+                raw_text: self.text.clone(),
+                markup_error: None,
                 attributes: self.attributes.clone(),
+                metadata: vec![],
+                node_name: "Start".to_owned(),
+                source_location: None,
             }
         }
     }
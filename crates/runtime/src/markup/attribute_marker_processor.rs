@@ -1,15 +1,43 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner/YarnSpinner.Markup/IAttributeMarkerProcessor.cs>
 
-pub(crate) use self::{dialogue_text_processor::*, no_markup_text_processor::*};
-use crate::markup::MarkupAttributeMarker;
+pub(crate) use self::{
+    button_glyph_processor::*, dialogue_text_processor::*, no_markup_text_processor::*,
+};
+use crate::markup::{MarkupAttributeMarker, MarkupValue};
 use crate::prelude::Language;
 use core::fmt::Debug;
+use std::collections::HashMap;
 
+mod button_glyph_processor;
 mod dialogue_text_processor;
 mod no_markup_text_processor;
 
 /// Provides a mechanism for producing replacement text for a marker.
-pub(crate) trait AttributeMarkerProcessor: Debug + Send + Sync {
+/// Register one for a marker name with [`Dialogue::add_markup_processor`](crate::prelude::Dialogue::add_markup_processor).
+///
+/// ## Example
+///
+/// A processor for `[icon=sword/]` that inserts a glyph from an icon font:
+///
+/// ```rust
+/// # use yarnspinner_runtime::markup::*;
+/// #[derive(Debug, Clone)]
+/// struct IconProcessor;
+///
+/// impl AttributeMarkerProcessor for IconProcessor {
+///     fn replacement_text_for_marker(&self, marker: &MarkupAttributeMarker) -> String {
+///         match marker.properties.get("icon") {
+///             Some(MarkupValue::String(icon)) if icon == "sword" => "\u{1F5E1}".to_owned(),
+///             _ => String::new(),
+///         }
+///     }
+///
+///     fn clone_box(&self) -> Box<dyn AttributeMarkerProcessor> {
+///         Box::new(self.clone())
+///     }
+/// }
+/// ```
+pub trait AttributeMarkerProcessor: Debug + Send + Sync {
     /// Produces the replacement text that should be inserted into a parse
     /// result for a given attribute.
     ///
@@ -17,7 +45,21 @@ pub(crate) trait AttributeMarkerProcessor: Debug + Send + Sync {
     /// position to its corresponding closing marker is provided as a string
     /// property called `contents`.
     fn replacement_text_for_marker(&self, marker: &MarkupAttributeMarker) -> String;
-    fn set_language_code(&mut self, language_code: Option<Language>);
+
+    /// Produces properties that are added to the attribute created for `marker`,
+    /// e.g. to tell views how to render something that has no text representation. Used by the `button` marker.
+    fn properties_for_marker(
+        &self,
+        _marker: &MarkupAttributeMarker,
+    ) -> HashMap<String, MarkupValue> {
+        HashMap::new()
+    }
+
+    /// Called with the language lines are parsed in whenever it changes, e.g. to apply its pluralization rules.
+    /// Does nothing by default.
+    fn set_language_code(&mut self, _language_code: Option<Language>) {}
+
+    /// Returns a boxed clone of this processor. Implement it as `Box::new(self.clone())`.
     fn clone_box(&self) -> Box<dyn AttributeMarkerProcessor>;
 }
 
@@ -0,0 +1,96 @@
+//! Implements the `[button=action/]` marker, see [`ButtonGlyphs`].
+
+use crate::markup::{
+    AttributeMarkerProcessor, ButtonGlyph, ButtonGlyphs, MarkupAttributeMarker, MarkupValue,
+    BUTTON_ATTRIBUTE, BUTTON_ATTRIBUTE_ICON_PROPERTY, BUTTON_ATTRIBUTE_PLATFORM_PROPERTY,
+};
+use crate::prelude::Language;
+use log::error;
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// The [`ButtonGlyphs`] of a [`Dialogue`](crate::prelude::Dialogue), shared with the [`ButtonGlyphProcessor`] registered in its line parser.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedButtonGlyphs(Arc<RwLock<Option<ButtonGlyphs>>>);
+
+impl SharedButtonGlyphs {
+    pub(crate) fn set(&self, glyphs: impl Into<Option<ButtonGlyphs>>) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = glyphs.into();
+    }
+
+    pub(crate) fn get(&self) -> Option<ButtonGlyphs> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// A markup text processor that implements the `[button]` attribute's behaviour.
+#[derive(Debug, Clone)]
+pub(crate) struct ButtonGlyphProcessor {
+    glyphs: SharedButtonGlyphs,
+}
+
+impl ButtonGlyphProcessor {
+    pub(crate) fn new(glyphs: SharedButtonGlyphs) -> Self {
+        Self { glyphs }
+    }
+
+    /// Looks up the glyph for the action named by `marker`. Returns [`None`] if no glyphs are set.
+    fn resolve(&self, marker: &MarkupAttributeMarker) -> Option<(String, Option<ButtonGlyph>)> {
+        let glyphs = self.glyphs.0.read().unwrap_or_else(PoisonError::into_inner);
+        let glyphs = glyphs.as_ref()?;
+        let glyph = match marker.properties.get(BUTTON_ATTRIBUTE) {
+            Some(MarkupValue::String(action)) => glyphs.glyph(action).cloned(),
+            _ => None,
+        };
+        Some((glyphs.platform().to_string(), glyph))
+    }
+}
+
+impl AttributeMarkerProcessor for ButtonGlyphProcessor {
+    fn replacement_text_for_marker(&self, marker: &MarkupAttributeMarker) -> String {
+        let Some((platform, glyph)) = self.resolve(marker) else {
+            return String::new();
+        };
+        match glyph {
+            Some(ButtonGlyph::Text(text)) => text,
+            Some(ButtonGlyph::Icon(_)) => String::new(),
+            None => {
+                match marker.properties.get(BUTTON_ATTRIBUTE) {
+                    Some(MarkupValue::String(action)) => {
+                        error!("No glyph for the action \"{action}\" on the platform \"{platform}\"")
+                    }
+                    _ => error!("A [{BUTTON_ATTRIBUTE}] marker must name an action, e.g. [{BUTTON_ATTRIBUTE}=confirm/]"),
+                }
+                String::new()
+            }
+        }
+    }
+
+    fn properties_for_marker(
+        &self,
+        marker: &MarkupAttributeMarker,
+    ) -> HashMap<String, MarkupValue> {
+        let Some((platform, glyph)) = self.resolve(marker) else {
+            return HashMap::new();
+        };
+        let mut properties = HashMap::from([(
+            BUTTON_ATTRIBUTE_PLATFORM_PROPERTY.to_owned(),
+            platform.into(),
+        )]);
+        if let Some(ButtonGlyph::Icon(icon)) = glyph {
+            properties.insert(BUTTON_ATTRIBUTE_ICON_PROPERTY.to_owned(), icon.into());
+        }
+        properties
+    }
+
+    fn set_language_code(&mut self, _language_code: Option<Language>) {
+        // no-op
+    }
+
+    fn clone_box(&self) -> Box<dyn AttributeMarkerProcessor> {
+        Box::new(self.clone())
+    }
+}
@@ -25,8 +25,20 @@ impl AttributeMarkerProcessor for DialogueTextProcessor {
     /// Returns the text that should be used to replace the
     /// contents of `marker`.
     ///
+    /// `select` markers choose the property named after the value, e.g. `she` for `[select value={$pronoun} he="his" she="her" they="their" /]`,
+    /// falling back to the property `other` if there is no property for the value.
+    ///
+    /// `plural` and `ordinal` markers choose the property named after the CLDR plural category of the value in the dialogue's language,
+    /// i.e. `zero`, `one`, `two`, `few`, `many` or `other`.
+    ///
     /// ## Panics
     /// Panics when the string contains a `plural` or `ordinal` marker, but the specified value cannot be parsed as a number.
+    ///
+    /// ## Implementation notes
+    ///
+    /// The original fails when the marker has no property for the value or its category.
+    /// This implementation falls back to `other` instead, like later versions of Yarn Spinner do for `select` markers.
+    /// Since every language has an `other` category in CLDR, this also means that a line written with only `one` and `other` still works in languages that distinguish more categories, e.g. `few` in Polish.
     fn replacement_text_for_marker(&self, marker: &crate::markup::MarkupAttributeMarker) -> String {
         let value_prop = marker
             .properties
@@ -39,6 +51,7 @@ impl AttributeMarkerProcessor for DialogueTextProcessor {
             let replacement_prop = marker
                 .properties
                 .get(&value)
+                .or_else(|| marker.properties.get("other"))
                 .unwrap_or_else(|| panic!("error: no replacement for {value}"));
             let replacement = replacement_prop.to_string();
 
@@ -70,9 +83,13 @@ impl AttributeMarkerProcessor for DialogueTextProcessor {
 
         // Now that we know the plural case, we can select the
         // appropriate replacement text for it
-        let replacement_value = marker.properties.get(plural_case_name).unwrap_or_else(|| {
-            panic!("error: no replacement for {value}'s plural case of {plural_case_name}")
-        });
+        let replacement_value = marker
+            .properties
+            .get(plural_case_name)
+            .or_else(|| marker.properties.get("other"))
+            .unwrap_or_else(|| {
+                panic!("error: no replacement for {value}'s plural case of {plural_case_name}")
+            });
         let input = replacement_value.to_string();
 
         replace_value_placeholders(&input, &value)
@@ -0,0 +1,109 @@
+//! Lets lines refer to input prompts with `[button=confirm/]`,
+//! which are resolved through a mapping provided by the game for the input device currently in use.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+/// The name of the marker that refers to an input action, e.g. `[button=confirm/]`.
+pub const BUTTON_ATTRIBUTE: &str = "button";
+
+/// The property added to a [`BUTTON_ATTRIBUTE`] attribute that holds the [`ButtonGlyph::Icon`] to render.
+/// Not present if the glyph was inserted as text or the action has no glyph.
+pub const BUTTON_ATTRIBUTE_ICON_PROPERTY: &str = "icon";
+
+/// The property added to a [`BUTTON_ATTRIBUTE`] attribute that holds the name of the [`InputPlatform`] the glyph was taken from.
+pub const BUTTON_ATTRIBUTE_PLATFORM_PROPERTY: &str = "platform";
+
+/// The kind of input device a [`ButtonGlyphs`] set is meant for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InputPlatform {
+    /// Keyboard and mouse.
+    Keyboard,
+    /// Xbox controllers.
+    Xbox,
+    /// PlayStation controllers.
+    PlayStation,
+    /// Nintendo Switch controllers.
+    Switch,
+    /// Any other device, identified by name.
+    Other(String),
+}
+
+impl Display for InputPlatform {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keyboard => f.write_str("keyboard"),
+            Self::Xbox => f.write_str("xbox"),
+            Self::PlayStation => f.write_str("playstation"),
+            Self::Switch => f.write_str("switch"),
+            Self::Other(name) => f.write_str(name),
+        }
+    }
+}
+
+/// How the input action of a `[button=...]` marker is shown.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ButtonGlyph {
+    /// The text is inserted into the line in place of the marker, e.g. `"Space"` or `"Ⓐ"`.
+    Text(String),
+    /// Nothing is inserted into the line. Instead, the marker's attribute gets a [`BUTTON_ATTRIBUTE_ICON_PROPERTY`] holding this value,
+    /// e.g. the path of a sprite, so that the view can render the icon at the attribute's position.
+    Icon(String),
+}
+
+/// Maps the input actions referred to by `[button=...]` markers to the glyphs of one [`InputPlatform`].
+/// Set it with [`Dialogue::set_button_glyphs`](crate::prelude::Dialogue::set_button_glyphs), and replace it whenever the player switches devices.
+///
+/// ## Example
+///
+/// ```rust
+/// # use yarnspinner_runtime::markup::*;
+/// let mut glyphs = ButtonGlyphs::new(InputPlatform::Xbox);
+/// glyphs
+///     .add_text("confirm", "Ⓐ")
+///     .add_icon("map", "sprites/xbox/view.png");
+/// ```
+///
+/// With these glyphs, the line `Press [button=confirm/] to talk.` is delivered as `Press Ⓐ to talk.`,
+/// while the line `Open the map with [button=map/].` is delivered as `Open the map with .` and a `button` attribute at the end carrying an `icon` property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ButtonGlyphs {
+    platform: InputPlatform,
+    glyphs: HashMap<String, ButtonGlyph>,
+}
+
+impl ButtonGlyphs {
+    /// Creates an empty set of glyphs for the given platform.
+    pub fn new(platform: InputPlatform) -> Self {
+        Self {
+            platform,
+            glyphs: HashMap::new(),
+        }
+    }
+
+    /// The platform these glyphs are meant for.
+    pub fn platform(&self) -> &InputPlatform {
+        &self.platform
+    }
+
+    /// Shows the given action as text. Replaces any glyph previously added for the action.
+    pub fn add_text(&mut self, action: impl Into<String>, text: impl Into<String>) -> &mut Self {
+        self.add_glyph(action, ButtonGlyph::Text(text.into()))
+    }
+
+    /// Shows the given action as an icon rendered by the view. Replaces any glyph previously added for the action.
+    pub fn add_icon(&mut self, action: impl Into<String>, icon: impl Into<String>) -> &mut Self {
+        self.add_glyph(action, ButtonGlyph::Icon(icon.into()))
+    }
+
+    /// Adds a glyph for the given action. Replaces any glyph previously added for the action.
+    pub fn add_glyph(&mut self, action: impl Into<String>, glyph: ButtonGlyph) -> &mut Self {
+        self.glyphs.insert(action.into(), glyph);
+        self
+    }
+
+    /// Returns the glyph for the given action, if there is one.
+    pub fn glyph(&self, action: &str) -> Option<&ButtonGlyph> {
+        self.glyphs.get(action)
+    }
+}
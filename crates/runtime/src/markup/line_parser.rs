@@ -3,7 +3,7 @@
 use crate::markup::parsed_markup::ParsedMarkup;
 use crate::markup::{
     AttributeMarkerProcessor, MarkupAttribute, MarkupAttributeMarker, MarkupParseError,
-    MarkupValue, NoMarkupTextProcessor, TagType,
+    MarkupStyles, MarkupValue, NoMarkupTextProcessor, TagType,
 };
 use crate::prelude::*;
 use once_cell::sync::Lazy;
@@ -29,6 +29,12 @@ pub(crate) struct LineParser {
     #[cfg_attr(feature = "bevy", reflect(ignore))]
     #[cfg_attr(feature = "serde", serde(skip))]
     marker_processors: HashMap<String, Box<dyn AttributeMarkerProcessor>>,
+    /// The styles that `[style=...]` markers expand to.
+    #[cfg_attr(feature = "bevy", reflect(ignore))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    styles: MarkupStyles,
+    /// How whitespace around markers is trimmed.
+    whitespace_trimming: MarkupWhitespaceTrimming,
     /// The original text that this line parser is parsing.
     input: String,
     /// The current position of the string reader in the plain text, measured in characters.
@@ -44,6 +50,8 @@ impl Default for LineParser {
                 "nomarkup".to_string(),
                 Box::new(NoMarkupTextProcessor::new()) as Box<dyn AttributeMarkerProcessor>,
             )]),
+            styles: Default::default(),
+            whitespace_trimming: Default::default(),
             input: Default::default(),
             source_position: Default::default(),
             position: Default::default(),
@@ -81,6 +89,25 @@ impl LineParser {
         self
     }
 
+    /// Like [`LineParser::register_marker_processor`], but replaces any processor previously registered for the marker name.
+    pub(crate) fn set_marker_processor(
+        &mut self,
+        attribute_name: impl Into<String>,
+        processor: Box<dyn AttributeMarkerProcessor>,
+    ) {
+        self.marker_processors
+            .insert(attribute_name.into(), processor);
+    }
+
+    pub(crate) fn marker_processor(
+        &self,
+        attribute_name: &str,
+    ) -> Option<&dyn AttributeMarkerProcessor> {
+        self.marker_processors
+            .get(attribute_name)
+            .map(|p| p.as_ref())
+    }
+
     /// Parses a line of text, and produces a [`ParsedMarkup`] containing the processed text
     ///
     /// ## Implementation notes
@@ -131,9 +158,11 @@ impl LineParser {
                         .as_ref()
                         .map(|name| self.marker_processors.contains_key(name))
                         .unwrap_or_default();
+                    let mut left_no_text = true;
                     if was_replacement_marker {
                         // Process it and get the replacement text!
                         let replacement_text = self.process_replacement_marker(&mut marker)?;
+                        left_no_text = replacement_text.is_empty();
 
                         // Insert it into our final string and update our position accordingly
                         text.push_str(&replacement_text);
@@ -143,9 +172,13 @@ impl LineParser {
                         // By default, self-closing markers will trim a single trailing whitespace after it if there was preceding whitespace.
                         // This doesn't happen if the marker was a replacement marker, or it has a property "trimwhitespace" (which must be boolean) set to false.
                         // All markers can opt-in to trailing whitespace trimming by having a 'trimwhitespace' property set to true.
-                        if marker.tag_type == TagType::SelfClosing {
-                            trim_whitespace_if_able = !was_replacement_marker;
-                        }
+                        trim_whitespace_if_able = match self.whitespace_trimming {
+                            MarkupWhitespaceTrimming::YarnSpinner => {
+                                marker.tag_type == TagType::SelfClosing && !was_replacement_marker
+                            }
+                            MarkupWhitespaceTrimming::PreserveAll => false,
+                            MarkupWhitespaceTrimming::Collapse => left_no_text,
+                        };
                         if let Some(prop) = marker.properties.get(TRIM_WHITESPACE_PROPERTY) {
                             let MarkupValue::Bool(trim_whitespace) = prop else {
                                 return Err(
@@ -153,6 +186,7 @@ impl LineParser {
                                         input: self.input.clone(),
                                         name: marker.name,
                                         position: self.position,
+                                        column: marker.source_position,
                                         type_: prop.type_name().to_lowercase(),
                                     },
                                 );
@@ -166,6 +200,11 @@ impl LineParser {
                             // Consume the single trailing whitespace character (and don't update position)
                             self.read_next();
                         }
+                        if self.whitespace_trimming == MarkupWhitespaceTrimming::Collapse {
+                            while let Some(true) = self.peek_whitespace() {
+                                self.read_next();
+                            }
+                        }
                     }
                     markers.push(marker);
                 }
@@ -179,7 +218,8 @@ impl LineParser {
             last_character = character;
         }
 
-        let mut attributes = self.build_attributes_from_markers(markers)?;
+        let attributes = self.build_attributes_from_markers(markers)?;
+        let mut attributes = self.styles.expand(attributes);
         let character_attribute_is_present = attributes
             .iter()
             .any(|attr| attr.name == CHARACTER_ATTRIBUTE);
@@ -188,17 +228,20 @@ impl LineParser {
         }
 
         // Attempt to generate a character attribute from the start
-        // of the string to the first colon
-        let Some(match_) = END_OF_CHARACTER_MARKER.find(&self.input) else {
+        // of the string to the first colon.
+        // Implementation note: the original searches the unparsed input and measures the attribute in UTF-16 code units,
+        // which breaks for names that are marked up or contain non-ASCII characters, e.g. `[b]Zoë[/b]: Hi!`.
+        // We search the parsed text and measure in graphemes like all other attributes instead.
+        let Some(match_) = END_OF_CHARACTER_MARKER.find(&text) else {
             return Ok(ParsedMarkup { text, attributes });
         };
 
-        let character_name = self.input[..match_.start()].to_string();
+        let character_name = text[..match_.start()].to_string();
 
         let character_attribute = MarkupAttribute {
             name: CHARACTER_ATTRIBUTE.to_string(),
             position: 0,
-            length: match_.end(),
+            length: text[..match_.end()].graphemes(true).count(),
             properties: HashMap::from([(
                 CHARACTER_ATTRIBUTE_NAME_PROPERTY.to_string(),
                 character_name.into(),
@@ -210,6 +253,25 @@ impl LineParser {
         Ok(ParsedMarkup { text, attributes })
     }
 
+    pub(crate) fn set_styles(&mut self, styles: MarkupStyles) {
+        self.styles = styles;
+    }
+
+    pub(crate) fn styles(&self) -> &MarkupStyles {
+        &self.styles
+    }
+
+    pub(crate) fn set_whitespace_trimming(
+        &mut self,
+        whitespace_trimming: MarkupWhitespaceTrimming,
+    ) {
+        self.whitespace_trimming = whitespace_trimming;
+    }
+
+    pub(crate) fn whitespace_trimming(&self) -> MarkupWhitespaceTrimming {
+        self.whitespace_trimming
+    }
+
     pub(crate) fn set_language_code(&mut self, language_code: impl Into<Option<Language>>) {
         let language_code = language_code.into();
         for processor in self.marker_processors.values_mut() {
@@ -287,6 +349,7 @@ impl LineParser {
                 .peek_next()
                 .ok_or_else(|| MarkupParseError::UnexpectedEndOfLine {
                     input: self.input.clone(),
+                    column: self.input.chars().count(),
                 })?;
             match next {
                 ']' => {
@@ -357,11 +420,10 @@ impl LineParser {
         }
         // Fetch the text that should be inserted into the string at
         // this point
-        let replacement = self
-            .marker_processors
-            .get(name)
-            .unwrap()
-            .replacement_text_for_marker(marker);
+        let processor = self.marker_processors.get(name).unwrap();
+        let replacement = processor.replacement_text_for_marker(marker);
+        let properties = processor.properties_for_marker(marker);
+        marker.properties.extend(properties);
         Ok(replacement)
     }
 
@@ -403,6 +465,7 @@ impl LineParser {
                             input: self.input.clone(),
                             name: marker.name.unwrap(),
                             position: marker.position,
+                            column: marker.source_position,
                         })?;
 
                     // This attribute is now closed, so we can
@@ -474,11 +537,14 @@ impl LineParser {
             .read_next()
             .ok_or_else(|| MarkupParseError::UnexpectedEndOfLine {
                 input: self.input.clone(),
+                column: self.input.chars().count(),
             })?;
         if next != character {
             return Err(MarkupParseError::UnexpectedCharacter {
                 input: self.input.clone(),
+                column: self.source_position - 1,
                 character,
+                found: next,
             });
         }
         Ok(())
@@ -499,6 +565,7 @@ impl LineParser {
                 self.peek_next()
                     .ok_or_else(|| MarkupParseError::UnexpectedWhitespaceEnd {
                         input: self.input.clone(),
+                        column: self.input.chars().count(),
                     })?;
             if !next.is_whitespace() {
                 // no more whitespace ahead; don't consume it, but
@@ -519,6 +586,7 @@ impl LineParser {
             .read_next()
             .ok_or_else(|| MarkupParseError::UnexpectedEndOfLine {
                 input: self.input.clone(),
+                column: self.input.chars().count(),
             })?;
 
         // Implementation notes: no surrogate checks because UTF-16 surrogates are not valid Rust chars
@@ -528,6 +596,8 @@ impl LineParser {
         } else {
             return Err(MarkupParseError::NoIdentifierFound {
                 input: self.input.clone(),
+                column: self.source_position - 1,
+                found: next,
             });
         }
 
@@ -603,32 +673,39 @@ impl LineParser {
     /// a close-all marker.
     ///
     /// The closing marker itself is not included in the returned text.
+    ///
+    /// ## Implementation notes
+    ///
+    /// Unlike the original, escaped brackets are unescaped here just like in the rest of the line,
+    /// so that an escaped closing marker, e.g. `\[/nomarkup]`, does not end the raw text.
+    /// The original also mixed up byte and character offsets when continuing after the closing marker,
+    /// which broke lines with non-ASCII text inside the raw text.
     fn parse_raw_text_up_to_attribute_close(&mut self, name: &str) -> Result<String> {
         let original_source_position = self.source_position;
         let remainder_of_line = self.read_to_end();
 
         // Parse up to either [/name] or [/], allowing whitespace between any elements.
         let regex = Regex::new(&format!(r"\[\s*\/\s*({name})?\s*\]")).unwrap();
-        let match_ =
-            regex
-                .find(&remainder_of_line)
-                .ok_or_else(|| MarkupParseError::UnterminatedMarker {
-                    input: self.input.clone(),
-                    name: name.to_string(),
-                    position: self.position,
-                })?;
+        let match_ = regex
+            .find_iter(&remainder_of_line)
+            .find(|match_| !remainder_of_line[..match_.start()].ends_with('\\'))
+            .ok_or_else(|| MarkupParseError::UnterminatedMarker {
+                input: self.input.clone(),
+                name: name.to_string(),
+                position: self.position,
+                column: original_source_position,
+            })?;
 
         // Split the line into the part up to the closing tag, and the
         // part afterwards
-        let close_marker_position = match_.start();
-        let raw_text_substring = &remainder_of_line[..close_marker_position];
+        let raw_text_substring = &remainder_of_line[..match_.start()];
 
         // We've consumed all of this text in the string reader, so to
         // make it possible to parse the rest, we need to create a new
         // "string reader" with the remaining text
-        self.source_position = original_source_position + close_marker_position;
+        self.source_position = original_source_position + raw_text_substring.chars().count();
 
-        Ok(raw_text_substring.to_string())
+        Ok(raw_text_substring.replace(r"\[", "[").replace(r"\]", "]"))
     }
 
     /// Peeks ahead in the LineParser's input without consuming any
@@ -656,6 +733,7 @@ impl LineParser {
                 .peek_next()
                 .ok_or_else(|| MarkupParseError::UnexpectedEndOfLine {
                     input: self.input.clone(),
+                    column: self.input.chars().count(),
                 })?;
             if next.is_ascii_digit() {
                 self.read_next().unwrap();
@@ -677,11 +755,14 @@ impl LineParser {
             .read_next()
             .ok_or_else(|| MarkupParseError::UnexpectedEndOfLine {
                 input: self.input.clone(),
+                column: self.input.chars().count(),
             })?;
 
         if next != '"' {
             return Err(MarkupParseError::NoStringFound {
                 input: self.input.clone(),
+                column: self.source_position - 1,
+                found: next,
             });
         }
         loop {
@@ -689,6 +770,7 @@ impl LineParser {
                 .read_next()
                 .ok_or_else(|| MarkupParseError::UnexpectedEndOfLine {
                     input: self.input.clone(),
+                    column: self.input.chars().count(),
                 })?;
             match next {
                 '"' => {
@@ -701,6 +783,7 @@ impl LineParser {
                         self.read_next()
                             .ok_or_else(|| MarkupParseError::UnexpectedEndOfLine {
                                 input: self.input.clone(),
+                                column: self.input.chars().count(),
                             })?;
                     if next == '"' || next == '\\' {
                         string.push(next);
@@ -709,6 +792,8 @@ impl LineParser {
                         // Not an error in the original implementation, but that seems like an oversight.
                         return Err(MarkupParseError::InvalidEscapeSequence {
                             input: self.input.clone(),
+                            column: self.source_position - 1,
+                            found: next,
                         });
                     }
                 }
@@ -738,5 +823,36 @@ pub const CHARACTER_ATTRIBUTE_NAME_PROPERTY: &str = "name";
 /// if a tag had preceding whitespace or begins the line. This property must be a bool value.
 pub const TRIM_WHITESPACE_PROPERTY: &str = "trimwhitespace";
 
+/// How the whitespace around markers is trimmed when a line is parsed.
+/// Set it with [`Dialogue::set_markup_whitespace_trimming`](crate::prelude::Dialogue::set_markup_whitespace_trimming).
+///
+/// In every mode, a marker with a [`TRIM_WHITESPACE_PROPERTY`] that follows whitespace or begins the line
+/// trims the whitespace after it if the property is `true` and keeps it if the property is `false`.
+///
+/// ## Implementation notes
+///
+/// Not part of the original implementation, which always behaves like [`MarkupWhitespaceTrimming::YarnSpinner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum MarkupWhitespaceTrimming {
+    /// Self-closing markers that are not replaced by text, e.g. `[pause/]`, trim a single whitespace character after them
+    /// if they follow whitespace or begin the line, so that `Wait [pause/] what?` becomes `Wait what?`.
+    /// This is what Yarn Spinner does in every engine.
+    #[default]
+    YarnSpinner,
+    /// Whitespace is kept as written, so that `Wait [pause/] what?` becomes `Wait  what?`.
+    PreserveAll,
+    /// Every marker that leaves no text behind, including replacement markers that were replaced by an empty text,
+    /// removes all whitespace after it if it follows whitespace or begins the line,
+    /// so that `Wait [pause/]  [b] what?[/b]` becomes `Wait what?`.
+    Collapse,
+}
+
 /// A regular expression that matches a colon followed by optional whitespace.
 static END_OF_CHARACTER_MARKER: Lazy<Regex> = Lazy::new(|| Regex::new(r":\s*").unwrap());
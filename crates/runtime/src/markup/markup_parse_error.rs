@@ -3,8 +3,15 @@ use crate::markup::TRIM_WHITESPACE_PROPERTY;
 use crate::prelude::*;
 use thiserror::Error;
 
+/// An error that occurred while parsing the markup of a line.
+///
+/// Every variant carries the `input` that failed to parse and the `column` of the offending character in it,
+/// counted in characters from 0. The input is the text of the line after substitutions and Unicode normalization.
+/// Use [`MarkupParseError::input`] and [`MarkupParseError::column`] to read them regardless of the variant.
+///
+/// To deliver lines with broken markup as plain text instead of failing, see [`Dialogue::set_lenient_markup`](crate::prelude::Dialogue::set_lenient_markup).
 #[allow(missing_docs)]
-#[derive(Error, Debug, PartialEq, Eq, Hash)]
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "bevy", derive(Reflect))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
@@ -13,35 +20,90 @@ use thiserror::Error;
     reflect(Serialize, Deserialize)
 )]
 pub enum MarkupParseError {
-    #[error("Error parsing line {input}: attribute {name:?} at position {position} has a {type_} property \"{TRIM_WHITESPACE_PROPERTY}\" - this property is required to be a boolean value.")]
+    #[error("Error parsing line {input}: attribute {name:?} at position {position} (column {column}) has a {type_} property \"{TRIM_WHITESPACE_PROPERTY}\" - this property is required to be a boolean value.")]
     TrimWhitespaceAttributeIsNotBoolean {
         input: String,
         name: Option<String>,
         position: usize,
+        column: usize,
         type_: String,
     },
     #[error("Line ended when expecting whitespace instead: \"{input}\"")]
-    UnexpectedWhitespaceEnd { input: String },
+    UnexpectedWhitespaceEnd { input: String, column: usize },
     #[error("Unexpected end of line inside markup in line \"{input}\"")]
-    UnexpectedEndOfLine { input: String },
-    #[error("Expected a {character} inside markup in line \"{input}\"")]
-    UnexpectedCharacter { input: String, character: char },
-    #[error("Unexpected close marker {name} at position {position} in line {input}")]
+    UnexpectedEndOfLine { input: String, column: usize },
+    #[error("Expected a {character} inside markup but found {found:?} at column {column} in line \"{input}\"")]
+    UnexpectedCharacter {
+        input: String,
+        column: usize,
+        character: char,
+        found: char,
+    },
+    #[error(
+        "Unexpected close marker {name} at position {position} (column {column}) in line {input}"
+    )]
     UnmatchedCloseMarker {
         input: String,
         name: String,
         position: usize,
+        column: usize,
+    },
+    #[error("Expected an identifier inside markup but found {found:?} at column {column} in line \"{input}\"")]
+    NoIdentifierFound {
+        input: String,
+        column: usize,
+        found: char,
+    },
+    #[error("Expected a string inside markup but found {found:?} at column {column} in line \"{input}\"")]
+    NoStringFound {
+        input: String,
+        column: usize,
+        found: char,
     },
-    #[error("Expected an identifier inside markup in line \"{input}\"")]
-    NoIdentifierFound { input: String },
-    #[error("Expected a string inside markup in line \"{input}\"")]
-    NoStringFound { input: String },
-    #[error("Invalid escaped character in line \"{input}\"")]
-    InvalidEscapeSequence { input: String },
-    #[error("Unterminated marker {name} in line {input} at position {position}")]
+    #[error("Invalid escaped character {found:?} at column {column} in line \"{input}\"")]
+    InvalidEscapeSequence {
+        input: String,
+        column: usize,
+        found: char,
+    },
+    #[error("Unterminated marker {name} in line {input} at position {position} (column {column})")]
     UnterminatedMarker {
         input: String,
         name: String,
         position: usize,
+        column: usize,
     },
 }
+
+impl MarkupParseError {
+    /// The text of the line that failed to parse.
+    pub fn input(&self) -> &str {
+        match self {
+            Self::TrimWhitespaceAttributeIsNotBoolean { input, .. }
+            | Self::UnexpectedWhitespaceEnd { input, .. }
+            | Self::UnexpectedEndOfLine { input, .. }
+            | Self::UnexpectedCharacter { input, .. }
+            | Self::UnmatchedCloseMarker { input, .. }
+            | Self::NoIdentifierFound { input, .. }
+            | Self::NoStringFound { input, .. }
+            | Self::InvalidEscapeSequence { input, .. }
+            | Self::UnterminatedMarker { input, .. } => input,
+        }
+    }
+
+    /// The index of the character in [`MarkupParseError::input`] where the error was found, counted in characters from 0.
+    /// Errors about the line ending too early point one past its last character.
+    pub fn column(&self) -> usize {
+        match self {
+            Self::TrimWhitespaceAttributeIsNotBoolean { column, .. }
+            | Self::UnexpectedWhitespaceEnd { column, .. }
+            | Self::UnexpectedEndOfLine { column, .. }
+            | Self::UnexpectedCharacter { column, .. }
+            | Self::UnmatchedCloseMarker { column, .. }
+            | Self::NoIdentifierFound { column, .. }
+            | Self::NoStringFound { column, .. }
+            | Self::InvalidEscapeSequence { column, .. }
+            | Self::UnterminatedMarker { column, .. } => *column,
+        }
+    }
+}
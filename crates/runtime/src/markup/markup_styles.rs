@@ -0,0 +1,118 @@
+//! Lets writers mark up lines with semantic style names, e.g. `[style=shouty]`,
+//! while the game defines which attributes each style stands for in one place.
+
+use crate::markup::{MarkupAttribute, MarkupValue};
+use std::collections::HashMap;
+
+/// The name of the marker that applies a style registered in [`MarkupStyles`], e.g. `[style=shouty]Get out![/style]`.
+pub const STYLE_ATTRIBUTE: &str = "style";
+
+/// Maps style names used in `[style=...]` markers to the attributes they stand for.
+/// Set it with [`Dialogue::set_markup_styles`](crate::prelude::Dialogue::set_markup_styles).
+///
+/// When a line is parsed, every attribute of a [`STYLE_ATTRIBUTE`] marker with a registered style is followed by the attributes of the style,
+/// covering the same text. The `style` attribute itself is kept, so views can still react to the style name.
+/// Styles that are not registered are left as plain `style` attributes.
+///
+/// ## Example
+///
+/// ```rust
+/// # use yarnspinner_runtime::markup::*;
+/// let mut styles = MarkupStyles::new();
+/// styles.add_style(
+///     "shouty",
+///     [
+///         StyledAttribute::new("color").with_property("color", "red"),
+///         StyledAttribute::new("size").with_property("size", 24),
+///         StyledAttribute::new("tremble"),
+///     ],
+/// );
+/// ```
+///
+/// With these styles, the line `Guard: [style=shouty]Halt![/style]` is delivered with `color`, `size` and `tremble` attributes on `Halt!`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MarkupStyles {
+    styles: HashMap<String, Vec<StyledAttribute>>,
+}
+
+/// An attribute that a style of [`MarkupStyles`] expands to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyledAttribute {
+    /// The name of the attribute, e.g. `color`.
+    pub name: String,
+    /// The properties of the attribute, e.g. `color` with the value `red`.
+    pub properties: HashMap<String, MarkupValue>,
+}
+
+impl StyledAttribute {
+    /// Creates an attribute without properties.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            properties: HashMap::new(),
+        }
+    }
+
+    /// Adds a property to the attribute. Replaces any property previously added under the same name.
+    #[must_use]
+    pub fn with_property(mut self, name: impl Into<String>, value: impl Into<MarkupValue>) -> Self {
+        self.properties.insert(name.into(), value.into());
+        self
+    }
+}
+
+impl MarkupStyles {
+    /// Creates a style table without any styles.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the attributes a style stands for. Replaces any style previously added under the same name.
+    pub fn add_style(
+        &mut self,
+        name: impl Into<String>,
+        attributes: impl IntoIterator<Item = StyledAttribute>,
+    ) -> &mut Self {
+        self.styles
+            .insert(name.into(), attributes.into_iter().collect());
+        self
+    }
+
+    /// Returns the attributes of the given style, if it is registered.
+    pub fn style(&self, name: &str) -> Option<&[StyledAttribute]> {
+        self.styles.get(name).map(Vec::as_slice)
+    }
+
+    /// Returns `true` if no styles are registered.
+    pub fn is_empty(&self) -> bool {
+        self.styles.is_empty()
+    }
+
+    /// Adds the attributes of the styles used by `style` attributes right after each of them.
+    pub(crate) fn expand(&self, attributes: Vec<MarkupAttribute>) -> Vec<MarkupAttribute> {
+        if self.is_empty() {
+            return attributes;
+        }
+        let mut expanded_attributes = Vec::with_capacity(attributes.len());
+        for attribute in attributes {
+            let styled_attributes = (attribute.name == STYLE_ATTRIBUTE)
+                .then(|| attribute.property(STYLE_ATTRIBUTE))
+                .flatten()
+                .and_then(|style| self.style(&style.to_string()))
+                .unwrap_or_default();
+            let expansion: Vec<_> = styled_attributes
+                .iter()
+                .map(|styled_attribute| MarkupAttribute {
+                    name: styled_attribute.name.clone(),
+                    position: attribute.position,
+                    length: attribute.length,
+                    properties: styled_attribute.properties.clone(),
+                    source_position: attribute.source_position,
+                })
+                .collect();
+            expanded_attributes.push(attribute);
+            expanded_attributes.extend(expansion);
+        }
+        expanded_attributes
+    }
+}
@@ -5,7 +5,10 @@ use crate::markup::{MarkupAttributeMarker, MarkupValue};
 #[cfg(any(feature = "bevy", feature = "serde"))]
 use crate::prelude::*;
 use core::fmt::Display;
+use core::hash::{Hash, Hasher};
 use std::collections::HashMap;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Represents a range of text in a marked-up string.
 ///
@@ -17,7 +20,7 @@ use std::collections::HashMap;
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "bevy", derive(Reflect))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
 #[cfg_attr(
     all(feature = "bevy", feature = "serde"),
     reflect(Serialize, Deserialize)
@@ -25,7 +28,8 @@ use std::collections::HashMap;
 pub struct MarkupAttribute {
     /// The name of the attribute.
     pub name: String,
-    /// The position in the plain text where this attribute begins.
+    /// The position in the plain text where this attribute begins, counted in text elements, i.e. extended grapheme clusters.
+    /// Use [`MarkupAttribute::byte_range_in`] or [`Line::attribute_byte_ranges`] to slice the text with it.
     pub position: usize,
     /// The number of text elements in the plain text that this attribute covers.
     pub length: usize,
@@ -46,6 +50,27 @@ impl MarkupAttribute {
         }
     }
 
+    /// Returns the range of UTF-8 bytes of `text` that this attribute covers, e.g. for `&text[range]`,
+    /// or [`None`] if the attribute extends past the end of `text`.
+    /// `text` is the plain text this attribute was parsed from, e.g. [`Line::text`].
+    ///
+    /// This walks the graphemes of `text` up to the end of the attribute. To convert all attributes of a line at once,
+    /// use [`Line::attribute_byte_ranges`], which walks the text only once.
+    ///
+    /// ## Implementation notes
+    ///
+    /// Not part of the original implementation, which relies on C# strings being indexed by UTF-16 code units.
+    pub fn byte_range_in(&self, text: &str) -> Option<Range<usize>> {
+        let mut boundaries = grapheme_boundaries(text);
+        let start = boundaries.nth(self.position)?;
+        let end = if self.length == 0 {
+            start
+        } else {
+            boundaries.nth(self.length - 1)?
+        };
+        Some(start..end)
+    }
+
     /// Returns the value of the property with the given name, if it exists.
     /// For examples, the text `Hello [size=20]world[/size]` has a property named `size` with the value `20`.
     pub fn property(&self, name: &str) -> Option<&MarkupValue> {
@@ -53,6 +78,19 @@ impl MarkupAttribute {
     }
 }
 
+impl Hash for MarkupAttribute {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.position.hash(state);
+        self.length.hash(state);
+        // `HashMap` does not implement `Hash` because its iteration order is arbitrary
+        let mut properties: Vec<_> = self.properties.iter().collect();
+        properties.sort_unstable_by_key(|(name, _)| *name);
+        properties.hash(state);
+        self.source_position.hash(state);
+    }
+}
+
 impl Display for MarkupAttribute {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let properties = (!self.properties.is_empty())
@@ -68,3 +106,10 @@ impl Display for MarkupAttribute {
         )
     }
 }
+
+/// The byte offsets at which the graphemes of `text` start, followed by the length of `text`.
+pub(crate) fn grapheme_boundaries(text: &str) -> impl Iterator<Item = usize> + '_ {
+    text.grapheme_indices(true)
+        .map(|(index, _)| index)
+        .chain(std::iter::once(text.len()))
+}
@@ -1,18 +1,28 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner/YarnSpinner.Markup/MarkupParseResult.cs>
 //! which was split into multiple files.
 
-#[cfg(any(feature = "bevy", feature = "serde"))]
 use crate::prelude::*;
 use core::fmt::Display;
+use core::hash::{Hash, Hasher};
+use thiserror::Error;
 
 /// A value associated with a markup name.
 ///
 /// You do not create instances of this struct yourself. It is created
 /// by objects that can parse markup, such as [`Dialogue`]
+///
+/// Like [`YarnValue`](yarnspinner_core::prelude::YarnValue), this implements [`Hash`] but not [`Eq`], since [`MarkupValue::Float`] holds an `f32`.
+///
+/// Markup values convert into [`YarnValue`](yarnspinner_core::prelude::YarnValue)s with [`TryFrom`] and back with [`From`],
+/// so that properties can be passed to functions or stored in a [`VariableStorage`](crate::prelude::VariableStorage) directly.
+/// Both [`MarkupValue::Integer`] and [`MarkupValue::Float`] become a [`YarnValue::Number`](yarnspinner_core::prelude::YarnValue::Number).
+/// Integers that an `f32` cannot store exactly, e.g. most above 2^24, fail with a [`MarkupValueConversionError`] instead of being rounded.
+/// In the other direction, numbers always become a [`MarkupValue::Float`], since a Yarn number does not know whether it was an integer.
+/// Both types are kept separate because Yarn numbers make no distinction between integers and floats while markup values do, e.g. for `[select value=1 1=one /]`.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "bevy", derive(Reflect))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
 #[cfg_attr(
     all(feature = "bevy", feature = "serde"),
     reflect(Serialize, Deserialize)
@@ -28,6 +38,21 @@ pub enum MarkupValue {
     Bool(bool),
 }
 
+impl Hash for MarkupValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        match self {
+            Self::Integer(integer) => integer.hash(state),
+            // All NaNs hash the same, no matter their payload
+            Self::Float(float) if float.is_nan() => f32::NAN.to_bits().hash(state),
+            // Adding 0.0 turns -0.0 into 0.0, which compare equal and so must hash the same
+            Self::Float(float) => (float + 0.0).to_bits().hash(state),
+            Self::String(string) => string.hash(state),
+            Self::Bool(boolean) => boolean.hash(state),
+        }
+    }
+}
+
 impl MarkupValue {
     /// Returns the name of the enum variant.
     pub fn type_name(&self) -> &'static str {
@@ -80,3 +105,57 @@ impl From<bool> for MarkupValue {
         MarkupValue::Bool(b)
     }
 }
+
+/// The error returned when a [`MarkupValue::Integer`] cannot be converted into a [`YarnValue`](yarnspinner_core::prelude::YarnValue)
+/// because an `f32` cannot store it exactly.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
+#[error("Markup integer {integer} cannot be converted to a Yarn number without losing precision")]
+pub struct MarkupValueConversionError {
+    /// The integer that could not be converted.
+    pub integer: u32,
+}
+
+impl TryFrom<MarkupValue> for YarnValue {
+    type Error = MarkupValueConversionError;
+
+    fn try_from(value: MarkupValue) -> Result<Self, Self::Error> {
+        match value {
+            MarkupValue::Integer(integer) => {
+                let number = integer as f32;
+                // Compared as f64, which holds every u32 exactly, as `number as u32` saturates
+                if f64::from(number) == f64::from(integer) {
+                    Ok(YarnValue::Number(number))
+                } else {
+                    Err(MarkupValueConversionError { integer })
+                }
+            }
+            MarkupValue::Float(float) => Ok(YarnValue::Number(float)),
+            MarkupValue::String(string) => Ok(YarnValue::String(string)),
+            MarkupValue::Bool(boolean) => Ok(YarnValue::Boolean(boolean)),
+        }
+    }
+}
+
+impl TryFrom<&MarkupValue> for YarnValue {
+    type Error = MarkupValueConversionError;
+
+    fn try_from(value: &MarkupValue) -> Result<Self, Self::Error> {
+        value.clone().try_into()
+    }
+}
+
+impl From<YarnValue> for MarkupValue {
+    fn from(value: YarnValue) -> Self {
+        match value {
+            YarnValue::Number(number) => MarkupValue::Float(number),
+            YarnValue::String(string) => MarkupValue::String(string),
+            YarnValue::Boolean(boolean) => MarkupValue::Bool(boolean),
+        }
+    }
+}
+
+impl From<&YarnValue> for MarkupValue {
+    fn from(value: &YarnValue) -> Self {
+        value.clone().into()
+    }
+}
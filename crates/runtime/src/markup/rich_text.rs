@@ -0,0 +1,210 @@
+//! Converts the markup of a [`Line`] into the tags of rich text formats,
+//! so that frontends with styled text widgets can display lines without walking the attributes themselves.
+
+use crate::markup::MarkupAttribute;
+use crate::prelude::*;
+use std::cmp::Reverse;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A rich text format that [`Line::to_rich_text`] can render lines into.
+///
+/// The built-in formats are [`HtmlFormat`] and [`BbcodeFormat`]. Implement this trait to map your own attributes,
+/// e.g. `[shout]`, or to target other formats.
+pub trait RichTextFormat {
+    /// Returns the opening and closing tags that surround the text covered by `attribute`,
+    /// or [`None`] if the attribute has no representation in this format.
+    fn tags_for_attribute(&self, attribute: &MarkupAttribute) -> Option<(String, String)>;
+
+    /// Escapes plain text of the line so that it is not interpreted as tags.
+    fn escape(&self, text: &str) -> String;
+}
+
+/// Renders `[b]`, `[i]`, `[u]` and `[s]` as the HTML elements of the same name, and `[color=...]` as a `<span>` with a `color` style.
+/// Other attributes are left out.
+///
+/// ## Example
+///
+/// ```text
+/// Mae: A [color=red][b]cat[/b][/color] & a dog!
+/// ```
+///
+/// is rendered as
+///
+/// ```text
+/// Mae: A <span style="color: red"><b>cat</b></span> &amp; a dog!
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HtmlFormat;
+
+impl RichTextFormat for HtmlFormat {
+    fn tags_for_attribute(&self, attribute: &MarkupAttribute) -> Option<(String, String)> {
+        match attribute.name.as_str() {
+            name @ ("b" | "i" | "u" | "s") => Some((format!("<{name}>"), format!("</{name}>"))),
+            "color" => {
+                let color = attribute.property("color")?.to_string();
+                Some((
+                    format!("<span style=\"color: {}\">", self.escape(&color)),
+                    "</span>".to_owned(),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn escape(&self, text: &str) -> String {
+        text.chars()
+            .map(|character| match character {
+                '&' => "&amp;".to_owned(),
+                '<' => "&lt;".to_owned(),
+                '>' => "&gt;".to_owned(),
+                '"' => "&quot;".to_owned(),
+                '\'' => "&#39;".to_owned(),
+                _ => character.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Renders `[b]`, `[i]`, `[u]`, `[s]` and `[color=...]` as the BBCode tags of the same name.
+/// Other attributes are left out.
+///
+/// Brackets in the text are escaped as `[lb]` and `[rb]`, like Godot's `RichTextLabel` expects them.
+#[derive(Debug, Clone, Default)]
+pub struct BbcodeFormat;
+
+impl RichTextFormat for BbcodeFormat {
+    fn tags_for_attribute(&self, attribute: &MarkupAttribute) -> Option<(String, String)> {
+        match attribute.name.as_str() {
+            name @ ("b" | "i" | "u" | "s") => Some((format!("[{name}]"), format!("[/{name}]"))),
+            "color" => {
+                let color = attribute.property("color")?.to_string();
+                Some((
+                    format!("[color={}]", self.escape(&color)),
+                    "[/color]".to_owned(),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn escape(&self, text: &str) -> String {
+        text.chars()
+            .map(|character| match character {
+                '[' => "[lb]".to_owned(),
+                ']' => "[rb]".to_owned(),
+                _ => character.to_string(),
+            })
+            .collect()
+    }
+}
+
+impl Line {
+    /// Renders the text of this line with its attributes as tags of the given [`RichTextFormat`].
+    ///
+    /// Attributes that overlap without one containing the other, e.g. `[b]A [i]B[/b] C[/i]`, cannot be represented by nested tags.
+    /// In that case, the inner tag is closed and reopened around the end of the outer one: `<b>A <i>B</i></b><i> C</i>`.
+    /// Attributes without a length are left out.
+    pub fn to_rich_text(&self, format: &impl RichTextFormat) -> String {
+        let graphemes: Vec<_> = self.text.graphemes(true).collect();
+        let mut tagged_attributes: Vec<_> = self
+            .attributes
+            .iter()
+            .filter(|attribute| attribute.length > 0)
+            .filter_map(|attribute| {
+                let (open, close) = format.tags_for_attribute(attribute)?;
+                let start = attribute.position.min(graphemes.len());
+                let end = (attribute.position + attribute.length).min(graphemes.len());
+                Some((start..end, open, close))
+            })
+            .collect();
+        // Open enclosing attributes first so that they need to be reopened as rarely as possible
+        tagged_attributes.sort_by_key(|(range, ..)| (range.start, Reverse(range.end)));
+
+        let mut boundaries: Vec<_> = tagged_attributes
+            .iter()
+            .flat_map(|(range, ..)| [range.start, range.end])
+            .chain([0, graphemes.len()])
+            .collect();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+
+        let mut rich_text = String::new();
+        // Indices into `tagged_attributes` of the currently open tags, outermost first
+        let mut open_tags: Vec<usize> = Vec::new();
+        for segment in boundaries.windows(2) {
+            let (start, end) = (segment[0], segment[1]);
+            let is_active = |index: &usize| tagged_attributes[*index].0.contains(&start);
+
+            let still_open = open_tags
+                .iter()
+                .position(|index| !is_active(index))
+                .unwrap_or(open_tags.len());
+            for index in open_tags.drain(still_open..).rev() {
+                rich_text.push_str(&tagged_attributes[index].2);
+            }
+            for index in (0..tagged_attributes.len()).filter(is_active) {
+                if !open_tags.contains(&index) {
+                    rich_text.push_str(&tagged_attributes[index].1);
+                    open_tags.push(index);
+                }
+            }
+            rich_text.push_str(&format.escape(&graphemes[start..end].concat()));
+        }
+        for index in open_tags.into_iter().rev() {
+            rich_text.push_str(&tagged_attributes[index].2);
+        }
+        rich_text
+    }
+
+    /// Renders this line as HTML, see [`HtmlFormat`] and [`Line::to_rich_text`].
+    pub fn to_html(&self) -> String {
+        self.to_rich_text(&HtmlFormat)
+    }
+
+    /// Renders this line as BBCode, see [`BbcodeFormat`] and [`Line::to_rich_text`].
+    pub fn to_bbcode(&self) -> String {
+        self.to_rich_text(&BbcodeFormat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::LineParser;
+
+    fn line(text: &str) -> Line {
+        let markup = LineParser::new().parse_markup(text).unwrap();
+        Line {
+            id: "line:test".into(),
+            text: markup.text,
+            raw_text: text.to_owned(),
+            markup_error: None,
+            attributes: markup.attributes,
+            metadata: vec![],
+            node_name: "Start".to_owned(),
+            source_location: None,
+        }
+    }
+
+    #[test]
+    fn renders_nested_and_overlapping_attributes() {
+        let line = line("Mae: I'm a [color=red][b]cat[/b][/color] & [b]you [i]are[/b] not[/i]!");
+
+        assert_eq!(
+            "Mae: I&#39;m a <span style=\"color: red\"><b>cat</b></span> &amp; <b>you <i>are</i></b><i> not</i>!",
+            line.to_html()
+        );
+        assert_eq!(
+            "Mae: I'm a [color=red][b]cat[/b][/color] & [b]you [i]are[/i][/b][i] not[/i]!",
+            line.to_bbcode()
+        );
+    }
+
+    #[test]
+    fn escapes_text_and_leaves_out_unknown_attributes() {
+        let line = line(r"[shout]Take the \[Sword\] <now>[/shout]");
+
+        assert_eq!("Take the [Sword] &lt;now&gt;", line.to_html());
+        assert_eq!("Take the [lb]Sword[rb] <now>", line.to_bbcode());
+    }
+}
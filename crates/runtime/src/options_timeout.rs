@@ -0,0 +1,41 @@
+//! Lets writers give an options block a time limit,
+//! so that games with timed choices don't have to agree on their own conventions for it.
+
+use crate::prelude::*;
+use std::time::Duration;
+
+/// The name of the command that gives the next options block a time limit, e.g. `<<options_timeout 5>>` or `<<options_timeout 5 2>>`.
+///
+/// The first parameter is the number of seconds the player has to choose. The optional second parameter is the position of the option
+/// that should be chosen when the time runs out, counting from 1 in the order the options appear in the Yarn file, including unavailable options.
+/// The command is handled by the [`Dialogue`] itself and emits a [`DialogueEvent::OptionsTimeout`] right before the [`DialogueEvent::Options`]
+/// it applies to instead of a [`DialogueEvent::Command`].
+///
+/// ## Example
+///
+/// ```text
+/// Guard: Who goes there?
+/// <<options_timeout 3 2>>
+/// -> A friend.
+/// -> ...
+/// ```
+pub const OPTIONS_TIMEOUT_COMMAND: &str = "options_timeout";
+
+/// How long the player has to choose one of the options delivered by the following [`DialogueEvent::Options`],
+/// as set by the [`OPTIONS_TIMEOUT_COMMAND`]. Enforcing the time limit, e.g. by showing a timer and
+/// calling [`Dialogue::set_selected_option`] when it runs out, is up to the game.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct OptionsTimeout {
+    /// The time the player has to choose an option.
+    pub duration: Duration,
+
+    /// The option to select when the time runs out. If [`None`], the script leaves the choice to the game.
+    pub default_option: Option<OptionId>,
+}
@@ -0,0 +1,82 @@
+//! Provides the random functions `random`, `random_range` and `dice`
+//! backed by a random number generator that can be seeded through [`Dialogue::set_rng_seed`](crate::prelude::Dialogue::set_rng_seed).
+
+use crate::prelude::*;
+use rand::rngs::SmallRng;
+use rand::{Rng, RngCore, SeedableRng};
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The random number generator of a [`Dialogue`](crate::prelude::Dialogue), shared with the random functions in its [`Library`].
+#[derive(Clone)]
+pub(crate) struct SharedRng(Arc<Mutex<Box<dyn RngCore + Send>>>);
+
+impl SharedRng {
+    pub(crate) fn from_entropy() -> Self {
+        Self(Arc::new(Mutex::new(Box::new(SmallRng::from_entropy()))))
+    }
+
+    pub(crate) fn set(&self, rng: impl RngCore + Send + 'static) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = Box::new(rng);
+    }
+
+    pub(crate) fn set_seed(&self, seed: u64) {
+        self.set(SmallRng::seed_from_u64(seed));
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        let mut rng = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        f(rng.as_mut())
+    }
+
+    /// Registers `random`, `random_range` and `dice` in the `library`.
+    pub(crate) fn add_functions_to(&self, library: &mut Library) {
+        library
+            .add_function("random", random(self.clone()))
+            .add_function("random_range", random_range(self.clone()))
+            .add_function("dice", dice(self.clone()));
+    }
+}
+
+impl Debug for SharedRng {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SharedRng").field(&"<rng>").finish()
+    }
+}
+
+/// Returns a random number between 0 and 1, excluding 1.
+fn random(rng: SharedRng) -> yarn_fn_type! { impl Fn() -> f32 } {
+    move || rng.with(|rng| rng.gen_range(0.0..1.0))
+}
+
+/// Returns a random number between `min` and `max`. If both are integers, the result is an integer and `max` is inclusive.
+fn random_range(rng: SharedRng) -> yarn_fn_type! { impl Fn(f32, f32) -> f32 } {
+    move |min: f32, max: f32| {
+        rng.with(|rng| {
+            if let (Some(min), Some(max_inclusive)) = (min.as_int(), max.as_int()) {
+                return rng.gen_range(min..=max_inclusive) as f32;
+            }
+            rng.gen_range(min..max)
+        })
+    }
+}
+
+/// Rolls a die with the given number of sides, i.e. returns an integer between 1 and `sides`.
+fn dice(rng: SharedRng) -> yarn_fn_type! { impl Fn(u32) -> u32 } {
+    move |sides: u32| {
+        if sides == 0 {
+            return 1;
+        }
+        rng.with(|rng| rng.gen_range(1..=sides))
+    }
+}
+
+trait FloatExt: Copy {
+    fn as_int(self) -> Option<i32>;
+}
+
+impl FloatExt for f32 {
+    fn as_int(self) -> Option<i32> {
+        (self.fract() <= f32::EPSILON).then_some(self as i32)
+    }
+}
@@ -0,0 +1,194 @@
+//! Decides which of several eligible pieces of content is shown,
+//! modelled after the saliency strategies of later Yarn Spinner versions.
+//!
+//! ## Implementation notes
+//!
+//! The candidates handed to a strategy have already passed their conditions, so they carry a single score
+//! instead of the counts of passing and failing conditions.
+
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// A piece of content that a [`ContentSaliencyStrategy`] may choose, e.g. a node passed to [`Dialogue::choose_start_node`](crate::prelude::Dialogue::choose_start_node).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaliencyCandidate {
+    /// The name of the content, e.g. the name of a node.
+    pub content_id: String,
+
+    /// How well the content fits the current situation, higher being better.
+    /// For [`Dialogue::choose_start_node`](crate::prelude::Dialogue::choose_start_node), this is the number of conditions other than `always`.
+    pub score: f32,
+
+    /// How often the player has seen the content, e.g. the visit count of a node.
+    pub view_count: usize,
+}
+
+/// Decides which of several eligible pieces of content is shown, e.g. which conversation an NPC starts.
+/// Set the strategy of a [`Dialogue`](crate::prelude::Dialogue) with [`Dialogue::set_content_saliency_strategy`](crate::prelude::Dialogue::set_content_saliency_strategy).
+///
+/// The built-in strategies are [`FirstSaliencyStrategy`], [`BestSaliencyStrategy`], [`RandomBestSaliencyStrategy`] and [`LeastRecentlySeenSaliencyStrategy`].
+pub trait ContentSaliencyStrategy: Debug + Send {
+    /// Returns the candidate that should be shown, or [`None`] if none of them should.
+    /// The candidates are in the order they were passed in by the caller and are never empty.
+    fn query_best_content<'a>(
+        &mut self,
+        candidates: &'a [SaliencyCandidate],
+    ) -> Option<&'a SaliencyCandidate>;
+
+    /// Called with the candidate returned by [`ContentSaliencyStrategy::query_best_content`], so that strategies can remember what was shown.
+    fn content_was_selected(&mut self, _candidate: &SaliencyCandidate) {}
+}
+
+/// Chooses the first candidate, ignoring scores and view counts.
+#[derive(Debug, Clone, Default)]
+pub struct FirstSaliencyStrategy;
+
+impl ContentSaliencyStrategy for FirstSaliencyStrategy {
+    fn query_best_content<'a>(
+        &mut self,
+        candidates: &'a [SaliencyCandidate],
+    ) -> Option<&'a SaliencyCandidate> {
+        candidates.first()
+    }
+}
+
+/// Chooses the candidate with the highest score. Ties go to the candidate that was seen less often, then to the first one.
+///
+/// This is the default strategy of a [`Dialogue`](crate::prelude::Dialogue).
+#[derive(Debug, Clone, Default)]
+pub struct BestSaliencyStrategy;
+
+impl ContentSaliencyStrategy for BestSaliencyStrategy {
+    fn query_best_content<'a>(
+        &mut self,
+        candidates: &'a [SaliencyCandidate],
+    ) -> Option<&'a SaliencyCandidate> {
+        best_candidates(candidates).min_by_key(|candidate| candidate.view_count)
+    }
+}
+
+/// Chooses a random candidate among the ones with the highest score.
+#[derive(Debug, Clone)]
+pub struct RandomBestSaliencyStrategy {
+    rng: SmallRng,
+}
+
+impl Default for RandomBestSaliencyStrategy {
+    fn default() -> Self {
+        Self {
+            rng: SmallRng::from_entropy(),
+        }
+    }
+}
+
+impl RandomBestSaliencyStrategy {
+    /// Creates a strategy that is seeded from the operating system.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a strategy that makes the same choices every time, e.g. for tests.
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl ContentSaliencyStrategy for RandomBestSaliencyStrategy {
+    fn query_best_content<'a>(
+        &mut self,
+        candidates: &'a [SaliencyCandidate],
+    ) -> Option<&'a SaliencyCandidate> {
+        let best_candidates: Vec<_> = best_candidates(candidates).collect();
+        best_candidates.choose(&mut self.rng).copied()
+    }
+}
+
+/// Chooses the candidate among the ones with the highest score that this strategy selected longest ago.
+/// Candidates it never selected come first, in order.
+///
+/// Unlike [`BestSaliencyStrategy`], this rotates through the candidates even if their view counts are not tracked,
+/// but it only remembers the selections made through it.
+#[derive(Debug, Clone, Default)]
+pub struct LeastRecentlySeenSaliencyStrategy {
+    last_selected: HashMap<String, u64>,
+    selection_count: u64,
+}
+
+impl LeastRecentlySeenSaliencyStrategy {
+    /// Creates a strategy that has not selected anything yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ContentSaliencyStrategy for LeastRecentlySeenSaliencyStrategy {
+    fn query_best_content<'a>(
+        &mut self,
+        candidates: &'a [SaliencyCandidate],
+    ) -> Option<&'a SaliencyCandidate> {
+        best_candidates(candidates)
+            .min_by_key(|candidate| self.last_selected.get(&candidate.content_id).copied())
+    }
+
+    fn content_was_selected(&mut self, candidate: &SaliencyCandidate) {
+        self.selection_count += 1;
+        self.last_selected
+            .insert(candidate.content_id.clone(), self.selection_count);
+    }
+}
+
+/// Returns the candidates that share the highest score, in order.
+fn best_candidates(
+    candidates: &[SaliencyCandidate],
+) -> impl Iterator<Item = &SaliencyCandidate> + '_ {
+    let best_score = candidates
+        .iter()
+        .map(|candidate| candidate.score)
+        .max_by(f32::total_cmp);
+    candidates
+        .iter()
+        .filter(move |candidate| Some(candidate.score) == best_score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates() -> Vec<SaliencyCandidate> {
+        [("Greeting", 0.0, 0), ("Quest", 2.0, 3), ("Rumor", 2.0, 1)]
+            .into_iter()
+            .map(|(content_id, score, view_count)| SaliencyCandidate {
+                content_id: content_id.to_owned(),
+                score,
+                view_count,
+            })
+            .collect()
+    }
+
+    fn choose(strategy: &mut dyn ContentSaliencyStrategy) -> String {
+        let candidates = candidates();
+        let candidate = strategy.query_best_content(&candidates).unwrap();
+        strategy.content_was_selected(candidate);
+        candidate.content_id.clone()
+    }
+
+    #[test]
+    fn built_in_strategies_choose_as_documented() {
+        assert_eq!(choose(&mut FirstSaliencyStrategy), "Greeting");
+        assert_eq!(choose(&mut BestSaliencyStrategy), "Rumor");
+
+        let mut least_recently_seen = LeastRecentlySeenSaliencyStrategy::new();
+        let choices: Vec<_> = (0..3).map(|_| choose(&mut least_recently_seen)).collect();
+        assert_eq!(choices, ["Quest", "Rumor", "Quest"]);
+
+        let mut random_best = RandomBestSaliencyStrategy::from_seed(42);
+        for _ in 0..10 {
+            assert_ne!(choose(&mut random_best), "Greeting");
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! Maps instructions back to the Yarn source code they were compiled from,
+//! so that runtime errors can point to the offending line.
+
+#[cfg(any(feature = "bevy", feature = "serde"))]
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use yarnspinner_core::prelude::*;
+
+/// The place in the Yarn source code an instruction was compiled from. Part of [`DialogueError::InvalidInstruction`](crate::prelude::DialogueError::InvalidInstruction),
+/// [`Line`](crate::prelude::Line) and [`Command`](crate::prelude::Command).
+///
+/// Only available for nodes whose positions were added with [`Dialogue::add_source_positions`](crate::prelude::Dialogue::add_source_positions).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct SourceLocation {
+    /// The name of the file the instruction was compiled from.
+    pub file_name: String,
+    /// The zero-indexed position of the instruction in the file, if the compiler recorded one.
+    pub position: Option<Position>,
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let file_name = &self.file_name;
+        match self.position {
+            Some(position) => write!(
+                f,
+                "{file_name}:{}:{}",
+                position.line + 1,
+                position.character + 1
+            ),
+            None => f.write_str(file_name),
+        }
+    }
+}
+
+/// The source positions of the instructions of each node, keyed by node name.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SourceMap(HashMap<String, NodeSourcePositions>);
+
+#[derive(Debug, Clone)]
+struct NodeSourcePositions {
+    file_name: String,
+    positions: HashMap<usize, Option<Position>>,
+}
+
+impl SourceMap {
+    pub(crate) fn insert(
+        &mut self,
+        node_name: String,
+        file_name: String,
+        positions: HashMap<usize, Option<Position>>,
+    ) {
+        self.0.insert(
+            node_name,
+            NodeSourcePositions {
+                file_name,
+                positions,
+            },
+        );
+    }
+
+    pub(crate) fn locate(
+        &self,
+        node_name: &str,
+        instruction_index: usize,
+    ) -> Option<SourceLocation> {
+        let node = self.0.get(node_name)?;
+        Some(SourceLocation {
+            file_name: node.file_name.clone(),
+            position: node.positions.get(&instruction_index).copied().flatten(),
+        })
+    }
+}
@@ -0,0 +1,242 @@
+//! Keeps variables in an SQLite database, for games that need
+//! dialogue state to survive crashes and restarts without writing their own storage adapter.
+
+use crate::prelude::*;
+use log::error;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection};
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use yarnspinner_core::prelude::*;
+
+/// A [`VariableStorage`] that persists every variable to an SQLite database as soon as it is set.
+/// Requires the `sqlite` feature.
+///
+/// All variables are loaded into memory when the storage is opened and reads are served from there,
+/// so only writes touch the database. Variables are kept in a table named `yarn_variables`,
+/// which is created if it does not exist yet. Other tables in the database are left alone,
+/// so the storage can share a database with the rest of the game's save data.
+///
+/// Shallow clones share the same connection and cache.
+///
+/// ## Example
+///
+/// ```rust
+/// # use yarnspinner_runtime::prelude::*;
+/// # let path = std::env::temp_dir().join("yarnspinner_sqlite_variable_storage_doc.db");
+/// # let _ = std::fs::remove_file(&path);
+/// let mut storage = SqliteVariableStorage::open(&path).unwrap();
+/// storage.set("$gold".to_owned(), 10.into()).unwrap();
+///
+/// // After a restart
+/// let storage = SqliteVariableStorage::open(&path).unwrap();
+/// assert_eq!(storage.get("$gold").unwrap(), YarnValue::from(10));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SqliteVariableStorage {
+    connection: Arc<Mutex<Connection>>,
+    cache: Arc<RwLock<HashMap<String, YarnValue>>>,
+}
+
+impl SqliteVariableStorage {
+    /// Opens the database at `path`, creating it if it does not exist, and loads the variables stored in it.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_connection(Connection::open(path).map_err(internal_error)?)
+    }
+
+    /// Creates a storage backed by a database that only lives in memory, e.g. for tests.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory().map_err(internal_error)?)
+    }
+
+    /// Uses an already opened database, e.g. one that was configured by the game, and loads the variables stored in it.
+    pub fn from_connection(connection: Connection) -> Result<Self> {
+        connection
+            .execute(
+                "CREATE TABLE IF NOT EXISTS yarn_variables (
+                    name TEXT PRIMARY KEY NOT NULL,
+                    kind TEXT NOT NULL,
+                    value NOT NULL
+                )",
+                [],
+            )
+            .map_err(internal_error)?;
+        let cache = {
+            let mut statement = connection
+                .prepare("SELECT name, kind, value FROM yarn_variables")
+                .map_err(internal_error)?;
+            let rows = statement
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Value>(2)?,
+                    ))
+                })
+                .map_err(internal_error)?;
+            let mut cache = HashMap::new();
+            for row in rows {
+                let (name, kind, value) = row.map_err(internal_error)?;
+                cache.insert(name.clone(), from_sql_value(&name, &kind, value)?);
+            }
+            cache
+        };
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+            cache: Arc::new(RwLock::new(cache)),
+        })
+    }
+
+    fn validate_name(name: &str) -> Result<()> {
+        if name.starts_with('$') {
+            Ok(())
+        } else {
+            Err(VariableStorageError::InvalidVariableName {
+                name: name.to_string(),
+            })
+        }
+    }
+}
+
+impl VariableStorage for SqliteVariableStorage {
+    fn clone_shallow(&self) -> Box<dyn VariableStorage> {
+        Box::new(self.clone())
+    }
+
+    fn set(&mut self, name: String, value: YarnValue) -> Result<()> {
+        self.extend(HashMap::from([(name, value)]))
+    }
+
+    fn get(&self, name: &str) -> Result<YarnValue> {
+        Self::validate_name(name)?;
+        self.cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .cloned()
+            .ok_or_else(|| VariableStorageError::VariableNotFound {
+                name: name.to_string(),
+            })
+    }
+
+    fn extend(&mut self, values: HashMap<String, YarnValue>) -> Result<()> {
+        for name in values.keys() {
+            Self::validate_name(name)?;
+        }
+        let mut connection = self
+            .connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // Written in one database transaction, so that either all values are persisted or none are
+        let transaction = connection.transaction().map_err(internal_error)?;
+        {
+            let mut statement = transaction
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO yarn_variables (name, kind, value) VALUES (?1, ?2, ?3)",
+                )
+                .map_err(internal_error)?;
+            for (name, value) in &values {
+                let (kind, value) = to_sql_value(value);
+                statement
+                    .execute(params![name, kind, value])
+                    .map_err(internal_error)?;
+            }
+        }
+        transaction.commit().map_err(internal_error)?;
+        self.cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(values);
+        Ok(())
+    }
+
+    fn variables(&self) -> HashMap<String, YarnValue> {
+        self.cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn clear(&mut self) {
+        let connection = self
+            .connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Err(e) = connection.execute("DELETE FROM yarn_variables", []) {
+            error!("Failed to clear the variables in the SQLite database: {e}");
+            return;
+        }
+        self.cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+fn internal_error(error: rusqlite::Error) -> VariableStorageError {
+    VariableStorageError::InternalError {
+        error: Box::new(error),
+    }
+}
+
+fn to_sql_value(value: &YarnValue) -> (&'static str, Value) {
+    match value {
+        YarnValue::Number(number) => ("number", Value::Real(f64::from(*number))),
+        YarnValue::String(string) => ("string", Value::Text(string.clone())),
+        YarnValue::Boolean(boolean) => ("boolean", Value::Integer(i64::from(*boolean))),
+    }
+}
+
+fn from_sql_value(name: &str, kind: &str, value: Value) -> Result<YarnValue> {
+    match (kind, value) {
+        ("number", Value::Real(number)) => Ok(YarnValue::Number(number as f32)),
+        ("number", Value::Integer(number)) => Ok(YarnValue::Number(number as f32)),
+        ("string", Value::Text(string)) => Ok(YarnValue::String(string)),
+        ("boolean", Value::Integer(boolean)) => Ok(YarnValue::Boolean(boolean != 0)),
+        (kind, value) => Err(VariableStorageError::InternalError {
+            error: format!(
+                "The SQLite database stores the variable {name} as a {kind} with the value {value:?}, which is not a valid Yarn value"
+            )
+            .into(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn persists_variables_across_connections() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("save.db");
+        let mut storage = SqliteVariableStorage::open(&path).unwrap();
+        storage
+            .extend(HashMap::from([
+                ("$gold".to_owned(), 10.5.into()),
+                ("$name".to_owned(), "Alex".into()),
+                ("$met_guard".to_owned(), true.into()),
+            ]))
+            .unwrap();
+        storage.set("$gold".to_owned(), 12.into()).unwrap();
+        assert!(storage.set("gold".to_owned(), 0.into()).is_err());
+
+        let mut reopened = SqliteVariableStorage::open(&path).unwrap();
+        assert_eq!(reopened.variables(), storage.variables());
+        assert_eq!(reopened.get("$gold").unwrap(), YarnValue::from(12));
+
+        reopened.clear();
+        let reopened = SqliteVariableStorage::open(&path).unwrap();
+        assert!(reopened.variables().is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+//! Resolves references like `{@player_name}` in lines when they are delivered,
+//! so that values that depend on the device or profile never have to be stored in variables or baked into translations.
+
+use log::error;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+
+/// The named substitution providers of a [`Dialogue`](crate::prelude::Dialogue), see [`Dialogue::add_substitution_provider`](crate::prelude::Dialogue::add_substitution_provider).
+#[derive(Default)]
+pub(crate) struct SubstitutionProviders(HashMap<String, Box<dyn Fn() -> String + Send + Sync>>);
+
+impl Debug for SubstitutionProviders {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+impl SubstitutionProviders {
+    pub(crate) fn insert(
+        &mut self,
+        name: String,
+        provider: impl Fn() -> String + Send + Sync + 'static,
+    ) {
+        self.0.insert(name, Box::new(provider));
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> bool {
+        self.0.remove(name).is_some()
+    }
+
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    /// Replaces every `{@name}` in `text` by the value of the provider with that name.
+    /// References to providers that are not registered are left as they are.
+    pub(crate) fn expand(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("{@") {
+            result.push_str(&rest[..start]);
+            let reference = &rest[start..];
+            let name_len = reference[2..]
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(reference.len() - 2);
+            let name = &reference[2..2 + name_len];
+            let is_reference = name_len > 0 && reference[2 + name_len..].starts_with('}');
+            if !is_reference {
+                result.push_str("{@");
+                rest = &reference[2..];
+                continue;
+            }
+            match self.0.get(name) {
+                Some(provider) => result.push_str(&provider()),
+                None => {
+                    error!("Line refers to substitution provider \"{name}\", but no provider with that name was added to the dialogue.");
+                    result.push_str(&reference[..name_len + 3]);
+                }
+            }
+            rest = &reference[name_len + 3..];
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_registered_providers() {
+        let mut providers = SubstitutionProviders::default();
+        providers.insert("player_name".to_owned(), || "Alex".to_owned());
+        providers.insert("button_confirm".to_owned(), || "Ⓐ".to_owned());
+
+        assert_eq!(
+            providers.expand("Press {@button_confirm}, {@player_name}! {@unknown} {@ {@}"),
+            "Press Ⓐ, Alex! {@unknown} {@ {@}"
+        );
+    }
+}
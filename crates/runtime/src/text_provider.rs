@@ -4,6 +4,7 @@ use log::error;
 use std::any::Any;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::task::{Context, Poll};
 use yarnspinner_core::prelude::*;
 
 /// A trait for providing text to a [`Dialogue`](crate::prelude::Dialogue). The default implementation is [`StringTableTextProvider`], which keeps the
@@ -17,6 +18,17 @@ pub trait TextProvider: Debug + Send + Sync {
     fn accept_line_hints(&mut self, line_ids: &[LineId]);
     /// Returns the text for the given [`LineId`]. Will only be called if [`TextProvider::are_lines_available`] returns `true`.
     fn get_text(&self, id: &LineId) -> Option<String>;
+    /// Polls the text for the given [`LineId`], for providers that load text asynchronously, e.g. from a remote CMS or a large packed file,
+    /// instead of blocking in [`TextProvider::get_text`].
+    /// While this returns [`Poll::Pending`], the dialogue emits [`DialogueEvent::TextPending`](crate::prelude::DialogueEvent::TextPending)
+    /// and polls again on the next call to [`Dialogue::continue_`](crate::prelude::Dialogue::continue_).
+    /// If the dialogue is driven by a `DialogueStream`, it is woken through `context` once the text is available.
+    ///
+    /// The default implementation returns the result of [`TextProvider::get_text`] right away.
+    fn poll_text(&mut self, id: &LineId, context: &mut Context<'_>) -> Poll<Option<String>> {
+        let _ = context;
+        Poll::Ready(self.get_text(id))
+    }
     /// Sets the current language. If `None` is passed, the base language will be used.
     fn set_language(&mut self, language: Option<Language>);
     /// Returns the current language. If `None` is returned, the base language is used.
@@ -0,0 +1,78 @@
+//! Lets hosts react to specific content being delivered for the first time,
+//! e.g. to unlock achievements, see [`Dialogue::add_trigger`](crate::prelude::Dialogue::add_trigger).
+
+use crate::prelude::*;
+
+/// The content a [`Trigger`] waits for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum TriggerCondition {
+    /// The line with the given ID was delivered through a [`DialogueEvent::Line`].
+    Line(LineId),
+    /// The node with the given name was started, as reported by [`DialogueEvent::NodeStart`].
+    Node(String),
+    /// Any node with the given tag in its `tags` header was started, as reported by [`DialogueEvent::NodeStart`].
+    NodeTag(String),
+}
+
+/// A named [`TriggerCondition`] registered with [`Dialogue::add_trigger`](crate::prelude::Dialogue::add_trigger).
+/// When its condition is met for the first time, the dialogue emits a [`DialogueEvent::TriggerFired`] with the trigger's name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Hash))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct Trigger {
+    /// The name reported by [`DialogueEvent::TriggerFired`]. Unique among the triggers of a [`Dialogue`](crate::prelude::Dialogue).
+    pub name: String,
+    /// The content that fires this trigger.
+    pub condition: TriggerCondition,
+}
+
+impl Trigger {
+    /// Creates a new [`Trigger`] that fires the first time `condition` is met.
+    pub fn new(name: impl Into<String>, condition: TriggerCondition) -> Self {
+        Self {
+            name: name.into(),
+            condition,
+        }
+    }
+
+    /// The name of the variable that remembers whether the trigger named `trigger_name` has already fired.
+    ///
+    /// Like the variables tracking node visits, it lives in the dialogue's [`VariableStorage`],
+    /// so it is saved and restored alongside the rest of the game's variables.
+    #[must_use]
+    pub fn variable_name(trigger_name: &str) -> String {
+        format!("$Yarn.Internal.Trigger.{trigger_name}")
+    }
+
+    pub(crate) fn has_fired(&self, variable_storage: &dyn VariableStorage) -> bool {
+        matches!(
+            variable_storage.get(&Self::variable_name(&self.name)),
+            Ok(YarnValue::Boolean(true))
+        )
+    }
+
+    pub(crate) fn matches(&self, event: &DialogueEvent, program: Option<&Program>) -> bool {
+        match (&self.condition, event) {
+            (TriggerCondition::Line(id), DialogueEvent::Line(line)) => &line.id == id,
+            (TriggerCondition::Node(name), DialogueEvent::NodeStart(node_name)) => {
+                name == node_name
+            }
+            (TriggerCondition::NodeTag(tag), DialogueEvent::NodeStart(node_name)) => program
+                .and_then(|program| program.nodes.get(node_name))
+                .is_some_and(|node| node.tags.contains(tag)),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Lets game code read and write variables as Rust types
+//! without converting every [`YarnValue`] by hand.
+
+use crate::prelude::*;
+use std::any::{type_name, TypeId};
+
+/// Adds typed accessors to every [`VariableStorage`]. Import it to call them, e.g. on [`Dialogue::variable_storage`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use yarnspinner_runtime::prelude::*;
+/// let mut storage = MemoryVariableStorage::new();
+/// storage.set_typed("$gold", 120).unwrap();
+///
+/// let gold: i32 = storage.get_as("$gold").unwrap();
+/// assert_eq!(gold, 120);
+/// assert!(storage.get_as::<bool>("$gold").is_err());
+/// assert!(storage.set_typed("$gold", "a lot").is_err());
+/// ```
+pub trait TypedVariableStorage: VariableStorage {
+    /// Gets the value of a variable as a `T`.
+    /// Fails with a [`VariableStorageError::TypeMismatch`] if the variable does not hold a value of the Yarn type that corresponds to `T`,
+    /// e.g. when reading a boolean as an `i32`, instead of converting it like `TryFrom<YarnValue>` would.
+    /// Numbers read as integers are truncated.
+    fn get_as<T>(&self, name: &str) -> Result<T>
+    where
+        T: TryFrom<YarnValue> + 'static,
+    {
+        let value = self.get(name)?;
+        let type_mismatch = |value| VariableStorageError::TypeMismatch {
+            name: name.to_owned(),
+            expected: type_name::<T>().to_owned(),
+            value,
+        };
+        match Type::try_from(TypeId::of::<T>()) {
+            Ok(Type::Any) | Err(_) => {}
+            Ok(expected) if expected == Type::from(&value) => {}
+            Ok(_) => return Err(type_mismatch(value)),
+        }
+        T::try_from(value.clone()).map_err(|_| type_mismatch(value))
+    }
+
+    /// Sets the value of a variable, like [`VariableStorage::set`].
+    /// Fails with a [`VariableStorageError::TypeMismatch`] if the variable is already defined with a value of a different Yarn type,
+    /// since Yarn variables never change their type.
+    fn set_typed(&mut self, name: &str, value: impl Into<YarnValue>) -> Result<()> {
+        let value = value.into();
+        match self.get(name) {
+            Ok(current_value) if Type::from(&current_value) != Type::from(&value) => {
+                Err(VariableStorageError::TypeMismatch {
+                    name: name.to_owned(),
+                    expected: Type::from(&current_value).to_string(),
+                    value,
+                })
+            }
+            _ => self.set(name.to_owned(), value),
+        }
+    }
+}
+
+impl<T: VariableStorage + ?Sized> TypedVariableStorage for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_accessors_reject_other_types() {
+        let mut storage: Box<dyn VariableStorage> = Box::new(MemoryVariableStorage::new());
+        storage.set_typed("$name", "Alex").unwrap();
+        storage.set_typed("$met_guard", true).unwrap();
+
+        assert_eq!(storage.get_as::<String>("$name").unwrap(), "Alex");
+        assert!(storage.get_as::<bool>("$met_guard").unwrap());
+        assert_eq!(
+            storage.get_as::<YarnValue>("$name").unwrap(),
+            YarnValue::from("Alex")
+        );
+        assert!(matches!(
+            storage.get_as::<f32>("$met_guard"),
+            Err(VariableStorageError::TypeMismatch { expected, .. }) if expected == "f32"
+        ));
+        assert!(matches!(
+            storage.get_as::<u8>("$gold"),
+            Err(VariableStorageError::VariableNotFound { .. })
+        ));
+        assert!(storage.set_typed("$name", 3).is_err());
+        assert_eq!(storage.get_as::<String>("$name").unwrap(), "Alex");
+    }
+}
@@ -0,0 +1,72 @@
+//! Allows saving and loading all variables of a [`VariableStorage`] at once.
+
+use crate::prelude::*;
+use std::collections::HashMap;
+
+/// The values of all variables of a [`VariableStorage`] at one point in time.
+/// Created with [`VariableStorage::snapshot`] and applied with [`VariableStorage::restore`].
+///
+/// With the `serde` feature enabled, this can be stored in a save game. Since every storage backend can take and restore snapshots,
+/// a save game written with one backend can be loaded into another. Together with a [`DialogueSnapshot`], this is everything needed
+/// to resume a conversation.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "bevy", derive(Reflect))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "bevy", reflect(Debug, PartialEq, Default))]
+#[cfg_attr(
+    all(feature = "bevy", feature = "serde"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct VariableSnapshot {
+    variables: HashMap<String, YarnValue>,
+}
+
+impl VariableSnapshot {
+    /// Creates a snapshot of the given variables, e.g. to migrate variables from an older save game format.
+    pub fn new(variables: HashMap<String, YarnValue>) -> Self {
+        Self { variables }
+    }
+
+    /// The variables in this snapshot, by name.
+    pub fn variables(&self) -> &HashMap<String, YarnValue> {
+        &self.variables
+    }
+
+    /// Returns the variables in this snapshot, by name.
+    pub fn into_variables(self) -> HashMap<String, YarnValue> {
+        self.variables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restoring_replaces_all_variables() {
+        let mut storage = MemoryVariableStorage::new();
+        storage.set("$gold".to_owned(), 10.into()).unwrap();
+        storage.set("$name".to_owned(), "Alex".into()).unwrap();
+        let snapshot = storage.snapshot();
+
+        storage.set("$gold".to_owned(), 0.into()).unwrap();
+        storage.set("$met_guard".to_owned(), true.into()).unwrap();
+        storage.restore(snapshot.clone()).unwrap();
+        assert_eq!(storage.variables(), snapshot.variables().clone());
+
+        let mut other_storage =
+            CopyOnWriteVariableStorage::new(Box::new(MemoryVariableStorage::new()));
+        other_storage.restore(snapshot.clone()).unwrap();
+        assert_eq!(other_storage.snapshot(), snapshot);
+    }
+
+    #[test]
+    fn restoring_an_invalid_snapshot_keeps_the_variables() {
+        let mut storage = MemoryVariableStorage::new();
+        storage.set("$gold".to_owned(), 10.into()).unwrap();
+
+        let snapshot = VariableSnapshot::new(HashMap::from([("gold".to_owned(), 20.into())]));
+        assert!(storage.restore(snapshot).is_err());
+        assert_eq!(storage.get("$gold").unwrap(), YarnValue::from(10));
+    }
+}
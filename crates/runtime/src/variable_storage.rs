@@ -1,4 +1,5 @@
 //! Adapted from <https://github.com/YarnSpinnerTool/YarnSpinner/blob/da39c7195107d8211f21c263e4084f773b84eaff/YarnSpinner/Dialogue.cs>, which we split off into multiple files
+use crate::prelude::VariableSnapshot;
 use std::any::Any;
 use std::collections::HashMap;
 use std::fmt::Debug;
@@ -9,6 +10,10 @@ use yarnspinner_core::prelude::*;
 #[allow(missing_docs)]
 pub type Result<T> = std::result::Result<T, VariableStorageError>;
 
+/// A callback registered with [`VariableStorage::add_observer`]. It is called with the name of a variable,
+/// its previous value if it was defined, and its new value whenever the variable is set.
+pub type VariableObserver = Box<dyn Fn(&str, Option<&YarnValue>, &YarnValue) + Send + Sync>;
+
 /// Provides a mechanism for storing and retrieving instances
 /// of the [`YarnValue`] type.
 ///
@@ -38,6 +43,73 @@ pub trait VariableStorage: Debug + Send + Sync {
     fn variables(&self) -> HashMap<String, YarnValue>;
     /// Clears all variables in this variable storage.
     fn clear(&mut self);
+    /// Starts buffering all changes to this variable storage until [`VariableStorage::commit_transaction`] applies them
+    /// or [`VariableStorage::rollback_transaction`] discards them. While a transaction is in progress, reads see the buffered changes.
+    /// The transaction is shared with shallow clones of this storage.
+    ///
+    /// Transactions are opt-in: the [`Dialogue`](crate::prelude::Dialogue) never begins, commits or rolls back a transaction on its own,
+    /// so changes made by a failed step stay applied unless you wrap the step yourself.
+    /// This keeps a failed step from leaving half-applied changes behind, e.g. when a command fails in the middle of a line:
+    ///
+    /// ```rust
+    /// # use yarnspinner_runtime::prelude::*;
+    /// # fn run(dialogue: &mut Dialogue) -> yarnspinner_runtime::Result<()> {
+    /// dialogue.variable_storage_mut().begin_transaction()?;
+    /// match dialogue.continue_() {
+    ///     Ok(_events) => dialogue.variable_storage_mut().commit_transaction()?,
+    ///     Err(_error) => dialogue.variable_storage_mut().rollback_transaction()?,
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Must fail with a [`VariableStorageError::TransactionInProgress`] if a transaction is already in progress.
+    /// Storages that don't support transactions fail with a [`VariableStorageError::Unsupported`], which is what the default implementation does.
+    fn begin_transaction(&mut self) -> Result<()> {
+        Err(VariableStorageError::Unsupported {
+            operation: "transactions".to_owned(),
+        })
+    }
+    /// Applies the changes buffered since [`VariableStorage::begin_transaction`]. Observers are notified of them now.
+    /// Must fail with a [`VariableStorageError::NoTransaction`] if no transaction is in progress.
+    fn commit_transaction(&mut self) -> Result<()> {
+        Err(VariableStorageError::NoTransaction)
+    }
+    /// Discards the changes buffered since [`VariableStorage::begin_transaction`].
+    /// Must fail with a [`VariableStorageError::NoTransaction`] if no transaction is in progress.
+    fn rollback_transaction(&mut self) -> Result<()> {
+        Err(VariableStorageError::NoTransaction)
+    }
+    /// Returns `true` if a transaction begun with [`VariableStorage::begin_transaction`] is in progress.
+    fn is_in_transaction(&self) -> bool {
+        false
+    }
+    /// Registers a callback that is called whenever a variable is set through [`VariableStorage::set`] or [`VariableStorage::extend`],
+    /// e.g. so that an achievement system can react to `$quest_state` changing without polling it.
+    /// Observers are shared with shallow clones of this storage and are called after the value was stored, so they may read the storage.
+    /// Changes made during a transaction are observed when it is committed.
+    ///
+    /// Storages that don't support observers fail with a [`VariableStorageError::Unsupported`], which is what the default implementation does.
+    fn add_observer(&mut self, observer: VariableObserver) -> Result<()> {
+        let _ = observer;
+        Err(VariableStorageError::Unsupported {
+            operation: "observers".to_owned(),
+        })
+    }
+    /// Returns the values of all variables in this variable storage, e.g. to persist them in a save game.
+    fn snapshot(&self) -> VariableSnapshot {
+        VariableSnapshot::new(self.variables())
+    }
+    /// Replaces all variables in this variable storage with the ones in `snapshot`, e.g. when loading a save game.
+    /// Must fail with a [`VariableStorageError::InvalidVariableName`] without changing any variables if any of the variable names do not start with a `$`.
+    fn restore(&mut self, snapshot: VariableSnapshot) -> Result<()> {
+        let variables = snapshot.into_variables();
+        if let Some(name) = variables.keys().find(|name| !name.starts_with('$')) {
+            return Err(VariableStorageError::InvalidVariableName { name: name.clone() });
+        }
+        self.clear();
+        self.extend(variables)
+    }
     /// Gets the [`VariableStorage`] as a trait object.
     /// This allows retrieving the concrete type by downcasting, using the `downcast_ref` method available through the `Any` trait.
     fn as_any(&self) -> &dyn Any;
@@ -61,6 +133,18 @@ pub enum VariableStorageError {
     InvalidVariableName { name: String },
     #[error("Variable name {name} is not defined")]
     VariableNotFound { name: String },
+    #[error("This variable storage does not support {operation}")]
+    Unsupported { operation: String },
+    #[error("Expected a {expected} for variable {name}, but got {value:?}")]
+    TypeMismatch {
+        name: String,
+        expected: String,
+        value: YarnValue,
+    },
+    #[error("No transaction is in progress on this variable storage")]
+    NoTransaction,
+    #[error("A transaction is already in progress on this variable storage")]
+    TransactionInProgress,
     #[error("Internal variable storage error: {error}")]
     InternalError {
         error: Box<dyn std::error::Error + Send + Sync>,
@@ -74,8 +158,62 @@ impl Clone for Box<dyn VariableStorage> {
 }
 
 /// A simple concrete implementation of [`VariableStorage`] that keeps all variables in memory.
+/// Supports [`VariableStorage::add_observer`] and [`VariableStorage::begin_transaction`].
 #[derive(Debug, Clone, Default)]
-pub struct MemoryVariableStorage(Arc<RwLock<HashMap<String, YarnValue>>>);
+pub struct MemoryVariableStorage {
+    variables: Arc<RwLock<MemoryVariables>>,
+    observers: Arc<RwLock<VariableObservers>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryVariables {
+    values: HashMap<String, YarnValue>,
+    /// The changes buffered by the transaction in progress, if any.
+    transaction: Option<Transaction>,
+}
+
+#[derive(Debug, Default)]
+struct Transaction {
+    values: HashMap<String, YarnValue>,
+    /// Set when the variables were cleared during the transaction, in which case the committed values are hidden.
+    is_cleared: bool,
+}
+
+impl MemoryVariables {
+    fn get(&self, name: &str) -> Option<&YarnValue> {
+        match &self.transaction {
+            Some(transaction) if transaction.is_cleared => transaction.values.get(name),
+            Some(transaction) => transaction
+                .values
+                .get(name)
+                .or_else(|| self.values.get(name)),
+            None => self.values.get(name),
+        }
+    }
+
+    /// Sets a variable and returns its previous value, unless a transaction is in progress,
+    /// in which case the value is buffered and [`None`] is returned as nothing is observable yet.
+    fn insert(&mut self, name: String, value: YarnValue) -> Option<Option<YarnValue>> {
+        if let Some(transaction) = self.transaction.as_mut() {
+            transaction.values.insert(name, value);
+            None
+        } else {
+            Some(self.values.insert(name, value))
+        }
+    }
+}
+
+/// The observers of a [`MemoryVariableStorage`]. They are reference counted so that they can be called without holding a lock.
+#[derive(Default)]
+struct VariableObservers(Vec<Arc<dyn Fn(&str, Option<&YarnValue>, &YarnValue) + Send + Sync>>);
+
+impl Debug for VariableObservers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("VariableObservers")
+            .field(&format!("{} observers", self.0.len()))
+            .finish()
+    }
+}
 
 impl MemoryVariableStorage {
     /// Creates a new empty `MemoryVariableStorage`.
@@ -91,33 +229,123 @@ impl VariableStorage for MemoryVariableStorage {
 
     fn set(&mut self, name: String, value: YarnValue) -> Result<()> {
         Self::validate_name(&name)?;
-        self.0.write().unwrap().insert(name, value);
+        let previous_value = self
+            .variables
+            .write()
+            .unwrap()
+            .insert(name.clone(), value.clone());
+        if let Some(previous_value) = previous_value {
+            self.notify_observers(&name, previous_value.as_ref(), &value);
+        }
         Ok(())
     }
 
     fn get(&self, name: &str) -> Result<YarnValue> {
         Self::validate_name(name)?;
-        self.0.read().unwrap().get(name).cloned().ok_or_else(|| {
-            VariableStorageError::VariableNotFound {
+        self.variables
+            .read()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| VariableStorageError::VariableNotFound {
                 name: name.to_string(),
-            }
-        })
+            })
     }
 
     fn extend(&mut self, values: HashMap<String, YarnValue>) -> Result<()> {
         for name in values.keys() {
             Self::validate_name(name)?;
         }
-        self.0.write().unwrap().extend(values);
+        let changes: Vec<_> = {
+            let mut variables = self.variables.write().unwrap();
+            values
+                .into_iter()
+                .filter_map(|(name, value)| {
+                    let previous_value = variables.insert(name.clone(), value.clone())?;
+                    Some((name, previous_value, value))
+                })
+                .collect()
+        };
+        for (name, previous_value, value) in changes {
+            self.notify_observers(&name, previous_value.as_ref(), &value);
+        }
         Ok(())
     }
 
     fn variables(&self) -> HashMap<String, YarnValue> {
-        self.0.read().unwrap().clone()
+        let variables = self.variables.read().unwrap();
+        match &variables.transaction {
+            Some(transaction) if transaction.is_cleared => transaction.values.clone(),
+            Some(transaction) => {
+                let mut values = variables.values.clone();
+                values.extend(transaction.values.clone());
+                values
+            }
+            None => variables.values.clone(),
+        }
     }
 
     fn clear(&mut self) {
-        self.0.write().unwrap().clear();
+        let mut variables = self.variables.write().unwrap();
+        if let Some(transaction) = variables.transaction.as_mut() {
+            transaction.values.clear();
+            transaction.is_cleared = true;
+        } else {
+            variables.values.clear();
+        }
+    }
+
+    fn add_observer(&mut self, observer: VariableObserver) -> Result<()> {
+        self.observers.write().unwrap().0.push(Arc::from(observer));
+        Ok(())
+    }
+
+    fn begin_transaction(&mut self) -> Result<()> {
+        let mut variables = self.variables.write().unwrap();
+        if variables.transaction.is_some() {
+            return Err(VariableStorageError::TransactionInProgress);
+        }
+        variables.transaction = Some(Transaction::default());
+        Ok(())
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        let changes: Vec<_> = {
+            let mut variables = self.variables.write().unwrap();
+            let transaction = variables
+                .transaction
+                .take()
+                .ok_or(VariableStorageError::NoTransaction)?;
+            if transaction.is_cleared {
+                variables.values.clear();
+            }
+            transaction
+                .values
+                .into_iter()
+                .map(|(name, value)| {
+                    let previous_value = variables.values.insert(name.clone(), value.clone());
+                    (name, previous_value, value)
+                })
+                .collect()
+        };
+        for (name, previous_value, value) in changes {
+            self.notify_observers(&name, previous_value.as_ref(), &value);
+        }
+        Ok(())
+    }
+
+    fn rollback_transaction(&mut self) -> Result<()> {
+        self.variables
+            .write()
+            .unwrap()
+            .transaction
+            .take()
+            .map(drop)
+            .ok_or(VariableStorageError::NoTransaction)
+    }
+
+    fn is_in_transaction(&self) -> bool {
+        self.variables.read().unwrap().transaction.is_some()
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -130,6 +358,14 @@ impl VariableStorage for MemoryVariableStorage {
 }
 
 impl MemoryVariableStorage {
+    fn notify_observers(&self, name: &str, previous_value: Option<&YarnValue>, value: &YarnValue) {
+        // Cloned so that observers may register other observers
+        let observers = self.observers.read().unwrap().0.clone();
+        for observer in observers {
+            observer(name, previous_value, value);
+        }
+    }
+
     fn validate_name(name: impl AsRef<str>) -> Result<()> {
         let name = name.as_ref();
         if name.starts_with('$') {
@@ -141,3 +377,82 @@ impl MemoryVariableStorage {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn observers_see_every_change() {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let mut storage = MemoryVariableStorage::new();
+        storage.set("$quest_state".to_owned(), 0.into()).unwrap();
+        let observed_changes = changes.clone();
+        storage
+            .add_observer(Box::new(move |name, previous_value, value| {
+                observed_changes.lock().unwrap().push((
+                    name.to_owned(),
+                    previous_value.cloned(),
+                    value.clone(),
+                ));
+            }))
+            .unwrap();
+
+        // Observers are shared with shallow clones
+        let mut shallow_clone = storage.clone_shallow();
+        shallow_clone
+            .set("$quest_state".to_owned(), 1.into())
+            .unwrap();
+        storage
+            .extend(HashMap::from([("$met_guard".to_owned(), true.into())]))
+            .unwrap();
+
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec![
+                ("$quest_state".to_owned(), Some(0.into()), 1.into()),
+                ("$met_guard".to_owned(), None, true.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn transactions_buffer_changes_until_committed() {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let mut storage = MemoryVariableStorage::new();
+        storage.set("$gold".to_owned(), 10.into()).unwrap();
+        let observed_changes = changes.clone();
+        storage
+            .add_observer(Box::new(move |name, _previous_value, _value| {
+                observed_changes.lock().unwrap().push(name.to_owned());
+            }))
+            .unwrap();
+
+        storage.begin_transaction().unwrap();
+        assert!(matches!(
+            storage.begin_transaction(),
+            Err(VariableStorageError::TransactionInProgress)
+        ));
+        storage.set("$gold".to_owned(), 5.into()).unwrap();
+        storage.set("$sword".to_owned(), true.into()).unwrap();
+        assert_eq!(storage.get("$gold").unwrap(), YarnValue::from(5));
+        assert!(changes.lock().unwrap().is_empty());
+        storage.rollback_transaction().unwrap();
+        assert_eq!(storage.get("$gold").unwrap(), YarnValue::from(10));
+        assert!(!storage.contains("$sword"));
+
+        storage.begin_transaction().unwrap();
+        storage.clear();
+        storage.set("$sword".to_owned(), true.into()).unwrap();
+        assert!(!storage.contains("$gold"));
+        storage.commit_transaction().unwrap();
+        assert!(!storage.is_in_transaction());
+        assert_eq!(storage.variables().len(), 1);
+        assert_eq!(*changes.lock().unwrap(), vec!["$sword".to_owned()]);
+        assert!(matches!(
+            storage.commit_transaction(),
+            Err(VariableStorageError::NoTransaction)
+        ));
+    }
+}
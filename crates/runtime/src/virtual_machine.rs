@@ -3,24 +3,81 @@
 //! ## Implementation Notes
 //! The `Operand` extensions and the `Operator` enum were moved into upstream crates to make them not depend on the runtime.
 
-pub(crate) use self::{execution_state::*, state::*};
-use crate::markup::{LineParser, ParsedMarkup};
+pub(crate) use self::{
+    execution_state::*, invalid_instruction::*, pending_function::*, rewind_history::*, state::*,
+};
+use crate::markup::{
+    AttributeMarkerProcessor, LineParser, MarkupStyles, MarkupWhitespaceTrimming, ParsedMarkup,
+};
 use crate::prelude::*;
 use crate::Result;
 use log::*;
+use std::any::Any;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
+use std::sync::Arc;
+use std::task::{Poll, Waker};
+use std::time::{Duration, Instant};
 use yarnspinner_core::prelude::OpCode;
 use yarnspinner_core::prelude::*;
 
 mod execution_state;
+mod invalid_instruction;
+mod pending_function;
+mod rewind_history;
 mod state;
 
+/// The prefix of the variables generated by [`Library::generate_unique_visited_variable_for_node`].
+const VISIT_TRACKING_PREFIX: &str = "$Yarn.Internal.Visiting.";
+
 #[derive(Debug)]
 pub(crate) struct VirtualMachine {
     pub(crate) library: Library,
-    pub(crate) program: Option<Program>,
+    pub(crate) program: Option<Arc<Program>>,
     pub(crate) variable_storage: Box<dyn VariableStorage>,
     pub(crate) line_hints_enabled: bool,
+    pub(crate) line_hint_lookahead: usize,
+    pub(crate) variable_change_events_enabled: bool,
+    /// The maximum number of instructions a single call to [`VirtualMachine::continue_`] may run.
+    pub(crate) instruction_limit: Option<usize>,
+    pub(crate) panic_on_error: bool,
+    pub(crate) conversion_failure_behavior: ConversionFailureBehavior,
+    pub(crate) rewind_history: RewindHistory,
+    pub(crate) history_enabled: bool,
+    pub(crate) metrics_enabled: bool,
+    pub(crate) lenient_markup: bool,
+    pub(crate) metrics: DialogueMetrics,
+    /// The value received by functions through a [`DialogueContext`] parameter.
+    pub(crate) context: Option<Arc<dyn Any + Send + Sync>>,
+    pub(crate) history: Vec<HistoryEntry>,
+    pub(crate) breakpoints: HashSet<Breakpoint>,
+    pub(crate) instruction_handlers: HashMap<i32, Box<dyn InstructionHandler>>,
+    pub(crate) triggers: Vec<Trigger>,
+    pub(crate) page_measurement: Option<PageMeasurement>,
+    pub(crate) substitution_providers: SubstitutionProviders,
+    pub(crate) source_map: SourceMap,
+    /// The metadata added with [`Dialogue::add_line_metadata`], by line.
+    pub(crate) line_metadata: HashMap<LineId, Vec<String>>,
+    /// The declarations added with [`Dialogue::add_variable_declarations`], by variable name.
+    pub(crate) variable_declarations: HashMap<String, VariableDeclaration>,
+    /// The names of the markers whose processors were added with [`Dialogue::add_markup_processor`], so that forks can take them over.
+    custom_markup_processors: HashSet<String>,
+    /// Set when the dialogue paused at a breakpoint, so that resuming does not immediately hit it again.
+    paused_at_breakpoint: bool,
+    pending_function: Option<PendingFunction>,
+    /// The waker that pending async functions are polled with, so that whoever drives the dialogue asynchronously is notified when they finish.
+    pub(crate) waker: Option<Waker>,
+    /// The span of the node currently running. Entered while its instructions run, so that all events they emit belong to it.
+    #[cfg(feature = "tracing")]
+    node_span: tracing::Span,
+    /// Set by the [`OPTIONS_TIMEOUT_COMMAND`] until the next options block is shown.
+    options_timeout: Option<OptionsTimeout>,
+    /// The pages of the current line that have not been delivered yet.
+    pending_pages: VecDeque<Line>,
+    /// The line that was delivered last and its substitutions, until the dialogue continues past it.
+    displayed_line: Option<(LineId, Vec<String>)>,
+    /// The substitutions of each of the [`State::current_options`], so that they can be delivered again in another language.
+    option_substitutions: Vec<Vec<String>>,
     current_node_name: Option<String>,
     state: State,
     execution_state: ExecutionState,
@@ -29,6 +86,7 @@ pub(crate) struct VirtualMachine {
     line_parser: LineParser,
     text_provider: Box<dyn TextProvider>,
     language_code: Option<Language>,
+    base_language: Option<Language>,
 }
 
 impl Iterator for VirtualMachine {
@@ -53,7 +111,9 @@ impl VirtualMachine {
             variable_storage,
             line_parser,
             text_provider,
+            custom_markup_processors: Default::default(),
             language_code: Default::default(),
+            base_language: Default::default(),
             program: Default::default(),
             current_node_name: Default::default(),
             state: Default::default(),
@@ -61,6 +121,35 @@ impl VirtualMachine {
             current_node: Default::default(),
             batched_events: Default::default(),
             line_hints_enabled: Default::default(),
+            line_hint_lookahead: Default::default(),
+            variable_change_events_enabled: Default::default(),
+            instruction_limit: Default::default(),
+            panic_on_error: Default::default(),
+            conversion_failure_behavior: Default::default(),
+            rewind_history: Default::default(),
+            history_enabled: Default::default(),
+            metrics_enabled: Default::default(),
+            lenient_markup: Default::default(),
+            metrics: Default::default(),
+            context: Default::default(),
+            history: Default::default(),
+            breakpoints: Default::default(),
+            instruction_handlers: Default::default(),
+            triggers: Default::default(),
+            page_measurement: Default::default(),
+            substitution_providers: Default::default(),
+            source_map: Default::default(),
+            line_metadata: Default::default(),
+            variable_declarations: Default::default(),
+            options_timeout: Default::default(),
+            pending_pages: Default::default(),
+            displayed_line: Default::default(),
+            option_substitutions: Default::default(),
+            paused_at_breakpoint: Default::default(),
+            pending_function: Default::default(),
+            waker: Default::default(),
+            #[cfg(feature = "tracing")]
+            node_span: tracing::Span::none(),
         }
     }
 
@@ -80,16 +169,77 @@ impl VirtualMachine {
         self.variable_storage.as_mut()
     }
 
+    /// Returns the value of a variable, falling back to its initial value in the program if it was never set.
+    pub(crate) fn variable_value(&self, name: &str) -> Option<YarnValue> {
+        self.variable_storage.get(name).ok().or_else(|| {
+            let initial_value = self.program.as_ref()?.initial_values.get(name)?;
+            Some(initial_value.clone().into())
+        })
+    }
+
     pub(crate) fn set_language_code(&mut self, language_code: impl Into<Option<Language>>) {
         let language_code = language_code.into();
         self.language_code.clone_from(&language_code);
-        self.line_parser.set_language_code(language_code.clone());
+        self.line_parser.set_language_code(self.markup_language());
         self.text_provider.set_language(language_code);
     }
 
+    pub(crate) fn base_language(&self) -> Option<&Language> {
+        self.base_language.as_ref()
+    }
+
+    pub(crate) fn set_base_language(&mut self, base_language: impl Into<Option<Language>>) {
+        self.base_language = base_language.into();
+        self.line_parser.set_language_code(self.markup_language());
+    }
+
+    pub(crate) fn add_markup_processor(
+        &mut self,
+        attribute_name: String,
+        mut processor: Box<dyn AttributeMarkerProcessor>,
+    ) {
+        processor.set_language_code(self.markup_language());
+        self.custom_markup_processors.insert(attribute_name.clone());
+        self.line_parser
+            .set_marker_processor(attribute_name, processor);
+    }
+
+    pub(crate) fn set_markup_styles(&mut self, styles: MarkupStyles) {
+        self.line_parser.set_styles(styles);
+    }
+
+    pub(crate) fn markup_styles(&self) -> &MarkupStyles {
+        self.line_parser.styles()
+    }
+
+    pub(crate) fn set_markup_whitespace_trimming(
+        &mut self,
+        whitespace_trimming: MarkupWhitespaceTrimming,
+    ) {
+        self.line_parser
+            .set_whitespace_trimming(whitespace_trimming);
+    }
+
+    pub(crate) fn markup_whitespace_trimming(&self) -> MarkupWhitespaceTrimming {
+        self.line_parser.whitespace_trimming()
+    }
+
+    /// The language used for markers such as `[plural]`. Lines in the base language are parsed with the base language's rules.
+    fn markup_language(&self) -> Option<Language> {
+        self.language_code
+            .clone()
+            .or_else(|| self.base_language.clone())
+    }
+
     pub(crate) fn reset_state(&mut self) {
         self.state = State::default();
         self.current_node_name = None;
+        self.paused_at_breakpoint = false;
+        self.pending_function = None;
+        self.pending_pages.clear();
+        self.displayed_line = None;
+        self.option_substitutions.clear();
+        self.options_timeout = None;
     }
 
     pub(crate) fn set_execution_state(&mut self, execution_state: ExecutionState) -> &mut Self {
@@ -117,6 +267,10 @@ impl VirtualMachine {
         self.reset_state();
 
         self.current_node_name = Some(node_name.clone());
+        #[cfg(feature = "tracing")]
+        {
+            self.node_span = tracing::info_span!("yarn_node", node = %node_name);
+        }
 
         self.batched_events
             .push(DialogueEvent::NodeStart(node_name));
@@ -128,33 +282,17 @@ impl VirtualMachine {
     }
 
     fn send_line_hints(&mut self) {
-        // Create a list; we will never have more lines and options
-        // than total instructions, so that's a decent capacity for
-        // the list
-        // [sic] TODO: maybe this list could be reused to save on allocations?
-
-        let string_ids: Vec<_> = self
-            .current_node
-            .as_ref()
-            .unwrap()
-            .instructions
-            .iter()
-            // Loop over every instruction and find the ones that run a
-            // line or add an option; these are the two instructions
-            // that will signal a line can appear to the player
-            .filter_map(|instruction| {
-                let opcode: OpCode = instruction.opcode.try_into().unwrap();
-                [OpCode::RunLine, OpCode::AddOption]
-                    .contains(&opcode)
-                    .then(|| {
-                        // Both RunLine and AddOption have the string ID
-                        // they want to show as their first operand, so
-                        // store that
-                        let id: String = instruction.operands[0].clone().try_into().unwrap();
-                        LineId(id)
-                    })
-            })
-            .collect();
+        let Some(current_node) = self.current_node.as_ref() else {
+            return;
+        };
+        let string_ids = match self.program.as_deref() {
+            Some(program) if self.line_hint_lookahead > 0 => line_hints_for_reachable_nodes(
+                program,
+                &current_node.name,
+                Some(self.line_hint_lookahead),
+            ),
+            _ => line_hints_for_node(current_node),
+        };
         self.text_provider.accept_line_hints(&string_ids);
         self.batched_events
             .push(DialogueEvent::LineHints(string_ids));
@@ -196,27 +334,303 @@ impl VirtualMachine {
     ///
     pub(crate) fn continue_(&mut self) -> crate::Result<Vec<DialogueEvent>> {
         self.assert_can_continue()?;
+        if self.deliver_next_page() {
+            return self.take_batched_events();
+        }
+        self.displayed_line = None;
         self.set_execution_state(ExecutionState::Running);
+        self.poll_pending_function();
 
+        let mut instructions_run = 0;
         while self.execution_state == ExecutionState::Running {
-            let current_node = self.current_node.clone().unwrap();
-            let current_instruction = &current_node.instructions[self.state.program_counter];
-            self.run_instruction(current_instruction)?;
-            // ## Implementation note
-            // The original increments the program counter here, but that leads to intentional underflow on [`OpCode::RunNode`],
-            // so we do the incrementation in [`VirtualMachine::run_instruction`] instead.
-
-            if self.state.program_counter < current_node.instructions.len() {
-                continue;
+            if self.pause_at_breakpoint() {
+                break;
             }
+            if let Some(limit) = self.instruction_limit {
+                if instructions_run == limit {
+                    return Err(trace_error(DialogueError::InstructionLimitExceeded {
+                        limit,
+                        node_name: self.current_node_name.clone().unwrap_or_default(),
+                    }));
+                }
+            }
+            self.run_current_instruction()?;
+            instructions_run += 1;
+        }
+        self.record_rewind_entry();
+        self.take_batched_events()
+    }
 
-            self.batched_events
-                .push(DialogueEvent::NodeComplete(current_node.name.clone()));
-            self.set_execution_state(ExecutionState::Stopped);
-            self.batched_events.push(DialogueEvent::DialogueComplete);
-            debug!("Run complete.");
+    /// Runs only the next instruction, ignoring breakpoints. If it does not cause the dialogue to wait or stop on its own,
+    /// the dialogue waits for the next call to [`VirtualMachine::continue_`] or [`VirtualMachine::step_instruction`].
+    pub(crate) fn step_instruction(&mut self) -> crate::Result<Vec<DialogueEvent>> {
+        self.assert_can_continue()?;
+        if self.deliver_next_page() {
+            return self.take_batched_events();
+        }
+        self.displayed_line = None;
+        self.set_execution_state(ExecutionState::Running);
+        self.paused_at_breakpoint = false;
+        if !self.poll_pending_function() {
+            return self.take_batched_events();
+        }
+        self.run_current_instruction()?;
+        if self.execution_state == ExecutionState::Running {
+            self.set_execution_state(ExecutionState::WaitingForContinue);
+        }
+        self.take_batched_events()
+    }
+
+    /// Prepares the line or options currently shown again, e.g. after the language changed.
+    /// A line that was split into pages starts over at its first page.
+    pub(crate) fn redeliver_displayed_content(&mut self) -> crate::Result<Vec<DialogueEvent>> {
+        if self.execution_state == ExecutionState::WaitingOnOptionSelection {
+            let line_ids: Vec<_> = self
+                .state
+                .current_options
+                .iter()
+                .map(|option| option.line.id.clone())
+                .collect();
+            let lines = line_ids
+                .into_iter()
+                .enumerate()
+                .map(|(index, line_id)| {
+                    let substitutions = self
+                        .option_substitutions
+                        .get(index)
+                        .cloned()
+                        .unwrap_or_default();
+                    self.prepare_line(line_id, &substitutions)
+                })
+                .collect::<crate::Result<Vec<_>>>()?;
+            for (option, line) in self.state.current_options.iter_mut().zip(lines) {
+                // The options were added by earlier instructions
+                option.line = Line {
+                    source_location: option.line.source_location.take(),
+                    ..line
+                };
+            }
+            return Ok(vec![DialogueEvent::Options(
+                self.state.current_options.clone(),
+            )]);
+        }
+        let Some((line_id, substitutions)) = self.displayed_line.clone() else {
+            return Ok(Vec::new());
+        };
+        let mut line = self.prepare_line(line_id, &substitutions)?;
+        // The program counter already moved past the line's instruction
+        line.source_location =
+            self.source_location_of(self.state.program_counter.saturating_sub(1));
+        let line = self.split_into_pages(line);
+        Ok(vec![DialogueEvent::Line(line)])
+    }
+
+    pub(crate) fn has_pending_pages(&self) -> bool {
+        !self.pending_pages.is_empty()
+    }
+
+    /// Delivers the next page of a line that was split by [`VirtualMachine::split_into_pages`], if any.
+    /// Returns `true` if a page was delivered, in which case the dialogue waits for the next continue.
+    fn deliver_next_page(&mut self) -> bool {
+        let Some(page) = self.pending_pages.pop_front() else {
+            return false;
+        };
+        self.batched_events.push(DialogueEvent::Line(page));
+        self.set_execution_state(ExecutionState::WaitingForContinue);
+        true
+    }
+
+    /// Returns the first page of `line` if a [`PageMeasurement`] is set and keeps the remaining pages for the following continues.
+    fn split_into_pages(&mut self, line: Line) -> Line {
+        let Some(page_measurement) = self.page_measurement.as_ref() else {
+            return line;
+        };
+        let mut pages: VecDeque<_> = line.paginate(|text| page_measurement.fits(text)).into();
+        // Only the last page of a line comes right before the options that follow it
+        let page_count = pages.len();
+        for page in pages.iter_mut().take(page_count - 1) {
+            page.metadata.retain(|tag| tag != "lastline");
         }
-        Ok(std::mem::take(&mut self.batched_events))
+        let first_page = pages
+            .pop_front()
+            .expect("Paginating a line always returns at least one page");
+        self.pending_pages = pages;
+        first_page
+    }
+
+    /// Returns the events batched since the last call, each followed by a [`DialogueEvent::TriggerFired`] for every [`Trigger`] it fired.
+    fn take_batched_events(&mut self) -> crate::Result<Vec<DialogueEvent>> {
+        let batched_events = std::mem::take(&mut self.batched_events);
+        if self.triggers.is_empty() {
+            return Ok(batched_events);
+        }
+        let mut events = Vec::with_capacity(batched_events.len());
+        for event in batched_events {
+            let fired_triggers: Vec<_> = self
+                .triggers
+                .iter()
+                .filter(|trigger| trigger.matches(&event, self.program.as_deref()))
+                .filter(|trigger| !trigger.has_fired(self.variable_storage.as_ref()))
+                .cloned()
+                .collect();
+            events.push(event);
+            for trigger in fired_triggers {
+                self.variable_storage
+                    .set(Trigger::variable_name(&trigger.name), true.into())?;
+                events.push(DialogueEvent::TriggerFired(trigger.name));
+            }
+        }
+        Ok(events)
+    }
+
+    /// Pushes the result of a pending async function onto the stack if it is available.
+    /// Otherwise, emits [`DialogueEvent::FunctionPending`] and makes the dialogue wait. Returns `true` if nothing is pending anymore.
+    fn poll_pending_function(&mut self) -> bool {
+        let Some(pending_function) = self.pending_function.as_mut() else {
+            return true;
+        };
+        match pending_function.poll(self.waker.as_ref()) {
+            Poll::Ready(value) => {
+                let return_type = pending_function.return_type.clone();
+                self.pending_function = None;
+                self.state.push(InternalValue {
+                    raw_value: value,
+                    r#type: return_type,
+                });
+                true
+            }
+            Poll::Pending => {
+                let function_name = pending_function.function_name.clone();
+                self.batched_events
+                    .push(DialogueEvent::FunctionPending(function_name));
+                self.set_execution_state(ExecutionState::WaitingForContinue);
+                false
+            }
+        }
+    }
+
+    fn run_current_instruction(&mut self) -> crate::Result<()> {
+        #[cfg(feature = "tracing")]
+        let _node_span = self.node_span.clone().entered();
+        let current_node = self
+            .current_node
+            .clone()
+            .ok_or(DialogueError::NoNodeSelectedOnContinue)?;
+        let instruction_index = self.state.program_counter;
+        let current_instruction = current_node
+            .instructions
+            .get(instruction_index)
+            .ok_or_else(|| {
+                self.locate_invalid_instruction(
+                    instruction_index,
+                    DialogueError::from(InvalidInstruction::new(
+                        "The program counter points past the end of the node",
+                    )),
+                )
+            })
+            .map_err(trace_error)?;
+        let start = self.metrics_enabled.then(Instant::now);
+        let result = self.run_instruction(current_instruction);
+        if let Some(start) = start {
+            self.metrics
+                .record_instruction(&current_node.name, start.elapsed());
+        }
+        result
+            .map_err(|error| self.locate_invalid_instruction(instruction_index, error))
+            .map_err(trace_error)?;
+        // ## Implementation note
+        // The original increments the program counter here, but that leads to intentional underflow on [`OpCode::RunNode`],
+        // so we do the incrementation in [`VirtualMachine::run_instruction`] instead.
+
+        if self.state.program_counter < current_node.instructions.len() {
+            return Ok(());
+        }
+
+        self.batched_events
+            .push(DialogueEvent::NodeComplete(current_node.name.clone()));
+        self.set_execution_state(ExecutionState::Stopped);
+        self.batched_events.push(DialogueEvent::DialogueComplete);
+        debug!("Run complete.");
+        Ok(())
+    }
+
+    /// Fills in where a [`DialogueError::InvalidInstruction`] happened. Other errors are returned unchanged.
+    fn locate_invalid_instruction(
+        &self,
+        instruction_index: usize,
+        error: DialogueError,
+    ) -> DialogueError {
+        let DialogueError::InvalidInstruction { reason, .. } = error else {
+            return error;
+        };
+        let node_name = self.current_node_name.clone().unwrap_or_default();
+        DialogueError::InvalidInstruction {
+            location: self.source_map.locate(&node_name, instruction_index),
+            node_name,
+            instruction_index,
+            reason,
+        }
+    }
+
+    /// Pauses the dialogue if the next instruction has a breakpoint, unless the dialogue is just resuming from that breakpoint.
+    fn pause_at_breakpoint(&mut self) -> bool {
+        if std::mem::take(&mut self.paused_at_breakpoint) || self.breakpoints.is_empty() {
+            return false;
+        }
+        let Some(node_name) = self.current_node_name.clone() else {
+            return false;
+        };
+        let breakpoint = Breakpoint::new(node_name, self.state.program_counter);
+        if !self.breakpoints.contains(&breakpoint) {
+            return false;
+        }
+        debug!("Hit breakpoint {breakpoint:?}");
+        self.batched_events
+            .push(DialogueEvent::BreakpointHit(breakpoint));
+        self.set_execution_state(ExecutionState::WaitingForContinue);
+        self.paused_at_breakpoint = true;
+        true
+    }
+
+    pub(crate) fn current_instruction(&self) -> Option<&Instruction> {
+        self.current_node_name.as_ref()?;
+        self.current_node
+            .as_ref()?
+            .instructions
+            .get(self.state.program_counter)
+    }
+
+    pub(crate) fn program_counter(&self) -> Option<usize> {
+        self.current_node_name
+            .as_ref()
+            .map(|_| self.state.program_counter)
+    }
+
+    pub(crate) fn stack(&self) -> Vec<YarnValue> {
+        self.state.stack.iter().cloned().map(Into::into).collect()
+    }
+
+    /// Remembers the point the dialogue is now waiting at so that [`VirtualMachine::rewind`] can return to it.
+    fn record_rewind_entry(&mut self) {
+        if self.rewind_history.capacity == 0 || self.paused_at_breakpoint {
+            return;
+        }
+        let Some(snapshot) = self.snapshot() else {
+            return;
+        };
+        let Some(event) = self.batched_events.iter().rev().find(|event| {
+            matches!(
+                event,
+                DialogueEvent::Line(_) | DialogueEvent::Command(_) | DialogueEvent::Options(_)
+            )
+        }) else {
+            return;
+        };
+        self.rewind_history.push(RewindEntry {
+            snapshot,
+            variables: self.variable_storage.variables(),
+            event: event.clone(),
+        });
     }
 
     pub(crate) fn parse_markup(&mut self, line: &str) -> crate::markup::Result<ParsedMarkup> {
@@ -261,9 +675,17 @@ impl VirtualMachine {
         // We no longer need the accumulated list of options; clear it
         // so that it's ready for the next one
         self.state.current_options.clear();
+        self.option_substitutions.clear();
 
         // We're no longer in the WaitingForOptions state; we are now waiting for our game to let us continue
         self.set_execution_state(ExecutionState::WaitingForContinue);
+
+        if let Some(HistoryEntry::Options {
+            selected_option, ..
+        }) = self.history.last_mut()
+        {
+            *selected_option = Some(selected_option_id);
+        }
         Ok(())
     }
 
@@ -279,39 +701,223 @@ impl VirtualMachine {
         self.current_node_name.clone()
     }
 
+    pub(crate) fn snapshot(&self) -> Option<DialogueSnapshot> {
+        if self.pending_function.is_some() {
+            return None;
+        }
+        if !matches!(
+            self.execution_state,
+            ExecutionState::WaitingForContinue | ExecutionState::WaitingOnOptionSelection
+        ) {
+            return None;
+        }
+        let current_node = self.current_node.as_ref()?;
+        Some(DialogueSnapshot {
+            node_name: current_node.name.clone(),
+            instruction_count: current_node.instructions.len(),
+            state: self.state.clone(),
+            execution_state: self.execution_state,
+        })
+    }
+
+    /// Copies the execution state and settings to `fork`, see `Dialogue::fork`.
+    /// The library, variable storage and text provider are set up by the caller.
+    pub(crate) fn fork_into(&self, fork: &mut VirtualMachine) -> Result<()> {
+        if let Some(pending_function) = &self.pending_function {
+            return Err(DialogueError::ForkWhileFunctionPending {
+                function_name: pending_function.function_name.clone(),
+            });
+        }
+        fork.program = self.program.clone();
+        fork.line_hints_enabled = self.line_hints_enabled;
+        fork.line_hint_lookahead = self.line_hint_lookahead;
+        fork.variable_change_events_enabled = self.variable_change_events_enabled;
+        fork.instruction_limit = self.instruction_limit;
+        fork.panic_on_error = self.panic_on_error;
+        fork.conversion_failure_behavior = self.conversion_failure_behavior;
+        fork.metrics_enabled = self.metrics_enabled;
+        fork.lenient_markup = self.lenient_markup;
+        fork.context = self.context.clone();
+        fork.breakpoints = self.breakpoints.clone();
+        fork.triggers = self.triggers.clone();
+        fork.source_map = self.source_map.clone();
+        fork.line_metadata = self.line_metadata.clone();
+        fork.variable_declarations = self.variable_declarations.clone();
+        fork.set_base_language(self.base_language.clone());
+        for attribute_name in &self.custom_markup_processors {
+            if let Some(processor) = self.line_parser.marker_processor(attribute_name) {
+                fork.add_markup_processor(attribute_name.clone(), processor.clone_box());
+            }
+        }
+
+        fork.paused_at_breakpoint = self.paused_at_breakpoint;
+        fork.pending_pages = self.pending_pages.clone();
+        fork.displayed_line = self.displayed_line.clone();
+        fork.option_substitutions = self.option_substitutions.clone();
+        fork.options_timeout = self.options_timeout.clone();
+        fork.current_node_name = self.current_node_name.clone();
+        fork.state = self.state.clone();
+        fork.execution_state = self.execution_state;
+        fork.current_node = self.current_node.clone();
+        fork.batched_events = self.batched_events.clone();
+        Ok(())
+    }
+
+    pub(crate) fn restore_snapshot(
+        &mut self,
+        snapshot: DialogueSnapshot,
+    ) -> Result<Vec<DialogueEvent>> {
+        let node = self.get_node_from_name(&snapshot.node_name)?;
+        if node.instructions.len() != snapshot.instruction_count
+            || snapshot.state.program_counter > node.instructions.len()
+        {
+            return Err(DialogueError::IncompatibleSnapshot {
+                node_name: snapshot.node_name,
+            });
+        }
+        self.current_node = Some(node.clone());
+        self.current_node_name = Some(snapshot.node_name);
+        self.state = snapshot.state;
+        self.execution_state = snapshot.execution_state;
+        self.batched_events.clear();
+        self.rewind_history.clear();
+        self.paused_at_breakpoint = false;
+        self.pending_function = None;
+        self.pending_pages.clear();
+        // Substitutions are not part of snapshots
+        self.displayed_line = None;
+        self.option_substitutions.clear();
+
+        let events = if self.execution_state == ExecutionState::WaitingOnOptionSelection {
+            vec![DialogueEvent::Options(self.state.current_options.clone())]
+        } else {
+            vec![]
+        };
+        Ok(events)
+    }
+
+    pub(crate) fn rewind(&mut self, steps: usize) -> Result<Vec<DialogueEvent>> {
+        let available_steps = self.rewind_history.available_steps();
+        let entry =
+            self.rewind_history
+                .entry(steps)
+                .cloned()
+                .ok_or(DialogueError::RewindOutOfRange {
+                    steps,
+                    available_steps,
+                })?;
+        // Keep the rewind history intact, restoring a snapshot would otherwise clear it.
+        // It is only shortened once the snapshot was restored, so a failed rewind can be retried.
+        let mut rewind_history = std::mem::take(&mut self.rewind_history);
+        let result = self.restore_snapshot(entry.snapshot);
+        if result.is_ok() {
+            rewind_history.drop_recent(steps);
+        }
+        self.rewind_history = rewind_history;
+        result?;
+
+        self.restore_variables(entry.variables)?;
+        Ok(vec![entry.event])
+    }
+
+    /// Sets the variables that changed since `variables` were remembered back to their remembered values.
+    /// The storage is only cleared if variables were added since, as there is no other way to remove them.
+    fn restore_variables(&mut self, variables: HashMap<String, YarnValue>) -> Result<()> {
+        let current_variables = self.variable_storage.variables();
+        if current_variables
+            .keys()
+            .any(|name| !variables.contains_key(name))
+        {
+            self.variable_storage.clear();
+            self.variable_storage.extend(variables)?;
+        } else {
+            let changed_variables = variables
+                .into_iter()
+                .filter(|(name, value)| current_variables.get(name) != Some(value))
+                .collect();
+            self.variable_storage.extend(changed_variables)?;
+        }
+        Ok(())
+    }
+
+    fn run_extension_instruction(&mut self, instruction: &Instruction) -> crate::Result<()> {
+        let opcode = instruction.opcode;
+        let node_name = self.current_node_name()?;
+        let Some(handler) = self.instruction_handlers.get_mut(&opcode) else {
+            return Err(DialogueError::UnknownInstruction { opcode, node_name });
+        };
+        let mut context = InstructionContext::new(
+            &node_name,
+            &mut self.state.stack,
+            self.variable_storage.as_mut(),
+            &mut self.batched_events,
+        );
+        let result = handler.execute(instruction, &mut context);
+        let is_waiting_for_continue = context.is_waiting_for_continue();
+        result.map_err(|source| DialogueError::InstructionHandlerError {
+            opcode,
+            node_name,
+            source,
+        })?;
+
+        if is_waiting_for_continue {
+            self.set_execution_state(ExecutionState::WaitingForContinue);
+        }
+        self.state.program_counter += 1;
+        Ok(())
+    }
+
     /// ## Implementation note
     ///
     /// Increments the program counter here instead of in `continue_` for cleaner code
     fn run_instruction(&mut self, instruction: &Instruction) -> crate::Result<()> {
-        let opcode: OpCode = instruction.opcode.try_into().unwrap();
+        let Ok(opcode) = OpCode::try_from(instruction.opcode) else {
+            return self.run_extension_instruction(instruction);
+        };
         match opcode {
             OpCode::JumpTo => {
                 // Jumps to a named label
-                let label_name: String = instruction.read_operand(0);
-                self.state.program_counter = self.find_instruction_point_for_label(&label_name);
+                let label_name: String = read_operand(instruction, 0)?;
+                self.state.program_counter = self.find_instruction_point_for_label(&label_name)?;
             }
             OpCode::Jump => {
                 // Jumps to a label whose name is on the stack.
-                let jump_destination: String = self.state.peek();
+                let jump_destination: String = self.state.peek()?;
                 self.state.program_counter =
-                    self.find_instruction_point_for_label(&jump_destination);
+                    self.find_instruction_point_for_label(&jump_destination)?;
             }
             OpCode::RunLine => {
                 // Looks up a string from the string table and passes it to the client as a line
 
-                let string_id: String = instruction.read_operand(0);
+                let string_id: String = read_operand(instruction, 0)?;
                 let string_id: LineId = string_id.into();
+                let Poll::Ready(line_text) = self.poll_text(&string_id) else {
+                    return Ok(());
+                };
 
                 // The second operand, if provided (compilers prior
                 // to v1.1 don't include it), indicates the number
                 // of expressions in the line. We need to pop these
                 // values off the stack and deliver them to the
                 // line handler.
-                assert_up_to_date_compiler(instruction.operands.len() >= 2);
+                ensure_up_to_date_compiler(instruction.operands.len() >= 2)?;
 
-                let substitutions = self.pop_substitutions_with_count_at_operand(instruction, 1);
-                let line = self.prepare_line(string_id, &substitutions)?;
+                let substitutions = self.pop_substitutions_with_count_at_operand(instruction, 1)?;
+                let line =
+                    self.prepare_line_with_text(string_id.clone(), line_text, &substitutions)?;
+                self.displayed_line = Some((string_id, substitutions));
 
+                if self.history_enabled {
+                    self.history.push(HistoryEntry::Line(line.clone()));
+                }
+                #[cfg(feature = "tracing")]
+                tracing::info!(
+                    line_id = %line.id,
+                    node = %line.node_name,
+                    location = ?line.source_location,
+                    "Delivering line"
+                );
+                let line = self.split_into_pages(line);
                 self.batched_events.push(DialogueEvent::Line(line));
 
                 // Implementation note:
@@ -324,16 +930,33 @@ impl VirtualMachine {
             }
             OpCode::RunCommand => {
                 // Passes a string to the client as a custom command
-                let command_text: String = instruction.read_operand(0);
-                assert_up_to_date_compiler(instruction.operands.len() >= 2);
+                let command_text: String = read_operand(instruction, 0)?;
+                ensure_up_to_date_compiler(instruction.operands.len() >= 2)?;
                 let command_text = self
-                    .pop_substitutions_with_count_at_operand(instruction, 1)
+                    .pop_substitutions_with_count_at_operand(instruction, 1)?
                     .into_iter()
                     .enumerate()
                     .fold(command_text, |command_text, (i, substitution)| {
                         command_text.replace(&format!("{{{i}}}"), &substitution)
                     });
-                let command = Command::parse(command_text);
+                let command = Command {
+                    node_name: self.current_node_name.clone(),
+                    source_location: self.source_location_of(self.state.program_counter),
+                    ..Command::parse(command_text)
+                };
+                if command.name == OPTIONS_TIMEOUT_COMMAND {
+                    self.options_timeout = Some(read_options_timeout(&command)?);
+                    self.state.program_counter += 1;
+                    return Ok(());
+                }
+                #[cfg(feature = "tracing")]
+                tracing::info!(
+                    command = %command.name,
+                    raw = %command.raw,
+                    node = ?command.node_name,
+                    location = ?command.source_location,
+                    "Dispatching command"
+                );
 
                 self.batched_events.push(DialogueEvent::Command(command));
 
@@ -347,29 +970,33 @@ impl VirtualMachine {
             }
             OpCode::AddOption => {
                 // Add an option to the current state
-                let string_id: String = instruction.read_operand(0);
+                let string_id: String = read_operand(instruction, 0)?;
                 let string_id: LineId = string_id.into();
-                assert_up_to_date_compiler(instruction.operands.len() >= 4);
-                let substitutions = self.pop_substitutions_with_count_at_operand(instruction, 2);
-                let line = self.prepare_line(string_id, &substitutions)?;
+                ensure_up_to_date_compiler(instruction.operands.len() >= 4)?;
+                let Poll::Ready(line_text) = self.poll_text(&string_id) else {
+                    return Ok(());
+                };
+                let substitutions = self.pop_substitutions_with_count_at_operand(instruction, 2)?;
+                let line = self.prepare_line_with_text(string_id, line_text, &substitutions)?;
+                self.option_substitutions.push(substitutions);
 
                 // Indicates whether the VM believes that the
                 // option should be shown to the user, based on any
                 // conditions that were attached to the option.
-                let line_condition_passed = if instruction.read_operand(3) {
+                let line_condition_passed = if read_operand(instruction, 3)? {
                     // The fourth operand is a bool that indicates
                     // whether this option had a condition or not.
                     // If it does, then a bool value will exist on
                     // the stack indicating whether the condition
                     // passed or not. We pass that information to
                     // the game.
-                    self.state.pop()
+                    self.state.pop()?
                 } else {
                     true
                 };
 
                 let index = self.state.current_options.len();
-                let node_name = instruction.read_operand(1);
+                let node_name = read_operand(instruction, 1)?;
                 // ## Implementation note:
                 // The original calculates the ID in the `ShowOptions` opcode,
                 // but this way is cleaner because it allows us to store a `DialogueOption` instead of a bunch of values in a big tuple.
@@ -397,6 +1024,21 @@ impl VirtualMachine {
                 // delegate for them to call when the user has made
                 // a selection
                 let current_options = self.state.current_options.clone();
+                if let Some(options_timeout) = self.options_timeout.take() {
+                    if let Some(OptionId(index)) = options_timeout.default_option {
+                        if index >= current_options.len() {
+                            return Err(InvalidInstruction(format!("The {OPTIONS_TIMEOUT_COMMAND} command chose option {} as the default, but there are only {} options", index + 1, current_options.len())).into());
+                        }
+                    }
+                    self.batched_events
+                        .push(DialogueEvent::OptionsTimeout(options_timeout));
+                }
+                if self.history_enabled {
+                    self.history.push(HistoryEntry::Options {
+                        options: current_options.clone(),
+                        selected_option: None,
+                    });
+                }
                 self.batched_events
                     .push(DialogueEvent::Options(current_options));
 
@@ -406,32 +1048,32 @@ impl VirtualMachine {
             }
             OpCode::PushString => {
                 // Pushes a string value onto the stack. The operand is an index into the string table, so that's looked up first.
-                let string_table_index: String = instruction.read_operand(0);
+                let string_table_index: String = read_operand(instruction, 0)?;
                 self.state.push(string_table_index);
                 self.state.program_counter += 1;
             }
             OpCode::PushFloat => {
                 // Pushes a floating point onto the stack.
-                let float: f32 = instruction.read_operand(0);
+                let float: f32 = read_operand(instruction, 0)?;
                 self.state.push(float);
                 self.state.program_counter += 1;
             }
             OpCode::PushBool => {
                 // Pushes a boolean value onto the stack.
-                let boolean: bool = instruction.read_operand(0);
+                let boolean: bool = read_operand(instruction, 0)?;
                 self.state.push(boolean);
                 self.state.program_counter += 1;
             }
 
             OpCode::PushNull => {
-                panic!("PushNull is no longer valid op code, because null is no longer a valid value from Yarn Spinner 2.0 onwards. To fix this error, re-compile the original source code.");
+                return Err(InvalidInstruction::new("PushNull is no longer valid op code, because null is no longer a valid value from Yarn Spinner 2.0 onwards. To fix this error, re-compile the original source code.").into());
             }
             OpCode::JumpIfFalse => {
                 // Jumps to a named label if the value on the top of the stack evaluates to the boolean value 'false'.
-                let is_top_value_true: bool = self.state.peek();
+                let is_top_value_true: bool = self.state.peek()?;
                 if !is_top_value_true {
-                    let label_name: String = instruction.read_operand(0);
-                    let instruction_point = self.find_instruction_point_for_label(&label_name);
+                    let label_name: String = read_operand(instruction, 0)?;
+                    let instruction_point = self.find_instruction_point_for_label(&label_name)?;
                     self.state.program_counter = instruction_point;
                 } else {
                     self.state.program_counter += 1;
@@ -439,23 +1081,22 @@ impl VirtualMachine {
             }
             OpCode::Pop => {
                 // Pops a value from the stack.
-                self.state.pop_value();
+                self.state.pop_value()?;
                 self.state.program_counter += 1;
             }
             OpCode::CallFunc => {
-                let actual_parameter_count: usize = self.state.pop();
+                let actual_parameter_count: usize = self.state.pop()?;
                 // Get the parameters, which were pushed in reverse
                 let parameters = {
-                    let mut parameters: Vec<_> = (0..actual_parameter_count)
-                        .rev()
-                        .map(|_| self.state.pop_value().raw_value)
-                        .collect();
+                    let mut parameters = (0..actual_parameter_count)
+                        .map(|_| self.state.pop_value().map(|value| value.raw_value))
+                        .collect::<std::result::Result<Vec<_>, _>>()?;
                     parameters.reverse();
                     parameters
                 };
 
                 // Call a function, whose parameters are expected to be on the stack. Pushes the function's return value, if it returns one.
-                let function_name: String = instruction.read_operand(0);
+                let function_name: String = read_operand(instruction, 0)?;
                 let function =
                     self.library
                         .get(&function_name)
@@ -463,22 +1104,65 @@ impl VirtualMachine {
                             function_name: function_name.to_string(),
                             library: self.library.clone(),
                         })?;
+                // Overloaded functions are called with the overload that accepts this many arguments
+                let function = resolve_overload(function, actual_parameter_count);
+                if self.metrics_enabled {
+                    self.metrics.record_function_call(&function_name);
+                }
 
                 // Expect the compiler to have placed the number of parameters
                 // actually passed at the top of the stack.
                 let expected_parameter_count = function.parameter_types().len();
+                let is_variadic = function.is_variadic();
+                // Optional parameters and the variadic parameter may receive no arguments at all
+                let required_parameter_count = expected_parameter_count
+                    - function.optional_parameter_count()
+                    - usize::from(is_variadic);
+
+                if is_variadic {
+                    if actual_parameter_count < required_parameter_count {
+                        return Err(InvalidInstruction(format!("Function {function_name} expected at least {required_parameter_count} parameters, but received {actual_parameter_count}")).into());
+                    }
+                } else if required_parameter_count != expected_parameter_count {
+                    if !(required_parameter_count..=expected_parameter_count)
+                        .contains(&actual_parameter_count)
+                    {
+                        return Err(InvalidInstruction(format!("Function {function_name} expected {required_parameter_count} to {expected_parameter_count} parameters, but received {actual_parameter_count}")).into());
+                    }
+                } else if expected_parameter_count != actual_parameter_count {
+                    return Err(InvalidInstruction(format!("Function {function_name} expected {expected_parameter_count} parameters, but received {actual_parameter_count}")).into());
+                }
 
-                assert_eq!(
-                    expected_parameter_count, actual_parameter_count,
-                    "Function {function_name} expected {expected_parameter_count} parameters, but received {actual_parameter_count}",
-                );
-
-                // Invoke the function
-                let return_value = function.call(parameters);
-                let return_type = function
+                let return_type: Type = function
                     .return_type()
                     .try_into()
-                    .unwrap_or_else(|e| panic!("Failed to get Yarn type for return type id of function {function_name}: {e:?}"));
+                    .map_err(|e| InvalidInstruction(format!("Failed to get Yarn type for return type id of function {function_name}: {e:?}")))?;
+                // Invoke the function
+                let output =
+                    with_conversion_failure_behavior(self.conversion_failure_behavior, || {
+                        with_dialogue_context(self.context.clone(), || {
+                            function.call_async(parameters)
+                        })
+                    });
+                let return_value = match output {
+                    YarnFnOutput::Ready(value) => value,
+                    YarnFnOutput::Pending(future) => {
+                        // The return value is pushed once the future resolves
+                        self.pending_function =
+                            Some(PendingFunction::new(function_name, return_type, future));
+                        self.state.program_counter += 1;
+                        self.poll_pending_function();
+                        return Ok(());
+                    }
+                    YarnFnOutput::Failed(source) => {
+                        return Err(DialogueError::FunctionFailed {
+                            function_name,
+                            node_name: self.current_node_name()?,
+                            instruction_index: self.state.program_counter,
+                            source,
+                        });
+                    }
+                };
                 let typed_return_value = InternalValue {
                     raw_value: return_value,
                     r#type: return_type,
@@ -491,46 +1175,55 @@ impl VirtualMachine {
             }
             OpCode::PushVariable => {
                 // Get the contents of a variable, push that onto the stack.
-                let variable_name: String = instruction.read_operand(0);
-                let loaded_value = self
-                    .variable_storage
-                    .get(&variable_name)
-                    .or_else(|e| {
-                        if let VariableStorageError::VariableNotFound { .. } = e {
-                            // We don't have a value for this. The initial
-                            // value may be found in the program. (If it's
-                            // not, then the variable's value is undefined,
-                            // which isn't allowed.)
-                            let initial_value = self
-                                .program
-                                .as_ref()
-                                .unwrap()
-                                .initial_values
-                                .get(&variable_name)
-                                .unwrap_or_else(|| panic!("The loaded program does not contain an initial value for the variable {variable_name}"))
-                                .clone();
-
-                            // Store the initial value in the variable_storage
-                            self.variable_storage.set(variable_name.clone(), initial_value.clone().into())?;
-
-                            Ok(initial_value.into())
-                        } else {
-                            Err(e)
-                        }
-                    })?;
+                let variable_name: String = read_operand(instruction, 0)?;
+                let loaded_value = match self.variable_storage.get(&variable_name) {
+                    Ok(value) => value,
+                    Err(VariableStorageError::VariableNotFound { .. }) => {
+                        // We don't have a value for this. The initial
+                        // value may be found in the program. (If it's
+                        // not, then the variable's value is undefined,
+                        // which isn't allowed.)
+                        let initial_value = self
+                            .program
+                            .as_ref()
+                            .ok_or(DialogueError::NoProgramLoaded)?
+                            .initial_values
+                            .get(&variable_name)
+                            .ok_or_else(|| InvalidInstruction(format!("The loaded program does not contain an initial value for the variable {variable_name}")))?
+                            .clone();
+
+                        // Store the initial value in the variable_storage
+                        self.variable_storage
+                            .set(variable_name.clone(), initial_value.clone().into())?;
+
+                        initial_value.into()
+                    }
+                    Err(e) => return Err(e.into()),
+                };
                 self.state.push(loaded_value);
                 self.state.program_counter += 1;
             }
             OpCode::StoreVariable => {
                 // Store the top value on the stack in a variable.
-                let top_value = self.state.peek_value().clone();
-                let variable_name: String = instruction.read_operand(0);
-                self.variable_storage.set(variable_name, top_value.into())?;
+                let top_value = self.state.peek_value()?.clone();
+                let variable_name: String = read_operand(instruction, 0)?;
+                let change = (self.variable_change_events_enabled
+                    && !variable_name.starts_with(VISIT_TRACKING_PREFIX))
+                .then(|| (variable_name.clone(), self.variable_value(&variable_name)));
+                self.variable_storage
+                    .set(variable_name, top_value.clone().into())?;
+                if let Some((name, old)) = change {
+                    self.batched_events.push(DialogueEvent::VariableChanged {
+                        name,
+                        old,
+                        new: top_value.into(),
+                    });
+                }
                 self.state.program_counter += 1;
             }
             OpCode::Stop => {
                 // Immediately stop execution, and report that fact.
-                let current_node_name = self.current_node_name.clone().unwrap();
+                let current_node_name = self.current_node_name()?;
                 self.batched_events
                     .push(DialogueEvent::NodeComplete(current_node_name));
                 self.batched_events.push(DialogueEvent::DialogueComplete);
@@ -543,7 +1236,7 @@ impl VirtualMachine {
 
                 // Pop a string from the stack, and jump to a node
                 // with that name.
-                let node_name: String = self.state.pop();
+                let node_name: String = self.state.pop()?;
                 self.batched_events
                     .push(DialogueEvent::NodeComplete(node_name.clone()));
                 self.set_node(&node_name)?;
@@ -554,71 +1247,236 @@ impl VirtualMachine {
         Ok(())
     }
 
+    /// Polls the [`TextProvider`] for the text of a line or option. While it is not available,
+    /// emits [`DialogueEvent::TextPending`] and waits so that the current instruction is run again on the next continue.
+    fn poll_text(&mut self, string_id: &LineId) -> Poll<Option<String>> {
+        let text_provider = self.text_provider.as_mut();
+        let line_text = poll_with_waker(self.waker.as_ref(), |context| {
+            text_provider.poll_text(string_id, context)
+        });
+        if line_text.is_pending() {
+            self.batched_events
+                .push(DialogueEvent::TextPending(string_id.clone()));
+            self.set_execution_state(ExecutionState::WaitingForContinue);
+            // Resuming runs the instruction again, which must not hit its breakpoint a second time
+            self.paused_at_breakpoint = true;
+        }
+        line_text
+    }
+
     fn prepare_line(&mut self, string_id: LineId, substitutions: &[String]) -> Result<Line> {
-        let line_text = self.text_provider.get_text(&string_id).ok_or_else(|| {
-            DialogueError::LineProviderError {
-                id: string_id.clone(),
-                language_code: self.language_code.clone(),
-            }
+        let line_text = self.text_provider.get_text(&string_id);
+        self.prepare_line_with_text(string_id, line_text, substitutions)
+    }
+
+    fn prepare_line_with_text(
+        &mut self,
+        string_id: LineId,
+        line_text: Option<String>,
+        substitutions: &[String],
+    ) -> Result<Line> {
+        let line_text = line_text.ok_or_else(|| DialogueError::LineProviderError {
+            id: string_id.clone(),
+            language_code: self.language_code.clone(),
         })?;
         let substituted_text = expand_substitutions(&line_text, substitutions);
-        let markup = self
-            .parse_markup(&substituted_text)
-            .map_err(DialogueError::MarkupParseError)?;
+        let substituted_text = self.substitution_providers.expand(&substituted_text);
+        let markup_language = self.markup_language();
+        let node_language = self
+            .current_node_language()
+            .filter(|language| markup_language.as_ref() != Some(language));
+        if let Some(node_language) = node_language.clone() {
+            self.line_parser.set_language_code(node_language);
+        }
+        let markup = self.parse_markup(&substituted_text);
+        if node_language.is_some() {
+            self.line_parser.set_language_code(markup_language);
+        }
+        let (markup, markup_error) = match markup {
+            Ok(markup) => (markup, None),
+            Err(error) if self.lenient_markup => {
+                warn!("Delivering line \"{string_id}\" without markup: {error}");
+                let markup = ParsedMarkup {
+                    text: substituted_text.clone(),
+                    attributes: Vec::new(),
+                };
+                (markup, Some(error))
+            }
+            Err(error) => return Err(DialogueError::MarkupParseError(error)),
+        };
         let line = Line {
-            id: string_id,
+            id: string_id.clone(),
             text: markup.text,
+            raw_text: substituted_text,
+            markup_error,
             attributes: markup.attributes,
+            metadata: self
+                .line_metadata
+                .get(&string_id)
+                .cloned()
+                .unwrap_or_default(),
+            node_name: self.current_node_name.clone().unwrap_or_default(),
+            source_location: self.source_location_of(self.state.program_counter),
         };
         Ok(line)
     }
 
-    /// Looks up the instruction number for a named label in the current node.
-    ///
-    /// # Panics
-    ///
-    /// Panics in the following cases:
-    /// - The label is not found in the current node
-    /// - The current node is unset
-    /// - The found instruction point is negative
-    fn find_instruction_point_for_label(&self, label_name: &str) -> usize {
+    /// The name of the node that is currently running.
+    fn current_node_name(&self) -> Result<String> {
+        self.current_node_name
+            .clone()
+            .ok_or(DialogueError::NoNodeSelectedOnContinue)
+    }
+
+    /// The place in the Yarn source code the instruction at the given index of the current node was compiled from, if known.
+    fn source_location_of(&self, instruction_index: usize) -> Option<SourceLocation> {
+        let node_name = self.current_node_name.as_deref()?;
+        self.source_map.locate(node_name, instruction_index)
+    }
+
+    /// The language set by the `language` header of the current node, if any.
+    /// Lines of such a node use this language for their markers, e.g. `[plural]`, instead of the dialogue's language.
+    /// Headers that are not a valid IETF BCP 47 code are ignored.
+    fn current_node_language(&self) -> Option<Language> {
         self.current_node
+            .as_ref()?
+            .headers
+            .iter()
+            .find(|header| header.key == "language")
+            .and_then(|header| header.value.trim().parse().ok())
+            .map(Language)
+    }
+
+    /// Looks up the instruction number for a named label in the current node.
+    /// Fails if no node is selected, the label is not found in the current node or the found instruction point is negative.
+    fn find_instruction_point_for_label(
+        &self,
+        label_name: &str,
+    ) -> std::result::Result<usize, InvalidInstruction> {
+        let instruction_point = self
+            .current_node
             .as_ref()
-            .unwrap()
+            .ok_or_else(|| {
+                InvalidInstruction::new("Tried to jump to a label, but no node is selected")
+            })?
             .labels
             .get(label_name)
             .copied()
-            .unwrap_or_else(|| {
-                panic!(
-                    "Unknown label {label_name} in node {}",
-                    self.current_node_name.as_ref().unwrap()
-                )
-            })
-            .try_into()
-            .unwrap()
+            .ok_or_else(|| InvalidInstruction(format!("Unknown label {label_name}")))?;
+        instruction_point.try_into().map_err(|_| {
+            InvalidInstruction(format!(
+                "Label {label_name} points to the negative instruction {instruction_point}"
+            ))
+        })
     }
 
     fn pop_substitutions_with_count_at_operand(
         &mut self,
         instruction: &Instruction,
         index: usize,
-    ) -> Vec<String> {
-        let expression_count: usize = instruction.operands[index].clone().try_into().unwrap();
-        let mut values: Vec<_> = (0..expression_count)
-            .rev()
+    ) -> std::result::Result<Vec<String>, InvalidInstruction> {
+        let expression_count: usize = read_operand(instruction, index)?;
+        let mut values = (0..expression_count)
             .map(|_| self.state.pop())
-            .collect();
+            .collect::<std::result::Result<Vec<String>, _>>()?;
         values.reverse();
-        values
+        Ok(values)
+    }
+}
+
+/// Returns the IDs of all lines and options that can be delivered while running `node`, in the order they appear in it.
+pub(crate) fn line_hints_for_node(node: &Node) -> Vec<LineId> {
+    // Create a list; we will never have more lines and options
+    // than total instructions, so that's a decent capacity for
+    // the list
+    // [sic] TODO: maybe this list could be reused to save on allocations?
+    node.instructions
+        .iter()
+        // Loop over every instruction and find the ones that run a
+        // line or add an option; these are the two instructions
+        // that will signal a line can appear to the player
+        .filter_map(|instruction| {
+            let opcode = OpCode::try_from(instruction.opcode).ok()?;
+            if ![OpCode::RunLine, OpCode::AddOption].contains(&opcode) {
+                return None;
+            }
+            // Both RunLine and AddOption have the string ID
+            // they want to show as their first operand, so
+            // store that. Malformed instructions are reported
+            // when they are run, not here.
+            let id: String = instruction.try_read_operand(0)?;
+            Some(LineId(id))
+        })
+        .collect()
+}
+
+/// Returns the IDs of all lines and options in `node_name` and in the nodes reachable from it through `<<jump>>`s to a fixed node name,
+/// with nodes closer to `node_name` first. Only nodes at most `max_depth` jumps away are included, or all reachable nodes if it is [`None`].
+pub(crate) fn line_hints_for_reachable_nodes(
+    program: &Program,
+    node_name: &str,
+    max_depth: Option<usize>,
+) -> Vec<LineId> {
+    let mut visited = HashSet::from([node_name.to_owned()]);
+    let mut queue = VecDeque::from([(node_name.to_owned(), 0)]);
+    let mut line_ids = Vec::new();
+    while let Some((node_name, depth)) = queue.pop_front() {
+        let Some(node) = program.nodes.get(&node_name) else {
+            continue;
+        };
+        line_ids.extend(line_hints_for_node(node));
+        if max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            continue;
+        }
+        for destination in node.jump_destinations() {
+            if visited.insert(destination.clone()) {
+                queue.push_back((destination, depth + 1));
+            }
+        }
+    }
+    line_ids
+}
+
+/// Emits an event for an error that stops the dialogue when the `tracing` feature is enabled, and returns the error unchanged.
+fn trace_error(error: DialogueError) -> DialogueError {
+    #[cfg(feature = "tracing")]
+    tracing::error!(%error, "Dialogue stopped with an error");
+    error
+}
+
+/// Reads the parameters of the [`OPTIONS_TIMEOUT_COMMAND`], see its documentation.
+fn read_options_timeout(
+    command: &Command,
+) -> std::result::Result<OptionsTimeout, InvalidInstruction> {
+    let usage = format!("Expected <<{OPTIONS_TIMEOUT_COMMAND} seconds>> or <<{OPTIONS_TIMEOUT_COMMAND} seconds default_option>>, but found <<{}>>", command.raw);
+    let (seconds, default_option) = match command.parameters.as_slice() {
+        [YarnValue::Number(seconds)] => (*seconds, None),
+        [YarnValue::Number(seconds), YarnValue::Number(position)]
+            if *position >= 1.0 && position.fract() == 0.0 =>
+        {
+            (*seconds, Some(OptionId(*position as usize - 1)))
+        }
+        _ => return Err(InvalidInstruction(usage)),
+    };
+    // Rejects durations that are not positive, not finite or too large to represent
+    match Duration::try_from_secs_f32(seconds) {
+        Ok(duration) if !duration.is_zero() => Ok(OptionsTimeout {
+            duration,
+            default_option,
+        }),
+        _ => Err(InvalidInstruction(usage)),
     }
 }
 
-fn assert_up_to_date_compiler(predicate: bool) {
-    assert!(
-        predicate,
-        "The Yarn script provided was compiled using an older compiler. \
-        Please recompile it using the latest version of either Yarn Spinner or Yarn Spinner."
-    )
+fn ensure_up_to_date_compiler(predicate: bool) -> std::result::Result<(), InvalidInstruction> {
+    if predicate {
+        Ok(())
+    } else {
+        Err(InvalidInstruction::new(
+            "The Yarn script provided was compiled using an older compiler. \
+            Please recompile it using the latest version of either Yarn Spinner or Yarn Spinner.",
+        ))
+    }
 }
 
 /// Replaces all substitution markers in a text with the given substitution list.
@@ -0,0 +1,40 @@
+use crate::prelude::*;
+
+/// The reason an instruction could not be run, e.g. a missing operand or an empty stack.
+/// Converted into a [`DialogueError::InvalidInstruction`] that is located by [`VirtualMachine::run_current_instruction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct InvalidInstruction(pub(crate) String);
+
+impl InvalidInstruction {
+    pub(crate) fn new(reason: impl Into<String>) -> Self {
+        Self(reason.into())
+    }
+}
+
+impl From<InvalidInstruction> for DialogueError {
+    fn from(InvalidInstruction(reason): InvalidInstruction) -> Self {
+        // The location is filled in by `VirtualMachine::run_current_instruction`
+        DialogueError::InvalidInstruction {
+            node_name: String::new(),
+            instruction_index: 0,
+            location: None,
+            reason,
+        }
+    }
+}
+
+/// Reads an operand of `instruction`, see [`Instruction::read_operand`].
+pub(crate) fn read_operand<T>(
+    instruction: &Instruction,
+    index: usize,
+) -> std::result::Result<T, InvalidInstruction>
+where
+    T: TryFrom<Operand>,
+{
+    instruction.try_read_operand(index).ok_or_else(|| {
+        InvalidInstruction(format!(
+            "Operand {index} of instruction {:?} is missing or has the wrong type",
+            instruction.opcode()
+        ))
+    })
+}
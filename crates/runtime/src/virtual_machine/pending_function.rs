@@ -0,0 +1,56 @@
+//! Tracks a call to an async function registered with [`Library::add_async_function`].
+
+use crate::prelude::*;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+#[derive(Debug)]
+pub(crate) struct PendingFunction {
+    pub(crate) function_name: String,
+    pub(crate) return_type: Type,
+    future: YarnFuture<YarnValue>,
+}
+
+impl PendingFunction {
+    pub(crate) fn new(
+        function_name: String,
+        return_type: Type,
+        future: YarnFuture<YarnValue>,
+    ) -> Self {
+        Self {
+            function_name,
+            return_type,
+            future,
+        }
+    }
+
+    /// Polls the future once. The dialogue polls again on every call to `continue_`, so unless it is driven by an executor that passes its `waker`,
+    /// there's nothing for a waker to do.
+    pub(crate) fn poll(&mut self, waker: Option<&Waker>) -> Poll<YarnValue> {
+        poll_with_waker(waker, |context| Pin::new(&mut self.future).poll(context))
+    }
+}
+
+/// Calls `poll` with a [`Context`] for `waker`, or for a waker that does nothing if there is none.
+pub(crate) fn poll_with_waker<T>(
+    waker: Option<&Waker>,
+    poll: impl FnOnce(&mut Context<'_>) -> Poll<T>,
+) -> Poll<T> {
+    let noop_waker;
+    let waker = match waker {
+        Some(waker) => waker,
+        None => {
+            noop_waker = Waker::from(Arc::new(NoopWaker));
+            &noop_waker
+        }
+    };
+    poll(&mut Context::from_waker(waker))
+}
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
@@ -0,0 +1,101 @@
+//! Keeps track of the states a [`Dialogue`] was waiting in, so that it can be rewound.
+
+use crate::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+/// A bounded list of the points at which the [`VirtualMachine`] waited for the game, oldest first.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct RewindHistory {
+    /// The maximum number of entries kept. A capacity of 0 disables rewinding.
+    pub(crate) capacity: usize,
+    entries: VecDeque<RewindEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RewindEntry {
+    pub(crate) snapshot: DialogueSnapshot,
+    /// The contents of the [`VariableStorage`] at that point.
+    pub(crate) variables: HashMap<String, YarnValue>,
+    /// The event that made the dialogue wait, i.e. a [`DialogueEvent::Line`], [`DialogueEvent::Command`] or [`DialogueEvent::Options`].
+    pub(crate) event: DialogueEvent,
+}
+
+impl RewindHistory {
+    pub(crate) fn push(&mut self, entry: RewindEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Returns the entry `steps` entries before the most recent one, without changing the rewind history.
+    pub(crate) fn entry(&self, steps: usize) -> Option<&RewindEntry> {
+        let index = self.entries.len().checked_sub(steps + 1)?;
+        self.entries.get(index)
+    }
+
+    /// Removes the `steps` most recent entries, so that the one returned by [`RewindHistory::entry`] becomes the most recent entry.
+    pub(crate) fn drop_recent(&mut self, steps: usize) {
+        self.entries
+            .truncate(self.entries.len().saturating_sub(steps));
+    }
+
+    /// The number of steps [`RewindHistory::entry`] can go back.
+    pub(crate) fn available_steps(&self) -> usize {
+        self.entries.len().saturating_sub(1)
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let excess = self.entries.len().saturating_sub(capacity);
+        self.entries.drain(..excess);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_oldest_entries_beyond_capacity() {
+        let mut history = RewindHistory::default();
+        history.set_capacity(2);
+        for i in 0..3 {
+            history.push(entry(i));
+        }
+        assert_eq!(history.available_steps(), 1);
+        assert_eq!(history.entry(1).unwrap().snapshot.node_name, "1");
+        assert!(history.entry(2).is_none());
+        history.drop_recent(1);
+        assert_eq!(history.available_steps(), 0);
+        assert!(history.entry(1).is_none());
+        assert_eq!(history.entry(0).unwrap().snapshot.node_name, "1");
+    }
+
+    #[test]
+    fn records_nothing_when_disabled() {
+        let mut history = RewindHistory::default();
+        history.push(entry(0));
+        assert!(history.entry(0).is_none());
+    }
+
+    fn entry(index: usize) -> RewindEntry {
+        RewindEntry {
+            snapshot: DialogueSnapshot {
+                node_name: index.to_string(),
+                instruction_count: 0,
+                state: Default::default(),
+                execution_state: ExecutionState::WaitingForContinue,
+            },
+            variables: Default::default(),
+            event: DialogueEvent::DialogueComplete,
+        }
+    }
+}
@@ -30,47 +30,41 @@ impl State {
     }
 
     /// Pops a value from the stack and tries to convert it to the specified type.
-    ///
-    /// ## Panics
-    /// - Panics on an empty stack to mirror C# behavior.
-    /// - Panics if the value cannot be converted to the specified type.
-    pub(crate) fn pop<T>(&mut self) -> T
+    /// Fails on an empty stack or if the value cannot be converted to the specified type.
+    pub(crate) fn pop<T>(&mut self) -> Result<T, InvalidInstruction>
     where
         T: TryFrom<InternalValue>,
         <T as TryFrom<InternalValue>>::Error: Debug,
     {
-        self.pop_value()
+        self.pop_value()?
             .try_into()
-            .unwrap_or_else(|e| panic!("Failed to convert popped value: {e:?}",))
+            .map_err(|e| InvalidInstruction(format!("Failed to convert popped value: {e:?}")))
     }
 
-    /// Pops a value from the stack. Panics on an empty stack to mirror C# behavior.
-    pub(crate) fn pop_value(&mut self) -> InternalValue {
+    /// Pops a value from the stack. Fails on an empty stack.
+    pub(crate) fn pop_value(&mut self) -> Result<InternalValue, InvalidInstruction> {
         self.stack
             .pop()
-            .unwrap_or_else(|| panic!("Tried to pop value, but the stack was empty."))
+            .ok_or_else(|| InvalidInstruction::new("Tried to pop value, but the stack was empty"))
     }
 
-    /// Peeks the top value of the stack. Panics on an empty stack to mirror C# behavior.
-    pub(crate) fn peek<T>(&self) -> T
+    /// Copies the top value of the stack and tries to convert it to the specified type.
+    /// Fails on an empty stack or if the value cannot be converted to the specified type.
+    pub(crate) fn peek<T>(&self) -> Result<T, InvalidInstruction>
     where
         T: TryFrom<InternalValue>,
         <T as TryFrom<InternalValue>>::Error: Debug,
     {
-        self.peek_value()
+        self.peek_value()?
             .clone()
             .try_into()
-            .unwrap_or_else(|e| panic!("Failed to convert popped value: {e:?}",))
+            .map_err(|e| InvalidInstruction(format!("Failed to convert peeked value: {e:?}")))
     }
 
-    /// Copies the top value of the stack and tries to convert it to the specified type.
-    ///
-    /// ## Panics
-    /// - Panics on an empty stack to mirror C# behavior.
-    /// - Panics if the value cannot be converted to the specified type.
-    pub(crate) fn peek_value(&self) -> &InternalValue {
+    /// Peeks the top value of the stack. Fails on an empty stack.
+    pub(crate) fn peek_value(&self) -> Result<&InternalValue, InvalidInstruction> {
         self.stack
             .last()
-            .unwrap_or_else(|| panic!("Tried to peek value, but the stack was empty."))
+            .ok_or_else(|| InvalidInstruction::new("Tried to peek value, but the stack was empty"))
     }
 }
@@ -7,31 +7,38 @@
 
 pub use log;
 
+pub mod validation;
+
 pub mod prelude {
     //! Everything you need to get started using Yarn Spinner.
     pub use crate::compiler::{
         Compilation, CompilationType, Compiler as YarnCompiler, CompilerError, File as YarnFile,
-        LineInfo, Result as YarnCompilerResult, StringInfo,
+        LineInfo, ProgramChunkManifest, ProgramChunks, ProgramSplit, Result as YarnCompilerResult,
+        StringInfo,
     };
     pub use crate::core::{
-        yarn_library, IntoYarnValueFromNonYarnValue, Library as YarnLibrary, LineId,
-        Program as YarnProgram, YarnFn, YarnValue,
+        yarn_library, DialogueContext, IntoYarnValueFromNonYarnValue, Library as YarnLibrary,
+        LineId, Program as YarnProgram, VariableDeclaration, YarnFn, YarnValue,
     };
     pub use crate::runtime::{
-        Command as YarnCommand, CompiledProgramAnalyser as YarnAnalyser,
+        ButtonGlyph, ButtonGlyphs, Command as YarnCommand, CompiledProgramAnalyser as YarnAnalyser,
         Context as YarnAnalysisContext, Dialogue, DialogueError, DialogueEvent, DialogueOption,
-        Language, Line as YarnLine, MarkupAttribute, MarkupValue, OptionId,
-        Result as YarnRuntimeResult, StringTable, TextProvider, VariableStorage,
+        InputPlatform, Language, Line as YarnLine, MarkupAttribute, MarkupValue, OptionId,
+        OptionsTimeout, Result as YarnRuntimeResult, SourceLocation, StringTable, TextProvider,
+        TypedVariableStorage, VariableSnapshot, VariableStorage,
     };
+    pub use crate::validation::{validate_project, ValidationOptions, ValidationReport};
 }
 
 pub mod core {
     //! Core types and traits that are used by both the compiler and runtime.
     pub use yarnspinner_core::prelude::{
-        yarn_fn_type, yarn_library, Header, Instruction, IntoYarnValueFromNonYarnValue,
-        InvalidOpCodeError, Library, LineId, Node, Position, Program, Type, UntypedYarnFn, YarnFn,
-        YarnFnParam, YarnFnParamItem, YarnValue, YarnValueCastError, YarnValueWrapper,
-        YarnValueWrapperIter,
+        resolve_overload, yarn_fn_type, yarn_library, AmbiguousOverloadError,
+        ConversionFailureBehavior, DialogueContext, FunctionSignature, Header, Instruction,
+        IntoYarnValueFromNonYarnValue, InvalidOpCodeError, Library, LineId, Node, OpCode, Operand,
+        Position, Program, Type, UntypedYarnFn, VariableDeclaration, YarnFn, YarnFnParam,
+        YarnFnParamItem, YarnFuture, YarnValue, YarnValueCastError, YarnValueWrapper,
+        YarnValueWrapperIter, INTERNAL_VARIABLE_PREFIX,
     };
 }
 pub mod compiler {
@@ -43,8 +50,12 @@ pub mod compiler {
 pub mod runtime {
     //! Types and traits used by the runtime, in particular the [`Dialogue`] struct.
     pub use yarnspinner_runtime::markup::{
-        MarkupAttribute, MarkupParseError, MarkupValue, CHARACTER_ATTRIBUTE,
-        CHARACTER_ATTRIBUTE_NAME_PROPERTY, TRIM_WHITESPACE_PROPERTY,
+        AttributeMarkerProcessor, BbcodeFormat, ButtonGlyph, ButtonGlyphs, HtmlFormat,
+        InputPlatform, MarkupAttribute, MarkupAttributeMarker, MarkupParseError, MarkupStyles,
+        MarkupValue, MarkupValueConversionError, MarkupWhitespaceTrimming, RichTextFormat,
+        StyledAttribute, TagType, BUTTON_ATTRIBUTE, BUTTON_ATTRIBUTE_ICON_PROPERTY,
+        BUTTON_ATTRIBUTE_PLATFORM_PROPERTY, CHARACTER_ATTRIBUTE, CHARACTER_ATTRIBUTE_NAME_PROPERTY,
+        STYLE_ATTRIBUTE, TRIM_WHITESPACE_PROPERTY,
     };
     pub use yarnspinner_runtime::prelude::*;
     pub use yarnspinner_runtime::Result;
@@ -0,0 +1,349 @@
+//! Runs every check Yarn Spinner offers on a project in one call,
+//! so that build tools and CI pipelines don't have to orchestrate the compiler, the analysers and the localization checks themselves.
+
+use crate::compiler::{
+    Compilation, CompilationStats, Compiler, CompilerError, Diagnostic, DiagnosticSeverity,
+    ReviewReport,
+};
+use crate::core::{LineId, Program};
+use crate::runtime::{
+    Context, Diagnosis, DiagnosisSeverity, Dialogue, Language, MemoryVariableStorage, StringTable,
+    StringTableTextProvider,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Compiles the Yarn files at the given paths and validates the result as configured by the [`ValidationOptions`]:
+/// - The files are compiled with [`ValidationOptions::compiler`], reporting all errors and warnings.
+/// - The compiled program is analysed with [`ValidationOptions::analysis`].
+/// - Every `<<jump>>` to a fixed node name is checked to lead to a node that exists.
+/// - Every translation added with [`ValidationOptions::audit_translation`] is checked to contain exactly the lines of the project.
+///
+/// Problems never cause this function to fail or return early. Instead, everything that was found is collected in the returned [`ValidationReport`].
+/// If a file cannot be read, the remaining files are still compiled.
+///
+/// ## Example
+///
+/// Failing a build when the project has problems:
+///
+/// ```no_run
+/// # use yarnspinner::prelude::*;
+/// let report = validate_project(["dialogue/intro.yarn", "dialogue/shop.yarn"], ValidationOptions::default());
+/// if !report.is_valid() {
+///     eprintln!("{report}");
+///     std::process::exit(1);
+/// }
+/// ```
+pub fn validate_project(
+    paths: impl IntoIterator<Item = impl AsRef<Path>>,
+    options: ValidationOptions,
+) -> ValidationReport {
+    let ValidationOptions {
+        mut compiler,
+        mut analysis,
+        translations,
+    } = options;
+    let mut report = ValidationReport::default();
+
+    for path in paths {
+        let path = path.as_ref();
+        if let Err(error) = compiler.try_read_file(path) {
+            report.unreadable_files.push(UnreadableFile {
+                path: path.to_owned(),
+                error: error.to_string(),
+            });
+        }
+    }
+
+    let compilation = match compiler.compile() {
+        Ok(compilation) => compilation,
+        Err(CompilerError(diagnostics)) => {
+            report.compiler_diagnostics = diagnostics;
+            return report;
+        }
+    };
+    report.compiler_diagnostics = compilation.warnings.clone();
+    report.stats = Some(compilation.stats());
+    report.review = Some(compilation.review_report());
+
+    if let Some(program) = compilation.program.clone() {
+        report.broken_jumps = broken_jumps(&compilation, &program);
+        let mut dialogue = Dialogue::new(
+            Box::new(MemoryVariableStorage::new()),
+            Box::new(StringTableTextProvider::new()),
+        );
+        dialogue.add_program(program).analyse(&mut analysis);
+        report.analysis = analysis.finish_analysis();
+    }
+
+    let line_ids: HashSet<_> = compilation.string_table.keys().collect();
+    report.translations = translations
+        .into_iter()
+        .map(|(language, string_table)| TranslationReport::new(language, &string_table, &line_ids))
+        .collect();
+    report
+}
+
+/// The configuration of [`validate_project`].
+#[derive(Debug)]
+pub struct ValidationOptions {
+    /// The compiler used to compile the project. The files passed to [`validate_project`] are added to it.
+    /// Configure it like any other [`Compiler`] to check the project as strictly as your game does,
+    /// e.g. with [`Compiler::extend_library`], [`Compiler::with_strict_variable_declarations`] or [`Compiler::with_draft_lines_forbidden`].
+    ///
+    /// Defaults to [`Compiler::default`].
+    pub compiler: Compiler,
+
+    /// The analysers run on the compiled program.
+    ///
+    /// Defaults to [`Context::default_analysers`].
+    pub analysis: Context,
+
+    /// The string tables of the translations to audit, see [`ValidationOptions::audit_translation`].
+    pub translations: Vec<(Language, StringTable)>,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        Self {
+            compiler: Compiler::default(),
+            analysis: Context::default_analysers(),
+            translations: Vec::new(),
+        }
+    }
+}
+
+impl ValidationOptions {
+    /// Creates options that run all default checks, see [`ValidationOptions::default`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the compiler used to compile the project, see [`ValidationOptions::compiler`].
+    pub fn with_compiler(&mut self, compiler: Compiler) -> &mut Self {
+        self.compiler = compiler;
+        self
+    }
+
+    /// Sets the analysers run on the compiled program, see [`ValidationOptions::analysis`].
+    pub fn with_analysis(&mut self, analysis: Context) -> &mut Self {
+        self.analysis = analysis;
+        self
+    }
+
+    /// Checks that the string table of the given translation contains exactly the lines of the project.
+    pub fn audit_translation(
+        &mut self,
+        language: impl Into<Language>,
+        string_table: StringTable,
+    ) -> &mut Self {
+        self.translations.push((language.into(), string_table));
+        self
+    }
+}
+
+/// The result of [`validate_project`]. All lists are sorted where the order is not given by the checks themselves.
+/// The [`Display`] implementation lists every problem found.
+///
+/// With the `serde` feature, the report can be exported as JSON with [`ValidationReport::to_json`] for further processing.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ValidationReport {
+    /// The files that could not be read. They are left out of all other checks.
+    pub unreadable_files: Vec<UnreadableFile>,
+
+    /// The errors of a failed compilation, or the warnings of a successful one.
+    pub compiler_diagnostics: Vec<Diagnostic>,
+
+    /// The diagnoses of the analysers in [`ValidationOptions::analysis`], including notes.
+    /// Empty if the compilation failed.
+    pub analysis: Vec<Diagnosis>,
+
+    /// The `<<jump>>`s that lead to nodes that don't exist.
+    /// Jumps to an expression, e.g. `<<jump {$destination}>>`, cannot be checked and are not included.
+    pub broken_jumps: Vec<BrokenJump>,
+
+    /// The results for each translation added with [`ValidationOptions::audit_translation`], in the order they were added.
+    /// Empty if the compilation failed.
+    pub translations: Vec<TranslationReport>,
+
+    /// The statistics of the compiled project. [`None`] if the compilation failed.
+    pub stats: Option<CompilationStats>,
+
+    /// The review status of the lines of the project. [`None`] if the compilation failed.
+    pub review: Option<ReviewReport>,
+}
+
+/// A file passed to [`validate_project`] that could not be read. Part of a [`ValidationReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UnreadableFile {
+    /// The path that was passed to [`validate_project`].
+    pub path: PathBuf,
+    /// The reason the file could not be read.
+    pub error: String,
+}
+
+/// A `<<jump>>` to a node that does not exist. Part of a [`ValidationReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BrokenJump {
+    /// The node containing the jump.
+    pub node_name: String,
+    /// The name of the file the node was found in.
+    pub file_name: Option<String>,
+    /// The node the jump leads to.
+    pub destination: String,
+}
+
+/// The audit of a single translation's string table, part of a [`ValidationReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TranslationReport {
+    /// The language of the translation.
+    pub language: Language,
+    /// Lines of the project that the translation has no entry for, sorted.
+    pub missing_lines: Vec<LineId>,
+    /// Entries of the translation whose line is not in the project, sorted.
+    pub orphaned_lines: Vec<LineId>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if every file was read and compiled and no check found an error.
+    /// Compiler warnings and analyser warnings and notes do not count as errors.
+    pub fn is_valid(&self) -> bool {
+        self.unreadable_files.is_empty()
+            && self.stats.is_some()
+            && self.error_count() == 0
+            && self.broken_jumps.is_empty()
+            && self
+                .translations
+                .iter()
+                .all(|translation| translation.is_complete())
+    }
+
+    /// The number of compiler and analyser diagnostics with an error severity.
+    pub fn error_count(&self) -> usize {
+        self.compiler_diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == DiagnosticSeverity::Error)
+            .count()
+            + self
+                .analysis
+                .iter()
+                .filter(|diagnosis| diagnosis.severity == DiagnosisSeverity::Error)
+                .count()
+    }
+
+    /// The number of compiler and analyser diagnostics with a warning severity.
+    pub fn warning_count(&self) -> usize {
+        self.compiler_diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == DiagnosticSeverity::Warning)
+            .count()
+            + self
+                .analysis
+                .iter()
+                .filter(|diagnosis| diagnosis.severity == DiagnosisSeverity::Warning)
+                .count()
+    }
+
+    /// Exports the report as pretty-printed JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Failed to serialize validation report")
+    }
+}
+
+impl TranslationReport {
+    /// Returns `true` if the translation contains exactly the lines of the project.
+    pub fn is_complete(&self) -> bool {
+        self.missing_lines.is_empty() && self.orphaned_lines.is_empty()
+    }
+
+    fn new(language: Language, string_table: &StringTable, line_ids: &HashSet<&LineId>) -> Self {
+        let translated_ids: HashSet<_> = string_table.keys().collect();
+        Self {
+            language,
+            missing_lines: sorted_difference(line_ids, &translated_ids),
+            orphaned_lines: sorted_difference(&translated_ids, line_ids),
+        }
+    }
+}
+
+fn broken_jumps(compilation: &Compilation, program: &Program) -> Vec<BrokenJump> {
+    let mut broken_jumps: Vec<_> = program
+        .nodes
+        .iter()
+        .flat_map(|(node_name, node)| {
+            node.jump_destinations()
+                .filter(|destination| !program.nodes.contains_key(destination))
+                .map(|destination| BrokenJump {
+                    node_name: node_name.clone(),
+                    file_name: compilation
+                        .file_name_of_node(node_name)
+                        .map(ToOwned::to_owned),
+                    destination,
+                })
+        })
+        .collect();
+    broken_jumps.sort_unstable();
+    broken_jumps.dedup();
+    broken_jumps
+}
+
+fn sorted_difference(lhs: &HashSet<&LineId>, rhs: &HashSet<&LineId>) -> Vec<LineId> {
+    let mut difference: Vec<_> = lhs.difference(rhs).map(|&id| id.clone()).collect();
+    difference.sort_unstable();
+    difference
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for file in &self.unreadable_files {
+            writeln!(
+                f,
+                "{}: could not be read: {}",
+                file.path.display(),
+                file.error
+            )?;
+        }
+        for diagnostic in &self.compiler_diagnostics {
+            writeln!(f, "{diagnostic}")?;
+        }
+        for diagnosis in &self.analysis {
+            writeln!(f, "{diagnosis}")?;
+        }
+        for jump in &self.broken_jumps {
+            let BrokenJump {
+                node_name,
+                destination,
+                ..
+            } = jump;
+            writeln!(
+                f,
+                "{node_name}: jumps to the node \"{destination}\", which does not exist"
+            )?;
+        }
+        for translation in &self.translations {
+            let language = &translation.language;
+            for id in &translation.missing_lines {
+                writeln!(f, "{id}: missing from the {language} translation")?;
+            }
+            for id in &translation.orphaned_lines {
+                writeln!(
+                    f,
+                    "{id}: in the {language} translation but not in the project"
+                )?;
+            }
+        }
+        if self.is_valid() {
+            writeln!(f, "The Yarn project is valid.")?;
+        }
+        Ok(())
+    }
+}
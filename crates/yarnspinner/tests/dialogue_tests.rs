@@ -3,9 +3,15 @@
 //! ## Implementation notes
 //! `TestDumpingCode` was not ported because `GetByteCode` is not used by a user directly and thus was not implemented at all.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::Poll;
 use test_base::prelude::*;
 use yarnspinner::compiler::*;
+use yarnspinner::core::*;
 use yarnspinner::runtime::*;
 
 mod test_base;
@@ -184,6 +190,30 @@ fn test_getting_headers() {
     assert_eq!(headers, expected_headers);
 }
 
+#[test]
+fn test_node_introspection() {
+    let compilation = Compiler::new()
+        .add_file(File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start\ntags: intro tutorial\nwhen: once\nwhen: always\n---\nHello\n===\ntitle: Other\n---\nBye\n==="
+                .to_string(),
+        })
+        .compile()
+        .unwrap();
+    let dialogue = TestBase::new().with_compilation(compilation).dialogue;
+
+    let mut node_names: Vec<_> = dialogue.nodes().map(|node| node.name.as_str()).collect();
+    node_names.sort();
+    assert_eq!(node_names, ["Other", "Start"]);
+
+    let headers = dialogue.headers_for_node("Start").unwrap();
+    assert_eq!(headers["when"], ["once", "always"]);
+    assert_eq!(headers["title"], ["Start"]);
+    assert_eq!(dialogue.node("Start").unwrap().tags, ["intro", "tutorial"]);
+    assert!(dialogue.headers_for_node("Missing").is_none());
+    assert!(dialogue.node("Missing").is_none());
+}
+
 /// ## Implementation note
 /// Corresponds to `TestPrepareForLine`
 #[test]
@@ -218,6 +248,48 @@ fn test_line_hints() {
     assert!(line_hints_were_sent);
 }
 
+#[test]
+fn test_line_hint_lookahead() {
+    let result = Compiler::new()
+        .add_file(File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start\n---\nStart #line:start\n-> Shop #line:to_shop\n    <<jump Shop>>\n-> Leave #line:leave\n===\n\
+                     title: Shop\n---\nShop #line:shop\n<<jump Storage>>\n===\n\
+                     title: Storage\n---\nStorage #line:storage\n==="
+                .to_string(),
+        })
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    let mut line_hints = |lookahead: usize| -> Vec<LineId> {
+        dialogue
+            .set_line_hints_enabled(true)
+            .set_line_hint_lookahead(lookahead)
+            .set_node("Start")
+            .unwrap();
+        dialogue.pop_line_hints().unwrap()
+    };
+
+    assert_eq!(
+        vec![
+            LineId::from("line:start"),
+            "line:to_shop".into(),
+            "line:leave".into()
+        ],
+        line_hints(0)
+    );
+    assert_eq!(
+        vec![
+            LineId::from("line:start"),
+            "line:to_shop".into(),
+            "line:leave".into(),
+            "line:shop".into()
+        ],
+        line_hints(1)
+    );
+    assert_eq!(5, line_hints(2).len());
+}
+
 #[test]
 fn test_function_argument_type_inference() {
     let test_base = TestBase::new().extend_library(|library| {
@@ -322,8 +394,2153 @@ fn test_selecting_option_from_inside_option_callback() {
                 DialogueEvent::Command(_)
                 | DialogueEvent::NodeComplete(_)
                 | DialogueEvent::NodeStart(_)
-                | DialogueEvent::LineHints(_) => {}
+                | DialogueEvent::LineHints(_)
+                | DialogueEvent::BreakpointHit(_)
+                | DialogueEvent::FunctionPending(_)
+                | DialogueEvent::TextPending(_)
+                | DialogueEvent::TriggerFired(_)
+                | DialogueEvent::VariableChanged { .. }
+                | DialogueEvent::OptionsTimeout(_) => {}
+            }
+        }
+    }
+}
+
+#[test]
+fn test_resuming_from_state_snapshot() {
+    let result = Compiler::from_test_source(
+        "first line\n-> option 1\n    picked one\n-> option 2\n    picked two\n",
+    )
+    .compile()
+    .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result.clone()).dialogue;
+    assert!(dialogue.state_snapshot().is_none());
+
+    dialogue.set_node("Start").unwrap();
+    let events = dialogue.continue_().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(line) if line.text == "first line")));
+    let line_snapshot = dialogue.state_snapshot().unwrap();
+    assert_eq!(line_snapshot.node_name(), "Start");
+    assert!(line_snapshot.pending_options().is_empty());
+
+    let events = dialogue.continue_().unwrap();
+    assert!(matches!(events.last(), Some(DialogueEvent::Options(_))));
+    let options_snapshot = dialogue.state_snapshot().unwrap();
+    assert_eq!(options_snapshot.pending_options().len(), 2);
+
+    // Resume after the line in a fresh dialogue
+    let mut dialogue = TestBase::new().with_compilation(result.clone()).dialogue;
+    let events = dialogue.restore_snapshot(line_snapshot).unwrap();
+    assert!(events.is_empty());
+    let events = dialogue.continue_().unwrap();
+    assert!(matches!(events.last(), Some(DialogueEvent::Options(_))));
+
+    // Resume while waiting for an option selection in a fresh dialogue
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    let events = dialogue.restore_snapshot(options_snapshot).unwrap();
+    let Some(DialogueEvent::Options(options)) = events.first() else {
+        panic!("Expected the pending options to be delivered again, got {events:?}");
+    };
+    assert_eq!(options[1].line.text, "option 2");
+    dialogue.set_selected_option(OptionId(1)).unwrap();
+    let events = dialogue.continue_().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(line) if line.text == "picked two")));
+}
+
+#[test]
+fn test_rewinding_dialogue() {
+    let result = Compiler::from_test_source(
+        "first line\n<<set $gold to 10>>\nsecond line\n-> option 1\n    picked one\n-> option 2\n    picked two\n",
+    )
+    .compile()
+    .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_rewind_capacity(10);
+    dialogue.set_node("Start").unwrap();
+
+    dialogue.continue_().unwrap();
+    dialogue.continue_().unwrap();
+    assert_eq!(
+        dialogue.variable_storage().get("$gold").unwrap(),
+        YarnValue::from(10.0)
+    );
+    dialogue.continue_().unwrap();
+    dialogue.set_selected_option(OptionId(0)).unwrap();
+    assert_eq!(dialogue.rewindable_steps(), 2);
+
+    // Going back to the options allows picking another one
+    let events = dialogue.rewind(0).unwrap();
+    assert!(matches!(events.as_slice(), [DialogueEvent::Options(_)]));
+    dialogue.set_selected_option(OptionId(1)).unwrap();
+    let events = dialogue.continue_().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(line) if line.text == "picked two")));
+
+    // Going back to before the variable was set reverts it
+    let events = dialogue.rewind(3).unwrap();
+    let [DialogueEvent::Line(line)] = events.as_slice() else {
+        panic!("Expected the first line to be delivered again, got {events:?}");
+    };
+    assert_eq!(line.text, "first line");
+    assert!(!dialogue.variable_storage().contains("$gold"));
+    assert_eq!(dialogue.rewindable_steps(), 0);
+    assert!(dialogue.rewind(1).is_err());
+
+    let events = dialogue.continue_().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(line) if line.text == "second line")));
+}
+
+#[test]
+fn test_failed_rewind_keeps_rewind_history() {
+    let original = Compiler::from_test_source("One\nTwo\nThree")
+        .compile()
+        .unwrap();
+    let patched = Compiler::from_test_source("One\nTwo\nThree\nFour")
+        .compile()
+        .unwrap();
+    let mut string_table = original.string_table.clone();
+    string_table.extend(patched.string_table.clone());
+    let original_program = original.program.clone().unwrap();
+    let mut dialogue = TestBase::new()
+        .with_compilation(original)
+        .with_string_table(string_table)
+        .dialogue;
+    dialogue.set_rewind_capacity(10);
+    dialogue.set_node("Start").unwrap();
+    for _ in 0..3 {
+        dialogue.continue_().unwrap();
+    }
+    assert_eq!(dialogue.rewindable_steps(), 2);
+
+    // The remembered points no longer match the instructions of the node
+    dialogue.patch_program(patched.program.unwrap());
+    assert!(dialogue.rewind(1).is_err());
+    assert_eq!(dialogue.rewindable_steps(), 2);
+
+    dialogue.patch_program(original_program);
+    let events = dialogue.rewind(1).unwrap();
+    let [DialogueEvent::Line(line)] = events.as_slice() else {
+        panic!("Expected the second line to be delivered again, got {events:?}");
+    };
+    assert_eq!(line.text, "Two");
+    assert_eq!(dialogue.rewindable_steps(), 1);
+}
+
+#[test]
+fn test_history_log() {
+    let result = Compiler::from_test_source(
+        "<<set $name to \"Bob\">>\nHi {$name}!\n-> option 1\n    picked one\n-> option 2\n    picked two\n",
+    )
+    .compile()
+    .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_history_enabled(true);
+    dialogue.set_node("Start").unwrap();
+
+    while !dialogue.is_waiting_for_option_selection() {
+        dialogue.continue_().unwrap();
+    }
+    dialogue.set_selected_option(OptionId(1)).unwrap();
+    while dialogue.is_active() {
+        dialogue.continue_().unwrap();
+    }
+
+    let history = dialogue.history();
+    assert_eq!(history.len(), 3);
+    assert!(matches!(&history[0], HistoryEntry::Line(line) if line.text == "Hi Bob!"));
+    let HistoryEntry::Options { options, .. } = &history[1] else {
+        panic!("Expected options, got {:?}", history[1]);
+    };
+    assert_eq!(options.len(), 2);
+    assert_eq!(history[1].selected_option().unwrap().line.text, "option 2");
+    assert!(matches!(&history[2], HistoryEntry::Line(line) if line.text == "picked two"));
+
+    dialogue.clear_history();
+    assert!(dialogue.history().is_empty());
+}
+
+#[test]
+fn test_breakpoints_and_stepping() {
+    let result = Compiler::from_test_source("first line\n<<set $gold to 1 + 2>>\nsecond line\n")
+        .compile()
+        .unwrap();
+    // The test node's body starts after the header and the `---` separator
+    let set_line = 3;
+    let instruction_index = result.debug_info["Start"]
+        .first_instruction_on_line(set_line)
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    let breakpoint = Breakpoint::new("Start", instruction_index);
+    dialogue.add_breakpoint(breakpoint.clone());
+    dialogue.set_node("Start").unwrap();
+
+    dialogue.continue_().unwrap();
+    let events = dialogue.continue_().unwrap();
+    assert_eq!(
+        events,
+        vec![DialogueEvent::BreakpointHit(breakpoint.clone())]
+    );
+    assert_eq!(dialogue.instruction_index(), Some(instruction_index));
+    assert!(dialogue.current_instruction().is_some());
+    assert!(dialogue.stack().is_empty());
+
+    assert!(dialogue.step_instruction().unwrap().is_empty());
+    assert_eq!(dialogue.instruction_index(), Some(instruction_index + 1));
+    assert_eq!(dialogue.stack(), vec![YarnValue::from(1.0)]);
+
+    // Resuming does not hit the same breakpoint again
+    let events = dialogue.continue_().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(line) if line.text == "second line")));
+    assert_eq!(
+        dialogue.variable_storage().get("$gold").unwrap(),
+        YarnValue::from(3.0)
+    );
+
+    assert!(dialogue.remove_breakpoint(&breakpoint));
+    assert_eq!(dialogue.breakpoints().count(), 0);
+}
+
+#[test]
+fn test_dialogue_events_can_be_hashed() {
+    let result = Compiler::from_test_source(
+        "Hello [wave size=2.5]there[/wave]!\n<<set $gold to 1>>\nHello [wave size=2.5]there[/wave]!",
+    )
+    .compile()
+    .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let mut events = Vec::new();
+    while dialogue.is_active() {
+        events.extend(dialogue.continue_().unwrap());
+    }
+    let lines: Vec<_> = events
+        .iter()
+        .filter(|event| matches!(event, DialogueEvent::Line(_)))
+        .collect();
+    // Both lines have the same text and markup, but different line IDs
+    assert_eq!(lines.len(), 2);
+    assert_eq!(hash(lines[0]), hash(&lines[0].clone()));
+    assert_ne!(hash(lines[0]), hash(lines[1]));
+    let node_start = DialogueEvent::NodeStart("Start".to_owned());
+    assert!(events
+        .iter()
+        .any(|event| event == &node_start && hash(event) == hash(&node_start)));
+
+    assert_eq!(hash(&YarnValue::from(0.0)), hash(&YarnValue::from(-0.0)));
+    let other_nan = f32::from_bits(f32::NAN.to_bits() + 1);
+    assert!(other_nan.is_nan());
+    let nan = YarnValue::from(f32::NAN);
+    assert_eq!(hash(&nan), hash(&YarnValue::from(other_nan)));
+    // Not reflexive, which is why `YarnValue` does not implement `Eq`
+    assert_ne!(nan, nan.clone());
+
+    fn hash(value: &impl std::hash::Hash) -> u64 {
+        use std::hash::Hasher;
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[test]
+fn test_custom_instruction_handler() {
+    #[derive(Debug)]
+    struct GrantBonus;
+
+    impl InstructionHandler for GrantBonus {
+        fn execute(
+            &mut self,
+            instruction: &Instruction,
+            context: &mut InstructionContext,
+        ) -> std::result::Result<(), InstructionHandlerError> {
+            let bonus: f32 = instruction.read_operand(0);
+            context
+                .variable_storage()
+                .set("$bonus".to_owned(), bonus.into())?;
+            context.wait_for_continue();
+            Ok(())
+        }
+    }
+
+    const GRANT_BONUS: i32 = Instruction::FIRST_EXTENSION_OPCODE;
+    let mut result = Compiler::from_test_source("Done").compile().unwrap();
+    // Stand-in for a custom compiler pass
+    let node = result
+        .program
+        .as_mut()
+        .unwrap()
+        .nodes
+        .get_mut("Start")
+        .unwrap();
+    node.instructions.insert(
+        0,
+        Instruction {
+            opcode: GRANT_BONUS,
+            operands: vec![10.0.into()],
+        },
+    );
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    assert!(dialogue
+        .register_instruction_handler(OpCode::RunLine as i32, GrantBonus)
+        .is_err());
+    dialogue
+        .register_instruction_handler(GRANT_BONUS, GrantBonus)
+        .unwrap();
+    dialogue.set_node("Start").unwrap();
+
+    let events = dialogue.continue_().unwrap();
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(_))));
+    assert_eq!(
+        dialogue.variable_storage().get("$bonus").unwrap(),
+        YarnValue::from(10.0)
+    );
+    let events = dialogue.continue_().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(line) if line.text == "Done")));
+
+    dialogue.remove_instruction_handler(GRANT_BONUS);
+    dialogue.set_node("Start").unwrap();
+    assert!(matches!(
+        dialogue.continue_(),
+        Err(DialogueError::UnknownInstruction { .. })
+    ));
+}
+
+#[test]
+fn test_synchronizing_dialogue_between_host_and_replicas() {
+    let result = Compiler::from_test_source(
+        "<<declare $name = \"\">>\n<<set $gold to 1>>\nHello {$name}\n-> option 1\n    picked one\n-> option 2\n    picked two\n",
+    )
+    .compile()
+    .unwrap();
+    let mut host_dialogue = TestBase::new().with_compilation(result.clone()).dialogue;
+    let mut replica_dialogue = TestBase::new().with_compilation(result.clone()).dialogue;
+    let mut host = DialogueSyncHost::new();
+    let mut replica = DialogueSyncReplica::new();
+
+    replica
+        .apply(&mut replica_dialogue, host.snapshot(&host_dialogue))
+        .unwrap();
+    let set_node = host.set_node(&mut host_dialogue, "Start").unwrap();
+    assert!(matches!(
+        DialogueSyncReplica::new().apply(&mut replica_dialogue, set_node.clone()),
+        Err(DialogueSyncError::MissingSnapshot { .. })
+    ));
+    replica.apply(&mut replica_dialogue, set_node).unwrap();
+
+    // Changed by game code, so the replica cannot know about it on its own
+    host_dialogue
+        .variable_storage_mut()
+        .set("$name".to_owned(), "Alice".into())
+        .unwrap();
+    let (host_events, message) = host.continue_(&mut host_dialogue).unwrap();
+    assert_eq!(message.variable_changes.len(), 1);
+    assert_eq!(
+        replica.apply(&mut replica_dialogue, message).unwrap(),
+        host_events
+    );
+    assert_eq!(
+        replica_dialogue.variable_storage().variables(),
+        host_dialogue.variable_storage().variables()
+    );
+
+    let (host_events, message) = host.continue_(&mut host_dialogue).unwrap();
+    assert!(matches!(
+        host_events.as_slice(),
+        [DialogueEvent::Options(_)]
+    ));
+    replica.apply(&mut replica_dialogue, message).unwrap();
+
+    // Late joiners get the pending options from the snapshot
+    let mut late_dialogue = TestBase::new().with_compilation(result).dialogue;
+    let mut late_replica = DialogueSyncReplica::new();
+    assert_eq!(
+        late_replica
+            .apply(&mut late_dialogue, host.snapshot(&host_dialogue))
+            .unwrap(),
+        host_events
+    );
+
+    let message = host
+        .set_selected_option(&mut host_dialogue, OptionId(1))
+        .unwrap();
+    replica
+        .apply(&mut replica_dialogue, message.clone())
+        .unwrap();
+    late_replica
+        .apply(&mut late_dialogue, message.clone())
+        .unwrap();
+    assert!(matches!(
+        replica.apply(&mut replica_dialogue, message),
+        Err(DialogueSyncError::OutOfOrder { .. })
+    ));
+
+    let (host_events, message) = host.continue_(&mut host_dialogue).unwrap();
+    assert_eq!(
+        late_replica.apply(&mut late_dialogue, message).unwrap(),
+        host_events
+    );
+}
+
+#[test]
+fn test_async_function() {
+    #[derive(Debug)]
+    struct WaitForName(Arc<AtomicBool>);
+
+    impl Future for WaitForName {
+        type Output = String;
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+            if self.0.load(Ordering::Relaxed) {
+                Poll::Ready("Alice".to_owned())
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    let is_name_loaded = Arc::new(AtomicBool::new(false));
+    let fetch_name = {
+        let is_name_loaded = is_name_loaded.clone();
+        move || YarnFuture::new(WaitForName(is_name_loaded.clone()))
+    };
+    let mut library = Library::new();
+    library.add_async_function("fetch_name", fetch_name.clone());
+    let result = Compiler::from_test_source("Hello {fetch_name()}!")
+        .extend_library(library)
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue
+        .library_mut()
+        .add_async_function("fetch_name", fetch_name);
+    dialogue.set_node("Start").unwrap();
+
+    let events = dialogue.continue_().unwrap();
+    assert!(events.contains(&DialogueEvent::FunctionPending("fetch_name".to_owned())));
+    assert!(dialogue.state_snapshot().is_none());
+    let events = dialogue.continue_().unwrap();
+    assert_eq!(
+        events,
+        vec![DialogueEvent::FunctionPending("fetch_name".to_owned())]
+    );
+
+    is_name_loaded.store(true, Ordering::Relaxed);
+    let events = dialogue.continue_().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(line) if line.text == "Hello Alice!")));
+}
+
+#[test]
+fn test_editing_a_node_of_a_running_project() {
+    let file_name = "Project.yarn";
+    let compiler = Compiler::new()
+        .add_file(File {
+            file_name: file_name.to_owned(),
+            source: "title: Start\n---\n<<declare $gold = 5>>\nYou have {$gold} gold.\n<<jump Shop>>\n===\ntitle: Shop\n---\nWelcome!\n===\n"
+                .to_owned(),
+        })
+        .clone();
+    let project = compiler.compile().unwrap();
+
+    let edited = compiler
+        .compile_node(
+            &project,
+            file_name,
+            "title: Shop\n---\nWelcome! You have {$gold} gold to spend.\n===\n",
+        )
+        .unwrap();
+    assert_eq!(
+        edited
+            .program
+            .as_ref()
+            .unwrap()
+            .nodes
+            .keys()
+            .collect::<Vec<_>>(),
+        vec!["Shop"]
+    );
+    let mut string_table = project.string_table.clone();
+    string_table.extend(edited.string_table);
+    let mut dialogue = TestBase::new()
+        .with_compilation(project.clone())
+        .with_string_table(string_table)
+        .dialogue;
+    dialogue.replace_nodes(edited.program.unwrap());
+
+    let (node_name, instruction_index) = project
+        .instruction_at_position(
+            file_name,
+            Position {
+                line: 3,
+                character: 0,
+            },
+        )
+        .unwrap();
+    assert_eq!(node_name, "Start");
+    assert_eq!(
+        Some(instruction_index),
+        project.debug_info["Start"].first_instruction_on_line(3)
+    );
+
+    dialogue.set_node("Start").unwrap();
+    let mut lines = Vec::new();
+    while dialogue.is_active() {
+        for event in dialogue.continue_().unwrap() {
+            if let DialogueEvent::Line(line) = event {
+                lines.push(line.text);
+            }
+        }
+    }
+    assert_eq!(
+        lines,
+        vec!["You have 5 gold.", "Welcome! You have 5 gold to spend."]
+    );
+}
+
+#[test]
+fn test_triggers_fire_once() {
+    let compilation = Compiler::new()
+        .add_file(File {
+            file_name: "Triggers.yarn".to_owned(),
+            source: "title: Start\n---\nHello. #line:greeting\n<<jump Cave>>\n===\ntitle: Cave\ntags: dark\n---\nIt's dark in here.\n===\n"
+                .to_owned(),
+        })
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue
+        .add_trigger("greeted", TriggerCondition::Line("line:greeting".into()))
+        .add_trigger("entered_cave", TriggerCondition::Node("Cave".to_owned()))
+        .add_trigger("in_the_dark", TriggerCondition::NodeTag("dark".to_owned()));
+    assert!(!dialogue.has_trigger_fired("greeted"));
+
+    let run_start = |dialogue: &mut Dialogue| {
+        dialogue.set_node("Start").unwrap();
+        let mut fired_triggers = Vec::new();
+        while dialogue.is_active() {
+            let events = dialogue.continue_().unwrap();
+            for (index, event) in events.iter().enumerate() {
+                if let DialogueEvent::TriggerFired(name) = event {
+                    assert!(matches!(
+                        events[index - 1],
+                        DialogueEvent::Line(_)
+                            | DialogueEvent::NodeStart(_)
+                            | DialogueEvent::TriggerFired(_)
+                    ));
+                    fired_triggers.push(name.clone());
+                }
+            }
+        }
+        fired_triggers
+    };
+
+    assert_eq!(
+        run_start(&mut dialogue),
+        vec!["greeted", "entered_cave", "in_the_dark"]
+    );
+    assert!(dialogue.has_trigger_fired("greeted"));
+    assert!(run_start(&mut dialogue).is_empty());
+
+    dialogue.reset_trigger("greeted").unwrap();
+    assert!(dialogue.remove_trigger("in_the_dark"));
+    assert_eq!(run_start(&mut dialogue), vec!["greeted"]);
+    assert_eq!(dialogue.triggers().count(), 2);
+}
+
+#[test]
+fn test_seeded_random_functions() {
+    let run = |seed: u64| {
+        let mut dialogue = TestBase::new().dialogue;
+        let compilation = Compiler::from_test_source(
+            "{random()} {random_range(1, 6)} {random_range(0.5, 1.5)} {dice(20)}\n{random()}",
+        )
+        .extend_library(dialogue.library().clone())
+        .compile()
+        .unwrap();
+        dialogue = TestBase::new().with_compilation(compilation).dialogue;
+        dialogue.set_rng_seed(seed).set_node("Start").unwrap();
+        let mut lines = Vec::new();
+        while dialogue.is_active() {
+            for event in dialogue.continue_().unwrap() {
+                if let DialogueEvent::Line(line) = event {
+                    lines.push(line.text);
+                }
+            }
+        }
+        lines
+    };
+    let lines = run(42);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines, run(42));
+    assert_ne!(lines, run(7));
+}
+
+#[test]
+fn test_verify_program() {
+    let compile = |source: &str| Compiler::from_test_source(source).compile().unwrap();
+    let old_compilation = compile("Hello. #line:hello");
+    let new_compilation = compile("Hello. #line:hello\nGoodbye. #line:goodbye");
+    let program_hash = new_compilation.program.as_ref().unwrap().content_hash();
+    assert_eq!(new_compilation.content_hash, Some(program_hash));
+    assert_eq!(
+        compile("Hello. #line:hello").content_hash,
+        old_compilation.content_hash
+    );
+    assert_ne!(old_compilation.content_hash, new_compilation.content_hash);
+
+    let dialogue = TestBase::new()
+        .with_program(new_compilation.program.clone().unwrap())
+        .with_string_table(new_compilation.string_table.clone())
+        .dialogue;
+    assert_eq!(dialogue.verify_program(None).unwrap(), program_hash);
+    assert_eq!(
+        dialogue
+            .verify_program(new_compilation.content_hash)
+            .unwrap(),
+        program_hash
+    );
+    assert!(matches!(
+        dialogue.verify_program(old_compilation.content_hash),
+        Err(DialogueError::ProgramHashMismatch { expected, actual })
+            if Some(expected) == old_compilation.content_hash && actual == program_hash
+    ));
+
+    let stale_dialogue = TestBase::new()
+        .with_program(new_compilation.program.unwrap())
+        .with_string_table(old_compilation.string_table)
+        .dialogue;
+    match stale_dialogue.verify_program(None) {
+        Err(DialogueError::StringTableMismatch {
+            line_count,
+            missing_line_ids,
+            ..
+        }) => {
+            assert_eq!(line_count, 2);
+            assert_eq!(missing_line_ids, vec![LineId::from("line:goodbye")]);
+        }
+        result => panic!("Expected a string table mismatch, got {result:?}"),
+    }
+}
+
+#[test]
+fn test_command_parameters_are_typed() {
+    let compilation = Compiler::from_test_source(
+        "<<set $gold to 5>>\n<<give {$gold * 2} \"{$gold}\" coins true>>",
+    )
+    .compile()
+    .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let command = dialogue
+        .continue_()
+        .unwrap()
+        .into_iter()
+        .find_map(|event| match event {
+            DialogueEvent::Command(command) => Some(command),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(command.raw, "give 10 \"5\" coins true");
+    assert_eq!(command.name, "give");
+    assert_eq!(
+        command.parameters,
+        vec![
+            YarnValue::Number(10.0),
+            YarnValue::String("5".to_owned()),
+            YarnValue::String("coins".to_owned()),
+            YarnValue::Boolean(true),
+        ]
+    );
+}
+
+#[test]
+fn test_line_pagination() {
+    let compilation = Compiler::from_test_source(
+        "Alice: The quick brown fox jumps over the lazy dog.\nShort line.",
+    )
+    .compile()
+    .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue.set_line_pagination(|text| text.chars().count() <= 20);
+    assert!(dialogue.line_pagination_enabled());
+
+    dialogue.set_node("Start").unwrap();
+    let mut pages = Vec::new();
+    while dialogue.is_active() {
+        for event in dialogue.continue_().unwrap() {
+            if let DialogueEvent::Line(line) = event {
+                assert_eq!(line.character_name().is_some(), pages.len() < 3);
+                pages.push((line.text, dialogue.has_pending_pages()));
+            }
+        }
+    }
+    assert_eq!(
+        pages,
+        vec![
+            ("Alice: The quick brown fox".to_owned(), true),
+            ("Alice: jumps over the lazy".to_owned(), true),
+            ("Alice: dog.".to_owned(), false),
+            ("Short line.".to_owned(), false),
+        ]
+    );
+}
+
+#[test]
+fn test_variadic_function() {
+    let max = |first: f32, rest: &[YarnValue]| {
+        rest.iter()
+            .map(|value| f32::try_from(value.clone()).unwrap())
+            .fold(first, f32::max)
+    };
+    let mut library = Library::new();
+    library.add_function("max", max);
+    let result = Compiler::from_test_source("{max(1)}\n{max(1, 5, 3)}")
+        .extend_library(library)
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.library_mut().add_function("max", max);
+    dialogue.set_node("Start").unwrap();
+
+    let mut lines = Vec::new();
+    while dialogue.is_active() {
+        for event in dialogue.continue_().unwrap() {
+            if let DialogueEvent::Line(line) = event {
+                lines.push(line.text);
+            }
+        }
+    }
+    assert_eq!(lines, vec!["1", "5"]);
+}
+
+#[test]
+fn test_function_with_optional_parameter() {
+    let greet = |name: &str, greeting: Option<String>| {
+        let greeting = greeting.unwrap_or_else(|| "Hello".to_owned());
+        format!("{greeting}, {name}!")
+    };
+    let mut library = Library::new();
+    library.add_function("greet", greet);
+    let result = Compiler::from_test_source("{greet(\"Alice\")}\n{greet(\"Bob\", \"Welcome\")}")
+        .extend_library(library)
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.library_mut().add_function("greet", greet);
+    dialogue.set_node("Start").unwrap();
+
+    let mut lines = Vec::new();
+    while dialogue.is_active() {
+        for event in dialogue.continue_().unwrap() {
+            if let DialogueEvent::Line(line) = event {
+                lines.push(line.text);
+            }
+        }
+    }
+    assert_eq!(lines, vec!["Hello, Alice!", "Welcome, Bob!"]);
+}
+
+#[test]
+fn test_overloaded_function_dispatches_on_argument_count() {
+    let mut library = Library::new();
+    library
+        .add_overload("random", || 4_usize)
+        .add_overload("random", |max: usize| max)
+        .add_overload("random", |min: usize, max: usize| min * max);
+    let result = Compiler::from_test_source("{random()}\n{random(6)}\n{random(2, 6)}")
+        .extend_library(library.clone())
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.library_mut().import(library);
+    dialogue.set_node("Start").unwrap();
+
+    let mut lines = Vec::new();
+    while dialogue.is_active() {
+        for event in dialogue.continue_().unwrap() {
+            if let DialogueEvent::Line(line) = event {
+                lines.push(line.text);
             }
         }
     }
+    assert_eq!(lines, vec!["4", "6", "12"]);
+}
+
+#[test]
+fn test_choosing_start_node() {
+    let compilation = Compiler::new()
+        .add_file(File {
+            file_name: "Shopkeeper.yarn".to_owned(),
+            source: "title: Shopkeeper_Default\nwhen: always\n---\n<<declare $gold = 0>>\n<<declare $robbed_shop = false>>\nWelcome!\n===\n\
+                     title: Shopkeeper_Rich\nwhen: $gold >= 100\nwhen: not $robbed_shop\n---\nWelcome, valued customer!\n===\n\
+                     title: Shopkeeper_Angry\nwhen: $robbed_shop\n---\nGet out!\n===\n"
+                .to_owned(),
+        })
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    let candidates = ["Shopkeeper_Default", "Shopkeeper_Rich", "Shopkeeper_Angry"];
+
+    assert_eq!(
+        dialogue.choose_start_node(candidates).unwrap().as_deref(),
+        Some("Shopkeeper_Default")
+    );
+    dialogue
+        .variable_storage_mut()
+        .set("$gold".to_owned(), 150.into())
+        .unwrap();
+    assert_eq!(
+        dialogue.choose_start_node(candidates).unwrap().as_deref(),
+        Some("Shopkeeper_Rich")
+    );
+    dialogue
+        .variable_storage_mut()
+        .set("$robbed_shop".to_owned(), true.into())
+        .unwrap();
+    assert_eq!(
+        dialogue.choose_start_node(candidates).unwrap().as_deref(),
+        Some("Shopkeeper_Angry")
+    );
+    assert_eq!(
+        dialogue.choose_start_node(["Shopkeeper_Rich"]).unwrap(),
+        None
+    );
+    assert!(dialogue.choose_start_node(["Missing"]).is_err());
+
+    let chosen = dialogue
+        .choose_start_node_by(candidates, |node_name, headers| {
+            (!headers.contains_key("when") || node_name.ends_with("Rich")).then_some(1.0)
+        })
+        .unwrap();
+    assert_eq!(chosen.as_deref(), Some("Shopkeeper_Rich"));
+}
+
+#[test]
+fn test_fallible_function() {
+    let parse_number = |text: &str| text.parse::<f32>();
+    let mut library = Library::new();
+    library.add_fallible_function("parse_number", parse_number);
+    let result = Compiler::from_test_source("{parse_number(\"12\")}\n{parse_number(\"twelve\")}")
+        .extend_library(library)
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue
+        .library_mut()
+        .add_fallible_function("parse_number", parse_number);
+    dialogue.set_node("Start").unwrap();
+
+    let events = dialogue.continue_().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(line) if line.text == "12")));
+
+    let (function_name, node_name, source) = match dialogue.continue_().unwrap_err() {
+        DialogueError::FunctionFailed {
+            function_name,
+            node_name,
+            source,
+            ..
+        } => (function_name, node_name, source),
+        error => panic!("Expected the function to fail, but got {error:?}"),
+    };
+    assert_eq!(function_name, "parse_number");
+    assert_eq!(node_name, "Start");
+    assert_eq!(source.to_string(), "invalid float literal");
+}
+
+#[test]
+fn test_line_ids_for_reachable_nodes() {
+    let compilation = Compiler::new()
+        .add_file(File {
+            file_name: "Scenes.yarn".to_owned(),
+            source: "title: Start\n---\nHello. #line:hello\n<<jump Middle>>\n===\n\
+                     title: Middle\n---\n-> Stay #line:stay\n-> Leave #line:leave\n    <<jump End>>\n<<jump Start>>\n===\n\
+                     title: End\n---\nGoodbye. #line:goodbye\n===\n\
+                     title: Unreachable\n---\nNobody sees this. #line:unreachable\n===\n"
+                .to_owned(),
+        })
+        .compile()
+        .unwrap();
+    let dialogue = TestBase::new().with_compilation(compilation).dialogue;
+
+    assert_eq!(
+        dialogue.get_line_ids_for_node("Start"),
+        Some(vec!["line:hello".into()])
+    );
+    assert_eq!(
+        dialogue.get_line_ids_for_reachable_nodes("Start"),
+        Some(vec![
+            "line:hello".into(),
+            "line:stay".into(),
+            "line:leave".into(),
+            "line:goodbye".into(),
+        ])
+    );
+    assert_eq!(
+        dialogue.get_line_ids_for_reachable_nodes("End"),
+        Some(vec!["line:goodbye".into()])
+    );
+    assert_eq!(dialogue.get_line_ids_for_node("Missing"), None);
+}
+
+#[test]
+fn test_visit_tracking_functions() {
+    let compilation = Compiler::new()
+        .add_file(File {
+            file_name: "Visits.yarn".to_owned(),
+            source: "title: Start\n---\nVisits: {visited_count(\"Shop\")}\n<<jump Shop>>\n===\n\
+                     title: Shop\n---\n<<if visited(\"Shop\")>>\nBack again\n<<else>>\nFirst time\n<<endif>>\n\
+                     <<if visited_count(\"Shop\") < 1>>\n<<jump Shop>>\n<<endif>>\n===\n"
+                .to_owned(),
+        })
+        .compile()
+        .unwrap();
+
+    TestBase::new()
+        .with_test_plan(
+            TestPlan::new()
+                .expect_line("Visits: 0")
+                .expect_line("First time")
+                .expect_line("Back again"),
+        )
+        .with_compilation(compilation)
+        .run_standard_testcase();
+}
+
+#[test]
+fn test_visit_count() {
+    let compilation =
+        Compiler::from_test_source("Been here before: {visited(\"Shop\")}\n<<jump Shop>>")
+            .add_file(File {
+                file_name: "Shop.yarn".to_owned(),
+                source: "title: Shop\ntracking: always\n---\nWelcome!\n===\n".to_owned(),
+            })
+            .compile()
+            .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    assert_eq!(0, dialogue.visit_count("Shop"));
+    assert!(!dialogue.has_visited("Shop"));
+
+    dialogue.set_node("Start").unwrap();
+    while dialogue.is_active() {
+        dialogue.continue_().unwrap();
+    }
+    assert_eq!(0, dialogue.visit_count("Start"));
+    assert_eq!(1, dialogue.visit_count("Shop"));
+    assert!(dialogue.has_visited("Shop"));
+    assert_eq!(0, dialogue.visit_count("Nowhere"));
+}
+
+#[test]
+fn test_events_are_yielded_one_at_a_time() {
+    let compilation =
+        Compiler::from_test_source("Hello\n-> First\n    Chose first\n-> Second\nBye")
+            .compile()
+            .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let mut lines = Vec::new();
+    let mut events = dialogue.events();
+    while let Some(event) = events.next() {
+        match event.unwrap() {
+            DialogueEvent::Line(line) => lines.push(line.text),
+            DialogueEvent::Options(options) => {
+                assert!(matches!(
+                    events.next(),
+                    Some(Err(DialogueError::ContinueOnOptionSelectionError))
+                ));
+                events.select_option(options[0].id).unwrap();
+            }
+            _ => {}
+        }
+    }
+
+    assert_eq!(lines, ["Hello", "Chose first", "Bye"]);
+    assert!(dialogue.events().next().is_none());
+}
+
+#[test]
+fn test_program_is_shared_between_dialogues() {
+    let compilation = Compiler::from_test_source("Hello").compile().unwrap();
+    let program = Arc::new(compilation.program.clone().unwrap());
+    let mut first = TestBase::new().dialogue;
+    let mut second = TestBase::new().dialogue;
+    first.replace_program(program.clone());
+    second.replace_program(program.clone());
+    assert!(Arc::ptr_eq(first.program().unwrap(), &program));
+    assert!(Arc::ptr_eq(second.program().unwrap(), &program));
+
+    first.remove_nodes(["Start"]);
+    assert!(!first.node_exists("Start"));
+    assert!(second.node_exists("Start"));
+    assert!(Arc::ptr_eq(second.program().unwrap(), &program));
+}
+
+#[test]
+fn test_patch_program_keeps_state() {
+    let compile = |source: &str| {
+        Compiler::new()
+            .add_file(File {
+                file_name: "test.yarn".to_string(),
+                source: source.to_string(),
+            })
+            .compile()
+            .unwrap()
+    };
+    let original = compile("title: Start\n---\nOne\nTwo\n===\ntitle: Shop\n---\nWelcome!\n===\ntitle: Old\n---\nBye\n===");
+    let patched = compile("title: Start\n---\nOne\nTwo\n===\ntitle: Shop\n---\nHello there!\n===\ntitle: New\n---\nHi\n===");
+    let mut string_table = original.string_table.clone();
+    string_table.extend(patched.string_table.clone());
+    let mut dialogue = TestBase::new()
+        .with_compilation(original)
+        .with_string_table(string_table)
+        .dialogue;
+    dialogue.set_node("Start").unwrap();
+    dialogue.continue_().unwrap();
+
+    let mut changed_nodes = dialogue.patch_program(patched.program.unwrap());
+    changed_nodes.sort();
+    assert_eq!(changed_nodes, ["New", "Old", "Shop"]);
+    assert_eq!(dialogue.current_node().as_deref(), Some("Start"));
+    assert!(!dialogue.node_exists("Old"));
+
+    let events = dialogue.continue_().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(line) if line.text == "Two")));
+}
+
+#[test]
+fn test_program_chunks_are_loaded_on_demand() {
+    let compilation = Compiler::new()
+        .add_file(File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start\n---\n<<jump Other>>\n===\ntitle: Other\n---\nHello\n==="
+                .to_string(),
+        })
+        .with_program_split(ProgramSplit::PerNode)
+        .compile()
+        .unwrap();
+    let program_chunks = compilation.program_chunks.clone().unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue.replace_program(program_chunks.manifest.base_program());
+    assert!(!dialogue.node_exists("Start"));
+
+    dialogue
+        .replace_nodes(program_chunks.chunks["Start"].clone())
+        .replace_nodes(program_chunks.chunks["Other"].clone())
+        .set_node("Start")
+        .unwrap();
+    dialogue.remove_nodes(["Start"]);
+    assert!(dialogue.node_exists("Start"));
+
+    let events = dialogue.continue_().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(line) if line.text == "Hello")));
+    dialogue.remove_nodes(["Start", "Missing"]);
+    assert!(!dialogue.node_exists("Start"));
+    assert!(dialogue.node_exists("Other"));
+}
+
+#[test]
+fn test_dialogue_driver_runs_on_another_thread() {
+    let compilation = Compiler::from_test_source(
+        "<<declare $name = \"\">>\n{$name}\n-> First\n    Chose first\n-> Second\nBye",
+    )
+    .compile()
+    .unwrap();
+    let dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    let (driver, handle) = DialogueDriver::new(dialogue);
+    let vm_thread = std::thread::spawn(move || driver.run());
+
+    handle.set_variable("$name", "Hello").unwrap();
+    handle.set_node("Start").unwrap();
+    handle.continue_().unwrap();
+
+    let mut lines = Vec::new();
+    let other_handle = handle.clone();
+    while let Some(event) = other_handle.recv() {
+        match event.unwrap() {
+            DialogueEvent::Line(line) => {
+                lines.push(line.text);
+                handle.continue_().unwrap();
+            }
+            DialogueEvent::Options(options) => {
+                handle.continue_().unwrap();
+                assert!(matches!(
+                    other_handle.recv(),
+                    Some(Err(DialogueError::ContinueOnOptionSelectionError))
+                ));
+                handle.select_option(options[0].id).unwrap();
+                handle.continue_().unwrap();
+            }
+            DialogueEvent::DialogueComplete => break,
+            _ => {}
+        }
+    }
+    drop((handle, other_handle));
+
+    let dialogue = vm_thread.join().unwrap();
+    assert_eq!(lines, ["Hello", "Chose first", "Bye"]);
+    assert!(dialogue.current_node().is_none());
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn test_dialogue_stream() {
+    use futures_lite::StreamExt;
+
+    #[derive(Debug, Default)]
+    struct LoadNameInBackground {
+        is_loaded: Arc<AtomicBool>,
+        started: bool,
+    }
+
+    impl Future for LoadNameInBackground {
+        type Output = String;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+            if self.is_loaded.load(Ordering::Acquire) {
+                return Poll::Ready("Alice".to_owned());
+            }
+            if !self.started {
+                self.started = true;
+                let is_loaded = self.is_loaded.clone();
+                let waker = cx.waker().clone();
+                std::thread::spawn(move || {
+                    is_loaded.store(true, Ordering::Release);
+                    waker.wake();
+                });
+            }
+            Poll::Pending
+        }
+    }
+
+    let fetch_name = || YarnFuture::new(LoadNameInBackground::default());
+    let mut library = Library::new();
+    library.add_async_function("fetch_name", fetch_name);
+    let result = Compiler::from_test_source(
+        "Hello {fetch_name()}!\n-> First\n    Chose first\n-> Second\nBye",
+    )
+    .extend_library(library)
+    .compile()
+    .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue
+        .library_mut()
+        .add_async_function("fetch_name", fetch_name);
+    dialogue.set_node("Start").unwrap();
+
+    let mut events = DialogueStream::new(dialogue);
+    let lines = futures_lite::future::block_on(async {
+        let mut lines = Vec::new();
+        while let Some(event) = events.next().await {
+            match event.unwrap() {
+                DialogueEvent::Line(line) => lines.push(line.text),
+                DialogueEvent::Options(options) => {
+                    events.select_option(options[1].id).await.unwrap();
+                }
+                DialogueEvent::FunctionPending(_) => {
+                    panic!("The stream should wait for async functions instead of yielding them")
+                }
+                _ => {}
+            }
+        }
+        lines
+    });
+
+    assert_eq!(lines, ["Hello Alice!", "Bye"]);
+    assert!(events.into_inner().current_node().is_none());
+}
+
+#[test]
+fn test_substitution_providers_are_resolved_on_delivery() {
+    let compilation = Compiler::new()
+        .add_file(File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start\n---\n<<declare $coins = 3>>\nPress {@button_confirm}, {@player_name}! You have {$coins} coins.\n==="
+                .to_string(),
+        })
+        .compile()
+        .unwrap();
+    assert!(compilation
+        .string_table
+        .values()
+        .any(|info| info.text == "Press {@button_confirm}, {@player_name}! You have {0} coins."));
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue
+        .add_substitution_provider("player_name", || "Alex".to_owned())
+        .add_substitution_provider("button_confirm", || "Ⓐ".to_owned());
+    dialogue.set_node("Start").unwrap();
+
+    let events = dialogue.continue_().unwrap();
+    assert!(events.iter().any(|event| matches!(
+        event,
+        DialogueEvent::Line(line) if line.text == "Press Ⓐ, Alex! You have 3 coins."
+    )));
+    assert!(dialogue.remove_substitution_provider("player_name"));
+    assert!(!dialogue.has_substitution_provider("player_name"));
+}
+
+#[test]
+fn test_button_glyphs_follow_the_input_platform() {
+    let compilation = Compiler::new()
+        .add_file(File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start\n---\nPress [button=confirm/] to talk or [button=map/] for the map.\nPress [button=confirm/] again.\n==="
+                .to_string(),
+        })
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    let mut xbox = ButtonGlyphs::new(InputPlatform::Xbox);
+    xbox.add_text("confirm", "Ⓐ")
+        .add_icon("map", "sprites/xbox/view.png");
+    dialogue.set_button_glyphs(xbox).set_node("Start").unwrap();
+
+    let line = dialogue
+        .continue_()
+        .unwrap()
+        .into_iter()
+        .find_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(line.text, "Press Ⓐ to talk or  for the map.");
+    let map = line
+        .attributes
+        .iter()
+        .find(|attribute| {
+            attribute.properties.get(BUTTON_ATTRIBUTE) == Some(&MarkupValue::from("map"))
+        })
+        .unwrap();
+    assert_eq!(map.position, 19);
+    assert_eq!(
+        map.properties[BUTTON_ATTRIBUTE_ICON_PROPERTY],
+        MarkupValue::from("sprites/xbox/view.png")
+    );
+    assert_eq!(
+        map.properties[BUTTON_ATTRIBUTE_PLATFORM_PROPERTY],
+        MarkupValue::from("xbox")
+    );
+
+    let mut keyboard = ButtonGlyphs::new(InputPlatform::Keyboard);
+    keyboard.add_text("confirm", "Enter");
+    dialogue.set_button_glyphs(keyboard);
+    let events = dialogue.continue_().unwrap();
+    assert!(events.iter().any(
+        |event| matches!(event, DialogueEvent::Line(line) if line.text == "Press Enter again.")
+    ));
+}
+
+#[test]
+fn test_evaluating_expressions_against_dialogue_state() {
+    let compilation = Compiler::new()
+        .add_file(File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start\n---\n<<declare $gold = 0>>\n<<set $gold to 12>>\n<<jump Shop>>\n===\ntitle: Shop\n---\n<<if visited(\"Shop\")>>\nBack again?\n<<endif>>\nWelcome!\n==="
+                .to_string(),
+        })
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue.set_node("Start").unwrap();
+    while !dialogue
+        .continue_()
+        .unwrap()
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(_)))
+    {}
+    dialogue.continue_().unwrap();
+
+    let evaluate = |expression: &str| {
+        Compiler::new()
+            .extend_library(dialogue.library().clone())
+            .evaluate_expression_with_variables(
+                expression,
+                &dialogue.variable_storage().variables(),
+            )
+            .unwrap()
+    };
+    assert_eq!(
+        evaluate("$gold >= 10 and visited(\"Shop\")"),
+        YarnValue::from(true)
+    );
+    assert_eq!(evaluate("$gold * 2"), YarnValue::from(24.0));
+}
+
+#[test]
+fn test_variable_changed_events() {
+    let compilation = Compiler::new()
+        .add_file(File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start\n---\n<<declare $gold = 5>>\n<<set $gold to $gold + 10>>\n<<set $gold to 0>>\nDone\n==="
+                .to_string(),
+        })
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue
+        .set_variable_change_events_enabled(true)
+        .set_node("Start")
+        .unwrap();
+
+    let changes: Vec<_> = dialogue
+        .continue_()
+        .unwrap()
+        .into_iter()
+        .filter_map(|event| match event {
+            DialogueEvent::VariableChanged { name, old, new } => Some((name, old, new)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (
+                "$gold".to_owned(),
+                Some(YarnValue::from(5.0)),
+                YarnValue::from(15.0)
+            ),
+            (
+                "$gold".to_owned(),
+                Some(YarnValue::from(15.0)),
+                YarnValue::from(0.0)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_instruction_limit_stops_endless_jumps() {
+    let compilation = Compiler::new()
+        .add_file(File {
+            file_name: "test.yarn".to_string(),
+            source:
+                "title: Start\n---\nHello\n<<jump Loop>>\n===\ntitle: Loop\n---\n<<jump Loop>>\n==="
+                    .to_string(),
+        })
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue
+        .set_instruction_limit(1000)
+        .set_node("Start")
+        .unwrap();
+
+    let events = dialogue.continue_().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Line(line) if line.text == "Hello")));
+    let Err(DialogueError::InstructionLimitExceeded { limit, node_name }) = dialogue.continue_()
+    else {
+        panic!("Expected the endless jumps to be stopped");
+    };
+    assert_eq!(1000, limit);
+    assert_eq!("Loop", node_name);
+}
+
+#[test]
+fn test_metrics_count_instructions_and_function_calls() {
+    let compilation = Compiler::new()
+        .add_file(File {
+            file_name: "test.yarn".to_string(),
+            source: "title: Start\n---\n<<declare $gold = 1>>\n<<set $gold to $gold + 1>>\nYou have {$gold} gold.\n<<jump Shop>>\n===\ntitle: Shop\n---\n<<if visited(\"Start\")>>\n    Welcome back.\n<<endif>>\n===".to_string(),
+        })
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue.set_node("Start").unwrap();
+    dialogue.continue_().unwrap();
+    assert_eq!(dialogue.metrics(), &DialogueMetrics::default());
+
+    dialogue.set_metrics_enabled(true);
+    while dialogue.is_active() {
+        dialogue.continue_().unwrap();
+    }
+
+    let metrics = dialogue.metrics().clone();
+    assert!(metrics.instructions_executed > 0);
+    assert_eq!(metrics.function_calls.get("visited"), Some(&1));
+    assert_eq!(metrics.function_calls.get("Number.Add"), None);
+    let nodes: HashSet<_> = metrics
+        .nodes_by_time()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(nodes, HashSet::from(["Start", "Shop"]));
+    assert_eq!(
+        metrics.total_time(),
+        metrics.time_per_node.values().sum::<std::time::Duration>()
+    );
+
+    dialogue.reset_metrics();
+    assert_eq!(dialogue.metrics(), &DialogueMetrics::default());
+}
+
+fn compile_with_broken_line() -> (Compilation, usize) {
+    let mut result = Compiler::from_test_source("Hello").compile().unwrap();
+    let node = result
+        .program
+        .as_mut()
+        .unwrap()
+        .nodes
+        .get_mut("Start")
+        .unwrap();
+    let index = node
+        .instructions
+        .iter()
+        .position(|instruction| instruction.opcode() == OpCode::RunLine)
+        .unwrap();
+    // Stand-in for a program produced by an incompatible compiler
+    node.instructions[index].operands.clear();
+    (result, index)
+}
+
+#[test]
+fn test_invalid_instructions_return_located_errors() {
+    let (result, index) = compile_with_broken_line();
+    let debug_info = result.debug_info["Start"].clone();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue
+        .add_source_positions(
+            &debug_info.node_name,
+            &debug_info.file_name,
+            debug_info.line_positions.clone(),
+        )
+        .set_node("Start")
+        .unwrap();
+
+    let Err(DialogueError::InvalidInstruction {
+        node_name,
+        instruction_index,
+        location,
+        ..
+    }) = dialogue.continue_()
+    else {
+        panic!("Expected the missing operand to be reported");
+    };
+    assert_eq!("Start", node_name);
+    assert_eq!(index, instruction_index);
+    assert_eq!(
+        Some(SourceLocation {
+            file_name: debug_info.file_name,
+            position: debug_info.line_positions[&index],
+        }),
+        location
+    );
+}
+
+#[test]
+fn test_operands_of_the_wrong_type_return_errors() {
+    let (mut result, index) = compile_with_broken_line();
+    result
+        .program
+        .as_mut()
+        .unwrap()
+        .nodes
+        .get_mut("Start")
+        .unwrap()
+        .instructions[index]
+        .operands
+        .push(Operand::from(1.0));
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    // Line hints read the same operand, but leave reporting it to the instruction itself
+    dialogue
+        .set_line_hints_enabled(true)
+        .set_node("Start")
+        .unwrap();
+
+    let Err(DialogueError::InvalidInstruction {
+        node_name,
+        instruction_index,
+        reason,
+        ..
+    }) = dialogue.continue_()
+    else {
+        panic!("Expected the wrong operand type to be reported");
+    };
+    assert_eq!("Start", node_name);
+    assert_eq!(index, instruction_index);
+    assert!(reason.contains("wrong type"), "{reason}");
+}
+
+#[test]
+fn test_jumps_to_missing_nodes_return_errors() {
+    let mut result = Compiler::from_test_source("<<jump Other>>\n===\ntitle: Other\n---\nHello")
+        .compile()
+        .unwrap();
+    // Stand-in for a program that was patched without the node its jump points to
+    result.program.as_mut().unwrap().nodes.remove("Other");
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let result = dialogue.continue_();
+    assert!(
+        matches!(result, Err(DialogueError::InvalidNode { ref node_name }) if node_name == "Other"),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn test_lines_and_commands_carry_their_source_location() {
+    let result = Compiler::from_test_source("Hello\n<<wave {1 + 1}>>")
+        .compile()
+        .unwrap();
+    let debug_info = result.debug_info["Start"].clone();
+    let instructions = &result.program.as_ref().unwrap().nodes["Start"].instructions;
+    let expected_location = |opcode| {
+        let index = instructions
+            .iter()
+            .position(|instruction| instruction.opcode() == opcode)
+            .unwrap();
+        Some(SourceLocation {
+            file_name: debug_info.file_name.clone(),
+            position: debug_info.line_positions[&index],
+        })
+    };
+    let expected_line_location = expected_location(OpCode::RunLine);
+    let expected_command_location = expected_location(OpCode::RunCommand);
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue
+        .add_source_positions(
+            &debug_info.node_name,
+            &debug_info.file_name,
+            debug_info.line_positions.clone(),
+        )
+        .set_node("Start")
+        .unwrap();
+
+    let mut lines = Vec::new();
+    let mut commands = Vec::new();
+    while dialogue.is_active() {
+        for event in dialogue.continue_().unwrap() {
+            match event {
+                DialogueEvent::Line(line) => lines.push(line),
+                DialogueEvent::Command(command) => commands.push(command),
+                _ => {}
+            }
+        }
+    }
+    assert_eq!("Start", lines[0].node_name);
+    assert_eq!(expected_line_location, lines[0].source_location);
+    assert_eq!("wave 2", commands[0].raw);
+    assert_eq!(Some("Start"), commands[0].node_name.as_deref());
+    assert_eq!(expected_command_location, commands[0].source_location);
+}
+
+#[test]
+#[should_panic = "Invalid instruction"]
+fn test_invalid_instructions_panic_in_compatibility_mode() {
+    let (result, _) = compile_with_broken_line();
+    let mut dialogue = TestBase::new().with_compilation(result).dialogue;
+    dialogue.set_panic_on_error(true).set_node("Start").unwrap();
+    let _ = dialogue.continue_();
+}
+
+#[test]
+fn test_text_provider_can_defer_lines() {
+    #[derive(Debug)]
+    struct RemoteTextProvider {
+        text: StringTableTextProvider,
+        is_loaded: Arc<AtomicBool>,
+    }
+
+    impl TextProvider for RemoteTextProvider {
+        fn accept_line_hints(&mut self, line_ids: &[LineId]) {
+            self.text.accept_line_hints(line_ids);
+        }
+
+        fn get_text(&self, id: &LineId) -> Option<String> {
+            self.text.get_text(id)
+        }
+
+        fn poll_text(
+            &mut self,
+            id: &LineId,
+            _context: &mut std::task::Context<'_>,
+        ) -> Poll<Option<String>> {
+            if self.is_loaded.load(Ordering::Relaxed) {
+                Poll::Ready(self.get_text(id))
+            } else {
+                Poll::Pending
+            }
+        }
+
+        fn set_language(&mut self, language: Option<Language>) {
+            self.text.set_language(language);
+        }
+
+        fn get_language(&self) -> Option<Language> {
+            self.text.get_language()
+        }
+
+        fn are_lines_available(&self) -> bool {
+            self.text.are_lines_available()
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
+    }
+
+    let result = Compiler::from_test_source("Hello from the server!")
+        .compile()
+        .unwrap();
+    let line_id = result.string_table.keys().next().unwrap().clone();
+    let mut text = StringTableTextProvider::new();
+    text.extend_base_language(
+        result
+            .string_table
+            .iter()
+            .map(|(id, info)| (id.clone(), info.text.clone()))
+            .collect(),
+    );
+    let is_loaded = Arc::new(AtomicBool::new(false));
+    let mut test_base = TestBase::new().with_compilation(result);
+    test_base.string_table.replace(RemoteTextProvider {
+        text,
+        is_loaded: is_loaded.clone(),
+    });
+    let mut dialogue = test_base.dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let events = dialogue.continue_().unwrap();
+    assert!(events.contains(&DialogueEvent::TextPending(line_id.clone())));
+    let events = dialogue.continue_().unwrap();
+    assert_eq!(events, vec![DialogueEvent::TextPending(line_id)]);
+
+    is_loaded.store(true, Ordering::Relaxed);
+    let events = dialogue.continue_().unwrap();
+    assert!(events.iter().any(
+        |event| matches!(event, DialogueEvent::Line(line) if line.text == "Hello from the server!")
+    ));
+}
+
+#[test]
+fn test_custom_analyser() {
+    #[derive(Debug, Default)]
+    struct CommandCounter {
+        commands: usize,
+    }
+
+    impl CompiledProgramAnalyser for CommandCounter {
+        fn diagnose(&mut self, program: &Program) {
+            self.commands += program
+                .nodes
+                .values()
+                .flat_map(|node| &node.instructions)
+                .filter(|instruction| instruction.opcode() == OpCode::RunCommand)
+                .count();
+        }
+
+        fn collect_diagnoses(&self) -> Vec<Diagnosis> {
+            vec![Diagnosis::new(
+                DiagnosisSeverity::Note,
+                format!("Script runs {} commands", self.commands),
+            )]
+        }
+    }
+
+    let result = Compiler::from_test_source("<<fade_in>>\nHello!\n<<fade_out>>")
+        .compile()
+        .unwrap();
+    let mut context = Context::empty().add_analyser(CommandCounter::default());
+    TestBase::new()
+        .with_compilation(result)
+        .dialogue
+        .analyse(&mut context);
+
+    let diagnoses = context.finish_analysis();
+    assert_eq!(1, diagnoses.len());
+    assert_eq!("Script runs 2 commands", diagnoses[0].message);
+}
+
+#[test]
+fn test_always_false_option_conditions_are_reported() {
+    let result = Compiler::from_test_source(
+        "<<declare $debug = false>>\n\
+        -> Hello\n\
+        -> Cheat <<if $debug>>\n\
+        -> Impossible <<if 1 > 2 and true>>\n\
+        -> Again <<if visited(\"Start\")>>",
+    )
+    .compile()
+    .unwrap();
+    let mut context = Context::empty().add_analyser(AlwaysFalseConditionChecker::new());
+    TestBase::new()
+        .with_compilation(result)
+        .dialogue
+        .analyse(&mut context);
+
+    let locations: Vec<_> = context
+        .finish_analysis()
+        .into_iter()
+        .map(|diagnosis| {
+            assert_eq!(Some("Start".to_owned()), diagnosis.node_name);
+            let Some(DiagnosisData::Instruction(location)) = diagnosis.data else {
+                panic!("Expected the option's instruction to be attached");
+            };
+            location.instruction_index
+        })
+        .collect();
+    assert_eq!(2, locations.len());
+    assert!(locations[0] < locations[1]);
+}
+
+#[test]
+fn test_replaying_a_recorded_playthrough() {
+    let dialogue_for = |source: &str| {
+        let library = TestBase::new().dialogue.library().clone();
+        let compilation = Compiler::from_test_source(source)
+            .extend_library(library)
+            .compile()
+            .unwrap();
+        TestBase::new().with_compilation(compilation).dialogue
+    };
+    let play = |recorder: &mut DialogueRecorder, dialogue: &mut Dialogue| {
+        recorder.set_node(dialogue, "Start").unwrap();
+        loop {
+            let events = recorder.continue_(dialogue).unwrap();
+            if events.contains(&DialogueEvent::DialogueComplete) {
+                break;
+            }
+            if events
+                .iter()
+                .any(|event| matches!(event, DialogueEvent::Options(_)))
+            {
+                recorder.set_selected_option(dialogue, OptionId(1)).unwrap();
+            }
+        }
+    };
+    let source = "You rolled {dice(20)}. #line:roll\n\
+        -> Stay #line:stay\n\
+        -> Leave #line:leave\n    \
+        You rolled {dice(6)} on the way out. #line:leave_roll";
+
+    let mut dialogue = dialogue_for(source);
+    let mut recorder = DialogueRecorder::new(&mut dialogue);
+    play(&mut recorder, &mut dialogue);
+    let recording = recorder.recording();
+    assert_eq!(2, recording.random_draws.len());
+
+    let replay = replay_recording(&mut dialogue_for(source), &recording).unwrap();
+    assert!(replay.matches(&recording));
+
+    let updated_source = source.replace("-> Leave #line:leave", "-> Run #line:run");
+    let result = replay_recording(&mut dialogue_for(&updated_source), &recording);
+    assert!(matches!(
+        result,
+        Err(ReplayError::OptionNotFound { input_index, .. }) if input_index == 3
+    ));
+}
+
+#[test]
+fn test_forked_dialogue_does_not_affect_the_original() {
+    let compilation = Compiler::from_test_source(
+        "<<declare $gold = 10>>\nHow much do you pay?\n-> Nothing\n-> Everything\n    <<set $gold to 0>>\n{$gold} gold left.",
+    )
+    .compile()
+    .unwrap();
+    let test_base = TestBase::new().with_compilation(compilation);
+    let text_provider = test_base.string_table.clone();
+    let mut dialogue = test_base.dialogue;
+    let run_until_options = |dialogue: &mut Dialogue| {
+        let mut lines = Vec::new();
+        while dialogue.is_active() {
+            for event in dialogue.continue_().unwrap() {
+                match event {
+                    DialogueEvent::Line(line) => lines.push(line.text),
+                    DialogueEvent::Options(_) => return lines,
+                    _ => {}
+                }
+            }
+        }
+        lines
+    };
+    dialogue.set_node("Start").unwrap();
+    assert_eq!(run_until_options(&mut dialogue), ["How much do you pay?"]);
+
+    let mut fork = dialogue.fork(Box::new(text_provider)).unwrap();
+    fork.set_selected_option(OptionId(1)).unwrap();
+    assert_eq!(run_until_options(&mut fork), ["0 gold left."]);
+    assert_eq!(
+        fork.variable_storage().get("$gold").unwrap(),
+        YarnValue::from(0)
+    );
+
+    dialogue.set_selected_option(OptionId(0)).unwrap();
+    assert_eq!(run_until_options(&mut dialogue), ["10 gold left."]);
+    assert!(!dialogue.variable_storage().contains("$gold"));
+}
+
+#[test]
+fn test_options_timeout_is_delivered_before_options() {
+    let compilation = Compiler::from_test_source(
+        "Guard: Who goes there?\n<<options_timeout 2.5 2>>\n-> A friend.\n-> ...\n<<options_timeout soon>>",
+    )
+    .compile()
+    .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let mut events = Vec::new();
+    while !events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Options(_)))
+    {
+        events.extend(dialogue.continue_().unwrap());
+    }
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Command(_))));
+    let [.., DialogueEvent::OptionsTimeout(timeout), DialogueEvent::Options(_)] = events.as_slice()
+    else {
+        panic!("Expected the timeout right before the options, but got {events:?}");
+    };
+    assert_eq!(
+        timeout,
+        &OptionsTimeout {
+            duration: std::time::Duration::from_secs_f32(2.5),
+            default_option: Some(OptionId(1)),
+        }
+    );
+
+    dialogue.set_selected_option(OptionId(1)).unwrap();
+    let result = dialogue.continue_();
+    assert!(
+        matches!(result, Err(DialogueError::InvalidInstruction { ref reason, .. }) if reason.contains("<<options_timeout soon>>")),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn test_options_timeout_rejects_durations_too_large_to_represent() {
+    let compilation = Compiler::from_test_source("<<options_timeout 1e30>>\n-> A friend.\n-> ...")
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let result = dialogue.continue_();
+    assert!(
+        matches!(result, Err(DialogueError::InvalidInstruction { ref reason, .. }) if reason.contains("<<options_timeout 1e30>>")),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn test_functions_read_the_dialogue_context() {
+    struct Inventory {
+        gold: usize,
+    }
+    fn can_afford(inventory: DialogueContext<Inventory>, price: usize) -> bool {
+        inventory.gold >= price
+    }
+
+    let mut test_base = TestBase::new();
+    test_base
+        .dialogue
+        .library_mut()
+        .add_function("can_afford", can_afford);
+    let compilation = Compiler::from_test_source(
+        "<<if can_afford(100)>>\nDeal.\n<<else>>\nToo expensive.\n<<endif>>",
+    )
+    .extend_library(test_base.dialogue.library().clone())
+    .compile()
+    .unwrap();
+    let mut dialogue = test_base.with_compilation(compilation).dialogue;
+    let first_line = |dialogue: &mut Dialogue| {
+        dialogue.set_node("Start").unwrap();
+        dialogue
+            .continue_()
+            .unwrap()
+            .into_iter()
+            .find_map(|event| match event {
+                DialogueEvent::Line(line) => Some(line.text),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    dialogue.set_context(Inventory { gold: 120 });
+    assert_eq!(first_line(&mut dialogue), "Deal.");
+
+    dialogue.context_mut::<Inventory>().unwrap().gold = 50;
+    assert_eq!(first_line(&mut dialogue), "Too expensive.");
+    assert_eq!(dialogue.context::<Inventory>().unwrap().gold, 50);
+    assert!(dialogue.context::<String>().is_none());
+}
+
+#[test]
+fn test_custom_markup_processors_replace_markers() {
+    #[derive(Debug, Clone)]
+    struct IconProcessor;
+
+    impl AttributeMarkerProcessor for IconProcessor {
+        fn replacement_text_for_marker(&self, marker: &MarkupAttributeMarker) -> String {
+            match marker.properties.get("icon") {
+                Some(MarkupValue::String(icon)) => format!("<{icon}>"),
+                _ => String::new(),
+            }
+        }
+
+        fn clone_box(&self) -> Box<dyn AttributeMarkerProcessor> {
+            Box::new(self.clone())
+        }
+    }
+
+    let compilation = Compiler::from_test_source("Take the [icon=sword/] and go.")
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue
+        .add_markup_processor("icon", IconProcessor)
+        .set_node("Start")
+        .unwrap();
+
+    let line = dialogue
+        .continue_()
+        .unwrap()
+        .into_iter()
+        .find_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(line.text, "Take the <sword> and go.");
+    assert_eq!(line.attributes[0].name, "icon");
+}
+
+#[test]
+fn test_forked_dialogue_keeps_custom_markup_processors() {
+    #[derive(Debug, Clone)]
+    struct IconProcessor;
+
+    impl AttributeMarkerProcessor for IconProcessor {
+        fn replacement_text_for_marker(&self, marker: &MarkupAttributeMarker) -> String {
+            match marker.properties.get("icon") {
+                Some(MarkupValue::String(icon)) => format!("<{icon}>"),
+                _ => String::new(),
+            }
+        }
+
+        fn clone_box(&self) -> Box<dyn AttributeMarkerProcessor> {
+            Box::new(self.clone())
+        }
+    }
+
+    let compilation = Compiler::from_test_source("Take the [icon=sword/] and go.")
+        .compile()
+        .unwrap();
+    let test_base = TestBase::new().with_compilation(compilation);
+    let text_provider = test_base.string_table.clone();
+    let mut dialogue = test_base.dialogue;
+    dialogue
+        .add_markup_processor("icon", IconProcessor)
+        .set_node("Start")
+        .unwrap();
+
+    let mut fork = dialogue.fork(Box::new(text_provider)).unwrap();
+    let line = fork
+        .continue_()
+        .unwrap()
+        .into_iter()
+        .find_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(line.text, "Take the <sword> and go.");
+}
+
+#[test]
+fn test_lines_keep_their_raw_text() {
+    let compilation = Compiler::from_test_source(r"Take the \[Sword\] [b]{1 + 1}[/b] times.")
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue.set_node("Start").unwrap();
+
+    let line = dialogue
+        .continue_()
+        .unwrap()
+        .into_iter()
+        .find_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(line.text, "Take the [Sword] 2 times.");
+    assert_eq!(line.raw_text, r"Take the \[Sword\] [b]2[/b] times.");
+}
+
+#[test]
+fn test_markup_styles_expand_to_their_attributes() {
+    let compilation =
+        Compiler::from_test_source("Guard: [style=shouty]Halt![/style] [style=quiet]Who?[/style]")
+            .compile()
+            .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    let mut styles = MarkupStyles::new();
+    styles.add_style(
+        "shouty",
+        [
+            StyledAttribute::new("color").with_property("color", "red"),
+            StyledAttribute::new("tremble"),
+        ],
+    );
+    dialogue
+        .set_markup_styles(styles)
+        .set_node("Start")
+        .unwrap();
+
+    let line = dialogue
+        .continue_()
+        .unwrap()
+        .into_iter()
+        .find_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line),
+            _ => None,
+        })
+        .unwrap();
+    let color = line.attribute("color").unwrap();
+    assert_eq!(line.text_for_attribute(color), "Halt!");
+    assert_eq!(color.property("color"), Some(&MarkupValue::from("red")));
+    assert_eq!(
+        line.text_for_attribute(line.attribute("tremble").unwrap()),
+        "Halt!"
+    );
+    let style_names: Vec<_> = line
+        .attributes
+        .iter()
+        .filter(|attribute| attribute.name == STYLE_ATTRIBUTE)
+        .map(|attribute| attribute.property(STYLE_ATTRIBUTE).unwrap().to_string())
+        .collect();
+    assert_eq!(style_names, ["shouty", "quiet"]);
+}
+
+#[test]
+fn test_lenient_markup_delivers_broken_lines_as_plain_text() {
+    let compilation = Compiler::from_test_source("Take the [b]sword[/i]!")
+        .compile()
+        .unwrap();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue.set_node("Start").unwrap();
+    assert!(matches!(
+        dialogue.continue_(),
+        Err(DialogueError::MarkupParseError(
+            MarkupParseError::UnmatchedCloseMarker { column: 17, .. }
+        ))
+    ));
+
+    dialogue.set_lenient_markup(true).set_node("Start").unwrap();
+    let line = dialogue
+        .continue_()
+        .unwrap()
+        .into_iter()
+        .find_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(line.text, "Take the [b]sword[/i]!");
+    assert!(line.attributes.is_empty());
+    assert_eq!(line.markup_error.unwrap().column(), 17);
+}
+
+#[test]
+fn test_lines_carry_their_metadata() {
+    let compilation =
+        Compiler::from_test_source("Guard: Stop! #shout #line:guard_1\n-> Run #scared")
+            .compile()
+            .unwrap();
+    let line_metadata: Vec<_> = compilation
+        .string_table
+        .iter()
+        .map(|(id, info)| (id.clone(), info.metadata.clone()))
+        .collect();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue
+        .add_line_metadata(line_metadata)
+        .set_node("Start")
+        .unwrap();
+
+    let events = dialogue.continue_().unwrap();
+    let line = events
+        .iter()
+        .find_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line),
+            _ => None,
+        })
+        .unwrap();
+    assert!(line.has_metadata("shout"));
+    assert!(!line.has_metadata("scared"));
+    assert!(!line.metadata.iter().any(|tag| tag.starts_with("line:")));
+
+    let events = dialogue.continue_().unwrap();
+    let options = events
+        .iter()
+        .find_map(|event| match event {
+            DialogueEvent::Options(options) => Some(options),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(vec!["scared".to_owned()], options[0].line.metadata);
+}
+
+#[test]
+fn test_variable_declarations_are_available_at_runtime() {
+    let compilation = Compiler::from_test_source(
+        "/// How much gold the player carries\n<<declare $gold = 10>>\n<<set $met_guard to true>>\n{$gold}",
+    )
+    .compile()
+    .unwrap();
+    let declarations: Vec<_> = compilation.declarations.iter().map(Into::into).collect();
+    let mut dialogue = TestBase::new().with_compilation(compilation).dialogue;
+    dialogue.add_variable_declarations(declarations);
+
+    let gold = dialogue.variable_declaration("$gold").unwrap();
+    assert_eq!(gold.r#type, Type::Number);
+    assert_eq!(gold.default_value, Some(YarnValue::from(10)));
+    assert_eq!(
+        gold.description.as_deref(),
+        Some("How much gold the player carries")
+    );
+    assert!(!gold.is_implicit);
+    assert!(!gold.is_internal());
+    assert!(
+        dialogue
+            .variable_declaration("$met_guard")
+            .unwrap()
+            .is_implicit
+    );
+    assert!(dialogue.variable_declaration("string").is_none());
+    assert!(dialogue
+        .variable_declarations()
+        .all(|declaration| declaration.name.starts_with('$')));
 }
@@ -101,3 +101,29 @@ fn test_compiling_same_file_twice_fails() {
         .iter()
         .any(|d| d.message.contains("Duplicate line ID line:794945")));
 }
+
+#[test]
+fn test_strict_variable_declarations_reject_implicit_declarations() {
+    let source = "<<declare $declared = 1>>\n\
+        <<set $declared to $declared + 1>>\n\
+        <<set $undeclared to 1>>\n\
+        <<if $undeclared > 0>>\n\
+        Hello\n\
+        <<endif>>";
+
+    Compiler::from_test_source(source).compile().unwrap();
+
+    let result = Compiler::from_test_source(source)
+        .with_strict_variable_declarations(true)
+        .compile()
+        .unwrap_err();
+
+    println!("{}", result);
+    let errors: Vec<_> = result
+        .0
+        .iter()
+        .filter(|d| d.message.contains("is used without being declared"))
+        .collect();
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().all(|d| d.message.contains("$undeclared")));
+}
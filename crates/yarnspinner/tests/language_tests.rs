@@ -220,3 +220,132 @@ fn crashes_on_command_expression_evaluating_whitespace() {
         .with_compilation(result)
         .run_standard_testcase();
 }
+
+#[test]
+fn test_node_language_header() {
+    let source = r#"title: Start
+---
+[plural value=0 one="a car" other="cars" /]
+<<jump French>>
+===
+title: French
+language: fr
+---
+[plural value=0 one="une voiture" other="voitures" /]
+==="#;
+    let result = Compiler::new()
+        .add_file(File {
+            file_name: "input.yarn".to_owned(),
+            source: source.to_owned(),
+        })
+        .compile()
+        .unwrap();
+    let languages: Vec<_> = result
+        .string_table
+        .values()
+        .map(|info| (info.node_name.as_str(), info.language.as_deref()))
+        .collect();
+    assert!(languages.contains(&("Start", None)));
+    assert!(languages.contains(&("French", Some("fr"))));
+
+    let mut dialogue = TestBase::default().with_compilation(result).dialogue;
+    dialogue.set_node("Start").unwrap();
+    let lines: Vec<_> = dialogue
+        .flatten()
+        .filter_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line.text),
+            _ => None,
+        })
+        .collect();
+    // English uses "other" for zero, while French uses "one"
+    assert_eq!(lines, vec!["cars", "une voiture"]);
+}
+
+#[test]
+fn test_base_language_is_used_for_markers() {
+    let result = Compiler::new()
+        .add_file(File {
+            file_name: "input.yarn".to_owned(),
+            source:
+                "title: Start\n---\n[plural value=0 one=\"une voiture\" other=\"voitures\" /]\n==="
+                    .to_owned(),
+        })
+        .with_base_language("fr")
+        .compile()
+        .unwrap();
+    assert_eq!(result.base_language.as_deref(), Some("fr"));
+
+    let base_language = result
+        .base_language
+        .as_deref()
+        .map(|language| language.parse::<Language>().unwrap());
+    let mut dialogue = TestBase::default().with_compilation(result).dialogue;
+    dialogue.set_language_code(None);
+    dialogue.set_base_language(base_language);
+    dialogue.set_node("Start").unwrap();
+    let lines: Vec<_> = dialogue
+        .flatten()
+        .filter_map(|event| match event {
+            DialogueEvent::Line(line) => Some(line.text),
+            _ => None,
+        })
+        .collect();
+    // French uses "one" for zero
+    assert_eq!(lines, vec!["une voiture"]);
+}
+
+#[test]
+fn test_switching_language_redelivers_current_content() {
+    let result = Compiler::new()
+        .add_file(File {
+            file_name: "input.yarn".to_owned(),
+            source: "title: Start\n---\n<<declare $name = \"Ada\">>\nHello, {$name}! #line:hello\n-> Bye #line:bye\n-> Stay, {$name} #line:stay\n==="
+                .to_owned(),
+        })
+        .compile()
+        .unwrap();
+    let mut text_provider = StringTableTextProvider::new();
+    text_provider.extend_base_language(
+        result
+            .string_table
+            .iter()
+            .map(|(id, info)| (id.clone(), info.text.clone()))
+            .collect(),
+    );
+    text_provider.extend_translation(
+        "de-CH",
+        HashMap::from([
+            (LineId("line:hello".to_owned()), "Hallo, {0}!".to_owned()),
+            (LineId("line:bye".to_owned()), "Tschüss".to_owned()),
+            (LineId("line:stay".to_owned()), "Bleib, {0}".to_owned()),
+        ]),
+    );
+    let mut dialogue = Dialogue::new(
+        Box::new(MemoryVariableStorage::new()),
+        Box::new(text_provider),
+    );
+    dialogue.add_program(result.program.unwrap());
+    dialogue.set_node("Start").unwrap();
+
+    assert!(dialogue.switch_language(None).unwrap().is_empty());
+    dialogue.continue_().unwrap();
+    let events = dialogue.switch_language(Language::new("de-CH")).unwrap();
+    let [DialogueEvent::Line(line)] = events.as_slice() else {
+        panic!("Expected the current line to be delivered again, got {events:?}");
+    };
+    assert_eq!("Hallo, Ada!", line.text);
+
+    let events = dialogue.continue_().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, DialogueEvent::Options(_))));
+    let events = dialogue.switch_language(None).unwrap();
+    let [DialogueEvent::Options(options)] = events.as_slice() else {
+        panic!("Expected the current options to be delivered again, got {events:?}");
+    };
+    let texts: Vec<_> = options
+        .iter()
+        .map(|option| option.line.text.as_str())
+        .collect();
+    assert_eq!(vec!["Bye", "Stay, Ada"], texts);
+}
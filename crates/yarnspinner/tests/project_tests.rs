@@ -7,6 +7,8 @@ use regex::Regex;
 use std::collections::HashSet;
 use test_base::prelude::*;
 use yarnspinner::compiler::*;
+use yarnspinner::runtime::{Language, StringTable};
+use yarnspinner::validation::*;
 use yarnspinner_core::prelude::*;
 
 mod test_base;
@@ -292,3 +294,65 @@ fn test_debug_output_is_produced() {
     assert_eq!(2, first_line_info.position.unwrap().line);
     assert_eq!(0, first_line_info.position.unwrap().character);
 }
+
+#[test]
+fn test_validating_a_project_collects_all_problems() {
+    let directory = tempfile::tempdir().unwrap();
+    let start = directory.path().join("start.yarn");
+    std::fs::write(
+        &start,
+        "title: Start
+---
+Hello! #line:hello
+<<jump Shop>>
+===
+",
+    )
+    .unwrap();
+    let shop = directory.path().join("shop.yarn");
+    std::fs::write(
+        &shop,
+        "title: Shop
+---
+Welcome to the shop. #line:welcome
+<<jump Storage>>
+===
+",
+    )
+    .unwrap();
+    let missing = directory.path().join("missing.yarn");
+
+    let mut options = ValidationOptions::new();
+    options.audit_translation(
+        "de-CH",
+        StringTable::from([
+            (LineId("line:hello".to_owned()), "Hallo!".to_owned()),
+            (LineId("line:goodbye".to_owned()), "Tschüss!".to_owned()),
+        ]),
+    );
+    let report = validate_project([&start, &shop, &missing], options);
+
+    assert!(!report.is_valid());
+    assert_eq!(0, report.error_count());
+    assert_eq!(1, report.unreadable_files.len());
+    assert_eq!(missing, report.unreadable_files[0].path);
+    assert_eq!(
+        vec![BrokenJump {
+            node_name: "Shop".to_owned(),
+            file_name: Some(shop.to_string_lossy().into_owned()),
+            destination: "Storage".to_owned(),
+        }],
+        report.broken_jumps
+    );
+    let translation = &report.translations[0];
+    assert_eq!(Language::new("de-CH"), translation.language);
+    assert_eq!(
+        vec![LineId("line:welcome".to_owned())],
+        translation.missing_lines
+    );
+    assert_eq!(
+        vec![LineId("line:goodbye".to_owned())],
+        translation.orphaned_lines
+    );
+    assert_eq!(2, report.stats.unwrap().nodes.len());
+}
@@ -235,6 +235,12 @@ impl TestBase {
                     DialogueEvent::NodeComplete(_) => {}
                     DialogueEvent::NodeStart(_) => {}
                     DialogueEvent::LineHints(_) => {}
+                    DialogueEvent::BreakpointHit(_) => {}
+                    DialogueEvent::FunctionPending(_) => {}
+                    DialogueEvent::TextPending(_) => {}
+                    DialogueEvent::TriggerFired(_) => {}
+                    DialogueEvent::VariableChanged { .. } => {}
+                    DialogueEvent::OptionsTimeout(_) => {}
                     DialogueEvent::DialogueComplete => {
                         let Some(test_plan) = self.test_plan.as_mut() else {
                             continue;
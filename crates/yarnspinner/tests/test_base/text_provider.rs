@@ -1,6 +1,7 @@
 use std::{
     any::Any,
     sync::{Arc, RwLock},
+    task::{Context, Poll},
 };
 use yarnspinner_core::prelude::*;
 use yarnspinner_runtime::prelude::*;
@@ -30,6 +31,10 @@ impl TextProvider for SharedTextProvider {
         self.0.read().unwrap().get_text(id)
     }
 
+    fn poll_text(&mut self, id: &LineId, context: &mut Context<'_>) -> Poll<Option<String>> {
+        self.0.write().unwrap().poll_text(id, context)
+    }
+
     fn set_language(&mut self, language: Option<Language>) {
         self.0.write().unwrap().set_language(language);
     }
@@ -297,6 +297,149 @@ fn test_function_signatures() {
             .any(|d| d.name == "$bool" && d.r#type == Type::Boolean));
     }
 }
+
+#[test]
+fn test_variadic_function_signatures() {
+    let mut library = Library::new();
+    library
+        .add_function("func_values_int", |values: &[YarnValue]| values.len())
+        .add_function("func_int_values_int", |i: i32, values: Vec<YarnValue>| {
+            i + values.len() as i32
+        });
+
+    for source in [
+        "<<set $int = func_values_int()>>",
+        "<<set $int = func_values_int(1, true, \"three\")>>",
+        "<<set $int = func_int_values_int(1)>>",
+        "<<set $int = func_int_values_int(1, 2, 3)>>",
+    ] {
+        let result = Compiler::from_test_source(source)
+            .extend_library(library.clone())
+            .compile()
+            .unwrap();
+
+        assert!(result
+            .declarations
+            .iter()
+            .any(|d| d.name == "$int" && d.r#type == Type::Number));
+    }
+
+    for (source, expected_exception_message) in [
+        (
+            "<<set $int = func_int_values_int()>>",
+            "expects at least 1 parameter, but received 0",
+        ),
+        (
+            "<<set $int = func_int_values_int(true, 2)>>",
+            "expects a Number, not a Bool",
+        ),
+    ] {
+        let result = Compiler::from_test_source(source)
+            .extend_library(library.clone())
+            .compile()
+            .unwrap_err();
+
+        assert!(result
+            .0
+            .iter()
+            .any(|d| d.message.contains(expected_exception_message)));
+    }
+}
+
+#[test]
+fn test_optional_parameter_function_signatures() {
+    let mut library = Library::new();
+    library.add_function("roll", |dice: &str, times: Option<usize>| {
+        dice.len() * times.unwrap_or(1)
+    });
+
+    for source in [
+        "<<set $int = roll(\"2d6\")>>",
+        "<<set $int = roll(\"2d6\", 3)>>",
+    ] {
+        let result = Compiler::from_test_source(source)
+            .extend_library(library.clone())
+            .compile()
+            .unwrap();
+
+        assert!(result
+            .declarations
+            .iter()
+            .any(|d| d.name == "$int" && d.r#type == Type::Number));
+    }
+
+    for (source, expected_exception_message) in [
+        (
+            "<<set $int = roll()>>",
+            "expects at least 1 parameter, but received 0",
+        ),
+        (
+            "<<set $int = roll(\"2d6\", 3, 4)>>",
+            "expects at most 2 parameters, but received 3",
+        ),
+        (
+            "<<set $int = roll(\"2d6\", \"3\")>>",
+            "expects a Number, not a String",
+        ),
+    ] {
+        let result = Compiler::from_test_source(source)
+            .extend_library(library.clone())
+            .compile()
+            .unwrap_err();
+
+        assert!(result
+            .0
+            .iter()
+            .any(|d| d.message.contains(expected_exception_message)));
+    }
+}
+
+#[test]
+fn test_overloaded_function_signatures() {
+    let mut library = Library::new();
+    library
+        .add_overload("random", || 0.5_f32)
+        .add_overload("random", |max: f32| max)
+        .add_overload("random", |min: f32, max: f32| min + max);
+
+    for source in [
+        "<<set $int = random()>>",
+        "<<set $int = random(6)>>",
+        "<<set $int = random(1, 6)>>",
+    ] {
+        let result = Compiler::from_test_source(source)
+            .extend_library(library.clone())
+            .compile()
+            .unwrap();
+
+        assert!(result
+            .declarations
+            .iter()
+            .any(|d| d.name == "$int" && d.r#type == Type::Number));
+    }
+
+    for (source, expected_exception_message) in [
+        (
+            "<<set $int = random(1, 2, 3)>>",
+            "has no overload that expects 3 parameters",
+        ),
+        (
+            "<<set $int = random(\"6\")>>",
+            "expects a Number, not a String",
+        ),
+    ] {
+        let result = Compiler::from_test_source(source)
+            .extend_library(library.clone())
+            .compile()
+            .unwrap_err();
+
+        assert!(result
+            .0
+            .iter()
+            .any(|d| d.message.contains(expected_exception_message)));
+    }
+}
+
 #[test]
 fn test_operators_are_type_checked() {
     let test_base = TestBase::default();
@@ -587,6 +730,20 @@ fn test_type_conversion_failure_to_bool() {
     test_base.with_compilation(result).run_standard_testcase();
 }
 
+#[test]
+fn test_type_conversion_failure_with_default_value() {
+    let source = "{number(\"hello\")} {bool(\"hello\")}";
+    let mut test_base = TestBase::new().with_test_plan(TestPlan::new().expect_line("0 false"));
+    test_base
+        .dialogue
+        .set_conversion_failure_behavior(ConversionFailureBehavior::DefaultValue);
+    let result = Compiler::from_test_source(source)
+        .extend_library(test_base.dialogue.library().clone())
+        .compile()
+        .unwrap();
+    test_base.with_compilation(result).run_standard_testcase();
+}
+
 #[test]
 fn test_implicit_function_declarations() {
     let source = "
@@ -741,3 +898,44 @@ fn test_if_statement_expressions_must_be_boolean() {
         .message
         .contains("Terms of 'if statement' must be Bool, not String")));
 }
+
+#[test]
+fn test_visit_tracking_functions_are_declared() {
+    let result = Compiler::from_test_source(
+        r#"
+        <<set $seen = visited("Start")>>
+        <<set $count = visited_count("Start")>>
+        "#,
+    )
+    .compile()
+    .unwrap();
+
+    assert!(result
+        .declarations
+        .iter()
+        .any(|d| d.name == "$seen" && d.r#type == Type::Boolean));
+    assert!(result
+        .declarations
+        .iter()
+        .any(|d| d.name == "$count" && d.r#type == Type::Number));
+    assert!(!result
+        .declarations
+        .iter()
+        .any(|d| matches!(d.r#type, Type::Function(_))));
+
+    let result = Compiler::from_test_source(
+        r#"
+        <<if visited_count("Start")>>
+        Hello
+        <<endif>>
+        "#,
+    )
+    .compile()
+    .unwrap_err();
+
+    println!("{}", result);
+
+    assert!(result.0.iter().any(|d| d
+        .message
+        .contains("Terms of 'if statement' must be Bool, not Number")));
+}